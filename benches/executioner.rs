@@ -0,0 +1,112 @@
+//! Allocations and time per save through the boxed [`Executioner`] and the
+//! [`UnboxedExecutioner`] paths, on an in-memory SQLite database.
+//!
+//! ```text
+//! cargo bench --features docs --bench executioner
+//! ```
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use sqlx::{Connection, Executor as _, SqliteConnection};
+use xiayu::prelude::*;
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[derive(Debug, Entity)]
+#[tablename = "counters"]
+pub struct Counter {
+    #[column(primary_key)]
+    pub id: i64,
+    pub hits: i64,
+}
+
+const ROUNDS: usize = 2_000;
+
+struct Measurement {
+    allocations: f64,
+    elapsed: Duration,
+}
+
+impl Measurement {
+    fn start() -> (usize, Instant) {
+        (ALLOCATIONS.load(Ordering::Relaxed), Instant::now())
+    }
+
+    fn stop((allocations, start): (usize, Instant)) -> Self {
+        let elapsed = start.elapsed();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+        Self {
+            allocations: allocations as f64 / ROUNDS as f64,
+            elapsed: elapsed / ROUNDS as u32,
+        }
+    }
+}
+
+async fn run() -> xiayu::Result<()> {
+    let mut conn = SqliteConnection::connect("sqlite::memory:").await?;
+    conn.execute("CREATE TABLE counters (id INTEGER PRIMARY KEY, hits INTEGER NOT NULL);")
+        .await?;
+    conn.execute("INSERT INTO counters (id, hits) VALUES (1, 0);")
+        .await?;
+    let mut counter = Counter { id: 1, hits: 0 };
+
+    // Warm up the statement cache of both paths.
+    (&mut conn).save(&mut counter).await?;
+    (&mut conn).save_entity(&mut counter).await?;
+
+    let started = Measurement::start();
+    for _ in 0..ROUNDS {
+        counter.hits += 1;
+        (&mut conn).save(&mut counter).await?;
+    }
+    let boxed = Measurement::stop(started);
+
+    let started = Measurement::start();
+    for _ in 0..ROUNDS {
+        counter.hits += 1;
+        (&mut conn).save_entity(&mut counter).await?;
+    }
+    let unboxed = Measurement::stop(started);
+
+    println!(
+        "Executioner::save                 {:>8.2} allocations {:>10?}",
+        boxed.allocations, boxed.elapsed
+    );
+    println!(
+        "UnboxedExecutioner::save_entity   {:>8.2} allocations {:>10?}",
+        unboxed.allocations, unboxed.elapsed
+    );
+    assert!(
+        boxed.allocations - unboxed.allocations >= 1.0,
+        "the unboxed path should save the boxed future"
+    );
+
+    Ok(())
+}
+
+fn main() {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run())
+        .unwrap();
+}
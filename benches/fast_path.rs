@@ -0,0 +1,76 @@
+//! Time per primary key lookup built by the visitor and by
+//! [`HasPrimaryKey::fast_select_by_pk`], on an in-memory SQLite database.
+//!
+//! ```text
+//! cargo bench --features docs --bench fast_path
+//! ```
+use std::time::{Duration, Instant};
+
+use sqlx::{Connection, Executor as _, Sqlite, SqliteConnection};
+use xiayu::prelude::*;
+
+#[derive(Debug, Entity)]
+#[tablename = "counters"]
+pub struct Counter {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+    pub hits: i64,
+}
+
+const ROUNDS: u32 = 20_000;
+
+fn per_round(started: Instant) -> Duration {
+    started.elapsed() / ROUNDS
+}
+
+async fn run() -> xiayu::Result<()> {
+    let mut conn = SqliteConnection::connect("sqlite::memory:").await?;
+    conn.execute("CREATE TABLE counters (id INTEGER PRIMARY KEY, name TEXT NOT NULL, hits INTEGER NOT NULL);")
+        .await?;
+    conn.execute("INSERT INTO counters (id, name, hits) VALUES (1, 'home', 0);")
+        .await?;
+
+    let started = Instant::now();
+    for id in 0..ROUNDS {
+        Counter::get::<Sqlite, _>(id as i64).prepare()?;
+    }
+    let visited = per_round(started);
+
+    let started = Instant::now();
+    for id in 0..ROUNDS {
+        Counter::fast_select_by_pk::<Sqlite, _>(id as i64)?;
+    }
+    let formatted = per_round(started);
+
+    // Both paths end in the same statement, so the round trips differ only by
+    // the time to build it.
+    let started = Instant::now();
+    for _ in 0..ROUNDS {
+        let _: Counter = Counter::get::<Sqlite, _>(1).prepare()?.fetch_one(&mut conn).await?;
+    }
+    let visited_fetch = per_round(started);
+
+    let started = Instant::now();
+    for _ in 0..ROUNDS {
+        let _: Counter = Counter::fast_select_by_pk::<Sqlite, _>(1)?.fetch_one(&mut conn).await?;
+    }
+    let formatted_fetch = per_round(started);
+
+    println!("build  visitor              {:>10?}", visited);
+    println!("build  fast_select_by_pk    {:>10?}", formatted);
+    println!("fetch  visitor              {:>10?}", visited_fetch);
+    println!("fetch  fast_select_by_pk    {:>10?}", formatted_fetch);
+    assert!(formatted < visited, "formatting the query should beat visiting it");
+
+    Ok(())
+}
+
+fn main() {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run())
+        .unwrap();
+}
@@ -5,7 +5,9 @@ use darling::{FromDeriveInput, FromMeta};
 use inflector::Inflector;
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
 
 #[derive(FromDeriveInput)]
 #[darling(attributes(entity), supports(struct_named))]
@@ -27,6 +29,80 @@ struct EntityDefinition {
     /// The forwarded attributes from the passed in type. These are controlled using the
     /// forward_attrs attribute.
     attrs: Vec<syn::Attribute>,
+
+    /// Generates `<Entity>Builder`, constructing new rows without the columns
+    /// generated by the database.
+    #[darling(default)]
+    builder: darling::util::Flag,
+
+    /// Leaves `Writable` out, the entity can not be saved nor deleted.
+    #[darling(default)]
+    readonly: darling::util::Flag,
+
+    /// Implements `Default`, with the client-side default of the columns
+    /// which have one.
+    #[darling(default)]
+    derive_default: darling::util::Flag,
+
+    /// Keeps the values loaded in the field of type `Snapshot`, for an
+    /// `update` of the columns changed since.
+    #[darling(default)]
+    track_changes: darling::util::Flag,
+
+    /// Adds the entity to `registry::registered`, with the `register`
+    /// feature of xiayu.
+    #[darling(default)]
+    register: darling::util::Flag,
+
+    /// The entity has no primary key on purpose: leaves out the `get`, `save`
+    /// and `delete` which otherwise fail to compile with a pointer to the
+    /// missing key.
+    #[darling(default)]
+    no_primary_key: darling::util::Flag,
+
+    /// The `SELECT` of a read-only entity, selected from as an inline view
+    /// aliased with the tablename.
+    #[darling(default)]
+    view: Option<String>,
+
+    /// Called with the entity by `save`, before it builds its statement,
+    /// not when the statement is executed.
+    #[darling(default)]
+    before_save: Option<syn::Path>,
+
+    /// Called with every entity decoded from a row.
+    #[darling(default)]
+    after_load: Option<syn::Path>,
+
+    /// A field referencing the primary key of the same entity,
+    /// `belongs_to(Self, via = "parent_id")`.
+    #[darling(default)]
+    belongs_to: Option<BelongsTo>,
+}
+
+/// `belongs_to(Self, via = "field")`, only relations of an entity to itself
+/// are supported.
+#[derive(Clone, Debug)]
+struct BelongsTo {
+    via: syn::LitStr,
+}
+
+impl FromMeta for BelongsTo {
+    fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+        #[derive(FromMeta)]
+        struct Via {
+            via: syn::LitStr,
+        }
+
+        match items.split_first() {
+            Some((syn::NestedMeta::Meta(syn::Meta::Path(path)), rest)) if path.is_ident("Self") => {
+                Via::from_list(rest).map(|Via { via }| BelongsTo { via })
+            }
+            _ => Err(darling::Error::custom(
+                "expected `belongs_to(Self, via = \"field\")`, only references to the entity itself are supported",
+            )),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, FromMeta)]
@@ -35,6 +111,7 @@ struct Tablename(String);
 #[derive(Clone, Debug, Default)]
 struct EntityOptions {
     tablename: Option<String>,
+    notify_channel: Option<String>,
     indexes: Vec<IndexMeta>,
 }
 
@@ -46,6 +123,54 @@ struct IndexMeta {
     pub unique: bool,
 }
 
+/// `default = <literal>` or `default(path::to::fn)`.
+#[derive(Clone, Debug)]
+enum ColumnDefault {
+    Lit(syn::Lit),
+    Path(syn::Path),
+}
+
+impl FromMeta for ColumnDefault {
+    fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+        Ok(ColumnDefault::Lit(value.clone()))
+    }
+
+    fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+        match items {
+            [syn::NestedMeta::Meta(syn::Meta::Path(path))] => Ok(ColumnDefault::Path(path.clone())),
+            _ => Err(darling::Error::custom(
+                "expected a single path to a function, e.g. `default(String::new)`",
+            )),
+        }
+    }
+}
+
+impl ColumnDefault {
+    /// The `DefaultValue` of a field, with the literal wrapped in `Some` for
+    /// nullable fields.
+    fn to_tokens(&self, nullable: bool, namespace: &TokenStream2) -> TokenStream2 {
+        let wrap = |value: TokenStream2| {
+            if nullable {
+                quote!(::std::option::Option::Some(#value))
+            } else {
+                value
+            }
+        };
+        match self {
+            ColumnDefault::Path(path) => quote!(#namespace::DefaultValue::Fn(#path)),
+            // Strings can not be built in constants, convert them on demand.
+            ColumnDefault::Lit(lit @ (syn::Lit::Str(_) | syn::Lit::ByteStr(_))) => {
+                let value = wrap(quote!(::std::convert::From::from(#lit)));
+                quote!(#namespace::DefaultValue::Fn(|| #value))
+            }
+            ColumnDefault::Lit(lit) => {
+                let value = wrap(quote!(#lit));
+                quote!(#namespace::DefaultValue::Value(&#value))
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, FromField)]
 #[darling(attributes(column))]
 struct ColumnOptions {
@@ -68,20 +193,68 @@ struct ColumnOptions {
     #[darling(default)]
     length: Option<usize>,
     #[darling(default)]
-    default: Option<syn::Lit>,
+    default: Option<ColumnDefault>,
+    /// Path to a function `fn() -> T` called when an existing row is saved,
+    /// its value is set to the column and written back to the field once
+    /// saved, e.g. `onupdate = "chrono::Utc::now"`. Nullable fields are set
+    /// to `Some` of it.
     #[darling(default)]
-    onupdate: Option<String>,
+    onupdate: Option<syn::LitStr>,
     #[darling(default)]
     foreign_key: Option<syn::LitStr>,
     #[darling(default)]
     server_default: Option<syn::Lit>,
     #[darling(default)]
     server_onupdate: Option<String>,
+    /// Set by the database when left out of an insert, e.g. an identity or
+    /// a key generated by a trigger. Implied by `autoincrement` and
+    /// `server_default`.
+    #[darling(default)]
+    server_generated: darling::util::Flag,
+    /// Path to a function generating the primary key on the client side,
+    /// called when a new row is inserted while the key is `None` or its
+    /// default, e.g. `generator = "uuid_v4"`.
+    #[darling(default)]
+    generator: Option<syn::LitStr>,
+    /// Path to a `Transformer` of the field, encoding the values bound to the
+    /// column and decoding the ones read, e.g. `transform = "crypto::Sealed"`.
+    #[darling(default)]
+    transform: Option<syn::LitStr>,
+    /// The type of the column when it is not the type of the field, e.g.
+    /// `try_from = "i64"` for an `u32` field. Decoded values are converted
+    /// with `TryFrom`, bound ones with `From`.
+    #[darling(default)]
+    try_from: Option<syn::LitStr>,
     /// Force quoting of this column’s name on or off, corresponding to true or false.
     /// When left at its default of None, the column identifier will be quoted according to whether the name is case sensitive (identifiers with at least one upper case character are treated as case sensitive), or if it’s a reserved word.
     /// This flag is only needed to force quoting of a reserved word which is not known by the SQLAlchemy dialect.
     #[darling(default)]
     quote: bool,
+    /// Marks the column holding the deletion time of soft deleted rows, rows
+    /// where it is `NULL` are alive.
+    #[darling(default)]
+    soft_delete: darling::util::Flag,
+    /// Embeds the columns of a struct deriving `EmbeddedColumns`, named
+    /// after its fields.
+    #[darling(default)]
+    flatten: darling::util::Flag,
+    /// Prepended to the names of the embedded columns of a flattened field,
+    /// e.g. `prefix = "home_"`.
+    #[darling(default)]
+    prefix: Option<String>,
+    /// The field is not a column, it is left out of the queries and filled
+    /// with `Default::default()` when decoded.
+    #[darling(default)]
+    skip: darling::util::Flag,
+    /// Path to a function building the value of a skipped field instead of
+    /// `Default::default()`, e.g. `skip_default = "Cache::empty"`.
+    #[darling(default)]
+    skip_default: Option<syn::LitStr>,
+    /// A secret such as a password hash: written by inserts and updates, left
+    /// out of the columns selected by default and redacted from the logged
+    /// parameters. The field is `None` or its `default` unless selected.
+    #[darling(default)]
+    write_only: darling::util::Flag,
 
     /// Get the ident of the field. For fields in tuple or newtype structs or
     /// enum bodies, this can be `None`.
@@ -111,7 +284,344 @@ macro_rules! quote_optional {
     };
 }
 
-#[proc_macro_derive(Entity, attributes(tablename, column))]
+/// Unwraps `Option<T>` into `T`.
+fn inner_type(ty: &syn::Type) -> &syn::Type {
+    if let syn::Type::Path(syn::TypePath { qself: None, path }) = ty {
+        if let Some(segment) = path.segments.last() {
+            if segment.ident == "Option" {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(ty)) = args.args.first() {
+                        return ty;
+                    }
+                }
+            }
+        }
+    }
+    ty
+}
+
+/// Whether `ty` is a type of the standard library implementing `Default`.
+fn has_std_default(ty: &syn::Type) -> bool {
+    let segment = match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => match path.segments.last() {
+            Some(segment) => segment,
+            None => return false,
+        },
+        syn::Type::Tuple(tuple) => return tuple.elems.iter().all(has_std_default),
+        _ => return false,
+    };
+    matches!(
+        segment.ident.to_string().as_str(),
+        "i8" | "i16"
+            | "i32"
+            | "i64"
+            | "i128"
+            | "isize"
+            | "u8"
+            | "u16"
+            | "u32"
+            | "u64"
+            | "u128"
+            | "usize"
+            | "f32"
+            | "f64"
+            | "bool"
+            | "char"
+            | "String"
+            | "Vec"
+            | "VecDeque"
+            | "HashMap"
+            | "HashSet"
+            | "BTreeMap"
+            | "BTreeSet"
+            | "Cow"
+    )
+}
+
+/// Whether `ty` is the `Snapshot` of an entity tracking its changes.
+fn is_snapshot(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            path.segments.last().is_some_and(|segment| segment.ident == "Snapshot")
+        }
+        _ => false,
+    }
+}
+
+/// `ty` with the lifetime `from` replaced by `to`.
+fn replace_lifetime(ty: &syn::Type, from: Option<&syn::Lifetime>, to: &syn::Lifetime) -> syn::Type {
+    struct Replace<'l> {
+        from: &'l syn::Lifetime,
+        to: &'l syn::Lifetime,
+    }
+
+    impl syn::visit_mut::VisitMut for Replace<'_> {
+        fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+            if lifetime == self.from {
+                *lifetime = self.to.clone();
+            }
+        }
+    }
+
+    let mut ty = ty.clone();
+    if let Some(from) = from {
+        syn::visit_mut::VisitMut::visit_type_mut(&mut Replace { from, to }, &mut ty);
+    }
+    ty
+}
+
+/// Infers the `TypeFamily` of a column from the rust type of its field.
+fn type_family(
+    ty: &syn::Type,
+    length: Option<usize>,
+    namespace: &TokenStream2,
+) -> Option<TokenStream2> {
+    let length = match length {
+        Some(length) if length <= u16::MAX as usize => {
+            let length = length as u16;
+            quote! { Some(#namespace::TypeDataLength::Constant(#length)) }
+        }
+        Some(_) => quote! { Some(#namespace::TypeDataLength::Maximum) },
+        None => quote! { None },
+    };
+
+    let segment = match inner_type(ty) {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path.segments.last()?,
+        syn::Type::Reference(syn::TypeReference { elem, .. }) => {
+            return match &**elem {
+                syn::Type::Path(path) if path.path.is_ident("str") => {
+                    Some(quote! { #namespace::TypeFamily::Text(#length) })
+                }
+                syn::Type::Slice(slice) => match &*slice.elem {
+                    syn::Type::Path(path) if path.path.is_ident("u8") => {
+                        Some(quote! { #namespace::TypeFamily::Bytes(#length) })
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+        }
+        _ => return None,
+    };
+
+    let family = match segment.ident.to_string().as_str() {
+        "i8" | "i16" | "i32" | "u8" | "u16" => quote! { #namespace::TypeFamily::Int32 },
+        "i64" | "u32" | "u64" => quote! { #namespace::TypeFamily::Int },
+        "f32" => quote! { #namespace::TypeFamily::Float },
+        "f64" => quote! { #namespace::TypeFamily::Double },
+        "bool" => quote! { #namespace::TypeFamily::Boolean },
+        "String" | "str" => quote! { #namespace::TypeFamily::Text(#length) },
+        "Uuid" => quote! { #namespace::TypeFamily::Uuid },
+        "DateTime" | "NaiveDateTime" | "OffsetDateTime" | "PrimitiveDateTime" => {
+            quote! { #namespace::TypeFamily::DateTime }
+        }
+        "Decimal" | "BigDecimal" => quote! { #namespace::TypeFamily::Decimal(None) },
+        "Vec" => match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(syn::GenericArgument::Type(syn::Type::Path(path)))
+                    if path.path.is_ident("u8") =>
+                {
+                    quote! { #namespace::TypeFamily::Bytes(#length) }
+                }
+                _ => return None,
+            },
+            _ => return None,
+        },
+        // `Cow<'a, str>` and `Cow<'a, [u8]>`, after the lifetime.
+        "Cow" => match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => match args.args.last() {
+                Some(syn::GenericArgument::Type(syn::Type::Path(path))) if path.path.is_ident("str") => {
+                    quote! { #namespace::TypeFamily::Text(#length) }
+                }
+                Some(syn::GenericArgument::Type(syn::Type::Slice(slice)))
+                    if matches!(&*slice.elem, syn::Type::Path(path) if path.path.is_ident("u8")) =>
+                {
+                    quote! { #namespace::TypeFamily::Bytes(#length) }
+                }
+                _ => return None,
+            },
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(family)
+}
+
+/// `get`, `get_many`, `save` and `delete` of an entity without a primary key,
+/// bound by the never implemented `PrimaryKeyRequired`: calling them fails
+/// with a message about the missing key instead of a method not found. The
+/// bound is higher-ranked for the error to be at the call and not here.
+fn missing_primary_key_helpers(ident: &syn::Ident, generics: &syn::Generics, namespace: &TokenStream2) -> TokenStream2 {
+    let unreachable = format!("`{}` has no primary key", ident);
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    quote! {
+        #[doc(hidden)]
+        impl #impl_generics #ident #ty_generics {
+            pub fn get<DB, K>(_pk: K) -> #namespace::SelectingExecution<Self, DB>
+            where
+                DB: ::sqlx::Database,
+                for<'k> &'k (): #namespace::PrimaryKeyRequired<Self>,
+            {
+                ::std::unreachable!(#unreachable)
+            }
+
+            pub fn get_many<DB, I>(_pks: I) -> #namespace::SelectingExecution<Self, DB>
+            where
+                DB: ::sqlx::Database,
+                for<'k> &'k (): #namespace::PrimaryKeyRequired<Self>,
+            {
+                ::std::unreachable!(#unreachable)
+            }
+
+            pub fn save<'e, DB>(&'e mut self) -> #namespace::SavingExecution<'e, Self, DB>
+            where
+                DB: ::sqlx::Database,
+                for<'k> &'k (): #namespace::PrimaryKeyRequired<Self>,
+            {
+                ::std::unreachable!(#unreachable)
+            }
+
+            pub fn delete<'e, DB>(&'e mut self) -> #namespace::DeletingExecution<'e, Self, DB>
+            where
+                DB: ::sqlx::Database,
+                for<'k> &'k (): #namespace::PrimaryKeyRequired<Self>,
+            {
+                ::std::unreachable!(#unreachable)
+            }
+        }
+    }
+}
+
+/// `fetch_parent`, `fetch_children`, `ancestors` and `join_parent` of an
+/// entity whose `via` field references its own primary key.
+fn self_reference_helpers(
+    ident: &syn::Ident,
+    generics: &syn::Generics,
+    via: &syn::Ident,
+    nullable: bool,
+    namespace: &TokenStream2,
+) -> TokenStream2 {
+    let parent_key = if nullable {
+        quote! {
+            match &self.#via {
+                ::std::option::Option::Some(key) => ::std::clone::Clone::clone(key),
+                ::std::option::Option::None => return ::std::result::Result::Ok(::std::option::Option::None),
+            }
+        }
+    } else {
+        quote!(::std::clone::Clone::clone(&self.#via))
+    };
+    let bounds = quote! {
+        DB: ::sqlx::Database + for<'v> #namespace::HasVisitor<'v>,
+        Self: for<'r> ::sqlx::FromRow<'r, <DB as ::sqlx::Database>::Row> + ::std::marker::Send + ::std::marker::Unpin,
+    };
+
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics #ident #ty_generics {
+            /// The entity referenced by the field of `belongs_to`, `None`
+            /// when it is not set.
+            pub async fn fetch_parent<'c, DB, C>(&self, conn: C) -> #namespace::Result<::std::option::Option<Self>>
+            where
+                #bounds
+                C: #namespace::Executioner<'c, DB>,
+            {
+                let key = #parent_key;
+                let parent = <Self as #namespace::HasPrimaryKey>::get::<DB, _>(key).conn(conn).await?;
+                ::std::result::Result::Ok(::std::option::Option::Some(parent))
+            }
+
+            /// The entities referencing this one by the field of
+            /// `belongs_to`.
+            pub async fn fetch_children<'c, DB, C>(&self, conn: C) -> #namespace::Result<::std::vec::Vec<Self>>
+            where
+                #bounds
+                C: #namespace::Executioner<'c, DB>,
+            {
+                let key = <Self as #namespace::HasPrimaryKey>::pk(self);
+                <Self as #namespace::Entity>::default_select()
+                    .so_that(#namespace::Comparable::equals(#ident::#via, key))
+                    .fetch_as::<Self, DB>()
+                    .prepare()?
+                    .fetch_all(conn)
+                    .await
+            }
+
+            /// The parent, its parent and so on, nearest first and at most
+            /// `max_depth` of them. Each level is a query of its own.
+            pub async fn ancestors<DB, C>(&self, conn: &mut C, max_depth: usize) -> #namespace::Result<::std::vec::Vec<Self>>
+            where
+                #bounds
+                for<'e> &'e mut C: #namespace::Executioner<'e, DB>,
+            {
+                let mut ancestors: ::std::vec::Vec<Self> = ::std::vec::Vec::new();
+                while ancestors.len() < max_depth {
+                    let parent = match ancestors.last() {
+                        ::std::option::Option::Some(last) => last.fetch_parent(&mut *conn).await?,
+                        ::std::option::Option::None => self.fetch_parent(&mut *conn).await?,
+                    };
+                    match parent {
+                        ::std::option::Option::Some(parent) => ancestors.push(parent),
+                        ::std::option::Option::None => break,
+                    }
+                }
+                ::std::result::Result::Ok(ancestors)
+            }
+
+            /// Joins `parent` to the rows of `child` they reference, both
+            /// aliases of the table of the entity.
+            pub fn join_parent<'t>(child: &#namespace::Table<'t>, parent: &#namespace::Table<'t>) -> #namespace::JoinData<'t> {
+                #namespace::Joinable::on(
+                    ::std::clone::Clone::clone(parent),
+                    #namespace::Comparable::equals(
+                        child.col(#ident::#via),
+                        parent.col(<Self as #namespace::HasPrimaryKey>::primary_key()),
+                    ),
+                )
+            }
+        }
+    }
+}
+
+/// The items generated in the `impl` of an entity next to the constants of
+/// its fields.
+const GENERATED_ITEMS: &[&str] = &["_table", "_schema", "_schema_hash", "_primary_key", "_flattened"];
+
+/// The lifetimes of the generated items, which the one of an entity would
+/// shadow.
+const GENERATED_LIFETIMES: &[&str] = &["c", "e", "k", "r", "t", "v", "insert", "__row"];
+
+/// Why `name` can not be a column even quoted, as `check_identifier` of the
+/// crate checks the identifiers of untrusted input. Names which only need
+/// quoting, e.g. not ASCII, are quoted by every quote style.
+fn invalid_identifier(name: &str) -> Option<&'static str> {
+    if name.is_empty() {
+        Some("the identifier is empty")
+    } else if name.chars().any(char::is_control) {
+        Some("control characters are not allowed")
+    } else if name.contains(&['"', '`', '[', ']'][..]) {
+        Some("quotes are not allowed")
+    } else {
+        None
+    }
+}
+
+/// The path of the prelude of xiayu, as named by the crate deriving.
+fn namespace() -> TokenStream2 {
+    let found_crate =
+        proc_macro_crate::crate_name("xiayu").expect("xiayu is not present in `Cargo.toml`");
+
+    match found_crate {
+        proc_macro_crate::FoundCrate::Itself => quote!(self),
+        proc_macro_crate::FoundCrate::Name(name) => {
+            let import = format_ident!("{}", &name);
+            quote!( #import::prelude )
+        }
+    }
+}
+
+#[proc_macro_derive(Entity, attributes(entity, tablename, notify_channel, index, column))]
 pub fn derive_entity(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as syn::DeriveInput);
     let mut entity_options = EntityOptions::default();
@@ -120,6 +630,17 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
             Ok(meta) => meta,
             Err(err) => return err.into_compile_error().into(),
         };
+        if meta.path().is_ident("entity") {
+            // Parsed with the definition.
+            continue;
+        }
+        if meta.path().is_ident("notify_channel") {
+            match String::from_meta(&meta) {
+                Ok(channel) => entity_options.notify_channel.replace(channel),
+                Err(err) => return err.write_errors().into(),
+            };
+            continue;
+        }
         if let Ok(Tablename(tablename)) = Tablename::from_meta(&meta) {
             entity_options.tablename.replace(tablename);
             continue;
@@ -136,124 +657,591 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
         Err(err) => return err.write_errors().into(),
     };
     let ident = entity_def.ident;
+    let entity_name = ident.to_string();
+    // An entity may borrow its fields, e.g. `Cow<'a, str>`, the columns are
+    // those of the owned fields.
+    let entity_generics = entity_def.generics.clone();
+    if entity_generics.type_params().next().is_some()
+        || entity_generics.const_params().next().is_some()
+        || entity_generics.lifetimes().count() > 1
+    {
+        return syn::Error::new_spanned(&entity_generics, "an entity is generic over one lifetime at most")
+            .into_compile_error()
+            .into();
+    }
+    let entity_lifetime = entity_generics.lifetimes().next().map(|def| def.lifetime.clone());
+    if let Some(lifetime) = entity_lifetime.as_ref().filter(|lifetime| GENERATED_LIFETIMES.contains(&&*lifetime.ident.to_string())) {
+        let message = format!("`{}` is a lifetime of the items generated for the entity, rename it", lifetime);
+        return syn::Error::new_spanned(lifetime, message).into_compile_error().into();
+    }
+    if let (Some(lifetime), true) = (&entity_lifetime, entity_def.builder.is_some()) {
+        return syn::Error::new_spanned(lifetime, "`#[entity(builder)]` is not supported on entities with a lifetime")
+            .into_compile_error()
+            .into();
+    }
+    let static_lifetime = syn::Lifetime::new("'static", proc_macro2::Span::call_site());
+    let (entity_impl, entity_ty, _) = entity_generics.split_for_impl();
+    let vis = entity_def.vis;
+    let builder = entity_def.builder.is_some();
+    let view = entity_def.view;
+    let readonly = entity_def.readonly.is_some() || view.is_some();
+    let no_primary_key = entity_def.no_primary_key.is_some();
+    let derive_default = entity_def.derive_default.is_some();
+    let register = entity_def.register.is_some();
+    let track_changes = entity_def.track_changes.is_some();
+    // The field keeping the values loaded, with `track_changes`.
+    let mut snapshot_field = None;
+    let before_save = entity_def.before_save;
+    let after_load = entity_def.after_load;
+    let belongs_to = entity_def.belongs_to;
+    // The field of `belongs_to` and whether it is nullable, once found.
+    let mut self_reference = None;
+    // `find_by_<field>` of the primary key and the unique columns.
+    let mut lookups = Vec::new();
+    if let (Some(hook), true) = (&before_save, readonly) {
+        return syn::Error::new_spanned(hook, "read-only entities are never saved")
+            .into_compile_error()
+            .into();
+    }
     let tablename = entity_options
         .tablename
         .clone()
         .unwrap_or_else(|| ident.to_string().to_table_case());
 
+    let notify_channel = entity_options.notify_channel.as_ref().map(|channel| {
+        quote! {
+            #[inline]
+            fn notify_channel() -> ::std::borrow::Cow<'static, str> {
+                ::std::borrow::Cow::Borrowed(#channel)
+            }
+        }
+    });
+
     let mut primary_key_type = None;
     let mut primary_key_column = None;
     let mut primary_key_value_type = None;
     let mut primary_key_column_name = None;
+    // The name of the primary key in the database.
+    let mut primary_key_name = None;
+    let mut primary_key_nullable = false;
+    let mut primary_key_generator = None;
+    let mut primary_key_autoincrement = false;
+    let mut soft_delete_column = None;
     let mut names = Vec::new();
     let mut types = Vec::new();
+    let mut bound_values = Vec::new();
+    // The values set by `save` to an existing row, fresh ones for the
+    // `onupdate` columns, and the write-only ones set once changed.
+    let mut update_names = Vec::new();
+    let mut update_values = Vec::new();
+    let mut write_only_updates = Vec::new();
+    let mut onupdate_values = Vec::new();
+    let mut onupdate_fields = Vec::new();
     let mut column_options = Vec::new();
+    let mut column_schemas = Vec::new();
+    let mut quoted_columns = Vec::new();
+    let mut required_fields = Vec::new();
+    let mut optional_fields = Vec::new();
+    let mut field_names = Vec::new();
+    // The columns in order, for entities with flattened fields.
+    let mut flattened_parts = Vec::new();
+    // Pushing the values of an entity in the order of its columns.
+    let mut value_parts = Vec::new();
+    let mut flattened_fields = Vec::new();
+    // `column_index` matches the names of the columns known at compile time,
+    // the index of a column after flattened fields adds up their columns.
+    let mut column_indexes = Vec::new();
+    let mut flattened_types: Vec<syn::Type> = Vec::new();
+    let mut embedded_hashes = Vec::new();
+    // The names of the `write_only` columns, not selected by default.
+    let mut write_only_columns = Vec::new();
+    // The fields of the `Default` impl, and the ones without a default.
+    let mut default_fields = Vec::new();
+    let mut defaultless_fields = Vec::new();
 
     let mut tokens = TokenStream2::new();
 
-    let found_crate =
-        proc_macro_crate::crate_name("xiayu").expect("xiayu is not present in `Cargo.toml`");
-
-    let namespace = match found_crate {
-        proc_macro_crate::FoundCrate::Itself => quote!(self),
-        proc_macro_crate::FoundCrate::Name(name) => {
-            let import = format_ident!("{}", &name);
-            quote!( #import::prelude )
-        }
-    };
-
-    let generics = &entity_def.generics;
-
-    let (lifetime, provided) = generics
-        .lifetimes()
-        .next()
-        .map(|def| (def.lifetime.clone(), false))
-        .unwrap_or_else(|| {
-            (
-                syn::Lifetime::new("'a", proc_macro2::Span::call_site()),
-                true,
-            )
-        });
+    let namespace = namespace();
 
-    let (_, ty_generics, _) = generics.split_for_impl();
-
-    let mut generics = generics.clone();
-    generics.params.insert(0, syn::parse_quote!(R: ::sqlx::Row));
-
-    if provided {
-        generics.params.insert(0, syn::parse_quote!(#lifetime));
-    }
+    // `FromRow<'r, R>`, the fields are decoded from a row borrowed for `'r`,
+    // which outlives the lifetime of the entity.
+    let lifetime = syn::Lifetime::new("'r", proc_macro2::Span::call_site());
+    let mut generics = entity_generics.clone();
+    generics.params.insert(0, syn::parse_quote!(#lifetime));
+    generics.params.push(syn::parse_quote!(R: ::sqlx::Row));
 
     let where_clause = generics.make_where_clause();
     let predicates = &mut where_clause.predicates;
 
+    if let Some(entity_lifetime) = &entity_lifetime {
+        predicates.push(syn::parse_quote!(#lifetime: #entity_lifetime));
+    }
     predicates.push(syn::parse_quote!(&#lifetime ::std::primitive::str: ::sqlx::ColumnIndex<R>));
 
     let mut reads: Vec<syn::Stmt> = Vec::new();
     if let darling::ast::Data::Struct(darling::ast::Fields { fields, .. }) = entity_def.data {
         for field in fields.into_iter() {
             let ty = field.ty;
-            types.push(ty.clone());
-            let name = field
-                .ident
-                .as_ref()
-                .map(|i| i.to_string().trim_start_matches("r#").to_owned())
-                .unwrap();
+            // The type decoded from the row, and the one of the column.
+            let row_ty = replace_lifetime(&ty, entity_lifetime.as_ref(), &lifetime);
+            let static_ty = replace_lifetime(&ty, entity_lifetime.as_ref(), &static_lifetime);
+            // `r#type` stays raw in the generated code, the column is `type`.
+            let field_ident = field.ident.clone().unwrap();
+            let name = field_ident.unraw().to_string();
+            if GENERATED_ITEMS.contains(&name.as_str()) {
+                let message = format!(
+                    "`{}` clashes with an item generated for the entity, rename the field, the column can keep its name with `#[column(name = \"{}\")]`",
+                    name, name
+                );
+                return syn::Error::new_spanned(&field_ident, message).into_compile_error().into();
+            }
+            field_names.push(field_ident.clone());
+
+            if track_changes && is_snapshot(&ty) {
+                if snapshot_field.is_some() {
+                    return syn::Error::new_spanned(&field_ident, "`track_changes` keeps the values loaded in a single `Snapshot`")
+                        .into_compile_error()
+                        .into();
+                }
+                reads.push(syn::parse_quote!(let #field_ident = #namespace::Snapshot::default();));
+                default_fields.push(quote!(#field_ident: #namespace::Snapshot::default()));
+                snapshot_field = Some(field_ident);
+                continue;
+            }
+
+            let write_only = field.write_only.is_some();
+            if write_only && (field.skip.is_some() || field.primary_key.is_some() || field.flatten.is_some()) {
+                return syn::Error::new_spanned(&field_ident, "a write-only column can not be skipped, flattened nor the primary key")
+                    .into_compile_error()
+                    .into();
+            }
+
+            if field.skip.is_some() {
+                if field.primary_key.is_some() || field.flatten.is_some() {
+                    return syn::Error::new_spanned(&field_ident, "a skipped field has no column, it can not be the primary key nor flattened")
+                        .into_compile_error()
+                        .into();
+                }
+                let value = match &field.skip_default {
+                    Some(skip_default) => match skip_default.parse::<syn::Path>() {
+                        Ok(path) => quote!(#path()),
+                        Err(err) => return err.into_compile_error().into(),
+                    },
+                    None => quote!(::std::default::Default::default()),
+                };
+                reads.push(syn::parse_quote!(let #field_ident: #row_ty = #value;));
+                default_fields.push(quote!(#field_ident: #value));
+                continue;
+            }
+            if let Some(skip_default) = &field.skip_default {
+                return syn::Error::new_spanned(skip_default, "`skip_default` is only supported on skipped fields")
+                    .into_compile_error()
+                    .into();
+            }
+
+            if field.flatten.is_some() {
+                if field.primary_key.is_some() || builder {
+                    let message = if builder {
+                        "flattened fields are not supported with `#[entity(builder)]`"
+                    } else {
+                        "a flattened field can not be the primary key"
+                    };
+                    return syn::Error::new_spanned(&field.ident, message)
+                        .into_compile_error()
+                        .into();
+                }
+                let prefix = field.prefix.unwrap_or_default();
+                let id = field.ident.as_ref();
+                flattened_parts.push(quote!(columns.embed::<#ty>(#name, #prefix);));
+                value_parts.push(quote!(values.extend(#namespace::EmbeddedColumns::values(&self.#id));));
+                flattened_fields.push((field_ident.clone(), name.clone()));
+                flattened_types.push(ty.clone());
+                embedded_hashes.push(quote!(.columns(#prefix, <#ty as #namespace::EmbeddedColumns>::COLUMNS)));
+                predicates.push(syn::parse_quote!(#row_ty: #namespace::FromEmbeddedRow<#lifetime, R>));
+                reads.push(syn::parse_quote!(let #id: #row_ty = #namespace::FromEmbeddedRow::from_row(row, #prefix)?;));
+                default_fields.push(quote!(#id: ::std::default::Default::default()));
+                continue;
+            }
+            if let Some(prefix) = &field.prefix {
+                return syn::Error::new_spanned(prefix, "a prefix is only supported on flattened fields")
+                    .into_compile_error()
+                    .into();
+            }
+
+            types.push(static_ty.clone());
             let column_name = field.name.unwrap_or(name.to_string());
+            if let Some(reason) = invalid_identifier(&column_name) {
+                let message = format!("`{}` can not be the name of a column: {}", column_name, reason);
+                return syn::Error::new_spanned(&field_ident, message).into_compile_error().into();
+            }
             let is_primary_key = field.primary_key.is_some();
+            if is_primary_key && static_ty != ty {
+                return syn::Error::new_spanned(&ty, "the primary key can not borrow, it is an owned value")
+                    .into_compile_error()
+                    .into();
+            }
+            if is_primary_key && no_primary_key {
+                return syn::Error::new_spanned(&field.ident, "`no_primary_key` on an entity with a primary key")
+                    .into_compile_error()
+                    .into();
+            }
             let autoincrement = field.autoincrement.is_some();
-            let comment = quote_optional!(field.comment.map(|v| { v.value().to_string() }));
-            let foreign_key = quote_optional!(field.foreign_key.map(|v| v.value().to_string()));
+            let comment = quote_optional!(field.comment.as_ref().map(|v| { v.value() }));
+            let foreign_key = quote_optional!(field.foreign_key.as_ref().map(|v| v.value()));
             let unique = field.unique;
+            let transform = match &field.transform {
+                Some(transform) => match transform.parse::<syn::Path>() {
+                    Ok(path) => Some(path),
+                    Err(err) => return err.into_compile_error().into(),
+                },
+                None => None,
+            };
+            let try_from = match &field.try_from {
+                Some(try_from) if transform.is_some() => {
+                    return syn::Error::new_spanned(try_from, "`try_from` can not be used with `transform`")
+                        .into_compile_error()
+                        .into();
+                }
+                Some(try_from) => match try_from.parse::<syn::Type>() {
+                    Ok(ty) => Some(ty),
+                    Err(err) => return err.into_compile_error().into(),
+                },
+                None => None,
+            };
+            // The stored type of a transformed column is not the field's.
+            let stored_type_family = match (&transform, &try_from) {
+                (Some(_), _) => None,
+                (None, Some(stored)) => type_family(stored, field.length, &namespace),
+                (None, None) => type_family(&ty, field.length, &namespace),
+            };
+            let type_family = quote_optional!(&stored_type_family);
+            let rust_type = quote!(#ty).to_string().replace(' ', "");
+            let nullable = !std::ptr::eq(inner_type(&ty), &ty);
+            if matches!(&belongs_to, Some(BelongsTo { via }) if via.value() == name) {
+                self_reference = Some((field_ident.clone(), nullable));
+            }
+            if let Some(syn::Lit::Str(sql)) = &field.server_default {
+                if !nullable && sql.value().eq_ignore_ascii_case("null") {
+                    return syn::Error::new_spanned(
+                        &ty,
+                        "a column defaulting to `NULL` needs an `Option` field",
+                    )
+                    .into_compile_error()
+                    .into();
+                }
+            }
+            let has_default = field.default.is_some();
+            if write_only && !nullable && !has_default {
+                return syn::Error::new_spanned(
+                    &ty,
+                    "a write-only column is not selected by default, its field must be an `Option` or have a `default`",
+                )
+                .into_compile_error()
+                .into();
+            }
+            let has_server_default = field.server_default.is_some();
+            let server_default_sql = quote_optional!(field.server_default.as_ref().map(|lit| match lit {
+                syn::Lit::Str(sql) => sql.value(),
+                lit => quote!(#lit).to_string(),
+            }));
             let length = quote_optional!(field.length);
             let quote_name = field.quote;
-            let default = quote_optional!(field.default.clone());
-            let onupdate = quote_optional!(field.onupdate);
-            let server_default = quote_optional!(field.server_default);
-            let server_onupdate = quote_optional!(field.server_onupdate);
+            let default = field
+                .default
+                .as_ref()
+                .map(|default| default.to_tokens(nullable, &namespace));
+            // A write-only column is decoded when selected, e.g. with
+            // `with_column`, and holds its loaded value otherwise.
+            let loaded = match &default {
+                Some(default) => quote!(#namespace::DefaultValue::get(&#default)),
+                None => quote!(::std::option::Option::None),
+            };
+            match &default {
+                Some(default) => default_fields.push(quote!(#field_ident: #namespace::DefaultValue::get(&#default))),
+                None if nullable || has_std_default(&ty) => {
+                    default_fields.push(quote!(#field_ident: ::std::default::Default::default()))
+                }
+                None => defaultless_fields.push(name.clone()),
+            }
+            let generator = match &field.generator {
+                Some(generator) if !is_primary_key => {
+                    return syn::Error::new_spanned(
+                        generator,
+                        "a generator is only supported on the primary key",
+                    )
+                    .into_compile_error()
+                    .into();
+                }
+                Some(generator) => match generator.parse::<syn::Path>() {
+                    Ok(path) => Some(path),
+                    Err(err) => return err.into_compile_error().into(),
+                },
+                None => None,
+            };
+            let onupdate = match &field.onupdate {
+                Some(onupdate) if write_only => {
+                    return syn::Error::new_spanned(onupdate, "`onupdate` can not be used on a write-only column")
+                        .into_compile_error()
+                        .into();
+                }
+                Some(onupdate) if is_primary_key => {
+                    return syn::Error::new_spanned(onupdate, "`onupdate` can not be used on the primary key")
+                        .into_compile_error()
+                        .into();
+                }
+                Some(onupdate) => match onupdate.parse::<syn::Path>() {
+                    Ok(path) => Some(path),
+                    Err(_) => {
+                        return syn::Error::new_spanned(
+                            onupdate,
+                            "`onupdate` expects the path of a function `fn() -> T`, e.g. `chrono::Utc::now`",
+                        )
+                        .into_compile_error()
+                        .into();
+                    }
+                },
+                None => None,
+            };
+            let conversion = match (&transform, &try_from) {
+                (Some(transform), _) => Some(Conversion::Transform(transform.clone())),
+                (None, Some(stored)) => Some(Conversion::From(Box::new(stored.clone()))),
+                (None, None) => None,
+            };
+            let encoder = conversion.map(|conversion| Encoder {
+                conversion,
+                ty: ty.clone(),
+                nullable,
+            });
+            let server_generated = field.server_generated.is_some();
+            // Only what is set, every option starts unset.
+            let mut options = Vec::new();
+            if is_primary_key {
+                options.push(quote!(.primary_key()));
+            }
+            if autoincrement {
+                options.push(quote!(.autoincrement()));
+            }
+            if unique {
+                options.push(quote!(.unique()));
+            }
+            if quote_name {
+                options.push(quote!(.quote()));
+            }
+            if let Some(reference) = &field.foreign_key {
+                options.push(quote!(.foreign_key(#reference)));
+            }
+            if let Some(comment) = &field.comment {
+                options.push(quote!(.comment(#comment)));
+            }
+            if let Some(length) = field.length {
+                options.push(quote!(.length(#length)));
+            }
+            if let Some(type_family) = &stored_type_family {
+                options.push(quote!(.type_family(#type_family)));
+            }
+            if let Some(sql) = &field.server_default {
+                let sql = match sql {
+                    syn::Lit::Str(sql) => sql.value(),
+                    lit => quote!(#lit).to_string(),
+                };
+                options.push(quote!(.server_default(#sql)));
+            }
+            if let Some(sql) = &field.server_onupdate {
+                options.push(quote!(.server_onupdate(#sql)));
+            }
+            if server_generated {
+                options.push(quote!(.server_generated()));
+            }
+            if write_only {
+                options.push(quote!(.write_only()));
+                write_only_columns.push(column_name.clone());
+            }
+            if let Some(default) = &default {
+                let bound = match &encoder {
+                    Some(encoder) => encoder.encode(&quote!(&value), false, &namespace),
+                    None => quote!(value),
+                };
+                options.push(quote!(.default(#default)));
+                options.push(quote! {
+                    .bound_default(|| {
+                        let value: #ty = #namespace::DefaultValue::get(&#default);
+                        #namespace::Value::from(#bound)
+                    })
+                });
+            }
             let column = quote! {
-                ColumnOptions::new(
-                    /* name: */ #column_name,
-                    /* tablename: */ #tablename,
-                    /* primary_key: */ #is_primary_key,
-                    /* autoincrement: */ #autoincrement,
-                    /* foreign_key: */ #foreign_key,
-                    /* comment: */ #comment,
-                    /* unique: */ #unique,
-                    /* length: */ #length,
-                    /* quote: */ #quote_name,
-                    /* default: */ #default,
-                    /* onupdate: #onupdate,
-                    server_default: #server_default,
-                    server_onupdate: #server_onupdate,
-                    */
-                )
+                #namespace::ColumnOptions::builder(#column_name, #tablename)#(#options)*.build()
             };
             if is_primary_key {
-                primary_key_type = Some(quote! { #namespace::ColumnOptions<#ty> });
+                primary_key_type = Some(quote! { #namespace::ColumnOptions<#static_ty> });
                 primary_key_value_type = Some(quote! { #ty });
                 // println!("primary_key_definition: {:?}", column.clone().to_string());
                 primary_key_column = Some(column.clone());
-                primary_key_column_name = Some(format_ident!("{}", name.clone()));
+                primary_key_column_name = Some(field_ident.clone());
+                primary_key_name = Some(column_name.clone());
+                primary_key_nullable = nullable;
+                primary_key_generator = generator.clone();
+                primary_key_autoincrement = autoincrement;
+            }
+            if field.soft_delete.is_some() {
+                soft_delete_column = Some(field_ident.clone());
+            }
+            let column_schema = quote! {
+                #namespace::ColumnSchema {
+                    name: #column_name,
+                    rust_type: #rust_type,
+                    type_family: #type_family,
+                    nullable: #nullable,
+                    primary_key: #is_primary_key,
+                    autoincrement: #autoincrement,
+                    unique: #unique,
+                    length: #length,
+                    foreign_key: #foreign_key,
+                    has_default: #has_default,
+                    has_server_default: #has_server_default,
+                    server_default: #server_default_sql,
+                    comment: #comment,
+                }
+            };
+            let field_name = field_ident.clone();
+            flattened_parts.push(quote!(columns.push(#column_schema, #ident::#field_name.column());));
+            let index = column_schemas.len();
+            column_indexes.push(quote! {
+                #column_name => ::std::option::Option::Some(#index #(+ <#flattened_types as #namespace::EmbeddedColumns>::COLUMNS.len())*)
+            });
+            column_schemas.push(column_schema);
+            if !write_only {
+                quoted_columns.push(column_name.clone());
+            }
+            // Left out by the builder unless set, the database or the default
+            // provides them.
+            let generated = autoincrement || has_server_default || server_generated || nullable || generator.is_some();
+            if generated || has_default {
+                optional_fields.push(OptionalField {
+                    name: field_ident.clone(),
+                    ty: inner_type(&ty).clone(),
+                    has_default,
+                    generator,
+                    encoder: encoder.clone(),
+                });
+            } else {
+                required_fields.push((field_ident.clone(), ty.clone(), encoder.clone()));
             }
-            names.push(format_ident!("{}", name));
-            column_options.push(column);
 
-            predicates.push(syn::parse_quote!(#ty: ::sqlx::decode::Decode<#lifetime, R::Database>));
-            predicates.push(syn::parse_quote!(#ty: ::sqlx::types::Type<R::Database>));
+            if is_primary_key || unique {
+                lookups.push(Lookup {
+                    field: field_ident.clone(),
+                    ty: inner_type(&static_ty).clone(),
+                    bound: match &encoder {
+                        Some(encoder) => encoder.encode(&quote!(&value), true, &namespace),
+                        None => quote!(value),
+                    },
+                });
+            }
+            let bound_value = match &encoder {
+                Some(encoder) => encoder.encode(&quote!(&self.#field_name), false, &namespace),
+                // A borrowed value is copied, the values of the entity live
+                // as long as the statements.
+                None if entity_lifetime.is_some() => quote! {
+                    #namespace::Value::into_owned(::std::convert::Into::into(self.#field_name.clone()))
+                },
+                None => quote!(self.#field_name.clone()),
+            };
+            value_parts.push(quote!(values.push(::std::convert::Into::into(#bound_value));));
+            match &onupdate {
+                // Saving a loaded entity keeps the secret, the column is only
+                // updated once the field is set.
+                None if write_only => {
+                    let unchanged = match &default {
+                        Some(_) => quote!(self.#field_name == #loaded),
+                        None => quote!(self.#field_name.is_none()),
+                    };
+                    write_only_updates.push(quote! {
+                        let update = if #unchanged { update } else { update.set(#ident::#field_name, #bound_value) };
+                    });
+                }
+                Some(path) => {
+                    let fresh = format_ident!("__onupdate_{}", name);
+                    let produced = inner_type(&ty);
+                    let value = quote_spanned!(path.span()=> {
+                        let onupdate: fn() -> #produced = #path;
+                        onupdate()
+                    });
+                    let value = if nullable { quote!(::std::option::Option::Some(#value)) } else { value };
+                    onupdate_values.push(quote!(let #fresh: #ty = #value;));
+                    onupdate_fields.push((field_name.clone(), fresh.clone()));
+                    update_names.push(field_name.clone());
+                    update_values.push(match &encoder {
+                        Some(encoder) => encoder.encode(&quote!(&#fresh), false, &namespace),
+                        None => quote!(::std::clone::Clone::clone(&#fresh)),
+                    });
+                }
+                None => {
+                    update_names.push(field_name.clone());
+                    update_values.push(bound_value.clone());
+                }
+            }
+            bound_values.push(bound_value);
+            names.push(field_name);
+            column_options.push(column);
 
             let id = field.ident.as_ref();
-            if field.default.is_some() {
+            if let Some(transform) = &transform {
+                predicates.push(syn::parse_quote!(<#transform as #namespace::Transformer<#ty>>::Stored: ::sqlx::decode::Decode<#lifetime, R::Database>));
+                predicates.push(syn::parse_quote!(<#transform as #namespace::Transformer<#ty>>::Stored: ::sqlx::types::Type<R::Database>));
+                let read = quote!(#namespace::decode_transformed::<R, #ty, #transform>(row, #column_name));
+                if write_only {
+                    reads.push(syn::parse_quote!(let #id: #ty = #read.or_else(|e| match e {
+                        ::sqlx::Error::ColumnNotFound(_) => ::std::result::Result::Ok(#loaded),
+                        e => ::std::result::Result::Err(e)
+                    })?;));
+                } else {
+                    reads.push(syn::parse_quote!(let #id: #ty = #read?;));
+                }
+                continue;
+            }
+
+            let field_of = quote! {
+                #namespace::FieldOf {
+                    entity: #entity_name,
+                    field: #name,
+                    column: #column_name,
+                    nullable: #nullable,
+                }
+            };
+            let read = match &try_from {
+                Some(stored) if nullable => {
+                    let inner = inner_type(&ty);
+                    predicates.push(syn::parse_quote!(::std::option::Option<#stored>: ::sqlx::decode::Decode<#lifetime, R::Database>));
+                    predicates.push(syn::parse_quote!(::std::option::Option<#stored>: ::sqlx::types::Type<R::Database>));
+                    quote!(#namespace::decode_optional_field_from::<R, #inner, #stored>(row, #field_of))
+                }
+                Some(stored) => {
+                    predicates.push(syn::parse_quote!(#stored: ::sqlx::decode::Decode<#lifetime, R::Database>));
+                    predicates.push(syn::parse_quote!(#stored: ::sqlx::types::Type<R::Database>));
+                    quote!(#namespace::decode_field_from::<R, #ty, #stored>(row, #field_of))
+                }
+                None => {
+                    predicates.push(syn::parse_quote!(#row_ty: ::sqlx::decode::Decode<#lifetime, R::Database>));
+                    predicates.push(syn::parse_quote!(#row_ty: ::sqlx::types::Type<R::Database>));
+                    quote!(#namespace::decode_field::<R, #row_ty>(row, #field_of))
+                }
+            };
+
+            if write_only {
+                reads.push(syn::parse_quote!(let #id: #ty = #read.or_else(|e| match e {
+                    ::sqlx::Error::ColumnNotFound(_) => ::std::result::Result::Ok(#loaded),
+                    e => ::std::result::Result::Err(e)
+                })?;));
+            } else if field.default.is_some() {
                 reads.push(
-                    syn::parse_quote!(let #id: #ty = row.try_get(#column_name).or_else(|e| match e {
-                    ::sqlx::Error::ColumnNotFound(_) => {
-                        ::std::result::Result::Ok(#default)
-                    },
+                    syn::parse_quote!(let #id: #ty = #read.or_else(|e| match e {
+                    e @ ::sqlx::Error::ColumnNotFound(_) => Self::#id.default_value().ok_or(e),
                     e => ::std::result::Result::Err(e)
                 })?;),
                 );
             } else {
-                reads.push(syn::parse_quote!(let #id: #ty = row.try_get(#column_name)?;));
+                reads.push(syn::parse_quote!(let #id: #ty = #read?;));
             }
         }
     } else {
@@ -266,19 +1254,136 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
             alias: None,
             database: None,
             index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
+        }
+    };
+
+    let index_schemas = entity_options.indexes.iter().map(|index| {
+        let name = &index.name;
+        let columns = index.columns.split(',').map(|column| column.trim());
+        let unique = index.unique;
+        quote! {
+            #namespace::IndexSchema {
+                name: #name,
+                columns: &[#(#columns),*],
+                unique: #unique,
+            }
+        }
+    });
+
+    let schema_def = quote! {
+        #namespace::EntitySchema {
+            tablename: #tablename,
+            database: None,
+            columns: &[#(#column_schemas),*],
+            indexes: &[#(#index_schemas),*],
+        }
+    };
+
+    // Quoted the way every visitor quotes them, for the statements formatted
+    // without one. Views and flattened fields are left to the visitors.
+    let qualified_columns = (view.is_none() && flattened_fields.is_empty() && invalid_identifier(&tablename).is_none()).then(|| {
+        let quoted = |open: &str, close: &str| {
+            let table = format!("{}{}{}", open, tablename, close);
+            let qualified = |column: &str| format!("{}.{}{}{}", table, open, column, close);
+            let columns = quoted_columns.iter().map(|column| qualified(column)).collect::<Vec<_>>().join(", ");
+            let primary_key = match primary_key_name.as_deref().map(qualified) {
+                Some(primary_key) => quote!(::std::option::Option::Some(#primary_key)),
+                None => quote!(::std::option::Option::None),
+            };
+            quote! {
+                #namespace::QuotedIdentifiers {
+                    table: #table,
+                    columns: #columns,
+                    primary_key: #primary_key,
+                }
+            }
+        };
+        let (backticks, double_quotes, brackets) = (quoted("`", "`"), quoted("\"", "\""), quoted("[", "]"));
+        quote! {
+            const QUALIFIED_COLUMNS: ::std::option::Option<#namespace::QualifiedColumns> = ::std::option::Option::Some(#namespace::QualifiedColumns {
+                backticks: #backticks,
+                double_quotes: #double_quotes,
+                brackets: #brackets,
+            });
+        }
+    });
+
+    let table = match &view {
+        Some(view) => quote! {
+            #namespace::Aliasable::alias(#namespace::Table::from(#namespace::Select::from_sql(#view)), #tablename)
+        },
+        None => quote!(#ident::_table),
+    };
+
+    // The names of embedded columns are only known at runtime, the columns
+    // and the schema of entities with flattened fields are built once.
+    let (columns, schema) = if flattened_fields.is_empty() {
+        (quote!(Self::COLUMNS), quote!(#ident::_schema))
+    } else {
+        tokens.extend(quote! {
+            impl #entity_impl #ident #entity_ty {
+                fn _flattened() -> &'static #namespace::FlattenedColumns {
+                    static COLUMNS: ::std::sync::OnceLock<#namespace::FlattenedColumns> = ::std::sync::OnceLock::new();
+                    COLUMNS.get_or_init(|| {
+                        let mut columns = #namespace::FlattenedColumns::new(#tablename);
+                        #(#flattened_parts)*
+                        columns
+                    })
+                }
+            }
+        });
+        (
+            quote!(#ident::_flattened().columns()),
+            quote! {
+                #namespace::EntitySchema {
+                    columns: #ident::_flattened().schemas(),
+                    ..#ident::_schema
+                }
+            },
+        )
+    };
+
+    // Filtered once, the write-only columns are known by name only.
+    let readable_columns = (!write_only_columns.is_empty()).then(|| {
+        quote! {
+            fn readable_columns() -> &'static [#namespace::Column<'static>] {
+                static COLUMNS: ::std::sync::OnceLock<::std::vec::Vec<#namespace::Column<'static>>> = ::std::sync::OnceLock::new();
+                COLUMNS.get_or_init(|| {
+                    <Self as #namespace::Entity>::columns()
+                        .iter()
+                        .filter(|column| ![#(#write_only_columns),*].contains(&&*column.name))
+                        .cloned()
+                        .collect()
+                })
+            }
         }
+    });
+
+    let unmatched = if flattened_fields.is_empty() {
+        quote!(::std::option::Option::None)
+    } else {
+        quote!(#ident::_flattened().columns().iter().position(|column| column.name == name))
     };
 
-    // let orig_generics = &entity_def.generics;
+    let mut row_generics = entity_generics.clone();
+    row_generics.params.insert(0, syn::parse_quote!('__row));
+    let (row_impl, _, _) = row_generics.split_for_impl();
     tokens.extend(quote! {
-        impl #ident {
+        impl #entity_impl #ident #entity_ty {
             const _table: #namespace::Table<'static> = #table_def;
+            const _schema: #namespace::EntitySchema = #schema_def;
+            const _schema_hash: u64 = #namespace::SchemaHasher::new(#tablename)
+                .columns("", #ident::_schema.columns)
+                #(#embedded_hashes)*
+                .finish();
 
             #(pub const #names: #namespace::ColumnOptions<#types> = #column_options;) *
         }
 
-        impl #namespace::Entity for #ident {
-            const COLUMNS: &'static [ #namespace::Column<'static> ] = &[ #(( #ident::#names.column() )), * ];
+        impl #entity_impl #namespace::Entity for #ident #entity_ty {
+            const COLUMNS: &'static [ #namespace::Column<'static> ] = &[ #(( #ident::#names.into_column() )), * ];
+            #qualified_columns
 
             #[inline]
             fn tablename() -> &'static str {
@@ -287,25 +1392,303 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
 
             #[inline]
             fn columns() -> &'static [#namespace::Column<'static>] {
-                Self::COLUMNS
+                #columns
             }
 
+            #readable_columns
+
             #[inline]
             fn table() -> #namespace::Table<'static> {
-                #ident::_table
+                #table
             }
+
+            #[inline]
+            fn schema() -> #namespace::EntitySchema {
+                #schema
+            }
+
+            #[inline]
+            fn schema_hash() -> u64 {
+                #ident::_schema_hash
+            }
+
+            fn column_index(name: &str) -> ::std::option::Option<usize> {
+                match name {
+                    #(#column_indexes,)*
+                    _ => #unmatched,
+                }
+            }
+
+            fn values(&self) -> ::std::vec::Vec<#namespace::Value<'static>> {
+                let mut values = ::std::vec::Vec::with_capacity(<Self as #namespace::Entity>::columns().len());
+                #(#value_parts)*
+                values
+            }
+
+            #notify_channel
         }
 
+        #[automatically_derived]
+        impl #row_impl ::std::convert::From<&#ident #entity_ty> for #namespace::Row<'__row> {
+            fn from(entity: &#ident #entity_ty) -> Self {
+                ::std::convert::From::from(<#ident #entity_ty as #namespace::Entity>::values(entity))
+            }
+        }
+
+        #[automatically_derived]
+        impl #row_impl ::std::convert::From<#ident #entity_ty> for #namespace::Row<'__row> {
+            fn from(entity: #ident #entity_ty) -> Self {
+                ::std::convert::From::from(&entity)
+            }
+        }
     });
 
+    if !lookups.is_empty() {
+        let mut finders = Vec::new();
+        for Lookup { field, ty, bound } in &lookups {
+            let finder = format_ident!("find_by_{}", field);
+            if field_names.contains(&finder) {
+                let message = format!("`{}` is generated for the unique column `{}`, rename the field", finder, field);
+                return syn::Error::new_spanned(&finder, message).into_compile_error().into();
+            }
+            let exists = format_ident!("exists_by_{}", field);
+            if field_names.contains(&exists) {
+                let message = format!("`{}` is generated for the unique column `{}`, rename the field", exists, field);
+                return syn::Error::new_spanned(&exists, message).into_compile_error().into();
+            }
+            let doc = format!("Fetches the entity whose `{}` is `value`.", field);
+            let exists_doc = format!("Whether an entity whose `{}` is `value` exists.", field);
+            finders.push(quote! {
+                #[doc = #doc]
+                pub fn #finder<DB, K>(value: K) -> #namespace::SelectingExecution<Self, DB>
+                where
+                    DB: ::sqlx::Database,
+                    K: #namespace::IntoPrimaryKey<#ty>,
+                    Self: for<'r> ::sqlx::FromRow<'r, <DB as ::sqlx::Database>::Row>,
+                {
+                    let value: #ty = #namespace::IntoPrimaryKey::into_primary_key(value);
+                    <Self as #namespace::Entity>::default_select()
+                        .so_that(#namespace::Comparable::equals(#ident::#field, #bound))
+                        .into()
+                }
+
+                #[doc = #exists_doc]
+                pub fn #exists<DB, K>(value: K) -> #namespace::SelectingExecution<#namespace::Scalar<bool>, DB>
+                where
+                    DB: ::sqlx::Database,
+                    K: #namespace::IntoPrimaryKey<#ty>,
+                    #namespace::Scalar<bool>: for<'r> ::sqlx::FromRow<'r, <DB as ::sqlx::Database>::Row>,
+                {
+                    let value: #ty = #namespace::IntoPrimaryKey::into_primary_key(value);
+                    let rows = #namespace::Select::from_table(<Self as #namespace::Entity>::table())
+                        .value(#namespace::IntoRaw::raw(1))
+                        .so_that(#namespace::Comparable::equals(#ident::#field, #bound));
+                    #namespace::Select::default().value(#namespace::exists(rows)).into()
+                }
+            });
+        }
+        tokens.extend(quote! {
+            impl #entity_impl #ident #entity_ty {
+                #(#finders)*
+            }
+        });
+    }
+
+    if let Some(soft_delete_column) = soft_delete_column {
+        tokens.extend(quote! {
+            impl #entity_impl #ident #entity_ty {
+                /// Rows which are not soft deleted.
+                pub fn not_deleted() -> #namespace::Scope<'static> {
+                    #namespace::Comparable::is_null(#ident::#soft_delete_column).into()
+                }
+            }
+        });
+    }
+
+    if let (Some(hook), None) = (&before_save, &primary_key_type) {
+        return syn::Error::new_spanned(hook, "entities without a primary key are never saved")
+            .into_compile_error()
+            .into();
+    }
+    if let Some(BelongsTo { via }) = &belongs_to {
+        let message = match (&self_reference, &primary_key_type) {
+            (None, _) => Some(format!("no field `{}` to reference the entity by", via.value())),
+            (Some(_), None) => Some("entities without a primary key can not be referenced".to_string()),
+            _ => None,
+        };
+        if let Some(message) = message {
+            return syn::Error::new_spanned(via, message).into_compile_error().into();
+        }
+    }
+
     if primary_key_type.is_some() {
+        let pk_name = &primary_key_column_name;
+        let not_set = quote!(#namespace::primary_key_not_set(#tablename));
+        let get = if primary_key_nullable {
+            quote! {
+                let pk: Self::PrimaryKeyValueType = #namespace::IntoPrimaryKey::into_primary_key(pk);
+                let select = <Self as #namespace::Entity>::default_select();
+                match pk {
+                    ::std::option::Option::Some(pk) => select.so_that(Self::primary_key().equals(pk)).into(),
+                    ::std::option::Option::None => #namespace::SelectingExecution::from(select).failed(#not_set),
+                }
+            }
+        } else {
+            quote! {
+                <Self as #namespace::Entity>::default_select()
+                    .so_that(Self::primary_key().equals(#namespace::IntoPrimaryKey::into_primary_key(pk)))
+                    .into()
+            }
+        };
+        let delete = if primary_key_nullable {
+            quote! {
+                let delete = #namespace::Delete::from_table(Self::table());
+                match self.pk() {
+                    ::std::option::Option::Some(pk) => #namespace::DeletingExecution::new(delete.so_that(Self::primary_key().equals(pk)), self),
+                    ::std::option::Option::None => #namespace::DeletingExecution::new(delete, self).failed(#not_set),
+                }
+            }
+        } else {
+            quote! {
+                #namespace::DeletingExecution::new(#namespace::Delete::from_table(Self::table()).so_that(Self::primary_key().equals(self.pk())), self)
+            }
+        };
+        let flattened_names: Vec<_> = flattened_fields.iter().map(|(field, _)| field).collect();
+        let flattened_columns: Vec<_> = flattened_fields.iter().map(|(_, name)| name).collect();
+        let update = if flattened_fields.is_empty() && write_only_updates.is_empty() {
+            quote! {
+                #namespace::Update::table(Self::table())
+                    #(.set(#ident::#update_names, #update_values))*
+                    .so_that(Self::primary_key().equals(self.pk()))
+            }
+        } else {
+            quote! {{
+                let update = #namespace::Update::table(Self::table())
+                    #(.set(#ident::#update_names, #update_values))*;
+                #(#write_only_updates)*
+                #(
+                    let update = #ident::_flattened()
+                        .embedded(#flattened_columns)
+                        .iter()
+                        .zip(#namespace::EmbeddedColumns::values(&self.#flattened_names))
+                        .fold(update, |update, (column, value)| update.set(column.clone(), value));
+                )*
+                update.so_that(Self::primary_key().equals(self.pk()))
+            }}
+        };
+        let single_insert = |names: &[&syn::Ident], values: &[&proc_macro2::TokenStream]| {
+            if flattened_fields.is_empty() {
+                quote! {
+                    #namespace::Insert::single_into(Self::table())
+                        #(.value(#ident::#names, #values))*
+                }
+            } else {
+                quote! {{
+                    let insert = #namespace::Insert::single_into(Self::table())
+                        #(.value(#ident::#names, #values))*;
+                    #(
+                        let insert = #ident::_flattened()
+                            .embedded(#flattened_columns)
+                            .iter()
+                            .zip(#namespace::EmbeddedColumns::values(&self.#flattened_names))
+                            .fold(insert, |insert, (column, value)| insert.value(column.clone(), value));
+                    )*
+                    insert
+                }}
+            }
+        };
+        let insert = single_insert(&names.iter().collect::<Vec<_>>(), &bound_values.iter().collect::<Vec<_>>());
+        // An autoincrement key is left to the database, and read back.
+        let (keyless_names, keyless_values): (Vec<_>, Vec<_>) = names
+            .iter()
+            .zip(&bound_values)
+            .filter(|(name, _)| Some(*name) != pk_name.as_ref())
+            .unzip();
+        let keyless_insert = single_insert(&keyless_names, &keyless_values);
+        // A new row is recognized by its unset key, the key is generated and
+        // written back before inserting it, or after for an autoincrement key.
+        // The `onupdate` values are written back to the entity once saved.
+        let updating = if onupdate_fields.is_empty() {
+            quote!(#namespace::SavingExecution::new(#update, self))
+        } else {
+            let (refreshed, fresh): (Vec<_>, Vec<_>) = onupdate_fields.iter().cloned().unzip();
+            quote! {{
+                #(#onupdate_values)*
+                let update = #update;
+                #namespace::SavingExecution::new(update, self).refreshed(move |entity: &mut #ident| {
+                    #(entity.#refreshed = #fresh;)*
+                })
+            }}
+        };
+        let save = match (&primary_key_generator, primary_key_nullable) {
+            (Some(generator), true) => quote! {
+                if self.#pk_name.is_none() {
+                    self.#pk_name = ::std::option::Option::Some(#generator());
+                    let insert = #insert;
+                    return #namespace::SavingExecution::new(insert, self);
+                }
+                #updating
+            },
+            (Some(generator), false) => quote! {
+                if self.#pk_name == ::std::default::Default::default() {
+                    self.#pk_name = #generator();
+                    let insert = #insert;
+                    return #namespace::SavingExecution::new(insert, self);
+                }
+                #updating
+            },
+            (None, nullable) if primary_key_autoincrement => {
+                let (unset, key) = if nullable {
+                    (
+                        quote!(self.#pk_name.is_none()),
+                        quote!(::std::option::Option::Some(key)),
+                    )
+                } else {
+                    let pk_type = &primary_key_value_type;
+                    (
+                        quote!(self.#pk_name == <#pk_type as ::std::default::Default>::default()),
+                        quote!(key),
+                    )
+                };
+                quote! {
+                    if #unset {
+                        let insert = #keyless_insert;
+                        return #namespace::SavingExecution::new(insert, self).generating_key(
+                            Self::primary_key(),
+                            |entity: &mut Self, id: i64| {
+                                let key = #namespace::convert_generated_key(id)?;
+                                entity.#pk_name = #key;
+                                ::std::result::Result::Ok(())
+                            },
+                        );
+                    }
+                    #updating
+                }
+            }
+            (None, true) => quote! {
+                if self.#pk_name.is_none() {
+                    return #updating.failed(#not_set);
+                }
+                #updating
+            },
+            (None, false) => updating.clone(),
+        };
+        let save = match &before_save {
+            Some(hook) => quote! {
+                if let ::std::result::Result::Err(error) = #hook(self) {
+                    return #updating.failed(error);
+                }
+                #save
+            },
+            None => save,
+        };
         // impl HasPrimaryKey if PrimaryKey exists.
         let token = quote! {
-            impl #ident {
+            impl #entity_impl #ident #entity_ty {
                 const _primary_key: <Self as #namespace::HasPrimaryKey>::PrimaryKey = #primary_key_column;
             }
 
-            impl #namespace::HasPrimaryKey for #ident {
+            impl #entity_impl #namespace::HasPrimaryKey for #ident #entity_ty {
                 type PrimaryKey = #primary_key_type;
                 type PrimaryKeyValueType = #primary_key_value_type;
                 #[inline]
@@ -314,18 +1697,18 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
                 }
 
                 #[inline]
-                fn pk(&self) -> <Self as HasPrimaryKey>::PrimaryKeyValueType {
-                    self.#primary_key_column_name
+                fn pk(&self) -> <Self as #namespace::HasPrimaryKey>::PrimaryKeyValueType {
+                    ::std::clone::Clone::clone(&self.#primary_key_column_name)
                 }
 
                 #[inline]
-                fn get<DB: ::sqlx::Database>(pk: Self::PrimaryKeyValueType) -> #namespace::SelectingExecution<Self, DB>
+                fn get<DB, K>(pk: K) -> #namespace::SelectingExecution<Self, DB>
                     where
+                        DB: ::sqlx::Database,
+                        K: #namespace::IntoPrimaryKey<Self::PrimaryKeyValueType>,
                         Self: for<'r> sqlx::FromRow<'r, <DB as sqlx::Database>::Row>,
                 {
-                    #namespace::Select::from_table(Self::table())
-                        .so_that(Self::primary_key().equals(pk))
-                        .into()
+                    #get
                 }
 
                 #[inline]
@@ -333,7 +1716,7 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
                     where
                         DB: ::sqlx::Database
                 {
-                    #namespace::DeletingExecution::new(#namespace::Delete::from_table(Self::table()).so_that(Self::primary_key().equals(self.pk())), self)
+                    #delete
                 }
 
                 #[inline]
@@ -341,29 +1724,141 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
                     where
                         DB: ::sqlx::Database
                 {
-                    #namespace::SavingExecution::new(
-                        #namespace::Update::table(Self::table())
-                            #(.set(#ident::#names, self.#names.clone()))*
-                            .so_that(Self::primary_key().equals(self.pk())),
-                        self
-                    )
+                    #save
                 }
             }
         };
         tokens.extend(token);
-    };
+
+        if !readonly {
+            tokens.extend(quote! {
+                impl #entity_impl #namespace::Writable for #ident #entity_ty {}
+            });
+        }
+
+        if let Some(snapshot) = &snapshot_field {
+            let update = if primary_key_nullable {
+                quote! {
+                    let conditions = match self.pk() {
+                        ::std::option::Option::Some(pk) => Self::primary_key().equals(pk),
+                        ::std::option::Option::None => {
+                            let conditions = Self::primary_key().is_null();
+                            return #namespace::UpdatingExecution::new(self, conditions, |entity: &mut Self| &mut entity.#snapshot)
+                                .failed(#not_set);
+                        }
+                    };
+                }
+            } else {
+                quote!(let conditions = Self::primary_key().equals(self.pk());)
+            };
+            tokens.extend(quote! {
+                impl #entity_impl #ident #entity_ty {
+                    /// Updates the columns changed since the entity was
+                    /// loaded, nothing when none changed.
+                    pub fn update<'e, DB>(&'e mut self) -> #namespace::UpdatingExecution<'e, Self, DB>
+                    where
+                        DB: ::sqlx::Database,
+                    {
+                        #update
+                        #namespace::UpdatingExecution::new(self, conditions, |entity: &mut Self| &mut entity.#snapshot)
+                    }
+                }
+            });
+        }
+
+        if let Some((via, nullable)) = &self_reference {
+            tokens.extend(self_reference_helpers(&ident, &entity_generics, via, *nullable, &namespace));
+        }
+    } else if !no_primary_key {
+        tokens.extend(missing_primary_key_helpers(&ident, &entity_generics, &namespace));
+    }
+
+    if derive_default {
+        if !defaultless_fields.is_empty() {
+            let message = format!(
+                "`derive_default` needs a default for every field, add `#[column(default = ..)]` to `{}`",
+                defaultless_fields.join("`, `")
+            );
+            return syn::Error::new_spanned(&ident, message).into_compile_error().into();
+        }
+        tokens.extend(quote! {
+            #[automatically_derived]
+            impl #entity_impl ::std::default::Default for #ident #entity_ty {
+                fn default() -> Self {
+                    #ident {
+                        #(#default_fields),*
+                    }
+                }
+            }
+        });
+    }
+
+    if track_changes {
+        let problem = if snapshot_field.is_none() {
+            Some(format!("`track_changes` keeps the values loaded in a field, add `snapshot: Snapshot` to `{}`", ident))
+        } else if readonly {
+            Some("read-only entities are never updated, they can not track changes".to_string())
+        } else if primary_key_column_name.is_none() {
+            Some("`track_changes` updates the row of the primary key, add `#[column(primary_key)]` to a field".to_string())
+        } else {
+            None
+        };
+        if let Some(message) = problem {
+            return syn::Error::new_spanned(&ident, message).into_compile_error().into();
+        }
+    }
+
+    if register {
+        if view.is_some() {
+            return syn::Error::new_spanned(&ident, "views have no table to create, they can not be registered")
+                .into_compile_error()
+                .into();
+        }
+        let entity_static = match &entity_lifetime {
+            Some(_) => quote!(#ident<'static>),
+            None => quote!(#ident),
+        };
+        tokens.extend(quote! {
+            #namespace::__register_entity!(#entity_static);
+        });
+    }
+
+    if builder {
+        tokens.extend(entity_builder(
+            &ident,
+            &vis,
+            &required_fields,
+            &optional_fields,
+            &namespace,
+        ));
+    }
 
     let (impl_generics, _, where_clause) = generics.split_for_impl();
 
+    // The values as read, before `after_load` changes any.
+    let take_snapshot = snapshot_field.as_ref().map(|snapshot| {
+        quote! {
+            entity.#snapshot = #namespace::Snapshot::taken(#namespace::Entity::values(&entity));
+        }
+    });
+    let after_load = after_load.map(|hook| {
+        quote! {
+            #hook(&mut entity).map_err(|error| ::sqlx::Error::Decode(::std::boxed::Box::new(error)))?;
+        }
+    });
     let token = quote! {
         #[automatically_derived]
-        impl #impl_generics ::sqlx::FromRow<#lifetime, R> for #ident #ty_generics #where_clause {
+        impl #impl_generics ::sqlx::FromRow<#lifetime, R> for #ident #entity_ty #where_clause {
             fn from_row(row: &#lifetime R) -> ::sqlx::Result<Self> {
                 #(#reads)*
 
-                ::std::result::Result::Ok(#ident {
-                    #(#names),*
-                })
+                #[allow(unused_mut)]
+                let mut entity = #ident {
+                    #(#field_names),*
+                };
+                #take_snapshot
+                #after_load
+                ::std::result::Result::Ok(entity)
             }
         }
     };
@@ -372,3 +1867,315 @@ pub fn derive_entity(input: TokenStream) -> TokenStream {
 
     tokens.into()
 }
+
+#[derive(FromDeriveInput)]
+#[darling(attributes(column), supports(struct_named))]
+struct EmbeddedDefinition {
+    ident: syn::Ident,
+    data: darling::ast::Data<(), EmbeddedField>,
+}
+
+#[derive(FromField)]
+#[darling(attributes(column))]
+struct EmbeddedField {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    /// The name of the column, before the prefix of the entity.
+    #[darling(default)]
+    name: Option<String>,
+    #[darling(default)]
+    length: Option<usize>,
+}
+
+/// Columns embedded into entities with `#[column(flatten)]`.
+#[proc_macro_derive(EmbeddedColumns, attributes(column))]
+pub fn derive_embedded_columns(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    let definition = match EmbeddedDefinition::from_derive_input(&input) {
+        Ok(definition) => definition,
+        Err(err) => return err.write_errors().into(),
+    };
+    let namespace = namespace();
+    let ident = definition.ident;
+    let fields = match definition.data {
+        darling::ast::Data::Struct(fields) => fields.fields,
+        darling::ast::Data::Enum(_) => unreachable!(),
+    };
+
+    let mut names = Vec::new();
+    let mut column_names = Vec::new();
+    let mut types = Vec::new();
+    let mut schemas = Vec::new();
+    for field in fields {
+        let name = field.ident.unwrap();
+        let column_name = field.name.unwrap_or_else(|| name.unraw().to_string());
+        if let Some(reason) = invalid_identifier(&column_name) {
+            let message = format!("`{}` can not be the name of a column: {}", column_name, reason);
+            return syn::Error::new_spanned(&name, message).into_compile_error().into();
+        }
+        let ty = field.ty;
+        let type_family = quote_optional!(type_family(&ty, field.length, &namespace));
+        let rust_type = quote!(#ty).to_string().replace(' ', "");
+        let nullable = !std::ptr::eq(inner_type(&ty), &ty);
+        let length = quote_optional!(field.length);
+        schemas.push(quote! {
+            #namespace::ColumnSchema {
+                name: #column_name,
+                rust_type: #rust_type,
+                type_family: #type_family,
+                nullable: #nullable,
+                primary_key: false,
+                autoincrement: false,
+                unique: false,
+                length: #length,
+                foreign_key: None,
+                has_default: false,
+                has_server_default: false,
+                server_default: None,
+                comment: None,
+            }
+        });
+        names.push(name);
+        column_names.push(column_name);
+        types.push(ty);
+    }
+
+    let token = quote! {
+        impl #namespace::EmbeddedColumns for #ident {
+            const COLUMNS: &'static [#namespace::ColumnSchema] = &[#(#schemas),*];
+
+            fn values(&self) -> ::std::vec::Vec<#namespace::Value<'static>> {
+                ::std::vec![#(::std::convert::Into::into(::std::clone::Clone::clone(&self.#names))),*]
+            }
+        }
+
+        #[automatically_derived]
+        impl<'r, R: ::sqlx::Row> #namespace::FromEmbeddedRow<'r, R> for #ident
+        where
+            for<'c> &'c ::std::primitive::str: ::sqlx::ColumnIndex<R>,
+            #(#types: ::sqlx::decode::Decode<'r, R::Database> + ::sqlx::types::Type<R::Database>,)*
+        {
+            fn from_row(row: &'r R, prefix: &::std::primitive::str) -> ::sqlx::Result<Self> {
+                ::std::result::Result::Ok(#ident {
+                    #(#names: row.try_get(::std::format!("{}{}", prefix, #column_names).as_str())?,)*
+                })
+            }
+        }
+    };
+
+    token.into()
+}
+
+/// How the values of a field are converted into the ones of its column.
+#[derive(Clone)]
+enum Conversion {
+    /// `#[column(transform = "...")]`, through a `Transformer`.
+    Transform(syn::Path),
+    /// `#[column(try_from = "...")]`, into the stored type with `From`.
+    From(Box<syn::Type>),
+}
+
+/// Encodes the values of a `#[column(transform = "...")]` or
+/// `#[column(try_from = "...")]` field.
+#[derive(Clone)]
+struct Encoder {
+    conversion: Conversion,
+    ty: syn::Type,
+    nullable: bool,
+}
+
+impl Encoder {
+    /// Encodes `value`, a reference to the field's value, or to its inner
+    /// value with `inner` for nullable fields.
+    fn encode(&self, value: &TokenStream2, inner: bool, namespace: &TokenStream2) -> TokenStream2 {
+        let Encoder { conversion, ty, nullable } = self;
+        let transform = match conversion {
+            Conversion::Transform(transform) => transform,
+            Conversion::From(stored) => {
+                let field_ty = inner_type(ty);
+                let from = quote!(<#stored as ::std::convert::From<#field_ty>>::from);
+                return if *nullable && !inner {
+                    quote!(#namespace::Value::from(::std::option::Option::map(::std::clone::Clone::clone(#value), #from)))
+                } else {
+                    quote!(#namespace::Value::from(#from(::std::clone::Clone::clone(#value))))
+                };
+            }
+        };
+        let value = if inner && *nullable {
+            quote!(&::std::option::Option::Some(::std::clone::Clone::clone(#value)))
+        } else {
+            value.clone()
+        };
+        quote! {
+            <#transform as #namespace::Transformer<#ty>>::encode(
+                &<#transform as ::std::default::Default>::default(),
+                #value,
+            )
+        }
+    }
+}
+
+/// A column entities are looked up by, `find_by_<field>`.
+struct Lookup {
+    field: syn::Ident,
+    /// The type of the field, without `Option`.
+    ty: syn::Type,
+    /// The value bound for `value`, encoded as the column stores it.
+    bound: TokenStream2,
+}
+
+/// A field of the builder the database, a generator or a default can fill.
+struct OptionalField {
+    name: syn::Ident,
+    ty: syn::Type,
+    has_default: bool,
+    generator: Option<syn::Path>,
+    encoder: Option<Encoder>,
+}
+
+/// The typestate builder of `#[entity(builder)]`, every required field is a
+/// type parameter going from `Missing` to `Provided<T>` when set, `insert` is
+/// only available once all of them are provided.
+fn entity_builder(
+    ident: &syn::Ident,
+    vis: &syn::Visibility,
+    required: &[(syn::Ident, syn::Type, Option<Encoder>)],
+    optional: &[OptionalField],
+    namespace: &TokenStream2,
+) -> TokenStream2 {
+    let builder = format_ident!("{}Builder", ident);
+    let doc = format!("Builds a new [`{}`] row, see `{}::builder`.", ident, ident);
+
+    let required_names: Vec<_> = required.iter().map(|(name, _, _)| name).collect();
+    let required_types: Vec<_> = required.iter().map(|(_, ty, _)| ty).collect();
+    let required_values: Vec<_> = required
+        .iter()
+        .map(|(name, _, encoder)| match encoder {
+            Some(encoder) => encoder.encode(&quote!(&self.#name.0), false, namespace),
+            None => quote!(self.#name.0),
+        })
+        .collect();
+    let params: Vec<_> = required
+        .iter()
+        .map(|(name, _, _)| format_ident!("__{}", name.unraw().to_string().to_pascal_case()))
+        .collect();
+    let missing = params.iter().map(|_| quote!(#namespace::Missing));
+    let optional_names: Vec<_> = optional.iter().map(|field| &field.name).collect();
+    let optional_types: Vec<_> = optional.iter().map(|field| &field.ty).collect();
+
+    let required_setters = required.iter().enumerate().map(|(i, (name, ty, _))| {
+        let others: Vec<_> = params
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, param)| param)
+            .collect();
+        let before = params.iter().enumerate().map(|(j, param)| {
+            if i == j {
+                quote!(#namespace::Missing)
+            } else {
+                quote!(#param)
+            }
+        });
+        let after = params.iter().enumerate().map(|(j, param)| {
+            if i == j {
+                quote!(#namespace::Provided<#ty>)
+            } else {
+                quote!(#param)
+            }
+        });
+        let rest: Vec<_> = required_names
+            .iter()
+            .chain(optional_names.iter())
+            .filter(|other| ***other != *name)
+            .collect();
+
+        quote! {
+            impl<#(#others),*> #builder<#(#before),*> {
+                #vis fn #name(self, #name: #ty) -> #builder<#(#after),*> {
+                    #builder {
+                        #name: #namespace::Provided(#name),
+                        #(#rest: self.#rest,)*
+                    }
+                }
+            }
+        }
+    });
+
+    let optional_values = optional.iter().map(|field| {
+        let OptionalField {
+            name,
+            has_default,
+            generator,
+            encoder,
+            ..
+        } = field;
+        let encode = |value: TokenStream2| match encoder {
+            Some(encoder) => encoder.encode(&quote!(&#value), true, namespace),
+            None => value,
+        };
+        let generated = encode(quote!(#generator()));
+        let value = encode(quote!(value));
+        let unset = if generator.is_some() {
+            quote!(insert.value(#ident::#name, #generated))
+        } else if *has_default {
+            quote! {
+                match #ident::#name.default_value() {
+                    ::std::option::Option::Some(value) => insert.value(#ident::#name, value),
+                    ::std::option::Option::None => insert,
+                }
+            }
+        } else {
+            quote!(insert)
+        };
+        quote! {
+            let insert = match self.#name {
+                ::std::option::Option::Some(value) => insert.value(#ident::#name, #value),
+                ::std::option::Option::None => #unset,
+            };
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #vis struct #builder<#(#params),*> {
+            #(#required_names: #params,)*
+            #(#optional_names: ::std::option::Option<#optional_types>,)*
+        }
+
+        impl #ident {
+            /// A builder for a new row, the columns generated by the database
+            /// can be left out.
+            #vis fn builder() -> #builder<#(#missing),*> {
+                #builder {
+                    #(#required_names: #namespace::Missing,)*
+                    #(#optional_names: ::std::option::Option::None,)*
+                }
+            }
+        }
+
+        #(#required_setters)*
+
+        impl<#(#params),*> #builder<#(#params),*> {
+            #(
+                #vis fn #optional_names(mut self, #optional_names: #optional_types) -> Self {
+                    self.#optional_names = ::std::option::Option::Some(#optional_names);
+                    self
+                }
+            )*
+        }
+
+        impl #builder<#(#namespace::Provided<#required_types>),*> {
+            /// Inserts the row, leaving the unset generated columns to the database.
+            #vis fn insert<'insert, DB>(self) -> #namespace::InsertingExecution<DB, #namespace::SingleRowInsert<'insert>>
+            where
+                DB: ::sqlx::Database,
+            {
+                let insert = <#ident as #namespace::Entity>::insert();
+                #(let insert = insert.value(#ident::#required_names, #required_values);)*
+                #(#optional_values)*
+                insert
+            }
+        }
+    }
+}
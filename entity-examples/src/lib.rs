@@ -1,4 +1,5 @@
-use xiayu::prelude::*;
+use xiayu::prelude::entity::*;
+use xiayu::prelude::{MultiRowInsert, SingleRowInsert};
 
 #[derive(Entity)]
 pub struct User {
@@ -29,3 +30,12 @@ pub struct Dog {
     age: i32,
     ingredients: String,
 }
+
+pub fn new_user<DB: sqlx::Database>(id: i32) -> InsertingExecution<DB, SingleRowInsert<'static>> {
+    User::insert().value(User::id, id)
+}
+
+pub fn new_posts<DB: sqlx::Database>(user_id: i32, ids: &[i32]) -> InsertingExecution<DB, MultiRowInsert<'static>> {
+    ids.iter()
+        .fold(Post::insert_many(), |insert, id| insert.values((*id, user_id)))
+}
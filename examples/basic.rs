@@ -7,19 +7,34 @@ use xiayu::prelude::*;
 use xiayu::visitors::Visitor;
 use xiayu_derive::*;
 
+/// The key of a `T`, stored as it is.
 #[derive(Debug)]
 pub struct Relation<T> {
+    id: i32,
     _phantom: PhantomData<T>,
 }
 
 impl<T> Relation<T> {
-    fn new() -> Self {
+    fn new(id: i32) -> Self {
         Self {
+            id,
             _phantom: PhantomData,
         }
     }
 }
 
+impl<T> Clone for Relation<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.id)
+    }
+}
+
+impl<'a, T> From<Relation<T>> for Value<'a> {
+    fn from(relation: Relation<T>) -> Self {
+        Value::from(relation.id)
+    }
+}
+
 #[derive(Debug, Entity)]
 #[tablename = "entities"]
 pub struct AnEntity {
@@ -43,7 +58,7 @@ fn main() {
 
     let entity = AnEntity {
         id: 2,
-        another_entity_id: Relation::<AnotherEntity>::new(),
+        another_entity_id: Relation::<AnotherEntity>::new(1),
         maybe_float: None,
     };
     assert_eq!(entity.id, 2);
@@ -57,6 +72,9 @@ fn main() {
                 .so_that(AnEntity::another_entity_id.equals(1)),
         )
         .unwrap(),
-        ("".to_string(), Vec::new())
+        (
+            r#"SELECT "entities".* FROM "entities" WHERE "entities"."another_entity_id" = $1"#.to_string(),
+            vec![Value::from(1)]
+        )
     );
 }
@@ -0,0 +1,40 @@
+#!/usr/bin/env sh
+# Compile time and code size of xiayu and a binary using it, to compare
+# changes to generic and macro generated code:
+#
+#     scripts/bloat.sh > before.txt
+#     git checkout my-branch
+#     scripts/bloat.sh > after.txt
+#     diff before.txt after.txt
+#
+# The binary is the `fixtures` test, built with the entities of
+# `entity-examples`. With `cargo bloat` installed its largest crates are
+# listed as well. Extra arguments are passed to cargo, e.g. `+nightly` or
+# `--features postgres`.
+set -eu
+
+cd "$(dirname "$0")/.."
+FEATURES="docs,json,test-util"
+TARGET_DIR="${CARGO_TARGET_DIR:-target}/bloat"
+
+cargo "$@" clean --release -p xiayu -p xiayu-derive --target-dir "$TARGET_DIR" >/dev/null 2>&1 || true
+
+start=$(date +%s)
+cargo "$@" build --release --features "$FEATURES" --target-dir "$TARGET_DIR" --lib >/dev/null 2>&1
+lib=$(( $(date +%s) - start ))
+
+start=$(date +%s)
+cargo "$@" build --release --features "$FEATURES" --target-dir "$TARGET_DIR" --test fixtures >/dev/null 2>&1
+test=$(( $(date +%s) - start ))
+
+binary=$(ls -t "$TARGET_DIR"/release/deps/fixtures-* | grep -v '\.d$' | head -n 1)
+rlib=$(ls -t "$TARGET_DIR"/release/deps/libxiayu-*.rlib | head -n 1)
+
+echo "build of the library:     ${lib}s"
+echo "build of the test binary: ${test}s"
+echo "size of the library:      $(wc -c < "$rlib") bytes"
+echo "size of the test binary:  $(wc -c < "$binary") bytes"
+
+if cargo "$@" bloat --version >/dev/null 2>&1; then
+    cargo "$@" bloat --release --features "$FEATURES" --target-dir "$TARGET_DIR" --test fixtures --crates -n 10
+fi
@@ -0,0 +1,248 @@
+use super::{Query, Table, TypeFamily};
+use crate::schema::{ColumnSchema, EntitySchema};
+use std::borrow::Cow;
+
+/// A change to the definition of a table, rendered as an `ALTER TABLE`, a
+/// `CREATE TABLE` or a `CREATE INDEX` statement.
+///
+/// ```rust
+/// # use entities::Recipe;
+/// # use xiayu::prelude::*;
+/// # use xiayu::visitors::{Visitor, Sqlite};
+/// # fn main() -> xiayu::Result<()> {
+/// let column = ColumnDefinition::new("rating", Some(TypeFamily::Int), true);
+/// let (sql, _) = Sqlite::build(AlterTable::add_column(Recipe::table(), column))?;
+///
+/// assert_eq!("ALTER TABLE `recipes` ADD COLUMN `rating` INTEGER", sql);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlterTable<'a> {
+    pub(crate) table: Table<'a>,
+    pub(crate) operation: AlterOperation,
+}
+
+/// What an [`AlterTable`] does to its table.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlterOperation {
+    /// Adds a new column.
+    AddColumn(ColumnDefinition),
+    /// Drops a column and its data.
+    DropColumn(Cow<'static, str>),
+    /// Changes the nullability of an existing column to the one of the
+    /// definition. The type is needed by MySQL and SQL Server, which restate
+    /// the whole column.
+    SetNullable(ColumnDefinition),
+    /// Creates an index over the named columns.
+    CreateIndex {
+        name: Cow<'static, str>,
+        columns: Vec<Cow<'static, str>>,
+        unique: bool,
+    },
+    /// Creates the table.
+    CreateTable(TableDefinition),
+}
+
+impl AlterOperation {
+    /// True if applying the operation loses data. A renamed field looks like
+    /// a dropped and an added column, tools should confirm these first.
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, Self::DropColumn(_))
+    }
+}
+
+/// A column as written in a DDL statement.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColumnDefinition {
+    pub name: Cow<'static, str>,
+    /// The type of the column, mapped to a database type by the visitor.
+    pub type_family: Option<TypeFamily>,
+    pub nullable: bool,
+    /// The SQL expression of the default value, set when creating the table.
+    pub server_default: Option<Cow<'static, str>>,
+}
+
+impl ColumnDefinition {
+    pub fn new<S>(name: S, type_family: Option<TypeFamily>, nullable: bool) -> Self
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        Self {
+            name: name.into(),
+            type_family,
+            nullable,
+            server_default: None,
+        }
+    }
+}
+
+impl From<&ColumnSchema> for ColumnDefinition {
+    fn from(column: &ColumnSchema) -> Self {
+        Self {
+            server_default: column.server_default.map(Cow::Borrowed),
+            ..Self::new(column.name, column.type_family, column.nullable)
+        }
+    }
+}
+
+/// The columns and constraints of a new table.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableDefinition {
+    pub columns: Vec<ColumnDefinition>,
+    pub primary_key: Vec<Cow<'static, str>>,
+    /// True if the database generates the values of a single column primary
+    /// key.
+    pub autoincrement: bool,
+    /// Columns with a unique constraint of their own.
+    pub unique: Vec<Cow<'static, str>>,
+    pub foreign_keys: Vec<ForeignKeyDefinition>,
+}
+
+/// A column referencing a column of another table.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForeignKeyDefinition {
+    pub column: Cow<'static, str>,
+    pub references_table: Cow<'static, str>,
+    pub references_column: Cow<'static, str>,
+}
+
+impl From<&EntitySchema> for TableDefinition {
+    fn from(schema: &EntitySchema) -> Self {
+        let primary_key: Vec<_> = schema.primary_key().collect();
+        let autoincrement = primary_key.len() == 1 && primary_key[0].autoincrement;
+
+        let foreign_keys = schema
+            .columns
+            .iter()
+            .filter_map(|column| {
+                let (table, referenced) = column.references()?;
+                Some(ForeignKeyDefinition {
+                    column: column.name.into(),
+                    references_table: table.into(),
+                    references_column: referenced.into(),
+                })
+            })
+            .collect();
+
+        Self {
+            columns: schema.columns.iter().map(ColumnDefinition::from).collect(),
+            primary_key: primary_key.iter().map(|column| column.name.into()).collect(),
+            autoincrement,
+            unique: schema
+                .columns
+                .iter()
+                .filter(|column| column.unique && !column.primary_key)
+                .map(|column| column.name.into())
+                .collect(),
+            foreign_keys,
+        }
+    }
+}
+
+impl<'a> AlterTable<'a> {
+    /// `ALTER TABLE table ADD COLUMN column`.
+    pub fn add_column<T>(table: T, column: ColumnDefinition) -> Self
+    where
+        T: Into<Table<'a>>,
+    {
+        Self::new(table, AlterOperation::AddColumn(column))
+    }
+
+    /// `ALTER TABLE table DROP COLUMN column`.
+    pub fn drop_column<T, S>(table: T, column: S) -> Self
+    where
+        T: Into<Table<'a>>,
+        S: Into<Cow<'static, str>>,
+    {
+        Self::new(table, AlterOperation::DropColumn(column.into()))
+    }
+
+    /// Makes `column` nullable or `NOT NULL`, following its definition.
+    pub fn set_nullable<T>(table: T, column: ColumnDefinition) -> Self
+    where
+        T: Into<Table<'a>>,
+    {
+        Self::new(table, AlterOperation::SetNullable(column))
+    }
+
+    /// `CREATE [UNIQUE] INDEX name ON table (columns)`.
+    pub fn create_index<T, S, I, C>(table: T, name: S, columns: I, unique: bool) -> Self
+    where
+        T: Into<Table<'a>>,
+        S: Into<Cow<'static, str>>,
+        I: IntoIterator<Item = C>,
+        C: Into<Cow<'static, str>>,
+    {
+        let operation = AlterOperation::CreateIndex {
+            name: name.into(),
+            columns: columns.into_iter().map(Into::into).collect(),
+            unique,
+        };
+
+        Self::new(table, operation)
+    }
+
+    /// `CREATE TABLE table (definition)`.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// #[derive(Entity)]
+    /// #[tablename = "posts"]
+    /// struct Post {
+    ///     #[column(primary_key, autoincrement)]
+    ///     id: i64,
+    ///     #[column(foreign_key = "users.id")]
+    ///     user_id: i64,
+    ///     title: Option<String>,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let schema = Post::schema();
+    /// let (sql, _) = Sqlite::build(AlterTable::create_table(Post::table(), TableDefinition::from(&schema)))?;
+    ///
+    /// assert_eq!(
+    ///     "CREATE TABLE `posts` (`id` INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, `user_id` INTEGER NOT NULL, `title` TEXT, FOREIGN KEY (`user_id`) REFERENCES `users` (`id`))",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_table<T>(table: T, definition: TableDefinition) -> Self
+    where
+        T: Into<Table<'a>>,
+    {
+        Self::new(table, AlterOperation::CreateTable(definition))
+    }
+
+    fn new<T>(table: T, operation: AlterOperation) -> Self
+    where
+        T: Into<Table<'a>>,
+    {
+        Self {
+            table: table.into(),
+            operation,
+        }
+    }
+
+    /// The table being altered.
+    pub fn table(&self) -> &Table<'a> {
+        &self.table
+    }
+
+    pub fn operation(&self) -> &AlterOperation {
+        &self.operation
+    }
+}
+
+impl<'a> From<AlterTable<'a>> for Query<'a> {
+    fn from(alter: AlterTable<'a>) -> Self {
+        Query::AlterTable(Box::new(alter))
+    }
+}
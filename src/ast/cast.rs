@@ -0,0 +1,64 @@
+use super::{Expression, ExpressionKind, TypeFamily};
+
+/// A conversion of an expression into another type, `CAST(expr AS type)`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cast<'a> {
+    pub(crate) expression: Expression<'a>,
+    pub(crate) target: TypeFamily,
+}
+
+impl<'a> Cast<'a> {
+    pub fn expression(&self) -> &Expression<'a> {
+        &self.expression
+    }
+
+    pub fn target(&self) -> TypeFamily {
+        self.target
+    }
+}
+
+/// Converts `expression` into the type of `target`, named as the database
+/// names it.
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     code: String,
+/// # }
+/// # fn main() -> std::result::Result<(), xiayu::error::Error> {
+/// let query = Select::from_table(User::table())
+///     .so_that(cast(User::code, TypeFamily::Int).equals(42));
+/// let (sql, _) = Mysql::build(query)?;
+///
+/// assert_eq!(
+///     "SELECT `users`.* FROM `users` WHERE CAST(`users`.`code` AS SIGNED) = ?",
+///     sql
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn cast<'a, E>(expression: E, target: TypeFamily) -> Expression<'a>
+where
+    E: Into<Expression<'a>>,
+{
+    let cast = Cast {
+        expression: expression.into(),
+        target,
+    };
+
+    Expression {
+        kind: ExpressionKind::Cast(Box::new(cast)),
+        alias: None,
+    }
+}
+
+impl<'a> Expression<'a> {
+    /// Converts the expression into the type of `target`, see [`cast`].
+    pub fn cast(self, target: TypeFamily) -> Self {
+        cast(self, target)
+    }
+}
@@ -1,16 +1,22 @@
 use super::{Aliasable, Expression, ExpressionKind, Table, Value};
+use crate::error::{Error, ErrorKind};
 use std::borrow::Cow;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TypeDataLength {
     Constant(u16),
     Maximum,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TypeFamily {
     Text(Option<TypeDataLength>),
+    /// A 64-bit integer, `BIGINT`.
     Int,
+    /// A 32-bit integer, `INT`, for the integers fitting in it.
+    Int32,
     Float,
     Double,
     Boolean,
@@ -18,27 +24,103 @@ pub enum TypeFamily {
     DateTime,
     Decimal(Option<(u8, u8)>),
     Bytes(Option<TypeDataLength>),
+    Json,
 }
 
 /// A column definition.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Column<'a> {
     pub name: Cow<'static, str>,
     pub(crate) table: Option<Table<'a>>,
     pub(crate) alias: Option<Cow<'a, str>>,
     pub(crate) default: Option<DefaultValue<'a>>,
     pub(crate) type_family: Option<TypeFamily>,
+    /// The maximum length of text or bytes, from `#[column(length = ..)]`.
+    pub(crate) length: Option<usize>,
+    /// Quote the name even if not required by the quote style.
+    pub(crate) quote: bool,
+    /// Not selected by default, its values are redacted from the logs, see
+    /// `#[column(write_only)]`.
+    #[cfg_attr(feature = "ast-serde", serde(default))]
+    pub(crate) write_only: bool,
+}
+
+/// The column a parameter is bound to, as kept by the visitors next to the
+/// parameters of inserts and updates.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColumnMeta {
+    pub name: Cow<'static, str>,
+    pub length: Option<usize>,
+    /// The values are shown as `<redacted>` in the logs.
+    #[cfg_attr(feature = "ast-serde", serde(default))]
+    pub write_only: bool,
+}
+
+impl<'a> From<&Column<'a>> for ColumnMeta {
+    fn from(column: &Column<'a>) -> Self {
+        Self {
+            name: column.name.clone(),
+            length: column.length,
+            write_only: column.write_only,
+        }
+    }
 }
 
 /// Defines a default value for a `Column`.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Deserialize))]
 pub enum DefaultValue<'a> {
     /// A static value.
     Provided(Value<'a>),
+    /// A value computed when needed, the client side default of the
+    /// columns of entities.
+    #[cfg_attr(feature = "ast-serde", serde(skip_deserializing))]
+    Computed(fn() -> Value<'static>),
     /// Generated in the database.
     Generated,
 }
 
+impl<'a> DefaultValue<'a> {
+    /// The value of a provided or computed default, `None` when generated.
+    pub fn value(&self) -> Option<Value<'a>> {
+        match self {
+            DefaultValue::Provided(value) => Some(value.clone()),
+            DefaultValue::Computed(compute) => Some(compute()),
+            DefaultValue::Generated => None,
+        }
+    }
+}
+
+// Computed defaults are equal by their values, the addresses of functions
+// are not reliable.
+impl<'a> PartialEq for DefaultValue<'a> {
+    fn eq(&self, other: &DefaultValue<'_>) -> bool {
+        match (self, other) {
+            (DefaultValue::Generated, DefaultValue::Generated) => true,
+            (DefaultValue::Generated, _) | (_, DefaultValue::Generated) => false,
+            _ => self.value() == other.value(),
+        }
+    }
+}
+
+// A computed default is written as the value it computes, read back as a
+// provided one.
+#[cfg(feature = "ast-serde")]
+impl<'a> serde::Serialize for DefaultValue<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            DefaultValue::Provided(value) => serializer.serialize_newtype_variant("DefaultValue", 0, "Provided", value),
+            DefaultValue::Computed(compute) => serializer.serialize_newtype_variant("DefaultValue", 0, "Provided", &compute()),
+            DefaultValue::Generated => serializer.serialize_unit_variant("DefaultValue", 2, "Generated"),
+        }
+    }
+}
+
 impl<'a> Default for DefaultValue<'a> {
     fn default() -> Self {
         Self::Generated
@@ -66,6 +148,7 @@ impl<'a> Column<'a> {
     pub(crate) fn into_bare(self) -> Self {
         Self {
             name: self.name,
+            quote: self.quote,
             ..Default::default()
         }
     }
@@ -116,6 +199,25 @@ impl<'a> Column<'a> {
         }
     }
 
+    /// Create a column definition from a name that can not be trusted, e.g.
+    /// picked by a user. Names with control characters or any of the quotes
+    /// of the supported databases are rejected.
+    ///
+    /// ```rust
+    /// # use xiayu::ast::*;
+    /// assert!(Column::new_checked("created_at").is_ok());
+    /// assert!(Column::new_checked("x` FROM users; --").is_err());
+    /// ```
+    pub fn new_checked<S>(name: S) -> crate::Result<Self>
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        let name = name.into();
+        check_identifier(&name)?;
+
+        Ok(Column::new(name))
+    }
+
     /// Include the table name in the column expression.
     pub fn table<T>(mut self, table: T) -> Self
     where
@@ -160,3 +262,29 @@ where
         column
     }
 }
+
+/// Checks an identifier coming from untrusted input, it must not be empty or
+/// contain control characters or the delimiters of any of the databases.
+pub(crate) fn check_identifier(identifier: &str) -> crate::Result<()> {
+    let reason = if identifier.is_empty() {
+        Some("the identifier is empty")
+    } else if identifier.chars().any(char::is_control) {
+        Some("control characters are not allowed")
+    } else if identifier.contains(&['"', '`', '[', ']'][..]) {
+        Some("quotes are not allowed")
+    } else {
+        None
+    };
+
+    match reason {
+        Some(reason) => {
+            let kind = ErrorKind::InvalidIdentifier {
+                identifier: identifier.to_string(),
+                reason,
+            };
+
+            Err(Error::builder(kind).build())
+        }
+        None => Ok(()),
+    }
+}
@@ -1,9 +1,10 @@
 use super::ExpressionKind;
-use crate::ast::{Column, ConditionTree, Expression};
+use crate::ast::{Column, ConditionTree, Expression, Row, Select, SelectQuery, Value, Values};
 use std::borrow::Cow;
 
 /// For modeling comparison expressions.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Compare<'a> {
     /// `left = right`
     Equals(Box<Expression<'a>>, Box<Expression<'a>>),
@@ -37,6 +38,10 @@ pub enum Compare<'a> {
     Null(Box<Expression<'a>>),
     /// `value IS NOT NULL`
     NotNull(Box<Expression<'a>>),
+    /// `left IS NOT DISTINCT FROM right`, equality treating `NULL`s as equal
+    NotDistinctFrom(Box<Expression<'a>>, Box<Expression<'a>>),
+    /// `left IS DISTINCT FROM right`
+    DistinctFrom(Box<Expression<'a>>, Box<Expression<'a>>),
     /// `value` BETWEEN `left` AND `right`
     Between(
         Box<Expression<'a>>,
@@ -49,6 +54,10 @@ pub enum Compare<'a> {
         Box<Expression<'a>>,
         Box<Expression<'a>>,
     ),
+    /// `EXISTS (SELECT ..)`
+    Exists(Box<SelectQuery<'a>>),
+    /// `NOT EXISTS (SELECT ..)`
+    NotExists(Box<SelectQuery<'a>>),
     /// Raw comparator, allows to use an operator `left <raw> right` as is,
     /// without visitor transformation in between.
     Raw(Box<Expression<'a>>, Cow<'a, str>, Box<Expression<'a>>),
@@ -64,6 +73,7 @@ pub enum Compare<'a> {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JsonCompare<'a> {
     ArrayContains(Box<Expression<'a>>, Box<Expression<'a>>),
     ArrayNotContains(Box<Expression<'a>>, Box<Expression<'a>>),
@@ -75,6 +85,7 @@ pub enum JsonCompare<'a> {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JsonType {
     Array,
     Object,
@@ -134,6 +145,7 @@ impl<'a> Compare<'a> {
                 alias: None,
                 database: None,
                 index_definitions: Vec::new(),
+                column_aliases: Vec::new(),
             };
             let base_select = super::Select::from_table(cte_table)
                 .column(super::column::Column::new(selected_columns.remove(0)));
@@ -241,11 +253,103 @@ impl<'a> Compare<'a> {
 
                 either::Either::Right((cond, ctes))
             }
+            Self::Exists(selection) => {
+                let (selection, ctes) = selection.convert_tuple_selects_to_ctes(level);
+                either::Either::Right((Self::Exists(Box::new(selection)), ctes))
+            }
+            Self::NotExists(selection) => {
+                let (selection, ctes) = selection.convert_tuple_selects_to_ctes(level);
+                either::Either::Right((Self::NotExists(Box::new(selection)), ctes))
+            }
             _ => either::Either::Left(self),
         }
     }
 }
 
+/// True when `selection` has at least one row, `EXISTS (SELECT ..)`. The
+/// selection can refer to the columns of the tables of the outer query.
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// # use xiayu::visitors::{Visitor, Sqlite};
+/// #[derive(Debug, Entity)]
+/// #[tablename = "users"]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i64,
+/// }
+///
+/// #[derive(Debug, Entity)]
+/// #[tablename = "posts"]
+/// struct Post {
+///     #[column(primary_key)]
+///     id: i64,
+///     user_id: i64,
+///     published: bool,
+/// }
+///
+/// # fn main() -> xiayu::Result<()> {
+/// let posts = Select::from_table(Post::table())
+///     .value(1.raw())
+///     .so_that(Post::user_id.equals(User::id).and(Post::published.equals(true)));
+/// let query = Select::from_table(User::table()).so_that(exists(posts));
+/// let (sql, _) = Sqlite::build(query)?;
+///
+/// assert_eq!(
+///     "SELECT `users`.* FROM `users` WHERE EXISTS (SELECT 1 FROM `posts` WHERE (`posts`.`user_id` = `users`.`id` AND `posts`.`published` = ?))",
+///     sql
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn exists<'a, S>(selection: S) -> Compare<'a>
+where
+    S: Into<SelectQuery<'a>>,
+{
+    Compare::Exists(Box::new(selection.into()))
+}
+
+/// True when `selection` has no rows, `NOT EXISTS (SELECT ..)`.
+pub fn not_exists<'a, S>(selection: S) -> Compare<'a>
+where
+    S: Into<SelectQuery<'a>>,
+{
+    Compare::NotExists(Box::new(selection.into()))
+}
+
+/// The right side of an `IN` on a column of an entity, see
+/// [`ColumnOptions::in_selection`](crate::prelude::ColumnOptions::in_selection):
+/// a [`Values`], a [`Select`], or the values of an iterator.
+pub trait IntoSelection<'a> {
+    fn into_selection(self) -> Expression<'a>;
+}
+
+impl<'a> IntoSelection<'a> for Values<'a> {
+    fn into_selection(self) -> Expression<'a> {
+        self.into()
+    }
+}
+
+impl<'a> IntoSelection<'a> for Select<'a> {
+    fn into_selection(self) -> Expression<'a> {
+        self.into()
+    }
+}
+
+impl<'a, I> IntoSelection<'a> for I
+where
+    I: IntoIterator,
+    I::Item: Into<Value<'a>>,
+{
+    fn into_selection(self) -> Expression<'a> {
+        let mut row = Row::new();
+        for value in self {
+            row.push(value.into());
+        }
+        row.into()
+    }
+}
+
 impl<'a> From<Compare<'a>> for ConditionTree<'a> {
     fn from(cmp: Compare<'a>) -> Self {
         ConditionTree::single(Expression::from(cmp))
@@ -272,7 +376,7 @@ pub trait Comparable<'a> {
     /// struct User {
     ///   foo: String,
     /// }
-    /// let query = Select::from_table(User).so_that(User::foo.equals("bar"));
+    /// let query = Select::from_table(User::table()).so_that(User::foo.equals("bar"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` = ?", sql);
@@ -294,15 +398,15 @@ pub trait Comparable<'a> {
     ///
     /// ```rust
     /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// #[derive(Entity)]
     /// struct User {
     ///   foo: String,
     /// }
-    /// let query = Select::from_table(User).so_that(User::foo.not_equals("bar"));
+    /// let query = Select::from_table(User::table()).so_that(User::foo.not_equals("bar"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` <> ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` <> ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -320,16 +424,16 @@ pub trait Comparable<'a> {
     /// Tests if the left side is smaller than the right side.
     ///
     /// ```rust
-    /// # fn main() -> Result<(), xiayu::error::Error> {
     /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
     /// #[derive(Entity)]
     /// struct User {
     ///   foo: i32,
     /// }
-    /// let query = Select::from_table(User).so_that(User::foo.less_than(10));
+    /// let query = Select::from_table(User::table()).so_that(User::foo.less_than(10));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` < ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` < ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -348,15 +452,15 @@ pub trait Comparable<'a> {
     ///
     /// ```rust
     /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// #[derive(Entity)]
     /// struct User {
     ///   foo: i32,
     /// }
-    /// let query = Select::from_table(User).so_that(User::foo.less_than_or_equals(10));
+    /// let query = Select::from_table(User::table()).so_that(User::foo.less_than_or_equals(10));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` <= ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` <= ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -375,15 +479,15 @@ pub trait Comparable<'a> {
     ///
     /// ```rust
     /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// #[derive(Entity)]
     /// struct User {
     ///   foo: i32,
     /// }
-    /// let query = Select::from_table(User).so_that(User::foo.greater_than(10));
+    /// let query = Select::from_table(User::table()).so_that(User::foo.greater_than(10));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` > ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` > ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -401,12 +505,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side is bigger than the right side or the same.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".greater_than_or_equals(10));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.greater_than_or_equals(10));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` >= ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` >= ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -424,12 +534,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side is included in the right side collection.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".in_selection(vec![1, 2]));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.in_selection(vec![1, 2]));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` IN (?,?)", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` IN (?,?)", sql);
     /// assert_eq!(vec![
     ///     Value::from(1),
     ///     Value::from(2),
@@ -444,12 +560,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side is not included in the right side collection.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".not_in_selection(vec![1, 2]));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.not_in_selection(vec![1, 2]));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` NOT IN (?,?)", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` NOT IN (?,?)", sql);
     ///
     /// assert_eq!(vec![
     ///     Value::from(1),
@@ -465,12 +587,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side includes the right side string.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".like("bar"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.like("bar"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` LIKE ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` LIKE ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -488,12 +616,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side does not include the right side string.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".not_like("bar"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.not_like("bar"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` NOT LIKE ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` NOT LIKE ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -511,12 +645,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side starts with the right side string.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".begins_with("bar"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.begins_with("bar"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` LIKE ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` LIKE ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -534,12 +674,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side doesn't start with the right side string.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".not_begins_with("bar"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.not_begins_with("bar"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` NOT LIKE ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` NOT LIKE ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -557,12 +703,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side ends into the right side string.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".ends_into("bar"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.ends_into("bar"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` LIKE ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` LIKE ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -580,12 +732,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side does not end into the right side string.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".not_ends_into("bar"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.not_ends_into("bar"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` NOT LIKE ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` NOT LIKE ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -603,12 +761,18 @@ pub trait Comparable<'a> {
     /// Tests if the left side is `NULL`.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".is_null());
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: Option<i32>,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.is_null());
     /// let (sql, _) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` IS NULL", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` IS NULL", sql);
     /// # Ok(())
     /// # }
     /// ```
@@ -618,27 +782,105 @@ pub trait Comparable<'a> {
     /// Tests if the left side is not `NULL`.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".is_not_null());
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: Option<i32>,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.is_not_null());
     /// let (sql, _) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` IS NOT NULL", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` IS NOT NULL", sql);
     /// # Ok(())
     /// # }
     /// ```
     #[allow(clippy::wrong_self_convention)]
     fn is_not_null(self) -> Compare<'a>;
 
+    /// Tests if both sides are the same value, considering two `NULL`s equal
+    /// and `NULL` different from any other value, where `equals` would be
+    /// `NULL` itself.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Mysql, Sqlite}};
+    /// # #[cfg(feature = "postgres")]
+    /// # use xiayu::visitors::Postgres;
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     nickname: Option<String>,
+    /// #     name: Option<String>,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::nickname.is_not_distinct_from(User::name));
+    ///
+    /// # #[cfg(feature = "postgres")]
+    /// # {
+    /// let (sql, _) = Postgres::build(query.clone())?;
+    /// assert_eq!(
+    ///     r#"SELECT "users".* FROM "users" WHERE "users"."nickname" IS NOT DISTINCT FROM "users"."name""#,
+    ///     sql
+    /// );
+    /// # }
+    ///
+    /// let (sql, _) = Mysql::build(query.clone())?;
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`nickname` <=> `users`.`name`", sql);
+    ///
+    /// let (sql, _) = Sqlite::build(query)?;
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`nickname` IS `users`.`name`", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[allow(clippy::wrong_self_convention)]
+    fn is_not_distinct_from<T>(self, comparison: T) -> Compare<'a>
+    where
+        T: Into<Expression<'a>>;
+
+    /// Tests if both sides are different, considering two `NULL`s equal and
+    /// `NULL` different from any other value.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     nickname: Option<String>,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let nickname: Option<&str> = None;
+    /// let query = Select::from_table(User::table()).so_that(User::nickname.is_distinct_from(nickname));
+    /// let (sql, params) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`nickname` IS NOT ?", sql);
+    /// assert_eq!(vec![Value::Text(None)], params);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[allow(clippy::wrong_self_convention)]
+    fn is_distinct_from<T>(self, comparison: T) -> Compare<'a>
+    where
+        T: Into<Expression<'a>>;
+
     /// Tests if the value is between two given values.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".between(420, 666));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.between(420, 666));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` BETWEEN ? AND ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` BETWEEN ? AND ?", sql);
     ///
     /// assert_eq!(vec![
     ///     Value::from(420),
@@ -655,12 +897,18 @@ pub trait Comparable<'a> {
     /// Tests if the value is not between two given values.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".not_between(420, 666));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.not_between(420, 666));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` NOT BETWEEN ? AND ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` NOT BETWEEN ? AND ?", sql);
     ///
     /// assert_eq!(vec![
     ///     Value::from(420),
@@ -677,12 +925,18 @@ pub trait Comparable<'a> {
     /// Tests if the JSON array contains a value.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Mysql}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("json".json_array_contains("1"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     json: serde_json::Value,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::json.json_array_contains("1"));
     /// let (sql, params) = Mysql::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE JSON_CONTAINS(`json`, ?)", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE JSON_CONTAINS(`users`.`json`, ?)", sql);
     ///
     /// assert_eq!(vec![Value::from("1")], params);
     /// # Ok(())
@@ -696,12 +950,18 @@ pub trait Comparable<'a> {
     /// Tests if the JSON array does not contain a value.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Mysql}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("json".json_array_not_contains("1"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     json: serde_json::Value,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::json.json_array_not_contains("1"));
     /// let (sql, params) = Mysql::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE JSON_CONTAINS(`json`, ?) = FALSE", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE JSON_CONTAINS(`users`.`json`, ?) = FALSE", sql);
     ///
     /// assert_eq!(vec![Value::from("1")], params);
     /// # Ok(())
@@ -715,12 +975,18 @@ pub trait Comparable<'a> {
     /// Tests if the JSON array starts with a value.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Mysql}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("json".json_array_begins_with("1"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     json: serde_json::Value,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::json.json_array_begins_with("1"));
     /// let (sql, params) = Mysql::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE JSON_EXTRACT(`json`, ?) = CAST(? AS JSON)", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE JSON_EXTRACT(`users`.`json`, ?) = CAST(? AS JSON)", sql);
     ///
     /// assert_eq!(vec![
     ///     Value::from("$[0]"),
@@ -737,12 +1003,18 @@ pub trait Comparable<'a> {
     /// Tests if the JSON array does not start with a value.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Mysql}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("json".json_array_not_begins_with("1"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     json: serde_json::Value,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::json.json_array_not_begins_with("1"));
     /// let (sql, params) = Mysql::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE JSON_EXTRACT(`json`, ?) <> CAST(? AS JSON)", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE JSON_EXTRACT(`users`.`json`, ?) <> CAST(? AS JSON)", sql);
     ///
     /// assert_eq!(vec![
     ///     Value::from("$[0]"),
@@ -759,14 +1031,20 @@ pub trait Comparable<'a> {
     /// Tests if the JSON array ends with a value.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Mysql}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("json".json_array_ends_into("1"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     json: serde_json::Value,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::json.json_array_ends_into("1"));
     /// let (sql, params) = Mysql::build(query)?;
     ///
     /// assert_eq!(
     ///   "SELECT `users`.* FROM `users` WHERE \
-    ///   JSON_EXTRACT(`json`, CONCAT(\'$[\', JSON_LENGTH(`json`) - 1, \']\')) = CAST(? AS JSON)", sql);
+    ///   JSON_EXTRACT(`users`.`json`, CONCAT(\'$[\', JSON_LENGTH(`users`.`json`) - 1, \']\')) = CAST(? AS JSON)", sql);
     ///
     /// assert_eq!(vec![Value::from("1")], params);
     /// # Ok(())
@@ -780,14 +1058,20 @@ pub trait Comparable<'a> {
     /// Tests if the JSON array does not end with a value.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Mysql}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("json".json_array_not_ends_into("1"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     json: serde_json::Value,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::json.json_array_not_ends_into("1"));
     /// let (sql, params) = Mysql::build(query)?;
     ///
     /// assert_eq!(
     ///   "SELECT `users`.* FROM `users` WHERE \
-    ///   JSON_EXTRACT(`json`, CONCAT(\'$[\', JSON_LENGTH(`json`) - 1, \']\')) <> CAST(? AS JSON)", sql);
+    ///   JSON_EXTRACT(`users`.`json`, CONCAT(\'$[\', JSON_LENGTH(`users`.`json`) - 1, \']\')) <> CAST(? AS JSON)", sql);
     ///
     /// assert_eq!(vec![Value::from("1")], params);
     /// # Ok(())
@@ -801,12 +1085,18 @@ pub trait Comparable<'a> {
     /// Tests if the JSON value is of a certain type.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Mysql}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("json".json_type_equals(JsonType::Array));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     json: serde_json::Value,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::json.json_type_equals(JsonType::Array));
     /// let (sql, params) = Mysql::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE (JSON_TYPE(`json`) = ?)", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE (JSON_TYPE(`users`.`json`) = ?)", sql);
     ///
     /// assert_eq!(vec![Value::from("ARRAY")], params);
     /// # Ok(())
@@ -822,7 +1112,7 @@ pub trait Comparable<'a> {
     /// ```rust
     /// # use entities::*;
     /// # use xiayu::{prelude::*, visitors::{Visitor, Postgres}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// let search: Expression = text_search(&[Recipe::name, Recipe::ingredients]).into();
     /// let query = Select::from_table(Recipe::table()).so_that(search.matches("chicken"));
     /// let (sql, params) = Postgres::build(query)?;
@@ -846,7 +1136,7 @@ pub trait Comparable<'a> {
     /// ```rust
     /// # use entities::*;
     /// # use xiayu::{prelude::*, visitors::{Visitor, Postgres}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// let search: Expression = text_search(&[Recipe::name, Recipe::ingredients]).into();
     /// let query = Select::from_table(Recipe::table()).so_that(search.not_matches("chicken"));
     /// let (sql, params) = Postgres::build(query)?;
@@ -869,11 +1159,17 @@ pub trait Comparable<'a> {
     ///
     /// ```rust
     /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// # }
     /// # fn main() -> Result<()> {
-    /// let query = Select::from_table("users").so_that("foo".compare_raw("ILIKE", "%bar%"));
+    /// let query = Select::from_table(User::table()).so_that(User::foo.compare_raw("ILIKE", "%bar%"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` ILIKE ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` ILIKE ?", sql);
     ///
     /// assert_eq!(vec![
     ///     Value::from("%bar%"),
@@ -1032,6 +1328,26 @@ where
         val.is_not_null()
     }
 
+    #[allow(clippy::wrong_self_convention)]
+    fn is_not_distinct_from<T>(self, comparison: T) -> Compare<'a>
+    where
+        T: Into<Expression<'a>>,
+    {
+        let col: Column<'a> = self.into();
+        let val: Expression<'a> = col.into();
+        val.is_not_distinct_from(comparison)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn is_distinct_from<T>(self, comparison: T) -> Compare<'a>
+    where
+        T: Into<Expression<'a>>,
+    {
+        let col: Column<'a> = self.into();
+        let val: Expression<'a> = col.into();
+        val.is_distinct_from(comparison)
+    }
+
     fn between<T, V>(self, left: T, right: V) -> Compare<'a>
     where
         T: Into<Expression<'a>>,
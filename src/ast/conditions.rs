@@ -2,6 +2,7 @@ use crate::ast::*;
 
 /// Tree structures and leaves for condition building.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConditionTree<'a> {
     /// `(left_expression AND right_expression)`
     And(Vec<Expression<'a>>),
@@ -108,6 +109,61 @@ impl<'a> ConditionTree<'a> {
         ConditionTree::Single(Box::new(left.into()))
     }
 
+    /// True when every expression is true, as a single `AND` of them all.
+    /// Without expressions the condition is always true.
+    ///
+    /// ```rust
+    /// # use entities::Dog;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let age: Option<i32> = Some(3);
+    /// let slave_id: Option<i32> = None;
+    ///
+    /// let mut filters = Vec::new();
+    /// if let Some(age) = age {
+    ///     filters.push(Dog::age.equals(age));
+    /// }
+    /// if let Some(slave_id) = slave_id {
+    ///     filters.push(Dog::slave_id.equals(slave_id));
+    /// }
+    ///
+    /// let query = Select::from_table(Dog::table()).so_that(ConditionTree::all_of(filters));
+    /// let (sql, _) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!("SELECT `dogs`.* FROM `dogs` WHERE `dogs`.`age` = ?", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all_of<I, E>(expressions: I) -> ConditionTree<'a>
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Expression<'a>>,
+    {
+        let mut expressions: Vec<Expression<'a>> = expressions.into_iter().map(Into::into).collect();
+
+        match expressions.len() {
+            0 => ConditionTree::NoCondition,
+            1 => ConditionTree::single(expressions.pop().unwrap()),
+            _ => ConditionTree::And(expressions),
+        }
+    }
+
+    /// True when one of the expressions is true, as a single `OR` of them
+    /// all. Without expressions the condition is always false.
+    pub fn any_of<I, E>(expressions: I) -> ConditionTree<'a>
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Expression<'a>>,
+    {
+        let mut expressions: Vec<Expression<'a>> = expressions.into_iter().map(Into::into).collect();
+
+        match expressions.len() {
+            0 => ConditionTree::NegativeCondition,
+            1 => ConditionTree::single(expressions.pop().unwrap()),
+            _ => ConditionTree::Or(expressions),
+        }
+    }
+
     /// Inverts the entire condition tree if condition is met.
     pub fn invert_if(self, invert: bool) -> ConditionTree<'a> {
         if invert {
@@ -116,6 +172,183 @@ impl<'a> ConditionTree<'a> {
             self
         }
     }
+
+    /// An equivalent tree without the clauses which do not change the
+    /// result: nested `AND`s and `OR`s are flattened, conditions always true
+    /// or always false are dropped or decide the whole branch, `NOT NOT x`
+    /// becomes `x`, and the `IN` lists of a column in the same `OR` are
+    /// merged into one.
+    ///
+    /// The parameters are collected while rendering, so they follow the
+    /// order of the simplified query.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// #[derive(Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i32,
+    ///     age: i32,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let conditions = ConditionTree::NoCondition
+    ///     .and(User::age.greater_than(18).and(ConditionTree::not(ConditionTree::not(User::id.not_equals(1)))))
+    ///     .and(User::id.in_selection(vec![2, 3]).or(ConditionTree::NegativeCondition).or(User::id.in_selection(vec![4])));
+    ///
+    /// let (sql, params) = Sqlite::build(Select::from_table(User::table()).so_that(conditions.simplify()))?;
+    ///
+    /// assert_eq!(
+    ///     "SELECT `users`.* FROM `users` WHERE (`users`.`age` > ? AND `users`.`id` <> ? AND `users`.`id` IN (?,?,?))",
+    ///     sql
+    /// );
+    /// assert_eq!(vec![Value::from(18), Value::from(1), Value::from(2), Value::from(3), Value::from(4)], params);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn simplify(self) -> ConditionTree<'a> {
+        match self {
+            Self::And(expressions) => {
+                let mut operands = Vec::with_capacity(expressions.len());
+                for expression in expressions {
+                    match tree_of(expression).simplify() {
+                        Self::NoCondition => (),
+                        Self::NegativeCondition => return Self::NegativeCondition,
+                        Self::And(nested) => operands.extend(nested),
+                        tree => operands.push(operand_of(tree)),
+                    }
+                }
+
+                match operands.len() {
+                    0 => Self::NoCondition,
+                    1 => tree_of(operands.pop().unwrap()),
+                    _ => Self::And(operands),
+                }
+            }
+            Self::Or(expressions) => {
+                let mut operands = Vec::with_capacity(expressions.len());
+                for expression in expressions {
+                    match tree_of(expression).simplify() {
+                        Self::NegativeCondition => (),
+                        Self::NoCondition => return Self::NoCondition,
+                        Self::Or(nested) => operands.extend(nested),
+                        tree => operands.push(operand_of(tree)),
+                    }
+                }
+
+                let mut operands = merge_in_lists(operands);
+                match operands.len() {
+                    0 => Self::NegativeCondition,
+                    1 => tree_of(operands.pop().unwrap()),
+                    _ => Self::Or(operands),
+                }
+            }
+            Self::Not(expression) => match tree_of(*expression).simplify() {
+                Self::NoCondition => Self::NegativeCondition,
+                Self::NegativeCondition => Self::NoCondition,
+                Self::Not(expression) => tree_of(*expression),
+                tree => Self::Not(Box::new(operand_of(tree))),
+            },
+            Self::Single(expression) => match tree_of(*expression) {
+                Self::Single(expression) => simplify_leaf(*expression),
+                tree => tree.simplify(),
+            },
+            tree => tree,
+        }
+    }
+}
+
+/// Simplifies the conditions of a statement, which have none left when they
+/// are always true.
+pub(crate) fn simplify_conditions(conditions: Option<ConditionTree<'_>>) -> Option<ConditionTree<'_>> {
+    match conditions.map(ConditionTree::simplify) {
+        Some(ConditionTree::NoCondition) => None,
+        conditions => conditions,
+    }
+}
+
+/// The tree of a condition, a leaf when it is not one.
+fn tree_of(expression: Expression<'_>) -> ConditionTree<'_> {
+    match expression {
+        Expression {
+            kind: ExpressionKind::ConditionTree(tree),
+            alias: None,
+        } => tree,
+        expression => ConditionTree::Single(Box::new(expression)),
+    }
+}
+
+fn operand_of(tree: ConditionTree<'_>) -> Expression<'_> {
+    match tree {
+        ConditionTree::Single(expression) => *expression,
+        tree => Expression::from(tree),
+    }
+}
+
+/// `x IN ()` is always false and `x NOT IN ()` always true.
+fn simplify_leaf(expression: Expression<'_>) -> ConditionTree<'_> {
+    match &expression.kind {
+        ExpressionKind::Compare(Compare::In(_, right)) if is_empty_row(right) => ConditionTree::NegativeCondition,
+        ExpressionKind::Compare(Compare::NotIn(_, right)) if is_empty_row(right) => ConditionTree::NoCondition,
+        _ => ConditionTree::Single(Box::new(expression)),
+    }
+}
+
+fn is_empty_row(expression: &Expression<'_>) -> bool {
+    matches!(&expression.kind, ExpressionKind::Row(row) if row.is_empty())
+}
+
+/// Merges `x IN (a) OR x IN (b)` into `x IN (a, b)`, where the first of the
+/// lists was.
+fn merge_in_lists(operands: Vec<Expression<'_>>) -> Vec<Expression<'_>> {
+    let mut merged: Vec<Expression<'_>> = Vec::with_capacity(operands.len());
+
+    for operand in operands {
+        let (left, values) = match in_list(operand) {
+            Ok(list) => list,
+            Err(operand) => {
+                merged.push(operand);
+                continue;
+            }
+        };
+
+        let list = merged.iter_mut().find_map(|expression| match expression {
+            Expression {
+                kind: ExpressionKind::Compare(Compare::In(other, right)),
+                alias: None,
+            } if *other == left => match &mut right.kind {
+                ExpressionKind::Row(row) => Some(row),
+                _ => None,
+            },
+            _ => None,
+        });
+
+        match list {
+            Some(row) => row.values.extend(values),
+            None => merged.push(Expression::from(Compare::In(left, Box::new(Row { values }.into())))),
+        }
+    }
+
+    merged
+}
+
+/// The column and the values of `x IN (..)`, the expression back otherwise.
+#[allow(clippy::type_complexity)]
+fn in_list(expression: Expression<'_>) -> Result<(Box<Expression<'_>>, Vec<Expression<'_>>), Expression<'_>> {
+    match expression {
+        Expression {
+            kind: ExpressionKind::Compare(Compare::In(left, right)),
+            alias: None,
+        } if !matches!(left.kind, ExpressionKind::Row(_)) => match *right {
+            Expression {
+                kind: ExpressionKind::Row(row),
+                alias: None,
+            } => Ok((left, row.values)),
+            right => Err(Compare::In(left, Box::new(right)).into()),
+        },
+        expression => Err(expression),
+    }
 }
 
 impl<'a> Default for ConditionTree<'a> {
@@ -143,3 +376,89 @@ impl<'a> From<Select<'a>> for ConditionTree<'a> {
         ConditionTree::single(exp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Entity)]
+    #[tablename = "foo"]
+    struct Foo {
+        bar: i32,
+        baz: i32,
+    }
+
+    #[test]
+    fn test_constant_branches_are_folded() {
+        let tree = ConditionTree::NoCondition.and(Foo::bar.equals(1));
+        assert_eq!(ConditionTree::single(Foo::bar.equals(1)), tree.simplify());
+
+        let tree = ConditionTree::NegativeCondition.and(Foo::bar.equals(1));
+        assert_eq!(ConditionTree::NegativeCondition, tree.simplify());
+
+        let tree = Foo::bar.equals(1).or(ConditionTree::NoCondition);
+        assert_eq!(ConditionTree::NoCondition, tree.simplify());
+
+        let tree = ConditionTree::not(ConditionTree::NegativeCondition.or(Foo::bar.in_selection(Vec::<i32>::new())));
+        assert_eq!(ConditionTree::NoCondition, tree.simplify());
+
+        assert_eq!(ConditionTree::NoCondition, ConditionTree::And(Vec::new()).simplify());
+        assert_eq!(ConditionTree::NegativeCondition, ConditionTree::Or(Vec::new()).simplify());
+    }
+
+    #[test]
+    fn test_nested_trees_are_flattened() {
+        let tree = Foo::bar
+            .equals(1)
+            .and(Foo::baz.equals(2).and(ConditionTree::single(Foo::bar.less_than(3))))
+            .or(Foo::baz.equals(4).or(Foo::baz.equals(5)));
+
+        let expected = ConditionTree::Or(vec![
+            ConditionTree::And(vec![
+                Foo::bar.equals(1).into(),
+                Foo::baz.equals(2).into(),
+                Foo::bar.less_than(3).into(),
+            ])
+            .into(),
+            Foo::baz.equals(4).into(),
+            Foo::baz.equals(5).into(),
+        ]);
+
+        assert_eq!(expected, tree.simplify());
+    }
+
+    #[test]
+    fn test_double_negation_is_removed() {
+        let tree = ConditionTree::not(ConditionTree::not(Foo::bar.equals(1).and(Foo::baz.equals(2))));
+        assert_eq!(Foo::bar.equals(1).and(Foo::baz.equals(2)), tree.simplify());
+
+        let tree = ConditionTree::not(ConditionTree::not(ConditionTree::not(Foo::bar.equals(1))));
+        assert_eq!(ConditionTree::not(Foo::bar.equals(1)), tree.simplify());
+    }
+
+    #[test]
+    fn test_in_lists_of_a_column_are_merged() {
+        let tree = Foo::bar
+            .in_selection(vec![1, 2])
+            .or(Foo::baz.in_selection(vec![3]))
+            .or(Foo::bar.in_selection(vec![4]));
+
+        let expected = Foo::bar.in_selection(vec![1, 2, 4]).or(Foo::baz.in_selection(vec![3]));
+        assert_eq!(expected, tree.simplify());
+
+        // Only a disjunction is a union of the lists.
+        let tree = Foo::bar.in_selection(vec![1, 2]).and(Foo::bar.in_selection(vec![2, 3]));
+        assert_eq!(tree.clone(), tree.simplify());
+    }
+
+    #[test]
+    fn test_statements_without_conditions_left() {
+        let query = Query::from(Select::from_table(Foo::table()).so_that(ConditionTree::NoCondition.and(ConditionTree::NoCondition)));
+
+        match query.simplify() {
+            Query::Select(select) => assert_eq!(None, select.conditions),
+            query => panic!("unexpected query {:?}", query),
+        }
+    }
+}
@@ -5,14 +5,23 @@ pub trait Conjunctive<'a> {
     /// Builds an `AND` condition having `self` as the left leaf and `other` as the right.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
+    /// # use xiayu::prelude::*;
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// #     wtf: i32,
+    /// # }
+    /// # fn main() {
     /// assert_eq!(
-    ///     "foo".equals("bar").and("wtf".less_than(3)),
+    ///     User::foo.equals("bar").and(User::wtf.less_than(3)),
     ///     ConditionTree::And(vec![
-    ///         Expression::from("foo".equals("bar")),
-    ///         Expression::from("wtf".less_than(3))
+    ///         Expression::from(User::foo.equals("bar")),
+    ///         Expression::from(User::wtf.less_than(3))
     ///     ])
     /// )
+    /// # }
     /// ```
     fn and<E>(self, other: E) -> ConditionTree<'a>
     where
@@ -21,14 +30,23 @@ pub trait Conjunctive<'a> {
     /// Builds an `OR` condition having `self` as the left leaf and `other` as the right.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
+    /// # use xiayu::prelude::*;
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// #     wtf: i32,
+    /// # }
+    /// # fn main() {
     /// assert_eq!(
-    ///     "foo".equals("bar").or("wtf".less_than(3)),
+    ///     User::foo.equals("bar").or(User::wtf.less_than(3)),
     ///     ConditionTree::Or(vec![
-    ///         Expression::from("foo".equals("bar")),
-    ///         Expression::from("wtf".less_than(3))
+    ///         Expression::from(User::foo.equals("bar")),
+    ///         Expression::from(User::wtf.less_than(3))
     ///     ])
     /// )
+    /// # }
     /// ```
     fn or<E>(self, other: E) -> ConditionTree<'a>
     where
@@ -37,11 +55,19 @@ pub trait Conjunctive<'a> {
     /// Builds a `NOT` condition having `self` as the condition.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
+    /// # use xiayu::prelude::*;
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// # }
+    /// # fn main() {
     /// assert_eq!(
-    ///     "foo".equals("bar").not(),
-    ///     ConditionTree::not("foo".equals("bar"))
+    ///     User::foo.equals("bar").not(),
+    ///     ConditionTree::not(User::foo.equals("bar"))
     /// )
+    /// # }
     /// ```
     fn not(self) -> ConditionTree<'a>;
 }
@@ -9,6 +9,7 @@ use super::{Column, SelectQuery};
 ///
 /// [`Select#with`]: struct.Select.html#method.with
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CommonTableExpression<'a> {
     pub(crate) identifier: Cow<'a, str>,
     pub(crate) columns: Vec<Column<'a>>,
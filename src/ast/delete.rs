@@ -1,10 +1,16 @@
 use crate::ast::*;
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 /// A builder for a `DELETE` statement.
 pub struct Delete<'a> {
     pub(crate) table: Table<'a>,
+    pub(crate) using: Vec<Table<'a>>,
     pub(crate) conditions: Option<ConditionTree<'a>>,
+    #[cfg_attr(feature = "ast-serde", serde(default))]
+    pub(crate) ordering: Ordering<'a>,
+    #[cfg_attr(feature = "ast-serde", serde(default))]
+    pub(crate) limit: Option<Value<'a>>,
 }
 
 impl<'a> From<Delete<'a>> for Query<'a> {
@@ -17,9 +23,10 @@ impl<'a> Delete<'a> {
     /// Creates a new `DELETE` statement for the given table.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Delete::from_table("users");
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Delete::from_table(User::table());
     /// let (sql, _) = Sqlite::build(query)?;
     ///
     /// assert_eq!("DELETE FROM `users`", sql);
@@ -32,7 +39,10 @@ impl<'a> Delete<'a> {
     {
         Self {
             table: table.into(),
+            using: Vec::new(),
             conditions: None,
+            ordering: Ordering::default(),
+            limit: None,
         }
     }
 
@@ -40,12 +50,18 @@ impl<'a> Delete<'a> {
     /// [Comparable](trait.Comparable.html#required-methods) for more examples.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Delete::from_table("users").so_that("bar".equals(false));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     bar: bool,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Delete::from_table(User::table()).so_that(User::bar.equals(false));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("DELETE FROM `users` WHERE `bar` = ?", sql);
+    /// assert_eq!("DELETE FROM `users` WHERE `users`.`bar` = ?", sql);
     /// assert_eq!(vec![Value::boolean(false)], params);
     /// # Ok(())
     /// # }
@@ -57,4 +73,143 @@ impl<'a> Delete<'a> {
         self.conditions = Some(conditions.into());
         self
     }
+
+    /// Deletes the rows matching the rows of another table, joined with the
+    /// `WHERE` conditions. PostgreSQL takes the tables in `USING`, MySQL and
+    /// SQL Server in the `FROM` of a multi-table delete, and SQLite in an
+    /// `EXISTS` subquery.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite, Mysql}};
+    /// #[derive(Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i32,
+    ///     banned: bool,
+    /// }
+    ///
+    /// #[derive(Entity)]
+    /// #[tablename = "posts"]
+    /// struct Post {
+    ///     #[column(primary_key)]
+    ///     id: i32,
+    ///     user_id: i32,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Delete::from_table(Post::table())
+    ///     .using(User::table())
+    ///     .so_that(Post::user_id.equals(User::id).and(User::banned.equals(true)));
+    ///
+    /// let (sql, _) = Mysql::build(query.clone())?;
+    /// assert_eq!(
+    ///     "DELETE `posts` FROM `posts`, `users` WHERE (`posts`.`user_id` = `users`.`id` AND `users`.`banned` = ?)",
+    ///     sql
+    /// );
+    ///
+    /// let (sql, _) = Sqlite::build(query)?;
+    /// assert_eq!(
+    ///     "DELETE FROM `posts` WHERE EXISTS (SELECT 1 FROM `users` WHERE (`posts`.`user_id` = `users`.`id` AND `users`.`banned` = ?))",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn using<T>(mut self, table: T) -> Self
+    where
+        T: Into<Table<'a>>,
+    {
+        self.using.push(table.into());
+        self
+    }
+
+    /// Deletes at most `limit` rows, the first ones in the order given with
+    /// [`order_by`](Delete::order_by). MySQL limits the delete itself, SQL
+    /// Server deletes through a `TOP` common table expression, and SQLite and
+    /// PostgreSQL the rows of a subquery, by `rowid` and `ctid`: SQLite
+    /// tables created `WITHOUT ROWID` can not be limited.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite, Mysql}};
+    /// #[derive(Entity)]
+    /// #[tablename = "events"]
+    /// struct Event {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     created_at: i64,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Delete::from_table(Event::table())
+    ///     .so_that(Event::created_at.less_than(1_000))
+    ///     .order_by(Event::created_at)
+    ///     .limit(500);
+    ///
+    /// let (sql, _) = Mysql::build(query.clone())?;
+    /// assert_eq!(
+    ///     "DELETE FROM `events` WHERE `events`.`created_at` < ? ORDER BY `events`.`created_at` LIMIT ?",
+    ///     sql
+    /// );
+    ///
+    /// let (sql, _) = Sqlite::build(query)?;
+    /// assert_eq!(
+    ///     "DELETE FROM `events` WHERE `rowid` IN (SELECT `rowid` FROM `events` WHERE `events`.`created_at` < ? ORDER BY `events`.`created_at` LIMIT ?)",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(Value::from(limit));
+        self
+    }
+
+    /// Adds an ordering of the rows deleted, which only matters with a
+    /// [`limit`](Delete::limit).
+    pub fn order_by<T>(mut self, value: T) -> Self
+    where
+        T: IntoOrderDefinition<'a>,
+    {
+        self.ordering = self.ordering.append(value.into_order_definition());
+        self
+    }
+
+    /// Applies a [Scope](struct.Scope.html), combining it with the previous
+    /// conditions using `AND`.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// # #[derive(Debug, Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     #[column(primary_key)]
+    /// #     id: i64,
+    /// #     name: String,
+    /// #     banned: bool,
+    /// #     deleted_at: Option<String>,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Delete::from_table(User::table())
+    ///     .so_that(User::banned.equals(false))
+    ///     .scope(User::deleted_at.is_null());
+    /// let (sql, _) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!("DELETE FROM `users` WHERE (`users`.`banned` = ? AND `users`.`deleted_at` IS NULL)", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scope<S>(mut self, scope: S) -> Self
+    where
+        S: Into<Scope<'a>>,
+    {
+        self.conditions = Some(scope.into().apply(self.conditions.take()));
+        self
+    }
+
+    pub(crate) fn simplify(mut self) -> Self {
+        self.conditions = simplify_conditions(self.conditions);
+        self
+    }
 }
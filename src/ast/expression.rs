@@ -7,6 +7,7 @@ use std::borrow::Cow;
 /// An expression that can be positioned in a query. Can be a single value or a
 /// statement that is evaluated into a value.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Expression<'a> {
     pub(crate) kind: ExpressionKind<'a>,
     pub(crate) alias: Option<Cow<'a, str>>,
@@ -182,6 +183,7 @@ impl<'a> Expression<'a> {
 
 /// An expression we can compare and use in database queries.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExpressionKind<'a> {
     /// Anything that we must parameterize before querying
     Parameterized(Value<'a>),
@@ -197,6 +199,11 @@ pub enum ExpressionKind<'a> {
     Function(Box<Function<'a>>),
     /// A qualified asterisk to a table
     Asterisk(Option<Box<Table<'a>>>),
+    /// The columns of a registered table but the ones named, see
+    /// [`asterisk_except`].
+    AsteriskExcept(Box<Table<'a>>, Vec<Cow<'a, str>>),
+    /// The value an upsert proposed for a column, see [`excluded`].
+    Excluded(Box<Column<'a>>),
     /// An operation: sum, sub, mul or div.
     Op(Box<SqlOp<'a>>),
     /// A `VALUES` statement
@@ -209,6 +216,8 @@ pub enum ExpressionKind<'a> {
     Value(Box<Expression<'a>>),
     /// DEFAULT keyword, e.g. for `INSERT INTO ... VALUES (..., DEFAULT, ...)`
     Default,
+    /// A conversion into another type
+    Cast(Box<Cast<'a>>),
 }
 
 impl<'a> ExpressionKind<'a> {
@@ -229,6 +238,66 @@ pub fn asterisk() -> Expression<'static> {
     }
 }
 
+/// Every column of `table` but `columns`, what `SELECT t.* EXCEPT (..)` is
+/// in the databases having it.
+///
+/// The columns are not known to the AST, the visitors look the table up in
+/// the registry set with
+/// [`set_default_registry`](crate::registry::set_default_registry) and write
+/// the remaining ones. Building the query fails for a table missing from the
+/// registry, such as a derived table, and for a column it does not have.
+///
+/// ```rust
+/// # use xiayu::{prelude::*, registry, visitors::{Sqlite, Visitor}};
+/// #[derive(Entity)]
+/// #[tablename = "documents"]
+/// struct Document {
+///     #[column(primary_key)]
+///     id: i64,
+///     title: String,
+///     body: String,
+/// }
+///
+/// # fn main() -> xiayu::Result<()> {
+/// registry::set_default_registry(registry::collect!(Document));
+///
+/// let query = Select::from_table(Document::table()).value(asterisk_except(Document::table(), [Document::body]));
+/// let (sql, _) = Sqlite::build(query)?;
+///
+/// assert_eq!("SELECT `documents`.`id`, `documents`.`title` FROM `documents`", sql);
+/// # Ok(())
+/// # }
+/// ```
+pub fn asterisk_except<'a, T, I, C>(table: T, columns: I) -> Expression<'a>
+where
+    T: Into<Table<'a>>,
+    I: IntoIterator<Item = C>,
+    C: Into<Column<'a>>,
+{
+    let columns = columns.into_iter().map(|column| column.into().name).collect();
+
+    Expression {
+        kind: ExpressionKind::AsteriskExcept(Box::new(table.into()), columns),
+        alias: None,
+    }
+}
+
+/// The value the `INSERT` proposed for `column`, to use in the assignments of
+/// an [`OnConflict::Update`].
+///
+/// Written `EXCLUDED.column` in PostgreSQL and SQLite, `VALUES(column)` in
+/// MySQL and as the column of the `MERGE` source in SQL Server. The databases
+/// reject it outside of an upsert.
+pub fn excluded<'a, C>(column: C) -> Expression<'a>
+where
+    C: Into<Column<'a>>,
+{
+    Expression {
+        kind: ExpressionKind::Excluded(Box::new(column.into())),
+        alias: None,
+    }
+}
+
 /// A quick alias to create a default value expression.
 pub fn default_value() -> Expression<'static> {
     Expression {
@@ -424,6 +493,22 @@ impl<'a> Comparable<'a> for Expression<'a> {
         Compare::NotNull(Box::new(self))
     }
 
+    #[allow(clippy::wrong_self_convention)]
+    fn is_not_distinct_from<T>(self, comparison: T) -> Compare<'a>
+    where
+        T: Into<Expression<'a>>,
+    {
+        Compare::NotDistinctFrom(Box::new(self), Box::new(comparison.into()))
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn is_distinct_from<T>(self, comparison: T) -> Compare<'a>
+    where
+        T: Into<Expression<'a>>,
+    {
+        Compare::DistinctFrom(Box::new(self), Box::new(comparison.into()))
+    }
+
     fn between<T, V>(self, left: T, right: V) -> Compare<'a>
     where
         T: Into<Expression<'a>>,
@@ -1,35 +1,53 @@
 mod aggregate_to_string;
 mod average;
 mod coalesce;
+mod concat;
 mod count;
+mod datetime;
+#[cfg(feature = "postgres")]
+mod json_build_object;
 #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
 mod json_extract;
+mod length;
 mod lower;
 mod maximum;
 mod minimum;
+mod nullif;
+#[cfg(feature = "postgres")]
+mod pg_notify;
 mod row_number;
-#[cfg(all(feature = "json", feature = "postgres"))]
+#[cfg(feature = "postgres")]
 mod row_to_json;
 #[cfg(feature = "postgres")]
 mod search;
 mod sum;
+mod trim;
 mod upper;
 
 pub use aggregate_to_string::*;
 pub use average::*;
 pub use coalesce::*;
+pub use concat::*;
 pub use count::*;
+pub use datetime::*;
+#[cfg(feature = "postgres")]
+pub use json_build_object::*;
 #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
 pub use json_extract::*;
+pub use length::*;
 pub use lower::*;
 pub use maximum::*;
 pub use minimum::*;
+pub use nullif::*;
+#[cfg(feature = "postgres")]
+pub use pg_notify::*;
 pub use row_number::*;
-#[cfg(all(feature = "json", feature = "postgres"))]
+#[cfg(feature = "postgres")]
 pub use row_to_json::*;
 #[cfg(feature = "postgres")]
 pub use search::*;
 pub use sum::*;
+pub use trim::*;
 pub use upper::*;
 
 use super::{Aliasable, Expression};
@@ -37,6 +55,7 @@ use std::borrow::Cow;
 
 /// A database function definition
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Function<'a> {
     pub(crate) typ_: FunctionType<'a>,
     pub(crate) alias: Option<Cow<'a, str>>,
@@ -44,8 +63,9 @@ pub struct Function<'a> {
 
 /// A database function type
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) enum FunctionType<'a> {
-    #[cfg(all(feature = "json", feature = "postgres"))]
+    #[cfg(feature = "postgres")]
     RowToJson(RowToJson<'a>),
     RowNumber(RowNumber<'a>),
     Count(Count<'a>),
@@ -57,12 +77,72 @@ pub(crate) enum FunctionType<'a> {
     Minimum(Minimum<'a>),
     Maximum(Maximum<'a>),
     Coalesce(Coalesce<'a>),
+    NullIf(NullIf<'a>),
+    Concat(Concat<'a>),
+    Length(Length<'a>),
+    Trim(Trim<'a>),
+    Now,
+    CurrentDate,
+    DateAdd(DateAdd<'a>),
     #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
     JsonExtract(JsonExtract<'a>),
     #[cfg(feature = "postgres")]
     TextSearch(TextSearch<'a>),
+    #[cfg(feature = "postgres")]
+    JsonBuildObject(JsonBuildObject<'a>),
+    #[cfg(feature = "postgres")]
+    PgNotify(PgNotify<'a>),
+}
+
+/// A list of expressions given to a function, such as [`coalesce`] or
+/// [`concat`]: a vector or an array of one type, or a tuple mixing columns,
+/// values and other expressions.
+pub trait IntoExpressions<'a> {
+    fn into_expressions(self) -> Vec<Expression<'a>>;
 }
 
+impl<'a, T> IntoExpressions<'a> for Vec<T>
+where
+    T: Into<Expression<'a>>,
+{
+    fn into_expressions(self) -> Vec<Expression<'a>> {
+        self.into_iter().map(Into::into).collect()
+    }
+}
+
+impl<'a, T, const N: usize> IntoExpressions<'a> for [T; N]
+where
+    T: Into<Expression<'a>>,
+{
+    fn into_expressions(self) -> Vec<Expression<'a>> {
+        IntoIterator::into_iter(self).map(Into::into).collect()
+    }
+}
+
+macro_rules! into_expressions_tuple {
+    ($($name:ident),+) => {
+        impl<'a, $($name),+> IntoExpressions<'a> for ($($name,)+)
+        where
+            $($name: Into<Expression<'a>>),+
+        {
+            #[allow(non_snake_case)]
+            fn into_expressions(self) -> Vec<Expression<'a>> {
+                let ($($name,)+) = self;
+                vec![$($name.into()),+]
+            }
+        }
+    };
+}
+
+into_expressions_tuple!(A);
+into_expressions_tuple!(A, B);
+into_expressions_tuple!(A, B, C);
+into_expressions_tuple!(A, B, C, D);
+into_expressions_tuple!(A, B, C, D, E);
+into_expressions_tuple!(A, B, C, D, E, F);
+into_expressions_tuple!(A, B, C, D, E, F, G);
+into_expressions_tuple!(A, B, C, D, E, F, G, H);
+
 impl<'a> Aliasable<'a> for Function<'a> {
     type Target = Function<'a>;
 
@@ -75,8 +155,8 @@ impl<'a> Aliasable<'a> for Function<'a> {
     }
 }
 
-#[cfg(all(feature = "json", feature = "postgres"))]
-function!(RowToJson);
+#[cfg(feature = "postgres")]
+function!(RowToJson, JsonBuildObject, PgNotify);
 
 #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
 function!(JsonExtract);
@@ -94,5 +174,10 @@ function!(
     Upper,
     Minimum,
     Maximum,
-    Coalesce
+    Coalesce,
+    NullIf,
+    Concat,
+    Length,
+    Trim,
+    DateAdd
 );
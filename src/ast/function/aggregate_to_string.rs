@@ -2,6 +2,7 @@ use super::Function;
 use crate::ast::Expression;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 /// An aggregate function that concatenates strings from a group into a single
 /// string with various options.
 pub struct AggregateToString<'a> {
@@ -11,13 +12,22 @@ pub struct AggregateToString<'a> {
 /// Aggregates the given field into a string.
 ///
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let query = Select::from_table("users").value(aggregate_to_string(Column::new("firstName")))
-///     .group_by("firstName");
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     first_name: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(aggregate_to_string(User::first_name))
+///     .group_by(User::first_name);
 ///
 /// let (sql, _) = Sqlite::build(query)?;
-/// assert_eq!("SELECT GROUP_CONCAT(`firstName`) FROM `users` GROUP BY `firstName`", sql);
+/// assert_eq!(
+///     "SELECT GROUP_CONCAT(`users`.`first_name`) FROM `users` GROUP BY `users`.`first_name`",
+///     sql
+/// );
 /// # Ok(())
 /// # }
 /// ```
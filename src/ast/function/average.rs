@@ -3,6 +3,7 @@ use crate::ast::Column;
 
 /// A representation of the `AVG` function in the database.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Average<'a> {
     pub(crate) column: Column<'a>,
 }
@@ -10,11 +11,17 @@ pub struct Average<'a> {
 /// Calculates the average value of a numeric column.
 ///
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let query = Select::from_table("users").value(avg("age"));
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     age: i32,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(avg(User::age));
 /// let (sql, _) = Sqlite::build(query)?;
-/// assert_eq!("SELECT AVG(`age`) FROM `users`", sql);
+/// assert_eq!("SELECT AVG(`users`.`age`) FROM `users`", sql);
 /// # Ok(())
 /// # }
 /// ```
@@ -1,35 +1,38 @@
-use super::Function;
+use super::{Function, IntoExpressions};
 use crate::ast::Expression;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 /// Returns the first non-null expression
 pub struct Coalesce<'a> {
     pub(crate) exprs: Vec<Expression<'a>>,
 }
 
-/// Returns the first non-null argument
+/// Returns the first non-null argument. The arguments are a vector or an array
+/// of expressions, or a tuple mixing columns and values.
 ///
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let exprs: Vec<Expression> = vec![
-///   Column::from(("users", "company")).into(),
-///   Value::text("Individual").into(),
-/// ];
-/// let query = Select::from_table("users").value(coalesce(exprs));
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     company: Option<String>,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(coalesce((User::company, "Individual")));
 /// let (sql, params) = Sqlite::build(query)?;
 /// assert_eq!("SELECT COALESCE(`users`.`company`, ?) FROM `users`", sql);
 /// assert_eq!(vec![Value::text("Individual")], params);
 /// # Ok(())
 /// # }
 /// ```
-pub fn coalesce<'a, T, V>(exprs: V) -> Function<'a>
+pub fn coalesce<'a, V>(exprs: V) -> Function<'a>
 where
-    T: Into<Expression<'a>>,
-    V: Into<Vec<T>>,
+    V: IntoExpressions<'a>,
 {
     let fun = Coalesce {
-        exprs: exprs.into().into_iter().map(|e| e.into()).collect(),
+        exprs: exprs.into_expressions(),
     };
 
     fun.into()
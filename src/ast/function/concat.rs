@@ -0,0 +1,55 @@
+use super::{Function, IntoExpressions};
+use crate::ast::Expression;
+
+/// A represention of string concatenation in the database.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Concat<'a> {
+    pub(crate) exprs: Vec<Expression<'a>>,
+}
+
+/// Concatenates strings, with `||` on PostgreSQL and SQLite, and `CONCAT` on
+/// MySQL and SQL Server.
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+/// # #[cfg(feature = "postgres")]
+/// # use xiayu::visitors::Postgres;
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     first_name: String,
+/// #     last_name: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let full_name = concat((User::first_name, " ", User::last_name)).alias("full_name");
+/// let query = Select::from_table(User::table()).value(full_name);
+///
+/// # #[cfg(feature = "postgres")]
+/// # {
+/// let (sql, _) = Postgres::build(query.clone())?;
+/// assert_eq!(
+///     r#"SELECT ("users"."first_name" || $1 || "users"."last_name") AS "full_name" FROM "users""#,
+///     sql
+/// );
+/// # }
+///
+/// let (sql, _) = Mysql::build(query)?;
+/// assert_eq!(
+///     "SELECT CONCAT(`users`.`first_name`, ?, `users`.`last_name`) AS `full_name` FROM `users`",
+///     sql
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn concat<'a, E>(exprs: E) -> Function<'a>
+where
+    E: IntoExpressions<'a>,
+{
+    let fun = Concat {
+        exprs: exprs.into_expressions(),
+    };
+
+    fun.into()
+}
@@ -2,17 +2,21 @@ use super::Function;
 use crate::ast::Expression;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 /// Returns the number of rows that matches a specified criteria.
 pub struct Count<'a> {
     pub(crate) exprs: Vec<Expression<'a>>,
+    #[cfg_attr(feature = "ast-serde", serde(default))]
+    pub(crate) distinct: bool,
 }
 
 /// Count of the underlying table where the given expression is not null.
 ///
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let query = Select::from_table("users").value(count(asterisk()));
+/// # use entities::User;
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(count(asterisk()));
 /// let (sql, _) = Sqlite::build(query)?;
 /// assert_eq!("SELECT COUNT(*) FROM `users`", sql);
 /// # Ok(())
@@ -24,6 +28,74 @@ where
 {
     let fun = Count {
         exprs: vec![expr.into()],
+        distinct: false,
+    };
+
+    fun.into()
+}
+
+/// Count of the rows, `COUNT(*)`.
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// # use xiayu::visitors::{Visitor, Sqlite};
+/// #[derive(Debug, Entity)]
+/// #[tablename = "users"]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i64,
+///     active: bool,
+/// }
+///
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(count_star()).so_that(User::active.equals(true));
+/// let (sql, _) = Sqlite::build(query)?;
+/// assert_eq!("SELECT COUNT(*) FROM `users` WHERE `users`.`active` = ?", sql);
+/// # Ok(())
+/// # }
+/// ```
+pub fn count_star<'a>() -> Function<'a> {
+    let fun = Count {
+        exprs: Vec::new(),
+        distinct: false,
+    };
+
+    fun.into()
+}
+
+/// Count of the distinct values of the given expression, leaving out nulls.
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// # use xiayu::visitors::{Visitor, Sqlite};
+/// #[derive(Debug, Entity)]
+/// #[tablename = "events"]
+/// struct Event {
+///     #[column(primary_key)]
+///     id: i64,
+///     user_id: i64,
+/// }
+///
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(Event::table()).value(count_distinct(Event::user_id));
+/// let (sql, _) = Sqlite::build(query)?;
+/// assert_eq!("SELECT COUNT(DISTINCT `events`.`user_id`) FROM `events`", sql);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// A [`Row`](crate::ast::Row) of columns counts their distinct combinations,
+/// `COUNT(DISTINCT (a, b))` in PostgreSQL and `COUNT(DISTINCT a, b)` in
+/// MySQL. SQLite and SQL Server count a single column, building the query
+/// fails with [`CountDistinctColumns`](crate::error::ErrorKind::CountDistinctColumns)
+/// there.
+pub fn count_distinct<'a, T>(expr: T) -> Function<'a>
+where
+    T: Into<Expression<'a>>,
+{
+    let fun = Count {
+        exprs: vec![expr.into()],
+        distinct: true,
     };
 
     fun.into()
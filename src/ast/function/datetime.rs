@@ -0,0 +1,225 @@
+use super::{Function, FunctionType};
+use crate::ast::Expression;
+#[cfg(feature = "chrono")]
+use crate::error::{Error, ErrorKind};
+
+/// A represention of the current timestamp in the database.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Now;
+
+/// A represention of the current date in the database.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CurrentDate;
+
+/// The unit of an [`IntervalArg`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntervalUnit {
+    Day,
+    Hour,
+    Minute,
+}
+
+/// An amount of days, hours or minutes to shift a date with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntervalArg {
+    pub(crate) amount: i32,
+    pub(crate) unit: IntervalUnit,
+}
+
+impl IntervalArg {
+    pub fn days(days: i32) -> Self {
+        Self {
+            amount: days,
+            unit: IntervalUnit::Day,
+        }
+    }
+
+    pub fn hours(hours: i32) -> Self {
+        Self {
+            amount: hours,
+            unit: IntervalUnit::Hour,
+        }
+    }
+
+    pub fn minutes(minutes: i32) -> Self {
+        Self {
+            amount: minutes,
+            unit: IntervalUnit::Minute,
+        }
+    }
+
+    pub fn amount(&self) -> i32 {
+        self.amount
+    }
+
+    pub fn unit(&self) -> IntervalUnit {
+        self.unit
+    }
+
+    fn negate(self) -> Self {
+        Self {
+            // `i32::MIN` can not be negated, it has no counterpart anyway.
+            amount: self.amount.saturating_neg(),
+            unit: self.unit,
+        }
+    }
+}
+
+/// Converts a `chrono::Duration` into the largest unit representing it
+/// exactly. Fails on durations with a precision below a minute or too large
+/// to fit the amount.
+///
+/// ```rust
+/// # use std::convert::TryFrom;
+/// # use xiayu::ast::IntervalArg;
+/// # use chrono::Duration;
+/// assert_eq!(IntervalArg::days(7), IntervalArg::try_from(Duration::weeks(1)).unwrap());
+/// assert_eq!(IntervalArg::minutes(90), IntervalArg::try_from(Duration::minutes(90)).unwrap());
+/// assert!(IntervalArg::try_from(Duration::seconds(30)).is_err());
+/// ```
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+impl std::convert::TryFrom<chrono::Duration> for IntervalArg {
+    type Error = Error;
+
+    fn try_from(duration: chrono::Duration) -> crate::Result<Self> {
+        use std::convert::TryInto;
+
+        let out_of_range = |message: &str| {
+            Error::builder(ErrorKind::ValueOutOfRange {
+                message: message.into(),
+            })
+            .build()
+        };
+
+        let minutes = duration.num_minutes();
+        if duration != chrono::Duration::minutes(minutes) {
+            return Err(out_of_range(
+                "Intervals are limited to a precision of a minute.",
+            ));
+        }
+        let (amount, unit) = if minutes % (24 * 60) == 0 {
+            (minutes / (24 * 60), IntervalUnit::Day)
+        } else if minutes % 60 == 0 {
+            (minutes / 60, IntervalUnit::Hour)
+        } else {
+            (minutes, IntervalUnit::Minute)
+        };
+
+        let amount = amount
+            .try_into()
+            .map_err(|_| out_of_range("The interval is too large."))?;
+
+        Ok(Self { amount, unit })
+    }
+}
+
+/// A date shifted by an interval.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DateAdd<'a> {
+    pub(crate) expression: Box<Expression<'a>>,
+    pub(crate) interval: IntervalArg,
+}
+
+/// The current timestamp.
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     created_at: String,
+/// # }
+/// # fn main() -> std::result::Result<(), xiayu::error::Error> {
+/// let query = Select::from_table(User::table()).so_that(User::created_at.less_than(now()));
+/// let (sql, _) = Mysql::build(query)?;
+/// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`created_at` < NOW()", sql);
+/// # Ok(())
+/// # }
+/// ```
+pub fn now<'a>() -> Function<'a> {
+    Now.into()
+}
+
+/// The current date, without the time.
+pub fn current_date<'a>() -> Function<'a> {
+    CurrentDate.into()
+}
+
+/// Shifts the date given in `expression` forward by `interval`.
+pub fn date_add<'a, E>(expression: E, interval: IntervalArg) -> Function<'a>
+where
+    E: Into<Expression<'a>>,
+{
+    let fun = DateAdd {
+        expression: Box::new(expression.into()),
+        interval,
+    };
+
+    fun.into()
+}
+
+/// Shifts the date given in `expression` backward by `interval`.
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     created_at: String,
+/// # }
+/// # fn main() -> std::result::Result<(), xiayu::error::Error> {
+/// let query = Select::from_table(User::table())
+///     .so_that(User::created_at.greater_than(date_sub(now(), IntervalArg::days(7))));
+/// let (sql, _) = Mysql::build(query)?;
+///
+/// assert_eq!(
+///     "SELECT `users`.* FROM `users` WHERE `users`.`created_at` > DATE_SUB(NOW(), INTERVAL 7 DAY)",
+///     sql
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn date_sub<'a, E>(expression: E, interval: IntervalArg) -> Function<'a>
+where
+    E: Into<Expression<'a>>,
+{
+    date_add(expression, interval.negate())
+}
+
+impl<'a> From<Now> for Function<'a> {
+    fn from(_: Now) -> Self {
+        Function {
+            typ_: FunctionType::Now,
+            alias: None,
+        }
+    }
+}
+
+impl<'a> From<CurrentDate> for Function<'a> {
+    fn from(_: CurrentDate) -> Self {
+        Function {
+            typ_: FunctionType::CurrentDate,
+            alias: None,
+        }
+    }
+}
+
+impl<'a> From<Now> for Expression<'a> {
+    fn from(now: Now) -> Self {
+        Function::from(now).into()
+    }
+}
+
+impl<'a> From<CurrentDate> for Expression<'a> {
+    fn from(current_date: CurrentDate) -> Self {
+        Function::from(current_date).into()
+    }
+}
@@ -0,0 +1,51 @@
+use std::borrow::Cow;
+
+use super::Function;
+use crate::ast::Expression;
+
+/// A representation of the `json_build_object` function in the database.
+/// Only for `postgres`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JsonBuildObject<'a> {
+    pub(crate) members: Vec<(Cow<'a, str>, Expression<'a>)>,
+}
+
+/// A JSON object of the given members, the keys are sent as parameters.
+///
+/// Only available for `postgres`
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Visitor, Postgres}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     name: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let object = json_build_object(vec![("id", Expression::from(User::id)), ("name", User::name.into())]);
+/// let query = Select::from_table(User::table()).value(object);
+/// let (sql, params) = Postgres::build(query)?;
+///
+/// assert_eq!(
+///     "SELECT json_build_object($1, \"users\".\"id\", $2, \"users\".\"name\") FROM \"users\"",
+///     sql
+/// );
+/// assert_eq!(vec![Value::from("id"), Value::from("name")], params);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
+pub fn json_build_object<'a, K, V, I>(members: I) -> Function<'a>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<Cow<'a, str>>,
+    V: Into<Expression<'a>>,
+{
+    let fun = JsonBuildObject {
+        members: members.into_iter().map(|(key, value)| (key.into(), value.into())).collect(),
+    };
+
+    fun.into()
+}
@@ -3,6 +3,7 @@ use crate::ast::Expression;
 use std::borrow::Cow;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
 pub struct JsonExtract<'a> {
     pub(crate) column: Box<Expression<'a>>,
@@ -11,6 +12,7 @@ pub struct JsonExtract<'a> {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
 pub enum JsonPath<'a> {
     #[cfg(feature = "mysql")]
@@ -46,22 +48,39 @@ impl<'a> JsonPath<'a> {
 ///
 /// For postgres:
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Postgres}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let extract: Expression = json_extract(Column::from(("users", "json")), JsonPath::array(["a", "b"]), false).into();
-/// let query = Select::from_table("users").so_that(extract.equals("c"));
+/// # use xiayu::prelude::*;
+/// # #[cfg(feature = "postgres")]
+/// # use xiayu::visitors::{Visitor, Postgres};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     json: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// # #[cfg(feature = "postgres")]
+/// # {
+/// let extract: Expression = json_extract(User::json, JsonPath::array(["a", "b"]), false).into();
+/// let query = Select::from_table(User::table()).so_that(extract.equals("c"));
 /// let (sql, params) = Postgres::build(query)?;
 /// assert_eq!("SELECT \"users\".* FROM \"users\" WHERE (\"users\".\"json\"#>ARRAY[$1, $2]::text[]) = $3", sql);
 /// assert_eq!(vec![Value::text("a"), Value::text("b"), Value::text("c")], params);
+/// # }
 /// # Ok(())
 /// # }
 /// ```
 /// For MySQL:
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Mysql}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let extract: Expression = json_extract(Column::from(("users", "json")), JsonPath::string("$.a.b"), false).into();
-/// let query = Select::from_table("users").so_that(extract.equals("c"));
+/// # use xiayu::{prelude::*, visitors::{Visitor, Mysql}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     json: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let extract: Expression = json_extract(User::json, JsonPath::string("$.a.b"), false).into();
+/// let query = Select::from_table(User::table()).so_that(extract.equals("c"));
 /// let (sql, params) = Mysql::build(query)?;
 /// assert_eq!(r#"SELECT `users`.* FROM `users` WHERE JSON_EXTRACT(`users`.`json`, ?) = ?"#, sql);
 /// assert_eq!(vec![Value::text("$.a.b"), Value::text("c")], params);
@@ -0,0 +1,42 @@
+use super::Function;
+use crate::ast::Expression;
+
+/// A represention of the `LENGTH` function in the database.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Length<'a> {
+    pub(crate) expression: Box<Expression<'a>>,
+}
+
+/// The number of characters of a string, `CHAR_LENGTH` on MySQL and `LEN` on
+/// SQL Server.
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Visitor, Mysql, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     name: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).so_that(Expression::from(length(User::name)).greater_than(3));
+///
+/// let (sql, _) = Sqlite::build(query.clone())?;
+/// assert_eq!("SELECT `users`.* FROM `users` WHERE LENGTH(`users`.`name`) > ?", sql);
+///
+/// let (sql, _) = Mysql::build(query)?;
+/// assert_eq!("SELECT `users`.* FROM `users` WHERE CHAR_LENGTH(`users`.`name`) > ?", sql);
+/// # Ok(())
+/// # }
+/// ```
+pub fn length<'a, E>(expression: E) -> Function<'a>
+where
+    E: Into<Expression<'a>>,
+{
+    let fun = Length {
+        expression: Box::new(expression.into()),
+    };
+
+    fun.into()
+}
@@ -3,6 +3,7 @@ use crate::ast::Column;
 
 /// A represention of the `MIN` function in the database.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Minimum<'a> {
     pub(crate) column: Column<'a>,
 }
@@ -10,11 +11,17 @@ pub struct Minimum<'a> {
 /// Calculates the minimum value of a numeric column.
 ///
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let query = Select::from_table("users").value(min("age"));
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     age: i32,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(min(User::age));
 /// let (sql, _) = Sqlite::build(query)?;
-/// assert_eq!("SELECT MIN(`age`) FROM `users`", sql);
+/// assert_eq!("SELECT MIN(`users`.`age`) FROM `users`", sql);
 /// # Ok(())
 /// # }
 /// ```
@@ -0,0 +1,42 @@
+use super::Function;
+use crate::ast::Expression;
+
+/// A represention of the `NULLIF` function in the database.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NullIf<'a> {
+    pub(crate) left: Box<Expression<'a>>,
+    pub(crate) right: Box<Expression<'a>>,
+}
+
+/// `NULL` when both expressions are equal, the first one otherwise.
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     nickname: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(nullif(User::nickname, ""));
+/// let (sql, params) = Sqlite::build(query)?;
+///
+/// assert_eq!("SELECT NULLIF(`users`.`nickname`, ?) FROM `users`", sql);
+/// assert_eq!(vec![Value::from("")], params);
+/// # Ok(())
+/// # }
+/// ```
+pub fn nullif<'a, L, R>(left: L, right: R) -> Function<'a>
+where
+    L: Into<Expression<'a>>,
+    R: Into<Expression<'a>>,
+{
+    let fun = NullIf {
+        left: Box::new(left.into()),
+        right: Box::new(right.into()),
+    };
+
+    fun.into()
+}
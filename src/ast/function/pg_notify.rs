@@ -0,0 +1,40 @@
+use super::Function;
+use crate::ast::Expression;
+
+/// A representation of the `pg_notify` function in the database.
+/// Only for `postgres`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PgNotify<'a> {
+    pub(crate) channel: Box<Expression<'a>>,
+    pub(crate) payload: Box<Expression<'a>>,
+}
+
+/// Sends `payload` to the listeners of `channel`, as `NOTIFY` does.
+///
+/// Only available for `postgres`
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Visitor, Postgres}};
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::default().value(pg_notify("users_changes", r#"{"pk":"42"}"#));
+/// let (sql, params) = Postgres::build(query)?;
+///
+/// assert_eq!("SELECT pg_notify($1, $2)", sql);
+/// assert_eq!(vec![Value::from("users_changes"), Value::from(r#"{"pk":"42"}"#)], params);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
+pub fn pg_notify<'a, C, P>(channel: C, payload: P) -> Function<'a>
+where
+    C: Into<Expression<'a>>,
+    P: Into<Expression<'a>>,
+{
+    let fun = PgNotify {
+        channel: Box::new(channel.into()),
+        payload: Box::new(payload.into()),
+    };
+
+    fun.into()
+}
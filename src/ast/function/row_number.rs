@@ -1,6 +1,7 @@
 use crate::ast::{Column, IntoOrderDefinition, Over};
 
 #[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 /// A window function that assigns a sequential integer
 /// number to each row in the query’s result set.
 pub struct RowNumber<'a> {
@@ -30,18 +31,25 @@ impl<'a> RowNumber<'a> {
 /// A number from 1 to n in specified order
 ///
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let fun = Function::from(row_number().order_by("created_at").partition_by("name"));
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     name: String,
+/// #     created_at: i64,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let fun = Function::from(row_number().order_by(User::created_at).partition_by(User::name));
 ///
-/// let query = Select::from_table("users")
-///     .column("id")
+/// let query = Select::from_table(User::table())
+///     .column(User::id)
 ///     .value(fun.alias("num"));
 ///
 /// let (sql, _) = Sqlite::build(query)?;
 ///
 /// assert_eq!(
-///     "SELECT `id`, ROW_NUMBER() OVER(PARTITION BY `name` ORDER BY `created_at`) AS `num` FROM `users`",
+///     "SELECT `users`.`id`, ROW_NUMBER() OVER(PARTITION BY `users`.`name` ORDER BY `users`.`created_at`) AS `num` FROM `users`",
 ///     sql
 /// );
 /// # Ok(())
@@ -2,8 +2,9 @@ use super::Function;
 use crate::ast::Table;
 
 #[derive(Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "postgres")))]
-#[cfg(all(feature = "json", feature = "postgres"))]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
+#[cfg(feature = "postgres")]
 /// A representation of the `ROW_TO_JSON` function in the database.
 /// Only for `postgres`
 pub struct RowToJson<'a> {
@@ -15,32 +16,30 @@ pub struct RowToJson<'a> {
 ///
 /// Only available for `postgres`
 ///
-/// ```no_run
-/// # use xiayu::{ast::*, prelude::Queryable, visitors::{Visitor, Postgres}, single::Quaint, val};
-/// # #[tokio::main]
-/// # async fn main() -> Result<(), xiayu::error::Error> {
-/// # let conn = Quaint::new_in_memory()?;
-/// let cte = Select::default()
-///     .value(val!("hello_world").alias("toto"))
-///     .into_cte("one");
-///
-/// let select = Select::from_table("one")
-///     .value(row_to_json("one", false))
-///     .with(cte);
-///
-/// let result = conn.select(select).await?;
+/// ```rust
+/// # use xiayu::prelude::*;
+/// # #[cfg(feature = "postgres")]
+/// # use xiayu::visitors::{Visitor, Postgres};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     name: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// # #[cfg(feature = "postgres")]
+/// # {
+/// let select = Select::from_table(User::table())
+///     .value(row_to_json(User::table(), false));
 ///
-/// assert_eq!(
-///     Value::Json(Some(serde_json::json!({
-///         "toto": "hello_world"
-///     }))),
-///     result.into_single().unwrap()[0]
-/// );
+/// let (sql, _) = Postgres::build(select)?;
+/// assert_eq!(r#"SELECT ROW_TO_JSON("users") FROM "users""#, sql);
+/// # }
 /// # Ok(())
 /// # }
 /// ```
-#[cfg_attr(feature = "docs", doc(cfg(feature = "postgres")))]
-#[cfg(all(feature = "json", feature = "postgres"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
+#[cfg(feature = "postgres")]
 pub fn row_to_json<'a, T>(expr: T, pretty_print: bool) -> Function<'a>
 where
     T: Into<Table<'a>>,
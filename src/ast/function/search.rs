@@ -1,6 +1,7 @@
 use crate::prelude::Column;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 /// Holds the columns on which to perform a full-text search
 pub struct TextSearch<'a> {
     pub(crate) columns: Vec<Column<'a>>,
@@ -10,7 +11,7 @@ pub struct TextSearch<'a> {
 ///
 /// ```rust
 /// # use xiayu::{prelude::*, visitors::{Visitor, Postgres}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
+/// # fn main() -> xiayu::Result<()> {
 /// #[derive(Entity)]
 /// struct Recipe {
 ///   name: String,
@@ -22,7 +23,7 @@ pub struct TextSearch<'a> {
 ///
 /// assert_eq!(
 ///    "SELECT \"recipes\".* FROM \"recipes\" \
-///     WHERE to_tsvector(\"name\"|| ' ' ||\"ingredients\") @@ to_tsquery($1)", sql
+///     WHERE to_tsvector(\"recipes\".\"name\"|| ' ' ||\"recipes\".\"ingredients\") @@ to_tsquery($1)", sql
 /// );
 ///
 /// assert_eq!(params, vec![Value::from("chicken")]);
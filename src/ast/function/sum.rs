@@ -3,6 +3,7 @@ use crate::ast::Expression;
 
 /// A represention of the `SUM` function in the database.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sum<'a> {
     pub(crate) expr: Box<Expression<'a>>,
 }
@@ -10,11 +11,17 @@ pub struct Sum<'a> {
 /// Calculates the sum value of a numeric column.
 ///
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}, col};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let query = Select::from_table("users").value(sum(col!("age")).alias("sum"));
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     age: i32,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(sum(User::age).alias("sum"));
 /// let (sql, _) = Sqlite::build(query)?;
-/// assert_eq!("SELECT SUM(`age`) AS `sum` FROM `users`", sql);
+/// assert_eq!("SELECT SUM(`users`.`age`) AS `sum` FROM `users`", sql);
 /// # Ok(())
 /// # }
 /// ```
@@ -0,0 +1,38 @@
+use super::Function;
+use crate::ast::Expression;
+
+/// A represention of the `TRIM` function in the database.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trim<'a> {
+    pub(crate) expression: Box<Expression<'a>>,
+}
+
+/// Removes the spaces around a string.
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     name: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(trim(User::name).alias("name"));
+/// let (sql, _) = Sqlite::build(query)?;
+///
+/// assert_eq!("SELECT TRIM(`users`.`name`) AS `name` FROM `users`", sql);
+/// # Ok(())
+/// # }
+/// ```
+pub fn trim<'a, E>(expression: E) -> Function<'a>
+where
+    E: Into<Expression<'a>>,
+{
+    let fun = Trim {
+        expression: Box::new(expression.into()),
+    };
+
+    fun.into()
+}
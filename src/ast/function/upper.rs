@@ -3,6 +3,7 @@ use crate::ast::Expression;
 
 /// A represention of the `UPPER` function in the database.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Upper<'a> {
     pub(crate) expression: Box<Expression<'a>>,
 }
@@ -10,11 +11,17 @@ pub struct Upper<'a> {
 /// Converts the result of the expression into uppercase string.
 ///
 /// ```rust
-/// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
-/// let query = Select::from_table("users").value(upper(Column::from("name")));
+/// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+/// # #[derive(Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     id: i32,
+/// #     name: String,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// let query = Select::from_table(User::table()).value(upper(User::name));
 /// let (sql, _) = Sqlite::build(query)?;
-/// assert_eq!("SELECT UPPER(`name`) FROM `users`", sql);
+/// assert_eq!("SELECT UPPER(`users`.`name`) FROM `users`", sql);
 /// # Ok(())
 /// # }
 /// ```
@@ -6,6 +6,7 @@ pub type GroupByDefinition<'a> = Expression<'a>;
 
 /// A list of definitions for the `GROUP BY` statement
 #[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Grouping<'a>(pub Vec<GroupByDefinition<'a>>);
 
 impl<'a> Grouping<'a> {
@@ -4,6 +4,7 @@ use super::{Column, Table};
 ///
 /// Used mainly for the transformation of a `INSERT` into a `MERGE`.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IndexDefinition<'a> {
     Single(Column<'a>),
     Compound(Vec<Column<'a>>),
@@ -1,17 +1,20 @@
 use crate::ast::*;
+use std::borrow::Cow;
 
 /// A builder for an `INSERT` statement.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Insert<'a> {
     pub(crate) table: Option<Table<'a>>,
     pub(crate) columns: Vec<Column<'a>>,
     pub(crate) values: Expression<'a>,
-    pub(crate) on_conflict: Option<OnConflict>,
+    pub(crate) on_conflict: Option<OnConflict<'a>>,
     pub(crate) returning: Option<Vec<Column<'a>>>,
 }
 
 /// A builder for an `INSERT` statement for a single row.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SingleRowInsert<'a> {
     pub(crate) table: Option<Table<'a>>,
     pub(crate) columns: Vec<Column<'a>>,
@@ -20,6 +23,7 @@ pub struct SingleRowInsert<'a> {
 
 /// A builder for an `INSERT` statement for multiple rows.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiRowInsert<'a> {
     pub(crate) table: Option<Table<'a>>,
     pub(crate) columns: Vec<Column<'a>>,
@@ -27,15 +31,17 @@ pub struct MultiRowInsert<'a> {
 }
 
 /// `INSERT` conflict resolution strategies.
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum OnConflict {
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OnConflict<'a> {
     /// When a row already exists, do nothing. Works with postgres, MySQL or
     /// SQLite without schema information.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query: Insert = Insert::single_into("users").into();
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query: Insert = Insert::single_into(User::table()).into();
     /// let (sql, _) = Sqlite::build(query.on_conflict(OnConflict::DoNothing))?;
     /// assert_eq!("INSERT OR IGNORE INTO `users` DEFAULT VALUES", sql);
     /// # Ok(())
@@ -49,12 +55,15 @@ pub enum OnConflict {
     /// In this example our `users` table holds one unique index for the `id` column.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Mssql}};
-    /// # use indoc::indoc;
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let id = Column::from("id").table("users");
-    /// let table = Table::from("users").add_unique_index(id.clone());
-    /// let query: Insert = Insert::single_into(table).value(id, 1).into();
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::Visitor};
+    /// # #[cfg(feature = "mssql")]
+    /// # use {indoc::indoc, xiayu::visitors::Mssql};
+    /// # fn main() -> xiayu::Result<()> {
+    /// # #[cfg(feature = "mssql")]
+    /// # {
+    /// let table = User::table().add_unique_index(User::id);
+    /// let query: Insert = Insert::single_into(table).value(User::id, 1).into();
     /// let (sql, _) = Mssql::build(query.on_conflict(OnConflict::DoNothing))?;
     ///
     /// let expected_sql = indoc!(
@@ -68,6 +77,7 @@ pub enum OnConflict {
     /// );
     ///
     /// assert_eq!(expected_sql.replace('\n', " ").trim(), sql);
+    /// # }
     /// # Ok(())
     /// # }
     /// ```
@@ -84,6 +94,129 @@ pub enum OnConflict {
     /// [`DefaultValue::Generated`]: enum.DefaultValue.html#variant.Generated
     /// [column has a default value]: struct.Column.html#method.default
     DoNothing,
+    /// Do nothing on a conflict in the given [`ConflictTarget`] only, other
+    /// conflicts still fail.
+    ///
+    /// ```rust
+    /// # use entities::Recipe;
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query: Insert = Insert::single_into(Recipe::table()).value(Recipe::name, "soup").into();
+    /// let target = ConflictTarget::columns(vec![Recipe::name]);
+    /// let (sql, _) = Sqlite::build(query.on_conflict(OnConflict::DoNothingOn(target)))?;
+    ///
+    /// assert_eq!(
+    ///     "INSERT INTO `recipes` (`name`) VALUES (?) ON CONFLICT (`name`) DO NOTHING",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// Supported on PostgreSQL and SQLite, which knows no constraint names.
+    /// MySQL ignores the target with a warning, as `INSERT IGNORE` can not be
+    /// limited to it. SQL Server joins the `MERGE` on the target columns,
+    /// failing on constraint names or conditions.
+    DoNothingOn(ConflictTarget<'a>),
+    /// On a conflict in the given [`ConflictTarget`], update the existing row
+    /// with the assignments instead, an upsert. The value the insert proposed
+    /// for a column is [`excluded`].
+    ///
+    /// ```rust
+    /// # use entities::Recipe;
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query: Insert = Insert::single_into(Recipe::table())
+    ///     .value(Recipe::name, "soup")
+    ///     .value(Recipe::ingredients, "water")
+    ///     .into();
+    /// let target = ConflictTarget::columns(vec![Recipe::name]);
+    /// let assignments = vec![(Recipe::ingredients.into(), excluded(Recipe::ingredients))];
+    /// let (sql, _) = Sqlite::build(query.on_conflict(OnConflict::Update(target, assignments)))?;
+    ///
+    /// assert_eq!(
+    ///     "INSERT INTO `recipes` (`name`, `ingredients`) VALUES (?,?) ON CONFLICT (`name`) DO UPDATE SET `ingredients` = EXCLUDED.`ingredients`",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// PostgreSQL and SQLite write `ON CONFLICT .. DO UPDATE SET`. MySQL
+    /// writes `ON DUPLICATE KEY UPDATE`, ignoring the target as it updates on
+    /// a conflict in any unique index. SQL Server adds a `WHEN MATCHED THEN
+    /// UPDATE` branch to the `MERGE`, joined on the target columns.
+    ///
+    /// [`excluded`]: crate::ast::excluded
+    Update(ConflictTarget<'a>, Vec<(Column<'a>, Expression<'a>)>),
+}
+
+/// The unique index or constraint an `ON CONFLICT` clause applies to.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConflictTarget<'a> {
+    pub(crate) typ: ConflictTargetType<'a>,
+    pub(crate) conditions: Option<ConditionTree<'a>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum ConflictTargetType<'a> {
+    Columns(Vec<Column<'a>>),
+    Constraint(Cow<'a, str>),
+}
+
+impl<'a> ConflictTarget<'a> {
+    /// The unique index over `columns`.
+    pub fn columns<I, K>(columns: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<Column<'a>>,
+    {
+        Self {
+            typ: ConflictTargetType::Columns(columns.into_iter().map(Into::into).collect()),
+            conditions: None,
+        }
+    }
+
+    /// The unique constraint called `name`, PostgreSQL only.
+    pub fn constraint<S>(name: S) -> Self
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        Self {
+            typ: ConflictTargetType::Constraint(name.into()),
+            conditions: None,
+        }
+    }
+
+    /// The predicate of a partial unique index over the target columns.
+    ///
+    /// ```rust
+    /// # use entities::Recipe;
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query: Insert = Insert::single_into(Recipe::table()).value(Recipe::name, "soup").into();
+    /// let target = ConflictTarget::columns(vec![Recipe::name]).so_that(Recipe::ingredients.is_null());
+    /// let (sql, _) = Sqlite::build(query.on_conflict(OnConflict::DoNothingOn(target)))?;
+    ///
+    /// assert_eq!(
+    ///     "INSERT INTO `recipes` (`name`) VALUES (?) ON CONFLICT (`name`) WHERE `recipes`.`ingredients` IS NULL DO NOTHING",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn so_that<T>(mut self, conditions: T) -> Self
+    where
+        T: Into<ConditionTree<'a>>,
+    {
+        self.conditions = Some(conditions.into());
+        self
+    }
 }
 
 impl<'a> From<Insert<'a>> for Query<'a> {
@@ -140,9 +273,10 @@ impl<'a> Insert<'a> {
     /// Creates a new single row `INSERT` statement for the given table.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Insert::single_into("users");
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Insert::single_into(User::table());
     /// let (sql, _) = Sqlite::build(query)?;
     ///
     /// assert_eq!("INSERT INTO `users` DEFAULT VALUES", sql);
@@ -182,6 +316,59 @@ impl<'a> Insert<'a> {
         }
     }
 
+    /// Creates a multi row `INSERT` of `entities` into their table, a row
+    /// for each one converted with its `From` implementation for [`Row`].
+    /// All of the columns are inserted but the autoincremented primary key,
+    /// left to the database.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Sqlite, Visitor}};
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "posts"]
+    /// struct Post {
+    ///     #[column(primary_key, autoincrement)]
+    ///     id: i64,
+    ///     title: String,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let posts = vec![
+    ///     Post { id: 0, title: "first".to_string() },
+    ///     Post { id: 0, title: "second".to_string() },
+    /// ];
+    /// let (sql, params) = Sqlite::build(Insert::multi_from_entities(posts))?;
+    ///
+    /// assert_eq!("INSERT INTO `posts` (`title`) VALUES (?), (?)", sql);
+    /// assert_eq!(vec![Value::from("first"), Value::from("second")], params);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn multi_from_entities<E, I>(entities: I) -> MultiRowInsert<'a>
+    where
+        E: crate::prelude::Entity + Into<Row<'a>>,
+        I: IntoIterator<Item = E>,
+    {
+        let generated: Vec<bool> = E::schema()
+            .columns
+            .iter()
+            .map(|column| column.primary_key && column.autoincrement)
+            .collect();
+        let columns = E::columns()
+            .iter()
+            .zip(&generated)
+            .filter(|(_, generated)| !**generated)
+            .map(|(column, _)| column.clone());
+
+        let mut insert = Self::multi_into(E::table(), columns);
+        for entity in entities {
+            let mut row: Row<'a> = entity.into();
+            let mut generated = generated.iter();
+            row.values.retain(|_| !generated.next().copied().unwrap_or(false));
+            insert = insert.values(row);
+        }
+        insert
+    }
+
     pub fn multi<K, I>(columns: I) -> MultiRowInsert<'a>
     where
         K: Into<Column<'a>>,
@@ -211,7 +398,7 @@ impl<'a> Insert<'a> {
     }
 
     /// Sets the conflict resolution strategy.
-    pub fn on_conflict(mut self, on_conflict: OnConflict) -> Self {
+    pub fn on_conflict(mut self, on_conflict: OnConflict<'a>) -> Self {
         self.on_conflict = Some(on_conflict);
         self
     }
@@ -219,19 +406,25 @@ impl<'a> Insert<'a> {
     /// Sets the returned columns.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Postgres}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Insert::single_into("users");
-    /// let insert = Insert::from(query).returning(vec!["id"]);
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::Visitor};
+    /// # #[cfg(feature = "postgres")]
+    /// # use xiayu::visitors::Postgres;
+    /// # fn main() -> xiayu::Result<()> {
+    /// # #[cfg(feature = "postgres")]
+    /// # {
+    /// let query = Insert::single_into(User::table());
+    /// let insert = Insert::from(query).returning(vec![User::id]);
     /// let (sql, _) = Postgres::build(insert)?;
     ///
-    /// assert_eq!("INSERT INTO \"users\" DEFAULT VALUES RETURNING \"id\"", sql);
+    /// assert_eq!("INSERT INTO \"users\" DEFAULT VALUES RETURNING \"users\".\"id\"", sql);
+    /// # }
     /// # Ok(())
     /// # }
     /// ```
     #[cfg(any(feature = "postgres", feature = "mssql", feature = "sqlite"))]
     #[cfg_attr(
-        feature = "docs",
+        docsrs,
         doc(cfg(any(feature = "postgres", feature = "mssql", feature = "sqlite")))
     )]
     pub fn returning<K, I>(mut self, columns: I) -> Self
@@ -248,9 +441,15 @@ impl<'a> SingleRowInsert<'a> {
     /// Adds a new value to the `INSERT` statement
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Insert::single_into("users").value("foo", 10);
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Insert::single_into(User::table()).value(User::foo, 10);
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!("INSERT INTO `users` (`foo`) VALUES (?)", sql);
@@ -279,9 +478,15 @@ impl<'a> MultiRowInsert<'a> {
     /// Adds a new row to be inserted.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Insert::multi_into("users", vec!["foo"])
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Insert::multi_into(User::table(), vec![User::foo])
     ///     .values(vec![1])
     ///     .values(vec![2]);
     ///
@@ -2,6 +2,7 @@ use crate::ast::{ConditionTree, Table};
 
 /// The `JOIN` table and conditions.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JoinData<'a> {
     pub(crate) table: Table<'a>,
     pub(crate) conditions: ConditionTree<'a>,
@@ -28,6 +29,7 @@ where
 
 /// A representation of a `JOIN` statement.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Join<'a> {
     /// Implements an `INNER JOIN` with given `JoinData`.
     Inner(JoinData<'a>),
@@ -39,19 +41,36 @@ pub enum Join<'a> {
     Full(JoinData<'a>),
 }
 
+impl<'a> Join<'a> {
+    pub(crate) fn simplify(self) -> Self {
+        let simplify = |data: JoinData<'a>| JoinData {
+            conditions: data.conditions.simplify(),
+            ..data
+        };
+
+        match self {
+            Self::Inner(data) => Self::Inner(simplify(data)),
+            Self::Left(data) => Self::Left(simplify(data)),
+            Self::Right(data) => Self::Right(simplify(data)),
+            Self::Full(data) => Self::Full(simplify(data)),
+        }
+    }
+}
+
 /// An item that can be joined.
 pub trait Joinable<'a> {
     /// Add the `JOIN` conditions.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join_data = "b".on(("b", "id").equals(Column::from(("a", "id"))));
-    /// let query = Select::from_table("a").inner_join(join_data);
+    /// # use entities::{Post, User};
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let join_data = Post::table().on(Post::user_id.equals(User::id));
+    /// let query = Select::from_table(User::table()).inner_join(join_data);
     /// let (sql, _) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
-    ///     "SELECT `a`.* FROM `a` INNER JOIN `b` ON `b`.`id` = `a`.`id`",
+    ///     "SELECT `users`.* FROM `users` INNER JOIN `posts` ON `posts`.`user_id` = `users`.`id`",
     ///     sql,
     /// );
     /// # Ok(())
@@ -0,0 +1,69 @@
+use crate::error::{Error, ErrorKind};
+
+/// Row locking requested by a `SELECT`, `FOR UPDATE` or `FOR SHARE` and how to
+/// behave on rows already locked by someone else.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Locking {
+    pub(crate) update: bool,
+    pub(crate) share: bool,
+    pub(crate) skip_locked: bool,
+    pub(crate) nowait: bool,
+}
+
+/// The strength of a row lock.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LockStrength {
+    /// `FOR UPDATE`, an exclusive lock.
+    Update,
+    /// `FOR SHARE`, a shared lock.
+    Share,
+}
+
+/// What to do when a row to lock is already locked.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LockWait {
+    /// Block until the lock is released.
+    Wait,
+    /// Leave the locked rows out from the result, `SKIP LOCKED`.
+    SkipLocked,
+    /// Fail instead of waiting, `NOWAIT`.
+    NoWait,
+}
+
+impl Locking {
+    /// True if no locking is requested.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Checks the combination of the locking options, returning the lock
+    /// strength and the waiting behaviour. `None` if nothing is locked.
+    pub fn validate(&self) -> crate::Result<Option<(LockStrength, LockWait)>> {
+        let invalid =
+            |message: &'static str| Error::builder(ErrorKind::InvalidLocking(message)).build();
+
+        let wait = match (self.skip_locked, self.nowait) {
+            (true, true) => {
+                return Err(invalid("`SKIP LOCKED` and `NOWAIT` are mutually exclusive"))
+            }
+            (true, false) => LockWait::SkipLocked,
+            (false, true) => LockWait::NoWait,
+            (false, false) => LockWait::Wait,
+        };
+
+        match (self.update, self.share) {
+            (true, true) => Err(invalid(
+                "`FOR UPDATE` and `FOR SHARE` are mutually exclusive",
+            )),
+            (true, false) => Ok(Some((LockStrength::Update, wait))),
+            (false, true) => Ok(Some((LockStrength::Share, wait))),
+            (false, false) if wait != LockWait::Wait => Err(invalid(
+                "`SKIP LOCKED` and `NOWAIT` require `FOR UPDATE` or `FOR SHARE`",
+            )),
+            (false, false) => Ok(None),
+        }
+    }
+}
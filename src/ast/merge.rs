@@ -7,10 +7,13 @@ use std::convert::TryFrom;
 /// Not complete and not meant for external use in this state. Made for
 /// compatibility purposes.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Merge<'a> {
     pub(crate) table: Table<'a>,
     pub(crate) using: Using<'a>,
     pub(crate) when_not_matched: Option<Query<'a>>,
+    #[cfg_attr(feature = "ast-serde", serde(default))]
+    pub(crate) when_matched: Option<Vec<(Column<'a>, Expression<'a>)>>,
     pub(crate) returning: Option<Vec<Column<'a>>>,
 }
 
@@ -24,6 +27,7 @@ impl<'a> Merge<'a> {
             table: table.into(),
             using: using.into(),
             when_not_matched: None,
+            when_matched: None,
             returning: None,
         }
     }
@@ -36,6 +40,13 @@ impl<'a> Merge<'a> {
         self
     }
 
+    /// Updates the matched rows with the assignments, an upsert.
+    #[cfg(feature = "mssql")]
+    pub(crate) fn when_matched(mut self, assignments: Vec<(Column<'a>, Expression<'a>)>) -> Self {
+        self.when_matched = Some(assignments);
+        self
+    }
+
     pub(crate) fn returning<K, I>(mut self, columns: I) -> Self
     where
         K: Into<Column<'a>>,
@@ -53,6 +64,7 @@ impl<'a> From<Merge<'a>> for Query<'a> {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Using<'a> {
     pub(crate) base_query: Query<'a>,
     pub(crate) columns: Vec<Column<'a>>,
@@ -119,7 +131,12 @@ impl<'a> TryFrom<Insert<'a>> for Merge<'a> {
             }
             ExpressionKind::Values(values) => {
                 let mut rows = values.rows;
-                let row = rows.pop().unwrap();
+                let row = rows.pop().ok_or_else(|| {
+                    let kind = ErrorKind::conversion(
+                        "Insert needs at least one row of values for conversion to Merge.",
+                    );
+                    Error::builder(kind).build()
+                })?;
                 let cols_vals = columns.iter().zip(row.values.into_iter());
 
                 let select = cols_vals.fold(Select::default(), |query, (col, val)| {
@@ -152,10 +169,11 @@ impl<'a> TryFrom<Insert<'a>> for Merge<'a> {
             alias: None,
             database: None,
             index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
         };
         let using = query
             .into_using(dual_table.clone(), bare_columns.clone())
-            .on(table.join_conditions(&columns).unwrap());
+            .on(table.join_conditions(&columns)?);
 
         let dual_columns: Vec<_> = columns
             .into_iter()
@@ -175,3 +193,25 @@ impl<'a> TryFrom<Insert<'a>> for Merge<'a> {
         Ok(merge)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Entity)]
+    #[tablename = "foo"]
+    struct Foo {
+        bar: i32,
+    }
+
+    #[test]
+    fn test_empty_values_do_not_convert() {
+        let table = Foo::table().add_unique_index(Foo::bar);
+        let insert = Insert::from(Insert::multi_into(table, vec![Foo::bar]));
+
+        let err = Merge::try_from(insert).unwrap_err();
+
+        assert!(matches!(err.kind(), ErrorKind::ConversionError(_)));
+    }
+}
@@ -5,6 +5,8 @@
 //! actual query building is in the [visitors](../visitors/index.html) module.
 //!
 //! For prelude, all important imports are in `xiayu::ast::*`.
+mod alter;
+mod cast;
 mod column;
 mod compare;
 mod conditions;
@@ -17,21 +19,28 @@ mod grouping;
 mod index;
 mod insert;
 mod join;
+mod lock;
 mod merge;
 mod ops;
 mod ordering;
 mod over;
 mod query;
 mod row;
+mod scope;
 mod select;
 mod table;
 mod union;
 mod update;
 mod values;
+#[cfg(feature = "ast-serde")]
+mod versioned;
 
-pub use column::{Column, DefaultValue, TypeDataLength, TypeFamily};
-pub use compare::{Comparable, Compare, JsonCompare, JsonType};
+pub use alter::{AlterOperation, AlterTable, ColumnDefinition, ForeignKeyDefinition, TableDefinition};
+pub use cast::{cast, Cast};
+pub use column::{Column, ColumnMeta, DefaultValue, TypeDataLength, TypeFamily};
+pub use compare::{exists, not_exists, Comparable, Compare, IntoSelection, JsonCompare, JsonType};
 pub use conditions::ConditionTree;
+pub(crate) use conditions::simplify_conditions;
 pub use conjunctive::Conjunctive;
 pub use cte::{CommonTableExpression, IntoCommonTableExpression};
 pub use delete::Delete;
@@ -41,12 +50,14 @@ pub use grouping::*;
 pub use index::*;
 pub use insert::*;
 pub use join::{Join, JoinData, Joinable};
+pub use lock::{LockStrength, LockWait, Locking};
 pub(crate) use merge::*;
 pub use ops::*;
 pub use ordering::{IntoOrderDefinition, Order, OrderDefinition, Orderable, Ordering};
 pub use over::*;
 pub use query::{Query, SelectQuery};
 pub use row::Row;
+pub use scope::Scope;
 pub use select::Select;
 pub use table::*;
 pub use union::Union;
@@ -55,3 +66,6 @@ pub use update::*;
 pub use values::Json;
 pub(crate) use values::Params;
 pub use values::{IntoRaw, Raw, Value, Values};
+#[cfg(feature = "ast-serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ast-serde")))]
+pub use versioned::{Versioned, AST_VERSION};
@@ -3,6 +3,7 @@ use std::ops::{Add, Div, Mul, Rem, Sub};
 
 /// Calculation operations in SQL queries.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SqlOp<'a> {
     Add(Expression<'a>, Expression<'a>),
     Sub(Expression<'a>, Expression<'a>),
@@ -1,10 +1,11 @@
-use crate::ast::{Column, Expression};
+use crate::ast::{Column, Expression, Function};
 
 /// Defines ordering for an `ORDER BY` statement.
 pub type OrderDefinition<'a> = (Expression<'a>, Option<Order>);
 
 /// A list of definitions for the `ORDER BY` statement.
 #[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ordering<'a>(pub Vec<OrderDefinition<'a>>);
 
 impl<'a> Ordering<'a> {
@@ -25,6 +26,7 @@ impl<'a> Ordering<'a> {
 
 /// The ordering direction
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Order {
     /// Ascending
     Asc,
@@ -71,6 +73,12 @@ impl<'a> IntoOrderDefinition<'a> for Column<'a> {
     }
 }
 
+impl<'a> IntoOrderDefinition<'a> for Function<'a> {
+    fn into_order_definition(self) -> OrderDefinition<'a> {
+        (self.into(), None)
+    }
+}
+
 impl<'a> IntoOrderDefinition<'a> for OrderDefinition<'a> {
     fn into_order_definition(self) -> OrderDefinition<'a> {
         self
@@ -83,6 +91,12 @@ impl<'a> Orderable<'a> for Column<'a> {
     }
 }
 
+impl<'a> Orderable<'a> for Function<'a> {
+    fn order(self, order: Option<Order>) -> OrderDefinition<'a> {
+        (self.into(), order)
+    }
+}
+
 /*
 impl<'a> Orderable<'a> for &'a str {
     fn order(self, order: Option<Order>) -> OrderDefinition<'a> {
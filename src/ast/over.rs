@@ -1,6 +1,7 @@
 use crate::ast::{Column, Ordering};
 
 #[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 /// Determines the partitioning and ordering of a rowset before the associated
 /// window function is applied.
 pub struct Over<'a> {
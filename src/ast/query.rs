@@ -1,10 +1,11 @@
-use crate::ast::{Delete, Insert, Merge, Select, Union, Update};
+use crate::ast::{AlterTable, Delete, Insert, Merge, Select, Union, Update};
 use std::borrow::Cow;
 
 use super::IntoCommonTableExpression;
 
 /// A database query
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Query<'a> {
     Select(Box<Select<'a>>),
     Insert(Box<Insert<'a>>),
@@ -12,6 +13,7 @@ pub enum Query<'a> {
     Delete(Box<Delete<'a>>),
     Union(Box<Union<'a>>),
     Merge(Box<Merge<'a>>),
+    AlterTable(Box<AlterTable<'a>>),
     Raw(Cow<'a, str>),
 }
 
@@ -44,10 +46,44 @@ impl<'a> Query<'a> {
     pub fn is_union(&self) -> bool {
         matches!(self, Query::Union(_))
     }
+
+    /// Simplifies the conditions of the query, its joins and the queries of
+    /// a union, see [`ConditionTree::simplify`]. Conditions always true are
+    /// left out, a `WHERE` included.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// #[derive(Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i32,
+    ///     deleted: bool,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let filters: Vec<Expression> = Vec::new();
+    /// let query = Delete::from_table(User::table()).so_that(ConditionTree::all_of(filters));
+    /// let (sql, _) = Sqlite::build(Query::from(query).simplify())?;
+    ///
+    /// assert_eq!("DELETE FROM `users`", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn simplify(self) -> Self {
+        match self {
+            Query::Select(select) => Query::Select(Box::new(select.simplify())),
+            Query::Update(update) => Query::Update(Box::new(update.simplify())),
+            Query::Delete(delete) => Query::Delete(Box::new(delete.simplify())),
+            Query::Union(union) => Query::Union(Box::new(union.simplify())),
+            query => query,
+        }
+    }
 }
 
 /// A database query that only returns data without modifying anything.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SelectQuery<'a> {
     Select(Box<Select<'a>>),
     Union(Box<Union<'a>>),
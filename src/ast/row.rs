@@ -5,6 +5,7 @@ use std::borrow::Cow;
 
 /// A collection of values surrounded by parentheses.
 #[derive(Debug, Default, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Row<'a> {
     pub values: Vec<Expression<'a>>,
 }
@@ -163,6 +164,81 @@ where
     }
 }
 
+impl<'a, A, B, C, D, E, F> From<(A, B, C, D, E, F)> for Row<'a>
+where
+    A: Into<Expression<'a>>,
+    B: Into<Expression<'a>>,
+    C: Into<Expression<'a>>,
+    D: Into<Expression<'a>>,
+    E: Into<Expression<'a>>,
+    F: Into<Expression<'a>>,
+{
+    fn from(vals: (A, B, C, D, E, F)) -> Self {
+        let mut row = Row::with_capacity(6);
+
+        row.push(vals.0);
+        row.push(vals.1);
+        row.push(vals.2);
+        row.push(vals.3);
+        row.push(vals.4);
+        row.push(vals.5);
+
+        row
+    }
+}
+
+impl<'a, A, B, C, D, E, F, G> From<(A, B, C, D, E, F, G)> for Row<'a>
+where
+    A: Into<Expression<'a>>,
+    B: Into<Expression<'a>>,
+    C: Into<Expression<'a>>,
+    D: Into<Expression<'a>>,
+    E: Into<Expression<'a>>,
+    F: Into<Expression<'a>>,
+    G: Into<Expression<'a>>,
+{
+    fn from(vals: (A, B, C, D, E, F, G)) -> Self {
+        let mut row = Row::with_capacity(7);
+
+        row.push(vals.0);
+        row.push(vals.1);
+        row.push(vals.2);
+        row.push(vals.3);
+        row.push(vals.4);
+        row.push(vals.5);
+        row.push(vals.6);
+
+        row
+    }
+}
+
+impl<'a, A, B, C, D, E, F, G, H> From<(A, B, C, D, E, F, G, H)> for Row<'a>
+where
+    A: Into<Expression<'a>>,
+    B: Into<Expression<'a>>,
+    C: Into<Expression<'a>>,
+    D: Into<Expression<'a>>,
+    E: Into<Expression<'a>>,
+    F: Into<Expression<'a>>,
+    G: Into<Expression<'a>>,
+    H: Into<Expression<'a>>,
+{
+    fn from(vals: (A, B, C, D, E, F, G, H)) -> Self {
+        let mut row = Row::with_capacity(8);
+
+        row.push(vals.0);
+        row.push(vals.1);
+        row.push(vals.2);
+        row.push(vals.3);
+        row.push(vals.4);
+        row.push(vals.5);
+        row.push(vals.6);
+        row.push(vals.7);
+
+        row
+    }
+}
+
 impl<'a> Comparable<'a> for Row<'a> {
     fn equals<T>(self, comparison: T) -> Compare<'a>
     where
@@ -288,6 +364,24 @@ impl<'a> Comparable<'a> for Row<'a> {
         value.is_not_null()
     }
 
+    #[allow(clippy::wrong_self_convention)]
+    fn is_not_distinct_from<T>(self, comparison: T) -> Compare<'a>
+    where
+        T: Into<Expression<'a>>,
+    {
+        let value: Expression<'a> = self.into();
+        value.is_not_distinct_from(comparison)
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn is_distinct_from<T>(self, comparison: T) -> Compare<'a>
+    where
+        T: Into<Expression<'a>>,
+    {
+        let value: Expression<'a> = self.into();
+        value.is_distinct_from(comparison)
+    }
+
     fn between<T, V>(self, left: T, right: V) -> Compare<'a>
     where
         T: Into<Expression<'a>>,
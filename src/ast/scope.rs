@@ -0,0 +1,91 @@
+use crate::ast::*;
+
+/// A named, reusable set of conditions.
+///
+/// Scopes are plain condition trees, usually returned from an associated
+/// function of an entity, and are applied to a query with `scope`. Applying
+/// a scope combines it with the conditions already set using `AND`.
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// # use xiayu::visitors::{Visitor, Sqlite};
+/// # #[derive(Debug, Entity)]
+/// # #[tablename = "users"]
+/// # struct User {
+/// #     #[column(primary_key)]
+/// #     id: i64,
+/// #     name: String,
+/// #     banned: bool,
+/// #     deleted_at: Option<String>,
+/// # }
+/// # fn main() -> xiayu::Result<()> {
+/// fn active() -> Scope<'static> {
+///     User::deleted_at.is_null().and(User::banned.equals(false)).into()
+/// }
+///
+/// let query = Select::from_table(User::table())
+///     .so_that(User::name.equals("musti"))
+///     .scope(active());
+/// let (sql, _) = Sqlite::build(query)?;
+///
+/// assert_eq!(
+///     "SELECT `users`.* FROM `users` WHERE (`users`.`name` = ? AND (`users`.`deleted_at` IS NULL AND `users`.`banned` = ?))",
+///     sql
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scope<'a>(ConditionTree<'a>);
+
+impl<'a> Scope<'a> {
+    /// A scope that has no effect on the query.
+    pub fn all() -> Self {
+        Self(ConditionTree::NoCondition)
+    }
+
+    /// Combines two scopes, both of them must match.
+    pub fn and<S>(self, other: S) -> Self
+    where
+        S: Into<Scope<'a>>,
+    {
+        Self(self.0.and(other.into().0))
+    }
+
+    /// Combines two scopes, either of them must match.
+    pub fn or<S>(self, other: S) -> Self
+    where
+        S: Into<Scope<'a>>,
+    {
+        Self(self.0.or(other.into().0))
+    }
+
+    /// Merges the scope into already existing conditions.
+    pub(crate) fn apply(self, conditions: Option<ConditionTree<'a>>) -> ConditionTree<'a> {
+        match (conditions, self.0) {
+            (None, scope) | (Some(ConditionTree::NoCondition), scope) => scope,
+            (Some(previous), ConditionTree::NoCondition) => previous,
+            (Some(previous), scope) => previous.and(scope),
+        }
+    }
+
+    pub fn into_conditions(self) -> ConditionTree<'a> {
+        self.0
+    }
+}
+
+impl<'a, T> From<T> for Scope<'a>
+where
+    T: Into<ConditionTree<'a>>,
+{
+    fn from(conditions: T) -> Self {
+        Self(conditions.into())
+    }
+}
+
+impl<'a> From<Scope<'a>> for Expression<'a> {
+    fn from(scope: Scope<'a>) -> Self {
+        Expression::from(scope.0)
+    }
+}
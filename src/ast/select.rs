@@ -1,7 +1,13 @@
 use crate::ast::*;
 
 /// A builder for a `SELECT` statement.
+///
+/// A select nests in another query as a value, a table, a common table
+/// expression or in `IN` and `EXISTS` conditions. Its parameters are then
+/// numbered in the order of the outer query, nest the select itself rather
+/// than the SQL it was built into.
 #[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Select<'a> {
     pub(crate) distinct: bool,
     pub(crate) tables: Vec<Table<'a>>,
@@ -14,6 +20,9 @@ pub struct Select<'a> {
     pub(crate) offset: Option<Value<'a>>,
     pub(crate) joins: Vec<Join<'a>>,
     pub(crate) ctes: Vec<CommonTableExpression<'a>>,
+    pub(crate) locking: Locking,
+    /// A select written as is, see [`from_sql`](Select::from_sql).
+    pub(crate) sql: Option<std::borrow::Cow<'a, str>>,
 }
 
 impl<'a> From<Select<'a>> for Expression<'a> {
@@ -25,6 +34,58 @@ impl<'a> From<Select<'a>> for Expression<'a> {
     }
 }
 
+/// A select in the columns of another one, a scalar subquery, named with
+/// its alias. Correlated with the outer query through the columns of its
+/// aliased tables, `users.col(User::id)` rather than `User::id`. The rows
+/// decode with [`fetch_as`](Select::fetch_as) into an entity of the same
+/// table with `#[entity(readonly)]` and a field named after the alias.
+///
+/// ```rust
+/// # use xiayu::{prelude::*, visitors::{Sqlite, Visitor}};
+/// #[derive(Debug, Entity)]
+/// #[tablename = "users"]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i64,
+///     name: String,
+/// }
+///
+/// #[derive(Debug, Entity)]
+/// #[tablename = "posts"]
+/// struct Post {
+///     #[column(primary_key)]
+///     id: i64,
+///     user_id: i64,
+/// }
+///
+/// # fn main() -> xiayu::Result<()> {
+/// let users = User::table().alias("u");
+/// let post_count = Select::from_table(Post::table())
+///     .value(count(asterisk()))
+///     .so_that(Post::user_id.equals(users.col(User::id)));
+/// let query = Select::from_table(users.clone())
+///     .value(users.asterisk())
+///     .value(post_count.alias("post_count"));
+///
+/// let (sql, _) = Sqlite::build(query)?;
+/// assert_eq!(
+///     "SELECT `u`.*, (SELECT COUNT(*) FROM `posts` WHERE `posts`.`user_id` = `u`.`id`) AS `post_count` FROM `users` AS `u`",
+///     sql
+/// );
+/// # Ok(())
+/// # }
+/// ```
+impl<'a> Aliasable<'a> for Select<'a> {
+    type Target = Expression<'a>;
+
+    fn alias<T>(self, alias: T) -> Self::Target
+    where
+        T: Into<std::borrow::Cow<'a, str>>,
+    {
+        Expression::from(self).alias(alias)
+    }
+}
+
 impl<'a> From<Select<'a>> for Query<'a> {
     fn from(sel: Select<'a>) -> Query<'a> {
         Query::Select(Box::new(sel))
@@ -35,9 +96,10 @@ impl<'a> Select<'a> {
     /// Creates a new `SELECT` statement for the given table.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users");
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table());
     /// let (sql, _) = Sqlite::build(query)?;
     ///
     /// assert_eq!("SELECT `users`.* FROM `users`", sql);
@@ -48,9 +110,10 @@ impl<'a> Select<'a> {
     /// The table can be in multiple parts, defining the database.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table(("crm", "users"));
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table().database("crm"));
     /// let (sql, _) = Sqlite::build(query)?;
     ///
     /// assert_eq!("SELECT `crm`.`users`.* FROM `crm`.`users`", sql);
@@ -62,7 +125,7 @@ impl<'a> Select<'a> {
     ///
     /// ```rust
     /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// let select = Table::from(Select::default().value(1)).alias("num");
     /// let query = Select::from_table(select.alias("num"));
     /// let (sql, params) = Sqlite::build(query)?;
@@ -78,7 +141,7 @@ impl<'a> Select<'a> {
     /// ```rust
     /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
     /// # use xiayu::values;
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// let expected_sql = "SELECT `vals`.* FROM (VALUES (?,?),(?,?)) AS `vals`";
     /// let values = Table::from(values!((1, 2), (3, 4))).alias("vals");
     /// let query = Select::from_table(values);
@@ -107,15 +170,50 @@ impl<'a> Select<'a> {
         }
     }
 
-    /// Adds a table to be selected.
+    /// A `SELECT` written in SQL, to be nested as a table, e.g. the inline view
+    /// of an entity with `#[entity(view = "...")]`. The SQL is written as is,
+    /// the clauses added to the select are not.
     ///
     /// ```rust
     /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users")
-    ///     .and_from(Table::from(Select::default().value(1)).alias("num"))
-    ///     .column(("users", "name"))
-    ///     .value(Table::from("num").asterisk());
+    /// # fn main() -> xiayu::Result<()> {
+    /// let totals = Table::from(Select::from_sql("SELECT day, SUM(amount) AS total FROM payments GROUP BY day"));
+    /// let query = Select::from_table(totals.alias("totals"));
+    /// let (sql, _) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!(
+    ///     "SELECT `totals`.* FROM (SELECT day, SUM(amount) AS total FROM payments GROUP BY day) AS `totals`",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_sql<S>(sql: S) -> Self
+    where
+        S: Into<std::borrow::Cow<'a, str>>,
+    {
+        Select {
+            sql: Some(sql.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a table to be selected.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     name: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let num = Table::from(Select::default().value(1)).alias("num");
+    /// let query = Select::from_table(User::table())
+    ///     .and_from(num.clone())
+    ///     .column(User::name)
+    ///     .value(num.asterisk());
     ///
     /// let (sql, _) = Sqlite::build(query)?;
     ///
@@ -135,7 +233,7 @@ impl<'a> Select<'a> {
     ///
     /// ```rust
     /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// let query = Select::default().value(1);
     /// let (sql, params) = Sqlite::build(query)?;
     ///
@@ -148,13 +246,14 @@ impl<'a> Select<'a> {
     /// Creating a qualified asterisk to a joined table:
     ///
     /// ```rust
-    /// # use xiayu::{col, val, ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join = "dogs".on(("dogs", "slave_id").equals(Column::from(("cats", "master_id"))));
-    ///
-    /// let query = Select::from_table("cats")
-    ///     .value(Table::from("cats").asterisk())
-    ///     .value(col!("dogs", "age") - val!(4))
+    /// # use entities::{Cat, Dog};
+    /// # use xiayu::{val, prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let join = Dog::table().on(Dog::slave_id.equals(Cat::master_id));
+    ///
+    /// let query = Select::from_table(Cat::table())
+    ///     .value(Cat::table().asterisk())
+    ///     .value(Expression::from(Dog::age) - val!(4))
     ///     .inner_join(join);
     ///
     /// let (sql, params) = Sqlite::build(query)?;
@@ -179,16 +278,21 @@ impl<'a> Select<'a> {
     /// Adds a column to be selected.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users")
-    ///     .column("name")
-    ///     .column(("users", "id"))
-    ///     .column((("crm", "users"), "foo"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     name: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table())
+    ///     .column(User::name)
+    ///     .column(User::id);
     ///
     /// let (sql, _) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `name`, `users`.`id`, `crm`.`users`.`foo` FROM `users`", sql);
+    /// assert_eq!("SELECT `users`.`name`, `users`.`id` FROM `users`", sql);
     /// # Ok(())
     /// # }
     /// ```
@@ -203,12 +307,19 @@ impl<'a> Select<'a> {
     /// A bulk method to select multiple values.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").columns(vec!["foo", "bar"]);
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// #     bar: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).columns(vec![User::foo, User::bar]);
     /// let (sql, _) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `foo`, `bar` FROM `users`", sql);
+    /// assert_eq!("SELECT `users`.`foo`, `users`.`bar` FROM `users`", sql);
     /// # Ok(())
     /// # }
     /// ```
@@ -221,15 +332,53 @@ impl<'a> Select<'a> {
         self
     }
 
+    /// Selects the columns of the entity `E`, in the order of
+    /// [`Entity::columns`](crate::prelude::Entity::columns), which is the
+    /// order of its fields. The write-only columns are left out, see
+    /// [`Entity::readable_columns`](crate::prelude::Entity::readable_columns).
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).columns_for::<User>();
+    /// let (sql, _) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!("SELECT `users`.`id`, `users`.`name` FROM `users`", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn columns_for<E>(self) -> Self
+    where
+        E: crate::prelude::Entity,
+    {
+        self.columns(E::readable_columns().iter().cloned())
+    }
+
     /// Adds `DISTINCT` to the select query.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").column("foo").column("bar").distinct();
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// #     bar: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).column(User::foo).column(User::bar).distinct();
     /// let (sql, _) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT DISTINCT `foo`, `bar` FROM `users`", sql);
+    /// assert_eq!("SELECT DISTINCT `users`.`foo`, `users`.`bar` FROM `users`", sql);
     /// # Ok(())
     /// # }
     /// ```
@@ -243,12 +392,18 @@ impl<'a> Select<'a> {
     /// examples.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").so_that("foo".equals("bar"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::foo.equals("bar"));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `foo` = ?", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`foo` = ?", sql);
     ///
     /// assert_eq!(vec![
     ///    Value::from("bar"),
@@ -264,20 +419,49 @@ impl<'a> Select<'a> {
         self
     }
 
+    /// Adds `WHERE` conditions true when all of `conditions` are, replacing
+    /// the previous conditions. See [`ConditionTree::all_of`].
+    ///
+    /// ```rust
+    /// # use entities::Dog;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(Dog::table()).so_that_all(vec![Dog::age.equals(3), Dog::slave_id.equals(1)]);
+    /// let (sql, _) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!("SELECT `dogs`.* FROM `dogs` WHERE (`dogs`.`age` = ? AND `dogs`.`slave_id` = ?)", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn so_that_all<I, E>(self, conditions: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+        E: Into<Expression<'a>>,
+    {
+        self.so_that(ConditionTree::all_of(conditions))
+    }
+
     /// Adds an additional `WHERE` condition to the query combining the possible
     /// previous condition with `AND`. See
     /// [Comparable](trait.Comparable.html#required-methods) for more examples.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users")
-    ///     .so_that("foo".equals("bar"))
-    ///     .and_where("lol".equals("wtf"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// #     lol: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table())
+    ///     .so_that(User::foo.equals("bar"))
+    ///     .and_where(User::lol.equals("wtf"));
     ///
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE (`foo` = ? AND `lol` = ?)", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE (`users`.`foo` = ? AND `users`.`lol` = ?)", sql);
     ///
     /// assert_eq!(vec![
     ///    Value::from("bar"),
@@ -299,20 +483,109 @@ impl<'a> Select<'a> {
         }
     }
 
+    /// Keyset pagination: keeps the rows sorting after `values` on `columns`,
+    /// combined with the previous conditions with `AND`. Order the query by
+    /// the same columns, ascending, to fetch the next page.
+    ///
+    /// The columns are compared as a row, `(a, b) > (x, y)`, expanded into
+    /// `a > x OR (a = x AND b > y)` on SQL Server. Both forms skip a row with
+    /// a `NULL` once the columns before it are equal, keep the paginated
+    /// columns `NOT NULL`.
+    ///
+    /// ```rust
+    /// # use entities::Post;
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(Post::table())
+    ///     .after((Post::user_id, Post::id), (3, 10))
+    ///     .order_by(Post::user_id.ascend())
+    ///     .order_by(Post::id.ascend())
+    ///     .limit(20);
+    /// let (sql, params) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!(
+    ///     "SELECT `posts`.* FROM `posts` WHERE (`posts`.`user_id`,`posts`.`id`) > (?,?) \
+    ///      ORDER BY `posts`.`user_id` ASC, `posts`.`id` ASC LIMIT ?",
+    ///     sql
+    /// );
+    /// assert_eq!(vec![Value::from(3), Value::from(10), Value::from(20)], params);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn after<C, V>(self, columns: C, values: V) -> Self
+    where
+        C: Into<Row<'a>>,
+        V: Into<Row<'a>>,
+    {
+        self.and_where(columns.into().greater_than(values.into()))
+    }
+
+    /// Keyset pagination on a descending order, keeping the rows sorting
+    /// before `values` on `columns`. See [`after`](Self::after).
+    pub fn before<C, V>(self, columns: C, values: V) -> Self
+    where
+        C: Into<Row<'a>>,
+        V: Into<Row<'a>>,
+    {
+        self.and_where(columns.into().less_than(values.into()))
+    }
+
+    /// Applies a [Scope](struct.Scope.html), combining it with the previous
+    /// conditions using `AND`.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// # #[derive(Debug, Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     #[column(primary_key)]
+    /// #     id: i64,
+    /// #     name: String,
+    /// #     banned: bool,
+    /// #     deleted_at: Option<String>,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table())
+    ///     .so_that(User::name.equals("bar"))
+    ///     .scope(User::deleted_at.is_null());
+    ///
+    /// let (sql, _) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE (`users`.`name` = ? AND `users`.`deleted_at` IS NULL)", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scope<S>(mut self, scope: S) -> Self
+    where
+        S: Into<Scope<'a>>,
+    {
+        self.conditions = Some(scope.into().apply(self.conditions.take()));
+        self
+    }
+
     /// Adds an additional `WHERE` condition to the query combining the possible
     /// previous condition with `OR`. See
     /// [Comparable](trait.Comparable.html#required-methods) for more examples.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users")
-    ///     .so_that("foo".equals("bar"))
-    ///     .or_where("lol".equals("wtf"));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// #     lol: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table())
+    ///     .so_that(User::foo.equals("bar"))
+    ///     .or_where(User::lol.equals("wtf"));
     ///
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` WHERE (`foo` = ? OR `lol` = ?)", sql);
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE (`users`.`foo` = ? OR `users`.`lol` = ?)", sql);
     ///
     /// assert_eq!(vec![
     ///    Value::from("bar"),
@@ -337,10 +610,12 @@ impl<'a> Select<'a> {
     /// Adds `INNER JOIN` clause to the query.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join = "posts".alias("p").on(("p", "user_id").equals(Column::from(("users", "id"))));
-    /// let query = Select::from_table("users").inner_join(join);
+    /// # use entities::{Post, User};
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let posts = Post::table().alias("p");
+    /// let join = posts.clone().on(posts.col(Post::user_id).equals(User::id));
+    /// let query = Select::from_table(User::table()).inner_join(join);
     /// let (sql, _) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
@@ -361,10 +636,18 @@ impl<'a> Select<'a> {
     /// Adds `LEFT JOIN` clause to the query.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join = "posts".alias("p").on(("p", "visible").equals(true));
-    /// let query = Select::from_table("users").left_join(join);
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "posts"]
+    /// # struct Post {
+    /// #     id: i32,
+    /// #     visible: bool,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let posts = Post::table().alias("p");
+    /// let join = posts.clone().on(posts.col(Post::visible).equals(true));
+    /// let query = Select::from_table(User::table()).left_join(join);
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
@@ -392,10 +675,18 @@ impl<'a> Select<'a> {
     /// Adds `RIGHT JOIN` clause to the query.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join = "posts".alias("p").on(("p", "visible").equals(true));
-    /// let query = Select::from_table("users").right_join(join);
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "posts"]
+    /// # struct Post {
+    /// #     id: i32,
+    /// #     visible: bool,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let posts = Post::table().alias("p");
+    /// let join = posts.clone().on(posts.col(Post::visible).equals(true));
+    /// let query = Select::from_table(User::table()).right_join(join);
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
@@ -423,10 +714,18 @@ impl<'a> Select<'a> {
     /// Adds `FULL JOIN` clause to the query.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join = "posts".alias("p").on(("p", "visible").equals(true));
-    /// let query = Select::from_table("users").full_join(join);
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "posts"]
+    /// # struct Post {
+    /// #     id: i32,
+    /// #     visible: bool,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let posts = Post::table().alias("p");
+    /// let join = posts.clone().on(posts.col(Post::visible).equals(true));
+    /// let query = Select::from_table(User::table()).full_join(join);
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
@@ -454,18 +753,30 @@ impl<'a> Select<'a> {
     /// Adds an ordering to the `ORDER BY` section.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users")
-    ///     .order_by("foo")
-    ///     .order_by("baz".ascend())
-    ///     .order_by("bar".descend());
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// #     bar: String,
+    /// #     baz: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table())
+    ///     .order_by(User::foo)
+    ///     .order_by(User::baz.ascend())
+    ///     .order_by(User::bar.descend());
     ///
     /// let (sql, _) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `users`.* FROM `users` ORDER BY `foo`, `baz` ASC, `bar` DESC", sql);
+    /// assert_eq!(
+    ///     "SELECT `users`.* FROM `users` ORDER BY `users`.`foo`, `users`.`baz` ASC, `users`.`bar` DESC",
+    ///     sql
+    /// );
     /// # Ok(())
     /// # }
+    /// ```
     pub fn order_by<T>(mut self, value: T) -> Self
     where
         T: IntoOrderDefinition<'a>,
@@ -479,17 +790,28 @@ impl<'a> Select<'a> {
     /// This does not check if the grouping is actually valid in respect to aggregated columns.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").column("foo").column("bar")
-    ///     .group_by("foo")
-    ///     .group_by("bar");
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: String,
+    /// #     bar: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).column(User::foo).column(User::bar)
+    ///     .group_by(User::foo)
+    ///     .group_by(User::bar);
     ///
     /// let (sql, _) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `foo`, `bar` FROM `users` GROUP BY `foo`, `bar`", sql);
+    /// assert_eq!(
+    ///     "SELECT `users`.`foo`, `users`.`bar` FROM `users` GROUP BY `users`.`foo`, `users`.`bar`",
+    ///     sql
+    /// );
     /// # Ok(())
     /// # }
+    /// ```
     pub fn group_by<T>(mut self, value: T) -> Self
     where
         T: IntoGroupByDefinition<'a>,
@@ -502,18 +824,29 @@ impl<'a> Select<'a> {
     /// [group_by](struct.Select.html#method.group_by) statement.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").column("foo").column("bar")
-    ///     .group_by("foo")
-    ///     .having("foo".greater_than(100));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// #     bar: String,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).column(User::foo).column(User::bar)
+    ///     .group_by(User::foo)
+    ///     .having(User::foo.greater_than(100));
     ///
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("SELECT `foo`, `bar` FROM `users` GROUP BY `foo` HAVING `foo` > ?", sql);
+    /// assert_eq!(
+    ///     "SELECT `users`.`foo`, `users`.`bar` FROM `users` GROUP BY `users`.`foo` HAVING `users`.`foo` > ?",
+    ///     sql
+    /// );
     /// assert_eq!(vec![Value::from(100)], params);
     /// # Ok(())
     /// # }
+    /// ```
     pub fn having<T>(mut self, conditions: T) -> Self
     where
         T: Into<ConditionTree<'a>>,
@@ -525,15 +858,17 @@ impl<'a> Select<'a> {
     /// Sets the `LIMIT` value.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").limit(10);
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).limit(10);
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!("SELECT `users`.* FROM `users` LIMIT ?", sql);
     /// assert_eq!(vec![Value::from(10)], params);
     /// # Ok(())
     /// # }
+    /// ```
     pub fn limit(mut self, limit: usize) -> Self {
         self.limit = Some(Value::from(limit));
         self
@@ -542,37 +877,142 @@ impl<'a> Select<'a> {
     /// Sets the `OFFSET` value.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Select::from_table("users").offset(10);
+    /// # use entities::User;
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).offset(10);
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!("SELECT `users`.* FROM `users` LIMIT ? OFFSET ?", sql);
     /// assert_eq!(vec![Value::from(-1), Value::from(10)], params);
     /// # Ok(())
     /// # }
+    /// ```
     pub fn offset(mut self, offset: usize) -> Self {
         self.offset = Some(Value::from(offset));
         self
     }
 
+    /// Locks the selected rows exclusively with `FOR UPDATE`.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Mysql};
+    /// # #[derive(Debug, Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     #[column(primary_key)]
+    /// #     id: i64,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::id.equals(1)).for_update();
+    /// let (sql, _) = Mysql::build(query)?;
+    ///
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`id` = ? FOR UPDATE", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn for_update(mut self) -> Self {
+        self.locking.update = true;
+        self
+    }
+
+    /// Locks the selected rows with a shared lock, `FOR SHARE`.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Mysql};
+    /// # #[derive(Debug, Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     #[column(primary_key)]
+    /// #     id: i64,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).for_share();
+    /// let (sql, _) = Mysql::build(query)?;
+    ///
+    /// assert_eq!("SELECT `users`.* FROM `users` FOR SHARE", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn for_share(mut self) -> Self {
+        self.locking.share = true;
+        self
+    }
+
+    /// Skips the rows already locked by others. Needs either
+    /// [`for_update`](#method.for_update) or [`for_share`](#method.for_share).
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Mysql};
+    /// # #[derive(Debug, Entity)]
+    /// # #[tablename = "jobs"]
+    /// # struct Job {
+    /// #     #[column(primary_key)]
+    /// #     id: i64,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(Job::table()).limit(1).for_update().skip_locked();
+    /// let (sql, _) = Mysql::build(query)?;
+    ///
+    /// assert_eq!("SELECT `jobs`.* FROM `jobs` LIMIT ? FOR UPDATE SKIP LOCKED", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn skip_locked(mut self) -> Self {
+        self.locking.skip_locked = true;
+        self
+    }
+
+    /// Fails instead of waiting for the rows already locked by others. Needs
+    /// either [`for_update`](#method.for_update) or [`for_share`](#method.for_share).
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Mysql};
+    /// # #[derive(Debug, Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     #[column(primary_key)]
+    /// #     id: i64,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).for_update().nowait();
+    /// let (sql, _) = Mysql::build(query)?;
+    ///
+    /// assert_eq!("SELECT `users`.* FROM `users` FOR UPDATE NOWAIT", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn nowait(mut self) -> Self {
+        self.locking.nowait = true;
+        self
+    }
+
     /// Adds a common table expression to the select.
     ///
     /// ```rust
-    /// # use xiayu::{val, ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # use xiayu::{val, prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "one"]
+    /// # struct One {
+    /// #     val: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
     /// let cte = Select::default()
     ///     .value(val!(1).alias("val"))
     ///     .into_cte("one")
-    ///     .column("val");
+    ///     .column(Column::new("val"));
     ///
-    /// let query = Select::from_table("one")
-    ///     .column("val")
+    /// let query = Select::from_table(One::table())
+    ///     .column(One::val)
     ///     .with(cte);
     ///
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("WITH `one` (`val`) AS (SELECT ? AS `val`) SELECT `val` FROM `one`", sql);
+    /// assert_eq!("WITH `one` (`val`) AS (SELECT ? AS `val`) SELECT `one`.`val` FROM `one`", sql);
     /// assert_eq!(vec![Value::from(1)], params);
     /// # Ok(())
     /// # }
@@ -589,6 +1029,77 @@ impl<'a> Select<'a> {
         self
     }
 
+    /// An `INSERT INTO .. SELECT` of the rows of the select into the table of
+    /// `T`, every pair of the `mapping` a column of `T` and the expression
+    /// selected for it. The select selects nothing else: one already
+    /// selecting columns fails with
+    /// [`ColumnCountMismatch`](crate::error::ErrorKind::ColumnCountMismatch),
+    /// as does an empty mapping.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Sqlite, Visitor}};
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "orders"]
+    /// struct Order {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     total: f64,
+    ///     placed_at: String,
+    /// }
+    ///
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "archived_orders"]
+    /// struct ArchivedOrder {
+    ///     #[column(primary_key)]
+    ///     order_id: i64,
+    ///     amount: f64,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let insert = Select::from_table(Order::table())
+    ///     .so_that(Order::placed_at.less_than("2020-01-01"))
+    ///     .insert_into::<ArchivedOrder, _, _, _>(vec![
+    ///         (ArchivedOrder::order_id.c(), Expression::from(Order::id)),
+    ///         (ArchivedOrder::amount.c(), Order::total.into()),
+    ///     ])?;
+    /// let (sql, _) = Sqlite::build(insert)?;
+    ///
+    /// assert_eq!(
+    ///     "INSERT INTO `archived_orders` (`order_id`, `amount`) SELECT `orders`.`id`, `orders`.`total` FROM `orders` WHERE `orders`.`placed_at` < ?",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn insert_into<T, I, C, E>(mut self, mapping: I) -> crate::Result<Insert<'a>>
+    where
+        T: crate::prelude::Entity,
+        I: IntoIterator<Item = (C, E)>,
+        C: Into<Column<'a>>,
+        E: Into<Expression<'a>>,
+    {
+        let selected = self.columns.len();
+        let mut columns = Vec::new();
+        for (column, expression) in mapping {
+            columns.push(column.into());
+            self.columns.push(expression.into());
+        }
+
+        if selected > 0 || columns.is_empty() {
+            let kind = crate::error::ErrorKind::ColumnCountMismatch {
+                columns: columns.len(),
+                values: self.columns.len(),
+            };
+            return Err(crate::error::Error::builder(kind).build());
+        }
+        if self.sql.is_some() {
+            let kind = crate::error::ErrorKind::conversion("a select written as is can not be mapped to columns");
+            return Err(crate::error::Error::builder(kind).build());
+        }
+
+        Ok(Insert::expression_into(T::table(), columns, self))
+    }
+
     /// Traverse the condition tree, looking for a comparison where the left
     /// side is a tuple and the right side a nested `SELECT` in an `IN` or `NOT
     /// IN` operation; converting it to a common table expression.
@@ -649,6 +1160,13 @@ impl<'a> Select<'a> {
         }
     }
 
+    pub(crate) fn simplify(mut self) -> Self {
+        self.conditions = simplify_conditions(self.conditions);
+        self.having = simplify_conditions(self.having);
+        self.joins = self.joins.into_iter().map(Join::simplify).collect();
+        self
+    }
+
     /// A list of item names in the query, skipping the anonymous values or
     /// columns.
     pub(crate) fn named_selection(&self) -> Vec<String> {
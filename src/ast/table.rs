@@ -20,6 +20,7 @@ pub trait Aliasable<'a> {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 /// Either an identifier or a nested query.
 pub enum TableType<'a> {
     Table(Cow<'static, str>),
@@ -30,11 +31,14 @@ pub enum TableType<'a> {
 
 /// A table definition
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table<'a> {
     pub typ: TableType<'a>,
     pub alias: Option<Cow<'a, str>>,
     pub database: Option<Cow<'a, str>>,
     pub index_definitions: Vec<IndexDefinition<'a>>,
+    /// Names of the columns of a derived table, written after its alias.
+    pub column_aliases: Vec<Cow<'a, str>>,
 }
 
 impl<'a> PartialEq for Table<'a> {
@@ -53,6 +57,69 @@ impl<'a> Table<'a> {
         self
     }
 
+    /// Alias the table and name its columns, `AS alias(a, b)`. Needed for
+    /// a `VALUES` table on SQL Server, which has no default column names.
+    ///
+    /// ```rust
+    /// # use xiayu::{ast::*, values, visitors::{Visitor, Mysql}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let values = Table::from(values!((1, 2), (3, 4))).alias_with_columns("vals", ["a", "b"]);
+    /// let query = Select::from_table(values);
+    /// let (sql, _) = Mysql::build(query)?;
+    ///
+    /// assert_eq!(
+    ///     "SELECT `vals`.* FROM (VALUES ROW(?,?),ROW(?,?)) AS `vals`(`a`, `b`)",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn alias_with_columns<A, I, C>(mut self, alias: A, columns: I) -> Self
+    where
+        A: Into<Cow<'a, str>>,
+        I: IntoIterator<Item = C>,
+        C: Into<Cow<'a, str>>,
+    {
+        self.alias = Some(alias.into());
+        self.column_aliases = columns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// `column` qualified with this table, by its alias when it has one.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     invited_by: Option<i64>,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let invitee = User::table().alias("invitee");
+    /// let inviter = User::table().alias("inviter");
+    /// let query = Select::from_table(invitee.clone())
+    ///     .inner_join(inviter.clone().on(invitee.col(User::invited_by).equals(inviter.col(User::id))))
+    ///     .column(inviter.col(User::id));
+    /// let (sql, _) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!(
+    ///     "SELECT `inviter`.`id` FROM `users` AS `invitee` INNER JOIN `users` AS `inviter` ON `invitee`.`invited_by` = `inviter`.`id`",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn col<C>(&self, column: C) -> Column<'a>
+    where
+        C: Into<Column<'a>>,
+    {
+        column.into().table(self.clone())
+    }
+
     /// A qualified asterisk to this table
     pub fn asterisk(self) -> Expression<'a> {
         Expression {
@@ -85,8 +152,10 @@ impl<'a> Table<'a> {
 
         let join_cond = |column: &Column<'a>| {
             let cond = if !inserted_columns.contains(column) {
-                match column.default.clone() {
-                    Some(DefaultValue::Provided(val)) => Some(column.clone().equals(val).into()),
+                match &column.default {
+                    Some(default @ (DefaultValue::Provided(_) | DefaultValue::Computed(_))) => {
+                        default.value().map(|val| column.clone().equals(val).into())
+                    }
                     Some(DefaultValue::Generated) => None,
                     None => {
                         let kind = ErrorKind::conversion(
@@ -102,6 +171,7 @@ impl<'a> Table<'a> {
                     alias: None,
                     database: None,
                     index_definitions: Vec::new(),
+                    column_aliases: Vec::new(),
                 };
                 let dual_col = column.clone().table(table);
                 Some(dual_col.equals(column.clone()).into())
@@ -148,11 +218,28 @@ impl<'a> Table<'a> {
     /// Useful to positionally add a JOIN clause in case you are selecting from multiple tables.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join = "posts".alias("p").on(("p", "visible").equals(true));
-    /// let joined_table = Table::from("users").left_join(join);
-    /// let query = Select::from_table(joined_table).and_from("comments");
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// # }
+    /// # #[derive(Entity)]
+    /// # #[tablename = "posts"]
+    /// # struct Post {
+    /// #     id: i32,
+    /// #     visible: bool,
+    /// # }
+    /// # #[derive(Entity)]
+    /// # #[tablename = "comments"]
+    /// # struct Comment {
+    /// #     id: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let posts = Post::table().alias("p");
+    /// let join = posts.clone().on(posts.col(Post::visible).equals(true));
+    /// let joined_table = User::table().left_join(join);
+    /// let query = Select::from_table(joined_table).and_from(Comment::table());
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
@@ -198,11 +285,28 @@ impl<'a> Table<'a> {
     /// Useful to positionally add a JOIN clause in case you are selecting from multiple tables.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join = "posts".alias("p").on(("p", "visible").equals(true));
-    /// let joined_table = Table::from("users").inner_join(join);
-    /// let query = Select::from_table(joined_table).and_from("comments");
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// # }
+    /// # #[derive(Entity)]
+    /// # #[tablename = "posts"]
+    /// # struct Post {
+    /// #     id: i32,
+    /// #     visible: bool,
+    /// # }
+    /// # #[derive(Entity)]
+    /// # #[tablename = "comments"]
+    /// # struct Comment {
+    /// #     id: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let posts = Post::table().alias("p");
+    /// let join = posts.clone().on(posts.col(Post::visible).equals(true));
+    /// let joined_table = User::table().inner_join(join);
+    /// let query = Select::from_table(joined_table).and_from(Comment::table());
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
@@ -246,11 +350,28 @@ impl<'a> Table<'a> {
     /// Useful to positionally add a JOIN clause in case you are selecting from multiple tables.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join = "posts".alias("p").on(("p", "visible").equals(true));
-    /// let joined_table = Table::from("users").right_join(join);
-    /// let query = Select::from_table(joined_table).and_from("comments");
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// # }
+    /// # #[derive(Entity)]
+    /// # #[tablename = "posts"]
+    /// # struct Post {
+    /// #     id: i32,
+    /// #     visible: bool,
+    /// # }
+    /// # #[derive(Entity)]
+    /// # #[tablename = "comments"]
+    /// # struct Comment {
+    /// #     id: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let posts = Post::table().alias("p");
+    /// let join = posts.clone().on(posts.col(Post::visible).equals(true));
+    /// let joined_table = User::table().right_join(join);
+    /// let query = Select::from_table(joined_table).and_from(Comment::table());
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
@@ -294,11 +415,28 @@ impl<'a> Table<'a> {
     /// Useful to positionally add a JOIN clause in case you are selecting from multiple tables.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let join = "posts".alias("p").on(("p", "visible").equals(true));
-    /// let joined_table = Table::from("users").full_join(join);
-    /// let query = Select::from_table(joined_table).and_from("comments");
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// # }
+    /// # #[derive(Entity)]
+    /// # #[tablename = "posts"]
+    /// # struct Post {
+    /// #     id: i32,
+    /// #     visible: bool,
+    /// # }
+    /// # #[derive(Entity)]
+    /// # #[tablename = "comments"]
+    /// # struct Comment {
+    /// #     id: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let posts = Post::table().alias("p");
+    /// let join = posts.clone().on(posts.col(Post::visible).equals(true));
+    /// let joined_table = User::table().full_join(join);
+    /// let query = Select::from_table(joined_table).and_from(Comment::table());
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
@@ -349,6 +487,7 @@ where
             alias: None,
             database: None,
             index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
         }
     }
 }
@@ -366,6 +505,7 @@ impl<'a> From<Values<'a>> for Table<'a> {
             alias: None,
             database: None,
             index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
         }
     }
 }
@@ -377,6 +517,7 @@ impl<'a> From<Select<'a>> for Table<'a> {
             alias: None,
             database: None,
             index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
         }
     }
 }
@@ -5,6 +5,7 @@ use super::CommonTableExpression;
 use super::IntoCommonTableExpression;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) enum UnionType {
     All,
     Distinct,
@@ -21,6 +22,7 @@ impl fmt::Display for UnionType {
 
 /// A builder for a `UNION`s over multiple `SELECT` statements.
 #[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Union<'a> {
     pub(crate) selects: Vec<Select<'a>>,
     pub(crate) types: Vec<UnionType>,
@@ -53,7 +55,7 @@ impl<'a> Union<'a> {
     ///
     /// ```rust
     /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// let s1 = Select::default().value(1);
     /// let s2 = Select::default().value(2);
     /// let (sql, params) = Sqlite::build(Union::new(s1).all(s2))?;
@@ -78,7 +80,7 @@ impl<'a> Union<'a> {
     ///
     /// ```rust
     /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
+    /// # fn main() -> xiayu::Result<()> {
     /// let s1 = Select::default().value(1);
     /// let s2 = Select::default().value(2);
     /// let (sql, params) = Sqlite::build(Union::new(s1).distinct(s2))?;
@@ -98,6 +100,11 @@ impl<'a> Union<'a> {
         self
     }
 
+    pub(crate) fn simplify(mut self) -> Self {
+        self.selects = self.selects.into_iter().map(Select::simplify).collect();
+        self
+    }
+
     /// A list of item names in the queries, skipping the anonymous values or
     /// columns.
     pub(crate) fn named_selection(&self) -> Vec<String> {
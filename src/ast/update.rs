@@ -2,6 +2,7 @@ use crate::ast::*;
 
 /// A builder for an `UPDATE` statement.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Update<'a> {
     pub(crate) table: Table<'a>,
     pub(crate) columns: Vec<Column<'a>>,
@@ -32,9 +33,16 @@ impl<'a> Update<'a> {
     /// Add another column value assignment to the query
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Update::table("users").set("foo", 10).set("bar", false);
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// #     bar: bool,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Update::table(User::table()).set(User::foo, 10).set(User::bar, false);
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!("UPDATE `users` SET `foo` = ?, `bar` = ?", sql);
@@ -64,12 +72,19 @@ impl<'a> Update<'a> {
     /// [Comparable](trait.Comparable.html#required-methods) for more examples.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let query = Update::table("users").set("foo", 1).so_that("bar".equals(false));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// #     bar: bool,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Update::table(User::table()).set(User::foo, 1).so_that(User::bar.equals(false));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
-    /// assert_eq!("UPDATE `users` SET `foo` = ? WHERE `bar` = ?", sql);
+    /// assert_eq!("UPDATE `users` SET `foo` = ? WHERE `users`.`bar` = ?", sql);
     ///
     /// assert_eq!(
     ///     vec![
@@ -85,14 +100,27 @@ impl<'a> Update<'a> {
     /// We can also use a nested `SELECT` in the conditions.
     ///
     /// ```rust
-    /// # use xiayu::{ast::*, visitors::{Visitor, Sqlite}};
-    /// # fn main() -> Result<(), xiayu::error::Error> {
-    /// let select = Select::from_table("bars").column("id").so_that("uniq_val".equals(3));
-    /// let query = Update::table("users").set("foo", 1).so_that("bar".equals(select));
+    /// # use xiayu::{prelude::*, visitors::{Visitor, Sqlite}};
+    /// # #[derive(Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     id: i32,
+    /// #     foo: i32,
+    /// #     bar_id: i32,
+    /// # }
+    /// # #[derive(Entity)]
+    /// # #[tablename = "bars"]
+    /// # struct Bar {
+    /// #     id: i32,
+    /// #     uniq_val: i32,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let select = Select::from_table(Bar::table()).column(Bar::id).so_that(Bar::uniq_val.equals(3));
+    /// let query = Update::table(User::table()).set(User::foo, 1).so_that(User::bar_id.equals(select));
     /// let (sql, params) = Sqlite::build(query)?;
     ///
     /// assert_eq!(
-    ///     "UPDATE `users` SET `foo` = ? WHERE `bar` = (SELECT `id` FROM `bars` WHERE `uniq_val` = ?)",
+    ///     "UPDATE `users` SET `foo` = ? WHERE `users`.`bar_id` = (SELECT `bars`.`id` FROM `bars` WHERE `bars`.`uniq_val` = ?)",
     ///     sql
     /// );
     ///
@@ -113,4 +141,40 @@ impl<'a> Update<'a> {
         self.conditions = Some(conditions.into());
         self
     }
+
+    /// Applies a [Scope](struct.Scope.html), combining it with the previous
+    /// conditions using `AND`.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// # #[derive(Debug, Entity)]
+    /// # #[tablename = "users"]
+    /// # struct User {
+    /// #     #[column(primary_key)]
+    /// #     id: i64,
+    /// #     name: String,
+    /// #     banned: bool,
+    /// #     deleted_at: Option<String>,
+    /// # }
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Update::table(User::table()).set(User::name, "musti").scope(User::deleted_at.is_null());
+    /// let (sql, _) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!("UPDATE `users` SET `name` = ? WHERE `users`.`deleted_at` IS NULL", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scope<S>(mut self, scope: S) -> Self
+    where
+        S: Into<Scope<'a>>,
+    {
+        self.conditions = Some(scope.into().apply(self.conditions.take()));
+        self
+    }
+
+    pub(crate) fn simplify(mut self) -> Self {
+        self.conditions = simplify_conditions(self.conditions);
+        self
+    }
 }
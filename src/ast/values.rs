@@ -19,6 +19,7 @@ use crate::error::{Error, ErrorKind};
 
 /// A value written to the query as-is without parameterization.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Raw<'a>(pub(crate) Value<'a>);
 
 /// Converts the value into a state to skip parameterization.
@@ -56,13 +57,16 @@ trait PgCompatibleType:
 // }
 
 #[cfg(feature = "json")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Json<'a> {
     // #[cfg(feature = "postgres")]
-    // #[cfg_attr(feature = "docs", doc(cfg(feature = "postgres")))]
+    // #[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
     // Json(Option<sqlx::types::Json<Box<Value<'a>>>>),
     JsonValue(Option<JsonValue>),
+    /// Borrowed from the input, can not be serialized.
+    #[cfg_attr(feature = "ast-serde", serde(skip))]
     JsonRawValue(Option<JsonRawValue<'a>>),
 }
 
@@ -111,9 +115,11 @@ impl<'a> Json<'a> {
     }
 }
 
+#[cfg(feature = "json")]
 #[derive(Debug, Clone, AsRef, Deref)]
 pub struct JsonRawValue<'a>(&'a serde_json::value::RawValue);
 
+#[cfg(feature = "json")]
 impl<'a> PartialEq for JsonRawValue<'a> {
     fn eq(&self, other: &Self) -> bool {
         self.0.get() == other.0.get()
@@ -123,7 +129,11 @@ impl<'a> PartialEq for JsonRawValue<'a> {
 /// A value we must parameterize for the prepared statement. Null values should be
 /// defined by their corresponding type variants with a `None` value for best
 /// compatibility.
+///
+/// With `ast-serde`, serializing a PostgreSQL interval or money, a big decimal
+/// or a raw JSON value is an error.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Value<'a> {
     /// 64-bit signed integer.
     Integer(Option<i64>),
@@ -147,92 +157,85 @@ pub enum Value<'a> {
     Enum(Option<Cow<'a, str>>),
     /// A single character.
     Char(Option<char>),
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "postgres")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
     /// An array value (PostgreSQL).
     Array(Option<Vec<Value<'a>>>),
     /// A numeric value.
     #[cfg(feature = "bigdecimal")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "bigdecimal")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
+    #[cfg_attr(feature = "ast-serde", serde(skip))]
     Numeric(Option<BigDecimal>),
     /// A XML value.
     Xml(Option<Cow<'a, str>>),
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     /// A datetime value.
     DateTime(Option<DateTime<Utc>>),
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     /// A date value.
     Date(Option<NaiveDate>),
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     /// A time value.
     Time(Option<NaiveTime>),
 
     #[cfg(feature = "json")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     /// A JSON value.
     Json(Json<'a>),
     #[cfg(feature = "uuid")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "uuid")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
     /// An UUID value.
     Uuid(Option<Uuid>),
 
     #[cfg(feature = "postgres")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "postgres")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
     /// INTERVAL
+    #[cfg_attr(feature = "ast-serde", serde(skip))]
     PgInterval(Option<sqlx::postgres::types::PgInterval>),
     /*
     #[cfg(feature = "postgres")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "postgres")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
     /// INT8RANGE, INT4RANGE, TSRANGE, TSTZTRANGE, DATERANGE, NUMRANGE
     PgRange(Option<sqlx::postgres::types::PgRange<Box<Value<'a>>>>),
     */
     #[cfg(feature = "postgres")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "postgres")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
     /// INT8RANGE, INT4RANGE, TSRANGE, TSTZTRANGE, DATERANGE, NUMRANGE
+    #[cfg_attr(feature = "ast-serde", serde(skip))]
     PgMoney(Option<sqlx::postgres::types::PgMoney>),
 
     /// A numeric value.
     #[cfg(feature = "bigdecimal")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "bigdecimal")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
+    #[cfg_attr(feature = "ast-serde", serde(skip))]
     BigDecimal(Option<sqlx::types::BigDecimal>),
     /// A numeric value.
     #[cfg(feature = "decimal")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "decimal")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "decimal")))]
     Decimal(Option<sqlx::types::Decimal>),
 
     /// TIMESTAMPTZ
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     UtcDateTime(Option<DateTime<Utc>>),
     /// TIMESTAMPTZ
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     LocalDateTime(Option<DateTime<Local>>),
     /// TIMESTAMP
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     NaiveDateTime(Option<NaiveDateTime>),
     /// DATE
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     NaiveDate(Option<NaiveDate>),
     /// TIME
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     NaiveTime(Option<NaiveTime>),
-    /// TIMETZ
-    #[cfg(all(feature = "time", feature = "postgres"))]
-    #[cfg_attr(
-        feature = "docs",
-        doc(cfg(all(feature = "time", feature = "postgres")))
-    )]
-    PgTimeTz(Option<sqlx::postgres::types::PgTimeTz>),
-    #[cfg(feature = "ipnetwork")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "ipnetwork")))]
-    /// INET, CIDR
-    IpNetwork(Option<sqlx::types::IpNetwork>),
 }
 
 pub(crate) struct Params<'a>(pub(crate) &'a [Value<'a>]);
@@ -304,7 +307,7 @@ impl<'a> fmt::Display for Value<'a> {
 }
 
 #[cfg(feature = "json")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 impl<'a> From<Value<'a>> for serde_json::Value {
     fn from(pv: Value<'a>) -> Self {
         let res = match pv {
@@ -370,7 +373,7 @@ impl<'a> Value<'a> {
 
     /// Creates a new decimal value.
     #[cfg(feature = "bigdecimal")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "bigdecimal")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
     pub const fn numeric(value: BigDecimal) -> Self {
         Value::Numeric(Some(value))
     }
@@ -436,35 +439,35 @@ impl<'a> Value<'a> {
 
     /// Creates a new uuid value.
     #[cfg(feature = "uuid")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "uuid")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
     pub const fn uuid(value: Uuid) -> Self {
         Value::Uuid(Some(value))
     }
 
     /// Creates a new datetime value.
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub const fn datetime(value: DateTime<Utc>) -> Self {
         Value::DateTime(Some(value))
     }
 
     /// Creates a new date value.
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub const fn date(value: NaiveDate) -> Self {
         Value::Date(Some(value))
     }
 
     /// Creates a new time value.
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub const fn time(value: NaiveTime) -> Self {
         Value::Time(Some(value))
     }
 
     /// Creates a new JSON value.
     #[cfg(feature = "json")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     pub const fn json(value: Json<'a>) -> Self {
         Value::Json(value)
     }
@@ -477,6 +480,65 @@ impl<'a> Value<'a> {
         Value::Xml(Some(value.into()))
     }
 
+    /// Copies borrowed text, bytes and raw JSON, detaching the value from
+    /// the lifetime of the query it was taken from.
+    pub fn into_owned(self) -> Value<'static> {
+        fn owned<T: ToOwned + ?Sized>(cow: Option<Cow<'_, T>>) -> Option<Cow<'static, T>> {
+            cow.map(|cow| Cow::Owned(cow.into_owned()))
+        }
+
+        match self {
+            Value::Integer(v) => Value::Integer(v),
+            Value::I8(v) => Value::I8(v),
+            Value::I16(v) => Value::I16(v),
+            Value::I32(v) => Value::I32(v),
+            Value::I64(v) => Value::I64(v),
+            Value::Float(v) => Value::Float(v),
+            Value::Double(v) => Value::Double(v),
+            Value::Text(v) => Value::Text(owned(v)),
+            Value::Bytes(v) => Value::Bytes(owned(v)),
+            Value::Boolean(v) => Value::Boolean(v),
+            Value::Enum(v) => Value::Enum(owned(v)),
+            Value::Char(v) => Value::Char(v),
+            Value::Array(v) => Value::Array(v.map(|v| v.into_iter().map(Value::into_owned).collect())),
+            #[cfg(feature = "bigdecimal")]
+            Value::Numeric(v) => Value::Numeric(v),
+            Value::Xml(v) => Value::Xml(owned(v)),
+            #[cfg(feature = "chrono")]
+            Value::DateTime(v) => Value::DateTime(v),
+            #[cfg(feature = "chrono")]
+            Value::Date(v) => Value::Date(v),
+            #[cfg(feature = "chrono")]
+            Value::Time(v) => Value::Time(v),
+            #[cfg(feature = "json")]
+            Value::Json(Json::JsonValue(v)) => Value::Json(Json::JsonValue(v)),
+            #[cfg(feature = "json")]
+            Value::Json(Json::JsonRawValue(v)) => Value::Json(Json::JsonValue(
+                v.and_then(|raw| serde_json::from_str(raw.get()).ok()),
+            )),
+            #[cfg(feature = "uuid")]
+            Value::Uuid(v) => Value::Uuid(v),
+            #[cfg(feature = "postgres")]
+            Value::PgInterval(v) => Value::PgInterval(v),
+            #[cfg(feature = "postgres")]
+            Value::PgMoney(v) => Value::PgMoney(v),
+            #[cfg(feature = "bigdecimal")]
+            Value::BigDecimal(v) => Value::BigDecimal(v),
+            #[cfg(feature = "decimal")]
+            Value::Decimal(v) => Value::Decimal(v),
+            #[cfg(feature = "chrono")]
+            Value::UtcDateTime(v) => Value::UtcDateTime(v),
+            #[cfg(feature = "chrono")]
+            Value::LocalDateTime(v) => Value::LocalDateTime(v),
+            #[cfg(feature = "chrono")]
+            Value::NaiveDateTime(v) => Value::NaiveDateTime(v),
+            #[cfg(feature = "chrono")]
+            Value::NaiveDate(v) => Value::NaiveDate(v),
+            #[cfg(feature = "chrono")]
+            Value::NaiveTime(v) => Value::NaiveTime(v),
+        }
+    }
+
     /// `true` if the `Value` is null.
     pub const fn is_null(&self) -> bool {
         match self {
@@ -601,7 +663,7 @@ impl<'a> Value<'a> {
 
     /// `true` if the `Value` is a numeric value or can be converted to one.
     #[cfg(feature = "bigdecimal")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "bigdecimal")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
     pub const fn is_numeric(&self) -> bool {
         matches!(self, Value::Numeric(_) | Value::Float(_) | Value::Double(_))
     }
@@ -609,7 +671,7 @@ impl<'a> Value<'a> {
     /// Returns a bigdecimal, if the value is a numeric, float or double value,
     /// otherwise `None`.
     #[cfg(feature = "bigdecimal")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "bigdecimal")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
     pub fn into_numeric(self) -> Option<BigDecimal> {
         match self {
             Value::Numeric(d) => d,
@@ -622,7 +684,7 @@ impl<'a> Value<'a> {
     /// Returns a reference to a bigdecimal, if the value is a numeric.
     /// Otherwise `None`.
     #[cfg(feature = "bigdecimal")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "bigdecimal")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
     pub const fn as_numeric(&self) -> Option<&BigDecimal> {
         match self {
             Value::Numeric(d) => d.as_ref(),
@@ -657,14 +719,14 @@ impl<'a> Value<'a> {
 
     /// `true` if the `Value` is of UUID type.
     #[cfg(feature = "uuid")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "uuid")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
     pub const fn is_uuid(&self) -> bool {
         matches!(self, Value::Uuid(_))
     }
 
     /// Returns an UUID if the value is of UUID type, otherwise `None`.
     #[cfg(feature = "uuid")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "uuid")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
     pub const fn as_uuid(&self) -> Option<Uuid> {
         match self {
             Value::Uuid(u) => *u,
@@ -674,14 +736,14 @@ impl<'a> Value<'a> {
 
     /// `true` if the `Value` is a DateTime.
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub const fn is_datetime(&self) -> bool {
         matches!(self, Value::DateTime(_))
     }
 
     /// Returns a `DateTime` if the value is a `DateTime`, otherwise `None`.
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub const fn as_datetime(&self) -> Option<DateTime<Utc>> {
         match self {
             Value::DateTime(dt) => *dt,
@@ -691,14 +753,14 @@ impl<'a> Value<'a> {
 
     /// `true` if the `Value` is a Date.
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub const fn is_date(&self) -> bool {
         matches!(self, Value::Date(_))
     }
 
     /// Returns a `NaiveDate` if the value is a `Date`, otherwise `None`.
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub const fn as_date(&self) -> Option<NaiveDate> {
         match self {
             Value::Date(dt) => *dt,
@@ -708,14 +770,14 @@ impl<'a> Value<'a> {
 
     /// `true` if the `Value` is a `Time`.
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub const fn is_time(&self) -> bool {
         matches!(self, Value::Time(_))
     }
 
     /// Returns a `NaiveTime` if the value is a `Time`, otherwise `None`.
     #[cfg(feature = "chrono")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
     pub const fn as_time(&self) -> Option<NaiveTime> {
         match self {
             Value::Time(time) => *time,
@@ -725,14 +787,14 @@ impl<'a> Value<'a> {
 
     /// `true` if the `Value` is a JSON value.
     #[cfg(feature = "json")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     pub const fn is_json(&self) -> bool {
         matches!(self, Value::Json(_))
     }
 
     /// Returns a reference to a JSON Value if of Json type, otherwise `None`.
     #[cfg(feature = "json")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     pub const fn as_json(&self) -> Option<&serde_json::Value> {
         match self {
             Value::Json(Json::JsonValue(Some(j))) => Some(j),
@@ -742,7 +804,7 @@ impl<'a> Value<'a> {
 
     /// Transforms to a JSON Value if of Json type, otherwise `None`.
     #[cfg(feature = "json")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "json")))]
     pub fn into_json(self) -> Option<serde_json::Value> {
         match self {
             Value::Json(Json::JsonValue(Some(j))) => Some(j),
@@ -777,28 +839,31 @@ value!(val: String, Text, val.into());
 value!(val: usize, Integer, i64::try_from(val).unwrap());
 value!(val: i32, Integer, i64::try_from(val).unwrap());
 value!(val: &'a [u8], Bytes, val.into());
+value!(val: Vec<u8>, Bytes, val.into());
+value!(val: Cow<'a, str>, Text, val);
+value!(val: Cow<'a, [u8]>, Bytes, val);
 value!(val: f64, Double, val);
 value!(val: f32, Float, val);
 
 #[cfg(feature = "chrono")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
 value!(val: DateTime<Utc>, DateTime, val);
 #[cfg(feature = "chrono")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
 value!(val: chrono::NaiveTime, Time, val);
 #[cfg(feature = "chrono")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
 value!(val: chrono::NaiveDate, Date, val);
 #[cfg(feature = "bigdecimal")]
 value!(val: BigDecimal, Numeric, val);
 #[cfg(feature = "json")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 impl_value_from_json!(val: JsonValue, JsonValue, val);
 #[cfg(feature = "json")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 impl_value_from_json!(val: JsonRawValue<'a>, JsonRawValue, val);
 #[cfg(feature = "uuid")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "uuid")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
 value!(val: Uuid, Uuid, val);
 
 impl<'a> TryFrom<Value<'a>> for i64 {
@@ -853,7 +918,7 @@ impl<'a> TryFrom<Value<'a>> for bool {
 }
 
 #[cfg(feature = "chrono")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "chrono")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
 impl<'a> TryFrom<Value<'a>> for DateTime<Utc> {
     type Error = Error;
 
@@ -867,26 +932,80 @@ impl<'a> TryFrom<Value<'a>> for DateTime<Utc> {
 /// An in-memory temporary table. Can be used in some of the databases in a
 /// place of an actual table. Doesn't work in MySQL 5.7.
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ast-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Values<'a> {
     pub(crate) rows: Vec<Row<'a>>,
+    /// The types of the columns, see [`typed`](Values::typed).
+    #[cfg_attr(feature = "ast-serde", serde(default))]
+    pub(crate) types: Vec<TypeFamily>,
 }
 
 impl<'a> Values<'a> {
     /// Create a new empty in-memory set of values.
     pub fn empty() -> Self {
-        Self { rows: Vec::new() }
+        Self::default()
     }
 
     /// Create a new in-memory set of values.
     pub fn new(rows: Vec<Row<'a>>) -> Self {
-        Self { rows }
+        Self {
+            rows,
+            types: Vec::new(),
+        }
     }
 
     /// Create a new in-memory set of values with an allocated capacity.
     pub fn with_capacity(capacity: usize) -> Self {
-        Self {
-            rows: Vec::with_capacity(capacity),
+        Self::new(Vec::with_capacity(capacity))
+    }
+
+    /// Types the columns of a `VALUES` table, in order, for the databases
+    /// that can not tell the type of a column of parameters. PostgreSQL
+    /// and SQL Server cast the values of the first row, the others ignore
+    /// the types.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, values};
+    /// # #[cfg(feature = "postgres")]
+    /// # use xiayu::visitors::{Visitor, Postgres};
+    /// # fn main() -> xiayu::Result<()> {
+    /// # #[cfg(feature = "postgres")]
+    /// # {
+    /// let pairs = Table::from(values!((1, "a"), (2, "b")).typed([TypeFamily::Int, TypeFamily::Text(None)]))
+    ///     .alias_with_columns("pairs", ["id", "payload"]);
+    /// let (sql, _) = Postgres::build(Select::from_table(pairs))?;
+    ///
+    /// assert_eq!(
+    ///     r#"SELECT "pairs".* FROM (VALUES ($1::bigint,$2::text),($3,$4)) AS "pairs"("id", "payload")"#,
+    ///     sql
+    /// );
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn typed<I>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = TypeFamily>,
+    {
+        self.types = types.into_iter().collect();
+        self
+    }
+
+    /// The values with the ones of the first row cast to the types of their
+    /// columns.
+    pub(crate) fn cast_first_row(self) -> Self {
+        let Values { mut rows, types } = self;
+        if let Some(first) = rows.first_mut() {
+            let mut types = types.into_iter();
+            first.values = std::mem::take(&mut first.values)
+                .into_iter()
+                .map(|value| match types.next() {
+                    Some(target) => cast(value, target),
+                    None => value,
+                })
+                .collect();
         }
+        Self::new(rows)
     }
 
     /// Add value to the temporary table.
@@ -934,9 +1053,7 @@ where
     R: Into<Row<'a>>,
 {
     fn from(rows: I) -> Self {
-        Self {
-            rows: rows.map(|r| r.into()).collect(),
-        }
+        Self::new(rows.map(|r| r.into()).collect())
     }
 }
 
@@ -0,0 +1,211 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+/// The version of the serialized AST, bumped whenever a change of the AST
+/// changes how it is serialized.
+pub const AST_VERSION: u32 = 1;
+
+/// A query, or any other part of the AST, serialized with the version of the
+/// AST. Reading a query stored by another version is an error instead of a
+/// different query.
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// # use xiayu::visitors::{Visitor, Sqlite};
+/// #[derive(Debug, Entity)]
+/// #[tablename = "users"]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i64,
+///     name: String,
+/// }
+///
+/// # fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+/// let query = Select::from_table(User::table()).so_that(User::name.equals("Ada"));
+/// let stored = serde_json::to_string(&Versioned::new(Query::from(query)))?;
+///
+/// let query: Versioned<Query> = serde_json::from_str(&stored)?;
+/// let (sql, params) = Sqlite::build(query.into_inner())?;
+/// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`name` = ?", sql);
+/// assert_eq!(vec![Value::from("Ada")], params);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Versioned<Q> {
+    query: Q,
+}
+
+impl<Q> Versioned<Q> {
+    /// Wraps `query` to be serialized with the current version.
+    pub fn new(query: Q) -> Self {
+        Self { query }
+    }
+
+    pub fn query(&self) -> &Q {
+        &self.query
+    }
+
+    pub fn into_inner(self) -> Q {
+        self.query
+    }
+}
+
+impl<Q> From<Q> for Versioned<Q> {
+    fn from(query: Q) -> Self {
+        Self::new(query)
+    }
+}
+
+impl<Q: Serialize> Serialize for Versioned<Q> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut envelope = serializer.serialize_struct("Versioned", 2)?;
+        envelope.serialize_field("version", &AST_VERSION)?;
+        envelope.serialize_field("query", &self.query)?;
+        envelope.end()
+    }
+}
+
+fn check_version<E: de::Error>(version: u32) -> Result<(), E> {
+    if version == AST_VERSION {
+        Ok(())
+    } else {
+        Err(E::custom(format_args!(
+            "a query of AST version {} can not be read by AST version {}",
+            version, AST_VERSION
+        )))
+    }
+}
+
+impl<'de, Q: Deserialize<'de>> Deserialize<'de> for Versioned<Q> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct EnvelopeVisitor<Q>(PhantomData<Q>);
+
+        impl<'de, Q: Deserialize<'de>> Visitor<'de> for EnvelopeVisitor<Q> {
+            type Value = Versioned<Q>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a versioned query")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let version: u32 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                check_version(version)?;
+                let query = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(Versioned { query })
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                // The version is checked as soon as it is read: written first
+                // it comes before a query of another version fails with a less
+                // helpful error. Stores such as `jsonb` reorder the keys
+                // though, it is then checked after the query.
+                let mut version = None;
+                let mut query = None;
+                while let Some(key) = map.next_key::<Field>()? {
+                    match key {
+                        Field::Version => {
+                            if version.is_some() {
+                                return Err(de::Error::duplicate_field("version"));
+                            }
+                            let value: u32 = map.next_value()?;
+                            check_version(value)?;
+                            version = Some(value);
+                        }
+                        Field::Query => {
+                            if query.is_some() {
+                                return Err(de::Error::duplicate_field("query"));
+                            }
+                            query = Some(map.next_value()?);
+                        }
+                    }
+                }
+                version.ok_or_else(|| de::Error::missing_field("version"))?;
+                let query = query.ok_or_else(|| de::Error::missing_field("query"))?;
+                Ok(Versioned { query })
+            }
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(field_identifier, rename_all = "lowercase")]
+        enum Field {
+            Version,
+            Query,
+        }
+
+        deserializer.deserialize_struct("Versioned", &["version", "query"], EnvelopeVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::*;
+
+    #[test]
+    fn the_version_is_written_first() {
+        let stored = serde_json::to_string(&Versioned::new(Value::from(1))).unwrap();
+
+        assert_eq!(r#"{"version":1,"query":{"Integer":1}}"#, stored);
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let err = serde_json::from_str::<Versioned<Value>>(r#"{"version":0,"query":{"Integer":1}}"#).unwrap_err();
+
+        assert!(
+            err.to_string().starts_with("a query of AST version 0 can not be read by AST version 1"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn the_version_may_follow_the_query() {
+        let query: Versioned<Value> = serde_json::from_str(r#"{"query":{"Integer":1},"version":1}"#).unwrap();
+        assert_eq!(Value::from(1), query.into_inner());
+
+        let err = serde_json::from_str::<Versioned<Value>>(r#"{"query":{"Integer":1},"version":2}"#).unwrap_err();
+        assert!(err.to_string().starts_with("a query of AST version 2"), "{}", err);
+    }
+
+    #[test]
+    fn the_version_is_required() {
+        let err = serde_json::from_str::<Versioned<Value>>(r#"{"query":{"Integer":1}}"#).unwrap_err();
+
+        assert!(err.to_string().starts_with("missing field `version`"), "{}", err);
+    }
+
+    #[test]
+    fn computed_defaults_are_stored_as_their_value() {
+        let column = Column::new("visits").default(DefaultValue::Computed(|| Value::from(0)));
+        let stored = serde_json::to_string(&Versioned::new(column)).unwrap();
+
+        let column: Versioned<Column> = serde_json::from_str(&stored).unwrap();
+        assert!(matches!(column.into_inner().default, Some(DefaultValue::Provided(Value::Integer(Some(0))))));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn raw_json_values_are_not_serialized() {
+        let raw = serde_json::value::RawValue::from_string(r#"{"a":1}"#.to_string()).unwrap();
+        let value = Value::Json(Json::from(super::super::values::JsonRawValue::from(&*raw)));
+
+        assert!(serde_json::to_string(&Versioned::new(value)).is_err());
+    }
+}
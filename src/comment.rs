@@ -0,0 +1,197 @@
+//! Tags appended to the statements as a trailing SQL comment, for telling
+//! which service or request sent a query in `pg_stat_activity` or the slow
+//! query log, in the format of [sqlcommenter].
+//!
+//! The tags of an execution are added with `comment`, those of every
+//! statement come from the commenter set with [`set_default_commenter`],
+//! e.g. reading the current trace from the tracing context. Keys and values
+//! are percent-encoded: neither `*/` nor a quote or placeholder ends up in
+//! the comment as is, and the parameters are numbered as without it.
+//!
+//! ```rust
+//! # use xiayu::prelude::*;
+//! #[derive(Debug, Entity)]
+//! #[tablename = "users"]
+//! struct User {
+//!     #[column(primary_key)]
+//!     id: i64,
+//!     name: String,
+//! }
+//!
+//! # fn main() -> xiayu::Result<()> {
+//! let lookup = User::get::<sqlx::Sqlite, _>(1).comment("route", "/users/:id").prepare()?;
+//!
+//! assert_eq!(
+//!     "SELECT `users`.* FROM `users` WHERE `users`.`id` = ? /*route='%2Fusers%2F%3Aid'*/",
+//!     lookup.sql()
+//! );
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [sqlcommenter]: https://google.github.io/sqlcommenter/spec/
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::sync::{Arc, RwLock};
+
+type Commenter = Arc<dyn Fn() -> Vec<(String, String)> + Send + Sync>;
+
+static DEFAULT_COMMENTER: RwLock<Option<Commenter>> = RwLock::new(None);
+
+/// Tags every statement with the key and value pairs returned by
+/// `commenter`, called each time a statement is built. The tags of an
+/// execution win over the default ones of the same key.
+///
+/// ```rust
+/// xiayu::comment::set_default_commenter(|| vec![("app".to_string(), "api".to_string())]);
+/// ```
+pub fn set_default_commenter<F>(commenter: F)
+where
+    F: Fn() -> Vec<(String, String)> + Send + Sync + 'static,
+{
+    *DEFAULT_COMMENTER.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(commenter));
+}
+
+/// Stops tagging the statements with default tags.
+pub fn clear_default_commenter() {
+    *DEFAULT_COMMENTER.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+fn default_tags() -> Vec<(String, String)> {
+    // Not holding the lock while the commenter runs, it may build statements
+    // of its own.
+    let commenter = DEFAULT_COMMENTER.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+    commenter.map(|commenter| commenter()).unwrap_or_default()
+}
+
+/// The tags of an execution, and whether the default ones are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    tags: Vec<(Cow<'static, str>, String)>,
+    defaults: bool,
+}
+
+impl Default for Comment {
+    fn default() -> Self {
+        Self {
+            tags: Vec::new(),
+            defaults: true,
+        }
+    }
+}
+
+impl Comment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a tag, replacing the one of the same key.
+    pub fn tag<K, V>(&mut self, key: K, value: V) -> &mut Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: ToString,
+    {
+        let key = key.into();
+        self.tags.retain(|(k, _)| *k != key);
+        self.tags.push((key, value.to_string()));
+        self
+    }
+
+    /// Leaves out the tags of the default commenter and those added so far.
+    pub fn clear(&mut self) -> &mut Self {
+        self.tags.clear();
+        self.defaults = false;
+        self
+    }
+
+    /// Appends the comment to `sql`, nothing without tags.
+    pub(crate) fn append_to(&self, sql: &mut String) {
+        let defaults = if self.defaults { default_tags() } else { Vec::new() };
+        self.append_with(defaults, sql)
+    }
+
+    fn append_with(&self, defaults: Vec<(String, String)>, sql: &mut String) {
+        let mut tags: Vec<(&str, &str)> = defaults
+            .iter()
+            .filter(|(key, _)| !self.tags.iter().any(|(k, _)| k == key))
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .chain(self.tags.iter().map(|(key, value)| (key.as_ref(), value.as_str())))
+            .collect();
+        if tags.is_empty() {
+            return;
+        }
+        // Sorted by key as the format asks, the same tags give the same SQL.
+        tags.sort_by(|a, b| a.0.cmp(b.0));
+
+        sql.push_str(" /*");
+        for (index, (key, value)) in tags.into_iter().enumerate() {
+            if index > 0 {
+                sql.push(',');
+            }
+            encode(key, sql);
+            sql.push_str("='");
+            encode(value, sql);
+            sql.push('\'');
+        }
+        sql.push_str("*/");
+    }
+}
+
+/// Percent-encodes all but the unreserved characters of RFC 3986.
+fn encode(text: &str, out: &mut String) {
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            _ => {
+                let _ = write!(out, "%{:02X}", byte);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(comment: &Comment, defaults: Vec<(&str, &str)>) -> String {
+        let defaults = defaults.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let mut sql = String::from("SELECT 1");
+        comment.append_with(defaults, &mut sql);
+        sql
+    }
+
+    #[test]
+    fn tags_are_sorted_and_quoted() {
+        let mut comment = Comment::new();
+        comment.tag("trace", "abc").tag("app", "api");
+
+        assert_eq!("SELECT 1 /*app='api',trace='abc'*/", render(&comment, vec![]));
+    }
+
+    #[test]
+    fn hostile_values_stay_in_the_comment() {
+        let mut comment = Comment::new();
+        comment.tag("user", "*/ DROP TABLE users; /*").tag("note", "it's $1 or ?");
+
+        assert_eq!(
+            "SELECT 1 /*note='it%27s%20%241%20or%20%3F',user='%2A%2F%20DROP%20TABLE%20users%3B%20%2F%2A'*/",
+            render(&comment, vec![])
+        );
+    }
+
+    #[test]
+    fn execution_tags_win_over_the_defaults() {
+        let mut comment = Comment::new();
+        comment.tag("trace", "abc");
+
+        assert_eq!(
+            "SELECT 1 /*app='api',trace='abc'*/",
+            render(&comment, vec![("app", "api"), ("trace", "default")])
+        );
+    }
+
+    #[test]
+    fn nothing_is_appended_without_tags() {
+        assert_eq!("SELECT 1", render(&Comment::new(), vec![]));
+    }
+}
@@ -0,0 +1,240 @@
+//! Bulk loading of entities with PostgreSQL `COPY ... FROM STDIN`.
+//!
+//! Far faster than inserting tens of thousands of rows, even with a
+//! multi-row `INSERT`. The rows are encoded in the CSV format from the
+//! [`values`](crate::prelude::Entity::values) of the entities, all of the
+//! columns as `save` inserts them, and sent in chunks while the entities
+//! are read: [`copy_from_stream`] never holds more than a chunk of them in
+//! memory.
+//!
+//! ```rust,no_run
+//! # use xiayu::prelude::*;
+//! #[derive(Debug, Entity)]
+//! #[tablename = "users"]
+//! struct User {
+//!     #[column(primary_key)]
+//!     id: i64,
+//!     name: String,
+//! }
+//!
+//! # async fn load(conn: &mut sqlx::PgConnection) -> xiayu::Result<()> {
+//! let users = (1..=50_000).map(|id| User { id, name: format!("user {}", id) });
+//! let copied = xiayu::copy::copy_from(users, conn).await?;
+//! assert_eq!(50_000, copied);
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+use std::fmt::Write;
+
+use futures_util::stream::{self, Stream, StreamExt};
+use sqlx::postgres::PgConnection;
+
+use crate::ast::Value;
+use crate::error::{Error, ErrorKind};
+use crate::prelude::Entity;
+
+/// The size of the data sent at once.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Copies `entities` into their table, returns the number of rows copied.
+pub async fn copy_from<E, I>(entities: I, conn: &mut PgConnection) -> crate::Result<u64>
+where
+    E: Entity,
+    I: IntoIterator<Item = E>,
+{
+    copy_from_stream(stream::iter(entities), conn).await
+}
+
+/// Copies the entities of a stream into their table, returns the number of
+/// rows copied.
+///
+/// An entity that can not be encoded aborts the copy, none of the rows are
+/// then in the table.
+pub async fn copy_from_stream<E, S>(entities: S, conn: &mut PgConnection) -> crate::Result<u64>
+where
+    E: Entity,
+    S: Stream<Item = E>,
+{
+    futures_util::pin_mut!(entities);
+
+    let mut copy = conn.copy_in_raw(&statement::<E>()).await?;
+    let mut buffer = String::with_capacity(CHUNK_SIZE);
+
+    while let Some(entity) = entities.next().await {
+        if let Err(error) = encode_row(&entity.values(), &mut buffer) {
+            copy.abort(error.to_string()).await?;
+            return Err(error);
+        }
+        if buffer.len() >= CHUNK_SIZE {
+            copy.send(std::mem::replace(&mut buffer, String::with_capacity(CHUNK_SIZE)).into_bytes())
+                .await?;
+        }
+    }
+    if !buffer.is_empty() {
+        copy.send(buffer.into_bytes()).await?;
+    }
+
+    Ok(copy.finish().await?)
+}
+
+fn statement<E: Entity>() -> String {
+    let schema = E::schema();
+    let mut sql = String::from("COPY ");
+    if let Some(database) = schema.database {
+        quote_identifier(database, &mut sql);
+        sql.push('.');
+    }
+    quote_identifier(schema.tablename, &mut sql);
+    sql.push_str(" (");
+    for (index, column) in schema.columns.iter().enumerate() {
+        if index > 0 {
+            sql.push_str(", ");
+        }
+        quote_identifier(column.name, &mut sql);
+    }
+    sql.push_str(") FROM STDIN (FORMAT csv)");
+    sql
+}
+
+fn quote_identifier(name: &str, out: &mut String) {
+    out.push('"');
+    out.push_str(&name.replace('"', "\"\""));
+    out.push('"');
+}
+
+/// Appends the values as a CSV line.
+fn encode_row(values: &[Value<'_>], out: &mut String) -> crate::Result<()> {
+    for (index, value) in values.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        encode_value(value, out)?;
+    }
+    out.push('\n');
+    Ok(())
+}
+
+/// Quoted, the text can hold delimiters and newlines, and an empty string
+/// is not read as `NULL`, nor `\.` as the end of the data.
+fn quote_text(text: &str, out: &mut String) {
+    out.push('"');
+    out.push_str(&text.replace('"', "\"\""));
+    out.push('"');
+}
+
+macro_rules! float {
+    ($value:expr, $out:expr) => {
+        if $value.is_nan() {
+            $out.push_str("NaN")
+        } else if $value.is_infinite() {
+            $out.push_str(if $value > 0.0 { "Infinity" } else { "-Infinity" })
+        } else {
+            write!($out, "{}", $value)?
+        }
+    };
+}
+
+/// Appends the value in the text format of PostgreSQL, `NULL` as an empty
+/// unquoted field.
+fn encode_value(value: &Value<'_>, out: &mut String) -> crate::Result<()> {
+    match value {
+        Value::Integer(Some(v)) | Value::I64(Some(v)) => write!(out, "{}", v)?,
+        Value::I8(Some(v)) => write!(out, "{}", v)?,
+        Value::I16(Some(v)) => write!(out, "{}", v)?,
+        Value::I32(Some(v)) => write!(out, "{}", v)?,
+        Value::Float(Some(v)) => float!(*v, out),
+        Value::Double(Some(v)) => float!(*v, out),
+        Value::Boolean(Some(v)) => out.push(if *v { 't' } else { 'f' }),
+        Value::Text(Some(v)) | Value::Enum(Some(v)) | Value::Xml(Some(v)) => quote_text(v, out),
+        Value::Char(Some(v)) => quote_text(v.encode_utf8(&mut [0; 4]), out),
+        Value::Bytes(Some(v)) => {
+            out.push_str("\\x");
+            for byte in v.iter() {
+                write!(out, "{:02x}", byte)?;
+            }
+        }
+        #[cfg(feature = "json")]
+        Value::Json(crate::ast::Json::JsonValue(Some(v))) => quote_text(&v.to_string(), out),
+        #[cfg(feature = "json")]
+        Value::Json(crate::ast::Json::JsonRawValue(Some(v))) => quote_text(v.get(), out),
+        #[cfg(feature = "uuid")]
+        Value::Uuid(Some(v)) => write!(out, "{}", v)?,
+        #[cfg(feature = "chrono")]
+        Value::DateTime(Some(v)) => out.push_str(&v.to_rfc3339()),
+        #[cfg(feature = "chrono")]
+        Value::Date(Some(v)) => write!(out, "{}", v)?,
+        #[cfg(feature = "chrono")]
+        Value::Time(Some(v)) => write!(out, "{}", v)?,
+        #[cfg(feature = "bigdecimal")]
+        Value::Numeric(Some(v)) | Value::BigDecimal(Some(v)) => write!(out, "{}", v)?,
+        #[cfg(feature = "decimal")]
+        Value::Decimal(Some(v)) => write!(out, "{}", v)?,
+        Value::PgInterval(Some(v)) => write!(out, "{} months {} days {} microseconds", v.months, v.days, v.microseconds)?,
+        value if value.is_null() => {}
+        value => {
+            let kind = ErrorKind::conversion(format!("{:?} can not be copied, insert it instead.", value));
+            return Err(Error::builder(kind).build());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(values: Vec<Value<'static>>) -> String {
+        let mut out = String::new();
+        encode_row(&values, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn text_is_quoted() {
+        assert_eq!(
+            "\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\",\"\\.\"\n",
+            row(vec!["a,b".into(), "say \"hi\"".into(), "two\nlines".into(), "\\.".into()])
+        );
+    }
+
+    #[test]
+    fn null_is_not_the_empty_string() {
+        assert_eq!(
+            "1,,\"\",\n",
+            row(vec![Value::from(1), Value::Text(None), "".into(), Value::Integer(None)])
+        );
+    }
+
+    #[test]
+    fn scalars_use_the_postgres_text_format() {
+        assert_eq!(
+            "t,f,0.1,NaN,-Infinity,\\x00ff,\"x\"\n",
+            row(vec![
+                true.into(),
+                false.into(),
+                Value::Float(Some(0.1)),
+                Value::Double(Some(f64::NAN)),
+                Value::Double(Some(f64::NEG_INFINITY)),
+                Value::from(vec![0u8, 255]),
+                Value::Char(Some('x')),
+            ])
+        );
+    }
+
+    #[test]
+    fn identifiers_are_quoted() {
+        let mut sql = String::new();
+        quote_identifier("say \"hi\"", &mut sql);
+
+        assert_eq!("\"say \"\"hi\"\"\"", sql);
+    }
+
+    #[test]
+    fn arrays_can_not_be_copied() {
+        let mut out = String::new();
+        let err = encode_row(&[Value::Array(Some(vec![Value::from(1)]))], &mut out).unwrap_err();
+
+        assert!(err.to_string().contains("can not be copied"), "{}", err);
+    }
+}
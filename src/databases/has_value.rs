@@ -0,0 +1,291 @@
+use std::borrow::Cow;
+
+use sqlx::database::HasArguments;
+
+use crate::ast::{ColumnMeta, Value};
+#[cfg(feature = "json")]
+use crate::ast::Json;
+
+/// A database with a value type of its own, holding the parameters it has a
+/// type for.
+///
+/// Visitors convert every parameter when a query is built, so a value the
+/// database cannot bind, e.g. a `PgMoney` in a query for SQLite, fails
+/// [`build`](crate::visitors::Visitor::build) rather than the execution.
+pub trait HasValue<'a>: sqlx::Database {
+    /// The name of the database, for errors.
+    const NAME: &'static str;
+
+    /// The parameter values of the database.
+    type Value: Send;
+
+    /// Converts `value`, handing it back when the database has no type for it.
+    fn convert(value: Value<'a>) -> Result<<Self as HasValue<'a>>::Value, Value<'a>>;
+}
+
+/// Binds a parameter, converted with [`HasValue`], to a query of `DB`.
+///
+/// Borrowed text and bytes are bound as they are, living as long as the
+/// query does, rather than copied.
+pub trait Binder<'a, DB>
+where
+    DB: HasValue<'a>,
+{
+    fn bind_value(self, value: <DB as HasValue<'a>>::Value) -> Self
+    where
+        Self: Sized;
+}
+
+/// Converts the parameters of a query for `DB`. The first value the database
+/// has no type for fails with its position, counting from one, and the column
+/// it is assigned to, if any.
+pub fn convert_parameters<'a, DB>(parameters: Vec<Value<'a>>, columns: &[Option<ColumnMeta>]) -> crate::Result<Vec<<DB as HasValue<'a>>::Value>>
+where
+    DB: HasValue<'a>,
+{
+    parameters
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| DB::convert(value).map_err(|value| unsupported::<DB>(index, &value, columns)))
+        .collect()
+}
+
+fn unsupported<'a, DB>(index: usize, value: &Value<'a>, columns: &[Option<ColumnMeta>]) -> crate::error::Error
+where
+    DB: HasValue<'a>,
+{
+    let message = match columns.get(index).and_then(Option::as_ref) {
+        Some(column) => format!(
+            "parameter {} for column `{}`, {:?}, is not supported by {}",
+            index + 1,
+            column.name,
+            value,
+            DB::NAME
+        ),
+        None => format!("parameter {}, {:?}, is not supported by {}", index + 1, value, DB::NAME),
+    };
+    let kind = crate::error::ErrorKind::conversion(message);
+    crate::error::Error::builder(kind).build()
+}
+
+/// Checks the parameters built by the visitor of `DB` convert, converting
+/// them in place.
+pub(crate) fn check_parameters<'a, DB>(parameters: &mut [Value<'a>], columns: &[Option<ColumnMeta>]) -> crate::Result<()>
+where
+    DB: HasValue<'a>,
+    Value<'a>: From<<DB as HasValue<'a>>::Value>,
+{
+    for (index, parameter) in parameters.iter_mut().enumerate() {
+        let value = std::mem::replace(parameter, Value::Integer(None));
+        match DB::convert(value) {
+            Ok(converted) => *parameter = Value::from(converted),
+            Err(value) => {
+                let error = unsupported::<DB>(index, &value, columns);
+                *parameter = value;
+                return Err(error);
+            }
+        }
+    }
+    Ok(())
+}
+
+macro_rules! database_value {
+    (
+        $(#[$meta: meta])*
+        $name: ident for $database: ty as $database_name: literal, |$query: ident| {
+            $( $(#[$attr: meta])* $variant: ident($value: ident: $typ: ty) => $bind: expr, )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum $name<'a> {
+            $( $(#[$attr])* $variant($typ), )*
+        }
+
+        impl<'a> HasValue<'a> for $database {
+            const NAME: &'static str = $database_name;
+            type Value = $name<'a>;
+
+            fn convert(value: Value<'a>) -> Result<$name<'a>, Value<'a>> {
+                match value {
+                    $( $(#[$attr])* Value::$variant(value) => Ok($name::$variant(value)), )*
+                    #[allow(unreachable_patterns)]
+                    value => Err(value),
+                }
+            }
+        }
+
+        impl<'a> From<$name<'a>> for Value<'a> {
+            fn from(value: $name<'a>) -> Self {
+                match value {
+                    $( $(#[$attr])* $name::$variant(value) => Value::$variant(value), )*
+                }
+            }
+        }
+
+        impl<'a> Binder<'a, $database>
+            for sqlx::query::Query<'a, $database, <$database as HasArguments<'a>>::Arguments>
+        {
+            fn bind_value(self, value: $name<'a>) -> Self {
+                let $query = self;
+                match value {
+                    $( $(#[$attr])* $name::$variant($value) => $bind, )*
+                }
+            }
+        }
+
+        impl<'a, O> Binder<'a, $database>
+            for sqlx::query::QueryAs<'a, $database, O, <$database as HasArguments<'a>>::Arguments>
+        {
+            fn bind_value(self, value: $name<'a>) -> Self {
+                let $query = self;
+                match value {
+                    $( $(#[$attr])* $name::$variant($value) => $bind, )*
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "sqlite")]
+database_value! {
+    /// A parameter value of SQLite.
+    SqliteValue for sqlx::Sqlite as "SQLite", |query| {
+        Integer(integer: Option<i64>) => query.bind(integer),
+        I8(int8: Option<i8>) => query.bind(int8),
+        I16(int16: Option<i16>) => query.bind(int16),
+        I32(int32: Option<i32>) => query.bind(int32),
+        I64(int64: Option<i64>) => query.bind(int64),
+        Float(float: Option<f32>) => query.bind(float),
+        Double(double: Option<f64>) => query.bind(double),
+        Text(text: Option<Cow<'a, str>>) => query.bind(text),
+        Enum(text: Option<Cow<'a, str>>) => query.bind(text),
+        Xml(text: Option<Cow<'a, str>>) => query.bind(text),
+        Char(c: Option<char>) => query.bind(c.map(String::from)),
+        Bytes(bytes: Option<Cow<'a, [u8]>>) => match bytes {
+            Some(Cow::Borrowed(bytes)) => query.bind(Some(bytes)),
+            bytes => query.bind(bytes.map(Cow::into_owned)),
+        },
+        Boolean(boolean: Option<bool>) => query.bind(boolean),
+        #[cfg(feature = "json")]
+        Json(json: Json<'a>) => match json {
+            Json::JsonValue(value) => query.bind(value),
+            Json::JsonRawValue(raw_value) => query.bind(raw_value.map(|v| sqlx::types::Json(*v))),
+        },
+        #[cfg(feature = "uuid")]
+        Uuid(uuid: Option<uuid::Uuid>) => query.bind(uuid),
+        #[cfg(feature = "chrono")]
+        DateTime(datetime: Option<chrono::DateTime<chrono::Utc>>) => query.bind(datetime),
+        #[cfg(feature = "chrono")]
+        Date(date: Option<chrono::NaiveDate>) => query.bind(date),
+        #[cfg(feature = "chrono")]
+        Time(time: Option<chrono::NaiveTime>) => query.bind(time),
+    }
+}
+
+#[cfg(feature = "mysql")]
+database_value! {
+    /// A parameter value of MySQL.
+    MySqlValue for sqlx::MySql as "MySQL", |query| {
+        Integer(integer: Option<i64>) => query.bind(integer),
+        I8(int8: Option<i8>) => query.bind(int8),
+        I16(int16: Option<i16>) => query.bind(int16),
+        I32(int32: Option<i32>) => query.bind(int32),
+        I64(int64: Option<i64>) => query.bind(int64),
+        Float(float: Option<f32>) => query.bind(float),
+        Double(double: Option<f64>) => query.bind(double),
+        Text(text: Option<Cow<'a, str>>) => query.bind(text),
+        Enum(text: Option<Cow<'a, str>>) => query.bind(text),
+        Xml(text: Option<Cow<'a, str>>) => query.bind(text),
+        Char(c: Option<char>) => query.bind(c.map(String::from)),
+        Bytes(bytes: Option<Cow<'a, [u8]>>) => match bytes {
+            Some(Cow::Borrowed(bytes)) => query.bind(Some(bytes)),
+            bytes => query.bind(bytes.map(Cow::into_owned)),
+        },
+        Boolean(boolean: Option<bool>) => query.bind(boolean),
+        #[cfg(feature = "json")]
+        Json(json: Json<'a>) => match json {
+            Json::JsonValue(value) => query.bind(value),
+            Json::JsonRawValue(raw_value) => query.bind(raw_value.map(|v| sqlx::types::Json(*v))),
+        },
+        #[cfg(feature = "uuid")]
+        Uuid(uuid: Option<uuid::Uuid>) => query.bind(uuid),
+        #[cfg(feature = "chrono")]
+        DateTime(datetime: Option<chrono::DateTime<chrono::Utc>>) => query.bind(datetime),
+        #[cfg(feature = "chrono")]
+        Date(date: Option<chrono::NaiveDate>) => query.bind(date),
+        #[cfg(feature = "chrono")]
+        Time(time: Option<chrono::NaiveTime>) => query.bind(time),
+    }
+}
+
+#[cfg(feature = "postgres")]
+database_value! {
+    /// A parameter value of PostgreSQL.
+    PostgresValue for sqlx::Postgres as "PostgreSQL", |query| {
+        Integer(integer: Option<i64>) => query.bind(integer),
+        I8(int8: Option<i8>) => query.bind(int8),
+        I16(int16: Option<i16>) => query.bind(int16),
+        I32(int32: Option<i32>) => query.bind(int32),
+        I64(int64: Option<i64>) => query.bind(int64),
+        Float(float: Option<f32>) => query.bind(float),
+        Double(double: Option<f64>) => query.bind(double),
+        Text(text: Option<Cow<'a, str>>) => query.bind(text),
+        Enum(text: Option<Cow<'a, str>>) => query.bind(text),
+        Xml(text: Option<Cow<'a, str>>) => query.bind(text),
+        Char(c: Option<char>) => query.bind(c.map(String::from)),
+        Bytes(bytes: Option<Cow<'a, [u8]>>) => match bytes {
+            Some(Cow::Borrowed(bytes)) => query.bind(Some(bytes)),
+            bytes => query.bind(bytes.map(Cow::into_owned)),
+        },
+        Boolean(boolean: Option<bool>) => query.bind(boolean),
+        #[cfg(feature = "json")]
+        Json(json: Json<'a>) => match json {
+            Json::JsonValue(value) => query.bind(value),
+            Json::JsonRawValue(raw_value) => query.bind(raw_value.map(|v| sqlx::types::Json(*v))),
+        },
+        #[cfg(feature = "uuid")]
+        Uuid(uuid: Option<uuid::Uuid>) => query.bind(uuid),
+        #[cfg(feature = "chrono")]
+        DateTime(datetime: Option<chrono::DateTime<chrono::Utc>>) => query.bind(datetime),
+        #[cfg(feature = "chrono")]
+        Date(date: Option<chrono::NaiveDate>) => query.bind(date),
+        #[cfg(feature = "chrono")]
+        Time(time: Option<chrono::NaiveTime>) => query.bind(time),
+        PgInterval(interval: Option<sqlx::postgres::types::PgInterval>) => query.bind(interval),
+        PgMoney(money: Option<sqlx::postgres::types::PgMoney>) => query.bind(money),
+        #[cfg(feature = "bigdecimal")]
+        BigDecimal(bigdecimal: Option<sqlx::types::BigDecimal>) => query.bind(bigdecimal),
+        #[cfg(feature = "decimal")]
+        Decimal(decimal: Option<sqlx::types::Decimal>) => query.bind(decimal),
+        #[cfg(feature = "chrono")]
+        UtcDateTime(datetime: Option<chrono::DateTime<chrono::Utc>>) => query.bind(datetime),
+        #[cfg(feature = "chrono")]
+        LocalDateTime(datetime: Option<chrono::DateTime<chrono::Local>>) => query.bind(datetime),
+        #[cfg(feature = "chrono")]
+        NaiveDateTime(datetime: Option<chrono::NaiveDateTime>) => query.bind(datetime),
+        #[cfg(feature = "chrono")]
+        NaiveDate(date: Option<chrono::NaiveDate>) => query.bind(date),
+        #[cfg(feature = "chrono")]
+        NaiveTime(time: Option<chrono::NaiveTime>) => query.bind(time),
+    }
+}
+
+#[cfg(feature = "mssql")]
+database_value! {
+    /// A parameter value of SQL Server.
+    MssqlValue for sqlx::Mssql as "SQL Server", |query| {
+        Integer(integer: Option<i64>) => query.bind(integer),
+        I8(int8: Option<i8>) => query.bind(int8),
+        I16(int16: Option<i16>) => query.bind(int16),
+        I32(int32: Option<i32>) => query.bind(int32),
+        I64(int64: Option<i64>) => query.bind(int64),
+        Float(float: Option<f32>) => query.bind(float),
+        Double(double: Option<f64>) => query.bind(double),
+        Text(text: Option<Cow<'a, str>>) => query.bind(text),
+        Enum(text: Option<Cow<'a, str>>) => query.bind(text),
+        Xml(text: Option<Cow<'a, str>>) => query.bind(text),
+        Char(c: Option<char>) => query.bind(c.map(String::from)),
+        Boolean(boolean: Option<bool>) => query.bind(boolean),
+    }
+}
@@ -1,25 +1,79 @@
+use std::borrow::Cow;
 use std::default;
 use std::marker::{PhantomData};
+use std::convert::TryFrom;
 
 #[cfg(feature = "chrono")]
 use sqlx::types::chrono;
 use sqlx::{Executor, Arguments, Database, IntoArguments, FromRow};
 use async_trait::async_trait;
+use futures_core::future::BoxFuture;
+use futures_core::stream::BoxStream;
 
-use crate::ast::{Value};
-#[cfg(feature = "json")]
-use crate::ast::Json;
-use crate::prelude::{Column, Delete, Entity, HasPrimaryKey, Insert, MultiRowInsert, SingleRowInsert, Row, OnConflict, Select, Update, Expression};
-use crate::visitors::Visitor;
+use crate::ast::{ColumnMeta, Comparable, IntoSelection, Value};
+use crate::comment::Comment;
+use crate::identity_map::{key_of, Eviction, IdentityMap};
+use crate::prelude::{Column, Delete, Entity, HasPrimaryKey, Writable, Insert, MultiRowInsert, SingleRowInsert, Row, Query, Select, Update, Expression};
+use crate::visitors::{QuoteStyle, Visitor};
+
+mod connect;
+mod has_value;
+mod stream;
+
+pub use connect::connect;
+pub use stream::RowStream;
+use stream::Generated;
+pub use has_value::{convert_parameters, Binder, HasValue};
+pub(crate) use has_value::check_parameters;
+#[cfg(feature = "mssql")]
+pub use has_value::MssqlValue;
+#[cfg(feature = "mysql")]
+pub use has_value::MySqlValue;
+#[cfg(feature = "postgres")]
+pub use has_value::PostgresValue;
+#[cfg(feature = "sqlite")]
+pub use has_value::SqliteValue;
 
 pub trait HasVisitor<'a> {
     type Visitor: crate::visitors::Visitor<'a>;
+
+    /// The name of the database, for errors.
+    const NAME: &'static str;
+
+    /// The most parameters a statement can bind, statements binding more
+    /// fail with [`TooManyParameters`](crate::error::ErrorKind::TooManyParameters)
+    /// before reaching the database.
+    const MAX_PARAMETERS: usize;
+
+    /// The quotes around identifiers, `C_BACKTICK_OPEN` and
+    /// `C_BACKTICK_CLOSE` of the visitor.
+    const IDENTIFIER_QUOTES: IdentifierQuotes;
+
+    /// The placeholder of the first parameter of a statement.
+    const FIRST_PARAMETER: &'static str;
+
     fn visitor() -> Self::Visitor;
 }
 
+/// How a database quotes identifiers, picking the identifiers of an entity
+/// quoted at compile time, see [`QualifiedColumns`](crate::prelude::QualifiedColumns).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierQuotes {
+    /// `` `name` ``, SQLite and MySQL.
+    Backticks,
+    /// `"name"`, PostgreSQL.
+    DoubleQuotes,
+    /// `[name]`, SQL Server.
+    Brackets,
+}
+
 #[cfg(feature = "postgres")]
 impl<'a> HasVisitor<'a> for sqlx::Postgres {
     type Visitor = crate::visitors::Postgres<'a>;
+    const NAME: &'static str = "PostgreSQL";
+    const MAX_PARAMETERS: usize = u16::MAX as usize;
+    const IDENTIFIER_QUOTES: IdentifierQuotes = IdentifierQuotes::DoubleQuotes;
+    const FIRST_PARAMETER: &'static str = "$1";
     fn visitor() -> Self::Visitor {
         Self::Visitor::default()
     }
@@ -28,6 +82,10 @@ impl<'a> HasVisitor<'a> for sqlx::Postgres {
 #[cfg(feature = "mssql")]
 impl<'a> HasVisitor<'a> for sqlx::Mssql {
     type Visitor = crate::visitors::Mssql<'a>;
+    const NAME: &'static str = "SQL Server";
+    const MAX_PARAMETERS: usize = 2100;
+    const IDENTIFIER_QUOTES: IdentifierQuotes = IdentifierQuotes::Brackets;
+    const FIRST_PARAMETER: &'static str = "@P1";
     fn visitor() -> Self::Visitor {
         Self::Visitor::default()
     }
@@ -36,6 +94,10 @@ impl<'a> HasVisitor<'a> for sqlx::Mssql {
 #[cfg(feature = "mysql")]
 impl<'a> HasVisitor<'a> for sqlx::MySql {
     type Visitor = crate::visitors::Mysql<'a>;
+    const NAME: &'static str = "MySQL";
+    const MAX_PARAMETERS: usize = u16::MAX as usize;
+    const IDENTIFIER_QUOTES: IdentifierQuotes = IdentifierQuotes::Backticks;
+    const FIRST_PARAMETER: &'static str = "?";
     fn visitor() -> Self::Visitor {
         Self::Visitor::default()
     }
@@ -44,127 +106,168 @@ impl<'a> HasVisitor<'a> for sqlx::MySql {
 #[cfg(feature = "sqlite")]
 impl<'a> HasVisitor<'a> for sqlx::Sqlite {
     type Visitor = crate::visitors::Sqlite<'a>;
+    const NAME: &'static str = "SQLite";
+    const MAX_PARAMETERS: usize = 32766;
+    const IDENTIFIER_QUOTES: IdentifierQuotes = IdentifierQuotes::Backticks;
+    const FIRST_PARAMETER: &'static str = "?";
     fn visitor() -> Self::Visitor {
         Self::Visitor::default()
     }
 }
 
-// pub struct Values<'a>(Vec<crate::ast::Value<'a>>);
-
-macro_rules! bind_value {
-    ($query:ident, $value: ident) => {
-        match $value {
-            Value::Integer(integer) => $query.bind(integer),
-
-            Value::I8(int8) => $query.bind(int8),
-            Value::I16(int16) => $query.bind(int16),
-            Value::I32(int32) => $query.bind(int32),
-            Value::I64(int64) => $query.bind(int64),
-            Value::Float(float) => $query.bind(float) ,
-            Value::Double(double) => $query.bind(double),
-            Value::Text(text) => $query.bind(text.map(|text|text.into_owned())),
-            Value::Bytes(bytes) => $query.bind(bytes.map(|b|b.into_owned())),
-            Value::Boolean(boolean) => $query.bind(boolean),
-            #[cfg(feature = "json")]
-            Value::Json(json) => match json {
-                // #[cfg(feature = "postgres")]
-                // Json::Json(json) => {
-                //     //
-                // }
-                Json::JsonValue(value) => $query.bind(value),
-                Json::JsonRawValue(raw_value) => $query.bind(raw_value.map(|v|sqlx::types::Json(*v)))
-            },
-            #[cfg(all(feature = "uuid", feature = "postgres"))]
-            Value::Uuid(uuid) => $query.bind(uuid),
-            #[cfg(feature = "postgres")]
-            Value::PgInterval(interval) => $query.bind(interval),
-            // #[cfg(feature = "postgres")]
-            // Value::PgRange(range) => $query.bind(range),
-            #[cfg(feature = "postgres")]
-            Value::PgMoney(money) => $query.bind(money),
-            #[cfg(all(feature = "bigdecimal", feature = "postgres"))]
-            Value::BigDecimal(bigdecimal) => $query.bind(bigdecimal),
-            #[cfg(all(feature = "decimal", feature = "postgres"))]
-            Value::Decimal(decimal) => $query.bind(decimal),
-            #[cfg(all(feature = "chrono", feature = "postgres"))]
-            Value::UtcDateTime(datetime) => $query.bind(datetime),
-            #[cfg(all(feature = "chrono", feature = "postgres"))]
-            Value::LocalDateTime(datetime) => $query.bind(datetime),
-            #[cfg(all(feature = "chrono", feature = "postgres"))]
-            Value::NaiveDateTime(datetime) => $query.bind(datetime),
-            #[cfg(all(feature = "chrono", feature = "postgres"))]
-            Value::NaiveDate(date) => $query.bind(date),
-            #[cfg(all(feature = "chrono", feature = "postgres"))]
-            Value::NaiveTime(time) => $query.bind(time),
-            #[cfg(all(feature = "time", feature = "postgres"))]
-            Value::PgTimeTz(timetz) => $query.bind(timetz),
-            #[cfg(all(feature = "ipnetwork", feature = "postgres"))]
-            Value::IpNetwork(ipnetwork) => $query.bind(ipnetwork),
-
-            _ => unimplemented!()
-        }
-    };
-}
+/// How a database hands back the key generated by an insert, see
+/// [`InsertingExecution::returning_id`].
+pub trait HasGeneratedKey: sqlx::Database {
+    /// Whether `RETURNING`, or `OUTPUT` on SQL Server, selects the key.
+    const RETURNING: bool;
 
-pub trait Binder<'a, DB> where DB: sqlx::Database {
-    fn bind_value(self, value: Value<'a>) -> Self
-    where
-        Self: Sized;
+    /// The key generated by an insert, from its result.
+    fn last_insert_id(result: &Self::QueryResult) -> Option<i64>;
 }
 
 #[cfg(feature = "postgres")]
-impl<'a> Binder<'a, sqlx::Postgres> for sqlx::query::Query<'a, sqlx::Postgres, sqlx::postgres::PgArguments> {
-    fn bind_value(self, value: Value<'a>) -> Self {
-        bind_value!(self, value)
+impl HasGeneratedKey for sqlx::Postgres {
+    const RETURNING: bool = true;
+
+    fn last_insert_id(_result: &Self::QueryResult) -> Option<i64> {
+        None
     }
 }
 
-#[cfg(feature = "postgres")]
-impl<'a, O> Binder<'a, sqlx::Postgres> for sqlx::query::QueryAs<'a, sqlx::Postgres, O, sqlx::postgres::PgArguments> {
-    fn bind_value(self, value: Value<'a>) -> Self {
-        bind_value!(self, value)
+#[cfg(feature = "mssql")]
+impl HasGeneratedKey for sqlx::Mssql {
+    const RETURNING: bool = true;
+
+    fn last_insert_id(_result: &Self::QueryResult) -> Option<i64> {
+        None
     }
 }
 
 #[cfg(feature = "mysql")]
-impl<'a> Binder<'a, sqlx::MySql> for sqlx::query::Query<'a, sqlx::MySql, sqlx::mysql::MySqlArguments> {
-    fn bind_value(self, value: Value<'a>) -> Self {
-        bind_value!(self, value)
+impl HasGeneratedKey for sqlx::MySql {
+    const RETURNING: bool = false;
+
+    fn last_insert_id(result: &Self::QueryResult) -> Option<i64> {
+        // 0 when the statement generated no key.
+        i64::try_from(result.last_insert_id()).ok().filter(|id| *id != 0)
     }
 }
 
-#[cfg(feature = "mysql")]
-impl<'a, O> Binder<'a, sqlx::MySql> for sqlx::query::QueryAs<'a, sqlx::MySql, O, sqlx::mysql::MySqlArguments> {
-    fn bind_value(self, value: Value<'a>) -> Self {
-        bind_value!(self, value)
+#[cfg(feature = "sqlite")]
+impl HasGeneratedKey for sqlx::Sqlite {
+    const RETURNING: bool = true;
+
+    fn last_insert_id(result: &Self::QueryResult) -> Option<i64> {
+        Some(result.last_insert_rowid())
     }
 }
 
-#[cfg(feature = "mssql")]
-impl<'a> Binder<'a, sqlx::Mssql> for sqlx::query::Query<'a, sqlx::Mssql, O, sqlx::mssql::MssqlArguments> {
-    fn bind_value(self, value: Value<'a>) -> Self {
-        bind_value!(self, value)
+/// The format of the plans shown by [`SelectingExecution::explain_as`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplainFormat {
+    /// Lines of text, as the database prints the plan.
+    Text,
+    /// A JSON document, on PostgreSQL and MySQL.
+    Json,
+}
+
+/// How a database shows the plan of a query, see
+/// [`SelectingExecution::explain`].
+pub trait HasExplain: sqlx::Database + for<'v> HasVisitor<'v> {
+    /// The statement showing the plan of `sql` in `format`, failing with
+    /// [`UnsupportedExplainFormat`](crate::error::ErrorKind::UnsupportedExplainFormat)
+    /// for a format the database has no plans in.
+    fn explain(sql: &str, format: ExplainFormat) -> crate::Result<String>;
+
+    /// The statements turning plans on before the query and off after it,
+    /// for databases showing them by a session option rather than a
+    /// prefix. Each is sent on its own.
+    fn plan_session(_format: ExplainFormat) -> Option<(&'static str, &'static str)> {
+        None
+    }
+
+    /// A line of the plan, from a row returned by the explained statement.
+    fn plan_line(row: &Self::Row) -> Result<String, sqlx::Error>;
+}
+
+fn unsupported_explain_format<DB: HasExplain>(format: ExplainFormat) -> crate::error::Error {
+    let kind = crate::error::ErrorKind::UnsupportedExplainFormat {
+        format,
+        database: <DB as HasVisitor<'static>>::NAME,
+    };
+    crate::error::Error::builder(kind).build()
+}
+
+#[cfg(feature = "postgres")]
+impl HasExplain for sqlx::Postgres {
+    fn explain(sql: &str, format: ExplainFormat) -> crate::Result<String> {
+        match format {
+            ExplainFormat::Text => Ok(format!("EXPLAIN {}", sql)),
+            ExplainFormat::Json => Ok(format!("EXPLAIN (FORMAT JSON) {}", sql)),
+        }
+    }
+
+    fn plan_line(row: &Self::Row) -> Result<String, sqlx::Error> {
+        // The JSON plan is a `json` column, sent as text.
+        sqlx::Row::try_get_unchecked(row, 0)
     }
 }
 
 #[cfg(feature = "mssql")]
-impl<'a, O> Binder<'a, sqlx::Mssql> for sqlx::query::QueryAs<'a, sqlx::Mssql, O, sqlx::mssql::MssqlArguments> {
-    fn bind_value(self, value: Value<'a>) -> Self {
-        bind_value!(self, value)
+impl HasExplain for sqlx::Mssql {
+    /// The plan of SQL Server is the XML of `SHOWPLAN_XML`, for the text
+    /// format as well: the query is compiled, not run.
+    fn explain(sql: &str, format: ExplainFormat) -> crate::Result<String> {
+        match format {
+            ExplainFormat::Text => Ok(sql.to_string()),
+            ExplainFormat::Json => Err(unsupported_explain_format::<Self>(format)),
+        }
+    }
+
+    fn plan_session(_format: ExplainFormat) -> Option<(&'static str, &'static str)> {
+        Some(("SET SHOWPLAN_XML ON", "SET SHOWPLAN_XML OFF"))
+    }
+
+    fn plan_line(row: &Self::Row) -> Result<String, sqlx::Error> {
+        sqlx::Row::try_get_unchecked(row, 0)
     }
 }
 
-#[cfg(feature = "sqlite")]
-impl<'a> Binder<'a, sqlx::Sqlite> for sqlx::query::Query<'a, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'a>> {
-    fn bind_value(self, value: Value<'a>) -> Self {
-        bind_value!(self, value)
+#[cfg(feature = "mysql")]
+impl HasExplain for sqlx::MySql {
+    /// The text format is the tree of MySQL 8.0.16 and later.
+    fn explain(sql: &str, format: ExplainFormat) -> crate::Result<String> {
+        match format {
+            ExplainFormat::Text => Ok(format!("EXPLAIN FORMAT=TREE {}", sql)),
+            ExplainFormat::Json => Ok(format!("EXPLAIN FORMAT=JSON {}", sql)),
+        }
+    }
+
+    fn plan_line(row: &Self::Row) -> Result<String, sqlx::Error> {
+        sqlx::Row::try_get_unchecked(row, 0)
     }
 }
 
 #[cfg(feature = "sqlite")]
-impl<'a, O> Binder<'a, sqlx::Sqlite> for sqlx::query::QueryAs<'a, sqlx::Sqlite, O, sqlx::sqlite::SqliteArguments<'a>> {
-    fn bind_value(self, value: Value<'a>) -> Self {
-        bind_value!(self, value)
+impl HasExplain for sqlx::Sqlite {
+    fn explain(sql: &str, format: ExplainFormat) -> crate::Result<String> {
+        match format {
+            ExplainFormat::Text => Ok(format!("EXPLAIN QUERY PLAN {}", sql)),
+            ExplainFormat::Json => Err(unsupported_explain_format::<Self>(format)),
+        }
+    }
+
+    fn plan_line(row: &Self::Row) -> Result<String, sqlx::Error> {
+        sqlx::Row::try_get(row, "detail")
+    }
+}
+
+/// A line of a plan, decoded by [`HasExplain::plan_line`].
+struct PlanLine<DB>(String, PhantomData<fn() -> DB>);
+
+impl<'r, DB: HasExplain> FromRow<'r, DB::Row> for PlanLine<DB> {
+    fn from_row(row: &'r DB::Row) -> Result<Self, sqlx::Error> {
+        DB::plan_line(row).map(|line| PlanLine(line, PhantomData))
     }
 }
 
@@ -172,270 +275,2153 @@ impl<'a, O> Binder<'a, sqlx::Sqlite> for sqlx::query::QueryAs<'a, sqlx::Sqlite,
 #[must_use = "query must be executed to affect database"]
 pub struct SelectingExecution<T, DB: Database> {
     select: Select<'static>,
-    compiled: Option<String>,
+    error: Option<crate::error::Error>,
+    comment: Comment,
     _marker: PhantomData<(T, DB)>,
 }
 
 impl<DB: Database, T: Send> SelectingExecution<T, DB> {
-    pub async fn conn<'a, C>(&'a mut self, conn: C) -> Result<T, crate::error::Error>
+    /// Fails with `error` when executed, for lookups which could not be built,
+    /// e.g. by a primary key which is not set.
+    pub fn failed(mut self, error: crate::error::Error) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    /// Tags the query with `key` and `value` in its trailing comment, see
+    /// [`comment`](crate::comment).
+    pub fn comment<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: ToString,
+    {
+        self.comment.tag(key, value);
+        self
+    }
+
+    /// Sends the query without the trailing comment, default tags included.
+    pub fn without_comments(mut self) -> Self {
+        self.comment.clear();
+        self
+    }
+
+    /// Runs the query, decoding its first row, see [`fetch_one`](Self::fetch_one).
+    pub async fn conn<'a, C>(self, conn: C) -> Result<T, crate::error::Error>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> sqlx::FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        self.fetch_one(conn).await
+    }
+
+    /// Runs the query, decoding its first row. Fails with
+    /// [`NotFound`](crate::error::ErrorKind::NotFound) without any.
+    pub async fn fetch_one<'a, C>(self, conn: C) -> crate::Result<T>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> sqlx::FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        self.prepare()?.fetch_one(conn).await
+    }
+
+    /// Runs the query, decoding its first row if it returns one.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # async fn run(pool: &sqlx::SqlitePool) -> xiayu::Result<()> {
+    /// match User::get(1).fetch_optional(pool).await? {
+    ///     Some(user) => println!("found {}", user.name),
+    ///     None => println!("no user 1"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    pub async fn fetch_optional<'a, C>(self, conn: C) -> crate::Result<Option<T>>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> sqlx::FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        self.prepare()?.fetch_optional(conn).await
+    }
+
+    /// Runs the query, decoding all of its rows.
+    pub async fn fetch_all<'a, C>(self, conn: C) -> crate::Result<Vec<T>>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> sqlx::FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        self.prepare()?.fetch_all(conn).await
+    }
+
+    /// Runs the query, decoding its rows as the database sends them instead
+    /// of collecting them first. A query which can not be built fails as the
+    /// first item of the stream.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// use futures_util::TryStreamExt;
+    ///
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # async fn run(pool: &sqlx::SqlitePool) -> xiayu::Result<()> {
+    /// let mut users = Select::from_table(User::table()).fetch_as::<User, sqlx::Sqlite>().fetch(pool);
+    /// while let Some(user) = users.try_next().await? {
+    ///     println!("{}", user.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    pub fn fetch<'a, C>(self, conn: C) -> RowStream<'a, T>
+    where
+        C: Executioner<'a, DB> + 'a,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> sqlx::FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin + 'a,
+    {
+        match self.prepare() {
+            Ok(prepared) => prepared.fetch(conn),
+            Err(err) => Generated::boxed(move |mut rows| async move { rows.send(Err(err)).await }),
+        }
+    }
+
+    /// The plan of the query as text, see [`explain_as`](Self::explain_as).
+    pub async fn explain<C>(self, conn: &mut C) -> crate::Result<Vec<String>>
+    where
+        DB: HasExplain,
+        for<'e> &'e mut C: Executioner<'e, DB>,
+    {
+        self.explain_as(ExplainFormat::Text, conn).await
+    }
+
+    /// The plan the database has for the query, with its parameters bound,
+    /// a line per row returned by the `EXPLAIN`. A JSON plan is a single
+    /// line.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+    /// let plan = User::get(1).explain(conn).await?;
+    ///
+    /// assert!(plan[0].starts_with("SEARCH users"));
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    pub async fn explain_as<C>(self, format: ExplainFormat, conn: &mut C) -> crate::Result<Vec<String>>
+    where
+        DB: HasExplain,
+        for<'e> &'e mut C: Executioner<'e, DB>,
+    {
+        self.prepare()?.explain(format, conn).await
+    }
+
+    /// Builds the query once, to run it as many times as needed.
+    pub fn prepare(self) -> crate::Result<PreparedQuery<DB>>
     where
-        C: 'a + sqlx::Executor<'a, Database = DB>,
-        DB: 'a + sqlx::Database + HasVisitor<'a>,
-        <DB as sqlx::database::HasArguments<'a>>::Arguments: 'a + IntoArguments<'a, DB>,
-        T: 'a + for<'r> sqlx::FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
-        sqlx::query::QueryAs<'a, DB, T, <DB as sqlx::database::HasArguments<'a>>::Arguments>: Binder<'a, DB>
+        DB: for<'v> HasVisitor<'v>,
     {
-        let (query, parameters) =
-            <<C as sqlx::Executor<'a>>::Database as HasVisitor>::Visitor::build(self.select.clone())?;
-        // 'a for borrowed from self.query
-        self.compiled.replace(query);
-        let mut query = sqlx::query_as::<DB, T>(self.compiled.as_ref().unwrap());
-        for parameter in parameters {
-            query = query.bind_value(parameter);
+        if let Some(error) = self.error {
+            return Err(error);
         }
+        PreparedQuery::build(self.select, false, &self.comment)
+    }
+}
 
-        let v = query.fetch_one(conn).await?;
-        Ok(v)
+impl<DB: Database, T: Entity> SelectingExecution<T, DB> {
+    /// Fetches `column` too, for the write-only columns left out of the
+    /// default selects, see
+    /// [`Entity::readable_columns`](crate::prelude::Entity::readable_columns).
+    pub fn with_column<C>(mut self, column: C) -> Self
+    where
+        C: Into<Column<'static>>,
+    {
+        // No columns select them all already.
+        if !self.select.columns.is_empty() {
+            self.select = self.select.column(column);
+        }
+        self
     }
 }
 
-impl<T, DB> From<crate::ast::Select<'static>> for SelectingExecution<T, DB>
+impl<DB, T> SelectingExecution<T, DB>
 where
-    T: for<'r> FromRow<'r, <DB as Database>::Row>,
-    DB: sqlx::Database
+    DB: Database,
+    T: HasPrimaryKey + Clone + Send + 'static,
+    T::PrimaryKey: Into<Column<'static>>,
 {
-    fn from(select: crate::ast::Select<'static>) -> Self {
-        Self {
-            select,
-            compiled: None,
-            _marker: PhantomData,
+    /// Looks the entity up in `map` first, and keeps it there once fetched.
+    /// Only lookups by the primary key, as built by
+    /// [`get`][crate::prelude::HasPrimaryKey::get], are cached.
+    pub fn cached(self, map: &IdentityMap) -> CachedSelectingExecution<T, DB> {
+        let key = key_of(self.select.conditions.as_ref(), &T::primary_key().into());
+        CachedSelectingExecution {
+            execution: self,
+            map: map.clone(),
+            key,
         }
     }
 }
 
-/// fetch entity from table. Returned by [`get`][crate::prelude::HasPrimaryKey::get].
-#[must_use = "delete must be executed to affect database"]
-pub struct DeletingExecution<'a, E, DB> {
-    delete: Delete<'static>,
-    compiled: Option<String>,
-    entity: &'a mut E,
+/// Entities fetched by their primary keys. Returned by
+/// [`get_many`][crate::prelude::HasPrimaryKey::get_many].
+#[must_use = "query must be executed to affect database"]
+pub struct FetchManyRequest<T, DB>
+where
+    T: HasPrimaryKey,
+{
+    keys: Vec<T::PrimaryKeyValueType>,
     _marker: PhantomData<DB>,
 }
 
-impl<'e, E: HasPrimaryKey, DB: Database> DeletingExecution<'e, E, DB> {
-    pub fn new<'a>(delete: crate::ast::Delete<'static>, entity: &'e mut E) -> Self
-    {
+impl<T, DB> FetchManyRequest<T, DB>
+where
+    T: HasPrimaryKey,
+{
+    pub(crate) fn new(keys: Vec<T::PrimaryKeyValueType>) -> Self {
         Self {
-            entity,
-            delete,
-            compiled: None,
+            keys,
             _marker: PhantomData,
         }
     }
 
-    pub async fn conn<'a, C>(&'a mut self, conn: C) -> Result<(), crate::error::Error>
+    /// Fetches the entities with a `primary_key IN (..)` query, one for each
+    /// chunk of keys the database can bind in a statement.
+    pub async fn conn<C>(self, conn: &mut C) -> crate::Result<FetchedMany<T, T::PrimaryKeyValueType>>
     where
-        'e: 'a,
-        C: 'a + sqlx::Executor<'a, Database = DB>,
-        DB: 'a + sqlx::Database + HasVisitor<'a>,
-        <DB as sqlx::database::HasArguments<'a>>::Arguments: 'a + IntoArguments<'a, DB>,
-        sqlx::query::Query<'a, DB, <DB as sqlx::database::HasArguments<'a>>::Arguments>: Binder<'a, DB>
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        for<'e> &'e mut C: Executioner<'e, DB>,
+        T: Clone + for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+        T::PrimaryKey: Into<Column<'static>>,
+        T::PrimaryKeyValueType: Clone + Eq + std::hash::Hash + Into<Value<'static>> + Send,
     {
-        let (compiled, parameters) =
-            <<C as sqlx::Executor<'a>>::Database as HasVisitor>::Visitor::build(self.delete.clone())?;
-        // 'a for borrowed from self.compiled
-        self.compiled.replace(compiled);
-        let mut query = sqlx::query::<DB>(self.compiled.as_ref().unwrap());
-        for parameter in parameters {
-            // query = bind_value!(query, parameter);
-            query = query.bind_value(parameter);
+        let mut unique = std::collections::HashSet::new();
+        let distinct: Vec<_> = self.keys.iter().filter(|key| unique.insert(*key)).cloned().collect();
+
+        let mut found = std::collections::HashMap::with_capacity(distinct.len());
+        for chunk in distinct.chunks(<DB as HasVisitor<'static>>::MAX_PARAMETERS) {
+            let keys = chunk.iter().cloned().into_selection();
+            let select = T::default_select().so_that(T::primary_key().in_selection(keys));
+            let entities: Vec<T> = PreparedQuery::<DB>::build(select, false, &Comment::default())?.fetch_all(&mut *conn).await?;
+            found.extend(entities.into_iter().map(|entity| (entity.pk(), entity)));
         }
 
-        let _query_result = query
-            .execute(conn)
-            .await?;
-        Ok(())
+        let mut fetched = FetchedMany {
+            entities: Vec::with_capacity(self.keys.len()),
+            missing: Vec::new(),
+        };
+        for key in self.keys {
+            match found.get(&key) {
+                Some(entity) => fetched.entities.push(entity.clone()),
+                None => fetched.missing.push(key),
+            }
+        }
+        Ok(fetched)
     }
 }
 
-/// fetch entity from table. Returned by [`get`][crate::prelude::HasPrimaryKey::get].
-#[must_use = "save must be executed to affect database"]
-pub struct SavingExecution<'a, E, DB> {
-    saving: Update<'static>,
-    compiled: Option<String>,
-    entity: &'a mut E,
-    _marker: PhantomData<DB>,
+/// The entities found by [`FetchManyRequest`], in the order of the keys
+/// asked for, and the keys no entity was found for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchedMany<T, K> {
+    entities: Vec<T>,
+    missing: Vec<K>,
 }
 
-impl<'e, E: HasPrimaryKey, DB: Database> SavingExecution<'e, E, DB> {
-    pub fn new<'a>(saving: Update<'static>, entity: &'e mut E) -> Self
+impl<T, K> FetchedMany<T, K> {
+    /// The entities found, a key asked for twice gives the entity twice.
+    pub fn entities(&self) -> &[T] {
+        &self.entities
+    }
+
+    /// The keys without an entity, in the order they were asked for.
+    pub fn missing(&self) -> &[K] {
+        &self.missing
+    }
+
+    pub fn into_parts(self) -> (Vec<T>, Vec<K>) {
+        (self.entities, self.missing)
+    }
+}
+
+/// A lookup answered by an [`IdentityMap`] when it can. Returned by
+/// [`SelectingExecution::cached`].
+#[must_use = "query must be executed to affect database"]
+pub struct CachedSelectingExecution<T, DB: Database> {
+    execution: SelectingExecution<T, DB>,
+    map: IdentityMap,
+    key: Option<Value<'static>>,
+}
+
+impl<DB, T> CachedSelectingExecution<T, DB>
+where
+    DB: Database,
+    T: Entity + Clone + Send + 'static,
+{
+    pub async fn conn<'a, C>(self, conn: C) -> Result<T, crate::error::Error>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> sqlx::FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
     {
+        let key = match self.key {
+            Some(key) => key,
+            None => return self.execution.conn(conn).await,
+        };
+        if let Some(entity) = self.map.get::<T>(T::tablename(), &key) {
+            return Ok(entity);
+        }
+
+        let entity = self.execution.conn(conn).await?;
+        self.map.insert(T::tablename(), key, entity.clone());
+        Ok(entity)
+    }
+}
+
+impl<T, DB> From<crate::ast::Select<'static>> for SelectingExecution<T, DB>
+where
+    T: for<'r> FromRow<'r, <DB as Database>::Row>,
+    DB: sqlx::Database
+{
+    fn from(select: crate::ast::Select<'static>) -> Self {
         Self {
-            entity,
-            saving,
-            compiled: None,
+            select,
+            error: None,
+            comment: Comment::default(),
             _marker: PhantomData,
         }
     }
+}
 
-    #[must_use = "this must be used."]
-    pub async fn conn<'a, C>(&'a mut self, conn: C) -> Result<(), crate::error::Error>
-    where
-        'e: 'a,
-        C: 'a + Executioner<'a, DB>,
-        DB: 'a + sqlx::Database + for<'v> HasVisitor<'v>,
-        <DB as sqlx::database::HasArguments<'a>>::Arguments: 'a + IntoArguments<'a, DB>,
-        sqlx::query::Query<'a, DB, <DB as sqlx::database::HasArguments<'a>>::Arguments>: Binder<'a, DB>
-    {
-        let (compiled, parameters) =
-            <<C as sqlx::Executor<'a>>::Database as HasVisitor>::Visitor::build(self.saving.clone())?;
-        // 'a for borrowed from self.compiled
-        println!("compiled update: {}", &compiled);
-        self.compiled.replace(compiled);
-        let mut query = sqlx::query::<DB>(self.compiled.as_ref().unwrap());
-        for parameter in parameters {
-            query = query.bind_value(parameter);
-        }
-        let _query_result = query
-            .execute(conn)
-            .await?;
-        Ok(())
+impl Select<'static> {
+    /// Fetches the first row of the selection into any `T: FromRow`, no entity
+    /// needed: tuples, entities, or a single column with [`Scalar`].
+    ///
+    /// ```rust
+    /// # use entities::Dog;
+    /// # use xiayu::prelude::*;
+    /// # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+    /// let dogs = Select::from_table(Dog::table())
+    ///     .value(count(asterisk()))
+    ///     .fetch_as::<Scalar<i64>, sqlx::Sqlite>()
+    ///     .conn(conn)
+    ///     .await?
+    ///     .into_inner();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fetch_as<T, DB>(self) -> SelectingExecution<T, DB>
+    where
+        T: for<'r> FromRow<'r, <DB as Database>::Row>,
+        DB: sqlx::Database,
+    {
+        self.into()
     }
 }
 
-/// create table. Returned by [`get`][crate::prelude::entity::create_table].
-#[must_use = "create table must be executed to affect database"]
-pub struct CreateTableExecution<DB> {
-    _marker: PhantomData<DB>,
-    compiled: Option<String>,
+/// A row of a single column, decoded as `T`. E.g. the result of a `COUNT(*)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Scalar<T>(pub T);
+
+impl<T> Scalar<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
 }
 
-/// create table. Returned by [`get`][crate::prelude::entity::create].
-#[must_use = "insert must be executed to affect database"]
-#[derive(Clone, Debug)]
-pub struct InsertingExecution<DB, I> {
+impl<'r, R, T> FromRow<'r, R> for Scalar<T>
+where
+    R: sqlx::Row,
+    usize: sqlx::ColumnIndex<R>,
+    T: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
+    fn from_row(row: &'r R) -> Result<Self, sqlx::Error> {
+        row.try_get(0).map(Scalar)
+    }
+}
+
+/// Counts the rows of a table. Returned by
+/// [`count_distinct`][crate::prelude::Entity::count_distinct].
+#[must_use = "query must be executed to affect database"]
+pub struct CountingExecution<DB> {
+    select: Select<'static>,
+    comment: Comment,
     _marker: PhantomData<DB>,
-    insertion: I,
-    compiled: Option<String>,
 }
 
-impl<'a, DB> InsertingExecution<DB, MultiRowInsert<'a>> {
-    pub fn values<V>(mut self, values: V) -> Self
+impl<DB: Database> CountingExecution<DB> {
+    pub(crate) fn new(select: Select<'static>) -> Self {
+        Self {
+            select,
+            comment: Comment::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Counts only the rows matching `conditions`.
+    pub fn so_that<T>(mut self, conditions: T) -> Self
     where
-        V: Into<Row<'a>>,
+        T: Into<crate::ast::ConditionTree<'static>>,
     {
-        self.insertion = self.insertion.values(values);
+        self.select = self.select.so_that(conditions);
         self
     }
-}
 
-impl<'a, DB> InsertingExecution<DB, SingleRowInsert<'a>> {
-    pub fn value<K, V>(mut self, key: K, val: V) -> Self
+    /// Tags the query with `key` and `value` in its trailing comment, see
+    /// [`comment`](crate::comment).
+    pub fn comment<K, V>(mut self, key: K, value: V) -> Self
     where
-        K: Into<Column<'a>>,
-        V: Into<Expression<'a>>,
+        K: Into<Cow<'static, str>>,
+        V: ToString,
     {
-        self.insertion = self.insertion.value(key, val);
+        self.comment.tag(key, value);
         self
     }
-}
 
-/*
-impl<'a, DB> InsertingExecution<DB, Insert<'a>> {
-    pub fn expression<K, V>(mut self, key: K, val: V) -> Self
+    pub async fn conn<'a, C>(self, conn: C) -> crate::Result<u64>
     where
-        K: Into<Column<'a>>,
-        V: Into<Expression<'a>>,
+        C: Executioner<'a, DB>,
+        DB: for<'v> HasVisitor<'v>,
+        Counted: for<'r> FromRow<'r, DB::Row>,
     {
-        self.insertion = self.insertion.value(key, val);
-        self
+        let counted: Counted = self.prepare()?.fetch_one(conn).await?;
+        Ok(counted.0)
     }
-}
-*/
 
-impl<DB, I> InsertingExecution<DB, I> {
-    pub async fn conn<'a, C>(self, conn: C) -> crate::Result<DB::QueryResult>
+    pub fn prepare(self) -> crate::Result<PreparedQuery<DB>>
     where
-        C: Executioner<'a, DB>,
-        DB: sqlx::Database + for <'v> HasVisitor<'v>,
-        I: for<'i> Into<Insert<'i>> + Clone + Send,
+        DB: for<'v> HasVisitor<'v>,
     {
-        conn.insert(self).await
+        PreparedQuery::build(self.select, false, &self.comment)
     }
 }
 
-impl<'insert, DB> From<Insert<'insert>> for InsertingExecution<DB, Insert<'insert>> {
-    fn from(ins: Insert<'insert>) -> Self {
-        Self {
-            insertion: ins,
-            compiled: None,
-            _marker: PhantomData
-        }
+/// A count, a `BIGINT` in most databases and an `INT` in SQL Server.
+#[doc(hidden)]
+pub struct Counted(u64);
+
+impl<'r, R> FromRow<'r, R> for Counted
+where
+    R: sqlx::Row,
+    usize: sqlx::ColumnIndex<R>,
+    i64: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    i32: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
+    fn from_row(row: &'r R) -> Result<Self, sqlx::Error> {
+        let count = match row.try_get::<i64, _>(0) {
+            Ok(count) => count,
+            Err(sqlx::Error::ColumnDecode { .. }) => i64::from(row.try_get::<i32, _>(0)?),
+            Err(err) => return Err(err),
+        };
+        let count = u64::try_from(count).map_err(|err| sqlx::Error::ColumnDecode {
+            index: "0".to_string(),
+            source: Box::new(err),
+        })?;
+        Ok(Counted(count))
     }
 }
 
-impl<'insert, DB> From<SingleRowInsert<'insert>> for InsertingExecution<DB, SingleRowInsert<'insert>> {
-    fn from(ins: SingleRowInsert<'insert>) -> Self {
-        Self {
-            insertion: ins,
-            compiled: None,
-            _marker: PhantomData
-        }
-    }
+/// fetch entity from table. Returned by [`get`][crate::prelude::HasPrimaryKey::get].
+#[must_use = "delete must be executed to affect database"]
+pub struct DeletingExecution<'a, E, DB> {
+    delete: Delete<'static>,
+    error: Option<crate::error::Error>,
+    comment: Comment,
+    eviction: Option<Eviction>,
+    entity: &'a mut E,
+    _marker: PhantomData<DB>,
 }
 
-impl<'insert, DB> From<MultiRowInsert<'insert>> for InsertingExecution<DB, MultiRowInsert<'insert>> {
-    fn from(ins: MultiRowInsert<'insert>) -> Self {
+impl<'e, E: HasPrimaryKey, DB: Database> DeletingExecution<'e, E, DB> {
+    pub fn new<'a>(delete: crate::ast::Delete<'static>, entity: &'e mut E) -> Self
+    {
         Self {
-            insertion: ins,
-            compiled: None,
-            _marker: PhantomData
+            entity,
+            delete,
+            error: None,
+            comment: Comment::default(),
+            eviction: None,
+            _marker: PhantomData,
         }
     }
-}
 
-#[async_trait]
-pub trait Executioner<'c, DB>: sqlx::Executor<'c, Database = DB> where DB: for<'v> HasVisitor<'v> + sqlx::Database {
-    async fn save<E: HasPrimaryKey + Send>(self, entity: &mut E) -> crate::Result<()>;
-    async fn insert<'query, I: Into<Insert<'query>> + Send, IE: Into<InsertingExecution<DB, I>> + Send>(self, insertion: IE) -> crate::Result<DB::QueryResult>;
-}
+    /// Removes the entity from `map` when deleting it.
+    pub fn cached(mut self, map: &IdentityMap) -> Self
+    where
+        E::PrimaryKey: Into<Column<'static>>,
+    {
+        let key = key_of(self.delete.conditions.as_ref(), &E::primary_key().into());
+        self.eviction = Some(Eviction::new(map, E::tablename(), key));
+        self
+    }
+
+    /// Fails with `error` when executed, e.g. for an entity without a
+    /// primary key.
+    pub fn failed(mut self, error: crate::error::Error) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    /// Tags the statement with `key` and `value` in its trailing comment, see
+    /// [`comment`](crate::comment).
+    pub fn comment<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: ToString,
+    {
+        self.comment.tag(key, value);
+        self
+    }
+
+    /// Sends the statement without the trailing comment, default tags
+    /// included.
+    pub fn without_comments(mut self) -> Self {
+        self.comment.clear();
+        self
+    }
+
+    /// Deletes the entity. No row is affected when it was deleted already,
+    /// see [`ExecResult::rows_affected`].
+    pub async fn conn<'a, C>(mut self, conn: C) -> crate::Result<ExecResult>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    {
+        if let Some(eviction) = self.eviction.take() {
+            eviction.evict();
+        }
+        Ok(self.prepare()?.execute(conn).await?.without_insert_id())
+    }
+
+    /// Builds the delete once, to run it as many times as needed.
+    pub fn prepare(self) -> crate::Result<PreparedQuery<DB>>
+    where
+        DB: for<'v> HasVisitor<'v>,
+    {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        PreparedQuery::build(self.delete, false, &self.comment)
+    }
+}
+
+/// Deletes the rows of a table matching a condition. Returned by
+/// [`delete_where`][crate::prelude::Entity::delete_where].
+#[must_use = "delete must be executed to affect database"]
+pub struct DeleteManyRequest<E, DB> {
+    delete: Delete<'static>,
+    comment: Comment,
+    _marker: PhantomData<(fn() -> E, DB)>,
+}
+
+impl<E: Entity, DB: Database> DeleteManyRequest<E, DB> {
+    pub(crate) fn new(delete: Delete<'static>) -> Self {
+        Self {
+            delete,
+            comment: Comment::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Tags the statement with `key` and `value` in its trailing comment, see
+    /// [`comment`](crate::comment).
+    pub fn comment<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: ToString,
+    {
+        self.comment.tag(key, value);
+        self
+    }
+
+    /// Sends the statement without the trailing comment, default tags
+    /// included.
+    pub fn without_comments(mut self) -> Self {
+        self.comment.clear();
+        self
+    }
+
+    /// Deletes the matching rows, returning how many were deleted.
+    pub async fn conn<'a, C>(self, conn: C) -> crate::Result<u64>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    {
+        Ok(self.prepare()?.execute(conn).await?.rows_affected())
+    }
+
+    /// Builds the delete once, to run it as many times as needed.
+    pub fn prepare(self) -> crate::Result<PreparedQuery<DB>>
+    where
+        DB: for<'v> HasVisitor<'v>,
+    {
+        PreparedQuery::build(self.delete, false, &self.comment)
+    }
+}
+
+/// Told the rows purged so far after every batch, see
+/// [`PurgeRequest::on_progress`].
+type Progress<'a> = Box<dyn FnMut(&Purged) + Send + 'a>;
+
+/// Deletes the rows older than a cutoff in batches, each one a short
+/// statement holding its locks briefly. Returned by
+/// [`purge_older_than`][crate::prelude::Entity::purge_older_than].
+#[must_use = "purge must be executed to affect database"]
+pub struct PurgeRequest<'a, E, DB> {
+    column: Column<'static>,
+    cutoff: Value<'static>,
+    batch_size: usize,
+    pause: Option<std::time::Duration>,
+    max_batches: Option<usize>,
+    progress: Option<Progress<'a>>,
+    comment: Comment,
+    _marker: PhantomData<(fn() -> E, DB)>,
+}
+
+impl<'a, E: Entity, DB: Database> PurgeRequest<'a, E, DB> {
+    pub(crate) fn new(column: Column<'static>, cutoff: Value<'static>, batch_size: usize) -> Self {
+        Self {
+            column,
+            cutoff,
+            batch_size,
+            pause: None,
+            max_batches: None,
+            progress: None,
+            comment: Comment::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Waits `pause` between two batches, letting other writers through.
+    pub fn pause(mut self, pause: std::time::Duration) -> Self {
+        self.pause = Some(pause);
+        self
+    }
+
+    /// Stops after `max_batches` batches even if rows older than the cutoff
+    /// are left, see [`Purged::is_complete`].
+    pub fn max_batches(mut self, max_batches: usize) -> Self {
+        self.max_batches = Some(max_batches);
+        self
+    }
+
+    /// Calls `progress` with the rows purged so far after every batch.
+    pub fn on_progress<F>(mut self, progress: F) -> Self
+    where
+        F: FnMut(&Purged) + Send + 'a,
+    {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Tags the statements with `key` and `value` in their trailing comment,
+    /// see [`comment`](crate::comment).
+    pub fn comment<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: ToString,
+    {
+        self.comment.tag(key, value);
+        self
+    }
+
+    /// Deletes the rows batch after batch, until a batch deletes fewer rows
+    /// than the batch size or the maximum number of batches ran.
+    pub async fn conn<C>(mut self, conn: &mut C) -> crate::Result<Purged>
+    where
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        for<'e> &'e mut C: Executioner<'e, DB>,
+    {
+        if self.batch_size == 0 {
+            let kind = crate::error::ErrorKind::InvalidDelete("a purge deletes batches of one row at least");
+            return Err(crate::error::Error::builder(kind).build());
+        }
+        // Compared as is, an index on the column finds the rows.
+        let batch = Delete::from_table(E::table())
+            .so_that(self.column.clone().less_than(self.cutoff))
+            .order_by(self.column)
+            .limit(self.batch_size);
+        let batch = PreparedQuery::<DB>::build(batch, false, &self.comment)?;
+
+        let mut purged = Purged::default();
+        loop {
+            let rows = batch.execute(&mut *conn).await?.rows_affected();
+            purged.rows += rows;
+            purged.batches += 1;
+            purged.complete = rows < self.batch_size as u64;
+            tracing::debug!(table = E::tablename(), rows, batches = purged.batches, "purged a batch");
+            if let Some(progress) = self.progress.as_mut() {
+                progress(&purged);
+            }
+
+            if purged.complete || self.max_batches.is_some_and(|max| purged.batches >= max) {
+                return Ok(purged);
+            }
+            if let Some(pause) = self.pause {
+                crate::retry::Delay::new(pause).await;
+            }
+        }
+    }
+}
+
+/// The rows deleted by a [`PurgeRequest`] and the batches it took.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Purged {
+    rows: u64,
+    batches: usize,
+    complete: bool,
+}
+
+impl Purged {
+    pub fn rows_deleted(&self) -> u64 {
+        self.rows
+    }
+
+    /// The statements run, the last one deleting fewer rows than the batch
+    /// size, maybe none.
+    pub fn batches(&self) -> usize {
+        self.batches
+    }
+
+    /// Whether no row older than the cutoff is left, `false` when stopped by
+    /// [`PurgeRequest::max_batches`].
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+/// Updates the entity once saved, see [`SavingExecution::refreshed`].
+type Refresh<'a, E> = Box<dyn FnOnce(&mut E) + Send + 'a>;
+
+/// Writes the key generated for an inserted entity back into it, see
+/// [`SavingExecution::generating_key`].
+pub type WriteKey<E> = fn(&mut E, i64) -> crate::Result<()>;
+
+/// fetch entity from table. Returned by [`get`][crate::prelude::HasPrimaryKey::get].
+#[must_use = "save must be executed to affect database"]
+pub struct SavingExecution<'a, E, DB> {
+    pub(crate) saving: Query<'static>,
+    pub(crate) error: Option<crate::error::Error>,
+    pub(crate) validate_lengths: bool,
+    pub(crate) comment: Comment,
+    eviction: Option<Eviction>,
+    refresh: Option<Refresh<'a, E>>,
+    pub(crate) generated_key: Option<(Column<'static>, WriteKey<E>)>,
+    entity: &'a mut E,
+    _marker: PhantomData<DB>,
+}
+
+impl<'e, E: HasPrimaryKey, DB: Database> SavingExecution<'e, E, DB> {
+    /// Saves with `saving`, an `UPDATE` of an existing row or an `INSERT`
+    /// of a new one.
+    pub fn new<Q>(saving: Q, entity: &'e mut E) -> Self
+    where
+        Q: Into<Query<'static>>,
+    {
+        Self {
+            entity,
+            saving: saving.into(),
+            error: None,
+            validate_lengths: false,
+            comment: Comment::default(),
+            eviction: None,
+            refresh: None,
+            generated_key: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Reads the value the database generates for `key` when inserting the
+    /// entity, as [`InsertingExecution::returning_id`] does, and writes it
+    /// into the entity with `write`. An update is saved as is.
+    pub fn generating_key<K>(mut self, key: K, write: WriteKey<E>) -> Self
+    where
+        K: Into<Column<'static>>,
+    {
+        self.generated_key = Some((key.into(), write));
+        self
+    }
+
+    /// Calls `refresh` with the entity once saved, e.g. to write back the
+    /// values of `onupdate` columns set by the statement.
+    pub fn refreshed<F>(mut self, refresh: F) -> Self
+    where
+        F: FnOnce(&mut E) + Send + 'e,
+    {
+        self.refresh = Some(Box::new(refresh));
+        self
+    }
+
+    /// Removes the entity from `map` when saving it, it is fetched again
+    /// as saved by the next cached lookup.
+    pub fn cached(mut self, map: &IdentityMap) -> Self
+    where
+        E::PrimaryKey: Into<Column<'static>>,
+    {
+        let key = match &self.saving {
+            Query::Update(update) => key_of(update.conditions.as_ref(), &E::primary_key().into()),
+            _ => None,
+        };
+        self.eviction = Some(Eviction::new(map, E::tablename(), key));
+        self
+    }
+
+    /// Fails with `error` when executed, e.g. for an entity without a
+    /// primary key.
+    pub fn failed(mut self, error: crate::error::Error) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    /// Tags the statement with `key` and `value` in its trailing comment, see
+    /// [`comment`](crate::comment).
+    pub fn comment<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: ToString,
+    {
+        self.comment.tag(key, value);
+        self
+    }
+
+    /// Sends the statement without the trailing comment, default tags
+    /// included.
+    pub fn without_comments(mut self) -> Self {
+        self.comment.clear();
+        self
+    }
+
+    /// Checks the text and bytes bound to columns with a `length`, see
+    /// [`InsertingExecution::validate_lengths`].
+    pub fn validate_lengths(mut self, validate: bool) -> Self {
+        self.validate_lengths = validate;
+        self
+    }
+
+    /// Saves the entity. Updating a row which does not exist, e.g. deleted
+    /// meanwhile, affects no row, see [`ExecResult::rows_affected`].
+    #[must_use = "this must be used."]
+    pub async fn conn<'a, C>(mut self, conn: C) -> crate::Result<ExecResult>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    {
+        if let Some(eviction) = self.eviction.take() {
+            eviction.evict();
+        }
+        let inserting = matches!(self.saving, Query::Insert(_));
+        let refresh = self.refresh.take();
+        let entity = &mut *self.entity;
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let result = match (self.saving, self.generated_key) {
+            (Query::Insert(insert), Some((key, write))) => {
+                let mut insertion = InsertingExecution::from(*insert)
+                    .generated_key(key)
+                    .validate_lengths(self.validate_lengths);
+                insertion.comment = self.comment;
+                let result = conn.insert_returning_key(insertion).await?;
+                write(entity, generated_key_of(&result)?)?;
+                result
+            }
+            (saving, _) => PreparedQuery::<DB>::build(saving, self.validate_lengths, &self.comment)?.execute(conn).await?,
+        };
+        if let Some(refresh) = refresh {
+            refresh(entity);
+        }
+        Ok(if inserting { result } else { result.without_insert_id() })
+    }
+
+    /// Builds the statement saving the entity as it is now, to run it as
+    /// many times as needed.
+    pub fn prepare(self) -> crate::Result<PreparedQuery<DB>>
+    where
+        DB: for<'v> HasVisitor<'v>,
+    {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        PreparedQuery::build(self.saving, self.validate_lengths, &self.comment)
+    }
+}
+
+/// Updates the columns of an entity changed since it was loaded. Returned by
+/// the `update` of entities deriving with `#[entity(track_changes)]`, see
+/// [`Snapshot`](crate::prelude::Snapshot).
+///
+/// Nothing is sent when nothing changed. Unlike `save`, the `onupdate`
+/// columns are left as they are.
+#[must_use = "update must be executed to affect database"]
+pub struct UpdatingExecution<'a, E, DB> {
+    update: Option<Update<'static>>,
+    values: Vec<Value<'static>>,
+    error: Option<crate::error::Error>,
+    validate_lengths: bool,
+    comment: Comment,
+    eviction: Option<Eviction>,
+    entity: &'a mut E,
+    snapshot: fn(&mut E) -> &mut crate::prelude::Snapshot,
+    _marker: PhantomData<DB>,
+}
+
+impl<'e, E: HasPrimaryKey, DB: Database> UpdatingExecution<'e, E, DB> {
+    /// Updates the columns of `entity` whose values differ from the ones in
+    /// its `snapshot`, in the row matching `conditions`.
+    pub fn new<C>(entity: &'e mut E, conditions: C, snapshot: fn(&mut E) -> &mut crate::prelude::Snapshot) -> Self
+    where
+        C: Into<crate::ast::ConditionTree<'static>>,
+    {
+        let values = entity.values();
+        let changed = snapshot(entity).changed(&values);
+        let columns = E::columns();
+        let update = changed
+            .into_iter()
+            .fold(None, |update: Option<Update<'static>>, index| {
+                let update = update.unwrap_or_else(|| Update::table(E::table()));
+                Some(update.set(columns[index].clone(), values[index].clone()))
+            })
+            .map(|update| update.so_that(conditions));
+
+        Self {
+            update,
+            values,
+            error: None,
+            validate_lengths: false,
+            comment: Comment::default(),
+            eviction: None,
+            entity,
+            snapshot,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Whether the entity changed, executing an unchanged one sends nothing.
+    pub fn has_changes(&self) -> bool {
+        self.update.is_some()
+    }
+
+    /// Removes the entity from `map` when updating it.
+    pub fn cached(mut self, map: &IdentityMap) -> Self
+    where
+        E::PrimaryKey: Into<Column<'static>>,
+    {
+        if let Some(update) = &self.update {
+            let key = key_of(update.conditions.as_ref(), &E::primary_key().into());
+            self.eviction = Some(Eviction::new(map, E::tablename(), key));
+        }
+        self
+    }
+
+    /// Fails with `error` when executed, e.g. for an entity without a
+    /// primary key.
+    pub fn failed(mut self, error: crate::error::Error) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    /// Tags the statement with `key` and `value` in its trailing comment, see
+    /// [`comment`](crate::comment).
+    pub fn comment<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: ToString,
+    {
+        self.comment.tag(key, value);
+        self
+    }
+
+    /// Sends the statement without the trailing comment, default tags
+    /// included.
+    pub fn without_comments(mut self) -> Self {
+        self.comment.clear();
+        self
+    }
+
+    /// Checks the text and bytes bound to columns with a `length`, see
+    /// [`InsertingExecution::validate_lengths`].
+    pub fn validate_lengths(mut self, validate: bool) -> Self {
+        self.validate_lengths = validate;
+        self
+    }
+
+    /// Runs the update, then takes a new snapshot of the entity. An
+    /// unchanged entity succeeds with an empty result, without a query.
+    #[must_use = "this must be used."]
+    pub async fn conn<'a, C>(mut self, conn: C) -> crate::Result<ExecResult>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let update = match self.update {
+            Some(update) => update,
+            None => return Ok(ExecResult::default()),
+        };
+        if let Some(eviction) = self.eviction.take() {
+            eviction.evict();
+        }
+        let result = PreparedQuery::<DB>::build(update, self.validate_lengths, &self.comment)?
+            .execute(conn)
+            .await?;
+        *(self.snapshot)(self.entity) = crate::prelude::Snapshot::taken(self.values);
+        Ok(result.without_insert_id())
+    }
+
+    /// Builds the update of the changed columns, `None` when nothing
+    /// changed. The snapshot of the entity is left as it is.
+    pub fn prepare(self) -> crate::Result<Option<PreparedQuery<DB>>>
+    where
+        DB: for<'v> HasVisitor<'v>,
+    {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        match self.update {
+            Some(update) => PreparedQuery::build(update, self.validate_lengths, &self.comment).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A query built once, to be run as many times as needed: the SQL and its
+/// parameters, owned. Returned by `prepare` of the executions.
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// #[derive(Debug, Entity)]
+/// #[tablename = "counters"]
+/// struct Counter {
+///     #[column(primary_key)]
+///     id: i64,
+///     hits: i64,
+/// }
+///
+/// # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+/// let lookup = Counter::get::<sqlx::Sqlite, _>(1).prepare()?;
+///
+/// let before = lookup.fetch_one::<Counter, _>(&mut *conn).await?;
+/// // ...
+/// let after = lookup.fetch_one::<Counter, _>(&mut *conn).await?;
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedQuery<DB> {
+    sql: String,
+    parameters: Vec<Value<'static>>,
+    _marker: PhantomData<DB>,
+}
+
+impl<DB> PreparedQuery<DB> {
+    /// A statement formatted without a visitor, see
+    /// [`fast_select_by_pk`](crate::prelude::HasPrimaryKey::fast_select_by_pk).
+    pub(crate) fn from_parts(sql: String, parameters: Vec<Value<'static>>) -> Self {
+        Self {
+            sql,
+            parameters,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn build<'q, Q>(query: Q, validate_lengths: bool, comment: &Comment) -> crate::Result<Self>
+    where
+        DB: HasVisitor<'q>,
+        Q: Into<Query<'q>>,
+    {
+        let (sql, parameters) = build_validated::<DB, Q>(query, validate_lengths, comment)?;
+        Ok(Self {
+            sql,
+            parameters: parameters.into_iter().map(Value::into_owned).collect(),
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// The SQL formatted for reading, see
+    /// [`format_sql`](crate::visitors::format_sql).
+    pub fn pretty_sql(&self) -> String {
+        crate::visitors::format_sql(&self.sql)
+    }
+
+    pub fn parameters(&self) -> &[Value<'static>] {
+        &self.parameters
+    }
+
+    /// Runs the statement.
+    pub async fn execute<'a, C>(&self, conn: C) -> crate::Result<ExecResult>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    {
+        conn.execute_compiled(self.sql.clone(), self.parameters.clone()).await
+    }
+
+    /// Runs the query, decoding its first row.
+    pub async fn fetch_one<'a, T, C>(&self, conn: C) -> crate::Result<T>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    {
+        conn.fetch_one_compiled(self.sql.clone(), self.parameters.clone()).await
+    }
+
+    /// Runs the query, decoding its first row if it returns one.
+    pub async fn fetch_optional<'a, T, C>(&self, conn: C) -> crate::Result<Option<T>>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    {
+        conn.fetch_optional_compiled(self.sql.clone(), self.parameters.clone()).await
+    }
+
+    /// Runs the query, decoding all of its rows.
+    pub async fn fetch_all<'a, T, C>(&self, conn: C) -> crate::Result<Vec<T>>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    {
+        conn.fetch_all_compiled(self.sql.clone(), self.parameters.clone()).await
+    }
+
+    /// Runs the query, decoding its rows as they arrive, see
+    /// [`SelectingExecution::fetch`].
+    pub fn fetch<'a, T, C>(&self, conn: C) -> RowStream<'a, T>
+    where
+        C: Executioner<'a, DB> + 'a,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin + 'a,
+    {
+        conn.fetch_compiled(self.sql.clone(), self.parameters.clone())
+    }
+
+    /// The plan of the query, see [`SelectingExecution::explain_as`].
+    pub async fn explain<C>(&self, format: ExplainFormat, conn: &mut C) -> crate::Result<Vec<String>>
+    where
+        DB: HasExplain,
+        for<'e> &'e mut C: Executioner<'e, DB>,
+    {
+        let sql = DB::explain(&self.sql, format)?;
+        let session = DB::plan_session(format);
+
+        if let Some((on, _)) = session {
+            (&mut *conn).execute_compiled(on.to_string(), Vec::new()).await?;
+        }
+        let plan = (&mut *conn).fetch_all_compiled::<PlanLine<DB>>(sql, self.parameters.clone()).await;
+        if let Some((_, off)) = session {
+            // Turned off whether the plan was shown or not, the connection
+            // would run nothing otherwise.
+            let off = (&mut *conn).execute_compiled(off.to_string(), Vec::new()).await;
+            if plan.is_ok() {
+                off?;
+            }
+        }
+
+        Ok(plan?.into_iter().map(|PlanLine(line, _)| line).collect())
+    }
+}
+
+/// SQL written by hand, decoded into `T`, for the queries the AST can not
+/// express. Returned by [`Entity::raw_query`].
+///
+/// The parameters are bound in order and converted as those of the built
+/// queries. Before anything is sent, their number is checked against the
+/// placeholders of the SQL, `?` or the numbered ones of the database,
+/// failing with
+/// [`IncorrectNumberOfParameters`](crate::error::ErrorKind::IncorrectNumberOfParameters).
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// #[derive(Debug, Entity)]
+/// #[tablename = "users"]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i64,
+///     email: String,
+/// }
+///
+/// # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+/// let users: Vec<User> = User::raw_query::<sqlx::Sqlite>("SELECT * FROM users WHERE lower(email) = lower(?)")
+///     .bind("ada@example.com")
+///     .fetch_all(conn)
+///     .await?;
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawQuery<T, DB> {
+    sql: String,
+    parameters: Vec<Value<'static>>,
+    _marker: PhantomData<fn() -> (T, DB)>,
+}
+
+impl<T, DB> RawQuery<T, DB> {
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            parameters: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Binds the value of the next placeholder.
+    pub fn bind<V: Into<Value<'static>>>(mut self, value: V) -> Self {
+        self.parameters.push(value.into());
+        self
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    pub fn parameters(&self) -> &[Value<'static>] {
+        &self.parameters
+    }
+
+    fn checked(&self) -> crate::Result<(String, Vec<Value<'static>>)>
+    where
+        DB: for<'v> HasVisitor<'v>,
+    {
+        let expected = <<DB as HasVisitor<'static>>::Visitor as Visitor>::count_placeholders(&self.sql);
+        if expected != self.parameters.len() {
+            let kind = crate::error::ErrorKind::IncorrectNumberOfParameters {
+                expected,
+                actual: self.parameters.len(),
+            };
+            return Err(crate::error::Error::builder(kind).build());
+        }
+        check_parameter_count::<DB>(expected)?;
+        Ok((self.sql.clone(), self.parameters.clone()))
+    }
+
+    /// Runs the statement.
+    pub async fn execute<'a, C>(&self, conn: C) -> crate::Result<ExecResult>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    {
+        let (sql, parameters) = self.checked()?;
+        conn.execute_compiled(sql, parameters).await
+    }
+
+    /// Runs the query, decoding its first row.
+    pub async fn fetch_one<'a, C>(&self, conn: C) -> crate::Result<T>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    {
+        let (sql, parameters) = self.checked()?;
+        conn.fetch_one_compiled(sql, parameters).await
+    }
+
+    /// Runs the query, decoding all of its rows.
+    pub async fn fetch_all<'a, C>(&self, conn: C) -> crate::Result<Vec<T>>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    {
+        let (sql, parameters) = self.checked()?;
+        conn.fetch_all_compiled(sql, parameters).await
+    }
+}
+
+/// Builds `query` with the visitor of `DB`, ending with `comment`. With
+/// `validate_lengths`, text and bytes longer than the `length` of the column
+/// they are bound to fail with
+/// [`LengthMismatch`](crate::error::ErrorKind::LengthMismatch).
+pub(crate) fn build_validated<'a, DB, Q>(
+    query: Q,
+    validate_lengths: bool,
+    comment: &Comment,
+) -> crate::Result<(String, Vec<Value<'a>>)>
+where
+    DB: HasVisitor<'a>,
+    Q: Into<Query<'a>>,
+{
+    if !validate_lengths {
+        let (mut sql, parameters) = DB::Visitor::build(query)?;
+        check_parameter_count::<DB>(parameters.len())?;
+        comment.append_to(&mut sql);
+        return Ok((sql, parameters));
+    }
+
+    let (mut sql, parameters, columns) = DB::Visitor::build_with_columns(query, QuoteStyle::default())?;
+    check_parameter_count::<DB>(parameters.len())?;
+    for (value, column) in parameters.iter().zip(columns.iter()) {
+        let (name, limit) = match column {
+            Some(ColumnMeta { name, length: Some(limit), .. }) => (name, *limit),
+            _ => continue,
+        };
+        let length = match value {
+            Value::Text(Some(text)) | Value::Enum(Some(text)) | Value::Xml(Some(text)) => text.chars().count(),
+            Value::Bytes(Some(bytes)) => bytes.len(),
+            _ => continue,
+        };
+
+        if length > limit {
+            let kind = crate::error::ErrorKind::LengthMismatch {
+                column: crate::error::Name::available(name),
+            };
+            return Err(crate::error::Error::builder(kind).build());
+        }
+    }
+
+    comment.append_to(&mut sql);
+    Ok((sql, parameters))
+}
+
+/// Fails with [`TooManyParameters`](crate::error::ErrorKind::TooManyParameters)
+/// when a statement binding `count` parameters is more than `DB` accepts.
+pub(crate) fn check_parameter_count<'a, DB>(count: usize) -> crate::Result<()>
+where
+    DB: HasVisitor<'a>,
+{
+    if count <= DB::MAX_PARAMETERS {
+        return Ok(());
+    }
+
+    let kind = crate::error::ErrorKind::TooManyParameters {
+        got: count,
+        max: DB::MAX_PARAMETERS,
+        database: DB::NAME,
+    };
+    Err(crate::error::Error::builder(kind).build())
+}
+
+/// What a statement did, the same for every database. Returned by the
+/// executions and the [`Executioner`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecResult {
+    rows_affected: u64,
+    last_insert_id: Option<i64>,
+}
+
+impl ExecResult {
+    pub fn new(rows_affected: u64, last_insert_id: Option<i64>) -> Self {
+        Self {
+            rows_affected,
+            last_insert_id,
+        }
+    }
+
+    pub fn rows_affected(&self) -> u64 {
+        self.rows_affected
+    }
+
+    /// The key generated by an insert, on MySQL and SQLite. `None` on
+    /// PostgreSQL and SQL Server, where
+    /// [`returning_id`](InsertingExecution::returning_id) reads it with
+    /// `RETURNING` or `OUTPUT`, and for statements other than inserts.
+    pub fn last_insert_id(&self) -> Option<i64> {
+        self.last_insert_id
+    }
+
+    /// Leaves out the key, for statements which are not inserts: SQLite
+    /// reports the last key inserted by the connection.
+    pub(crate) fn without_insert_id(self) -> Self {
+        Self {
+            last_insert_id: None,
+            ..self
+        }
+    }
+}
+
+/// What the statements run by
+/// [`execute_batch`](Executioner::execute_batch) did, by statement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    rows_affected: Vec<u64>,
+}
+
+impl BatchReport {
+    /// The rows affected by each statement, in order.
+    pub fn rows_affected(&self) -> &[u64] {
+        &self.rows_affected
+    }
+
+    /// The rows affected by all the statements.
+    pub fn total_rows_affected(&self) -> u64 {
+        self.rows_affected.iter().sum()
+    }
+
+    /// The number of statements run.
+    pub fn len(&self) -> usize {
+        self.rows_affected.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows_affected.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, rows_affected: u64) {
+        self.rows_affected.push(rows_affected);
+    }
+}
+
+/// Builds every statement of a batch before any runs, a statement failing to
+/// build fails the batch with its index.
+pub(crate) fn build_batch<'a, DB, I>(statements: I) -> crate::Result<Vec<(String, Vec<Value<'static>>)>>
+where
+    DB: HasVisitor<'a>,
+    I: IntoIterator<Item = Query<'a>>,
+{
+    statements
+        .into_iter()
+        .enumerate()
+        .map(|(index, statement)| {
+            let built = DB::Visitor::build(statement)
+                .and_then(|(sql, parameters)| check_parameter_count::<DB>(parameters.len()).map(|_| (sql, parameters)));
+            match built {
+                Ok((mut sql, parameters)) => {
+                    Comment::default().append_to(&mut sql);
+                    Ok((sql, parameters.into_iter().map(Value::into_owned).collect()))
+                }
+                Err(source) => Err(batch_failed(index, String::new(), source)),
+            }
+        })
+        .collect()
+}
+
+/// The error of a batch whose statement at `index` failed with `source`.
+pub(crate) fn batch_failed(index: usize, sql: String, source: crate::error::Error) -> crate::error::Error {
+    let code = source.original_code().map(str::to_owned);
+    let message = source.original_message().map(str::to_owned);
+    let mut builder = crate::error::Error::builder(crate::error::ErrorKind::BatchFailed {
+        index,
+        sql,
+        source: Box::new(source),
+    });
+    if let Some(code) = code {
+        builder.set_original_code(code);
+    }
+    if let Some(message) = message {
+        builder.set_original_message(message);
+    }
+    builder.build()
+}
+
+/// create table. Returned by [`get`][crate::prelude::entity::create_table].
+#[must_use = "create table must be executed to affect database"]
+pub struct CreateTableExecution<DB> {
+    _marker: PhantomData<DB>,
+}
+
+/// create table. Returned by [`get`][crate::prelude::entity::create].
+#[must_use = "insert must be executed to affect database"]
+#[derive(Clone, Debug)]
+pub struct InsertingExecution<DB, I> {
+    _marker: PhantomData<DB>,
+    pub(crate) insertion: I,
+    pub(crate) validate_lengths: bool,
+    pub(crate) comment: Comment,
+    generated_key: Option<Column<'static>>,
+    use_returning: bool,
+}
+
+impl<'a, DB> InsertingExecution<DB, MultiRowInsert<'a>> {
+    pub fn values<V>(mut self, values: V) -> Self
+    where
+        V: Into<Row<'a>>,
+    {
+        self.insertion = self.insertion.values(values);
+        self
+    }
+}
+
+impl<'a, DB> InsertingExecution<DB, SingleRowInsert<'a>> {
+    pub fn value<K, V>(mut self, key: K, val: V) -> Self
+    where
+        K: Into<Column<'a>>,
+        V: Into<Expression<'a>>,
+    {
+        self.insertion = self.insertion.value(key, val);
+        self
+    }
+}
+
+/*
+impl<'a, DB> InsertingExecution<DB, Insert<'a>> {
+    pub fn expression<K, V>(mut self, key: K, val: V) -> Self
+    where
+        K: Into<Column<'a>>,
+        V: Into<Expression<'a>>,
+    {
+        self.insertion = self.insertion.value(key, val);
+        self
+    }
+}
+*/
+
+impl<DB, I> InsertingExecution<DB, I> {
+    /// The insert, e.g. for building it with a visitor.
+    pub fn into_inner(self) -> I {
+        self.insertion
+    }
+
+    /// Checks the text and bytes bound to columns with a `length` before
+    /// sending the insert, failing with [`LengthMismatch`] instead of
+    /// letting the database truncate the value or reject it.
+    ///
+    /// [`LengthMismatch`]: crate::error::ErrorKind::LengthMismatch
+    pub fn validate_lengths(mut self, validate: bool) -> Self {
+        self.validate_lengths = validate;
+        self
+    }
+
+    /// Tags the statement with `key` and `value` in its trailing comment, see
+    /// [`comment`](crate::comment).
+    pub fn comment<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'static, str>>,
+        V: ToString,
+    {
+        self.comment.tag(key, value);
+        self
+    }
+
+    /// Sends the statement without the trailing comment, default tags
+    /// included.
+    pub fn without_comments(mut self) -> Self {
+        self.comment.clear();
+        self
+    }
+
+    /// Builds the insert, once, and runs it: a pool or a retry gets the
+    /// built statement.
+    pub async fn conn<'a, 'i, C>(self, conn: C) -> crate::Result<ExecResult>
+    where
+        C: Executioner<'a, DB>,
+        DB: sqlx::Database + for <'v> HasVisitor<'v>,
+        I: Into<Insert<'i>>,
+    {
+        self.prepare()?.execute(conn).await
+    }
+
+    /// Builds the insert once, to run it as many times as needed.
+    pub fn prepare<'i>(self) -> crate::Result<PreparedQuery<DB>>
+    where
+        DB: for<'v> HasVisitor<'v>,
+        I: Into<Insert<'i>>,
+    {
+        PreparedQuery::build::<Insert>(self.insertion.into(), self.validate_lengths, &self.comment)
+    }
+
+    /// The column generated by the database, read by
+    /// [`returning_id`](Self::returning_id). Inserts built by
+    /// [`Entity::insert`](crate::prelude::Entity::insert) use the primary key.
+    pub fn generated_key<K>(mut self, column: K) -> Self
+    where
+        K: Into<Column<'static>>,
+    {
+        self.generated_key = Some(column.into());
+        self
+    }
+
+    /// Reads the generated key from the result of the insert rather than with
+    /// `RETURNING`, for SQLite before 3.35.
+    pub fn without_returning(mut self) -> Self {
+        self.use_returning = false;
+        self
+    }
+
+    /// Inserts the row, returning the key generated for it: selected with
+    /// `RETURNING` on PostgreSQL and SQLite, with `OUTPUT` on SQL Server, and
+    /// read from the result of the insert on MySQL.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "tags"]
+    /// struct Tag {
+    ///     #[column(primary_key, autoincrement)]
+    ///     id: i32,
+    ///     label: String,
+    /// }
+    ///
+    /// # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+    /// let id: i32 = Tag::insert()
+    ///     .value(Tag::label, "rust")
+    ///     .returning_id(conn)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    pub async fn returning_id<'a, 'i, K>(self, conn: impl Executioner<'a, DB>) -> crate::Result<K>
+    where
+        DB: HasGeneratedKey + for<'v> HasVisitor<'v>,
+        I: Into<Insert<'i>>,
+        K: TryFrom<i64> + Send + Unpin,
+        Scalar<K>: for<'r> FromRow<'r, DB::Row>,
+    {
+        if DB::RETURNING && self.use_returning {
+            let key = match self.generated_key {
+                Some(key) => key,
+                None => {
+                    let kind = crate::error::ErrorKind::conversion("the generated key of the insert is not known");
+                    return Err(crate::error::Error::builder(kind).build());
+                }
+            };
+            let insert = self.insertion.into().returning(vec![key]);
+            // On SQL Server the key is the `OUTPUT` of a plain insert, or
+            // selected after a `MERGE`: the first row is read either way.
+            let (sql, parameters) = build_validated::<DB, Insert>(insert, self.validate_lengths, &self.comment)?;
+            let parameters = parameters.into_iter().map(Value::into_owned).collect();
+            let Scalar(id) = conn.fetch_one_compiled::<Scalar<K>>(sql, parameters).await?;
+            return Ok(id);
+        }
+
+        let result = self.prepare()?.execute(conn).await?;
+        crate::prelude::convert_generated_key(generated_key_of(&result)?)
+    }
+}
+
+/// The key generated by an insert, from its result.
+fn generated_key_of(result: &ExecResult) -> crate::Result<i64> {
+    result.last_insert_id().ok_or_else(|| {
+        let kind = crate::error::ErrorKind::conversion("the database reports no generated key without RETURNING");
+        crate::error::Error::builder(kind).build()
+    })
+}
+
+impl<'insert, DB> From<Insert<'insert>> for InsertingExecution<DB, Insert<'insert>> {
+    fn from(ins: Insert<'insert>) -> Self {
+        Self {
+            insertion: ins,
+            validate_lengths: false,
+            comment: Comment::default(),
+            generated_key: None,
+            use_returning: true,
+            _marker: PhantomData
+        }
+    }
+}
+
+impl<'insert, DB> From<SingleRowInsert<'insert>> for InsertingExecution<DB, SingleRowInsert<'insert>> {
+    fn from(ins: SingleRowInsert<'insert>) -> Self {
+        Self {
+            insertion: ins,
+            validate_lengths: false,
+            comment: Comment::default(),
+            generated_key: None,
+            use_returning: true,
+            _marker: PhantomData
+        }
+    }
+}
+
+impl<'insert, DB> From<MultiRowInsert<'insert>> for InsertingExecution<DB, MultiRowInsert<'insert>> {
+    fn from(ins: MultiRowInsert<'insert>) -> Self {
+        Self {
+            insertion: ins,
+            validate_lengths: false,
+            comment: Comment::default(),
+            generated_key: None,
+            use_returning: true,
+            _marker: PhantomData
+        }
+    }
+}
+
+/// Saves and inserts through a connection, pool or transaction.
+///
+/// The futures are boxed, and `Send` unless the `send-less` feature is
+/// enabled. [`UnboxedExecutioner`] runs the same without boxing.
+#[cfg_attr(not(feature = "send-less"), async_trait)]
+#[cfg_attr(feature = "send-less", async_trait(?Send))]
+pub trait Executioner<'c, DB>: sqlx::Executor<'c, Database = DB> where DB: for<'v> HasVisitor<'v> + sqlx::Database {
+    async fn save<E: HasPrimaryKey + Writable + Send>(self, entity: &mut E) -> crate::Result<ExecResult>;
+    async fn insert<'query, I: Into<Insert<'query>> + Send, IE: Into<InsertingExecution<DB, I>> + Send>(self, insertion: IE) -> crate::Result<ExecResult>;
+
+    /// Runs an insert, the value of its
+    /// [`generated_key`](InsertingExecution::generated_key) as the
+    /// `last_insert_id` of the result: selected with `RETURNING` where the
+    /// database has it, as [`returning_id`](InsertingExecution::returning_id)
+    /// does. By default the result of [`insert`](Self::insert).
+    async fn insert_returning_key(self, insertion: InsertingExecution<DB, Insert<'static>>) -> crate::Result<ExecResult> {
+        self.insert(insertion).await
+    }
+
+    /// Runs a statement built by a visitor. Every execution but the calls to
+    /// `save` and `insert` of the executioner goes through here, which makes
+    /// it the seam for test doubles.
+    async fn execute_compiled(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<ExecResult>;
+
+    /// Runs a query built by a visitor, decoding its first row.
+    async fn fetch_one_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<T>
+    where
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin;
+
+    /// Runs a query built by a visitor, decoding its first row if it has
+    /// one. By default the result of
+    /// [`fetch_one_compiled`](Self::fetch_one_compiled), a missing row as
+    /// `None`.
+    async fn fetch_optional_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Option<T>>
+    where
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    {
+        match self.fetch_one_compiled(sql, parameters).await {
+            Ok(row) => Ok(Some(row)),
+            Err(err) if matches!(err.kind(), crate::error::ErrorKind::NotFound(sqlx::Error::RowNotFound)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Runs a query built by a visitor, decoding all of its rows.
+    async fn fetch_all_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Vec<T>>
+    where
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin;
+
+    /// Runs a query built by a visitor, decoding its rows as they arrive.
+    /// By default the rows of
+    /// [`fetch_all_compiled`](Self::fetch_all_compiled), all fetched before
+    /// the first one is yielded.
+    fn fetch_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> RowStream<'c, T>
+    where
+        Self: 'c,
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin + 'c,
+    {
+        Generated::boxed(move |mut rows| async move {
+            match self.fetch_all_compiled(sql, parameters).await {
+                Ok(entities) => {
+                    for entity in entities {
+                        rows.send(Ok(entity)).await;
+                    }
+                }
+                Err(err) => rows.send(Err(err)).await,
+            }
+        })
+    }
+
+    /// Runs `statements` in order in one transaction, a savepoint when
+    /// already in one. Each statement is sent on its own, never joined with
+    /// others, which keeps the DDL SQL Server wants alone in a batch working.
+    ///
+    /// A failing statement rolls back the ones before it and fails with
+    /// [`BatchFailed`](crate::error::ErrorKind::BatchFailed), naming its
+    /// index and SQL.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+    /// let report = conn
+    ///     .execute_batch(vec![
+    ///         Insert::single_into(User::table()).value(User::name, "alice").into(),
+    ///         Update::table(User::table()).set(User::name, "bob").into(),
+    ///     ])
+    ///     .await?;
+    ///
+    /// assert_eq!(&[1, 1], report.rows_affected());
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    async fn execute_batch<'q, I>(self, statements: I) -> crate::Result<BatchReport>
+    where
+        I: IntoIterator<Item = Query<'q>> + Send,
+        I::IntoIter: Send;
+}
+
+/// [`Executioner`] without a boxed future per call. The futures are `Send`
+/// whenever the executor is, as known from its concrete type.
+///
+/// ```rust
+/// # use entities::Dog;
+/// # use xiayu::prelude::*;
+/// # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+/// conn.insert_into(Insert::single_into(Dog::table()).value(Dog::age, 3)).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[allow(async_fn_in_trait)]
+pub trait UnboxedExecutioner<'c, DB>: sqlx::Executor<'c, Database = DB> where DB: for<'v> HasVisitor<'v> + sqlx::Database {
+    async fn save_entity<E: HasPrimaryKey + Writable>(self, entity: &mut E) -> crate::Result<ExecResult>;
+    async fn insert_into<'query, I: Into<Insert<'query>>, IE: Into<InsertingExecution<DB, I>>>(self, insertion: IE) -> crate::Result<ExecResult>;
+}
+
+/// The databases the executors of sqlx run statements built by a visitor on.
+///
+/// Binding parameters is all that depends on the database, the bodies of the
+/// executioners below are shared generic functions over it instead of a copy
+/// per executor type.
+pub(crate) trait CompiledQuery: sqlx::Database + for<'v> HasVisitor<'v> {
+    fn query<'q>(
+        sql: &'q str,
+        parameters: Vec<Value<'q>>,
+    ) -> crate::Result<sqlx::query::Query<'q, Self, <Self as sqlx::database::HasArguments<'q>>::Arguments>>;
+
+    fn query_as<'q, T>(
+        sql: &'q str,
+        parameters: Vec<Value<'q>>,
+    ) -> crate::Result<sqlx::query::QueryAs<'q, Self, T, <Self as sqlx::database::HasArguments<'q>>::Arguments>>
+    where
+        T: for<'r> FromRow<'r, Self::Row>;
+
+    fn exec_result(result: &Self::QueryResult) -> ExecResult;
+}
+
+macro_rules! impl_compiled_query {
+    ($database: ty) => {
+        impl CompiledQuery for $database {
+            fn query<'q>(
+                sql: &'q str,
+                parameters: Vec<Value<'q>>,
+            ) -> crate::Result<sqlx::query::Query<'q, Self, <Self as sqlx::database::HasArguments<'q>>::Arguments>> {
+                let mut query = sqlx::query::<$database>(sql);
+                for parameter in convert_parameters::<$database>(parameters, &[])? {
+                    query = query.bind_value(parameter);
+                }
+                Ok(query)
+            }
+
+            fn query_as<'q, T>(
+                sql: &'q str,
+                parameters: Vec<Value<'q>>,
+            ) -> crate::Result<sqlx::query::QueryAs<'q, Self, T, <Self as sqlx::database::HasArguments<'q>>::Arguments>>
+            where
+                T: for<'r> FromRow<'r, Self::Row>,
+            {
+                let mut query = sqlx::query_as::<$database, T>(sql);
+                for parameter in convert_parameters::<$database>(parameters, &[])? {
+                    query = query.bind_value(parameter);
+                }
+                Ok(query)
+            }
+
+            fn exec_result(result: &Self::QueryResult) -> ExecResult {
+                ExecResult::new(result.rows_affected(), <Self as HasGeneratedKey>::last_insert_id(result))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "mssql")]
+impl_compiled_query!(sqlx::Mssql);
+#[cfg(feature = "mysql")]
+impl_compiled_query!(sqlx::MySql);
+#[cfg(feature = "sqlite")]
+impl_compiled_query!(sqlx::Sqlite);
+#[cfg(feature = "postgres")]
+impl_compiled_query!(sqlx::Postgres);
+
+async fn save_impl<'c, DB, E, X>(executor: X, entity: &mut E) -> crate::Result<ExecResult>
+where
+    DB: CompiledQuery + HasGeneratedKey,
+    Scalar<i64>: for<'r> FromRow<'r, DB::Row>,
+    E: HasPrimaryKey + Writable,
+    X: sqlx::Executor<'c, Database = DB>,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    let request = entity.save::<DB>();
+    if let Some(error) = request.error {
+        return Err(error);
+    }
+    let saving = match (request.saving, request.generated_key) {
+        (Query::Insert(insert), Some((key, write))) => {
+            let mut insertion = InsertingExecution::from(*insert)
+                .generated_key(key)
+                .validate_lengths(request.validate_lengths);
+            insertion.comment = request.comment;
+            let result = insert_returning_key_impl(executor, insertion).await?;
+            write(request.entity, generated_key_of(&result)?)?;
+            return Ok(result);
+        }
+        (saving, _) => saving,
+    };
+    let inserting = matches!(saving, Query::Insert(_));
+    let (compiled, parameters) = build_validated::<DB, _>(saving, request.validate_lengths, &request.comment)?;
+    let query = DB::query(&compiled, parameters)?;
+    let result = DB::exec_result(&executor.execute(query).await?);
+    Ok(if inserting { result } else { result.without_insert_id() })
+}
+
+/// Inserts the row, the key generated for it selected with `RETURNING` or
+/// `OUTPUT` where the database has it, as the `last_insert_id` of the result.
+async fn insert_returning_key_impl<'c, DB, X>(executor: X, insertion: InsertingExecution<DB, Insert<'static>>) -> crate::Result<ExecResult>
+where
+    DB: CompiledQuery + HasGeneratedKey,
+    Scalar<i64>: for<'r> FromRow<'r, DB::Row>,
+    X: sqlx::Executor<'c, Database = DB>,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    let InsertingExecution {
+        insertion,
+        validate_lengths,
+        comment,
+        generated_key,
+        use_returning,
+        ..
+    } = insertion;
+    match generated_key {
+        Some(key) if DB::RETURNING && use_returning => {
+            let (sql, parameters) = build_validated::<DB, Insert>(insertion.returning(vec![key]), validate_lengths, &comment)?;
+            let parameters = parameters.into_iter().map(Value::into_owned).collect();
+            let Scalar(id) = fetch_one_impl::<DB, Scalar<i64>, X>(executor, sql, parameters).await?;
+            Ok(ExecResult::new(1, Some(id)))
+        }
+        _ => {
+            let (sql, parameters) = build_validated::<DB, Insert>(insertion, validate_lengths, &comment)?;
+            let query = DB::query(&sql, parameters)?;
+            Ok(DB::exec_result(&executor.execute(query).await?))
+        }
+    }
+}
+
+async fn insert_impl<'c, 'query, DB, I, IE, X>(executor: X, insertion: IE) -> crate::Result<ExecResult>
+where
+    DB: CompiledQuery,
+    I: Into<Insert<'query>>,
+    IE: Into<InsertingExecution<DB, I>>,
+    X: sqlx::Executor<'c, Database = DB>,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    let request = insertion.into();
+    let (sql, parameters) = build_validated::<DB, Insert>(request.insertion.into(), request.validate_lengths, &request.comment)?;
+    let query = DB::query(&sql, parameters)?;
+    Ok(DB::exec_result(&executor.execute(query).await?))
+}
+
+async fn execute_impl<'c, DB, X>(executor: X, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<ExecResult>
+where
+    DB: CompiledQuery,
+    X: sqlx::Executor<'c, Database = DB>,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    let query = DB::query(&sql, parameters)?;
+    Ok(DB::exec_result(&executor.execute(query).await?))
+}
+
+async fn fetch_one_impl<'c, DB, T, X>(executor: X, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<T>
+where
+    DB: CompiledQuery,
+    T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    X: sqlx::Executor<'c, Database = DB>,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    let query = DB::query_as::<T>(&sql, parameters)?;
+    Ok(query.fetch_one(executor).await?)
+}
+
+async fn fetch_optional_impl<'c, DB, T, X>(executor: X, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Option<T>>
+where
+    DB: CompiledQuery,
+    T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    X: sqlx::Executor<'c, Database = DB>,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    let query = DB::query_as::<T>(&sql, parameters)?;
+    Ok(query.fetch_optional(executor).await?)
+}
+
+async fn fetch_all_impl<'c, DB, T, X>(executor: X, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Vec<T>>
+where
+    DB: CompiledQuery,
+    T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    X: sqlx::Executor<'c, Database = DB>,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    let query = DB::query_as::<T>(&sql, parameters)?;
+    Ok(query.fetch_all(executor).await?)
+}
+
+fn fetch_impl<'c, DB, T, X>(executor: X, sql: String, parameters: Vec<Value<'static>>) -> RowStream<'c, T>
+where
+    DB: CompiledQuery,
+    T: for<'r> FromRow<'r, DB::Row> + Send + Unpin + 'c,
+    X: sqlx::Executor<'c, Database = DB> + 'c,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    // The stream owns the SQL, the query borrowing it lives in the future.
+    Generated::boxed(move |mut rows| async move {
+        let query = match DB::query_as::<T>(&sql, parameters) {
+            Ok(query) => query,
+            Err(err) => return rows.send(Err(err)).await,
+        };
+        let mut entities = query.fetch(executor);
+        while let Some(entity) = stream::next(&mut entities).await {
+            rows.send(entity.map_err(Into::into)).await;
+        }
+    })
+}
+
+/// Runs the built statements of a batch on `executor`, stopping at the first
+/// failing one.
+async fn run_batch<DB, X>(executor: &mut X, statements: Vec<(String, Vec<Value<'static>>)>) -> crate::Result<BatchReport>
+where
+    DB: CompiledQuery,
+    X: ?Sized,
+    for<'e> &'e mut X: sqlx::Executor<'e, Database = DB>,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    let mut report = BatchReport::default();
+    for (index, (sql, parameters)) in statements.into_iter().enumerate() {
+        match execute_impl(&mut *executor, sql.clone(), parameters).await {
+            Ok(result) => report.push(result.rows_affected()),
+            Err(source) => return Err(batch_failed(index, sql, source)),
+        }
+    }
+    Ok(report)
+}
+
+/// Runs a batch in a transaction begun on `executor`, a savepoint when it
+/// is a transaction already.
+async fn batch_impl<'c, 'q, DB, A, I>(executor: A, statements: I) -> crate::Result<BatchReport>
+where
+    DB: CompiledQuery,
+    A: sqlx::Acquire<'c, Database = DB>,
+    I: IntoIterator<Item = Query<'q>>,
+    for<'e> &'e mut DB::Connection: sqlx::Executor<'e, Database = DB>,
+    for<'a> <DB as sqlx::database::HasArguments<'a>>::Arguments: IntoArguments<'a, DB>,
+{
+    let statements = build_batch::<DB, _>(statements)?;
+    let mut transaction = executor.begin().await?;
+    let report = run_batch::<DB, DB::Connection>(&mut *transaction, statements).await;
+    if report.is_ok() {
+        transaction.commit().await?;
+    } else {
+        transaction.rollback().await?;
+    }
+    report
+}
+
+/// [`batch_impl`] for a listener, which does not begin transactions of its
+/// own.
+#[cfg(feature = "postgres")]
+async fn listener_batch_impl<'q, I>(listener: &mut sqlx::postgres::PgListener, statements: I) -> crate::Result<BatchReport>
+where
+    I: IntoIterator<Item = Query<'q>>,
+{
+    let statements = build_batch::<sqlx::Postgres, _>(statements)?;
+    (&mut *listener).execute("BEGIN").await?;
+    let report = run_batch::<sqlx::Postgres, sqlx::postgres::PgListener>(listener, statements).await;
+    if report.is_ok() {
+        (&mut *listener).execute("COMMIT").await?;
+    } else {
+        (&mut *listener).execute("ROLLBACK").await?;
+    }
+    report
+}
 
 macro_rules! impl_executioner_for {
     (<$($lifetime: lifetime),*>, $executor: ty, $database: ty) => {
-        #[async_trait]
+        impl_executioner_for!(<$($lifetime),*>, $executor, $database, batch_impl::<$database, _, _>);
+    };
+    (<$($lifetime: lifetime),*>, $executor: ty, $database: ty, $batch: expr) => {
+        impl<$($lifetime),*> UnboxedExecutioner<'c, $database> for $executor {
+            async fn save_entity<E: HasPrimaryKey + Writable>(self, entity: &mut E) -> crate::Result<ExecResult> {
+                save_impl(self, entity).await
+            }
+
+            async fn insert_into<'query, I, IE>(self, insertion: IE) -> crate::Result<ExecResult>
+            where IE: Into<InsertingExecution<$database, I>>,
+                  I: Into<Insert<'query>>,
+            {
+                insert_impl(self, insertion).await
+            }
+        }
+
+        #[cfg_attr(not(feature = "send-less"), async_trait)]
+        #[cfg_attr(feature = "send-less", async_trait(?Send))]
         impl<$($lifetime),*> Executioner<'c, $database> for $executor {
-            async fn save<E: HasPrimaryKey + Send>(self, entity: &mut E) -> crate::Result<()> {
-                let mut request = entity.save::<$database>();
-                let (compiled, parameters) =
-                    <$database as HasVisitor>::Visitor::build(request.saving.clone())?;
-                // 'a for borrowed from self.compiled
-                // println!("compiled saving: {}", &compiled);
-                // println!("parameters ---> {:?}", parameters);
-                request.compiled.replace(compiled);
-                let mut query = sqlx::query::<$database>(request.compiled.as_ref().unwrap());
-                for parameter in parameters {
-                    query = query.bind_value(parameter);
-                }
-                // println!("query ---> {:?}", query);
-                let _query_result = self.execute(query).await?;
-                Ok(())
+            async fn save<E: HasPrimaryKey + Writable + Send>(self, entity: &mut E) -> crate::Result<ExecResult> {
+                save_impl(self, entity).await
             }
 
-            async fn insert<'query, I, IE>(self, insertion: IE) -> crate::Result<<$database as sqlx::Database>::QueryResult>
+            async fn insert<'query, I, IE>(self, insertion: IE) -> crate::Result<ExecResult>
             where IE: Into<InsertingExecution<$database, I>> + Send,
                   I: Into<Insert<'query>> + Send,
             {
-                let mut request = insertion.into();
-                let (compiled, parameters) =
-                    <$database as HasVisitor>::Visitor::build(request.insertion.into())?;
-                request.compiled.replace(compiled);
-                let mut query = sqlx::query::<$database>(request.compiled.as_ref().unwrap());
-                for parameter in parameters {
-                    query = query.bind_value(parameter);
-                }
-                // println!("query ---> {:?}", query);
-                let query_result = self.execute(query).await?;
-                Ok(query_result)
+                insert_impl(self, insertion).await
+            }
+
+            async fn insert_returning_key(self, insertion: InsertingExecution<$database, Insert<'static>>) -> crate::Result<ExecResult> {
+                insert_returning_key_impl(self, insertion).await
+            }
+
+            async fn execute_compiled(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<ExecResult> {
+                execute_impl(self, sql, parameters).await
+            }
+
+            async fn fetch_one_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<T>
+            where
+                T: for<'r> FromRow<'r, <$database as sqlx::Database>::Row> + Send + Unpin,
+            {
+                fetch_one_impl(self, sql, parameters).await
+            }
+
+            async fn fetch_optional_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Option<T>>
+            where
+                T: for<'r> FromRow<'r, <$database as sqlx::Database>::Row> + Send + Unpin,
+            {
+                fetch_optional_impl(self, sql, parameters).await
+            }
+
+            async fn fetch_all_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Vec<T>>
+            where
+                T: for<'r> FromRow<'r, <$database as sqlx::Database>::Row> + Send + Unpin,
+            {
+                fetch_all_impl(self, sql, parameters).await
+            }
+
+            fn fetch_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> RowStream<'c, T>
+            where
+                T: for<'r> FromRow<'r, <$database as sqlx::Database>::Row> + Send + Unpin + 'c,
+            {
+                fetch_impl(self, sql, parameters)
+            }
+
+            async fn execute_batch<'q, I>(self, statements: I) -> crate::Result<BatchReport>
+            where
+                I: IntoIterator<Item = Query<'q>> + Send,
+                I::IntoIter: Send,
+            {
+                $batch(self, statements).await
             }
         }
     };
@@ -450,7 +2436,7 @@ impl_executioner_for!(<'c>, &'c mut sqlx::pool::PoolConnection<sqlx::Sqlite>, sq
 #[cfg(feature = "postgres")]
 impl_executioner_for!(<'c>, &'c mut sqlx::pool::PoolConnection<sqlx::Postgres>, sqlx::Postgres);
 #[cfg(feature = "postgres")]
-impl_executioner_for!(<'c>, &'c mut sqlx::postgres::PgListener, sqlx::Postgres);
+impl_executioner_for!(<'c>, &'c mut sqlx::postgres::PgListener, sqlx::Postgres, listener_batch_impl);
 #[cfg(feature = "mssql")]
 impl_executioner_for!(<'c>, &'c mut sqlx::MssqlConnection, sqlx::Mssql);
 #[cfg(feature = "mysql")]
@@ -468,23 +2454,280 @@ impl_executioner_for!(<'c, 't>, &'c mut sqlx::Transaction<'t, sqlx::Sqlite>, sql
 #[cfg(feature = "postgres")]
 impl_executioner_for!(<'c, 't>, &'c mut sqlx::Transaction<'t, sqlx::Postgres>, sqlx::Postgres);
 
-#[async_trait]
+#[cfg_attr(not(feature = "send-less"), async_trait)]
+#[cfg_attr(feature = "send-less", async_trait(?Send))]
 impl<'p, DB> Executioner<'p, DB> for &'_ sqlx::Pool<DB> where
     DB: sqlx::Database + for <'v> HasVisitor<'v>,
     for<'c> &'c mut <DB as sqlx::Database>::Connection: Executioner<'c, DB>,
 {
-    async fn save<E: HasPrimaryKey + Send>(self, entity: &mut E) -> crate::Result<()> {
-        let pool = self.clone();
-        let mut conn = pool.acquire().await?;
+    async fn save<E: HasPrimaryKey + Writable + Send>(self, entity: &mut E) -> crate::Result<ExecResult> {
+        let mut conn = self.acquire().await?;
         conn.save(entity).await
     }
 
-    async fn insert<'query, I, IE>(self, insertion: IE) -> crate::Result<<DB as sqlx::Database>::QueryResult>
+    async fn insert<'query, I, IE>(self, insertion: IE) -> crate::Result<ExecResult>
     where IE: Into<InsertingExecution<DB, I>> + Send,
           I: Into<Insert<'query>> + Send,
     {
-        let pool = self.clone();
-        let mut conn = pool.acquire().await?;
+        let mut conn = self.acquire().await?;
         conn.insert(insertion).await
-    }       
+    }
+
+    async fn insert_returning_key(self, insertion: InsertingExecution<DB, Insert<'static>>) -> crate::Result<ExecResult> {
+        let mut conn = self.acquire().await?;
+        conn.insert_returning_key(insertion).await
+    }
+
+    async fn execute_compiled(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<ExecResult> {
+        let mut conn = self.acquire().await?;
+        conn.execute_compiled(sql, parameters).await
+    }
+
+    async fn fetch_one_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<T>
+    where
+        T: for<'r> FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        let mut conn = self.acquire().await?;
+        conn.fetch_one_compiled(sql, parameters).await
+    }
+
+    async fn fetch_optional_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Option<T>>
+    where
+        T: for<'r> FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        let mut conn = self.acquire().await?;
+        conn.fetch_optional_compiled(sql, parameters).await
+    }
+
+    async fn fetch_all_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Vec<T>>
+    where
+        T: for<'r> FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        let mut conn = self.acquire().await?;
+        conn.fetch_all_compiled(sql, parameters).await
+    }
+
+    /// Streams on a connection acquired once the stream is first polled.
+    fn fetch_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> RowStream<'p, T>
+    where
+        T: for<'r> FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin + 'p,
+    {
+        let pool = self.clone();
+        Generated::boxed(move |mut rows| async move {
+            let mut conn = match pool.acquire().await {
+                Ok(conn) => conn,
+                Err(err) => return rows.send(Err(err.into())).await,
+            };
+            let mut entities = (&mut *conn).fetch_compiled(sql, parameters);
+            while let Some(entity) = stream::next(&mut entities).await {
+                rows.send(entity).await;
+            }
+        })
+    }
+
+    async fn execute_batch<'q, I>(self, statements: I) -> crate::Result<BatchReport>
+    where
+        I: IntoIterator<Item = Query<'q>> + Send,
+        I::IntoIter: Send,
+    {
+        let mut conn = self.acquire().await?;
+        conn.execute_batch(statements).await
+    }
+}
+
+impl<'p, DB> UnboxedExecutioner<'p, DB> for &'_ sqlx::Pool<DB> where
+    DB: sqlx::Database + for <'v> HasVisitor<'v>,
+    for<'c> &'c mut <DB as sqlx::Database>::Connection: UnboxedExecutioner<'c, DB>,
+{
+    async fn save_entity<E: HasPrimaryKey + Writable>(self, entity: &mut E) -> crate::Result<ExecResult> {
+        let mut conn = self.acquire().await?;
+        conn.save_entity(entity).await
+    }
+
+    async fn insert_into<'query, I, IE>(self, insertion: IE) -> crate::Result<ExecResult>
+    where IE: Into<InsertingExecution<DB, I>>,
+          I: Into<Insert<'query>>,
+    {
+        let mut conn = self.acquire().await?;
+        conn.insert_into(insertion).await
+    }
+}
+
+/// A connection acquired from a pool, returned by
+/// [`acquire_executioner`][AcquireExecutioner::acquire_executioner].
+///
+/// Every operation run through `&mut Acquired` uses the same connection, which
+/// goes back to the pool once the guard is dropped.
+pub struct Acquired<'p, DB: Database> {
+    conn: sqlx::pool::PoolConnection<DB>,
+    _pool: PhantomData<&'p sqlx::Pool<DB>>,
+}
+
+impl<'p, DB: Database> Acquired<'p, DB> {
+    /// Detaches the underlying connection from the guard.
+    pub fn into_inner(self) -> sqlx::pool::PoolConnection<DB> {
+        self.conn
+    }
+}
+
+impl<'p, DB: Database> std::ops::Deref for Acquired<'p, DB> {
+    type Target = <DB as Database>::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl<'p, DB: Database> std::ops::DerefMut for Acquired<'p, DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
+impl<'p, DB: Database> std::fmt::Debug for Acquired<'p, DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Acquired").field("conn", &self.conn).finish()
+    }
+}
+
+impl<'c, 'p, DB> sqlx::Executor<'c> for &'c mut Acquired<'p, DB>
+where
+    DB: Database,
+    for<'e> &'e mut <DB as Database>::Connection: sqlx::Executor<'e, Database = DB>,
+{
+    type Database = DB;
+
+    fn fetch_many<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxStream<'e, Result<sqlx::Either<DB::QueryResult, DB::Row>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: sqlx::Execute<'q, DB> + 'q,
+    {
+        (&mut *self.conn).fetch_many(query)
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E>(
+        self,
+        query: E,
+    ) -> BoxFuture<'e, Result<Option<DB::Row>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: sqlx::Execute<'q, DB> + 'q,
+    {
+        (&mut *self.conn).fetch_optional(query)
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+        parameters: &'e [DB::TypeInfo],
+    ) -> BoxFuture<'e, Result<<DB as sqlx::database::HasStatement<'q>>::Statement, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        (&mut *self.conn).prepare_with(sql, parameters)
+    }
+
+    fn describe<'e, 'q: 'e>(
+        self,
+        sql: &'q str,
+    ) -> BoxFuture<'e, Result<sqlx::Describe<DB>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        (&mut *self.conn).describe(sql)
+    }
+}
+
+#[cfg_attr(not(feature = "send-less"), async_trait)]
+#[cfg_attr(feature = "send-less", async_trait(?Send))]
+impl<'c, 'p, DB> Executioner<'c, DB> for &'c mut Acquired<'p, DB> where
+    DB: sqlx::Database + for <'v> HasVisitor<'v>,
+    for<'e> &'e mut <DB as sqlx::Database>::Connection: Executioner<'e, DB>,
+{
+    async fn save<E: HasPrimaryKey + Writable + Send>(self, entity: &mut E) -> crate::Result<ExecResult> {
+        (&mut *self.conn).save(entity).await
+    }
+
+    async fn insert<'query, I, IE>(self, insertion: IE) -> crate::Result<ExecResult>
+    where IE: Into<InsertingExecution<DB, I>> + Send,
+          I: Into<Insert<'query>> + Send,
+    {
+        (&mut *self.conn).insert(insertion).await
+    }
+
+    async fn execute_compiled(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<ExecResult> {
+        (&mut *self.conn).execute_compiled(sql, parameters).await
+    }
+
+    async fn fetch_one_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<T>
+    where
+        T: for<'r> FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        (&mut *self.conn).fetch_one_compiled(sql, parameters).await
+    }
+
+    async fn fetch_optional_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Option<T>>
+    where
+        T: for<'r> FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        (&mut *self.conn).fetch_optional_compiled(sql, parameters).await
+    }
+
+    async fn fetch_all_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Vec<T>>
+    where
+        T: for<'r> FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin,
+    {
+        (&mut *self.conn).fetch_all_compiled(sql, parameters).await
+    }
+
+    fn fetch_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> RowStream<'c, T>
+    where
+        T: for<'r> FromRow<'r, <DB as sqlx::Database>::Row> + Send + Unpin + 'c,
+    {
+        (&mut *self.conn).fetch_compiled(sql, parameters)
+    }
+
+    async fn execute_batch<'q, I>(self, statements: I) -> crate::Result<BatchReport>
+    where
+        I: IntoIterator<Item = Query<'q>> + Send,
+        I::IntoIter: Send,
+    {
+        (&mut *self.conn).execute_batch(statements).await
+    }
+}
+
+impl<'c, 'p, DB> UnboxedExecutioner<'c, DB> for &'c mut Acquired<'p, DB> where
+    DB: sqlx::Database + for <'v> HasVisitor<'v>,
+    for<'e> &'e mut <DB as sqlx::Database>::Connection: UnboxedExecutioner<'e, DB>,
+{
+    async fn save_entity<E: HasPrimaryKey + Writable>(self, entity: &mut E) -> crate::Result<ExecResult> {
+        (&mut *self.conn).save_entity(entity).await
+    }
+
+    async fn insert_into<'query, I, IE>(self, insertion: IE) -> crate::Result<ExecResult>
+    where IE: Into<InsertingExecution<DB, I>>,
+          I: Into<Insert<'query>>,
+    {
+        (&mut *self.conn).insert_into(insertion).await
+    }
+}
+
+/// Acquires a connection from a pool once, to run many operations on it.
+#[async_trait]
+pub trait AcquireExecutioner<DB: Database> {
+    async fn acquire_executioner(&self) -> crate::Result<Acquired<'_, DB>>;
+}
+
+#[async_trait]
+impl<DB: Database> AcquireExecutioner<DB> for sqlx::Pool<DB> {
+    async fn acquire_executioner(&self) -> crate::Result<Acquired<'_, DB>> {
+        let conn = self.acquire().await?;
+        Ok(Acquired {
+            conn,
+            _pool: PhantomData,
+        })
+    }
 }
@@ -0,0 +1,113 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures_core::stream::Stream;
+
+/// The rows of a query, decoded as the database sends them. `Send` unless
+/// the `send-less` feature is enabled.
+#[cfg(not(feature = "send-less"))]
+pub type RowStream<'a, T> = futures_core::stream::BoxStream<'a, crate::Result<T>>;
+/// The rows of a query, decoded as the database sends them. `Send` unless
+/// the `send-less` feature is enabled.
+#[cfg(feature = "send-less")]
+pub type RowStream<'a, T> = futures_core::stream::LocalBoxStream<'a, crate::Result<T>>;
+
+#[cfg(not(feature = "send-less"))]
+type Producer<'a> = futures_core::future::BoxFuture<'a, ()>;
+#[cfg(feature = "send-less")]
+type Producer<'a> = futures_core::future::LocalBoxFuture<'a, ()>;
+
+/// Hands the rows over from the future producing them to the stream.
+pub(crate) struct Yielder<T> {
+    slot: Arc<Mutex<Option<crate::Result<T>>>>,
+}
+
+impl<T> Yielder<T> {
+    /// Makes `row` the next item of the stream, waiting until it is taken.
+    pub(crate) async fn send(&mut self, row: crate::Result<T>) {
+        *self.slot.lock().unwrap() = Some(row);
+        Yield(false).await
+    }
+}
+
+/// Pending once, giving the hand back to the stream holding a row.
+struct Yield(bool);
+
+impl Future for Yield {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        // Not woken: the stream returns the row, and is polled for the next
+        // one.
+        self.0 = true;
+        Poll::Pending
+    }
+}
+
+/// A stream of the rows sent by a future, which owns what the query borrows:
+/// its SQL, its parameters and the connection.
+pub(crate) struct Generated<'a, T> {
+    slot: Arc<Mutex<Option<crate::Result<T>>>>,
+    producer: Option<Producer<'a>>,
+}
+
+impl<'a, T> Generated<'a, T> {
+    #[cfg(not(feature = "send-less"))]
+    pub(crate) fn boxed<F, Fut>(produce: F) -> RowStream<'a, T>
+    where
+        F: FnOnce(Yielder<T>) -> Fut,
+        Fut: Future<Output = ()> + Send + 'a,
+        T: Send + 'a,
+    {
+        let slot = Arc::new(Mutex::new(None));
+        let producer = Box::pin(produce(Yielder { slot: slot.clone() }));
+        Box::pin(Self {
+            slot,
+            producer: Some(producer),
+        })
+    }
+
+    #[cfg(feature = "send-less")]
+    pub(crate) fn boxed<F, Fut>(produce: F) -> RowStream<'a, T>
+    where
+        F: FnOnce(Yielder<T>) -> Fut,
+        Fut: Future<Output = ()> + 'a,
+        T: 'a,
+    {
+        let slot = Arc::new(Mutex::new(None));
+        let producer = Box::pin(produce(Yielder { slot: slot.clone() }));
+        Box::pin(Self {
+            slot,
+            producer: Some(producer),
+        })
+    }
+}
+
+impl<'a, T> Stream for Generated<'a, T> {
+    type Item = crate::Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let done = match self.producer.as_mut() {
+            Some(producer) => producer.as_mut().poll(cx).is_ready(),
+            None => return Poll::Ready(None),
+        };
+        if done {
+            self.producer = None;
+        }
+        match self.slot.lock().unwrap().take() {
+            Some(row) => Poll::Ready(Some(row)),
+            None if done => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// Polls the next item of `stream`.
+pub(crate) fn next<S: Stream + Unpin>(stream: &mut S) -> impl Future<Output = Option<S::Item>> + '_ {
+    std::future::poll_fn(move |cx| Pin::new(&mut *stream).poll_next(cx))
+}
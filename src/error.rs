@@ -129,6 +129,16 @@ impl Error {
     pub fn is_closed(&self) -> bool {
         matches!(self.kind, ErrorKind::ConnectionClosed)
     }
+
+    /// Determines if running the transaction again may succeed, see
+    /// [`retry`](crate::retry).
+    pub fn is_retryable(&self) -> bool {
+        match &self.kind {
+            ErrorKind::SerializationFailure(_) | ErrorKind::Deadlock(_) => true,
+            ErrorKind::BatchFailed { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -205,6 +215,17 @@ pub enum ErrorKind {
     #[error("{0}")]
     SQLxWorkerCrashed(#[source] sqlx::error::Error),
 
+    /// The transaction could not be serialized with concurrent ones, e.g. the
+    /// `40001` of PostgreSQL. Running it again may succeed.
+    #[error("{0}")]
+    SerializationFailure(#[source] sqlx::error::Error),
+
+    /// The transaction was chosen as the victim of a deadlock, e.g. the
+    /// `40P01` of PostgreSQL or the `1213` of MySQL. Running it again may
+    /// succeed.
+    #[error("{0}")]
+    Deadlock(#[source] sqlx::error::Error),
+
     /// Other SQLx error not handled yet.
     #[error("{0}")]
     OtherSQLxError(#[source] sqlx::error::Error),
@@ -288,16 +309,110 @@ pub enum ErrorKind {
     ValueOutOfRange { message: String },
 
     #[cfg(feature = "serde-support")]
-    #[cfg_attr(feature = "docs", doc(cfg(feature = "serde-support")))]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde-support")))]
     #[error("Deserializing a ResultRow {:?}", _0)]
     FromRowError(serde::de::value::Error),
 
+    #[error("Invalid row locking: {}", _0)]
+    InvalidLocking(&'static str),
+
+    #[error("Invalid delete: {}", _0)]
+    InvalidDelete(&'static str),
+
+    #[error("The primary key of `{}` is not set.", table)]
+    PrimaryKeyNotSet { table: Name },
+
+    #[error("Altering `{}` requires rebuilding the table: {}", table, reason)]
+    TableRebuildRequired { table: Name, reason: &'static str },
+
+    #[error("The foreign keys of tables {} form a cycle.", tables.join(", "))]
+    ForeignKeyCycle { tables: Vec<String> },
+
+    #[error("Invalid identifier `{}`: {}", identifier, reason)]
+    InvalidIdentifier {
+        identifier: String,
+        reason: &'static str,
+    },
+
     #[error(
         "Incorrect number of parameters given to a statement. Expected {}: got: {}.",
         expected,
         actual
     )]
     IncorrectNumberOfParameters { expected: usize, actual: usize },
+
+    /// A statement binds more parameters than the database accepts, it was
+    /// not sent.
+    #[error("The statement binds {} parameters, {} accepts at most {}.", got, database, max)]
+    TooManyParameters {
+        got: usize,
+        max: usize,
+        database: &'static str,
+    },
+
+    /// The query nests expressions deeper than the limit set with
+    /// [`set_max_depth`](crate::visitors::set_max_depth), it was not built.
+    #[error("The query nests expressions more than {} levels deep.", depth)]
+    QueryTooComplex { depth: usize },
+
+    /// The database counts the distinct values of a single column only.
+    #[error("Counting the distinct values of {} columns is not supported by the database.", columns)]
+    CountDistinctColumns { columns: usize },
+
+    /// The columns of an insert from a select are not as many as the values
+    /// selected.
+    #[error("Inserting {} values into {} columns.", values, columns)]
+    ColumnCountMismatch { columns: usize, values: usize },
+
+    /// A column has no value to insert, and no default either.
+    #[error("No value to insert into `{}` of `{}`, and no default.", column, table)]
+    MissingColumnValue { table: Name, column: Name },
+
+    /// An [`asterisk_except`](crate::ast::asterisk_except) could not be
+    /// expanded into the columns of its table.
+    #[error("Cannot select the columns of `{}` but some: {}.", table, reason)]
+    UnexpandableAsterisk { table: Name, reason: String },
+
+    /// The database shows no plans in the format asked for.
+    #[error("{} cannot explain queries as {:?}.", database, format)]
+    UnsupportedExplainFormat {
+        format: crate::databases::ExplainFormat,
+        database: &'static str,
+    },
+
+    /// A statement of a batch failed, the statements before it were rolled
+    /// back. `sql` is empty when the statement could not be built.
+    #[error("Statement {} of the batch failed: {}. Statement: {}", index, source, sql)]
+    BatchFailed {
+        index: usize,
+        sql: String,
+        source: Box<Error>,
+    },
+
+    /// The layout of the entity is not the one recorded by the last
+    /// migration, see [`verify_schema`](crate::schema_diff::verify_schema).
+    #[error(
+        "The schema of {} changed since the last migration: recorded hash {:016x}, compiled hash {:016x}.",
+        entity,
+        recorded,
+        compiled
+    )]
+    SchemaMismatch {
+        entity: &'static str,
+        recorded: u64,
+        compiled: u64,
+    },
+
+    /// The table of an entity is not the one its schema describes, `alters`
+    /// are the statements bringing it up to date, see
+    /// [`Registry::validate_all`](crate::registry::Registry::validate_all).
+    #[error("The table `{}` does not match its entity, it needs: {}", table, alters.join("; "))]
+    TableMismatch { table: Name, alters: Vec<String> },
+
+    /// The query misses a part it can not be written without, such as the
+    /// table of an `INSERT`, it was not built.
+    #[error("The query is incomplete, it misses {}.", missing)]
+    IncompleteQuery { missing: &'static str },
 }
 
 impl ErrorKind {
@@ -312,7 +427,6 @@ impl ErrorKind {
         Self::ConversionError(msg.into())
     }
 
-    #[allow(dead_code)]
     pub(crate) fn database_url_is_invalid(msg: impl Into<String>) -> Self {
         Self::DatabaseUrlIsInvalid(msg.into())
     }
@@ -323,6 +437,12 @@ impl ErrorKind {
     }
 }
 
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Self::builder(kind).build()
+    }
+}
+
 impl From<Error> for ErrorKind {
     fn from(e: Error) -> Self {
         e.kind
@@ -331,7 +451,7 @@ impl From<Error> for ErrorKind {
 
 /*
 #[cfg(feature = "bigdecimal")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "bigdecimal")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
 impl From<bigdecimal::ParseBigDecimalError> for Error {
     fn from(e: bigdecimal::ParseBigDecimalError) -> Self {
         let kind = ErrorKind::conversion(format!("{}", e));
@@ -341,7 +461,7 @@ impl From<bigdecimal::ParseBigDecimalError> for Error {
 */
 
 #[cfg(feature = "json")]
-#[cfg_attr(feature = "docs", doc(cfg(feature = "json")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 impl From<serde_json::Error> for Error {
     fn from(_: serde_json::Error) -> Self {
         Self::builder(ErrorKind::conversion("Malformed JSON data.")).build()
@@ -357,11 +477,41 @@ impl From<std::fmt::Error> for Error {
     }
 }
 
+/// Failures of concurrent transactions, which can be retried.
+fn transient_failure(err: &(dyn sqlx::error::DatabaseError + 'static)) -> Option<fn(sqlx::error::Error) -> ErrorKind> {
+    #[cfg(feature = "mysql")]
+    {
+        // SQLSTATE `40001` as well, but a deadlock.
+        if let Some(1213) = err.try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>().map(|err| err.number()) {
+            return Some(ErrorKind::Deadlock);
+        }
+    }
+    match err.code().as_deref() {
+        Some("40001") => Some(ErrorKind::SerializationFailure),
+        Some("40P01") => Some(ErrorKind::Deadlock),
+        _ => None,
+    }
+}
+
 impl From<sqlx::error::Error> for Error {
     fn from(err: sqlx::error::Error) -> Self {
+        if let sqlx::error::Error::Database(ref database) = err {
+            let code = database.code().map(Cow::into_owned);
+            let message = database.message().to_owned();
+            let kind = match transient_failure(&**database) {
+                Some(kind) => kind(err),
+                None => ErrorKind::SQLxDatabase(err),
+            };
+            let mut builder = Self::builder(kind);
+            if let Some(code) = code {
+                builder.set_original_code(code);
+            }
+            builder.set_original_message(message);
+            return builder.build();
+        }
+
         let kind = match err {
             sqlx::error::Error::Configuration(_) => ErrorKind::SQLxConfiguration(err),
-            sqlx::error::Error::Database(_) => ErrorKind::SQLxDatabase(err),
             sqlx::error::Error::Io(_) => ErrorKind::SQLxIo(err),
             sqlx::error::Error::Tls(_) => ErrorKind::SQLxTls(err),
             sqlx::error::Error::Protocol(_) => ErrorKind::SQLxProtocol(err),
@@ -371,7 +521,16 @@ impl From<sqlx::error::Error> for Error {
                 ErrorKind::SQLxColumnIndexOutOfBounds(err)
             }
             sqlx::error::Error::ColumnNotFound(_) => ErrorKind::SQLxColumnNotFound(err),
+            sqlx::error::Error::ColumnDecode { source, .. }
+                if source.is::<crate::prelude::TransformError>() || source.is::<crate::prelude::FieldDecodeError>() =>
+            {
+                ErrorKind::ColumnReadFailure(source)
+            }
             sqlx::error::Error::ColumnDecode { .. } => ErrorKind::SQLxColumnDecode(err),
+            // Failed by a hook of the entity, e.g. `#[entity(after_load = "..")]`.
+            sqlx::error::Error::Decode(source) if source.is::<Error>() => {
+                return *source.downcast::<Error>().unwrap();
+            }
             sqlx::error::Error::Decode(_) => ErrorKind::SQLxDecode(err),
             sqlx::error::Error::PoolTimedOut => ErrorKind::SQLxPoolTimedOut(err),
             sqlx::error::Error::PoolClosed => ErrorKind::SQLxPoolClosed(err),
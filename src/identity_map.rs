@@ -0,0 +1,150 @@
+//! Entities fetched once per transaction.
+//!
+//! An [`IdentityMap`] keeps the entities looked up by their primary key, by
+//! table and key. Attached to a lookup with
+//! [`cached`](crate::prelude::SelectingExecution::cached), it answers the
+//! lookups of a key it has seen without querying the database; attached to
+//! a save or a delete, it forgets the saved or deleted entity. Nothing is
+//! kept without it.
+//!
+//! The map does not know about transactions, create one with each
+//! transaction and drop it with the transaction, or entities changed by
+//! others are returned as they were.
+//!
+//! ```rust
+//! # use xiayu::prelude::*;
+//! # use xiayu::identity_map::IdentityMap;
+//! #[derive(Debug, Clone, Entity)]
+//! #[tablename = "users"]
+//! struct User {
+//!     #[column(primary_key)]
+//!     id: i64,
+//!     name: String,
+//! }
+//!
+//! # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+//! let map = IdentityMap::new();
+//!
+//! let mut user = User::get(1).cached(&map).conn(&mut *conn).await?;
+//! // Not queried again.
+//! let same = User::get(1).cached(&map).conn(&mut *conn).await?;
+//!
+//! user.name = "bob".to_string();
+//! user.save().cached(&map).conn(&mut *conn).await?;
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::ast::{Column, Compare, ConditionTree, ExpressionKind, Value};
+
+type Entries = HashMap<&'static str, Vec<(Value<'static>, Box<dyn Any + Send>)>>;
+
+/// Entities by table and primary key. Clones share the entities.
+#[derive(Clone, Default)]
+pub struct IdentityMap {
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl std::fmt::Debug for IdentityMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdentityMap").field("len", &self.len()).finish()
+    }
+}
+
+impl IdentityMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of entities kept.
+    pub fn len(&self) -> usize {
+        self.entries().values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets all the entities.
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    /// A copy of the entity of `table` with `key`.
+    pub(crate) fn get<T: Clone + 'static>(&self, table: &str, key: &Value<'static>) -> Option<T> {
+        let entries = self.entries();
+        entries
+            .get(table)?
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, entity)| entity.downcast_ref::<T>())
+            .cloned()
+    }
+
+    pub(crate) fn insert<T: Send + 'static>(&self, table: &'static str, key: Value<'static>, entity: T) {
+        let mut entries = self.entries();
+        let keys = entries.entry(table).or_default();
+        keys.retain(|(k, _)| *k != key);
+        keys.push((key, Box::new(entity)));
+    }
+
+    pub(crate) fn evict(&self, table: &str, key: &Value<'static>) {
+        if let Some(keys) = self.entries().get_mut(table) {
+            keys.retain(|(k, _)| k != key);
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Entries> {
+        // The entries are consistent after each call, a panic elsewhere
+        // leaves nothing half done.
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The key of a statement on a single entity, the value of a
+/// `primary_key = value` condition. `None` for anything else, which the map
+/// leaves alone.
+pub(crate) fn key_of(conditions: Option<&ConditionTree<'_>>, primary_key: &Column<'_>) -> Option<Value<'static>> {
+    let expression = match conditions? {
+        ConditionTree::Single(expression) => expression,
+        _ => return None,
+    };
+
+    match &expression.kind {
+        ExpressionKind::Compare(Compare::Equals(left, right)) => match (&left.kind, &right.kind) {
+            (ExpressionKind::Column(column), ExpressionKind::Parameterized(value)) if **column == *primary_key => {
+                Some(value.clone().into_owned())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A map and the key of the entity it is to forget, attached to a save or a
+/// delete.
+pub(crate) struct Eviction {
+    map: IdentityMap,
+    table: &'static str,
+    key: Option<Value<'static>>,
+}
+
+impl Eviction {
+    pub(crate) fn new(map: &IdentityMap, table: &'static str, key: Option<Value<'static>>) -> Self {
+        Self {
+            map: map.clone(),
+            table,
+            key,
+        }
+    }
+
+    pub(crate) fn evict(self) {
+        if let Some(key) = self.key {
+            self.map.evict(self.table, &key);
+        }
+    }
+}
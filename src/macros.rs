@@ -1,17 +1,18 @@
 /// Convert given set of tuples into `Values`.
 ///
 /// ```rust
-/// # use xiayu::{col, values, ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
+/// # use entities::Cat;
+/// # use xiayu::{values, prelude::*, visitors::{Visitor, Sqlite}};
+/// # fn main() -> xiayu::Result<()> {
 ///
-/// let condition = Row::from((col!("id"), col!("name")))
+/// let condition = Row::from((Cat::master_id, Cat::ingredients))
 ///     .in_selection(values!((1, "Musti"), (2, "Naukio")));
 ///
 /// let query = Select::from_table(Cat::table()).so_that(condition);
 /// let (sql, _) = Sqlite::build(query)?;
 ///
 /// assert_eq!(
-///     "SELECT `cats`.* FROM `cats` WHERE (`id`,`name`) IN (VALUES (?,?),(?,?))",
+///     "SELECT `cats`.* FROM `cats` WHERE (`cats`.`master_id`,`cats`.`ingredients`) IN (VALUES (?,?),(?,?))",
 ///     sql
 /// );
 /// # Ok(())
@@ -29,13 +30,13 @@ macro_rules! values {
 ///
 /// ``` rust
 /// # use entities::{Cat, Dog};
-/// # use xiayu::{col, val, ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
+/// # use xiayu::{col, val, prelude::*, visitors::{Visitor, Sqlite}};
+/// # fn main() -> xiayu::Result<()> {
 /// let join = Dog::table().on(Dog::slave_id.equals(Cat::master_id));
 ///
 /// let query = Select::from_table(Cat::table())
 ///     .value(Cat::table().asterisk())
-///     .value(Dog::age - val!(4))
+///     .value(col!("age", Dog::table()) - val!(4))
 ///     .inner_join(join);
 ///
 /// let (sql, params) = Sqlite::build(query)?;
@@ -63,13 +64,13 @@ macro_rules! col {
 ///
 /// ``` rust
 /// # use entities::{Cat, Dog};
-/// # use xiayu::{col, val, ast::*, visitors::{Visitor, Sqlite}};
-/// # fn main() -> Result<(), xiayu::error::Error> {
+/// # use xiayu::{val, prelude::*, visitors::{Visitor, Sqlite}};
+/// # fn main() -> xiayu::Result<()> {
 /// let join = Dog::table().on(Dog::slave_id.equals(Cat::master_id));
 ///
 /// let query = Select::from_table(Cat::table())
 ///     .value(Cat::table().asterisk())
-///     .value(Dog::age - val!(4))
+///     .value(Expression::from(Dog::age) - val!(4))
 ///     .inner_join(join);
 ///
 /// let (sql, params) = Sqlite::build(query)?;
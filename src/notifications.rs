@@ -0,0 +1,275 @@
+//! Entity change events over PostgreSQL `NOTIFY` / `LISTEN`.
+//!
+//! Every entity has a channel, [`notify_channel`][crate::prelude::Entity::notify_channel],
+//! `<tablename>_changes` by default. [`notify`] and [`notify_row`] publish a
+//! [`ChangeEvent`] to it, [`listen`] subscribes a [`PgListener`] to it and
+//! yields the decoded events.
+//!
+//! The payload is a JSON object, `{"pk":"<primary key>"}`, with an optional
+//! `"row"` member holding the whole row as produced by `row_to_json`.
+use std::fmt::Display;
+use std::str::FromStr;
+
+use futures_util::stream::{Stream, StreamExt};
+use sqlx::postgres::{PgListener, PgNotification};
+
+use crate::ast::{json_build_object, pg_notify, row_to_json, Column, Comparable, Expression, Select, TypeFamily, Value};
+use crate::databases::CompiledQuery;
+use crate::error::{Error, ErrorKind};
+use crate::prelude::HasPrimaryKey;
+use crate::visitors::{Postgres, Visitor};
+
+/// A change of the entity with the primary key `pk`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent<K> {
+    /// Primary key of the changed entity.
+    pub pk: K,
+    /// The whole row as JSON, if it was sent along with the notification.
+    pub row: Option<serde_json::Value>,
+}
+
+impl<K> ChangeEvent<K> {
+    pub fn new(pk: K) -> Self {
+        Self { pk, row: None }
+    }
+
+    pub fn with_row(mut self, row: serde_json::Value) -> Self {
+        self.row = Some(row);
+        self
+    }
+
+    /// Encode into a `NOTIFY` payload.
+    pub fn encode(&self) -> String
+    where
+        K: Display,
+    {
+        let mut payload = serde_json::Map::new();
+        payload.insert("pk".into(), serde_json::Value::String(self.pk.to_string()));
+        if let Some(row) = &self.row {
+            payload.insert("row".into(), row.clone());
+        }
+
+        serde_json::Value::Object(payload).to_string()
+    }
+
+    /// Decode from a `NOTIFY` payload.
+    pub fn decode(payload: &str) -> crate::Result<Self>
+    where
+        K: FromStr,
+    {
+        let malformed = |reason: &str| {
+            let kind = ErrorKind::conversion(format!("Malformed change event payload: {}.", reason));
+            Error::builder(kind).build()
+        };
+
+        let payload = serde_json::from_str(payload).map_err(|_| malformed("invalid JSON"))?;
+        let mut payload = match payload {
+            serde_json::Value::Object(payload) => payload,
+            _ => return Err(malformed("not an object")),
+        };
+
+        let pk = match payload.remove("pk") {
+            Some(serde_json::Value::String(pk)) => pk,
+            Some(_) => return Err(malformed("`pk` must be a string")),
+            None => return Err(malformed("missing `pk`")),
+        };
+        let pk = pk
+            .parse()
+            .map_err(|_| malformed("`pk` can not be parsed into the primary key type"))?;
+
+        let row = match payload.remove("row") {
+            Some(serde_json::Value::Null) | None => None,
+            Some(row) => Some(row),
+        };
+
+        Ok(Self { pk, row })
+    }
+}
+
+/// Publish a [`ChangeEvent`] carrying the primary key of `entity`.
+pub async fn notify<'c, E, C>(conn: C, entity: &E) -> crate::Result<()>
+where
+    E: HasPrimaryKey,
+    E::PrimaryKeyValueType: Display,
+    C: sqlx::Executor<'c, Database = sqlx::Postgres>,
+{
+    let payload = ChangeEvent::new(entity.pk()).encode();
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(E::notify_channel().into_owned())
+        .bind(payload)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Publish a [`ChangeEvent`] carrying the primary key and the whole row of
+/// `entity`, the row is read and serialized by the database.
+pub async fn notify_row<'c, E, C>(conn: C, entity: &E) -> crate::Result<()>
+where
+    E: HasPrimaryKey,
+    E::PrimaryKey: Into<Column<'static>>,
+    E::PrimaryKeyValueType: Display + Into<Value<'static>>,
+    C: sqlx::Executor<'c, Database = sqlx::Postgres>,
+{
+    let (sql, parameters) = Postgres::build(notify_row_query(entity))?;
+    sqlx::Postgres::query(&sql, parameters)?.execute(conn).await?;
+    Ok(())
+}
+
+/// The select publishing the change event of `entity`, the key is compared
+/// as it is stored and sent in the payload as text.
+fn notify_row_query<E>(entity: &E) -> Select<'static>
+where
+    E: HasPrimaryKey,
+    E::PrimaryKey: Into<Column<'static>>,
+    E::PrimaryKeyValueType: Display + Into<Value<'static>>,
+{
+    let pk = entity.pk();
+    let payload = json_build_object(vec![
+        ("pk", Expression::from(pk.to_string())),
+        ("row", row_to_json(E::table(), false).into()),
+    ]);
+    let primary_key: Column<'static> = E::primary_key().into();
+
+    Select::from_table(E::table())
+        .value(pg_notify(E::notify_channel().into_owned(), Expression::from(payload).cast(TypeFamily::Text(None))))
+        .so_that(primary_key.equals(pk))
+}
+
+/// Subscribe `listener` to the channel of `E`, yielding every change event
+/// published on it.
+///
+/// Notifications of other channels the listener is subscribed to are skipped,
+/// payloads that can not be decoded are yielded as errors.
+pub async fn listen<E>(
+    mut listener: PgListener,
+) -> crate::Result<impl Stream<Item = crate::Result<ChangeEvent<E::PrimaryKeyValueType>>>>
+where
+    E: HasPrimaryKey,
+    E::PrimaryKeyValueType: FromStr,
+{
+    let channel = E::notify_channel();
+    listener.listen(&channel).await?;
+
+    let events = listener.into_stream().filter_map(move |notification| {
+        let event = match notification {
+            Ok(notification) if notification.channel() == channel => Some(decode(&notification)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err.into())),
+        };
+        futures_util::future::ready(event)
+    });
+
+    Ok(events)
+}
+
+fn decode<K: FromStr>(notification: &PgNotification) -> crate::Result<ChangeEvent<K>> {
+    ChangeEvent::decode(notification.payload())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Entity)]
+    #[tablename = "users"]
+    struct User {
+        #[column(primary_key)]
+        id: i32,
+        name: String,
+    }
+
+    #[derive(Entity)]
+    #[tablename = "posts"]
+    #[notify_channel = "post_events"]
+    struct Post {
+        #[column(primary_key)]
+        id: i32,
+    }
+
+    #[test]
+    fn test_notify_channel() {
+        assert_eq!("users_changes", User::notify_channel());
+        assert_eq!("post_events", Post::notify_channel());
+    }
+
+    #[test]
+    fn test_notify_row_query() {
+        let user = User {
+            id: 42,
+            name: "musti".into(),
+        };
+        let (sql, params) = Postgres::build(notify_row_query(&user)).unwrap();
+
+        assert_eq!(
+            r#"SELECT pg_notify($1, json_build_object($2, $3, $4, ROW_TO_JSON("users"))::text) FROM "users" WHERE "users"."id" = $5"#,
+            sql
+        );
+        assert_eq!(
+            vec![
+                Value::from("users_changes"),
+                Value::from("pk"),
+                Value::from("42"),
+                Value::from("row"),
+                Value::from(42),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_encode_pk() {
+        let event = ChangeEvent::new(42);
+        assert_eq!(r#"{"pk":"42"}"#, event.encode());
+    }
+
+    #[test]
+    fn test_encode_decode_with_row() {
+        let row = serde_json::json!({"id": 42, "name": "musti"});
+        let event = ChangeEvent::new(42).with_row(row.clone());
+        let decoded = ChangeEvent::<i32>::decode(&event.encode()).unwrap();
+
+        assert_eq!(42, decoded.pk);
+        assert_eq!(Some(row), decoded.row);
+    }
+
+    #[test]
+    fn test_decode_null_row() {
+        let decoded = ChangeEvent::<i64>::decode(r#"{"pk":"7","row":null}"#).unwrap();
+        assert_eq!(ChangeEvent::new(7), decoded);
+    }
+
+    #[test]
+    fn test_decode_malformed() {
+        for payload in &["", "42", r#"{"row":{}}"#, r#"{"pk":7}"#, r#"{"pk":"seven"}"#] {
+            let err = ChangeEvent::<i32>::decode(payload).unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::ConversionError(_)), "{}", payload);
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a running postgres, set DATABASE_URL"]
+    fn test_notify_listen() {
+        async fn run() -> crate::Result<()> {
+            let url = std::env::var("DATABASE_URL").expect("DATABASE_URL");
+            let pool = sqlx::PgPool::connect(&url).await?;
+            let listener = PgListener::connect_with(&pool).await?;
+            let mut events = Box::pin(listen::<User>(listener).await?);
+
+            let user = User { id: 42, name: "musti".into() };
+            notify(&pool, &user).await?;
+
+            let event = events.next().await.expect("stream closed")?;
+            assert_eq!(42, event.pk);
+            assert_eq!(None, event.row);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
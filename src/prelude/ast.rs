@@ -0,0 +1,2 @@
+//! Building blocks of queries, without anything entity related.
+pub use crate::ast::*;
@@ -0,0 +1,23 @@
+//! Deprecated shims, imported one by one by the code still relying on them:
+//! `use xiayu::prelude::compat::EntityAlias;`. Neither
+//! [`prelude`](super) nor its halves bring them in.
+#![allow(deprecated)]
+
+use super::entity::{self, Entity, EntityAliasExt};
+use crate::ast::Table;
+
+/// Keeps `entity.alias("..")` compiling from when every entity was
+/// [`Aliasable`](crate::ast::Aliasable).
+#[deprecated(note = "use `EntityAliasExt::aliased` instead")]
+pub trait EntityAlias: Entity + entity::sealed::Sealed {
+    #[deprecated(note = "use `EntityAliasExt::aliased` instead")]
+    fn alias<'a, A>(self, alias: A) -> Table<'a>
+    where
+        Self: Sized,
+        A: Into<std::borrow::Cow<'a, str>>,
+    {
+        Self::aliased(alias)
+    }
+}
+
+impl<T> EntityAlias for T where T: Entity {}
@@ -0,0 +1,2015 @@
+//! Entities and everything derived from them: the [`Entity`] trait, column
+//! options generated by `#[derive(Entity)]`, their schemas and executions.
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+pub use xiayu_derive::*;
+
+use crate::ast::*;
+pub use crate::databases::{
+    AcquireExecutioner, Acquired, BatchReport, CachedSelectingExecution, CountingExecution, CreateTableExecution, DeleteManyRequest, DeletingExecution, ExecResult, Executioner,
+    ExplainFormat, FetchManyRequest, FetchedMany, HasExplain, HasVisitor, IdentifierQuotes, InsertingExecution, PreparedQuery, Purged, PurgeRequest, RawQuery, SavingExecution, Scalar, SelectingExecution,
+    UnboxedExecutioner, UpdatingExecution,
+};
+pub use crate::schema::{ColumnSchema, EntitySchema, IndexSchema, SchemaHasher};
+
+/// The client side default of a column, set with `#[column(default = ..)]`.
+///
+/// Literals of numbers and booleans are kept as a [`DefaultValue::Value`],
+/// everything else is built when asked for: `#[column(default(path::to::fn))]`
+/// becomes a [`DefaultValue::Fn`], as do string literals, which are converted
+/// into the type of the column.
+///
+/// Neither owns a `T`, the default is `Copy` whatever the type of the
+/// column: the column constants of a type which is not `Clone` are still
+/// `Clone`, and dropping one in a constant drops no `T`.
+pub enum DefaultValue<T: 'static> {
+    Fn(fn() -> T),
+    Value(&'static T),
+}
+
+impl<T> Clone for DefaultValue<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for DefaultValue<T> {}
+
+impl<T: Clone> DefaultValue<T> {
+    pub fn get(&self) -> T {
+        match self {
+            DefaultValue::Fn(f) => f(),
+            DefaultValue::Value(v) => (*v).clone(),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for DefaultValue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            // The same function, as far as addresses tell.
+            (DefaultValue::Fn(a), DefaultValue::Fn(b)) => *a as usize == *b as usize,
+            (DefaultValue::Value(a), DefaultValue::Value(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for DefaultValue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DefaultValue::Fn(fun) => f.debug_tuple("Fn").field(fun).finish(),
+            DefaultValue::Value(_) => f.debug_tuple("Value").field(&format_args!("_")).finish(),
+        }
+    }
+}
+
+/// Transforms the values of a column on their way to and from the database,
+/// set with `#[column(transform = "path::to::Transformer")]`. The field keeps
+/// the plain value and the table the stored one, e.g. encrypted, compressed
+/// or serialized.
+///
+/// The transformer is built with `Default` for every value bound or decoded,
+/// keys it needs are best kept in a static.
+pub trait Transformer<T>: Default {
+    /// The type of the stored value, decoded from rows.
+    type Stored;
+
+    /// The stored value of `value`, bound to inserts and updates.
+    fn encode(&self, value: &T) -> Value<'static>;
+
+    /// The value of the field from its stored value.
+    fn decode(&self, stored: Self::Stored) -> Result<T, sqlx::error::BoxDynError>;
+}
+
+/// A column its [`Transformer`] could not decode, surfacing as a
+/// [`ColumnReadFailure`](crate::error::ErrorKind::ColumnReadFailure).
+#[derive(Debug)]
+pub struct TransformError {
+    pub column: &'static str,
+    pub source: sqlx::error::BoxDynError,
+}
+
+impl std::fmt::Display for TransformError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cannot decode the column `{}`: {}", self.column, self.source)
+    }
+}
+
+impl std::error::Error for TransformError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+/// Reads a column through its transformer `X`, for the derived `FromRow`.
+#[doc(hidden)]
+pub fn decode_transformed<'r, R, T, X>(row: &'r R, column: &'static str) -> Result<T, sqlx::Error>
+where
+    R: sqlx::Row,
+    &'r str: sqlx::ColumnIndex<R>,
+    X: Transformer<T>,
+    X::Stored: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
+    let stored: X::Stored = row.try_get(column)?;
+    X::default().decode(stored).map_err(|source| sqlx::Error::ColumnDecode {
+        index: column.to_owned(),
+        source: Box::new(TransformError { column, source }),
+    })
+}
+
+/// A column which could not be decoded into the field of an entity, e.g. a
+/// `NULL` read into a field which is not an `Option`. Surfaces as a
+/// [`ColumnReadFailure`](crate::error::ErrorKind::ColumnReadFailure).
+#[derive(Debug)]
+pub struct FieldDecodeError {
+    pub entity: &'static str,
+    pub field: &'static str,
+    pub column: &'static str,
+    pub source: sqlx::error::BoxDynError,
+}
+
+impl std::fmt::Display for FieldDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cannot decode the column `{}` into `{}.{}`: {}",
+            self.column, self.entity, self.field, self.source
+        )
+    }
+}
+
+impl std::error::Error for FieldDecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+/// Where a column is read into, for the errors of the derived `FromRow`.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct FieldOf {
+    pub entity: &'static str,
+    pub field: &'static str,
+    pub column: &'static str,
+    /// Whether the field is an `Option`, `NULL`s fail to decode otherwise.
+    pub nullable: bool,
+}
+
+impl FieldOf {
+    fn error(self, source: sqlx::error::BoxDynError) -> sqlx::Error {
+        sqlx::Error::ColumnDecode {
+            index: self.column.to_owned(),
+            source: Box::new(FieldDecodeError {
+                entity: self.entity,
+                field: self.field,
+                column: self.column,
+                source,
+            }),
+        }
+    }
+}
+
+/// Reads the column of a field, for the derived `FromRow`. Columns which
+/// are not found are left to the caller, which may have a default.
+#[doc(hidden)]
+pub fn decode_field<'r, R, T>(row: &'r R, field: FieldOf) -> Result<T, sqlx::Error>
+where
+    R: sqlx::Row,
+    &'r str: sqlx::ColumnIndex<R>,
+    T: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+{
+    // Not all the drivers check for `NULL`s, SQLite decodes them as zeros
+    // and empty strings.
+    if !field.nullable && sqlx::ValueRef::is_null(&row.try_get_raw(field.column)?) {
+        return Err(field.error(Box::new(sqlx::error::UnexpectedNullError)));
+    }
+    row.try_get(field.column).map_err(|err| match err {
+        sqlx::Error::ColumnDecode { source, .. } => field.error(source),
+        err => err,
+    })
+}
+
+/// Reads the column of a `#[column(try_from = "S")]` field as an `S`,
+/// converted into the type of the field.
+#[doc(hidden)]
+pub fn decode_field_from<'r, R, T, S>(row: &'r R, field: FieldOf) -> Result<T, sqlx::Error>
+where
+    R: sqlx::Row,
+    &'r str: sqlx::ColumnIndex<R>,
+    S: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    T: std::convert::TryFrom<S>,
+    T::Error: Into<sqlx::error::BoxDynError>,
+{
+    let stored: S = decode_field(row, field)?;
+    T::try_from(stored).map_err(|err| field.error(err.into()))
+}
+
+/// [`decode_field_from`] of an `Option` field.
+#[doc(hidden)]
+pub fn decode_optional_field_from<'r, R, T, S>(row: &'r R, field: FieldOf) -> Result<Option<T>, sqlx::Error>
+where
+    R: sqlx::Row,
+    &'r str: sqlx::ColumnIndex<R>,
+    Option<S>: sqlx::Decode<'r, R::Database> + sqlx::Type<R::Database>,
+    T: std::convert::TryFrom<S>,
+    T::Error: Into<sqlx::error::BoxDynError>,
+{
+    let stored: Option<S> = decode_field(row, field)?;
+    stored
+        .map(T::try_from)
+        .transpose()
+        .map_err(|err| field.error(err.into()))
+}
+
+/// Columns grouped in a struct of their own, embedded into entities with
+/// `#[column(flatten)]`, derived with `#[derive(EmbeddedColumns)]`.
+///
+/// The columns are named after the fields of the struct, prefixed with the
+/// `prefix` of the embedding field, if any.
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// #[derive(Debug, EmbeddedColumns)]
+/// struct Address {
+///     street: String,
+///     city: String,
+/// }
+///
+/// #[derive(Debug, Entity)]
+/// #[tablename = "users"]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i32,
+///     #[column(flatten, prefix = "home_")]
+///     home: Address,
+/// }
+///
+/// # fn main() {
+/// let columns: Vec<_> = User::schema().columns.iter().map(|column| column.name).collect();
+/// assert_eq!(vec!["id", "home_street", "home_city"], columns);
+/// # }
+/// ```
+pub trait EmbeddedColumns {
+    /// The columns, named after the fields.
+    const COLUMNS: &'static [ColumnSchema];
+
+    /// The values of the columns, in order.
+    fn values(&self) -> Vec<Value<'static>>;
+}
+
+/// Decodes embedded columns from the row of their entity, derived with
+/// [`EmbeddedColumns`].
+pub trait FromEmbeddedRow<'r, R: sqlx::Row>: Sized {
+    /// Reads the columns named `prefix` followed by the name of each field.
+    fn from_row(row: &'r R, prefix: &str) -> Result<Self, sqlx::Error>;
+}
+
+/// The columns of an entity with flattened fields, built once when first
+/// asked for, as the names of embedded columns are only known then.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct FlattenedColumns {
+    tablename: &'static str,
+    schemas: Vec<ColumnSchema>,
+    columns: Vec<Column<'static>>,
+    embedded: Vec<(&'static str, std::ops::Range<usize>)>,
+}
+
+impl FlattenedColumns {
+    pub fn new(tablename: &'static str) -> Self {
+        Self {
+            tablename,
+            schemas: Vec::new(),
+            columns: Vec::new(),
+            embedded: Vec::new(),
+        }
+    }
+
+    /// Adds the column of a field of the entity.
+    pub fn push(&mut self, schema: ColumnSchema, column: Column<'static>) {
+        self.schemas.push(schema);
+        self.columns.push(column);
+    }
+
+    /// Adds the columns of the flattened field `field`.
+    pub fn embed<E: EmbeddedColumns>(&mut self, field: &'static str, prefix: &str) {
+        let start = self.columns.len();
+        for schema in E::COLUMNS {
+            // Kept for the life of the program, once per column.
+            let name: &'static str = Box::leak(format!("{}{}", prefix, schema.name).into_boxed_str());
+            let column = Column {
+                name: std::borrow::Cow::Borrowed(name),
+                table: Some(Table {
+                    typ: TableType::Table(std::borrow::Cow::Borrowed(self.tablename)),
+                    alias: None,
+                    database: None,
+                    index_definitions: Vec::new(),
+                    column_aliases: Vec::new(),
+                }),
+                type_family: schema.type_family,
+                length: schema.length,
+                ..Default::default()
+            };
+            self.schemas.push(ColumnSchema { name, ..*schema });
+            self.columns.push(column);
+        }
+        self.embedded.push((field, start..self.columns.len()));
+    }
+
+    pub fn schemas(&self) -> &[ColumnSchema] {
+        &self.schemas
+    }
+
+    pub fn columns(&self) -> &[Column<'static>] {
+        &self.columns
+    }
+
+    /// The columns of the flattened field `field`.
+    pub fn embedded(&self, field: &str) -> &[Column<'static>] {
+        self.embedded
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, range)| &self.columns[range.clone()])
+            .unwrap_or_default()
+    }
+}
+
+/// select().where(Entity::last_modified == now())
+///
+/// An entity may borrow its fields, generic over a lifetime: a `Cow<'a, str>`
+/// decoded with [`FromRow::from_row`](sqlx::FromRow::from_row) borrows from
+/// the row. The executions fetching entities need owned ones, and the
+/// primary key is owned. The columns are
+/// those of the owned fields, `ColumnOptions<Cow<'static, str>>`. More than
+/// one lifetime, or a type parameter, fail to compile:
+///
+/// ```compile_fail
+/// # use std::borrow::Cow;
+/// # use xiayu::prelude::*;
+/// #[derive(Entity)]
+/// #[tablename = "docs"]
+/// struct Doc<'a, 'b> {
+///     #[column(primary_key)]
+///     id: i64,
+///     title: Cow<'a, str>,
+///     body: Cow<'b, str>,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// `#[entity(derive_default)]` implements `Default` with the client-side
+/// default of the columns, `#[column(default = ..)]`, and `Default::default()`
+/// for the other fields, if they are an `Option` or a type of the standard
+/// library. Any other field needs a default:
+///
+/// ```compile_fail
+/// # use xiayu::prelude::*;
+/// #[derive(Entity)]
+/// #[tablename = "settings"]
+/// #[entity(derive_default)]
+/// struct Setting {
+///     #[column(primary_key)]
+///     id: i64,
+///     value: serde_json::Value,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// `#[entity(register)]` adds the entity to
+/// [`registry::registered`](crate::registry), which needs the `register`
+/// feature.
+pub trait Entity {
+    /// The columns of the fields, built at compile time. The columns of
+    /// flattened fields are left out, [`columns`](Self::columns) has all of
+    /// them.
+    const COLUMNS: &'static [Column<'static>];
+    /// The table, its columns and its primary key quoted and qualified at
+    /// compile time, for the statements formatted without a visitor, see
+    /// [`HasPrimaryKey::fast_select_by_pk`]. `None` for views and entities
+    /// with flattened fields.
+    const QUALIFIED_COLUMNS: Option<QualifiedColumns> = None;
+    fn tablename() -> &'static str;
+    /// The columns in the order the fields are declared in, the columns of
+    /// a flattened field in place of the field. Selecting them in this order,
+    /// e.g. with [`Select::columns_for`], allows decoding rows by position.
+    fn columns() -> &'static [Column<'static>];
+    /// The columns selected by default, by [`Select::columns_for`] and the
+    /// lookups of the entity: the [`columns`](Self::columns) but the
+    /// `#[column(write_only)]` ones.
+    fn readable_columns() -> &'static [Column<'static>] {
+        Self::columns()
+    }
+    fn table() -> Table<'static>;
+    /// Everything known about the table of this entity.
+    fn schema() -> EntitySchema;
+    /// The values of the entity, in the order of [`columns`](Self::columns).
+    fn values(&self) -> Vec<Value<'static>>;
+
+    /// The `SELECT` the lookups of the entity start from: of `table.*`, or of
+    /// the [`readable_columns`](Self::readable_columns) when the entity has
+    /// write-only columns.
+    fn default_select<'a>() -> Select<'a> {
+        let select = Select::from_table(Self::table());
+        if Self::readable_columns().len() == Self::columns().len() {
+            select
+        } else {
+            select.columns(Self::readable_columns().iter().cloned())
+        }
+    }
+
+    /// A hash of the layout of the table: its name, and the name, type,
+    /// nullability and primary key membership of the columns, computed at
+    /// compile time. The order of the fields does not matter.
+    ///
+    /// Compared at startup to the one recorded by the last migration with
+    /// [`verify_schema`](crate::schema_diff::verify_schema).
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # fn main() {
+    /// assert_eq!(User::schema().hash(), User::schema_hash());
+    /// # }
+    /// ```
+    fn schema_hash() -> u64 {
+        Self::schema().hash()
+    }
+
+    /// The position of the column `name` in [`columns`](Self::columns), by
+    /// its name in the database.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     #[column(name = "full_name")]
+    ///     name: String,
+    /// }
+    ///
+    /// # fn main() {
+    /// assert_eq!(Some(1), User::column_index("full_name"));
+    /// assert_eq!(None, User::column_index("name"));
+    /// # }
+    /// ```
+    fn column_index(name: &str) -> Option<usize> {
+        Self::columns().iter().position(|column| column.name == name)
+    }
+
+    /// The table of this entity under another name, e.g. the copy of a
+    /// tenant in `events_tenant42`. Conditions on its columns need the
+    /// columns bound to it with [`ColumnOptions::for_table`], the columns of
+    /// the entity are qualified with [`tablename`](Self::tablename).
+    ///
+    /// ```rust
+    /// # use entities::Dog;
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let table = Dog::table_named("dogs_tenant42");
+    /// let query = Select::from_table(table.clone()).so_that(Dog::age.for_table(&table).equals(3));
+    /// let (sql, _) = Sqlite::build(query)?;
+    ///
+    /// assert_eq!(
+    ///     "SELECT `dogs_tenant42`.* FROM `dogs_tenant42` WHERE `dogs_tenant42`.`age` = ?",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn table_named<S>(name: S) -> Table<'static>
+    where
+        S: Into<std::borrow::Cow<'static, str>>,
+    {
+        let mut table = Self::table();
+        let index_definitions = std::mem::take(&mut table.index_definitions);
+        table.typ = TableType::Table(name.into());
+        table.index_definitions = index_definitions
+            .into_iter()
+            .map(|definition| definition.set_table(table.clone()))
+            .collect();
+        table
+    }
+
+    /// The channel used to `NOTIFY` / `LISTEN` for changes of this entity,
+    /// `<tablename>_changes` unless overridden with `#[notify_channel = "..."]`.
+    fn notify_channel() -> std::borrow::Cow<'static, str> {
+        format!("{}_changes", Self::tablename()).into()
+    }
+
+    /*
+    fn select<'a, E>() -> Select<'a>
+    where
+        E: Entity,
+    {
+        Select::from_table(Self::table()).columns(E::columns())
+    }
+    */
+    fn insert<'insert, DB>() -> InsertingExecution<DB, SingleRowInsert<'insert>>
+    where
+        DB: sqlx::Database,
+    {
+        let insert: InsertingExecution<DB, _> = Insert::single_into(Self::table()).into();
+        let schema = Self::schema();
+        let primary_key = schema
+            .columns
+            .iter()
+            .zip(Self::columns())
+            .find(|(column, _)| column.primary_key);
+        match primary_key {
+            Some((_, column)) => insert.generated_key(column.clone()),
+            None => insert,
+        }
+    }
+
+    /// A query written by hand decoded into this entity, see [`RawQuery`].
+    fn raw_query<DB>(sql: impl Into<String>) -> RawQuery<Self, DB>
+    where
+        Self: Sized,
+    {
+        RawQuery::new(sql)
+    }
+
+    fn multi<'insert, C, I, DB>(columns: I) -> InsertingExecution<DB, MultiRowInsert<'insert>>
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Column<'static>>,
+        DB: sqlx::Database,
+    {
+        Insert::multi_into(Self::table(), columns).into()
+    }
+
+    /// A multi-row insert into the table, of all the columns but an
+    /// autoincremented primary key, in the order of the fields.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key, autoincrement)]
+    ///     id: i64,
+    ///     #[column(name = "display_name")]
+    ///     name: String,
+    ///     admin: bool,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let insert = User::insert_many::<sqlx::Sqlite>()
+    ///     .values(("Ada", true))
+    ///     .values(("Grace", false))
+    ///     .into_inner();
+    /// let (sql, _) = Sqlite::build(insert)?;
+    ///
+    /// assert_eq!(
+    ///     "INSERT INTO `users` (`display_name`, `admin`) VALUES (?,?), (?,?)",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn insert_many<'insert, DB>() -> InsertingExecution<DB, MultiRowInsert<'insert>>
+    where
+        DB: sqlx::Database,
+    {
+        let schema = Self::schema();
+        let mut primary_key = None;
+        let mut columns = Vec::new();
+        for (definition, column) in schema.columns.iter().zip(Self::columns()) {
+            if definition.primary_key {
+                primary_key = Some(column.clone());
+                if definition.autoincrement {
+                    continue;
+                }
+            }
+            columns.push(column.clone());
+        }
+        let insert: InsertingExecution<DB, _> = Insert::multi_into(Self::table(), columns).into();
+        match primary_key {
+            Some(column) => insert.generated_key(column),
+            None => insert,
+        }
+    }
+
+    /// Copies the rows of `S` matching `conditions` into the table, an
+    /// `INSERT INTO .. SELECT` of the columns of the same name, see
+    /// [`Select::insert_into`]. A column not in `S` takes its default,
+    /// or `NULL` when optional, other ones fail with
+    /// [`MissingColumnValue`](crate::error::ErrorKind::MissingColumnValue).
+    /// An autoincremented primary key is left to the database.
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, visitors::{Sqlite, Visitor}};
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    ///     banned: bool,
+    /// }
+    ///
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "banned_users"]
+    /// struct BannedUser {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    ///     reason: Option<String>,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let (sql, _) = Sqlite::build(BannedUser::copy_from::<User, _>(User::banned.equals(true))?)?;
+    ///
+    /// assert_eq!(
+    ///     "INSERT INTO `banned_users` (`id`, `name`) SELECT `users`.`id`, `users`.`name` FROM `users` WHERE `users`.`banned` = ?",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn copy_from<'a, S, C>(conditions: C) -> crate::Result<Insert<'a>>
+    where
+        Self: Sized,
+        S: Entity,
+        C: Into<ConditionTree<'a>>,
+    {
+        let source = S::schema();
+        let mut mapping: Vec<(Column<'a>, Expression<'a>)> = Vec::new();
+        for (definition, column) in Self::schema().columns.iter().zip(Self::columns()) {
+            if definition.primary_key && definition.autoincrement {
+                continue;
+            }
+            let source_column = source
+                .columns
+                .iter()
+                .zip(S::columns())
+                .find(|(source, _)| source.name == definition.name)
+                .map(|(_, column)| column.clone());
+
+            match (source_column, column.default.as_ref().and_then(|default| default.value())) {
+                (Some(source_column), _) => mapping.push((column.clone(), source_column.into())),
+                (None, Some(default)) => mapping.push((column.clone(), default.into())),
+                (None, None) if definition.nullable || column.default.is_some() => {}
+                (None, None) => {
+                    use crate::error::{Error, ErrorKind, Name};
+
+                    return Err(Error::builder(ErrorKind::MissingColumnValue {
+                        table: Name::available(Self::tablename()),
+                        column: Name::available(definition.name),
+                    })
+                    .build());
+                }
+            }
+        }
+
+        Select::from_table(S::table()).so_that(conditions).insert_into::<Self, _, _, _>(mapping)
+    }
+
+    /// Counts the distinct values of `expr` in the table, see
+    /// [`count_distinct`](crate::ast::count_distinct).
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "events"]
+    /// struct Event {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     user_id: i64,
+    ///     kind: String,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let visitors = Event::count_distinct::<sqlx::Sqlite, _>(Event::user_id)
+    ///     .so_that(Event::kind.equals("visit"))
+    ///     .prepare()?;
+    ///
+    /// assert_eq!(
+    ///     "SELECT COUNT(DISTINCT `events`.`user_id`) FROM `events` WHERE `events`.`kind` = ?",
+    ///     visitors.sql()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn count_distinct<DB, T>(expr: T) -> CountingExecution<DB>
+    where
+        DB: sqlx::Database,
+        T: Into<Expression<'static>>,
+    {
+        CountingExecution::new(Select::from_table(Self::table()).value(count_distinct(expr)))
+    }
+
+    /// Deletes the rows matching `conditions`, any number of them, without
+    /// loading them.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "posts"]
+    /// struct Post {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     user_id: i64,
+    /// }
+    ///
+    /// # async fn run(pool: &sqlx::SqlitePool) -> xiayu::Result<()> {
+    /// let deleted = Post::delete_where(Post::user_id.equals(42)).conn(pool).await?;
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    fn delete_where<DB, C>(conditions: C) -> DeleteManyRequest<Self, DB>
+    where
+        Self: Sized,
+        DB: sqlx::Database,
+        C: Into<ConditionTree<'static>>,
+    {
+        DeleteManyRequest::new(Delete::from_table(Self::table()).so_that(conditions))
+    }
+
+    /// Deletes the rows whose `column` is older than `cutoff`, in batches of
+    /// `batch_size` rows, the oldest first, see [`PurgeRequest`]. An index on
+    /// `column` keeps every batch short.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use std::time::Duration;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "events"]
+    /// struct Event {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     created_at: i64,
+    /// }
+    ///
+    /// # async fn run(conn: &mut sqlx::SqliteConnection, cutoff: i64) -> xiayu::Result<()> {
+    /// let purged = Event::purge_older_than(Event::created_at, cutoff, 1_000)
+    ///     .pause(Duration::from_millis(50))
+    ///     .max_batches(100)
+    ///     .on_progress(|purged| println!("{} rows purged", purged.rows_deleted()))
+    ///     .conn(conn)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    fn purge_older_than<'a, DB, C, V>(column: C, cutoff: V, batch_size: usize) -> PurgeRequest<'a, Self, DB>
+    where
+        Self: Sized,
+        DB: sqlx::Database,
+        C: Into<Column<'static>>,
+        V: Into<Value<'static>>,
+    {
+        PurgeRequest::new(column.into(), cutoff.into(), batch_size)
+    }
+}
+
+pub trait EntityInstantiated: Entity + sealed::Sealed {
+    fn tablename(&self) -> &'static str {
+        <Self as Entity>::tablename()
+    }
+
+    fn columns(&self) -> &'static [Column<'static>] {
+        <Self as Entity>::columns()
+    }
+}
+
+impl<T> EntityInstantiated for T where T: Entity {}
+
+/// Aliasing the table of an entity, `User::aliased("u")`.
+///
+/// Implemented for every [`Entity`], and sealed so it can not conflict with
+/// implementations outside of this crate.
+pub trait EntityAliasExt: Entity + sealed::Sealed {
+    /// The table of this entity, under another name.
+    fn aliased<'a, A>(alias: A) -> Table<'a>
+    where
+        A: Into<std::borrow::Cow<'a, str>>,
+    {
+        let mut table = Self::table();
+        table.alias.replace(alias.into());
+        table
+    }
+}
+
+impl<T> EntityAliasExt for T where T: Entity {}
+
+pub(crate) mod sealed {
+    pub trait Sealed {}
+
+    impl<T> Sealed for T where T: super::Entity {}
+}
+
+pub trait HasPrimaryKey: Entity {
+    type PrimaryKey;
+    type PrimaryKeyValueType;
+    fn primary_key() -> <Self as HasPrimaryKey>::PrimaryKey;
+    fn pk(&self) -> <Self as HasPrimaryKey>::PrimaryKeyValueType;
+    /// Fetches the entity with the given primary key, `User::get(42)`.
+    fn get<DB, K>(pk: K) -> SelectingExecution<Self, DB>
+    where
+        DB: sqlx::Database,
+        K: IntoPrimaryKey<Self::PrimaryKeyValueType>,
+        Self: for<'r> sqlx::FromRow<'r, <DB as sqlx::Database>::Row> + Sized;
+    /// Fetches the entities with the given primary keys in one query, or
+    /// one by chunk of keys when there are more than a statement can bind.
+    /// The entities come in the order of `pks`, the keys without an entity
+    /// are reported apart.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Clone, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+    /// let fetched = User::get_many(vec![3, 1, 42]).conn(conn).await?;
+    /// for user in fetched.entities() {
+    ///     println!("{}", user.name);
+    /// }
+    /// if !fetched.missing().is_empty() {
+    ///     println!("no users {:?}", fetched.missing());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    fn get_many<DB, I>(pks: I) -> FetchManyRequest<Self, DB>
+    where
+        DB: sqlx::Database,
+        I: IntoIterator<Item = Self::PrimaryKeyValueType>,
+        Self: Sized,
+    {
+        FetchManyRequest::new(pks.into_iter().collect())
+    }
+    fn delete<'e, DB: sqlx::Database>(&'e mut self) -> DeletingExecution<'e, Self, DB>
+    where
+        Self: Sized + Writable;
+    /// The statement of [`get`](Self::get), formatted from
+    /// [`Entity::QUALIFIED_COLUMNS`] with no AST and no visitor, for the
+    /// hottest lookups. Selects the columns of the entity by name, the SQL is
+    /// the one a visitor builds for
+    /// `Select::from_table(Self::table()).columns_for::<Self>()` and the key,
+    /// which it falls back to without quoted columns.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = User::fast_select_by_pk::<sqlx::Sqlite, _>(42)?;
+    /// assert_eq!(
+    ///     "SELECT `users`.`id`, `users`.`name` FROM `users` WHERE `users`.`id` = ?",
+    ///     query.sql()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn fast_select_by_pk<DB, K>(pk: K) -> crate::Result<PreparedQuery<DB>>
+    where
+        Self: Sized,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        K: IntoPrimaryKey<Self::PrimaryKeyValueType>,
+        Self::PrimaryKey: Into<Column<'static>>,
+        Self::PrimaryKeyValueType: Into<Value<'static>>,
+    {
+        let pk = fast_key::<Self, _>(pk)?;
+        match quoted_identifiers::<Self, DB>() {
+            Some((quoted, primary_key)) => {
+                let sql = format!(
+                    "SELECT {} FROM {} WHERE {} = {}",
+                    quoted.columns,
+                    quoted.table,
+                    primary_key,
+                    <DB as HasVisitor<'static>>::FIRST_PARAMETER
+                );
+                Ok(fast_query(sql, pk))
+            }
+            None => {
+                let select = Select::from_table(Self::table()).columns_for::<Self>().so_that(Self::primary_key().into().equals(pk));
+                PreparedQuery::build(select, false, &Default::default())
+            }
+        }
+    }
+    /// The statement of [`delete`](Self::delete), formatted like
+    /// [`fast_select_by_pk`](Self::fast_select_by_pk).
+    fn fast_delete_by_pk<DB, K>(pk: K) -> crate::Result<PreparedQuery<DB>>
+    where
+        Self: Sized + Writable,
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+        K: IntoPrimaryKey<Self::PrimaryKeyValueType>,
+        Self::PrimaryKey: Into<Column<'static>>,
+        Self::PrimaryKeyValueType: Into<Value<'static>>,
+    {
+        let pk = fast_key::<Self, _>(pk)?;
+        match quoted_identifiers::<Self, DB>() {
+            Some((quoted, primary_key)) => {
+                let sql = format!(
+                    "DELETE FROM {} WHERE {} = {}",
+                    quoted.table,
+                    primary_key,
+                    <DB as HasVisitor<'static>>::FIRST_PARAMETER
+                );
+                Ok(fast_query(sql, pk))
+            }
+            None => {
+                let delete = Delete::from_table(Self::table()).so_that(Self::primary_key().into().equals(pk));
+                PreparedQuery::build(delete, false, &Default::default())
+            }
+        }
+    }
+    /// Updates the row of the entity, or inserts it when its key is
+    /// generated and not set yet.
+    ///
+    /// The function of `#[entity(before_save = "path::to::fn")]` is called
+    /// with the entity first, and `#[entity(after_load = "path::to::fn")]`
+    /// with every entity decoded from a row. Both take `&mut Self` and return
+    /// a [`Result`](crate::Result), an error fails the save or the decoding.
+    /// Statements built by hand, such as an [`Update`] of many rows or
+    /// [`Entity::insert`], do not call them.
+    ///
+    /// `before_save` runs when `save` is called, as the statement is built
+    /// from the entity it leaves: before a generated key is set and whether
+    /// or not the returned execution is ever run.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "tags"]
+    /// #[entity(before_save = "Tag::normalize")]
+    /// struct Tag {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// impl Tag {
+    ///     fn normalize(&mut self) -> xiayu::Result<()> {
+    ///         self.name = self.name.trim().to_lowercase();
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+    /// let mut tag = Tag { id: 1, name: " Rust ".to_string() };
+    /// tag.save().conn(&mut *conn).await?;
+    /// assert_eq!("rust", tag.name);
+    /// # Ok(())
+    /// # }
+    /// # fn main() {}
+    /// ```
+    fn save<'e, DB: sqlx::Database>(&'e mut self) -> SavingExecution<'e, Self, DB>
+    where
+        Self: Sized + Writable;
+}
+
+/// Entities which can be saved and deleted, derived for all of them but the
+/// ones with `#[entity(readonly)]` or `#[entity(view = "...")]`, e.g. for
+/// database views and reporting tables.
+///
+/// ```rust,compile_fail
+/// # use xiayu::prelude::*;
+/// #[derive(Debug, Entity)]
+/// #[tablename = "daily_totals"]
+/// #[entity(readonly)]
+/// struct DailyTotal {
+///     #[column(primary_key)]
+///     day: String,
+///     total: i64,
+/// }
+///
+/// # async fn run(conn: &sqlx::SqlitePool) -> xiayu::Result<()> {
+/// let mut total = DailyTotal::get("2021-11-02").conn(conn).await?;
+/// total.total = 0;
+/// // Read-only entities are not `Writable`.
+/// total.save().conn(conn).await?;
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is a read-only entity, it can not be saved or deleted",
+    label = "read-only entity",
+    note = "entities with `#[entity(readonly)]` or `#[entity(view = \"...\")]` are only read"
+)]
+pub trait Writable: HasPrimaryKey {}
+
+/// The identifiers of an entity in the quotes of a database: `` `users` ``,
+/// `` `users`.`id`, `users`.`name` `` and `` `users`.`id` ``.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotedIdentifiers {
+    pub table: &'static str,
+    /// The columns qualified with the table, separated with commas, in the
+    /// order of [`Entity::columns`].
+    pub columns: &'static str,
+    /// The primary key qualified with the table, if any.
+    pub primary_key: Option<&'static str>,
+}
+
+/// The identifiers of an entity quoted at compile time for every database,
+/// see [`Entity::QUALIFIED_COLUMNS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QualifiedColumns {
+    pub backticks: QuotedIdentifiers,
+    pub double_quotes: QuotedIdentifiers,
+    pub brackets: QuotedIdentifiers,
+}
+
+impl QualifiedColumns {
+    pub fn quoted(&self, quotes: IdentifierQuotes) -> QuotedIdentifiers {
+        match quotes {
+            IdentifierQuotes::Backticks => self.backticks,
+            IdentifierQuotes::DoubleQuotes => self.double_quotes,
+            IdentifierQuotes::Brackets => self.brackets,
+        }
+    }
+}
+
+/// The values of the columns of an entity as loaded, kept in a field of
+/// entities deriving with `#[entity(track_changes)]`. Their `update` sets the
+/// columns changed since, see [`UpdatingExecution`].
+///
+/// An entity built rather than loaded has none, its `update` sets every
+/// column.
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// #[derive(Debug, Entity)]
+/// #[entity(track_changes)]
+/// #[tablename = "accounts"]
+/// struct Account {
+///     #[column(primary_key)]
+///     id: i64,
+///     email: String,
+///     balance: i64,
+///     snapshot: Snapshot,
+/// }
+///
+/// # async fn run(pool: &sqlx::SqlitePool) -> xiayu::Result<()> {
+/// let mut account = Account::get(1).conn(pool).await?;
+/// account.balance += 10;
+/// // UPDATE `accounts` SET `balance` = ? WHERE `accounts`.`id` = ?
+/// account.update().conn(pool).await?;
+/// // Nothing changed since, nothing is sent.
+/// account.update().conn(pool).await?;
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot(Option<Vec<Value<'static>>>);
+
+impl Snapshot {
+    /// The snapshot of the values of an entity, in the order of its columns.
+    #[doc(hidden)]
+    pub fn taken(values: Vec<Value<'static>>) -> Self {
+        Self(Some(values))
+    }
+
+    /// Whether the entity was loaded, or updated since.
+    pub fn is_taken(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Forgets the values, the next `update` sets every column.
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    /// The indexes of the `values` which differ from the snapshot, all of
+    /// them without a snapshot.
+    pub(crate) fn changed(&self, values: &[Value<'static>]) -> Vec<usize> {
+        match &self.0 {
+            Some(taken) if taken.len() == values.len() => values
+                .iter()
+                .zip(taken)
+                .enumerate()
+                .filter(|(_, (value, taken))| value != taken)
+                .map(|(index, _)| index)
+                .collect(),
+            _ => (0..values.len()).collect(),
+        }
+    }
+}
+
+/// The bound of the `get`, `save` and `delete` generated for an entity
+/// without a primary key, never implemented: calling them fails to compile.
+///
+/// ```rust,compile_fail,E0277
+/// # use xiayu::prelude::*;
+/// #[derive(Debug, Entity)]
+/// #[tablename = "page_views"]
+/// struct PageView {
+///     path: String,
+///     viewed_at: i64,
+/// }
+///
+/// # async fn run(conn: &sqlx::SqlitePool) -> xiayu::Result<()> {
+/// // `PageView` has no primary key, it can not be looked up, saved or deleted
+/// let view = PageView::get("/").conn(conn).await?;
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+///
+/// Entities without a key on purpose have `#[entity(no_primary_key)]`, these
+/// methods are then left out.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{E}` has no primary key, it can not be looked up, saved or deleted",
+    label = "requires a primary key",
+    note = "add `#[column(primary_key)]` to the key of `{E}`, or `#[entity(no_primary_key)]` to leave these methods out"
+)]
+pub trait PrimaryKeyRequired<E> {}
+
+/// Anything to look up an entity with a primary key of type `T`: the key
+/// itself, a reference to it, or a narrower type such as an `i32` for an `i64`
+/// key or a `&str` for a `String` key.
+pub trait IntoPrimaryKey<T> {
+    fn into_primary_key(self) -> T;
+}
+
+impl<T> IntoPrimaryKey<T> for T {
+    fn into_primary_key(self) -> T {
+        self
+    }
+}
+
+impl<T> IntoPrimaryKey<T> for &T
+where
+    T: Clone,
+{
+    fn into_primary_key(self) -> T {
+        self.clone()
+    }
+}
+
+impl IntoPrimaryKey<String> for &str {
+    fn into_primary_key(self) -> String {
+        self.to_owned()
+    }
+}
+
+/// Looking up an entity with an optional key, e.g. `Option<Uuid>`, by the key
+/// itself.
+impl<T> IntoPrimaryKey<Option<T>> for T {
+    fn into_primary_key(self) -> Option<T> {
+        Some(self)
+    }
+}
+
+impl<T> IntoPrimaryKey<Option<T>> for &T
+where
+    T: Clone,
+{
+    fn into_primary_key(self) -> Option<T> {
+        Some(self.clone())
+    }
+}
+
+/// The key of a statement formatted without a visitor, a key which is not
+/// set fails like the one of [`HasPrimaryKey::get`].
+fn fast_key<E, K>(pk: K) -> crate::Result<Value<'static>>
+where
+    E: HasPrimaryKey,
+    K: IntoPrimaryKey<E::PrimaryKeyValueType>,
+    E::PrimaryKeyValueType: Into<Value<'static>>,
+{
+    let pk: Value<'static> = pk.into_primary_key().into();
+    if pk.is_null() {
+        return Err(primary_key_not_set(E::tablename()));
+    }
+    Ok(pk)
+}
+
+/// The quoted identifiers of `E` in the quotes of `DB`, and its quoted primary
+/// key.
+fn quoted_identifiers<E, DB>() -> Option<(QuotedIdentifiers, &'static str)>
+where
+    E: Entity,
+    DB: for<'v> HasVisitor<'v>,
+{
+    let quoted = E::QUALIFIED_COLUMNS?.quoted(<DB as HasVisitor<'static>>::IDENTIFIER_QUOTES);
+    Some((quoted, quoted.primary_key?))
+}
+
+/// Formatted by hand, the statement gets the comment a visitor would add.
+fn fast_query<DB>(mut sql: String, pk: Value<'static>) -> PreparedQuery<DB> {
+    crate::comment::Comment::default().append_to(&mut sql);
+    PreparedQuery::from_parts(sql, vec![pk])
+}
+
+/// The error of executing a query by a primary key which is not set, e.g. an
+/// `Option` key of an entity not saved yet.
+#[doc(hidden)]
+pub fn primary_key_not_set(tablename: &'static str) -> crate::error::Error {
+    use crate::error::{Error, ErrorKind, Name};
+
+    Error::builder(ErrorKind::PrimaryKeyNotSet {
+        table: Name::available(tablename),
+    })
+    .build()
+}
+
+/// Registers an entity deriving with `#[entity(register)]`, see
+/// [`registry::registered`](crate::registry).
+#[doc(hidden)]
+pub use crate::__register_entity;
+
+/// The key generated by the database for an inserted row, in the type of the
+/// key of the entity.
+#[doc(hidden)]
+pub fn convert_generated_key<K: std::convert::TryFrom<i64>>(id: i64) -> crate::Result<K> {
+    use crate::error::{Error, ErrorKind};
+
+    K::try_from(id).map_err(|_| {
+        let kind = ErrorKind::conversion(format!("the generated key {} does not fit the type of the key", id));
+        Error::builder(kind).build()
+    })
+}
+
+/// A random version 4 uuid, for client side generated keys:
+/// `#[column(primary_key, generator = "uuid_v4")]`.
+#[cfg(feature = "uuid")]
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
+pub fn uuid_v4() -> sqlx::types::Uuid {
+    uuid::Uuid::new_v4()
+}
+
+macro_rules! widening_primary_key {
+    ($($from:ty => $($to:ty),+;)*) => {
+        $($(
+            impl IntoPrimaryKey<$to> for $from {
+                fn into_primary_key(self) -> $to {
+                    self.into()
+                }
+            }
+        )+)*
+    };
+}
+
+widening_primary_key! {
+    i8 => i16, i32, i64;
+    i16 => i32, i64;
+    i32 => i64;
+    u8 => u16, u32, u64, i16, i32, i64;
+    u16 => u32, u64, i32, i64;
+    u32 => u64, i64;
+}
+
+/// The types of columns filtered by a range with [`ColumnOptions::within`],
+/// compared to bounds of type `Bound`, the type itself for a nullable column
+/// too.
+pub trait Ranged {
+    type Bound: Into<Value<'static>> + Clone;
+}
+
+impl<T: Ranged> Ranged for Option<T> {
+    type Bound = T::Bound;
+}
+
+macro_rules! ranged {
+    ($($(#[$meta:meta])* $ty:ty;)*) => {
+        $(
+            $(#[$meta])*
+            impl Ranged for $ty {
+                type Bound = $ty;
+            }
+        )*
+    };
+}
+
+ranged! {
+    i32;
+    i64;
+    f32;
+    f64;
+    #[cfg(feature = "chrono")]
+    chrono::DateTime<chrono::Utc>;
+    #[cfg(feature = "chrono")]
+    chrono::NaiveDate;
+    #[cfg(feature = "chrono")]
+    chrono::NaiveTime;
+}
+
+/// A required field not set yet in a builder generated by `#[entity(builder)]`.
+///
+/// Rows can only be inserted once every required field is provided:
+///
+/// ```compile_fail
+/// # use xiayu::prelude::*;
+/// #[derive(Entity)]
+/// #[entity(builder)]
+/// struct User {
+///     #[column(primary_key, autoincrement)]
+///     id: i32,
+///     name: String,
+/// }
+///
+/// # fn main() {
+/// // `name` is required.
+/// let insert = User::builder().id(1).insert::<sqlx::Sqlite>();
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Missing;
+
+/// A required field set in a builder generated by `#[entity(builder)]`.
+#[derive(Debug, Clone)]
+pub struct Provided<T>(pub T);
+
+/// A column of an entity, the constants generated by `#[derive(Entity)]`.
+/// Built with [`ColumnOptions::builder`].
+///
+/// The constants are named after the fields, `Parcel::r#type` for the column
+/// `type` of a field `r#type`. Names which can not be a column even quoted,
+/// and fields named after the items generated next to the constants, such as
+/// `_schema`, fail to compile:
+///
+/// ```compile_fail
+/// # use xiayu::prelude::*;
+/// #[derive(Entity)]
+/// #[tablename = "parcels"]
+/// struct Parcel {
+///     #[column(primary_key)]
+///     id: i64,
+///     #[column(name = "weight`kg")]
+///     weight: i64,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// # use xiayu::prelude::*;
+/// #[derive(Entity)]
+/// #[tablename = "parcels"]
+/// struct Parcel {
+///     #[column(primary_key)]
+///     id: i64,
+///     _schema: String,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// A field with `#[column(skip)]` has no column, it is filled with
+/// `Default::default()` when the entity is decoded, or with the function of
+/// `skip_default = "path::to::fn"`. Without it the type of the field has to
+/// implement `Default`:
+///
+/// ```compile_fail
+/// # use xiayu::prelude::*;
+/// struct Cache;
+///
+/// #[derive(Entity)]
+/// #[tablename = "parcels"]
+/// struct Parcel {
+///     #[column(primary_key)]
+///     id: i64,
+///     #[column(skip)]
+///     cache: Cache,
+/// }
+/// # fn main() {}
+/// ```
+pub struct ColumnOptions<T: 'static> {
+    name: &'static str,
+    tablename: &'static str,
+    /// Set up "auto increment" semantics for an integer primary key column.
+    /// The default value is the string "auto" which indicates that a single-column primary key that is of an INTEGER type with no stated client-side or python-side defaults should receive auto increment semantics automatically; all other varieties of primary key columns will not.
+    /// This includes that DDL such as PostgreSQL SERIAL or MySQL AUTO_INCREMENT will be emitted for this column during a table create, as well as that the column is assumed to generate new integer primary key values when an INSERT statement invokes which will be retrieved by the dialect.
+    /// When used in conjunction with Identity on a dialect that supports it, this parameter has no effect.
+    primary_key: bool,
+    autoincrement: bool,
+    /// Optional string that will render an SQL comment on table creation.
+    comment: Option<&'static str>,
+    unique: bool,
+    foreign_key: Option<&'static str>,
+    /// The name of this column as represented in the database. This argument may be the first positional argument, or specified via keyword.
+    length: Option<usize>,
+    /// Quote the name even when not required by [`QuoteStyle::Minimal`](crate::visitors::QuoteStyle::Minimal).
+    quote: bool,
+    default: Option<DefaultValue<T>>,
+    /// Inferred from the rust type of the field, used for DDL and the SQL Server `OUTPUT` hack.
+    type_family: Option<TypeFamily>,
+    /// The SQL of the default the database provides.
+    server_default: Option<&'static str>,
+    /// The SQL the database sets the column to when the row is updated.
+    server_onupdate: Option<&'static str>,
+    /// The client side default as a value, for the `MERGE` of SQL Server
+    /// when the column is left out of an insert.
+    bound_default: Option<fn() -> Value<'static>>,
+    /// Set by the database when left out of an insert, e.g. an identity.
+    server_generated: bool,
+    /// Written but not selected by default, see `#[column(write_only)]`.
+    write_only: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> ColumnOptions<T> {
+    /// Starts the options of the column `name` of `tablename`, everything
+    /// else unset. New metadata of columns is added to the builder only.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// const ID: ColumnOptions<i64> = ColumnOptions::builder("id", "users")
+    ///     .primary_key()
+    ///     .autoincrement()
+    ///     .type_family(TypeFamily::Int)
+    ///     .build();
+    ///
+    /// assert_eq!(Some(TypeFamily::Int), ID.type_family());
+    /// ```
+    pub const fn builder(name: &'static str, tablename: &'static str) -> ColumnOptionsBuilder<T> {
+        ColumnOptionsBuilder {
+            options: Self {
+                name,
+                tablename,
+                primary_key: false,
+                autoincrement: false,
+                foreign_key: None,
+                comment: None,
+                unique: false,
+                length: None,
+                quote: false,
+                default: None,
+                type_family: None,
+                server_default: None,
+                server_onupdate: None,
+                bound_default: None,
+                server_generated: false,
+                write_only: false,
+                _phantom: PhantomData,
+            },
+        }
+    }
+
+    #[deprecated(note = "use `ColumnOptions::builder`, the arguments are easily mixed up")]
+    #[allow(clippy::too_many_arguments)]
+    pub const fn new(
+        name: &'static str,
+        tablename: &'static str,
+        primary_key: bool,
+        autoincrement: bool,
+        foreign_key: Option<&'static str>,
+        comment: Option<&'static str>,
+        unique: bool,
+        length: Option<usize>,
+        quote: bool,
+        default: Option<DefaultValue<T>>,
+        type_family: Option<TypeFamily>,
+    ) -> Self {
+        Self {
+            name,
+            tablename,
+            primary_key,
+            autoincrement,
+            foreign_key,
+            comment,
+            unique,
+            length,
+            quote,
+            default,
+            type_family,
+            server_default: None,
+            server_onupdate: None,
+            bound_default: None,
+            server_generated: false,
+            write_only: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The column, with its default: [`Generated`](crate::ast::DefaultValue::Generated)
+    /// when the database sets it, see [`ColumnOptionsBuilder::server_generated`],
+    /// or the value of the client side default.
+    pub const fn column(&self) -> Column<'static> {
+        let default = if self.server_generated || self.autoincrement || self.server_default.is_some() {
+            Some(crate::ast::DefaultValue::Generated)
+        } else if let Some(value) = self.bound_default {
+            Some(crate::ast::DefaultValue::Computed(value))
+        } else {
+            None
+        };
+        Column {
+            name: std::borrow::Cow::Borrowed(self.name),
+            table: Some(self.table()),
+            alias: None,
+            default,
+            type_family: self.type_family,
+            length: self.length,
+            quote: self.quote,
+            write_only: self.write_only,
+        }
+    }
+
+    /// Like [`column`](Self::column), taking the options.
+    pub const fn into_column(self) -> Column<'static> {
+        self.column()
+    }
+
+    pub const fn table(&self) -> Table<'static> {
+        Table {
+            typ: TableType::Table(std::borrow::Cow::Borrowed(self.tablename)),
+            alias: None,
+            database: None,
+            index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
+        }
+    }
+
+    pub const fn type_family(&self) -> Option<TypeFamily> {
+        self.type_family
+    }
+
+    /// The SQL of the default the database provides, if any.
+    pub const fn server_default(&self) -> Option<&'static str> {
+        self.server_default
+    }
+
+    /// The SQL the database sets the column to on update, if any.
+    pub const fn server_onupdate(&self) -> Option<&'static str> {
+        self.server_onupdate
+    }
+
+    /// Whether the column is left out of the columns selected by default.
+    pub const fn is_write_only(&self) -> bool {
+        self.write_only
+    }
+
+    /// The client side default of the column, if any.
+    pub fn default_value(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.default.as_ref().map(DefaultValue::get)
+    }
+
+    /// `column IN (..)`, with a [`Values`], a [`Select`] or the values of an
+    /// iterator.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     banned: bool,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::id.in_selection(vec![1, 2]));
+    /// let (sql, _) = Sqlite::build(query)?;
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`id` IN (?,?)", sql);
+    ///
+    /// let banned = Select::from_table(User::table()).column(User::id).so_that(User::banned.equals(true));
+    /// let query = Select::from_table(User::table()).so_that(User::id.not_in_selection(banned));
+    /// let (sql, _) = Sqlite::build(query)?;
+    /// assert_eq!(
+    ///     "SELECT `users`.* FROM `users` WHERE `users`.`id` NOT IN (SELECT `users`.`id` FROM `users` WHERE `users`.`banned` = ?)",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn in_selection<'a, S>(self, selection: S) -> Compare<'a>
+    where
+        S: IntoSelection<'a>,
+    {
+        Comparable::in_selection(self.column(), selection.into_selection())
+    }
+
+    /// `column NOT IN (..)`, see [`in_selection`](Self::in_selection).
+    pub fn not_in_selection<'a, S>(self, selection: S) -> Compare<'a>
+    where
+        S: IntoSelection<'a>,
+    {
+        Comparable::not_in_selection(self.column(), selection.into_selection())
+    }
+
+    /// The column within `range`: `start..end` is `column >= start AND
+    /// column < end`, `start..=end` includes `end`, and a range open on a side
+    /// compares the other bound only. A full range `..` is no condition.
+    ///
+    /// ```rust
+    /// # use xiayu::prelude::*;
+    /// # use xiayu::visitors::{Visitor, Sqlite};
+    /// #[derive(Debug, Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     age: i32,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let query = Select::from_table(User::table()).so_that(User::age.within(18..65));
+    /// let (sql, _) = Sqlite::build(query)?;
+    /// assert_eq!("SELECT `users`.* FROM `users` WHERE (`users`.`age` >= ? AND `users`.`age` < ?)", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn within<'a, R>(&self, range: R) -> ConditionTree<'a>
+    where
+        T: Ranged,
+        R: RangeBounds<T::Bound>,
+    {
+        let lower = match range.start_bound() {
+            Bound::Included(start) => Some(self.column().greater_than_or_equals(start.clone())),
+            Bound::Excluded(start) => Some(self.column().greater_than(start.clone())),
+            Bound::Unbounded => None,
+        };
+        let upper = match range.end_bound() {
+            Bound::Included(end) => Some(self.column().less_than_or_equals(end.clone())),
+            Bound::Excluded(end) => Some(self.column().less_than(end.clone())),
+            Bound::Unbounded => None,
+        };
+
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => lower.and(upper),
+            (Some(bound), None) | (None, Some(bound)) => ConditionTree::single(bound),
+            (None, None) => ConditionTree::NoCondition,
+        }
+    }
+
+    /// The timestamp on `date` in UTC, from its midnight included to the next
+    /// one excluded. The bounds are computed here, the same SQL as
+    /// [`within`](Self::within) a range of timestamps.
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    pub fn on_date<'a>(&self, date: chrono::NaiveDate) -> ConditionTree<'a>
+    where
+        T: Ranged<Bound = chrono::DateTime<chrono::Utc>>,
+    {
+        use chrono::{Duration, TimeZone, Utc};
+
+        let start = Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is a valid time"));
+        self.within(start..start + Duration::days(1))
+    }
+
+    /// The column qualified with `table` instead of the table of its entity,
+    /// see [`Entity::table_named`]. An aliased `table` qualifies it by its
+    /// alias, as [`Table::col`].
+    pub fn for_table<'a>(&self, table: &Table<'a>) -> Column<'a> {
+        let column: Column<'a> = self.column();
+        column.table(table.clone())
+    }
+
+    pub fn c(&self) -> Column<'static> {
+        self.column()
+    }
+
+    pub fn t(&self) -> Table<'static> {
+        self.table()
+    }
+
+    // pub create_table() -> CreateTable;
+}
+
+impl<'a, T> From<ColumnOptions<T>> for Column<'a> {
+    fn from(options: ColumnOptions<T>) -> Self {
+        options.c()
+    }
+}
+
+impl<'a, T> From<&ColumnOptions<T>> for Column<'a> {
+    fn from(options: &ColumnOptions<T>) -> Self {
+        options.c()
+    }
+}
+
+impl<'a, T> From<ColumnOptions<T>> for Expression<'a> {
+    fn from(col: ColumnOptions<T>) -> Self {
+        Expression {
+            kind: ExpressionKind::Column(Box::new(col.column())),
+            alias: None,
+        }
+    }
+}
+
+impl<'a, T> From<&ColumnOptions<T>> for Expression<'a> {
+    fn from(col: &ColumnOptions<T>) -> Self {
+        Expression {
+            kind: ExpressionKind::Column(Box::new(col.column())),
+            alias: None,
+        }
+    }
+}
+
+impl<'a, T> Aliasable<'a> for ColumnOptions<T> {
+    type Target = Column<'a>;
+
+    fn alias<A>(self, alias: A) -> Self::Target
+    where
+        A: Into<std::borrow::Cow<'a, str>>,
+    {
+        let mut target = self.column();
+        target.alias = Some(alias.into());
+        target
+    }
+}
+
+impl<'a, T> IntoOrderDefinition<'a> for ColumnOptions<T> {
+    fn into_order_definition(self) -> OrderDefinition<'a> {
+        (self.column().into(), None)
+    }
+}
+
+impl<'a, T> Orderable<'a> for ColumnOptions<T> {
+    fn order(self, order: Option<Order>) -> OrderDefinition<'a> {
+        (self.column().into(), order)
+    }
+}
+
+// Not derived, `T` need not be `Clone`: the default holds no `T`.
+impl<T> Clone for ColumnOptions<T> {
+    fn clone(&self) -> Self {
+        ColumnOptions {
+            name: self.name,
+            tablename: self.tablename,
+            primary_key: self.primary_key,
+            autoincrement: self.autoincrement,
+            comment: self.comment,
+            unique: self.unique,
+            foreign_key: self.foreign_key,
+            length: self.length,
+            quote: self.quote,
+            default: self.default,
+            type_family: self.type_family,
+            server_default: self.server_default,
+            server_onupdate: self.server_onupdate,
+            bound_default: self.bound_default,
+            server_generated: self.server_generated,
+            write_only: self.write_only,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for ColumnOptions<T> {
+    fn eq(&self, other: &ColumnOptions<T>) -> bool {
+        self.name == other.name
+            && self.tablename == other.tablename
+            && self.primary_key == other.primary_key
+            && self.autoincrement == other.autoincrement
+            && self.comment == other.comment
+            && self.unique == other.unique
+            && self.foreign_key == other.foreign_key
+            && self.length == other.length
+            && self.quote == other.quote
+            && self.default == other.default
+            && self.type_family == other.type_family
+            && self.server_default == other.server_default
+            && self.server_onupdate == other.server_onupdate
+            && self.server_generated == other.server_generated
+            && self.write_only == other.write_only
+    }
+}
+
+impl<T> std::fmt::Debug for ColumnOptions<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColumnOptions")
+            .field("name", &self.name)
+            .field("tablename", &self.tablename)
+            .field("primary_key", &self.primary_key)
+            .field("autoincrement", &self.autoincrement)
+            .field("comment", &self.comment)
+            .field("unique", &self.unique)
+            .field("foreign_key", &self.foreign_key)
+            .field("length", &self.length)
+            .field("quote", &self.quote)
+            .field("default", &self.default)
+            .field("type_family", &self.type_family)
+            .field("server_default", &self.server_default)
+            .field("server_onupdate", &self.server_onupdate)
+            .field("server_generated", &self.server_generated)
+            .field("write_only", &self.write_only)
+            .finish()
+    }
+}
+
+/// Builds [`ColumnOptions`] in constants, returned by
+/// [`ColumnOptions::builder`].
+pub struct ColumnOptionsBuilder<T: 'static> {
+    options: ColumnOptions<T>,
+}
+
+impl<T> ColumnOptionsBuilder<T> {
+    pub const fn primary_key(mut self) -> Self {
+        self.options.primary_key = true;
+        self
+    }
+
+    pub const fn autoincrement(mut self) -> Self {
+        self.options.autoincrement = true;
+        self
+    }
+
+    pub const fn unique(mut self) -> Self {
+        self.options.unique = true;
+        self
+    }
+
+    /// Quotes the name even when not required.
+    pub const fn quote(mut self) -> Self {
+        self.options.quote = true;
+        self
+    }
+
+    /// The referenced column, as `table.column`.
+    pub const fn foreign_key(mut self, reference: &'static str) -> Self {
+        self.options.foreign_key = Some(reference);
+        self
+    }
+
+    pub const fn comment(mut self, comment: &'static str) -> Self {
+        self.options.comment = Some(comment);
+        self
+    }
+
+    pub const fn length(mut self, length: usize) -> Self {
+        self.options.length = Some(length);
+        self
+    }
+
+    pub const fn type_family(mut self, type_family: TypeFamily) -> Self {
+        self.options.type_family = Some(type_family);
+        self
+    }
+
+    pub const fn server_default(mut self, sql: &'static str) -> Self {
+        self.options.server_default = Some(sql);
+        self
+    }
+
+    pub const fn server_onupdate(mut self, sql: &'static str) -> Self {
+        self.options.server_onupdate = Some(sql);
+        self
+    }
+
+    /// The database sets the column when an insert leaves it out, as for
+    /// autoincremented columns and those with a server default.
+    pub const fn server_generated(mut self) -> Self {
+        self.options.server_generated = true;
+        self
+    }
+
+    /// The column is left out of the columns selected by default and its
+    /// values are redacted from the logs, for secrets.
+    pub const fn write_only(mut self) -> Self {
+        self.options.write_only = true;
+        self
+    }
+
+    /// The client side default as bound to the column, the default of the
+    /// [`column`](ColumnOptions::column). Set along with
+    /// [`default`](Self::default) by `#[derive(Entity)]`.
+    pub const fn bound_default(mut self, value: fn() -> Value<'static>) -> Self {
+        self.options.bound_default = Some(value);
+        self
+    }
+
+    pub const fn default(mut self, default: DefaultValue<T>) -> Self {
+        self.options.default = Some(default);
+        self
+    }
+
+    pub const fn build(self) -> ColumnOptions<T> {
+        self.options
+    }
+}
+
+/*
+impl<'a> ::xiayu::prelude::Selectable<'a> for #ident {
+    fn select<C: AsRef<&[Column]>>(columns: C) {}
+    //
+}
+*/
+
+pub struct Many<T>
+where
+    T: Entity,
+{
+    _phantom: PhantomData<T>,
+}
+
+pub struct Relationship<T>
+where
+    T: Entity,
+{
+    _phantom: PhantomData<T>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DefaultValue;
+    use crate::ast::{self, Value};
+    use crate::prelude::*;
+
+    #[derive(Debug, Entity)]
+    #[tablename = "cats"]
+    struct Cat {
+        #[column(primary_key, autoincrement)]
+        id: i64,
+        #[column(default = "purr")]
+        sound: String,
+        #[column(default = 9)]
+        lives: i32,
+        #[column(server_generated)]
+        tag: String,
+        #[column(server_default = "'tabby'")]
+        coat: String,
+        #[column(server_onupdate = "CURRENT_TIMESTAMP")]
+        fed_at: String,
+        name: String,
+    }
+
+    #[test]
+    fn columns_carry_their_defaults() {
+        assert_eq!(Some(Value::from("purr")), Cat::sound.column().default.and_then(|d| d.value()));
+        assert_eq!(Some(Value::from(9)), Cat::lives.column().default.and_then(|d| d.value()));
+
+        assert_eq!(Some(ast::DefaultValue::Generated), Cat::id.column().default);
+        assert_eq!(Some(ast::DefaultValue::Generated), Cat::tag.column().default);
+        assert_eq!(Some(ast::DefaultValue::Generated), Cat::coat.column().default);
+        assert_eq!(None, Cat::name.column().default);
+    }
+
+    #[test]
+    fn columns_carry_their_server_onupdate() {
+        assert_eq!(Some("CURRENT_TIMESTAMP"), Cat::fed_at.server_onupdate());
+        assert_eq!(None, Cat::name.server_onupdate());
+    }
+
+    #[test]
+    fn default_value_from_fn() {
+        let default: DefaultValue<String> = DefaultValue::Fn(|| String::from("pending"));
+        assert_eq!("pending", default.get());
+        assert_eq!("pending", default.clone().get());
+        assert!(format!("{:?}", default).starts_with("Fn("));
+    }
+
+    #[test]
+    fn default_value_from_value() {
+        let default = DefaultValue::Value(&[1, 2]);
+        assert_eq!([1, 2], default.get());
+        assert_eq!([1, 2], default.get());
+        assert_eq!("Value(_)", format!("{:?}", default));
+    }
+
+    /// Not `Clone`.
+    struct Label(String);
+
+    const BYTES: ColumnOptions<Vec<u8>> = ColumnOptions::builder("bytes", "labels")
+        .default(DefaultValue::Fn(Vec::new))
+        .build();
+    const TEXT: ColumnOptions<String> = ColumnOptions::builder("text", "labels")
+        .default(DefaultValue::Fn(String::new))
+        .build();
+    const LABEL: ColumnOptions<Label> = ColumnOptions::builder("label", "labels")
+        .default(DefaultValue::Fn(|| Label("new".to_string())))
+        .build();
+
+    const _: fn() = || {
+        fn clone<T: Clone>() {}
+        clone::<ColumnOptions<Vec<u8>>>();
+        clone::<ColumnOptions<String>>();
+        clone::<ColumnOptions<Label>>();
+    };
+
+    #[test]
+    fn columns_of_any_type_are_clone() {
+        assert_eq!(BYTES, BYTES.clone());
+        assert_eq!(Some(String::new()), TEXT.clone().default_value());
+
+        let label = LABEL.clone();
+        assert_eq!("label", label.c().name);
+        match label.default {
+            Some(DefaultValue::Fn(new)) => assert_eq!("new", new().0),
+            _ => panic!("the default is lost"),
+        }
+    }
+}
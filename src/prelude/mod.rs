@@ -0,0 +1,19 @@
+//! Everything needed to define entities and query them, in one import.
+//!
+//! The prelude is split by concern, import only what is needed:
+//!
+//! - [`prelude::ast`](self::ast), the query building blocks.
+//! - [`prelude::entity`](self::entity), entities, their schemas and executions.
+//!
+//! `xiayu::prelude::*` still brings in both of them. The deprecated shims are
+//! left out, in [`prelude::compat`](self::compat).
+pub mod ast;
+pub mod compat;
+pub mod entity;
+
+pub use self::ast::*;
+pub use self::entity::*;
+// Both halves have a `DefaultValue`, the column defaults of entities win, the
+// one of the query ast stays at `prelude::ast::DefaultValue`.
+pub use self::entity::DefaultValue;
+pub use crate::Result;
@@ -0,0 +1,330 @@
+//! A collection of entity schemas, for tools working with all entities of an
+//! application at once, e.g. a migration diff.
+//!
+//! ```rust
+//! # use entities::{Cat, Dog};
+//! # use xiayu::registry;
+//! let registry = registry::collect!(Cat, Dog);
+//!
+//! assert_eq!(2, registry.len());
+//! assert!(registry.get("dogs").is_some());
+//! ```
+//!
+//! With the `register` feature, entities deriving with `#[entity(register)]`
+//! are collected from every crate linked in, see [`registered`].
+//! [`create_all`](Registry::create_all) creates the missing tables of a
+//! registry, referenced tables first, and
+//! [`validate_all`](Registry::validate_all) checks the existing ones.
+use std::sync::{Arc, RwLock};
+
+use crate::ast::{AlterTable, TableDefinition};
+use crate::comment::Comment;
+use crate::databases::{Executioner, HasVisitor, PreparedQuery};
+use crate::error::{Error, ErrorKind, Name};
+use crate::prelude::Entity;
+use crate::schema::EntitySchema;
+use crate::schema_diff::{self, Introspect};
+use crate::visitors::Visitor;
+
+static DEFAULT_REGISTRY: RwLock<Option<Arc<Registry>>> = RwLock::new(None);
+
+/// Makes the schemas of `registry` known to the visitors, which expand
+/// [`asterisk_except`](crate::ast::asterisk_except) into the columns of the
+/// registered tables. Replaces the registry set before.
+pub fn set_default_registry(registry: Registry) {
+    *DEFAULT_REGISTRY.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(registry));
+}
+
+/// Forgets the registry set with [`set_default_registry`].
+pub fn clear_default_registry() {
+    *DEFAULT_REGISTRY.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// The schema of `tablename` in the default registry.
+pub(crate) fn default_schema(tablename: &str) -> Option<EntitySchema> {
+    let registry = DEFAULT_REGISTRY.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    registry.as_ref().and_then(|registry| registry.get(tablename)).copied()
+}
+
+/// An entity registered with `#[entity(register)]`.
+#[cfg(feature = "register")]
+#[doc(hidden)]
+pub struct Registration {
+    pub schema: fn() -> EntitySchema,
+}
+
+#[cfg(feature = "register")]
+inventory::collect!(Registration);
+
+#[cfg(feature = "register")]
+#[doc(hidden)]
+pub use inventory;
+
+#[cfg(feature = "register")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_entity {
+    ($entity:ty) => {
+        $crate::registry::inventory::submit! {
+            $crate::registry::Registration {
+                schema: <$entity as $crate::prelude::Entity>::schema,
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "register"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_entity {
+    ($entity:ty) => {
+        ::std::compile_error!("`#[entity(register)]` needs the `register` feature of xiayu");
+    };
+}
+
+/// The entities registered with `#[entity(register)]`, by the name of their
+/// tables.
+///
+/// ```rust,ignore
+/// #[derive(Entity)]
+/// #[entity(register)]
+/// #[tablename = "users"]
+/// struct User {
+///     #[column(primary_key)]
+///     id: i64,
+/// }
+///
+/// let created = xiayu::registry::create_all(&pool).await?;
+/// ```
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub fn registered() -> Registry {
+    let mut schemas: Vec<EntitySchema> = inventory::iter::<Registration>
+        .into_iter()
+        .map(|registration| (registration.schema)())
+        .collect();
+    // Collected in no particular order.
+    schemas.sort_by_key(|schema| schema.tablename);
+    schemas.dedup();
+    Registry { schemas }
+}
+
+/// Creates the missing tables of the [`registered`] entities, see
+/// [`Registry::create_all`].
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub async fn create_all<DB>(pool: &sqlx::Pool<DB>) -> crate::Result<Vec<&'static str>>
+where
+    DB: Introspect + for<'v> HasVisitor<'v>,
+    for<'c> &'c sqlx::Pool<DB>: Executioner<'c, DB>,
+{
+    registered().create_all(pool).await
+}
+
+/// Checks the tables of the [`registered`] entities, see
+/// [`Registry::validate_all`].
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub async fn validate_all<DB>(pool: &sqlx::Pool<DB>) -> crate::Result<()>
+where
+    DB: Introspect + for<'v> HasVisitor<'v>,
+{
+    registered().validate_all(pool).await
+}
+
+/// Schemas of a set of entities, in the order of registration.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    schemas: Vec<EntitySchema>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the schema of `E`, registering an entity twice has no effect.
+    pub fn register<E: Entity>(&mut self) -> &mut Self {
+        let schema = E::schema();
+        if !self.schemas.contains(&schema) {
+            self.schemas.push(schema);
+        }
+        self
+    }
+
+    /// Finds a schema by the name of its table.
+    pub fn get(&self, tablename: &str) -> Option<&EntitySchema> {
+        self.schemas
+            .iter()
+            .find(|schema| schema.tablename == tablename)
+    }
+
+    pub fn schemas(&self) -> &[EntitySchema] {
+        &self.schemas
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, EntitySchema> {
+        self.schemas.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    /// The schemas ordered so that every table comes after the tables its
+    /// foreign keys reference, the order of registration otherwise. Tables
+    /// referencing themselves or tables missing from the registry are fine,
+    /// tables referencing each other are a
+    /// [`ForeignKeyCycle`](ErrorKind::ForeignKeyCycle).
+    ///
+    /// ```rust
+    /// # use xiayu::{prelude::*, registry};
+    /// #[derive(Entity)]
+    /// #[tablename = "posts"]
+    /// struct Post {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    ///     #[column(foreign_key = "users.id")]
+    ///     user_id: i64,
+    /// }
+    ///
+    /// #[derive(Entity)]
+    /// #[tablename = "users"]
+    /// struct User {
+    ///     #[column(primary_key)]
+    ///     id: i64,
+    /// }
+    ///
+    /// # fn main() -> xiayu::Result<()> {
+    /// let registry = registry::collect!(Post, User);
+    /// let ordered = registry.in_dependency_order()?;
+    ///
+    /// assert_eq!(vec!["users", "posts"], ordered.iter().map(|s| s.tablename).collect::<Vec<_>>());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn in_dependency_order(&self) -> crate::Result<Vec<EntitySchema>> {
+        let mut pending: Vec<&EntitySchema> = self.schemas.iter().collect();
+        let mut ordered: Vec<EntitySchema> = Vec::with_capacity(pending.len());
+
+        while !pending.is_empty() {
+            let ready = pending.iter().position(|schema| {
+                schema.references().all(|table| {
+                    table == schema.tablename
+                        || self.get(table).is_none()
+                        || ordered.iter().any(|created| created.tablename == table)
+                })
+            });
+
+            match ready {
+                Some(position) => ordered.push(*pending.remove(position)),
+                None => {
+                    let tables = pending.iter().map(|schema| schema.tablename.to_string()).collect();
+                    return Err(Error::builder(ErrorKind::ForeignKeyCycle { tables }).build());
+                }
+            }
+        }
+
+        Ok(ordered)
+    }
+
+    /// Creates the tables missing from the database, with their indexes,
+    /// in [dependency order](Self::in_dependency_order). Returns the names
+    /// of the tables created, the existing ones are left as they are.
+    ///
+    /// ```rust,no_run
+    /// # use entities::{Cat, Dog};
+    /// # use xiayu::registry;
+    /// # async fn run(pool: sqlx::SqlitePool) -> xiayu::Result<()> {
+    /// let created = registry::collect!(Cat, Dog).create_all(&pool).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_all<DB>(&self, pool: &sqlx::Pool<DB>) -> crate::Result<Vec<&'static str>>
+    where
+        DB: Introspect + for<'v> HasVisitor<'v>,
+        for<'c> &'c sqlx::Pool<DB>: Executioner<'c, DB>,
+    {
+        let mut created = Vec::new();
+        for schema in self.in_dependency_order()? {
+            // Not holding the connection afterwards, the pool may have no
+            // other.
+            let exists = {
+                let mut conn = pool.acquire().await?;
+                schema_diff::table_exists::<DB>(&mut conn, schema.tablename).await?
+            };
+            if exists {
+                continue;
+            }
+
+            let create = AlterTable::create_table(schema.table(), TableDefinition::from(&schema));
+            PreparedQuery::<DB>::build(create, false, &Comment::default())?.execute(pool).await?;
+            for index in schema.indexes {
+                let create = AlterTable::create_index(schema.table(), index.name, index.columns.iter().copied(), index.unique);
+                PreparedQuery::<DB>::build(create, false, &Comment::default())?.execute(pool).await?;
+            }
+            created.push(schema.tablename);
+        }
+        Ok(created)
+    }
+
+    /// Checks that the table of every schema exists and matches it, failing
+    /// with [`TableDoesNotExist`](ErrorKind::TableDoesNotExist) or
+    /// [`TableMismatch`](ErrorKind::TableMismatch) for the first one which
+    /// does not. See [`schema_diff::diff`] for what is compared.
+    pub async fn validate_all<DB>(&self, pool: &sqlx::Pool<DB>) -> crate::Result<()>
+    where
+        DB: Introspect + for<'v> HasVisitor<'v>,
+    {
+        let mut conn = pool.acquire().await?;
+        for schema in self.schemas.iter() {
+            let current = schema_diff::introspect::<DB>(&mut conn, schema.tablename).await?;
+            let alters = schema_diff::diff(&current, schema);
+            if alters.is_empty() {
+                continue;
+            }
+
+            // An alter the database can not run, e.g. one rebuilding the
+            // table, is told by its error.
+            let alters = alters
+                .into_iter()
+                .map(|alter| match <DB as HasVisitor<'static>>::Visitor::build(alter) {
+                    Ok((sql, _)) => sql,
+                    Err(err) => err.to_string(),
+                })
+                .collect();
+            return Err(Error::builder(ErrorKind::TableMismatch {
+                table: Name::available(schema.tablename),
+                alters,
+            })
+            .build());
+        }
+        Ok(())
+    }
+}
+
+impl<'r> IntoIterator for &'r Registry {
+    type Item = &'r EntitySchema;
+    type IntoIter = std::slice::Iter<'r, EntitySchema>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __registry_collect {
+    ($($entity:ty),* $(,)?) => {{
+        let mut registry = $crate::registry::Registry::new();
+        $(registry.register::<$entity>();)*
+        registry
+    }};
+}
+
+/// Collects the schemas of the given entities into a [`Registry`].
+pub use crate::__registry_collect as collect;
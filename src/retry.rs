@@ -0,0 +1,243 @@
+//! Running transactions again when they fail because of concurrent ones.
+//!
+//! Serialization failures and deadlocks, see
+//! [`Error::is_retryable`](crate::error::Error::is_retryable), are expected
+//! under contention: the database aborts one of the transactions, which
+//! succeeds once run again. [`with_retries`] runs an operation until it
+//! succeeds, fails with another error or exhausts the attempts of its
+//! [`RetryPolicy`], waiting a little longer before each attempt.
+//!
+//! ```rust
+//! # use xiayu::prelude::*;
+//! # use xiayu::retry::{with_retries, RetryPolicy};
+//! #[derive(Debug, Entity)]
+//! #[tablename = "accounts"]
+//! struct Account {
+//!     #[column(primary_key)]
+//!     id: i64,
+//!     balance: i64,
+//! }
+//!
+//! # async fn run(pool: &sqlx::SqlitePool) -> xiayu::Result<()> {
+//! let policy = RetryPolicy::new(5);
+//! let account = with_retries(&policy, || async move { Account::get(1).conn(pool).await }).await?;
+//! # Ok(())
+//! # }
+//! # fn main() {}
+//! ```
+//!
+//! [`transaction`] runs a whole transaction again, rolling back the failed
+//! attempts.
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use futures_core::future::BoxFuture;
+
+use crate::error::Error;
+
+/// How many times an operation is attempted, and how long to wait between
+/// attempts.
+///
+/// The wait doubles after each attempt, from the initial backoff up to the
+/// maximum one. With jitter, a random part of up to half the wait is left
+/// out, so that the transactions which failed together are not retried
+/// together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl RetryPolicy {
+    /// Attempts an operation up to `max_attempts` times, waiting from 10
+    /// milliseconds up to a second between attempts, with jitter.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+            jitter: true,
+        }
+    }
+
+    /// A policy attempting operations once.
+    pub fn never() -> Self {
+        Self::new(1)
+    }
+
+    /// Waits `initial` before the second attempt, doubling up to `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Whether part of the wait is random, enabled by default.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// The wait before attempting again once `attempt`, counting from one,
+    /// failed with `error`. `None` when the error is not retryable or no
+    /// attempts are left.
+    pub fn retry_after(&self, attempt: u32, error: &Error) -> Option<Duration> {
+        if attempt >= self.max_attempts || !error.is_retryable() {
+            return None;
+        }
+
+        let backoff = self
+            .initial_backoff
+            .checked_mul(1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        if !self.jitter {
+            return Some(backoff);
+        }
+
+        let random = RandomState::new().hash_one((attempt, Instant::now())) % 1024;
+        let random = (backoff / 2).as_nanos() as u64 * random / 1024;
+        Some(backoff - Duration::from_nanos(random))
+    }
+}
+
+/// Runs `operation` until it succeeds, fails with an error which is not
+/// retryable, or the attempts of `policy` are exhausted, failing with the
+/// last error.
+pub async fn with_retries<T, F, Fut>(policy: &RetryPolicy, mut operation: F) -> crate::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = crate::Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        let error = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        match policy.retry_after(attempt, &error) {
+            Some(wait) => {
+                tracing::debug!(attempt, ?wait, %error, "retrying");
+                Delay::new(wait).await;
+                attempt += 1;
+            }
+            None => return Err(error),
+        }
+    }
+}
+
+/// Runs `body` in a transaction of `pool`, committed when it succeeds and
+/// rolled back when it fails. With a `policy`, the transaction is run again
+/// while it fails with a retryable error, see [`with_retries`].
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// # use xiayu::retry::{transaction, RetryPolicy};
+/// # #[derive(Debug, Entity)]
+/// # #[tablename = "accounts"]
+/// # struct Account {
+/// #     #[column(primary_key)]
+/// #     id: i64,
+/// #     balance: i64,
+/// # }
+/// # async fn run(pool: &sqlx::SqlitePool) -> xiayu::Result<()> {
+/// let policy = RetryPolicy::default();
+/// let balance = transaction(pool, Some(&policy), |tx| {
+///     Box::pin(async move {
+///         let mut account = Account::get(1).conn(&mut *tx).await?;
+///         account.balance -= 10;
+///         account.save().conn(&mut *tx).await?;
+///         Ok(account.balance)
+///     })
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+pub async fn transaction<DB, T, F>(pool: &sqlx::Pool<DB>, policy: Option<&RetryPolicy>, mut body: F) -> crate::Result<T>
+where
+    DB: sqlx::Database,
+    F: for<'t> FnMut(&'t mut sqlx::Transaction<'static, DB>) -> BoxFuture<'t, crate::Result<T>>,
+{
+    let never = RetryPolicy::never();
+    let policy = policy.unwrap_or(&never);
+    let mut attempt = 1;
+    loop {
+        let mut tx = pool.begin().await?;
+        let error = match body(&mut tx).await {
+            Ok(value) => match tx.commit().await {
+                Ok(()) => return Ok(value),
+                Err(error) => Error::from(error),
+            },
+            Err(error) => {
+                tx.rollback().await?;
+                error
+            }
+        };
+        match policy.retry_after(attempt, &error) {
+            Some(wait) => {
+                tracing::debug!(attempt, ?wait, %error, "retrying the transaction");
+                Delay::new(wait).await;
+                attempt += 1;
+            }
+            None => return Err(error),
+        }
+    }
+}
+
+/// Waits on a thread of its own, working with any runtime. Retries are
+/// rare enough for the thread not to matter.
+pub(crate) struct Delay {
+    deadline: Instant,
+    waker: Option<Arc<Mutex<Waker>>>,
+}
+
+impl Delay {
+    pub(crate) fn new(wait: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + wait,
+            waker: None,
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let deadline = self.deadline;
+        if Instant::now() >= deadline {
+            return Poll::Ready(());
+        }
+        match &self.waker {
+            Some(waker) => *waker.lock().unwrap() = cx.waker().clone(),
+            None => {
+                let waker = Arc::new(Mutex::new(cx.waker().clone()));
+                self.waker = Some(waker.clone());
+                std::thread::spawn(move || {
+                    std::thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                    waker.lock().unwrap().wake_by_ref();
+                });
+            }
+        }
+        Poll::Pending
+    }
+}
@@ -0,0 +1,201 @@
+//! Runtime inspectable schema of entities.
+//!
+//! Everything known about an entity at derive time is kept in an
+//! [`EntitySchema`], returned by [`Entity::schema`](crate::prelude::Entity::schema).
+//! Meant for external tooling, e.g. schema diffs and admin interfaces.
+use crate::ast::{Table, TableType, TypeDataLength, TypeFamily};
+use std::borrow::Cow;
+
+/// The schema of an entity's table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntitySchema {
+    pub tablename: &'static str,
+    /// The database, or schema, the table lives in.
+    pub database: Option<&'static str>,
+    pub columns: &'static [ColumnSchema],
+    pub indexes: &'static [IndexSchema],
+}
+
+/// The schema of a single column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnSchema {
+    pub name: &'static str,
+    /// The rust type of the field, as written in the entity.
+    pub rust_type: &'static str,
+    pub type_family: Option<TypeFamily>,
+    /// True if the field is an `Option`.
+    pub nullable: bool,
+    pub primary_key: bool,
+    pub autoincrement: bool,
+    pub unique: bool,
+    pub length: Option<usize>,
+    /// The referenced column, `table.column`.
+    pub foreign_key: Option<&'static str>,
+    /// True if a default value is provided in rust.
+    pub has_default: bool,
+    /// True if a default value is generated by the database.
+    pub has_server_default: bool,
+    /// The SQL expression of the default value generated by the database.
+    pub server_default: Option<&'static str>,
+    pub comment: Option<&'static str>,
+}
+
+/// The schema of an index.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexSchema {
+    pub name: &'static str,
+    pub columns: &'static [&'static str],
+    pub unique: bool,
+}
+
+impl EntitySchema {
+    /// Finds a column by its name in the database.
+    pub fn column(&self, name: &str) -> Option<&'static ColumnSchema> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// The primary key columns.
+    pub fn primary_key(&self) -> impl Iterator<Item = &'static ColumnSchema> {
+        self.columns.iter().filter(|column| column.primary_key)
+    }
+
+    /// The table of the entity.
+    pub fn table(&self) -> Table<'static> {
+        Table {
+            typ: TableType::Table(Cow::Borrowed(self.tablename)),
+            alias: None,
+            database: self.database.map(Cow::Borrowed),
+            index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
+        }
+    }
+
+    /// The tables referenced by the foreign keys of the entity, itself
+    /// included when it references itself.
+    pub fn references(&self) -> impl Iterator<Item = &'static str> {
+        self.columns
+            .iter()
+            .filter_map(|column| column.references())
+            .map(|(table, _)| table)
+    }
+
+    /// The hash of the layout of the table, see
+    /// [`Entity::schema_hash`](crate::prelude::Entity::schema_hash).
+    pub const fn hash(&self) -> u64 {
+        SchemaHasher::new(self.tablename).columns("", self.columns).finish()
+    }
+}
+
+impl ColumnSchema {
+    /// The table and the column referenced by the foreign key.
+    pub fn references(&self) -> Option<(&'static str, &'static str)> {
+        self.foreign_key?.rsplit_once('.')
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hashes the layout of a table at compile time: its name and the name,
+/// type, nullability and primary key membership of each column. The order of
+/// the columns does not matter, moving a field does not change the table.
+///
+/// FNV-1a, the same on every platform and for every build.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaHasher {
+    table: u64,
+    columns: u64,
+}
+
+impl SchemaHasher {
+    pub const fn new(tablename: &str) -> Self {
+        Self {
+            table: write_bytes(FNV_OFFSET, tablename.as_bytes()),
+            columns: 0,
+        }
+    }
+
+    /// Adds `columns`, their names starting with `prefix`.
+    pub const fn columns(mut self, prefix: &str, columns: &[ColumnSchema]) -> Self {
+        let mut i = 0;
+        while i < columns.len() {
+            let column = &columns[i];
+            let mut hash = write_bytes(FNV_OFFSET, prefix.as_bytes());
+            hash = write_bytes(hash, column.name.as_bytes());
+            // Ends the name, `ab` + `c` is not `a` + `bc`.
+            hash = write_byte(hash, 0xff);
+            hash = write_type_family(hash, column.type_family);
+            hash = write_byte(hash, column.nullable as u8);
+            hash = write_byte(hash, column.primary_key as u8);
+            // Summed up, in any order.
+            self.columns = self.columns.wrapping_add(mix(hash));
+            i += 1;
+        }
+        self
+    }
+
+    pub const fn finish(self) -> u64 {
+        let bytes = self.columns.to_le_bytes();
+        let mut hash = self.table;
+        let mut i = 0;
+        while i < bytes.len() {
+            hash = write_byte(hash, bytes[i]);
+            i += 1;
+        }
+        hash
+    }
+}
+
+const fn write_byte(hash: u64, byte: u8) -> u64 {
+    (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+}
+
+const fn write_bytes(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = write_byte(hash, bytes[i]);
+        i += 1;
+    }
+    hash
+}
+
+const fn write_length(hash: u64, length: Option<TypeDataLength>) -> u64 {
+    match length {
+        None => write_byte(hash, 0),
+        Some(TypeDataLength::Maximum) => write_byte(hash, 1),
+        Some(TypeDataLength::Constant(length)) => {
+            let [high, low] = length.to_be_bytes();
+            write_byte(write_byte(write_byte(hash, 2), high), low)
+        }
+    }
+}
+
+const fn write_type_family(hash: u64, type_family: Option<TypeFamily>) -> u64 {
+    match type_family {
+        None => write_byte(hash, 0),
+        Some(TypeFamily::Text(length)) => write_length(write_byte(hash, 1), length),
+        // The width of the integers is left out, as before it was known.
+        Some(TypeFamily::Int | TypeFamily::Int32) => write_byte(hash, 2),
+        Some(TypeFamily::Float) => write_byte(hash, 3),
+        Some(TypeFamily::Double) => write_byte(hash, 4),
+        Some(TypeFamily::Boolean) => write_byte(hash, 5),
+        Some(TypeFamily::Uuid) => write_byte(hash, 6),
+        Some(TypeFamily::DateTime) => write_byte(hash, 7),
+        Some(TypeFamily::Decimal(None)) => write_byte(write_byte(hash, 8), 0),
+        Some(TypeFamily::Decimal(Some((precision, scale)))) => {
+            write_byte(write_byte(write_byte(write_byte(hash, 8), 1), precision), scale)
+        }
+        Some(TypeFamily::Bytes(length)) => write_length(write_byte(hash, 9), length),
+        Some(TypeFamily::Json) => write_byte(hash, 10),
+    }
+}
+
+/// Spreads the bits of a column hash before it is summed up.
+const fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
@@ -0,0 +1,538 @@
+//! Differences between a table in the database and the schema of its entity,
+//! the seed of migrations.
+//!
+//! [`introspect`] reads the current definition of a table from the catalog
+//! of the database, [`diff`] compares it to an [`EntitySchema`] and returns
+//! the [`AlterTable`] statements bringing the table up to date, rendered by
+//! the visitor of the database.
+//!
+//! ```rust
+//! # use entities::Dog;
+//! # use xiayu::prelude::*;
+//! # use xiayu::{schema_diff, visitors::{Sqlite, Visitor}};
+//! # async fn run(conn: &mut sqlx::SqliteConnection) -> xiayu::Result<()> {
+//! let current = schema_diff::introspect::<sqlx::Sqlite>(conn, "dogs").await?;
+//!
+//! for alter in schema_diff::diff(&current, &Dog::schema()) {
+//!     let (sql, _) = Sqlite::build(alter)?;
+//!     sqlx::query(&sql).execute(&mut *conn).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Renames are not detected, a renamed field shows up as a dropped and an
+//! added column. See
+//! [`AlterOperation::is_destructive`](crate::ast::AlterOperation::is_destructive).
+//!
+//! [`verify_schema`] tells at startup whether an entity still has the layout
+//! recorded by the last migration, comparing its
+//! [`schema_hash`](crate::prelude::Entity::schema_hash) to the one in the
+//! `_xiayu_schema` table.
+use crate::ast::{AlterTable, Column, ColumnDefinition, Comparable, Delete, Insert, Select, TableDefinition, TypeDataLength, TypeFamily};
+use crate::comment::Comment;
+use crate::databases::{Executioner, HasVisitor, PreparedQuery, Scalar};
+use crate::error::{Error, ErrorKind, Name};
+use crate::prelude::Entity;
+use crate::schema::{ColumnSchema, EntitySchema};
+use async_trait::async_trait;
+
+/// A table as found in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableSchema {
+    pub tablename: String,
+    pub columns: Vec<TableColumn>,
+    /// Indexes besides the primary key.
+    pub indexes: Vec<TableIndex>,
+}
+
+/// A column as found in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableColumn {
+    pub name: String,
+    pub nullable: bool,
+    /// The type, as named by the database.
+    pub data_type: String,
+}
+
+/// An index as found in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableIndex {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+impl TableSchema {
+    /// Finds a column by its name.
+    pub fn column(&self, name: &str) -> Option<&TableColumn> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Finds an index by its name.
+    pub fn index(&self, name: &str) -> Option<&TableIndex> {
+        self.indexes.iter().find(|index| index.name == name)
+    }
+}
+
+/// Databases whose tables can be read from their catalog.
+#[async_trait]
+pub trait Introspect: sqlx::Database {
+    /// Reads the definition of `tablename`, failing with
+    /// [`ErrorKind::TableDoesNotExist`] if there is no such table.
+    async fn introspect(conn: &mut Self::Connection, tablename: &str)
+        -> crate::Result<TableSchema>;
+}
+
+/// Reads the definition of `tablename` from the database behind `conn`.
+pub async fn introspect<DB>(conn: &mut DB::Connection, tablename: &str) -> crate::Result<TableSchema>
+where
+    DB: Introspect,
+{
+    DB::introspect(conn, tablename).await
+}
+
+/// Whether `tablename` exists in the database behind `conn`.
+pub async fn table_exists<DB>(conn: &mut DB::Connection, tablename: &str) -> crate::Result<bool>
+where
+    DB: Introspect,
+{
+    match DB::introspect(conn, tablename).await {
+        Ok(_) => Ok(true),
+        Err(error) if matches!(error.kind(), ErrorKind::TableDoesNotExist { .. }) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// The statements altering `current` to match `entity`: added columns,
+/// changed nullability, dropped columns and missing indexes, in this order.
+/// An empty result means the table is up to date.
+///
+/// The nullability of primary keys is left alone, an `Option` key is still
+/// `NOT NULL` in the database.
+pub fn diff(current: &TableSchema, entity: &EntitySchema) -> Vec<AlterTable<'static>> {
+    let table = || entity.table();
+
+    let mut alters = Vec::new();
+
+    for column in entity.columns {
+        if current.column(column.name).is_none() {
+            alters.push(AlterTable::add_column(table(), ColumnDefinition::from(column)));
+        }
+    }
+
+    for column in entity.columns.iter().filter(|column| !column.primary_key) {
+        match current.column(column.name) {
+            Some(existing) if existing.nullable != column.nullable => {
+                alters.push(AlterTable::set_nullable(table(), ColumnDefinition::from(column)));
+            }
+            _ => (),
+        }
+    }
+
+    for existing in current.columns.iter() {
+        if entity.column(&existing.name).is_none() {
+            alters.push(AlterTable::drop_column(table(), existing.name.clone()));
+        }
+    }
+
+    for index in entity.indexes {
+        if current.index(index.name).is_none() {
+            alters.push(AlterTable::create_index(
+                table(),
+                index.name,
+                index.columns.iter().copied(),
+                index.unique,
+            ));
+        }
+    }
+
+    alters
+}
+
+const fn schema_column(name: &'static str, type_family: TypeFamily, primary_key: bool) -> ColumnSchema {
+    ColumnSchema {
+        name,
+        rust_type: "",
+        type_family: Some(type_family),
+        nullable: false,
+        primary_key,
+        autoincrement: false,
+        unique: false,
+        length: None,
+        foreign_key: None,
+        has_default: false,
+        has_server_default: false,
+        server_default: None,
+        comment: None,
+    }
+}
+
+/// The table recording the schema hash of each entity, by the name of its
+/// table.
+pub const SCHEMA_TABLE: EntitySchema = EntitySchema {
+    tablename: "_xiayu_schema",
+    database: None,
+    columns: &[
+        schema_column("entity", TypeFamily::Text(Some(TypeDataLength::Constant(255))), true),
+        schema_column("schema_hash", TypeFamily::Int, false),
+    ],
+    indexes: &[],
+};
+
+/// Compares the [`schema_hash`](Entity::schema_hash) of `E` to the one
+/// recorded in [`SCHEMA_TABLE`], failing with
+/// [`ErrorKind::SchemaMismatch`] if they differ. The first time, the hash
+/// is recorded, and the table created if missing.
+///
+/// ```rust,no_run
+/// # use entities::Dog;
+/// # use xiayu::schema_diff;
+/// # async fn run(pool: sqlx::SqlitePool) -> xiayu::Result<()> {
+/// // After migrating:
+/// schema_diff::record_schema::<Dog, _>(&pool).await?;
+/// // At startup:
+/// schema_diff::verify_schema::<Dog, _>(&pool).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn verify_schema<E, DB>(pool: &sqlx::Pool<DB>) -> crate::Result<()>
+where
+    E: Entity,
+    DB: Introspect + for<'v> HasVisitor<'v>,
+    for<'c> &'c sqlx::Pool<DB>: Executioner<'c, DB>,
+    Scalar<i64>: for<'r> sqlx::FromRow<'r, DB::Row>,
+{
+    let compiled = E::schema_hash();
+    match recorded_hash(pool, E::tablename()).await? {
+        None => record_hash(pool, E::tablename(), compiled).await,
+        Some(recorded) if recorded == compiled => Ok(()),
+        Some(recorded) => Err(Error::builder(ErrorKind::SchemaMismatch {
+            entity: E::tablename(),
+            recorded,
+            compiled,
+        })
+        .build()),
+    }
+}
+
+/// Records the [`schema_hash`](Entity::schema_hash) of `E` in
+/// [`SCHEMA_TABLE`], replacing the one recorded before. Called once the
+/// table of the entity is migrated.
+pub async fn record_schema<E, DB>(pool: &sqlx::Pool<DB>) -> crate::Result<()>
+where
+    E: Entity,
+    DB: Introspect + for<'v> HasVisitor<'v>,
+    for<'c> &'c sqlx::Pool<DB>: Executioner<'c, DB>,
+    Scalar<i64>: for<'r> sqlx::FromRow<'r, DB::Row>,
+{
+    recorded_hash(pool, E::tablename()).await?;
+    let delete = Delete::from_table(SCHEMA_TABLE.table()).so_that(Column::new("entity").equals(E::tablename()));
+    PreparedQuery::<DB>::build(delete, false, &Comment::default())?.execute(pool).await?;
+    record_hash(pool, E::tablename(), E::schema_hash()).await
+}
+
+/// The hash recorded for `tablename`, creating [`SCHEMA_TABLE`] if missing.
+async fn recorded_hash<DB>(pool: &sqlx::Pool<DB>, tablename: &'static str) -> crate::Result<Option<u64>>
+where
+    DB: Introspect + for<'v> HasVisitor<'v>,
+    for<'c> &'c sqlx::Pool<DB>: Executioner<'c, DB>,
+    Scalar<i64>: for<'r> sqlx::FromRow<'r, DB::Row>,
+{
+    // Not holding the connection afterwards, the pool may have no other.
+    let exists = {
+        let mut conn = pool.acquire().await?;
+        table_exists::<DB>(&mut conn, SCHEMA_TABLE.tablename).await?
+    };
+    if !exists {
+        let create = AlterTable::create_table(SCHEMA_TABLE.table(), TableDefinition::from(&SCHEMA_TABLE));
+        PreparedQuery::<DB>::build(create, false, &Comment::default())?.execute(pool).await?;
+        return Ok(None);
+    }
+
+    let select = Select::from_table(SCHEMA_TABLE.table())
+        .column(Column::new("schema_hash"))
+        .so_that(Column::new("entity").equals(tablename));
+    let recorded: Vec<Scalar<i64>> = PreparedQuery::<DB>::build(select, false, &Comment::default())?
+        .fetch_all(pool)
+        .await?;
+    // Stored as a signed `BIGINT`.
+    Ok(recorded.first().map(|hash| hash.0 as u64))
+}
+
+async fn record_hash<DB>(pool: &sqlx::Pool<DB>, tablename: &'static str, hash: u64) -> crate::Result<()>
+where
+    DB: Introspect + for<'v> HasVisitor<'v>,
+    for<'c> &'c sqlx::Pool<DB>: Executioner<'c, DB>,
+{
+    let insert = Insert::single_into(SCHEMA_TABLE.table())
+        .value(Column::new("entity"), tablename)
+        .value(Column::new("schema_hash"), hash as i64);
+    PreparedQuery::<DB>::build(insert, false, &Comment::default())?.execute(pool).await?;
+    Ok(())
+}
+
+fn table_does_not_exist(tablename: &str) -> Error {
+    Error::builder(ErrorKind::TableDoesNotExist {
+        table: Name::available(tablename),
+    })
+    .build()
+}
+
+/// Groups `(index, unique, column)` rows, ordered by index and position.
+fn group_indexes(rows: impl IntoIterator<Item = (String, bool, String)>) -> Vec<TableIndex> {
+    let mut indexes: Vec<TableIndex> = Vec::new();
+
+    for (name, unique, column) in rows {
+        match indexes.last_mut() {
+            Some(index) if index.name == name => index.columns.push(column),
+            _ => indexes.push(TableIndex {
+                name,
+                columns: vec![column],
+                unique,
+            }),
+        }
+    }
+
+    indexes
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl Introspect for sqlx::Sqlite {
+    async fn introspect(
+        conn: &mut sqlx::SqliteConnection,
+        tablename: &str,
+    ) -> crate::Result<TableSchema> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(r#"SELECT name, "notnull", type FROM pragma_table_info(?1) ORDER BY cid"#)
+            .bind(tablename)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        if rows.is_empty() {
+            return Err(table_does_not_exist(tablename));
+        }
+
+        let mut columns = Vec::with_capacity(rows.len());
+        for row in rows {
+            columns.push(TableColumn {
+                name: row.try_get("name")?,
+                nullable: row.try_get::<i64, _>("notnull")? == 0,
+                data_type: row.try_get("type")?,
+            });
+        }
+
+        // Indexes of the primary key have the `pk` origin.
+        let rows = sqlx::query(
+            r#"SELECT il.name AS name, il."unique" AS is_unique, ii.name AS column_name
+            FROM pragma_index_list(?1) AS il JOIN pragma_index_info(il.name) AS ii
+            WHERE il.origin <> 'pk' ORDER BY il.name, ii.seqno"#,
+        )
+        .bind(tablename)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut indexes = Vec::with_capacity(rows.len());
+        for row in rows {
+            indexes.push((
+                row.try_get("name")?,
+                row.try_get::<i64, _>("is_unique")? != 0,
+                row.try_get("column_name")?,
+            ));
+        }
+
+        Ok(TableSchema {
+            tablename: tablename.to_string(),
+            columns,
+            indexes: group_indexes(indexes),
+        })
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl Introspect for sqlx::Postgres {
+    async fn introspect(
+        conn: &mut sqlx::PgConnection,
+        tablename: &str,
+    ) -> crate::Result<TableSchema> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            "SELECT column_name::text AS name, is_nullable = 'YES' AS nullable, data_type::text AS data_type
+            FROM information_schema.columns
+            WHERE table_schema = current_schema() AND table_name = $1
+            ORDER BY ordinal_position",
+        )
+        .bind(tablename)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if rows.is_empty() {
+            return Err(table_does_not_exist(tablename));
+        }
+
+        let mut columns = Vec::with_capacity(rows.len());
+        for row in rows {
+            columns.push(TableColumn {
+                name: row.try_get("name")?,
+                nullable: row.try_get("nullable")?,
+                data_type: row.try_get("data_type")?,
+            });
+        }
+
+        let rows = sqlx::query(
+            "SELECT i.relname::text AS name, ix.indisunique AS is_unique, a.attname::text AS column_name
+            FROM pg_index ix
+            JOIN pg_class t ON t.oid = ix.indrelid
+            JOIN pg_class i ON i.oid = ix.indexrelid
+            JOIN pg_namespace n ON n.oid = t.relnamespace
+            JOIN LATERAL unnest(ix.indkey) WITH ORDINALITY AS k(attnum, position) ON true
+            JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = k.attnum
+            WHERE t.relname = $1 AND n.nspname = current_schema() AND NOT ix.indisprimary
+            ORDER BY i.relname, k.position",
+        )
+        .bind(tablename)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut indexes = Vec::with_capacity(rows.len());
+        for row in rows {
+            indexes.push((
+                row.try_get("name")?,
+                row.try_get("is_unique")?,
+                row.try_get("column_name")?,
+            ));
+        }
+
+        Ok(TableSchema {
+            tablename: tablename.to_string(),
+            columns,
+            indexes: group_indexes(indexes),
+        })
+    }
+}
+
+#[cfg(feature = "mysql")]
+#[async_trait]
+impl Introspect for sqlx::MySql {
+    async fn introspect(
+        conn: &mut sqlx::MySqlConnection,
+        tablename: &str,
+    ) -> crate::Result<TableSchema> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            "SELECT CAST(column_name AS CHAR) AS name, CAST(is_nullable = 'YES' AS SIGNED) AS nullable,
+            CAST(column_type AS CHAR) AS data_type
+            FROM information_schema.columns
+            WHERE table_schema = DATABASE() AND table_name = ?
+            ORDER BY ordinal_position",
+        )
+        .bind(tablename)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if rows.is_empty() {
+            return Err(table_does_not_exist(tablename));
+        }
+
+        let mut columns = Vec::with_capacity(rows.len());
+        for row in rows {
+            columns.push(TableColumn {
+                name: row.try_get("name")?,
+                nullable: row.try_get::<i64, _>("nullable")? != 0,
+                data_type: row.try_get("data_type")?,
+            });
+        }
+
+        let rows = sqlx::query(
+            "SELECT CAST(index_name AS CHAR) AS name, CAST(non_unique = 0 AS SIGNED) AS is_unique,
+            CAST(column_name AS CHAR) AS column_name
+            FROM information_schema.statistics
+            WHERE table_schema = DATABASE() AND table_name = ? AND index_name <> 'PRIMARY'
+            ORDER BY index_name, seq_in_index",
+        )
+        .bind(tablename)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut indexes = Vec::with_capacity(rows.len());
+        for row in rows {
+            indexes.push((
+                row.try_get("name")?,
+                row.try_get::<i64, _>("is_unique")? != 0,
+                row.try_get("column_name")?,
+            ));
+        }
+
+        Ok(TableSchema {
+            tablename: tablename.to_string(),
+            columns,
+            indexes: group_indexes(indexes),
+        })
+    }
+}
+
+#[cfg(feature = "mssql")]
+#[async_trait]
+impl Introspect for sqlx::Mssql {
+    async fn introspect(
+        conn: &mut sqlx::MssqlConnection,
+        tablename: &str,
+    ) -> crate::Result<TableSchema> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            "SELECT c.name AS name, c.is_nullable AS nullable, t.name AS data_type
+            FROM sys.columns c JOIN sys.types t ON t.user_type_id = c.user_type_id
+            WHERE c.object_id = OBJECT_ID(@p1)
+            ORDER BY c.column_id",
+        )
+        .bind(tablename)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if rows.is_empty() {
+            return Err(table_does_not_exist(tablename));
+        }
+
+        let mut columns = Vec::with_capacity(rows.len());
+        for row in rows {
+            columns.push(TableColumn {
+                name: row.try_get("name")?,
+                nullable: row.try_get("nullable")?,
+                data_type: row.try_get("data_type")?,
+            });
+        }
+
+        let rows = sqlx::query(
+            "SELECT i.name AS name, i.is_unique AS is_unique, c.name AS column_name
+            FROM sys.indexes i
+            JOIN sys.index_columns ic ON ic.object_id = i.object_id AND ic.index_id = i.index_id
+            JOIN sys.columns c ON c.object_id = ic.object_id AND c.column_id = ic.column_id
+            WHERE i.object_id = OBJECT_ID(@p1) AND i.is_primary_key = 0 AND i.type > 0
+            ORDER BY i.name, ic.key_ordinal",
+        )
+        .bind(tablename)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut indexes = Vec::with_capacity(rows.len());
+        for row in rows {
+            indexes.push((
+                row.try_get("name")?,
+                row.try_get("is_unique")?,
+                row.try_get("column_name")?,
+            ));
+        }
+
+        Ok(TableSchema {
+            tablename: tablename.to_string(),
+            columns,
+            indexes: group_indexes(indexes),
+        })
+    }
+}
@@ -0,0 +1,391 @@
+//! Utilities for testing code built on xiayu without a database.
+//!
+//! [`assert_sql!`] checks the SQL and parameters a query compiles to with
+//! every listed visitor:
+//!
+//! ```rust
+//! # use entities::Recipe;
+//! # use xiayu::prelude::*;
+//! # use xiayu::test::assert_sql;
+//! let query = Select::from_table(Recipe::table()).so_that(Recipe::name.equals("pancakes"));
+//!
+//! assert_sql!(
+//!     query,
+//!     sqlite: "SELECT `recipes`.* FROM `recipes` WHERE `recipes`.`name` = ?",
+//!     mysql: "SELECT `recipes`.* FROM `recipes` WHERE `recipes`.`name` = ?";
+//!     params: ["pancakes"],
+//! );
+//! ```
+//!
+//! [`MockExecutioner`] stands in for a connection, recording the queries
+//! given to it and answering selects with canned rows.
+//!
+//! [`fixtures::TestDb`] creates an in-memory SQLite database with the tables
+//! of entities.
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use futures_core::future::BoxFuture;
+use futures_core::stream::{BoxStream, Stream};
+use sqlx::FromRow;
+
+use crate::ast::{Insert, Query, Value};
+use crate::databases::{batch_failed, build_batch, build_validated, BatchReport, ExecResult, Executioner, HasVisitor, InsertingExecution};
+use crate::prelude::{HasPrimaryKey, Writable};
+use crate::visitors::Visitor;
+
+#[cfg(feature = "sqlite")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
+pub mod fixtures;
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_sql {
+    ($query: expr, $($dialect: ident : $sql: expr),+ $(,)?) => {{
+        let query = $crate::ast::Query::from($query);
+        $(
+            let (sql, _) = $crate::test::build::<$crate::__assert_sql_visitor!($dialect)>(query.clone());
+            assert_eq!($sql, sql, "SQL built by {}", stringify!($dialect));
+        )+
+    }};
+    ($query: expr, $($dialect: ident : $sql: expr),+ ; params: [$($param: expr),* $(,)?] $(,)?) => {{
+        let query = $crate::ast::Query::from($query);
+        let expected: Vec<$crate::ast::Value> = vec![$($crate::ast::Value::from($param)),*];
+        $(
+            let (sql, params) = $crate::test::build::<$crate::__assert_sql_visitor!($dialect)>(query.clone());
+            assert_eq!($sql, sql, "SQL built by {}", stringify!($dialect));
+            assert_eq!(expected, params, "parameters built by {}", stringify!($dialect));
+        )+
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_sql_visitor {
+    (sqlite) => {
+        $crate::visitors::Sqlite
+    };
+    (mysql) => {
+        $crate::visitors::Mysql
+    };
+    (postgres) => {
+        $crate::visitors::Postgres
+    };
+    (mssql) => {
+        $crate::visitors::Mssql
+    };
+}
+
+/// Asserts the SQL, and optionally the parameters, a query builds to with
+/// each of the named visitors: `sqlite`, `mysql`, `postgres` or `mssql`.
+pub use crate::__assert_sql as assert_sql;
+
+#[doc(hidden)]
+pub fn build<'a, V: Visitor<'a>>(query: Query<'a>) -> (String, Vec<Value<'a>>) {
+    match V::build(query) {
+        Ok(built) => built,
+        Err(error) => panic!("cannot build the query: {}", error),
+    }
+}
+
+/// A query received by a [`MockExecutioner`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedQuery {
+    pub sql: String,
+    pub parameters: Vec<Value<'static>>,
+}
+
+/// An [`Executioner`] recording compiled queries instead of running them.
+///
+/// Statements succeed with a default result. Queries fetching a row are
+/// answered with the rows pushed by [`push_row`][Self::push_row], in order,
+/// and fail with `RowNotFound` once they run out. Errors pushed by
+/// [`push_error`][Self::push_error] fail the next queries instead.
+///
+/// ```rust
+/// # use xiayu::prelude::*;
+/// # use xiayu::test::MockExecutioner;
+/// #[derive(Debug, Entity)]
+/// #[tablename = "recipes"]
+/// struct Recipe {
+///     #[column(primary_key)]
+///     id: i64,
+///     name: String,
+/// }
+///
+/// # async fn run() -> xiayu::Result<()> {
+/// let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+/// conn.push_row(vec![("id", Value::from(1)), ("name", Value::from("pancakes"))]);
+///
+/// let recipe = Recipe::get(1).conn(&mut conn).await?;
+///
+/// assert_eq!("pancakes", recipe.name);
+/// assert_eq!(1, conn.queries().len());
+/// # Ok(())
+/// # }
+/// # fn main() {}
+/// ```
+pub struct MockExecutioner<DB> {
+    queries: Vec<RecordedQuery>,
+    rows: VecDeque<Vec<(String, Value<'static>)>>,
+    errors: VecDeque<crate::error::Error>,
+    _marker: PhantomData<DB>,
+}
+
+impl<DB> MockExecutioner<DB> {
+    pub fn new() -> Self {
+        Self {
+            queries: Vec::new(),
+            rows: VecDeque::new(),
+            errors: VecDeque::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Queues a row, as column name and value pairs, for the next query
+    /// fetching one.
+    pub fn push_row<I, K, V>(&mut self, row: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<Value<'static>>,
+    {
+        let row = row
+            .into_iter()
+            .map(|(column, value)| (column.into(), value.into()))
+            .collect();
+        self.rows.push_back(row);
+        self
+    }
+
+    /// Queues an error failing the next query, after recording it.
+    pub fn push_error(&mut self, error: impl Into<crate::error::Error>) -> &mut Self {
+        self.errors.push_back(error.into());
+        self
+    }
+
+    /// The queries received so far, in order.
+    pub fn queries(&self) -> &[RecordedQuery] {
+        &self.queries
+    }
+
+    /// The SQL of the queries received so far, in order.
+    pub fn sql(&self) -> Vec<&str> {
+        self.queries.iter().map(|query| query.sql.as_str()).collect()
+    }
+
+    fn record(&mut self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<()> {
+        self.queries.push(RecordedQuery { sql, parameters });
+        match self.errors.pop_front() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<DB> Default for MockExecutioner<DB> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<DB> fmt::Debug for MockExecutioner<DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockExecutioner")
+            .field("queries", &self.queries)
+            .field("rows", &self.rows)
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+/// Databases a [`MockExecutioner`] can stand in for.
+pub trait MockDatabase: sqlx::Database + for<'v> HasVisitor<'v> {
+    /// Turns a canned row into a row of the database, to be decoded by
+    /// [`FromRow`].
+    fn materialize(row: Vec<(String, Value<'static>)>) -> BoxFuture<'static, crate::Result<Self::Row>>;
+}
+
+#[cfg(feature = "sqlite")]
+impl MockDatabase for sqlx::Sqlite {
+    fn materialize(row: Vec<(String, Value<'static>)>) -> BoxFuture<'static, crate::Result<Self::Row>> {
+        use crate::ast::{Aliasable, Expression, Select};
+        use crate::databases::{convert_parameters, Binder};
+        use sqlx::Connection;
+
+        Box::pin(async move {
+            let select = row.into_iter().fold(Select::default(), |select, (column, value)| {
+                select.value(Expression::from(value).alias(column))
+            });
+            let (sql, parameters) = crate::visitors::Sqlite::build(select)?;
+            let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+            let mut query = sqlx::query::<sqlx::Sqlite>(&sql);
+            for parameter in convert_parameters::<sqlx::Sqlite>(parameters, &[])? {
+                query = query.bind_value(parameter);
+            }
+            Ok(query.fetch_one(&mut conn).await?)
+        })
+    }
+}
+
+macro_rules! impl_mock_database_without_rows {
+    ($database: ty) => {
+        impl MockDatabase for $database {
+            fn materialize(_row: Vec<(String, Value<'static>)>) -> BoxFuture<'static, crate::Result<Self::Row>> {
+                Box::pin(async {
+                    let kind = crate::error::ErrorKind::conversion(
+                        "canned rows of a MockExecutioner are only decoded for SQLite",
+                    );
+                    Err(crate::error::Error::builder(kind).build())
+                })
+            }
+        }
+    };
+}
+
+#[cfg(feature = "mysql")]
+impl_mock_database_without_rows!(sqlx::MySql);
+#[cfg(feature = "postgres")]
+impl_mock_database_without_rows!(sqlx::Postgres);
+#[cfg(feature = "mssql")]
+impl_mock_database_without_rows!(sqlx::Mssql);
+
+fn not_executed() -> sqlx::Error {
+    sqlx::Error::Configuration("MockExecutioner does not run sqlx queries".into())
+}
+
+/// A stream failing with its first item.
+struct FailingMany<T>(Option<sqlx::Error>, PhantomData<T>);
+
+impl<T> Unpin for FailingMany<T> {}
+
+impl<T> Stream for FailingMany<T> {
+    type Item = Result<T, sqlx::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.0.take().map(Err))
+    }
+}
+
+// Needed by the bounds of `Executioner`, the mock only answers through it.
+impl<'c, DB: MockDatabase> sqlx::Executor<'c> for &'c mut MockExecutioner<DB> {
+    type Database = DB;
+
+    fn fetch_many<'e, 'q: 'e, E>(
+        self,
+        _query: E,
+    ) -> BoxStream<'e, Result<sqlx::Either<DB::QueryResult, DB::Row>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: sqlx::Execute<'q, DB> + 'q,
+    {
+        Box::pin(FailingMany(Some(not_executed()), PhantomData))
+    }
+
+    fn fetch_optional<'e, 'q: 'e, E>(self, _query: E) -> BoxFuture<'e, Result<Option<DB::Row>, sqlx::Error>>
+    where
+        'c: 'e,
+        E: sqlx::Execute<'q, DB> + 'q,
+    {
+        Box::pin(async { Err(not_executed()) })
+    }
+
+    fn prepare_with<'e, 'q: 'e>(
+        self,
+        _sql: &'q str,
+        _parameters: &'e [DB::TypeInfo],
+    ) -> BoxFuture<'e, Result<<DB as sqlx::database::HasStatement<'q>>::Statement, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        Box::pin(async { Err(not_executed()) })
+    }
+
+    fn describe<'e, 'q: 'e>(self, _sql: &'q str) -> BoxFuture<'e, Result<sqlx::Describe<DB>, sqlx::Error>>
+    where
+        'c: 'e,
+    {
+        Box::pin(async { Err(not_executed()) })
+    }
+}
+
+#[cfg_attr(not(feature = "send-less"), async_trait)]
+#[cfg_attr(feature = "send-less", async_trait(?Send))]
+impl<'c, DB> Executioner<'c, DB> for &'c mut MockExecutioner<DB>
+where
+    DB: MockDatabase,
+{
+    async fn save<E: HasPrimaryKey + Writable + Send>(self, entity: &mut E) -> crate::Result<ExecResult> {
+        let mut request = entity.save::<DB>();
+        if let Some(error) = request.error.take() {
+            return Err(error);
+        }
+        let (sql, parameters) = build_validated::<DB, _>(request.saving.clone(), request.validate_lengths, &request.comment)?;
+        self.record(sql, parameters.into_iter().map(Value::into_owned).collect())?;
+        Ok(ExecResult::default())
+    }
+
+    async fn insert<'query, I, IE>(self, insertion: IE) -> crate::Result<ExecResult>
+    where
+        IE: Into<InsertingExecution<DB, I>> + Send,
+        I: Into<Insert<'query>> + Send,
+    {
+        let request = insertion.into();
+        let (sql, parameters) = build_validated::<DB, Insert>(request.insertion.into(), request.validate_lengths, &request.comment)?;
+        self.record(sql, parameters.into_iter().map(Value::into_owned).collect())?;
+        Ok(ExecResult::default())
+    }
+
+    async fn execute_compiled(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<ExecResult> {
+        self.record(sql, parameters)?;
+        Ok(ExecResult::default())
+    }
+
+    async fn fetch_one_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<T>
+    where
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    {
+        self.record(sql, parameters)?;
+        let row = match self.rows.pop_front() {
+            Some(row) => DB::materialize(row).await?,
+            None => return Err(sqlx::Error::RowNotFound.into()),
+        };
+        Ok(T::from_row(&row)?)
+    }
+
+    /// Decodes all the canned rows.
+    async fn fetch_all_compiled<T>(self, sql: String, parameters: Vec<Value<'static>>) -> crate::Result<Vec<T>>
+    where
+        T: for<'r> FromRow<'r, DB::Row> + Send + Unpin,
+    {
+        self.record(sql, parameters)?;
+        let mut entities = Vec::with_capacity(self.rows.len());
+        while let Some(row) = self.rows.pop_front() {
+            let row = DB::materialize(row).await?;
+            entities.push(T::from_row(&row)?);
+        }
+        Ok(entities)
+    }
+
+    /// Records the statements, a pushed error failing one of them fails the
+    /// batch as a database would. Nothing is rolled back, the statements
+    /// before it stay recorded.
+    async fn execute_batch<'q, I>(self, statements: I) -> crate::Result<BatchReport>
+    where
+        I: IntoIterator<Item = Query<'q>> + Send,
+        I::IntoIter: Send,
+    {
+        let mut report = BatchReport::default();
+        for (index, (sql, parameters)) in build_batch::<DB, _>(statements)?.into_iter().enumerate() {
+            if let Err(source) = self.record(sql.clone(), parameters) {
+                return Err(batch_failed(index, sql, source));
+            }
+            report.push(0);
+        }
+        Ok(report)
+    }
+}
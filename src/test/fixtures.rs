@@ -0,0 +1,148 @@
+//! Fresh in-memory SQLite databases with the tables of entities.
+//!
+//! [`TestDb`] creates the tables from the schemas of the entities, in the
+//! order of their foreign keys, and inserts the seeded rows. The database
+//! goes away with the last connection of the returned pool.
+//!
+//! ```rust
+//! # use xiayu::prelude::*;
+//! # use xiayu::test::fixtures::TestDb;
+//! #[derive(Debug, Entity)]
+//! #[tablename = "users"]
+//! struct User {
+//!     #[column(primary_key, autoincrement)]
+//!     id: i64,
+//!     name: String,
+//! }
+//!
+//! #[derive(Debug, Entity)]
+//! #[tablename = "posts"]
+//! struct Post {
+//!     #[column(primary_key, autoincrement)]
+//!     id: i64,
+//!     #[column(foreign_key = "users.id")]
+//!     user_id: i64,
+//! }
+//!
+//! # async fn run() -> xiayu::Result<()> {
+//! let pool = TestDb::new()
+//!     .with_entity::<Post>()
+//!     .with_entity::<User>()
+//!     .seed::<User, _>(vec![(1, "alice"), (2, "bob")])
+//!     .build()
+//!     .await?;
+//!
+//! let bob = User::get(2).conn(&pool).await?;
+//! assert_eq!("bob", bob.name);
+//! # Ok(())
+//! # }
+//! # fn main() {
+//! #     tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(run()).unwrap();
+//! # }
+//! ```
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+
+use crate::ast::{AlterTable, Insert, MultiRowInsert, Query, Row, TableDefinition};
+use crate::databases::InsertingExecution;
+use crate::prelude::Entity;
+use crate::registry::Registry;
+use crate::visitors::{Sqlite, Visitor};
+
+/// Builds an in-memory SQLite database with the tables of the given
+/// entities.
+#[derive(Debug, Default)]
+pub struct TestDb {
+    registry: Registry,
+    seeds: Vec<(&'static str, MultiRowInsert<'static>)>,
+}
+
+impl TestDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the table of `E`.
+    pub fn with_entity<E: Entity>(mut self) -> Self {
+        self.registry.register::<E>();
+        self
+    }
+
+    /// Creates the table of `E` and inserts `rows`, the values of all the
+    /// columns of `E` in order, with a single insert.
+    pub fn seed<E, I>(mut self, rows: I) -> Self
+    where
+        E: Entity,
+        I: IntoIterator,
+        I::Item: Into<Row<'static>>,
+    {
+        self.registry.register::<E>();
+
+        let mut insert = Insert::multi_into(E::table(), E::columns().iter().cloned());
+        let mut empty = true;
+        for row in rows {
+            insert = insert.values(row);
+            empty = false;
+        }
+
+        if !empty {
+            self.seeds.push((E::tablename(), insert));
+        }
+
+        self
+    }
+
+    /// Opens the database and creates the tables, referenced tables first,
+    /// then inserts the seeded rows. Fails with a
+    /// [`ForeignKeyCycle`](crate::error::ErrorKind::ForeignKeyCycle) when
+    /// tables reference each other.
+    pub async fn build(self) -> crate::Result<SqlitePool> {
+        let schemas = self.registry.in_dependency_order()?;
+
+        // Every connection to `sqlite::memory:` opens a database of its own,
+        // the pool keeps the one connection for its whole life.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
+            .await?;
+
+        for schema in schemas.iter() {
+            let create = AlterTable::create_table(schema.table(), TableDefinition::from(schema));
+            execute(&pool, create).await?;
+
+            for index in schema.indexes {
+                let create = AlterTable::create_index(
+                    schema.table(),
+                    index.name,
+                    index.columns.iter().copied(),
+                    index.unique,
+                );
+                execute(&pool, create).await?;
+            }
+        }
+
+        let mut seeds = self.seeds;
+        for schema in schemas.iter() {
+            let (ready, rest): (Vec<_>, Vec<_>) = seeds
+                .into_iter()
+                .partition(|(table, _)| *table == schema.tablename);
+            seeds = rest;
+
+            for (_, insert) in ready {
+                InsertingExecution::<sqlx::Sqlite, _>::from(insert).conn(&pool).await?;
+            }
+        }
+
+        Ok(pool)
+    }
+}
+
+async fn execute<'a, Q>(pool: &SqlitePool, query: Q) -> crate::Result<()>
+where
+    Q: Into<Query<'a>>,
+{
+    let (sql, _) = Sqlite::build(query)?;
+    sqlx::query(&sql).execute(pool).await?;
+    Ok(())
+}
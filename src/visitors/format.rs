@@ -0,0 +1,294 @@
+//! Formatting of the rendered SQL for humans, in logs and while debugging.
+use std::fmt::Write;
+use std::sync::atomic::{self, AtomicBool};
+
+use super::code_segments;
+use crate::ast::{ColumnMeta, Value};
+
+/// Subqueries deeper than this are not indented any further.
+const MAX_INDENT: usize = 16;
+const INDENT: &str = "  ";
+
+/// The keywords starting a clause on a line of its own.
+const CLAUSES: &[&str] = &[
+    "SELECT",
+    "FROM",
+    "WHERE",
+    "GROUP",
+    "HAVING",
+    "ORDER",
+    "LIMIT",
+    "OFFSET",
+    "UNION",
+    "INTERSECT",
+    "EXCEPT",
+    "SET",
+    "VALUES",
+    "RETURNING",
+    "JOIN",
+    "INNER",
+    "LEFT",
+    "RIGHT",
+    "FULL",
+    "CROSS",
+];
+
+/// The words before `JOIN` which already started its line.
+const JOIN_KINDS: &[&str] = &["INNER", "LEFT", "RIGHT", "FULL", "CROSS", "OUTER"];
+
+static PRETTY_SQL: AtomicBool = AtomicBool::new(false);
+
+/// Whether the SQL of the `render_sql` spans is logged formatted with
+/// [`format_sql`] instead of on a single line, off by default.
+pub fn set_pretty_sql(pretty_sql: bool) {
+    PRETTY_SQL.store(pretty_sql, atomic::Ordering::Relaxed);
+}
+
+/// Logs the SQL of a rendered query and its parameters at the debug level, in
+/// the `render_sql` span of the visitor.
+pub(crate) fn trace_sql(sql: &str, parameters: &[Value<'_>], columns: &[Option<ColumnMeta>]) {
+    if !tracing::enabled!(tracing::Level::DEBUG) {
+        return;
+    }
+    let parameters = format_parameters(parameters, columns);
+    if PRETTY_SQL.load(atomic::Ordering::Relaxed) {
+        tracing::debug!(sql = %format_sql(sql), %parameters, "rendered");
+    } else {
+        tracing::debug!(sql, %parameters, "rendered");
+    }
+}
+
+/// The parameters as logged, the values bound to write-only columns replaced
+/// by `<redacted>`.
+pub(crate) fn format_parameters(parameters: &[Value<'_>], columns: &[Option<ColumnMeta>]) -> String {
+    let mut formatted = String::from("[");
+    for (i, parameter) in parameters.iter().enumerate() {
+        if i > 0 {
+            formatted.push_str(", ");
+        }
+        match columns.get(i) {
+            Some(Some(column)) if column.write_only => formatted.push_str("<redacted>"),
+            _ => {
+                let _ = write!(formatted, "{}", parameter);
+            }
+        }
+    }
+    formatted.push(']');
+    formatted
+}
+
+/// A paren opened in the SQL.
+#[derive(Clone, Copy, PartialEq)]
+enum Paren {
+    /// Not known yet, the word after it tells.
+    Pending,
+    /// Around a subquery, its clauses are indented.
+    Block,
+    /// Around anything else, a list or the arguments of a function.
+    Inline,
+}
+
+/// `sql` with its clauses on lines of their own and its subqueries indented.
+///
+/// Only the whitespace outside of string literals, quoted identifiers and
+/// comments changes: the formatted SQL runs as the original one.
+///
+/// ```rust
+/// let sql = "SELECT `users`.* FROM `users` WHERE `users`.`id` IN (SELECT `user_id` FROM `orders` WHERE `note` = 'FROM x')";
+///
+/// assert_eq!(
+///     "SELECT `users`.*\nFROM `users`\nWHERE `users`.`id` IN (\n  SELECT `user_id`\n  FROM `orders`\n  WHERE `note` = 'FROM x'\n)",
+///     xiayu::visitors::format_sql(sql)
+/// );
+/// ```
+pub fn format_sql(sql: &str) -> String {
+    let mut formatter = Formatter {
+        out: String::with_capacity(sql.len() + sql.len() / 4),
+        parens: Vec::new(),
+        blocks: 0,
+        previous: "",
+    };
+
+    let mut end = 0;
+    for segment in code_segments(sql, true, true) {
+        let start = segment.as_ptr() as usize - sql.as_ptr() as usize;
+        // The literal, identifier or comment before the segment, as is.
+        formatter.out.push_str(&sql[end..start]);
+        formatter.code(segment);
+        end = start + segment.len();
+    }
+    formatter.out.push_str(&sql[end..]);
+
+    formatter.out
+}
+
+struct Formatter<'s> {
+    out: String,
+    parens: Vec<Paren>,
+    /// The number of subquery parens open.
+    blocks: usize,
+    /// The last word written, upper case keywords compared without case.
+    previous: &'s str,
+}
+
+impl<'s> Formatter<'s> {
+    fn code(&mut self, code: &'s str) {
+        let mut rest = code;
+        while let Some(c) = rest.chars().next() {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+                // `LEFT(..)` or `VALUES(..)` call a function.
+                let call = rest[len..].starts_with('(');
+                self.word(&rest[..len], call);
+                rest = &rest[len..];
+                continue;
+            }
+            match c {
+                '(' => self.parens.push(Paren::Pending),
+                ')' if self.parens.pop() == Some(Paren::Block) => {
+                    self.blocks -= 1;
+                    self.newline();
+                }
+                _ => (),
+            }
+            if !c.is_whitespace() {
+                self.previous = "";
+            }
+            self.out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    fn word(&mut self, word: &'s str, call: bool) {
+        let qualified = self.out.ends_with('.');
+        let mut starts_block = false;
+        if let Some(paren @ Paren::Pending) = self.parens.last_mut() {
+            starts_block = word.eq_ignore_ascii_case("SELECT") || word.eq_ignore_ascii_case("WITH");
+            *paren = if starts_block {
+                self.blocks += 1;
+                Paren::Block
+            } else {
+                Paren::Inline
+            };
+        }
+        let in_block = matches!(self.parens.last(), None | Some(Paren::Block));
+        let joined = word.eq_ignore_ascii_case("JOIN") && JOIN_KINDS.iter().any(|kind| kind.eq_ignore_ascii_case(self.previous));
+        let clause = CLAUSES.iter().any(|clause| clause.eq_ignore_ascii_case(word)) && !call && !qualified && !joined;
+
+        if starts_block || (in_block && clause) {
+            self.newline();
+        }
+        self.out.push_str(word);
+        self.previous = word;
+    }
+
+    /// Ends the line, unless nothing is written on it yet, and indents the
+    /// next one.
+    fn newline(&mut self) {
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+        for _ in 0..self.blocks.min(MAX_INDENT) {
+            self.out.push_str(INDENT);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_only_parameters_are_redacted() {
+        let name = ColumnMeta { name: "name".into(), length: None, write_only: false };
+        let secret = ColumnMeta { name: "password_hash".into(), length: None, write_only: true };
+
+        assert_eq!(
+            "[1, \"ann\", <redacted>, null]",
+            format_parameters(
+                &[Value::integer(1), Value::text("ann"), Value::text("hunter2"), Value::Integer(None)],
+                &[None, Some(name), Some(secret), None],
+            )
+        );
+    }
+
+    #[test]
+    fn clauses_start_lines() {
+        assert_eq!(
+            "SELECT `users`.*\nFROM `users`\nINNER JOIN `orders` ON `orders`.`user_id` = `users`.`id`\nLEFT OUTER JOIN `carts` ON `carts`.`user_id` = `users`.`id`\nWHERE `users`.`id` = ?\nORDER BY `users`.`name` ASC\nLIMIT ?",
+            format_sql(
+                "SELECT `users`.* FROM `users` INNER JOIN `orders` ON `orders`.`user_id` = `users`.`id` LEFT OUTER JOIN `carts` ON `carts`.`user_id` = `users`.`id` WHERE `users`.`id` = ? ORDER BY `users`.`name` ASC LIMIT ?"
+            )
+        );
+    }
+
+    #[test]
+    fn subqueries_are_indented() {
+        assert_eq!(
+            "(\n  SELECT \"a\"\n  FROM \"t\"\n  WHERE \"b\" IN (\n    SELECT \"b\"\n    FROM \"u\"\n  )\n)\nUNION ALL (\n  SELECT \"a\"\n  FROM \"v\"\n)",
+            format_sql("(SELECT \"a\" FROM \"t\" WHERE \"b\" IN (SELECT \"b\" FROM \"u\")) UNION ALL (SELECT \"a\" FROM \"v\")")
+        );
+    }
+
+    #[test]
+    fn lists_and_functions_stay_inline() {
+        assert_eq!(
+            "SELECT COUNT(*), EXTRACT(YEAR FROM \"created_at\"), ROW_NUMBER() OVER (PARTITION BY \"kind\" ORDER BY \"id\")\nFROM \"t\"\nWHERE \"id\" IN ($1, $2)",
+            format_sql("SELECT COUNT(*), EXTRACT(YEAR FROM \"created_at\"), ROW_NUMBER() OVER (PARTITION BY \"kind\" ORDER BY \"id\") FROM \"t\" WHERE \"id\" IN ($1, $2)")
+        );
+    }
+
+    #[test]
+    fn inserts_and_updates() {
+        assert_eq!(
+            "INSERT INTO `users` (`name`)\nVALUES (?)",
+            format_sql("INSERT INTO `users` (`name`) VALUES (?)")
+        );
+        assert_eq!(
+            "UPDATE \"users\"\nSET \"name\" = $1\nWHERE \"users\".\"id\" = $2\nRETURNING \"id\"",
+            format_sql("UPDATE \"users\" SET \"name\" = $1 WHERE \"users\".\"id\" = $2 RETURNING \"id\"")
+        );
+    }
+
+    #[test]
+    fn literals_identifiers_and_comments_are_kept() {
+        let sql = "SELECT 'a FROM b (SELECT', \"WHERE\", [ORDER BY], $tag$ UNION $tag$ FROM t -- SELECT FROM\nWHERE x = 'it''s' /* JOIN ( */";
+
+        assert_eq!(
+            "SELECT 'a FROM b (SELECT', \"WHERE\", [ORDER BY], $tag$ UNION $tag$\nFROM t -- SELECT FROM\nWHERE x = 'it''s' /* JOIN ( */",
+            format_sql(sql)
+        );
+    }
+
+    #[test]
+    fn qualified_names_and_functions_are_not_keywords() {
+        assert_eq!("SELECT t.order, t.from\nFROM t", format_sql("SELECT t.order, t.from FROM t"));
+        assert_eq!(
+            "SELECT LEFT(`name`, 2)\nFROM `t`",
+            format_sql("SELECT LEFT(`name`, 2) FROM `t`")
+        );
+        assert_eq!(
+            "INSERT INTO `t` (`a`)\nVALUES (?) ON DUPLICATE KEY UPDATE `a` = VALUES(`a`)",
+            format_sql("INSERT INTO `t` (`a`) VALUES (?) ON DUPLICATE KEY UPDATE `a` = VALUES(`a`)")
+        );
+    }
+
+    #[test]
+    fn deep_nesting_is_capped() {
+        let depth = 1_000;
+        let sql = format!("{}SELECT 1{}", "(SELECT * FROM ".repeat(depth), ")".repeat(depth));
+        let formatted = format_sql(&sql);
+
+        let widest = formatted.lines().map(|line| line.len() - line.trim_start().len()).max();
+        assert_eq!(Some(MAX_INDENT * INDENT.len()), widest);
+        assert!(formatted.len() < sql.len() * 16, "{}", formatted.len());
+    }
+
+    #[test]
+    fn unbalanced_parens() {
+        assert_eq!("SELECT (1))\nFROM t", format_sql("SELECT (1)) FROM t"));
+        assert_eq!("SELECT (\n  SELECT 1", format_sql("SELECT (SELECT 1"));
+    }
+}
@@ -6,6 +6,7 @@
 //! [ast](../ast/index.html) module.
 //!
 //! For prelude, all important imports are in `xiayu::visitors::*`;
+mod format;
 #[cfg(feature = "mssql")]
 mod mssql;
 #[cfg(feature = "mysql")]
@@ -15,20 +16,166 @@ mod postgres;
 #[cfg(feature = "sqlite")]
 mod sqlite;
 
+pub use self::format::{format_sql, set_pretty_sql};
 #[cfg(feature = "mssql")]
 pub use self::mssql::Mssql;
 #[cfg(feature = "mysql")]
-pub use self::mysql::Mysql;
+pub use self::mysql::{Mysql, MysqlVersion};
 #[cfg(feature = "postgres")]
 pub use self::postgres::Postgres;
 #[cfg(feature = "sqlite")]
 pub use self::sqlite::Sqlite;
 
 use crate::ast::*;
-use std::fmt;
+use std::sync::atomic::{self, AtomicUsize};
+use std::{borrow::Cow, fmt};
 
 pub type Result = crate::Result<()>;
 
+/// A built query: the SQL, its parameters and the column each parameter is
+/// bound to, if any.
+pub type Built<'a> = (String, Vec<Value<'a>>, Vec<Option<ColumnMeta>>);
+
+/// The SQL and parameters of a query built with
+/// [`build_into`](Visitor::build_into). Building again clears them but keeps
+/// their allocations, sparing queries built in a loop from allocating anew.
+///
+/// ```
+/// # use entities::Cat;
+/// # use xiayu::{prelude::*, visitors::*};
+/// # fn main() -> xiayu::visitors::Result {
+/// let mut buffers = VisitorBuffers::new();
+///
+/// for id in 1..3 {
+///     Sqlite::build_into(Select::from_table(Cat::table()).so_that(Cat::master_id.equals(id)), &mut buffers)?;
+///
+///     assert_eq!("SELECT `cats`.* FROM `cats` WHERE `cats`.`master_id` = ?", buffers.sql());
+///     assert_eq!(&[Value::from(id)], buffers.parameters());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct VisitorBuffers<'a> {
+    pub(crate) query: String,
+    pub(crate) parameters: Vec<Value<'a>>,
+    pub(crate) parameter_columns: Vec<Option<ColumnMeta>>,
+}
+
+impl<'a> VisitorBuffers<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffers sized for `query`, from the number of values and conditions it
+    /// has at the top level, for queries built only once.
+    pub fn for_query(query: &Query<'a>) -> Self {
+        fn conditions(tree: &Option<ConditionTree<'_>>) -> usize {
+            match tree {
+                Some(ConditionTree::And(exprs)) | Some(ConditionTree::Or(exprs)) => exprs.len(),
+                Some(ConditionTree::Not(_)) | Some(ConditionTree::Single(_)) => 1,
+                _ => 0,
+            }
+        }
+
+        let slots = match query {
+            Query::Select(select) => {
+                select.columns.len() + conditions(&select.conditions) + select.joins.len() + 2
+            }
+            Query::Insert(insert) => match &insert.values.kind {
+                ExpressionKind::Row(row) => row.values.len(),
+                ExpressionKind::Values(values) => values.rows.len() * insert.columns.len(),
+                _ => insert.columns.len(),
+            },
+            Query::Update(update) => update.values.len() + conditions(&update.conditions),
+            Query::Delete(delete) => conditions(&delete.conditions),
+            _ => 8,
+        };
+
+        Self {
+            query: String::with_capacity(64 + 24 * slots),
+            parameters: Vec::with_capacity(slots),
+            parameter_columns: Vec::with_capacity(slots),
+        }
+    }
+
+    /// The SQL of the last query built.
+    pub fn sql(&self) -> &str {
+        &self.query
+    }
+
+    /// The parameters of the last query built.
+    pub fn parameters(&self) -> &[Value<'a>] {
+        &self.parameters
+    }
+
+    /// The column of every parameter of the last query built assigned to one.
+    pub fn parameter_columns(&self) -> &[Option<ColumnMeta>] {
+        &self.parameter_columns
+    }
+
+    /// Moves the parameters of the last query built out, e.g. to bind them.
+    pub fn drain_parameters(&mut self) -> std::vec::Drain<'_, Value<'a>> {
+        self.parameters.drain(..)
+    }
+
+    fn clear(&mut self) {
+        self.query.clear();
+        self.parameters.clear();
+        self.parameter_columns.clear();
+    }
+
+    /// Takes the buffers for a visitor, handed back with
+    /// [`restore`](Self::restore) once the query is visited.
+    pub(crate) fn take(&mut self) -> Self {
+        self.clear();
+        std::mem::take(self)
+    }
+
+    pub(crate) fn restore(&mut self, query: String, parameters: Vec<Value<'a>>, parameter_columns: Vec<Option<ColumnMeta>>) {
+        *self = Self {
+            query,
+            parameters,
+            parameter_columns,
+        };
+    }
+}
+
+static MAX_DEPTH: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_DEPTH);
+
+/// How deep expressions nest by default before the visit fails. A debug
+/// build takes up to 34 KiB of stack by level, this stays within the 2 MiB
+/// of a spawned thread.
+pub const DEFAULT_MAX_DEPTH: usize = 48;
+
+/// Sets how deep expressions may nest in the queries built from now on,
+/// e.g. operations in operations or functions in functions, deeper ones
+/// fail with [`QueryTooComplex`](crate::error::ErrorKind::QueryTooComplex).
+/// Conditions are walked without nesting the visit: `AND` and `OR` in one
+/// another do not count.
+///
+/// Raising it is only safe with a larger stack.
+pub fn set_max_depth(depth: usize) {
+    MAX_DEPTH.store(depth, atomic::Ordering::Relaxed);
+}
+
+/// How deep expressions may nest, see [`set_max_depth`].
+pub fn max_depth() -> usize {
+    MAX_DEPTH.load(atomic::Ordering::Relaxed)
+}
+
+/// When to surround identifiers, such as table and column names, with
+/// backticks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuoteStyle {
+    /// Every identifier is quoted.
+    #[default]
+    Always,
+    /// Identifiers are only quoted when required: names with upper case
+    /// or special characters, reserved words, and columns with `quote = true`.
+    Minimal,
+}
+
 /// A function travelling through the query AST, building the final query string
 /// and gathering parameters sent to the database together with the query.
 pub trait Visitor<'a> {
@@ -38,6 +185,9 @@ pub trait Visitor<'a> {
     const C_BACKTICK_CLOSE: &'static str;
     /// Wildcard character to be used in `LIKE` queries.
     const C_WILDCARD: &'static str;
+    /// Words that must be quoted to be used as identifiers, in upper case and
+    /// sorted.
+    const RESERVED_WORDS: &'static [&'static str];
 
     /// Convert the given `Query` to an SQL string and a vector of parameters.
     /// When certain parameters are replaced with the `C_PARAM` character in the
@@ -47,32 +197,114 @@ pub trait Visitor<'a> {
     ///
     /// ```
     /// # use entities::Cat;
-    /// # use xiayu::{prelude::*, visitors::*, error::Error};
+    /// # use xiayu::{prelude::*, visitors::*};
     /// # fn main() -> xiayu::visitors::Result {
     /// let query = Select::from_table(Cat::table());
     /// let (sqlite, _) = Sqlite::build(query.clone())?;
-    /// let (psql, _) = Postgres::build(query.clone())?;
     /// let (mysql, _) = Mysql::build(query.clone())?;
-    /// let (mssql, _) = Mssql::build(query.clone())?;
     ///
     /// assert_eq!("SELECT `cats`.* FROM `cats`", sqlite);
-    /// assert_eq!("SELECT \"cats\".* FROM \"cats\"", psql);
     /// assert_eq!("SELECT `cats`.* FROM `cats`", mysql);
+    ///
+    /// # #[cfg(feature = "postgres")]
+    /// # {
+    /// let (psql, _) = Postgres::build(query.clone())?;
+    /// assert_eq!("SELECT \"cats\".* FROM \"cats\"", psql);
+    /// # }
+    /// # #[cfg(feature = "mssql")]
+    /// # {
+    /// let (mssql, _) = Mssql::build(query.clone())?;
     /// assert_eq!("SELECT [cats].* FROM [cats]", mssql);
+    /// # }
     /// # Ok(())
     /// # }
     /// ```
     fn build<Q>(query: Q) -> crate::Result<(String, Vec<Value<'a>>)>
+    where
+        Q: Into<Query<'a>>,
+    {
+        Self::build_with(query, QuoteStyle::default())
+    }
+
+    /// Like [`build`](Visitor::build), quoting identifiers following `style`.
+    ///
+    /// ```
+    /// # use entities::Cat;
+    /// # use xiayu::{prelude::*, visitors::*};
+    /// # fn main() -> xiayu::visitors::Result {
+    /// let query = Select::from_table(Cat::table()).column(Cat::master_id);
+    /// let (sql, _) = Sqlite::build_with(query, QuoteStyle::Minimal)?;
+    ///
+    /// assert_eq!("SELECT cats.master_id FROM cats", sql);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn build_with<Q>(query: Q, style: QuoteStyle) -> crate::Result<(String, Vec<Value<'a>>)>
+    where
+        Q: Into<Query<'a>>,
+    {
+        let (sql, parameters, _) = Self::build_with_columns(query, style)?;
+        Ok((sql, parameters))
+    }
+
+    /// Like [`build`](Visitor::build), writing the query to `buffers` to
+    /// reuse their allocations, see [`VisitorBuffers`].
+    fn build_into<Q>(query: Q, buffers: &mut VisitorBuffers<'a>) -> Result
+    where
+        Q: Into<Query<'a>>,
+    {
+        Self::build_into_with(query, QuoteStyle::default(), buffers)
+    }
+
+    /// Like [`build_into`](Visitor::build_into), quoting identifiers
+    /// following `style`.
+    fn build_into_with<Q>(query: Q, style: QuoteStyle, buffers: &mut VisitorBuffers<'a>) -> Result
     where
         Q: Into<Query<'a>>;
 
+    /// Like [`build_with`](Visitor::build_with), also returning the column
+    /// of every parameter assigned to one in an `INSERT` or an `UPDATE`.
+    ///
+    /// ```
+    /// # use entities::Recipe;
+    /// # use xiayu::{prelude::*, visitors::*};
+    /// # fn main() -> xiayu::visitors::Result {
+    /// let insert = Insert::single_into(Recipe::table()).value(Recipe::name, "pancakes");
+    /// let (_, _, columns) = Sqlite::build_with_columns(insert, QuoteStyle::default())?;
+    ///
+    /// assert_eq!("name", columns[0].as_ref().unwrap().name);
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn build_with_columns<Q>(query: Q, style: QuoteStyle) -> crate::Result<Built<'a>>
+    where
+        Q: Into<Query<'a>>,
+    {
+        let query = query.into();
+        let mut buffers = VisitorBuffers::for_query(&query);
+        Self::build_into_with(query, style, &mut buffers)?;
+        Ok((buffers.query, buffers.parameters, buffers.parameter_columns))
+    }
+
+    /// The quoting style the query is built with.
+    fn quote_style(&self) -> QuoteStyle;
+
+    /// The name of the database type used for `type_family`.
+    fn type_name(type_family: TypeFamily) -> Cow<'static, str>;
+
+    /// The type of a column of `type_family` in a table definition, the same
+    /// as [`type_name`](Visitor::type_name) unless casts use other names.
+    fn column_type(type_family: TypeFamily) -> Cow<'static, str> {
+        Self::type_name(type_family)
+    }
+
     /// Write to the query.
     fn write<D: fmt::Display>(&mut self, s: D) -> Result;
 
     /// A point to modify an incoming query to make it compatible with the
     /// underlying database.
-    fn compatibility_modifications(&self, query: Query<'a>) -> Query<'a> {
-        query
+    fn compatibility_modifications(&self, query: Query<'a>) -> crate::Result<Query<'a>> {
+        Ok(query)
     }
 
     fn surround_with<F>(&mut self, begin: &str, end: &str, f: F) -> Result
@@ -88,6 +320,17 @@ pub trait Visitor<'a> {
     /// replacing it with the `C_PARAM`, calling `add_parameter` with the replaced value.
     fn add_parameter(&mut self, value: Value<'a>);
 
+    /// Binds the parameters added from now on to `column`, `None` for
+    /// parameters not assigned to a column.
+    fn bind_parameters_to(&mut self, column: Option<ColumnMeta>);
+
+    /// The number of expressions being visited, one in another.
+    fn depth(&mut self) -> &mut usize;
+
+    /// The number of predicates being visited, one in another. Aliases are
+    /// not rendered in them, see [`Visitor::visit_alias`].
+    fn predicates(&mut self) -> &mut usize;
+
     /// The `LIMIT` and `OFFSET` statement in the query
     fn visit_limit_and_offset(
         &mut self,
@@ -101,6 +344,31 @@ pub trait Visitor<'a> {
     /// What to use to substitute a parameter in the query.
     fn parameter_substitution(&mut self) -> Result;
 
+    /// The number of parameters `sql` written by hand takes, its `?`
+    /// placeholders outside of literals, quoted identifiers and comments.
+    fn count_placeholders(sql: &str) -> usize
+    where
+        Self: Sized,
+    {
+        code_segments(sql, false, false)
+            .iter()
+            .map(|segment| segment.matches('?').count())
+            .sum()
+    }
+
+    /// The expression of a `COUNT(DISTINCT ..)`. A row of more than one
+    /// column is an error, unless the database counts their combinations.
+    fn visit_count_distinct(&mut self, expr: Expression<'a>) -> Result {
+        match expr.kind {
+            ExpressionKind::Row(mut row) if row.len() == 1 => self.visit_expression(row.values.remove(0)),
+            ExpressionKind::Row(row) => {
+                let kind = crate::error::ErrorKind::CountDistinctColumns { columns: row.len() };
+                Err(crate::error::Error::builder(kind).build())
+            }
+            _ => self.visit_expression(expr),
+        }
+    }
+
     /// What to use to substitute a parameter in the query.
     fn visit_aggregate_to_string(&mut self, value: Expression<'a>) -> Result;
 
@@ -186,8 +454,38 @@ pub trait Visitor<'a> {
         self.visit_conditions(data.conditions)
     }
 
+    /// Row locking of a `SELECT`, rendered after `LIMIT` and `OFFSET`.
+    fn visit_locking(&mut self, strength: LockStrength, wait: LockWait) -> Result {
+        match strength {
+            LockStrength::Update => self.write(" FOR UPDATE")?,
+            LockStrength::Share => self.write(" FOR SHARE")?,
+        }
+
+        match wait {
+            LockWait::Wait => Ok(()),
+            LockWait::SkipLocked => self.write(" SKIP LOCKED"),
+            LockWait::NoWait => self.write(" NOWAIT"),
+        }
+    }
+
+    /// Hints rendered right after a table in the `FROM` clause of a locking
+    /// `SELECT`, for databases locking through table hints.
+    fn visit_lock_hints(&mut self, _strength: LockStrength, _wait: LockWait) -> Result {
+        Ok(())
+    }
+
     /// A walk through a `SELECT` statement
     fn visit_select(&mut self, select: Select<'a>) -> Result {
+        if let Some(sql) = select.sql {
+            return self.write(sql);
+        }
+
+        if select.tables.is_empty() && select.columns.is_empty() {
+            let kind = crate::error::ErrorKind::IncompleteQuery { missing: "a table or a value to select" };
+            return Err(crate::error::Error::builder(kind).build());
+        }
+
+        let locking = select.locking.validate()?;
         let number_of_ctes = select.ctes.len();
 
         if number_of_ctes > 0 {
@@ -220,22 +518,14 @@ pub trait Visitor<'a> {
                     match &table.typ {
                         TableType::Query(_) | TableType::Values(_) => match table.alias {
                             Some(ref alias) => {
-                                self.surround_with(
-                                    Self::C_BACKTICK_OPEN,
-                                    Self::C_BACKTICK_CLOSE,
-                                    |ref mut s| s.write(alias),
-                                )?;
+                                self.visit_identifier(alias, false)?;
                                 self.write(".*")?;
                             }
                             None => self.write("*")?,
                         },
                         TableType::Table(_) => match table.alias.clone() {
                             Some(ref alias) => {
-                                self.surround_with(
-                                    Self::C_BACKTICK_OPEN,
-                                    Self::C_BACKTICK_CLOSE,
-                                    |ref mut s| s.write(alias),
-                                )?;
+                                self.visit_identifier(alias, false)?;
                                 self.write(".*")?;
                             }
                             None => {
@@ -245,11 +535,7 @@ pub trait Visitor<'a> {
                         },
                         TableType::JoinedTable(jt) => match table.alias.clone() {
                             Some(ref alias) => {
-                                self.surround_with(
-                                    Self::C_BACKTICK_OPEN,
-                                    Self::C_BACKTICK_CLOSE,
-                                    |ref mut s| s.write(alias),
-                                )?;
+                                self.visit_identifier(alias, false)?;
                                 self.write(".*")?;
                             }
                             None => {
@@ -276,6 +562,10 @@ pub trait Visitor<'a> {
                 }
 
                 self.visit_table(table, true)?;
+
+                if let Some((strength, wait)) = locking {
+                    self.visit_lock_hints(strength, wait)?;
+                }
             }
 
             if !select.joins.is_empty() {
@@ -300,6 +590,10 @@ pub trait Visitor<'a> {
             }
 
             self.visit_limit_and_offset(select.limit, select.offset)?;
+
+            if let Some((strength, wait)) = locking {
+                self.visit_locking(strength, wait)?;
+            }
         } else if select.columns.is_empty() {
             self.write(" *")?;
         } else {
@@ -311,6 +605,11 @@ pub trait Visitor<'a> {
 
     /// A walk through an `UPDATE` statement
     fn visit_update(&mut self, update: Update<'a>) -> Result {
+        if update.columns.is_empty() {
+            let kind = crate::error::ErrorKind::IncompleteQuery { missing: "a column to update" };
+            return Err(crate::error::Error::builder(kind).build());
+        }
+
         self.write("UPDATE ")?;
         self.visit_table(update.table, true)?;
 
@@ -320,9 +619,12 @@ pub trait Visitor<'a> {
             let len = pairs.len();
 
             for (i, (key, value)) in pairs.enumerate() {
+                let column = ColumnMeta::from(&key);
                 self.visit_column(key)?;
                 self.write(" = ")?;
+                self.bind_parameters_to(Some(column));
                 self.visit_expression(value)?;
+                self.bind_parameters_to(None);
 
                 if i < (len - 1) {
                     self.write(", ")?;
@@ -340,6 +642,17 @@ pub trait Visitor<'a> {
 
     /// A walk through an `DELETE` statement
     fn visit_delete(&mut self, delete: Delete<'a>) -> Result {
+        if delete.limit.is_some() {
+            if !delete.using.is_empty() {
+                let kind = crate::error::ErrorKind::InvalidDelete("a limited delete can not use other tables");
+                return Err(crate::error::Error::builder(kind).build());
+            }
+            return self.visit_limited_delete(delete);
+        }
+        if !delete.using.is_empty() {
+            return self.visit_delete_using(delete);
+        }
+
         self.write("DELETE FROM ")?;
         self.visit_table(delete.table, true)?;
 
@@ -351,17 +664,85 @@ pub trait Visitor<'a> {
         Ok(())
     }
 
+    /// A delete of at most `LIMIT` rows, see [`Delete::limit`]. Deletes the
+    /// rows of a subquery by `rowid` unless the database knows better.
+    fn visit_limited_delete(&mut self, delete: Delete<'a>) -> Result {
+        self.visit_delete_by_row_id(delete, "rowid")
+    }
+
+    /// `DELETE FROM t WHERE row_id IN (SELECT row_id FROM t WHERE .. LIMIT ..)`,
+    /// `row_id` identifying the rows of the table.
+    fn visit_delete_by_row_id(&mut self, delete: Delete<'a>, row_id: &'static str) -> Result {
+        let mut limited = Select::from_table(delete.table.clone()).value(Column::new(row_id));
+        limited.conditions = delete.conditions;
+        limited.ordering = delete.ordering;
+        limited.limit = delete.limit;
+
+        self.write("DELETE FROM ")?;
+        self.visit_table(delete.table, true)?;
+        self.write(" WHERE ")?;
+        self.visit_conditions(Column::new(row_id).in_selection(limited).into())
+    }
+
+    /// A delete of the rows matching the rows of other tables,
+    /// `DELETE FROM t USING u WHERE ..`.
+    fn visit_delete_using(&mut self, delete: Delete<'a>) -> Result {
+        self.write("DELETE FROM ")?;
+        self.visit_table(delete.table, true)?;
+        self.write(" USING ")?;
+        self.visit_tables(delete.using)?;
+
+        if let Some(conditions) = delete.conditions {
+            self.write(" WHERE ")?;
+            self.visit_conditions(conditions)?;
+        }
+
+        Ok(())
+    }
+
+    /// Tables separated with commas, `a, b`.
+    fn visit_tables(&mut self, tables: Vec<Table<'a>>) -> Result {
+        let len = tables.len();
+        for (i, table) in tables.into_iter().enumerate() {
+            self.visit_table(table, true)?;
+
+            if i < (len - 1) {
+                self.write(", ")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The multi-table delete of MySQL and SQL Server,
+    /// `DELETE t FROM t, u WHERE ..`.
+    fn visit_multi_table_delete(&mut self, delete: Delete<'a>) -> Result {
+        self.write("DELETE ")?;
+        match delete.table.alias.clone() {
+            Some(alias) => self.delimited_identifiers(&[&*alias])?,
+            None => self.visit_table(delete.table.clone(), false)?,
+        }
+
+        self.write(" FROM ")?;
+        let mut tables = vec![delete.table];
+        tables.extend(delete.using);
+        self.visit_tables(tables)?;
+
+        if let Some(conditions) = delete.conditions {
+            self.write(" WHERE ")?;
+            self.visit_conditions(conditions)?;
+        }
+
+        Ok(())
+    }
+
     /// A helper for delimiting an identifier, surrounding every part with `C_BACKTICK`
     /// and delimiting the values with a `.`
     fn delimited_identifiers(&mut self, parts: &[&str]) -> Result {
         let len = parts.len();
 
         for (i, parts) in parts.iter().enumerate() {
-            self.surround_with(
-                Self::C_BACKTICK_OPEN,
-                Self::C_BACKTICK_CLOSE,
-                |ref mut s| s.write(parts),
-            )?;
+            self.visit_identifier(parts, false)?;
 
             if i < (len - 1) {
                 self.write(".")?;
@@ -371,14 +752,218 @@ pub trait Visitor<'a> {
         Ok(())
     }
 
+    /// A single identifier, surrounded with `C_BACKTICK` if the quote style
+    /// or the identifier itself requires it, or if `force_quote` is set.
+    fn visit_identifier(&mut self, identifier: &str, force_quote: bool) -> Result {
+        let quote = match self.quote_style() {
+            QuoteStyle::Always => true,
+            QuoteStyle::Minimal => force_quote || requires_quoting::<Self>(identifier),
+        };
+
+        if !quote {
+            return self.write(identifier);
+        }
+
+        // A backtick inside of the identifier is escaped by doubling it.
+        let escaped = identifier.replace(
+            Self::C_BACKTICK_CLOSE,
+            &Self::C_BACKTICK_CLOSE.repeat(2),
+        );
+
+        self.surround_with(Self::C_BACKTICK_OPEN, Self::C_BACKTICK_CLOSE, |s| {
+            s.write(escaped)
+        })
+    }
+
     /// Visit an SQL `MERGE` query.
     fn visit_merge(&mut self, _merge: Merge<'a>) -> Result {
         unimplemented!("Merges not supported for the underlying database.")
     }
 
+    /// A walk through a change of a table definition.
+    fn visit_alter_table(&mut self, alter: AlterTable<'a>) -> Result {
+        let table = alter.table;
+
+        match alter.operation {
+            AlterOperation::AddColumn(column) => self.visit_add_column(table, column),
+            AlterOperation::DropColumn(column) => self.visit_drop_column(table, column),
+            AlterOperation::SetNullable(column) => self.visit_set_nullable(table, column),
+            AlterOperation::CreateIndex {
+                name,
+                columns,
+                unique,
+            } => self.visit_create_index(table, name, columns, unique),
+            AlterOperation::CreateTable(definition) => self.visit_create_table(table, definition),
+        }
+    }
+
+    /// `CREATE TABLE table (columns, constraints)`
+    fn visit_create_table(&mut self, table: Table<'a>, definition: TableDefinition) -> Result {
+        self.write("CREATE TABLE ")?;
+        self.visit_table(table, false)?;
+
+        let generated_key = match definition.primary_key.as_slice() {
+            [column] if definition.autoincrement => Some(column.clone()),
+            _ => None,
+        };
+
+        self.surround_with(" (", ")", |s| {
+            let len = definition.columns.len();
+            for (i, mut column) in definition.columns.into_iter().enumerate() {
+                let server_default = column.server_default.take();
+                let name = column.name.clone();
+                // Keys are `NOT NULL`, even when the field is an `Option`.
+                if definition.primary_key.contains(&name) {
+                    column.nullable = false;
+                }
+
+                s.visit_column_definition(column)?;
+
+                if let Some(server_default) = server_default {
+                    s.write(" DEFAULT ")?;
+                    s.write(server_default)?;
+                }
+
+                if generated_key.as_ref() == Some(&name) {
+                    s.visit_generated_primary_key()?;
+                }
+
+                if i < (len - 1) {
+                    s.write(", ")?;
+                }
+            }
+
+            if generated_key.is_none() && !definition.primary_key.is_empty() {
+                s.write(", PRIMARY KEY ")?;
+                s.visit_identifier_list(&definition.primary_key)?;
+            }
+
+            for column in definition.unique.iter() {
+                s.write(", UNIQUE ")?;
+                s.visit_identifier_list(std::slice::from_ref(column))?;
+            }
+
+            for foreign_key in definition.foreign_keys {
+                s.write(", FOREIGN KEY ")?;
+                s.visit_identifier_list(&[foreign_key.column])?;
+                s.write(" REFERENCES ")?;
+                s.visit_identifier(&foreign_key.references_table, false)?;
+                s.write(" ")?;
+                s.visit_identifier_list(&[foreign_key.references_column])?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// The primary key generated by the database, following its definition.
+    fn visit_generated_primary_key(&mut self) -> Result {
+        self.write(" GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY")
+    }
+
+    /// Identifiers in parentheses, `(a, b)`.
+    fn visit_identifier_list(&mut self, identifiers: &[Cow<'static, str>]) -> Result {
+        let len = identifiers.len();
+        self.surround_with("(", ")", |s| {
+            for (i, identifier) in identifiers.iter().enumerate() {
+                s.visit_identifier(identifier, false)?;
+
+                if i < (len - 1) {
+                    s.write(", ")?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// `ALTER TABLE table ADD COLUMN definition`
+    fn visit_add_column(&mut self, table: Table<'a>, column: ColumnDefinition) -> Result {
+        self.write("ALTER TABLE ")?;
+        self.visit_table(table, false)?;
+        self.write(" ADD COLUMN ")?;
+        self.visit_column_definition(column)
+    }
+
+    /// `ALTER TABLE table DROP COLUMN column`
+    fn visit_drop_column(&mut self, table: Table<'a>, column: Cow<'static, str>) -> Result {
+        self.write("ALTER TABLE ")?;
+        self.visit_table(table, false)?;
+        self.write(" DROP COLUMN ")?;
+        self.visit_identifier(&column, false)
+    }
+
+    /// `ALTER TABLE table ALTER COLUMN column SET NOT NULL`, or `DROP NOT NULL`
+    fn visit_set_nullable(&mut self, table: Table<'a>, column: ColumnDefinition) -> Result {
+        self.write("ALTER TABLE ")?;
+        self.visit_table(table, false)?;
+        self.write(" ALTER COLUMN ")?;
+        self.visit_identifier(&column.name, false)?;
+
+        if column.nullable {
+            self.write(" DROP NOT NULL")
+        } else {
+            self.write(" SET NOT NULL")
+        }
+    }
+
+    /// `CREATE [UNIQUE] INDEX name ON table (columns)`
+    fn visit_create_index(
+        &mut self,
+        table: Table<'a>,
+        name: Cow<'static, str>,
+        columns: Vec<Cow<'static, str>>,
+        unique: bool,
+    ) -> Result {
+        if unique {
+            self.write("CREATE UNIQUE INDEX ")?;
+        } else {
+            self.write("CREATE INDEX ")?;
+        }
+
+        self.visit_identifier(&name, false)?;
+        self.write(" ON ")?;
+        self.visit_table(table, false)?;
+        self.write(" ")?;
+        self.visit_identifier_list(&columns)
+    }
+
+    /// A column in a DDL statement, `name TYPE [NOT NULL]`.
+    fn visit_column_definition(&mut self, column: ColumnDefinition) -> Result {
+        let type_family = match column.type_family {
+            Some(type_family) => type_family,
+            None => {
+                let kind = crate::error::ErrorKind::conversion(format!(
+                    "The type of column `{}` is unknown.",
+                    column.name
+                ));
+                return Err(crate::error::Error::builder(kind).build());
+            }
+        };
+
+        self.visit_identifier(&column.name, false)?;
+        self.write(" ")?;
+        self.write(Self::column_type(type_family))?;
+
+        if !column.nullable {
+            self.write(" NOT NULL")?;
+        }
+
+        Ok(())
+    }
+
     /// A walk through a complete `Query` statement
     fn visit_query(&mut self, mut query: Query<'a>) -> Result {
-        query = self.compatibility_modifications(query);
+        // Only the `INSERT` of a `MERGE` goes without a table, visited on its
+        // own.
+        if let Query::Insert(ref insert) = query {
+            if insert.table.is_none() {
+                let kind = crate::error::ErrorKind::IncompleteQuery { missing: "a table to insert into" };
+                return Err(crate::error::Error::builder(kind).build());
+            }
+        }
+
+        query = self.compatibility_modifications(query)?;
 
         match query {
             Query::Select(select) => self.visit_select(*select),
@@ -387,15 +972,21 @@ pub trait Visitor<'a> {
             Query::Delete(delete) => self.visit_delete(*delete),
             Query::Union(union) => self.visit_union(*union),
             Query::Merge(merge) => self.visit_merge(*merge),
+            Query::AlterTable(alter) => self.visit_alter_table(*alter),
             Query::Raw(string) => self.write(string),
         }
     }
 
     fn visit_selection(&mut self, query: SelectQuery<'a>) -> Result {
+        // A subquery of a predicate has a select list and sources of its own.
+        let predicates = std::mem::take(self.predicates());
         match query {
-            SelectQuery::Select(select) => self.visit_select(*select),
-            SelectQuery::Union(union) => self.visit_union(*union),
+            SelectQuery::Select(select) => self.visit_select(*select)?,
+            SelectQuery::Union(union) => self.visit_union(*union)?,
         }
+        *self.predicates() = predicates;
+
+        Ok(())
     }
 
     /// A walk through a union of `SELECT` statements
@@ -479,8 +1070,18 @@ pub trait Visitor<'a> {
         }
     }
 
-    /// A visit to a value used in an expression
+    /// A visit to a value used in an expression. Expressions nested deeper
+    /// than [`max_depth`] fail with
+    /// [`QueryTooComplex`](crate::error::ErrorKind::QueryTooComplex) before
+    /// their visit overflows the stack.
     fn visit_expression(&mut self, value: Expression<'a>) -> Result {
+        // Not decremented on errors, they end the visit.
+        *self.depth() += 1;
+        if *self.depth() > max_depth() {
+            let kind = crate::error::ErrorKind::QueryTooComplex { depth: max_depth() };
+            return Err(crate::error::Error::builder(kind).build());
+        }
+
         match value.kind {
             ExpressionKind::Value(value) => self.visit_expression(*value)?,
             ExpressionKind::ConditionTree(tree) => self.visit_conditions(tree)?,
@@ -497,20 +1098,22 @@ pub trait Visitor<'a> {
             ExpressionKind::Values(values) => self.visit_values(*values)?,
             ExpressionKind::Asterisk(table) => match table {
                 Some(table) => {
-                    self.visit_table(*table, false)?;
+                    self.visit_qualifier(*table)?;
                     self.write(".*")?
                 }
                 None => self.write("*")?,
             },
+            ExpressionKind::AsteriskExcept(table, excluded) => self.visit_asterisk_except(*table, excluded)?,
+            ExpressionKind::Excluded(column) => self.visit_excluded(*column)?,
             ExpressionKind::Default => self.write("DEFAULT")?,
+            ExpressionKind::Cast(cast) => self.visit_cast(*cast)?,
         }
 
         if let Some(alias) = value.alias {
-            self.write(" AS ")?;
-
-            self.delimited_identifiers(&[&*alias])?;
+            self.visit_alias(&alias)?;
         };
 
+        *self.depth() -= 1;
         Ok(())
     }
 
@@ -539,6 +1142,122 @@ pub trait Visitor<'a> {
         })
     }
 
+    /// A `VALUES` list used as a table, `(VALUES (1, 2), (3, 4)) AS t(a, b)`.
+    fn visit_values_table(
+        &mut self,
+        values: Values<'a>,
+        alias: Option<Cow<'a, str>>,
+        columns: Vec<Cow<'a, str>>,
+    ) -> Result {
+        self.surround_with("(VALUES ", ")", |ref mut s| {
+            let len = values.len();
+            for (i, row) in values.into_iter().enumerate() {
+                s.visit_row(row)?;
+
+                if i < (len - 1) {
+                    s.write(",")?;
+                }
+            }
+            Ok(())
+        })?;
+
+        if let Some(alias) = alias {
+            self.write(" AS ")?;
+            self.delimited_identifiers(&[&*alias])?;
+            self.visit_column_aliases(columns)?;
+        }
+
+        Ok(())
+    }
+
+    /// The target of `ON CONFLICT`, `(a, b) WHERE ...` or `ON CONSTRAINT name`.
+    fn visit_conflict_target(&mut self, target: ConflictTarget<'a>) -> Result {
+        match target.typ {
+            ConflictTargetType::Columns(columns) => {
+                self.surround_with("(", ")", |ref mut s| {
+                    let len = columns.len();
+                    for (i, column) in columns.into_iter().enumerate() {
+                        s.visit_column(column.into_bare())?;
+
+                        if i < (len - 1) {
+                            s.write(", ")?;
+                        }
+                    }
+                    Ok(())
+                })?;
+
+                if let Some(conditions) = target.conditions {
+                    self.write(" WHERE ")?;
+                    self.visit_conditions(conditions)?;
+                }
+            }
+            ConflictTargetType::Constraint(name) => {
+                if target.conditions.is_some() {
+                    let kind = crate::error::ErrorKind::conversion(
+                        "A conflict target naming a constraint can not have conditions.",
+                    );
+                    return Err(crate::error::Error::builder(kind).build());
+                }
+
+                self.write("ON CONSTRAINT ")?;
+                self.delimited_identifiers(&[&*name])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The assignments of an upsert, `a = EXCLUDED.a, b = ...`.
+    fn visit_conflict_assignments(&mut self, assignments: Vec<(Column<'a>, Expression<'a>)>) -> Result {
+        if assignments.is_empty() {
+            let kind = crate::error::ErrorKind::IncompleteQuery { missing: "a column to update on conflict" };
+            return Err(crate::error::Error::builder(kind).build());
+        }
+
+        let len = assignments.len();
+
+        for (i, (key, value)) in assignments.into_iter().enumerate() {
+            let column = ColumnMeta::from(&key);
+            self.visit_column(key.into_bare())?;
+            self.write(" = ")?;
+            self.bind_parameters_to(Some(column));
+            self.visit_expression(value)?;
+            self.bind_parameters_to(None);
+
+            if i < (len - 1) {
+                self.write(", ")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The value an upsert proposed for a column, see
+    /// [`excluded`](crate::ast::excluded).
+    fn visit_excluded(&mut self, column: Column<'a>) -> Result {
+        self.write("EXCLUDED.")?;
+        self.visit_column(column.into_bare())
+    }
+
+    /// The column list of an aliased derived table, if any.
+    fn visit_column_aliases(&mut self, columns: Vec<Cow<'a, str>>) -> Result {
+        if columns.is_empty() {
+            return Ok(());
+        }
+
+        self.surround_with("(", ")", |ref mut s| {
+            let len = columns.len();
+            for (i, column) in columns.iter().enumerate() {
+                s.visit_identifier(column, false)?;
+
+                if i < (len - 1) {
+                    s.write(", ")?;
+                }
+            }
+            Ok(())
+        })
+    }
+
     /// A database table identifier
     fn visit_table(&mut self, table: Table<'a>, include_alias: bool) -> Result {
         match table.typ {
@@ -546,7 +1265,10 @@ pub trait Visitor<'a> {
                 Some(database) => self.delimited_identifiers(&[&*database, &*table_name])?,
                 None => self.delimited_identifiers(&[&*table_name])?,
             },
-            TableType::Values(values) => self.visit_values(values)?,
+            TableType::Values(values) => {
+                let alias = table.alias.filter(|_| include_alias);
+                return self.visit_values_table(values, alias, table.column_aliases);
+            }
             TableType::Query(select) => {
                 self.surround_with("(", ")", |ref mut s| s.visit_select(*select))?
             }
@@ -564,7 +1286,62 @@ pub trait Visitor<'a> {
                 self.write(" AS ")?;
 
                 self.delimited_identifiers(&[&*alias])?;
+                self.visit_column_aliases(table.column_aliases)?;
+            };
+        }
+
+        Ok(())
+    }
+
+    /// The table qualifying a column, its alias when it has one.
+    fn visit_qualifier(&mut self, table: Table<'a>) -> Result {
+        match table.alias {
+            Some(ref alias) => self.visit_identifier(alias, false),
+            None => self.visit_table(table, false),
+        }
+    }
+
+    /// The columns of a registered table but `excluded`, see
+    /// [`asterisk_except`](crate::ast::asterisk_except).
+    fn visit_asterisk_except(&mut self, table: Table<'a>, excluded: Vec<Cow<'a, str>>) -> Result {
+        let unexpandable = |table: &Table<'a>, reason: String| {
+            let table = match (&table.alias, &table.typ) {
+                (Some(alias), _) => crate::error::Name::available(&**alias),
+                (None, TableType::Table(name)) => crate::error::Name::available(&**name),
+                (None, _) => crate::error::Name::Unavailable,
             };
+            let kind = crate::error::ErrorKind::UnexpandableAsterisk { table, reason };
+            Err(crate::error::Error::builder(kind).build())
+        };
+
+        let schema = match &table.typ {
+            TableType::Table(name) => crate::registry::default_schema(name),
+            _ => return unexpandable(&table, "only the columns of a table are known, not of a derived one".to_string()),
+        };
+        let schema = match schema {
+            Some(schema) => schema,
+            None => {
+                return unexpandable(
+                    &table,
+                    "the table is not in the registry, set it with `registry::set_default_registry`".to_string(),
+                )
+            }
+        };
+        if let Some(name) = excluded.iter().find(|name| !schema.columns.iter().any(|column| column.name == &***name)) {
+            return unexpandable(&table, format!("`{}` is not one of its columns", name));
+        }
+
+        let mut columns = schema.columns.iter().filter(|column| !excluded.iter().any(|name| column.name == &**name)).peekable();
+        if columns.peek().is_none() {
+            return unexpandable(&table, "every column is excluded".to_string());
+        }
+        for (index, column) in columns.enumerate() {
+            if index > 0 {
+                self.write(", ")?;
+            }
+            self.visit_qualifier(table.clone())?;
+            self.write(".")?;
+            self.visit_identifier(column.name, false)?;
         }
 
         Ok(())
@@ -574,21 +1351,34 @@ pub trait Visitor<'a> {
     fn visit_column(&mut self, column: Column<'a>) -> Result {
         match column.table {
             Some(table) => {
-                self.visit_table(table, false)?;
+                self.visit_qualifier(table)?;
                 self.write(".")?;
-                self.delimited_identifiers(&[&*column.name])?;
+                self.visit_identifier(&column.name, column.quote)?;
             }
-            _ => self.delimited_identifiers(&[&*column.name])?,
+            _ => self.visit_identifier(&column.name, column.quote)?,
         };
 
         if let Some(alias) = column.alias {
-            self.write(" AS ")?;
-            self.delimited_identifiers(&[&*alias])?;
+            self.visit_alias(&alias)?;
         }
 
         Ok(())
     }
 
+    /// ` AS alias`, after an expression, a column or a function.
+    ///
+    /// Aliases are rendered in select lists, `FROM` and `JOIN` sources and
+    /// `RETURNING` or `OUTPUT` only. In a predicate, `a AS x = b` being no
+    /// valid SQL, they are dropped.
+    fn visit_alias(&mut self, alias: &str) -> Result {
+        if *self.predicates() > 0 {
+            tracing::debug!(alias, "alias dropped in a predicate");
+            return Ok(());
+        }
+        self.write(" AS ")?;
+        self.delimited_identifiers(&[alias])
+    }
+
     /// A row of data used as an expression
     fn visit_row(&mut self, row: Row<'a>) -> Result {
         self.surround_with("(", ")", |ref mut s| {
@@ -605,43 +1395,76 @@ pub trait Visitor<'a> {
         })
     }
 
-    /// A walk through the query conditions
-    fn visit_conditions(&mut self, tree: ConditionTree<'a>) -> Result {
-        match tree {
-            ConditionTree::And(expressions) => self.surround_with("(", ")", |ref mut s| {
-                let len = expressions.len();
-
-                for (i, expr) in expressions.into_iter().enumerate() {
-                    s.visit_expression(expr)?;
+    /// A row of values inserted into `columns`, binding every value to its
+    /// column.
+    fn visit_insert_row(&mut self, columns: &[ColumnMeta], row: Row<'a>) -> Result {
+        self.surround_with("(", ")", |ref mut s| {
+            let len = row.values.len();
+            for (i, value) in row.values.into_iter().enumerate() {
+                s.bind_parameters_to(columns.get(i).cloned());
+                s.visit_expression(value)?;
+                s.bind_parameters_to(None);
 
-                    if i < (len - 1) {
-                        s.write(" AND ")?;
-                    }
+                if i < (len - 1) {
+                    s.write(",")?;
                 }
+            }
 
-                Ok(())
-            }),
-            ConditionTree::Or(expressions) => self.surround_with("(", ")", |ref mut s| {
-                let len = expressions.len();
+            Ok(())
+        })
+    }
 
-                for (i, expr) in expressions.into_iter().enumerate() {
-                    s.visit_expression(expr)?;
+    /// A walk through the query conditions, predicates: the aliases of their
+    /// expressions are not rendered, see [`Visitor::visit_alias`].
+    fn visit_conditions(&mut self, tree: ConditionTree<'a>) -> Result {
+        *self.predicates() += 1;
+        // Walked with a stack of its own, nested conditions do not nest the
+        // calls: a filter of thousands of clauses could overflow the stack.
+        enum Step<'a> {
+            Tree(ConditionTree<'a>),
+            Expression(Expression<'a>),
+            Write(&'static str),
+        }
 
-                    if i < (len - 1) {
-                        s.write(" OR ")?;
-                    }
+        fn operands<'a>(steps: &mut Vec<Step<'a>>, expressions: Vec<Expression<'a>>, separator: &'static str) {
+            steps.push(Step::Write(")"));
+            for (i, expression) in expressions.into_iter().enumerate().rev() {
+                steps.push(Step::Expression(expression));
+                if i > 0 {
+                    steps.push(Step::Write(separator));
                 }
+            }
+            steps.push(Step::Write("("));
+        }
 
-                Ok(())
-            }),
-            ConditionTree::Not(expression) => self.surround_with("(", ")", |ref mut s| {
-                s.write("NOT ")?;
-                s.visit_expression(*expression)
-            }),
-            ConditionTree::Single(expression) => self.visit_expression(*expression),
-            ConditionTree::NoCondition => self.write("1=1"),
-            ConditionTree::NegativeCondition => self.write("1=0"),
+        let mut steps = vec![Step::Tree(tree)];
+
+        while let Some(step) = steps.pop() {
+            match step {
+                // Without operands, `AND` is always true and `OR` always false.
+                Step::Tree(ConditionTree::And(expressions)) if expressions.is_empty() => self.write("1=1")?,
+                Step::Tree(ConditionTree::Or(expressions)) if expressions.is_empty() => self.write("1=0")?,
+                Step::Tree(ConditionTree::And(expressions)) => operands(&mut steps, expressions, " AND "),
+                Step::Tree(ConditionTree::Or(expressions)) => operands(&mut steps, expressions, " OR "),
+                Step::Tree(ConditionTree::Not(expression)) => {
+                    steps.push(Step::Write(")"));
+                    steps.push(Step::Expression(*expression));
+                    steps.push(Step::Write("(NOT "));
+                }
+                Step::Tree(ConditionTree::Single(expression)) => steps.push(Step::Expression(*expression)),
+                Step::Tree(ConditionTree::NoCondition) => self.write("1=1")?,
+                Step::Tree(ConditionTree::NegativeCondition) => self.write("1=0")?,
+                Step::Expression(Expression {
+                    kind: ExpressionKind::ConditionTree(tree),
+                    alias: None,
+                }) => steps.push(Step::Tree(tree)),
+                Step::Expression(expression) => self.visit_expression(expression)?,
+                Step::Write(s) => self.write(s)?,
+            }
         }
+        *self.predicates() -= 1;
+
+        Ok(())
     }
 
     fn visit_greater_than(&mut self, left: Expression<'a>, right: Expression<'a>) -> Result {
@@ -672,9 +1495,11 @@ pub trait Visitor<'a> {
         self.visit_expression(right)
     }
 
-    /// A comparison expression
+    /// A comparison expression, a predicate: the aliases of its operands
+    /// are not rendered, see [`Visitor::visit_alias`].
     fn visit_compare(&mut self, compare: Compare<'a>) -> Result {
-        match compare {
+        *self.predicates() += 1;
+        let visited = match compare {
             Compare::Equals(left, right) => self.visit_equals(*left, *right),
             Compare::NotEquals(left, right) => self.visit_not_equals(*left, *right),
             Compare::LessThan(left, right) => self.visit_less_than(*left, *right),
@@ -893,6 +1718,10 @@ pub trait Visitor<'a> {
                 self.visit_expression(*column)?;
                 self.write(" IS NOT NULL")
             }
+            Compare::NotDistinctFrom(left, right) => {
+                self.visit_null_safe_equals(*left, *right, false)
+            }
+            Compare::DistinctFrom(left, right) => self.visit_null_safe_equals(*left, *right, true),
             Compare::Between(val, left, right) => {
                 self.visit_expression(*val)?;
                 self.write(" BETWEEN ")?;
@@ -907,6 +1736,14 @@ pub trait Visitor<'a> {
                 self.write(" AND ")?;
                 self.visit_expression(*right)
             }
+            Compare::Exists(selection) => {
+                self.write("EXISTS ")?;
+                self.surround_with("(", ")", |ref mut s| s.visit_selection(*selection))
+            }
+            Compare::NotExists(selection) => {
+                self.write("NOT EXISTS ")?;
+                self.surround_with("(", ")", |ref mut s| s.visit_selection(*selection))
+            }
             Compare::Raw(left, comp, right) => {
                 self.visit_expression(*left)?;
                 self.write(" ")?;
@@ -942,7 +1779,29 @@ pub trait Visitor<'a> {
             Compare::Matches(left, right) => self.visit_matches(*left, right, false),
             #[cfg(feature = "postgres")]
             Compare::NotMatches(left, right) => self.visit_matches(*left, right, true),
+        };
+        *self.predicates() -= 1;
+
+        visited
+    }
+
+    /// Equality treating `NULL`s as equal, `left IS NOT DISTINCT FROM right`,
+    /// or `left IS DISTINCT FROM right` when negated.
+    fn visit_null_safe_equals(
+        &mut self,
+        left: Expression<'a>,
+        right: Expression<'a>,
+        negate: bool,
+    ) -> Result {
+        self.visit_expression(left)?;
+
+        if negate {
+            self.write(" IS DISTINCT FROM ")?;
+        } else {
+            self.write(" IS NOT DISTINCT FROM ")?;
         }
+
+        self.visit_expression(right)
     }
 
     fn visit_equals(&mut self, left: Expression<'a>, right: Expression<'a>) -> Result {
@@ -1003,6 +1862,12 @@ pub trait Visitor<'a> {
         Ok(())
     }
 
+    fn visit_sum(&mut self, sum: Sum<'a>) -> Result {
+        self.write("SUM")?;
+        self.surround_with("(", ")", |ref mut s| s.visit_expression(*sum.expr))?;
+        Ok(())
+    }
+
     fn visit_function(&mut self, fun: Function<'a>) -> Result {
         match fun.typ_ {
             FunctionType::RowNumber(fun_rownum) => {
@@ -1015,6 +1880,15 @@ pub trait Visitor<'a> {
                     })?;
                 }
             }
+            FunctionType::Count(fun_count) if fun_count.distinct => {
+                self.write("COUNT")?;
+                self.surround_with("(DISTINCT ", ")", |ref mut s| {
+                    for expr in fun_count.exprs {
+                        s.visit_count_distinct(expr)?;
+                    }
+                    Ok(())
+                })?;
+            }
             FunctionType::Count(fun_count) => {
                 if fun_count.exprs.is_empty() {
                     self.write("COUNT(*)")?;
@@ -1026,7 +1900,7 @@ pub trait Visitor<'a> {
             FunctionType::AggregateToString(agg) => {
                 self.visit_aggregate_to_string(agg.value.as_ref().clone())?;
             }
-            #[cfg(all(feature = "json", feature = "postgres"))]
+            #[cfg(feature = "postgres")]
             FunctionType::RowToJson(row_to_json) => {
                 self.write("ROW_TO_JSON")?;
                 self.surround_with("(", ")", |ref mut s| s.visit_table(row_to_json.expr, false))?
@@ -1035,8 +1909,7 @@ pub trait Visitor<'a> {
                 self.visit_average(avg)?;
             }
             FunctionType::Sum(sum) => {
-                self.write("SUM")?;
-                self.surround_with("(", ")", |ref mut s| s.visit_expression(*sum.expr))?;
+                self.visit_sum(sum)?;
             }
             FunctionType::Lower(lower) => {
                 self.write("LOWER")?;
@@ -1058,6 +1931,32 @@ pub trait Visitor<'a> {
                 self.write("COALESCE")?;
                 self.surround_with("(", ")", |s| s.visit_columns(coalesce.exprs))?;
             }
+            FunctionType::NullIf(nullif) => {
+                self.write("NULLIF")?;
+                self.surround_with("(", ")", |s| {
+                    s.visit_expression(*nullif.left)?;
+                    s.write(", ")?;
+                    s.visit_expression(*nullif.right)
+                })?;
+            }
+            FunctionType::Concat(concat) => {
+                self.visit_concat(concat.exprs)?;
+            }
+            FunctionType::Length(length) => {
+                self.visit_length(*length.expression)?;
+            }
+            FunctionType::Trim(trim) => {
+                self.visit_trim(*trim.expression)?;
+            }
+            FunctionType::Now => {
+                self.visit_now()?;
+            }
+            FunctionType::CurrentDate => {
+                self.visit_current_date()?;
+            }
+            FunctionType::DateAdd(date_add) => {
+                self.visit_date_add(*date_add.expression, date_add.interval)?;
+            }
             #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
             FunctionType::JsonExtract(json_extract) => {
                 self.visit_json_extract(json_extract)?;
@@ -1066,16 +1965,113 @@ pub trait Visitor<'a> {
             FunctionType::TextSearch(text_search) => {
                 self.visit_text_search(text_search)?;
             }
+            #[cfg(feature = "postgres")]
+            FunctionType::JsonBuildObject(object) => {
+                self.write("json_build_object")?;
+                self.surround_with("(", ")", |s| {
+                    let len = object.members.len();
+                    for (i, (key, value)) in object.members.into_iter().enumerate() {
+                        s.visit_parameterized(Value::text(key))?;
+                        s.write(", ")?;
+                        s.visit_expression(value)?;
+
+                        if i < (len - 1) {
+                            s.write(", ")?;
+                        }
+                    }
+                    Ok(())
+                })?;
+            }
+            #[cfg(feature = "postgres")]
+            FunctionType::PgNotify(notify) => {
+                self.write("pg_notify")?;
+                self.surround_with("(", ")", |s| {
+                    s.visit_expression(*notify.channel)?;
+                    s.write(", ")?;
+                    s.visit_expression(*notify.payload)
+                })?;
+            }
         };
 
         if let Some(alias) = fun.alias {
-            self.write(" AS ")?;
-            self.delimited_identifiers(&[&*alias])?;
+            self.visit_alias(&alias)?;
         }
 
         Ok(())
     }
 
+    /// A conversion into another type, `CAST(expr AS type)`.
+    fn visit_cast(&mut self, cast: Cast<'a>) -> Result {
+        self.write("CAST")?;
+        self.surround_with("(", ")", |s| {
+            s.visit_expression(cast.expression)?;
+            s.write(" AS ")?;
+            s.write(Self::type_name(cast.target))
+        })
+    }
+
+    /// String concatenation, `(a || b)`.
+    fn visit_concat(&mut self, exprs: Vec<Expression<'a>>) -> Result {
+        let len = exprs.len();
+
+        self.surround_with("(", ")", |s| {
+            for (i, expr) in exprs.into_iter().enumerate() {
+                s.visit_expression(expr)?;
+
+                if i < (len - 1) {
+                    s.write(" || ")?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// The number of characters of a string, `LENGTH(expr)`.
+    fn visit_length(&mut self, expression: Expression<'a>) -> Result {
+        self.write("LENGTH")?;
+        self.surround_with("(", ")", |s| s.visit_expression(expression))
+    }
+
+    /// A string without its surrounding spaces, `TRIM(expr)`.
+    fn visit_trim(&mut self, expression: Expression<'a>) -> Result {
+        self.write("TRIM")?;
+        self.surround_with("(", ")", |s| s.visit_expression(expression))
+    }
+
+    /// The current timestamp.
+    fn visit_now(&mut self) -> Result {
+        self.write("NOW()")
+    }
+
+    /// The current date.
+    fn visit_current_date(&mut self) -> Result {
+        self.write("CURRENT_DATE")
+    }
+
+    /// A date shifted by an interval, `expr + INTERVAL '7 days'`.
+    fn visit_date_add(&mut self, expression: Expression<'a>, interval: IntervalArg) -> Result {
+        self.visit_expression(expression)?;
+
+        if interval.amount() < 0 {
+            self.write(" - ")?;
+        } else {
+            self.write(" + ")?;
+        }
+
+        let unit = match interval.unit() {
+            IntervalUnit::Day => "days",
+            IntervalUnit::Hour => "hours",
+            IntervalUnit::Minute => "minutes",
+        };
+
+        self.write(format!(
+            "INTERVAL '{} {}'",
+            (interval.amount() as i64).abs(),
+            unit
+        ))
+    }
+
     fn visit_partitioning(&mut self, over: Over<'a>) -> Result {
         if !over.partitioning.is_empty() {
             let len = over.partitioning.len();
@@ -1122,3 +2118,107 @@ pub trait Visitor<'a> {
         self.surround_with("(", ")", |ref mut s| s.visit_selection(selection))
     }
 }
+
+/// True if `identifier` can not be written as is: it is empty, a reserved
+/// word, not all lower case or has anything but letters, digits and
+/// underscores in it.
+fn requires_quoting<'a, V>(identifier: &str) -> bool
+where
+    V: Visitor<'a> + ?Sized,
+{
+    let mut chars = identifier.chars();
+
+    let starts_well = matches!(chars.next(), Some(c) if c.is_ascii_lowercase() || c == '_');
+    let plain = chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    !starts_well
+        || !plain
+        || V::RESERVED_WORDS
+            .binary_search(&identifier.to_ascii_uppercase().as_str())
+            .is_ok()
+}
+
+/// The parts of `sql` outside of string literals, quoted identifiers and
+/// comments. `brackets` also skips `[identifiers]`, `dollar_quotes` the
+/// `$tag$strings$tag$` of PostgreSQL.
+pub(crate) fn code_segments(sql: &str, brackets: bool, dollar_quotes: bool) -> Vec<&str> {
+    let bytes = sql.as_bytes();
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let end = match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => closing(bytes, i + 1, quote),
+            b'[' if brackets => closing(bytes, i + 1, b']'),
+            b'-' if bytes.get(i + 1) == Some(&b'-') => match sql[i..].find('\n') {
+                Some(newline) => i + newline + 1,
+                None => bytes.len(),
+            },
+            b'/' if bytes.get(i + 1) == Some(&b'*') => match sql[i + 2..].find("*/") {
+                Some(close) => i + 2 + close + 2,
+                None => bytes.len(),
+            },
+            b'$' if dollar_quotes => match dollar_tag(&sql[i..]) {
+                Some(tag) => match sql[i + tag.len()..].find(tag) {
+                    Some(close) => i + tag.len() + close + tag.len(),
+                    None => bytes.len(),
+                },
+                None => {
+                    i += 1;
+                    continue;
+                }
+            },
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        segments.push(&sql[start..i]);
+        start = end;
+        i = end;
+    }
+    segments.push(&sql[start..]);
+
+    segments
+}
+
+/// The position after the `quote` closing a literal opened before `from`,
+/// a doubled quote being part of it.
+fn closing(bytes: &[u8], mut from: usize, quote: u8) -> usize {
+    while from < bytes.len() {
+        if bytes[from] == quote {
+            if bytes.get(from + 1) == Some(&quote) && quote != b']' {
+                from += 2;
+                continue;
+            }
+            return from + 1;
+        }
+        from += 1;
+    }
+    bytes.len()
+}
+
+/// The `$tag$` opening a dollar-quoted string at the start of `sql`.
+fn dollar_tag(sql: &str) -> Option<&str> {
+    let tag_len = sql[1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))?;
+    let tag = &sql[..tag_len + 2];
+    // `$1` is a placeholder, a tag does not start with a digit.
+    let starts_with_digit = tag[1..].starts_with(|c: char| c.is_ascii_digit());
+    (tag.ends_with('$') && !starts_with_digit).then_some(tag)
+}
+
+/// The highest number of the placeholders `prefix` followed by a number in
+/// `segments`, `$1` or `@P1`.
+#[cfg(any(feature = "postgres", feature = "mssql"))]
+pub(crate) fn highest_numbered_placeholder(segments: &[&str], prefix: &str) -> usize {
+    segments
+        .iter()
+        .flat_map(|segment| segment.match_indices(prefix).map(move |(at, _)| &segment[at + prefix.len()..]))
+        .filter_map(|rest| {
+            let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            rest[..digits].parse::<usize>().ok()
+        })
+        .max()
+        .unwrap_or(0)
+}
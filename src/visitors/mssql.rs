@@ -1,37 +1,86 @@
-use super::Visitor;
+use super::{QuoteStyle, Visitor, VisitorBuffers};
 #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
-use crate::prelude::{JsonExtract, JsonType, TableType};
+use crate::prelude::{JsonExtract, JsonType};
+use crate::prelude::TableType;
 use crate::{
     ast::{
-        Column, Comparable, Expression, ExpressionKind, Insert, IntoRaw, Join, JoinData, Joinable,
-        Merge, OnConflict, Order, Ordering, Row, Table, TypeDataLength, TypeFamily, Value, Values,
+        Column, ColumnDefinition, ColumnMeta, Comparable, ConflictTarget, ConflictTargetType, Delete, Expression, ExpressionKind, Insert, IntervalArg, IntervalUnit, IntoRaw, Join, JoinData, Joinable,
+        LockStrength, LockWait, Merge, OnConflict, Order, Ordering, Row, Table, TypeDataLength,
+        TypeFamily, Value, Values,
     },
+    databases::check_parameters,
     error::{Error, ErrorKind},
-    prelude::{Aliasable, Average, Query},
+    prelude::{Aliasable, Average, Query, Sum},
     visitors,
 };
-use std::{convert::TryFrom, fmt::Write, iter};
+use std::{borrow::Cow, convert::TryFrom, fmt::Write, iter};
 
 static GENERATED_KEYS: &str = "@generated_keys";
 
 /// A visitor to generate queries for the SQL Server database.
 ///
 /// The returned parameter values can be used directly with the tiberius crate.
-#[cfg_attr(feature = "docs", doc(cfg(feature = "mssql")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "mssql")))]
 pub struct Mssql<'a> {
     query: String,
     parameters: Vec<Value<'a>>,
+    parameter_columns: Vec<Option<ColumnMeta>>,
+    /// The column parameters are bound to, set by `bind_parameters_to`.
+    column: Option<ColumnMeta>,
+    quote_style: QuoteStyle,
+    /// The number of expressions being visited.
+    depth: usize,
+    /// The number of predicates being visited, aliases are dropped in them.
+    predicates: usize,
     order_by_set: bool,
 }
 
+/// Reserved words of SQL Server, quoted even with [`QuoteStyle::Minimal`].
+const RESERVED_WORDS: &[&str] = &[
+    "ADD", "ALL", "ALTER", "AND", "ANY", "AS", "ASC", "AUTHORIZATION", "BACKUP", "BEGIN",
+    "BETWEEN", "BREAK", "BROWSE", "BULK", "BY", "CASCADE", "CASE", "CHECK", "CHECKPOINT", "CLOSE",
+    "CLUSTERED", "COALESCE", "COLLATE", "COLUMN", "COMMIT", "COMPUTE", "CONSTRAINT", "CONTAINS",
+    "CONTAINSTABLE", "CONTINUE", "CONVERT", "CREATE", "CROSS", "CURRENT", "CURRENT_DATE",
+    "CURRENT_TIME", "CURRENT_TIMESTAMP", "CURRENT_USER", "CURSOR", "DATABASE", "DBCC",
+    "DEALLOCATE", "DECLARE", "DEFAULT", "DELETE", "DENY", "DESC", "DISK", "DISTINCT",
+    "DISTRIBUTED", "DOUBLE", "DROP", "DUMP", "ELSE", "END", "ERRLVL", "ESCAPE", "EXCEPT", "EXEC",
+    "EXECUTE", "EXISTS", "EXIT", "EXTERNAL", "FALSE", "FETCH", "FILE", "FILLFACTOR", "FOR",
+    "FOREIGN", "FREETEXT", "FREETEXTTABLE", "FROM", "FULL", "FUNCTION", "GOTO", "GRANT", "GROUP",
+    "HAVING", "HOLDLOCK", "IDENTITY", "IDENTITYCOL", "IDENTITY_INSERT", "IF", "IN", "INDEX",
+    "INNER", "INSERT", "INTERSECT", "INTO", "IS", "JOIN", "KEY", "KILL", "LEFT", "LIKE", "LIMIT",
+    "LINENO", "LOAD", "MERGE", "NATIONAL", "NOCHECK", "NONCLUSTERED", "NOT", "NULL", "NULLIF",
+    "OF", "OFF", "OFFSETS", "ON", "OPEN", "OPENDATASOURCE", "OPENQUERY", "OPENROWSET", "OPENXML",
+    "OPTION", "OR", "ORDER", "OUTER", "OVER", "PERCENT", "PIVOT", "PLAN", "PRECISION", "PRIMARY",
+    "PRINT", "PROC", "PROCEDURE", "PUBLIC", "RAISERROR", "READ", "READTEXT", "RECONFIGURE",
+    "REFERENCES", "REPLICATION", "RESTORE", "RESTRICT", "RETURN", "REVERT", "REVOKE", "RIGHT",
+    "ROLLBACK", "ROWCOUNT", "ROWGUIDCOL", "RULE", "SAVE", "SCHEMA", "SECURITYAUDIT", "SELECT",
+    "SESSION_USER", "SET", "SETUSER", "SHUTDOWN", "SOME", "STATISTICS", "SYSTEM_USER", "TABLE",
+    "TABLESAMPLE", "TEXTSIZE", "THEN", "TO", "TOP", "TRAN", "TRANSACTION", "TRIGGER", "TRUE",
+    "TRUNCATE", "TRY_CONVERT", "TSEQUAL", "UNION", "UNIQUE", "UNPIVOT", "UPDATE", "UPDATETEXT",
+    "USE", "USER", "USING", "VALUES", "VARYING", "VIEW", "WAITFOR", "WHEN", "WHERE", "WHILE",
+    "WITH", "WITHIN", "WRITETEXT",
+];
+
 impl<'a> Mssql<'a> {
     // TODO: figure out that merge shit
+    /// `OUTPUT` into the table variable of the generated keys, selected after
+    /// the statement with `select_generated_keys`.
     fn visit_returning(&mut self, columns: Vec<Column<'a>>) -> visitors::Result {
+        self.visit_output(columns)?;
+        self.write(" INTO ")?;
+        self.write(GENERATED_KEYS)?;
+
+        Ok(())
+    }
+
+    /// `OUTPUT` the inserted values as the result of the statement.
+    fn visit_output(&mut self, columns: Vec<Column<'a>>) -> visitors::Result {
         let inserted_table = Table {
             typ: crate::ast::TableType::Table("Inserted".into()),
             alias: None,
             database: None,
             index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
         };
         let cols: Vec<_> = columns
             .into_iter()
@@ -49,53 +98,9 @@ impl<'a> Mssql<'a> {
             }
         }
 
-        self.write(" INTO ")?;
-        self.write(GENERATED_KEYS)?;
-
         Ok(())
     }
 
-    fn visit_type_family(&mut self, type_family: TypeFamily) -> visitors::Result {
-        match type_family {
-            TypeFamily::Text(len) => {
-                self.write("NVARCHAR(")?;
-                match len {
-                    Some(TypeDataLength::Constant(len)) => self.write(len)?,
-                    Some(TypeDataLength::Maximum) => self.write("MAX")?,
-                    None => self.write(4000)?,
-                }
-                self.write(")")
-            }
-            TypeFamily::Int => self.write("BIGINT"),
-            TypeFamily::Float => self.write("FLOAT(24)"),
-            TypeFamily::Double => self.write("FLOAT(53)"),
-            TypeFamily::Decimal(size) => {
-                self.write("DECIMAL(")?;
-                match size {
-                    Some((p, s)) => {
-                        self.write(p)?;
-                        self.write(",")?;
-                        self.write(s)?;
-                    }
-                    None => self.write("32,16")?,
-                }
-                self.write(")")
-            }
-            TypeFamily::Boolean => self.write("BIT"),
-            TypeFamily::Uuid => self.write("UNIQUEIDENTIFIER"),
-            TypeFamily::DateTime => self.write("DATETIMEOFFSET"),
-            TypeFamily::Bytes(len) => {
-                self.write("VARBINARY(")?;
-                match len {
-                    Some(TypeDataLength::Constant(len)) => self.write(len)?,
-                    Some(TypeDataLength::Maximum) => self.write("MAX")?,
-                    None => self.write(8000)?,
-                }
-                self.write(")")
-            }
-        }
-    }
-
     fn create_generated_keys(&mut self, columns: Vec<Column<'a>>) -> visitors::Result {
         self.write("DECLARE ")?;
         self.write(GENERATED_KEYS)?;
@@ -105,11 +110,11 @@ impl<'a> Mssql<'a> {
             let columns_len = columns.len();
 
             for (i, column) in columns.into_iter().enumerate() {
-                this.visit_column(column.clone())?;
+                this.visit_identifier(&column.name, column.quote)?;
                 this.write(" ")?;
 
                 match column.type_family {
-                    Some(type_family) => this.visit_type_family(type_family)?,
+                    Some(type_family) => this.write(Self::type_name(type_family))?,
                     None => this.write("NVARCHAR(255)")?,
                 }
 
@@ -136,6 +141,7 @@ impl<'a> Mssql<'a> {
             alias: None,
             database: None,
             index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
         };
 
         let g_table = Table {
@@ -143,6 +149,7 @@ impl<'a> Mssql<'a> {
             alias: None,
             database: None,
             index_definitions: Vec::new(),
+            column_aliases: Vec::new(),
         };
 
         let join = columns
@@ -173,13 +180,92 @@ impl<'a> Mssql<'a> {
 
         Ok(())
     }
+
+    /// `<`, `<=`, `>` and `>=`, expanding row comparisons SQL Server does not
+    /// support into the equivalent lexicographic conditions.
+    fn visit_ordering_comparison(
+        &mut self,
+        left: Expression<'a>,
+        right: Expression<'a>,
+        sign: &str,
+    ) -> visitors::Result {
+        match (left.kind, right.kind) {
+            (ExpressionKind::Row(left), ExpressionKind::Row(right)) => {
+                self.visit_row_comparison(left, right, sign)
+            }
+            (left_kind, right_kind) => {
+                let left = Expression {
+                    kind: left_kind,
+                    alias: left.alias,
+                };
+                let right = Expression {
+                    kind: right_kind,
+                    alias: right.alias,
+                };
+
+                self.visit_expression(left)?;
+                self.write(format!(" {} ", sign))?;
+                self.visit_expression(right)
+            }
+        }
+    }
+
+    /// `(a, b, c) >= (x, y, z)` as
+    /// `(a > x OR (a = x AND b > y) OR (a = x AND b = y AND c >= z))`.
+    fn visit_row_comparison(&mut self, left: Row<'a>, right: Row<'a>, sign: &str) -> visitors::Result {
+        if left.len() != right.len() || left.is_empty() {
+            let msg = "Row comparisons need rows of the same, non-zero length.";
+            let kind = ErrorKind::conversion(msg);
+
+            let mut builder = Error::builder(kind);
+            builder.set_original_message(msg);
+
+            return Err(builder.build());
+        }
+
+        // All but the last pair must differ strictly.
+        let strict = sign.trim_end_matches('=');
+        let pairs: Vec<_> = left.values.into_iter().zip(right.values).collect();
+        let len = pairs.len();
+
+        self.surround_with("(", ")", |this| {
+            for i in 0..len {
+                if i > 0 {
+                    this.write(" OR (")?;
+                }
+
+                for (left, right) in pairs[..i].iter().cloned() {
+                    this.visit_expression(left)?;
+                    this.write(" = ")?;
+                    this.visit_expression(right)?;
+                    this.write(" AND ")?;
+                }
+
+                let (left, right) = pairs[i].clone();
+                this.visit_expression(left)?;
+                this.write(format!(" {} ", if i == len - 1 { sign } else { strict }))?;
+                this.visit_expression(right)?;
+
+                if i > 0 {
+                    this.write(")")?;
+                }
+            }
+
+            Ok(())
+        })
+    }
 }
 
 impl<'a> Default for Mssql<'a> {
     fn default() -> Self {
         Mssql {
-            query: String::with_capacity(4096),
-            parameters: Vec::with_capacity(128),
+            query: String::new(),
+            parameters: Vec::new(),
+            parameter_columns: Vec::new(),
+            column: None,
+            quote_style: QuoteStyle::default(),
+            depth: 0,
+            predicates: 0,
             order_by_set: false,
         }
     }
@@ -189,21 +275,120 @@ impl<'a> Visitor<'a> for Mssql<'a> {
     const C_BACKTICK_OPEN: &'static str = "[";
     const C_BACKTICK_CLOSE: &'static str = "]";
     const C_WILDCARD: &'static str = "%";
+    const RESERVED_WORDS: &'static [&'static str] = RESERVED_WORDS;
 
-    #[tracing::instrument(name = "render_sql", skip(query))]
-    fn build<Q>(query: Q) -> crate::Result<(String, Vec<Value<'a>>)>
+    #[tracing::instrument(name = "render_sql", skip(query, buffers))]
+    fn build_into_with<Q>(query: Q, quote_style: QuoteStyle, buffers: &mut VisitorBuffers<'a>) -> visitors::Result
     where
         Q: Into<crate::ast::Query<'a>>,
     {
+        let VisitorBuffers {
+            query: sql,
+            parameters,
+            parameter_columns,
+        } = buffers.take();
         let mut this = Mssql {
-            query: String::with_capacity(4096),
-            parameters: Vec::with_capacity(128),
+            query: sql,
+            parameters,
+            parameter_columns,
+            column: None,
+            quote_style,
+            depth: 0,
+            predicates: 0,
             order_by_set: false,
         };
 
-        Mssql::visit_query(&mut this, query.into())?;
+        let visited = Mssql::visit_query(&mut this, query.into())
+            .and_then(|_| check_parameters::<sqlx::Mssql>(&mut this.parameters, &this.parameter_columns));
+        if visited.is_ok() {
+            super::format::trace_sql(&this.query, &this.parameters, &this.parameter_columns);
+        }
+        buffers.restore(this.query, this.parameters, this.parameter_columns);
+
+        visited
+    }
+
+    fn quote_style(&self) -> QuoteStyle {
+        self.quote_style
+    }
+
+    fn type_name(type_family: TypeFamily) -> Cow<'static, str> {
+        match type_family {
+            TypeFamily::Text(len) => match len {
+                Some(TypeDataLength::Constant(len)) => format!("NVARCHAR({})", len).into(),
+                Some(TypeDataLength::Maximum) => "NVARCHAR(MAX)".into(),
+                None => "NVARCHAR(4000)".into(),
+            },
+            TypeFamily::Int => "BIGINT".into(),
+            TypeFamily::Int32 => "INT".into(),
+            TypeFamily::Float => "FLOAT(24)".into(),
+            TypeFamily::Double => "FLOAT(53)".into(),
+            TypeFamily::Decimal(size) => match size {
+                Some((p, s)) => format!("DECIMAL({},{})", p, s).into(),
+                None => "DECIMAL(32,16)".into(),
+            },
+            TypeFamily::Boolean => "BIT".into(),
+            TypeFamily::Uuid => "UNIQUEIDENTIFIER".into(),
+            TypeFamily::DateTime => "DATETIMEOFFSET".into(),
+            TypeFamily::Bytes(len) => match len {
+                Some(TypeDataLength::Constant(len)) => format!("VARBINARY({})", len).into(),
+                Some(TypeDataLength::Maximum) => "VARBINARY(MAX)".into(),
+                None => "VARBINARY(8000)".into(),
+            },
+            TypeFamily::Json => "NVARCHAR(MAX)".into(),
+        }
+    }
+
+    fn visit_add_column(&mut self, table: Table<'a>, column: ColumnDefinition) -> visitors::Result {
+        self.write("ALTER TABLE ")?;
+        self.visit_table(table, false)?;
+        self.write(" ADD ")?;
+        self.visit_column_definition(column)
+    }
+
+    fn visit_delete_using(&mut self, delete: Delete<'a>) -> visitors::Result {
+        self.visit_multi_table_delete(delete)
+    }
+
+    /// `DELETE TOP` takes no order, the delete goes through a common table
+    /// expression selecting the rows.
+    fn visit_limited_delete(&mut self, delete: Delete<'a>) -> visitors::Result {
+        self.write("WITH [limited] AS (SELECT TOP (")?;
+        if let Some(limit) = delete.limit {
+            self.visit_parameterized(limit)?;
+        }
+        self.write(") * FROM ")?;
+        self.visit_table(delete.table, true)?;
+
+        if let Some(conditions) = delete.conditions {
+            self.write(" WHERE ")?;
+            self.visit_conditions(conditions)?;
+        }
+        if !delete.ordering.is_empty() {
+            self.write(" ORDER BY ")?;
+            self.visit_ordering(delete.ordering)?;
+        }
+        self.write(") DELETE FROM [limited]")
+    }
+
+    fn visit_generated_primary_key(&mut self) -> visitors::Result {
+        self.write(" IDENTITY(1,1) PRIMARY KEY")
+    }
+
+    /// T-SQL restates the type, `ALTER COLUMN name TYPE [NOT] NULL`.
+    fn visit_set_nullable(&mut self, table: Table<'a>, column: ColumnDefinition) -> visitors::Result {
+        let nullable = column.nullable;
+
+        self.write("ALTER TABLE ")?;
+        self.visit_table(table, false)?;
+        self.write(" ALTER COLUMN ")?;
+        self.visit_column_definition(column)?;
+
+        if nullable {
+            self.write(" NULL")?;
+        }
 
-        Ok((this.query, this.parameters))
+        Ok(())
     }
 
     fn write<D: std::fmt::Display>(&mut self, s: D) -> visitors::Result {
@@ -212,49 +397,100 @@ impl<'a> Visitor<'a> for Mssql<'a> {
     }
 
     fn add_parameter(&mut self, value: Value<'a>) {
-        self.parameters.push(value)
+        self.parameters.push(value);
+        self.parameter_columns.push(self.column.clone());
+    }
+
+    fn bind_parameters_to(&mut self, column: Option<ColumnMeta>) {
+        self.column = column;
+    }
+
+    fn depth(&mut self) -> &mut usize {
+        &mut self.depth
+    }
+
+    fn predicates(&mut self) -> &mut usize {
+        &mut self.predicates
     }
 
     /// A point to modify an incoming query to make it compatible with the
     /// SQL Server.
-    fn compatibility_modifications(&self, query: Query<'a>) -> Query<'a> {
+    fn compatibility_modifications(&self, query: Query<'a>) -> crate::Result<Query<'a>> {
         match query {
             // Finding possible `(a, b) (NOT) IN (SELECT x, y ...)` comparisons,
             // and replacing them with common table expressions.
-            Query::Select(select) => select
+            Query::Select(select) => Ok(select
                 .convert_tuple_selects_to_ctes(true, &mut 0)
                 .expect_left("Top-level query was right")
-                .into(),
+                .into()),
             // Replacing the `ON CONFLICT DO NOTHING` clause with a `MERGE` statement.
             Query::Insert(insert) => match insert.on_conflict {
-                Some(OnConflict::DoNothing) => Merge::try_from(*insert).unwrap().into(),
-                _ => Query::Insert(insert),
+                Some(OnConflict::DoNothing) => Ok(Merge::try_from(*insert)?.into()),
+                // Joining the `MERGE` on the target columns only, conditions
+                // and constraint names fail when visiting the insert.
+                Some(OnConflict::DoNothingOn(ConflictTarget {
+                    typ: ConflictTargetType::Columns(ref columns),
+                    conditions: None,
+                })) => {
+                    let columns = columns.clone();
+                    let mut insert = *insert;
+
+                    insert.table = insert.table.take().map(|mut table| {
+                        table.index_definitions.clear();
+                        table.add_unique_index(columns)
+                    });
+                    insert.on_conflict = Some(OnConflict::DoNothing);
+
+                    Ok(Merge::try_from(insert)?.into())
+                }
+                // An upsert updates the rows matched on the target columns.
+                Some(OnConflict::Update(
+                    ConflictTarget {
+                        typ: ConflictTargetType::Columns(ref columns),
+                        conditions: None,
+                    },
+                    _,
+                )) => {
+                    let columns = columns.clone();
+                    let mut insert = *insert;
+
+                    let assignments = match insert.on_conflict.take() {
+                        Some(OnConflict::Update(_, assignments)) => assignments,
+                        _ => unreachable!(),
+                    };
+
+                    insert.table = insert.table.take().map(|mut table| {
+                        table.index_definitions.clear();
+                        table.add_unique_index(columns)
+                    });
+                    insert.on_conflict = Some(OnConflict::DoNothing);
+
+                    Ok(Merge::try_from(insert)?.when_matched(assignments).into())
+                }
+                _ => Ok(Query::Insert(insert)),
             },
-            _ => query,
+            _ => Ok(query),
         }
     }
 
     fn visit_equals(&mut self, left: Expression<'a>, right: Expression<'a>) -> visitors::Result {
-        match (left.kind, right.kind) {
+        match (left, right) {
             // we can't compare with tuples, so we'll convert it to an AND
-            (ExpressionKind::Row(left), ExpressionKind::Row(right)) => {
+            (
+                Expression {
+                    kind: ExpressionKind::Row(left),
+                    ..
+                },
+                Expression {
+                    kind: ExpressionKind::Row(right),
+                    ..
+                },
+            ) => {
                 self.visit_multiple_tuple_comparison(left, Values::from(iter::once(right)), false)?;
             }
-            (left_kind, right_kind) => {
-                let (l_alias, r_alias) = (left.alias, right.alias);
-                let (left_xml, right_xml) = (left_kind.is_xml_value(), right_kind.is_xml_value());
-
-                let mut left = Expression::from(left_kind);
-
-                if let Some(alias) = l_alias {
-                    left = left.alias(alias);
-                }
-
-                let mut right = Expression::from(right_kind);
-
-                if let Some(alias) = r_alias {
-                    right = right.alias(alias);
-                }
+            // The aliases are dropped by `visit_expression`, in a predicate.
+            (left, right) => {
+                let (left_xml, right_xml) = (left.is_xml_value(), right.is_xml_value());
 
                 if right_xml {
                     self.surround_with("CAST(", " AS NVARCHAR(MAX))", |x| {
@@ -284,26 +520,23 @@ impl<'a> Visitor<'a> for Mssql<'a> {
         left: Expression<'a>,
         right: Expression<'a>,
     ) -> visitors::Result {
-        match (left.kind, right.kind) {
+        match (left, right) {
             // we can't compare with tuples, so we'll convert it to an AND
-            (ExpressionKind::Row(left), ExpressionKind::Row(right)) => {
+            (
+                Expression {
+                    kind: ExpressionKind::Row(left),
+                    ..
+                },
+                Expression {
+                    kind: ExpressionKind::Row(right),
+                    ..
+                },
+            ) => {
                 self.visit_multiple_tuple_comparison(left, Values::from(iter::once(right)), true)?;
             }
-            (left_kind, right_kind) => {
-                let (l_alias, r_alias) = (left.alias, right.alias);
-                let (left_xml, right_xml) = (left_kind.is_xml_value(), right_kind.is_xml_value());
-
-                let mut left = Expression::from(left_kind);
-
-                if let Some(alias) = l_alias {
-                    left = left.alias(alias);
-                }
-
-                let mut right = Expression::from(right_kind);
-
-                if let Some(alias) = r_alias {
-                    right = right.alias(alias);
-                }
+            // The aliases are dropped by `visit_expression`, in a predicate.
+            (left, right) => {
+                let (left_xml, right_xml) = (left.is_xml_value(), right.is_xml_value());
 
                 if right_xml {
                     self.surround_with("CAST(", " AS NVARCHAR(MAX))", |x| {
@@ -331,6 +564,10 @@ impl<'a> Visitor<'a> for Mssql<'a> {
     fn visit_raw_value(&mut self, value: Value<'a>) -> visitors::Result {
         let res = match value {
             Value::Integer(i) => i.map(|i| self.write(i)),
+            Value::I8(i) => i.map(|i| self.write(i)),
+            Value::I16(i) => i.map(|i| self.write(i)),
+            Value::I32(i) => i.map(|i| self.write(i)),
+            Value::I64(i) => i.map(|i| self.write(i)),
             Value::Float(d) => d.map(|f| match f {
                 f if f.is_nan() => self.write("'NaN'"),
                 f if f == f32::INFINITY => self.write("'Infinity'"),
@@ -358,9 +595,17 @@ impl<'a> Visitor<'a> for Mssql<'a> {
                 return Err(builder.build());
             }
             #[cfg(feature = "json")]
-            Value::Json(j) => {
-                j.map(|j| self.write(format!("'{}'", serde_json::to_string(&j).unwrap())))
-            }
+            Value::Json(j) => match j {
+                crate::ast::Json::JsonValue(Some(ref v)) => {
+                    let s = serde_json::to_string(&v)?;
+                    Some(self.write(format!("'{}'", s)))
+                }
+                crate::ast::Json::JsonRawValue(Some(ref v)) => {
+                    let s = serde_json::to_string(&**v)?;
+                    Some(self.write(format!("'{}'", s)))
+                }
+                _ => None,
+            },
             #[cfg(feature = "bigdecimal")]
             Value::Numeric(r) => r.map(|r| self.write(r)),
             #[cfg(feature = "uuid")]
@@ -389,6 +634,45 @@ impl<'a> Visitor<'a> for Mssql<'a> {
             // Style 3 is keep all whitespace + internal DTD processing:
             // https://docs.microsoft.com/en-us/sql/t-sql/functions/cast-and-convert-transact-sql?redirectedfrom=MSDN&view=sql-server-ver15#xml-styles
             Value::Xml(cow) => cow.map(|cow| self.write(format!("CONVERT(XML, N'{}', 3)", cow))),
+            #[cfg(feature = "bigdecimal")]
+            Value::BigDecimal(d) => d.map(|d| self.write(d)),
+            #[cfg(feature = "decimal")]
+            Value::Decimal(d) => d.map(|d| self.write(d)),
+            #[cfg(feature = "chrono")]
+            Value::UtcDateTime(dt) => dt.map(|dt| {
+                let s = format!("CONVERT(datetimeoffset, N'{}')", dt.to_rfc3339());
+                self.write(s)
+            }),
+            #[cfg(feature = "chrono")]
+            Value::LocalDateTime(dt) => dt.map(|dt| {
+                let s = format!("CONVERT(datetimeoffset, N'{}')", dt.to_rfc3339());
+                self.write(s)
+            }),
+            #[cfg(feature = "chrono")]
+            Value::NaiveDateTime(dt) => dt.map(|dt| {
+                let s = format!("CONVERT(datetime2, N'{}')", dt.format("%Y-%m-%dT%H:%M:%S%.f"));
+                self.write(s)
+            }),
+            #[cfg(feature = "chrono")]
+            Value::NaiveDate(date) => date.map(|date| {
+                let s = format!("CONVERT(date, N'{}')", date);
+                self.write(s)
+            }),
+            #[cfg(feature = "chrono")]
+            Value::NaiveTime(time) => time.map(|time| {
+                let s = format!("CONVERT(time, N'{}')", time);
+                self.write(s)
+            }),
+            #[cfg(feature = "postgres")]
+            Value::PgInterval(_) | Value::PgMoney(_) => {
+                let msg = "PostgreSQL intervals and money are not supported in T-SQL.";
+                let kind = ErrorKind::conversion(msg);
+
+                let mut builder = Error::builder(kind);
+                builder.set_original_message(msg);
+
+                return Err(builder.build());
+            }
         };
 
         match res {
@@ -444,11 +728,18 @@ impl<'a> Visitor<'a> for Mssql<'a> {
     }
 
     fn visit_insert(&mut self, insert: Insert<'a>) -> visitors::Result {
-        if let Some(returning) = insert.returning.as_ref().cloned() {
-            self.create_generated_keys(returning)?;
-            self.write(" ")?;
+        if let Some(OnConflict::DoNothingOn(_) | OnConflict::Update(..)) = insert.on_conflict {
+            let msg = "T-SQL can only target columns on conflict, without conditions.";
+            let kind = ErrorKind::conversion(msg);
+
+            let mut builder = Error::builder(kind);
+            builder.set_original_message(msg);
+
+            return Err(builder.build());
         }
 
+        // A plain insert outputs the keys directly, the table variable is only
+        // needed by `MERGE`.
         self.write("INSERT")?;
 
         if let Some(ref table) = insert.table {
@@ -456,27 +747,32 @@ impl<'a> Visitor<'a> for Mssql<'a> {
             self.visit_table(table.clone(), true)?;
         }
 
+        let bound: Vec<ColumnMeta> = insert.columns.iter().map(ColumnMeta::from).collect();
+        // The column list takes the names alone, `[foo].[bar]` is not valid there.
+        let columns: Vec<_> = insert.columns.into_iter().map(|c| c.into_bare()).collect();
+
         match insert.values {
             Expression {
                 kind: ExpressionKind::Row(row),
                 ..
             } => {
                 if row.values.is_empty() {
-                    if let Some(ref returning) = insert.returning {
-                        self.visit_returning(returning.clone())?;
+                    // `OUTPUT` precedes the values, `DEFAULT VALUES` included.
+                    if let Some(returning) = insert.returning {
+                        self.visit_output(returning)?;
                     }
 
                     self.write(" DEFAULT VALUES")?;
                 } else {
                     self.write(" ")?;
-                    self.visit_row(Row::from(insert.columns))?;
+                    self.visit_row(Row::from(columns))?;
 
-                    if let Some(ref returning) = insert.returning {
-                        self.visit_returning(returning.clone())?;
+                    if let Some(returning) = insert.returning {
+                        self.visit_output(returning)?;
                     }
 
                     self.write(" VALUES ")?;
-                    self.visit_row(row)?;
+                    self.visit_insert_row(&bound, row)?;
                 }
             }
             Expression {
@@ -484,30 +780,40 @@ impl<'a> Visitor<'a> for Mssql<'a> {
                 ..
             } => {
                 self.write(" ")?;
-                self.visit_row(Row::from(insert.columns))?;
+                self.visit_row(Row::from(columns))?;
 
-                if let Some(ref returning) = insert.returning {
-                    self.visit_returning(returning.clone())?;
+                if let Some(returning) = insert.returning {
+                    self.visit_output(returning)?;
                 }
 
                 self.write(" VALUES ")?;
 
                 let values_len = values.len();
                 for (i, row) in values.into_iter().enumerate() {
-                    self.visit_row(row)?;
+                    self.visit_insert_row(&bound, row)?;
 
                     if i < (values_len - 1) {
                         self.write(",")?;
                     }
                 }
             }
-            expr => self.surround_with("(", ")", |ref mut s| s.visit_expression(expr))?,
-        }
+            Expression {
+                kind: ExpressionKind::Selection(selection),
+                ..
+            } => {
+                if !columns.is_empty() {
+                    self.write(" ")?;
+                    self.visit_row(Row::from(columns))?;
+                }
 
-        if let Some(returning) = insert.returning {
-            let table = insert.table.unwrap();
-            self.write(" ")?;
-            self.select_generated_keys(returning, table)?;
+                if let Some(returning) = insert.returning {
+                    self.visit_output(returning)?;
+                }
+
+                self.write(" ")?;
+                self.visit_selection(selection)?;
+            }
+            expr => self.surround_with("(", ")", |ref mut s| s.visit_expression(expr))?,
         }
 
         Ok(())
@@ -535,9 +841,19 @@ impl<'a> Visitor<'a> for Mssql<'a> {
         self.write(" ON ")?;
         self.visit_conditions(merge.using.on_conditions)?;
 
+        if let Some(assignments) = merge.when_matched {
+            self.write(" WHEN MATCHED THEN UPDATE SET ")?;
+            self.visit_conflict_assignments(assignments)?;
+        }
+
         if let Some(query) = merge.when_not_matched {
             self.write(" WHEN NOT MATCHED THEN ")?;
-            self.visit_query(query)?;
+
+            // The insert has no table, the `MERGE` names it.
+            match query {
+                Query::Insert(insert) => self.visit_insert(*insert)?,
+                query => self.visit_query(query)?,
+            }
         }
 
         if let Some(columns) = merge.returning {
@@ -551,11 +867,42 @@ impl<'a> Visitor<'a> for Mssql<'a> {
         Ok(())
     }
 
+    /// The proposed values are the columns of the `MERGE` source.
+    fn visit_excluded(&mut self, column: Column<'a>) -> visitors::Result {
+        self.delimited_identifiers(&["dual"])?;
+        self.write(".")?;
+        self.visit_column(column.into_bare())
+    }
+
     fn parameter_substitution(&mut self) -> visitors::Result {
         self.write("@P")?;
         self.write(self.parameters.len())
     }
 
+    fn count_placeholders(sql: &str) -> usize {
+        super::highest_numbered_placeholder(&super::code_segments(sql, true, false), "@P")
+    }
+
+    /// Locks are taken with table hints, see `visit_lock_hints`.
+    fn visit_locking(&mut self, _: LockStrength, _: LockWait) -> visitors::Result {
+        Ok(())
+    }
+
+    fn visit_lock_hints(&mut self, strength: LockStrength, wait: LockWait) -> visitors::Result {
+        match strength {
+            LockStrength::Update => self.write(" WITH (UPDLOCK, ROWLOCK")?,
+            LockStrength::Share => self.write(" WITH (HOLDLOCK, ROWLOCK")?,
+        }
+
+        match wait {
+            LockWait::Wait => (),
+            LockWait::SkipLocked => self.write(", READPAST")?,
+            LockWait::NoWait => self.write(", NOWAIT")?,
+        }
+
+        self.write(")")
+    }
+
     fn visit_aggregate_to_string(&mut self, value: crate::ast::Expression<'a>) -> visitors::Result {
         self.write("STRING_AGG")?;
         self.surround_with("(", ")", |ref mut se| {
@@ -565,6 +912,107 @@ impl<'a> Visitor<'a> for Mssql<'a> {
         })
     }
 
+    fn visit_now(&mut self) -> visitors::Result {
+        self.write("SYSDATETIMEOFFSET()")
+    }
+
+    fn visit_current_date(&mut self) -> visitors::Result {
+        self.write("CAST(SYSDATETIMEOFFSET() AS DATE)")
+    }
+
+    // No null-safe comparison before SQL Server 2022, but INTERSECT compares
+    // NULLs as equal.
+    fn visit_null_safe_equals(
+        &mut self,
+        left: Expression<'a>,
+        right: Expression<'a>,
+        negate: bool,
+    ) -> visitors::Result {
+        if negate {
+            self.write("NOT ")?;
+        }
+
+        self.surround_with("EXISTS (", ")", |s| {
+            s.write("SELECT ")?;
+            s.visit_expression(left)?;
+            s.write(" INTERSECT SELECT ")?;
+            s.visit_expression(right)
+        })
+    }
+
+    // CONCAT takes at least two arguments.
+    fn visit_concat(&mut self, mut exprs: Vec<Expression<'a>>) -> visitors::Result {
+        if exprs.len() == 1 {
+            return self.visit_expression(exprs.remove(0));
+        }
+
+        self.write("CONCAT")?;
+        self.surround_with("(", ")", |s| s.visit_columns(exprs))
+    }
+
+    fn visit_length(&mut self, expression: Expression<'a>) -> visitors::Result {
+        self.write("LEN")?;
+        self.surround_with("(", ")", |s| s.visit_expression(expression))
+    }
+
+    // TRIM needs SQL Server 2017.
+    fn visit_trim(&mut self, expression: Expression<'a>) -> visitors::Result {
+        self.write("LTRIM(RTRIM")?;
+        self.surround_with("(", "))", |s| s.visit_expression(expression))
+    }
+
+    fn visit_values_table(
+        &mut self,
+        values: Values<'a>,
+        alias: Option<Cow<'a, str>>,
+        columns: Vec<Cow<'a, str>>,
+    ) -> visitors::Result {
+        if alias.is_some() && columns.is_empty() {
+            let msg = "A VALUES table needs column names in T-SQL, use `Table::alias_with_columns`.";
+            let kind = ErrorKind::conversion(msg);
+
+            let mut builder = Error::builder(kind);
+            builder.set_original_message(msg);
+
+            return Err(builder.build());
+        }
+
+        let values = values.cast_first_row();
+        self.surround_with("(VALUES ", ")", |ref mut s| {
+            let len = values.len();
+            for (i, row) in values.into_iter().enumerate() {
+                s.visit_row(row)?;
+
+                if i < (len - 1) {
+                    s.write(",")?;
+                }
+            }
+            Ok(())
+        })?;
+
+        if let Some(alias) = alias {
+            self.write(" AS ")?;
+            self.delimited_identifiers(&[&*alias])?;
+            self.visit_column_aliases(columns)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_date_add(&mut self, expression: Expression<'a>, interval: IntervalArg) -> visitors::Result {
+        let unit = match interval.unit() {
+            IntervalUnit::Day => "day",
+            IntervalUnit::Hour => "hour",
+            IntervalUnit::Minute => "minute",
+        };
+
+        self.write("DATEADD")?;
+        self.surround_with("(", ")", |s| {
+            s.write(format!("{}, {}, ", unit, interval.amount()))?;
+            s.visit_expression(expression)
+        })
+    }
+
     // MSSQL doesn't support tuples, we do AND/OR.
     fn visit_multiple_tuple_comparison(
         &mut self,
@@ -606,6 +1054,30 @@ impl<'a> Visitor<'a> for Mssql<'a> {
         })
     }
 
+    fn visit_greater_than(&mut self, left: Expression<'a>, right: Expression<'a>) -> visitors::Result {
+        self.visit_ordering_comparison(left, right, ">")
+    }
+
+    fn visit_greater_than_or_equals(
+        &mut self,
+        left: Expression<'a>,
+        right: Expression<'a>,
+    ) -> visitors::Result {
+        self.visit_ordering_comparison(left, right, ">=")
+    }
+
+    fn visit_less_than(&mut self, left: Expression<'a>, right: Expression<'a>) -> visitors::Result {
+        self.visit_ordering_comparison(left, right, "<")
+    }
+
+    fn visit_less_than_or_equals(
+        &mut self,
+        left: Expression<'a>,
+        right: Expression<'a>,
+    ) -> visitors::Result {
+        self.visit_ordering_comparison(left, right, "<=")
+    }
+
     fn visit_ordering(&mut self, ordering: Ordering<'a>) -> visitors::Result {
         let len = ordering.0.len();
 
@@ -645,6 +1117,29 @@ impl<'a> Visitor<'a> for Mssql<'a> {
         Ok(())
     }
 
+    fn visit_sum(&mut self, sum: Sum<'a>) -> visitors::Result {
+        self.write("SUM")?;
+
+        // SQL Server has no sum of bits, the booleans are converted to a
+        // decimal first, as for the average.
+        self.surround_with("(", ")", |ref mut s| match *sum.expr {
+            Expression {
+                kind: ExpressionKind::Column(column),
+                ..
+            } if column.type_family == Some(TypeFamily::Boolean) => {
+                s.write("CONVERT")?;
+
+                s.surround_with("(", ")", |ref mut s| {
+                    s.write("DECIMAL(32,16),")?;
+                    s.visit_column(*column)
+                })
+            }
+            expr => s.visit_expression(expr),
+        })?;
+
+        Ok(())
+    }
+
     #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
     fn visit_json_extract(&mut self, _json_extract: JsonExtract<'a>) -> visitors::Result {
         unimplemented!("JSON filtering is not yet supported on MSSQL")
@@ -713,7 +1208,7 @@ mod tests {
     use crate::{
         prelude::*,
         val,
-        visitors::{Mssql, Visitor},
+        visitors::{Mssql, QuoteStyle, Visitor},
     };
     use indoc::indoc;
 
@@ -769,18 +1264,59 @@ mod tests {
         assert_eq!(vec![Value::Integer(None)], params);
     }
 
-    #[derive(Entity)]
-    #[tablename = "musti"]
-    struct Musti {
-        foo: i32,
-        baz: i32,
-        bar: i32,
-        paw: String,
-        nose: String,
-    }
-
     #[test]
-    fn test_select_star_from() {
+    fn test_limited_delete() {
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i32,
+            created_at: i32,
+        }
+
+        let query = Delete::from_table(Event::table())
+            .so_that(Event::created_at.less_than(1_000))
+            .order_by(Event::created_at.descend())
+            .limit(500);
+        let (sql, params) = Mssql::build(query).unwrap();
+
+        assert_eq!("WITH [limited] AS (SELECT TOP (@P1) * FROM [events] WHERE [events].[created_at] < @P2 ORDER BY [events].[created_at] DESC) DELETE FROM [limited]", sql);
+        assert_eq!(vec![Value::from(500usize), Value::from(1_000)], params);
+
+        let err = Mssql::build(Delete::from_table(Event::table()).using(Event::table().alias("e")).limit(1)).unwrap_err();
+        assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidDelete(_)));
+    }
+
+    #[test]
+    fn test_aliases_are_dropped_in_predicates() {
+        #[derive(Entity)]
+        #[tablename = "test"]
+        struct Test {
+            #[column(primary_key)]
+            id: i32,
+        }
+
+        let subquery = Select::default().value(val!(4).alias("c"));
+        let conditions = val!(2).alias("x").equals(val!(3).alias("y")).and(Column::new("a").alias("b").in_selection(subquery));
+        let query = Select::from_table(Test::table()).value(val!(1).alias("one")).so_that(conditions);
+        let (sql, params) = Mssql::build(query).unwrap();
+
+        assert_eq!("SELECT @P1 AS [one] FROM [test] WHERE (@P2 = @P3 AND [a] IN (SELECT @P4 AS [c]))", sql);
+        assert_eq!(vec![Value::from(1), Value::from(2), Value::from(3), Value::from(4)], params);
+    }
+
+    #[derive(Entity)]
+    #[tablename = "musti"]
+    struct Musti {
+        foo: i32,
+        baz: i32,
+        bar: i32,
+        paw: String,
+        nose: String,
+    }
+
+    #[test]
+    fn test_select_star_from() {
         let expected_sql = "SELECT [musti].* FROM [musti]";
         let query = Select::from_table(Musti::table());
         let (sql, params) = Mssql::build(query).unwrap();
@@ -1247,6 +1783,25 @@ mod tests {
         assert_eq!(expected_sql, sql);
     }
 
+    #[test]
+    fn test_returning_insert_default() {
+        let insert = Insert::from(Insert::single_into(Foo::table())).returning(vec![Foo::bar]);
+        let (sql, params) = Mssql::build(insert).unwrap();
+
+        assert_eq!("INSERT INTO [foo] OUTPUT [Inserted].[bar] DEFAULT VALUES", sql);
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_insert_empty_values_on_conflict() {
+        let table = Foo::table().add_unique_index(Foo::bar);
+        let insert = Insert::from(Insert::multi_into(table, vec![Foo::bar])).on_conflict(OnConflict::DoNothing);
+
+        let err = Mssql::build(insert).unwrap_err();
+
+        assert!(matches!(err.kind(), crate::error::ErrorKind::ConversionError(_)));
+    }
+
     #[derive(Entity)]
     #[tablename = "bar"]
     struct Bar {
@@ -1324,6 +1879,18 @@ mod tests {
         assert!(params.is_empty());
     }
 
+    #[test]
+    fn test_raw_sized_ints() {
+        let select = Select::default()
+            .value(Value::I8(Some(-8)).raw())
+            .value(Value::I16(Some(16)).raw())
+            .value(Value::I32(Some(32)).raw())
+            .value(Value::I64(Some(64)).raw());
+        let (sql, params) = Mssql::build(select).unwrap();
+        assert_eq!("SELECT -8, 16, 32, 64", sql);
+        assert!(params.is_empty());
+    }
+
     #[test]
     fn test_raw_real() {
         let (sql, params) = Mssql::build(Select::default().value(1.3f64.raw())).unwrap();
@@ -1425,7 +1992,7 @@ mod tests {
         let (sql, params) = Mssql::build(insert).unwrap();
 
         assert_eq!(
-            "INSERT INTO [foo] ([foo].[bar],[foo].[wtf]) VALUES (@P1,@P2)",
+            "INSERT INTO [foo] ([bar],[wtf]) VALUES (@P1,@P2)",
             sql
         );
         assert_eq!(vec![Value::from("lol"), Value::from("meow")], params);
@@ -1446,11 +2013,62 @@ mod tests {
         let insert = Insert::single_into(Foo::table()).value(Foo::bar, "lol");
         let (sql, params) = Mssql::build(Insert::from(insert).returning(vec![Foo::bar])).unwrap();
 
-        assert_eq!("DECLARE @generated_keys table([bar] NVARCHAR(255)) INSERT INTO [foo] ([foo].[bar]) OUTPUT [Inserted].[bar] INTO @generated_keys VALUES (@P1) SELECT [t].[bar] FROM @generated_keys AS g INNER JOIN [foo] AS [t] ON [t].[bar] = [g].[bar] WHERE @@ROWCOUNT > 0", sql);
+        assert_eq!("INSERT INTO [foo] ([bar]) OUTPUT [Inserted].[bar] VALUES (@P1)", sql);
 
         assert_eq!(vec![Value::from("lol")], params);
     }
 
+    #[derive(Entity)]
+    #[tablename = "keyed"]
+    struct Keyed {
+        #[column(primary_key)]
+        id: i32,
+        #[column(length = 40)]
+        name: String,
+        uuid: sqlx::types::Uuid,
+        created_at: sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>,
+    }
+
+    fn keyed_columns() -> Vec<Column<'static>> {
+        vec![Keyed::id.into(), Keyed::name.into(), Keyed::uuid.into(), Keyed::created_at.into()]
+    }
+
+    #[test]
+    fn test_returning_insert_outputs_all_columns() {
+        let insert = Insert::single_into(Keyed::table()).value(Keyed::name, "lol");
+        let insert = Insert::from(insert).returning(keyed_columns());
+        let (sql, _) = Mssql::build(insert).unwrap();
+
+        assert_eq!(
+            "INSERT INTO [keyed] ([name]) OUTPUT [Inserted].[id],[Inserted].[name],[Inserted].[uuid],[Inserted].[created_at] VALUES (@P1)",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_returning_merge_typed_generated_keys() {
+        let insert = Insert::single_into(Keyed::table().add_unique_index(Keyed::name)).value(Keyed::name, "lol");
+        let insert = Insert::from(insert)
+            .on_conflict(OnConflict::DoNothing)
+            .returning(keyed_columns());
+        let (sql, _) = Mssql::build(insert).unwrap();
+
+        assert!(sql.starts_with(
+            "DECLARE @generated_keys table([id] INT,[name] NVARCHAR(40),[uuid] UNIQUEIDENTIFIER,[created_at] DATETIMEOFFSET) MERGE INTO [keyed]"
+        ), "{}", sql);
+    }
+
+    #[test]
+    fn test_returning_merge_without_type_family() {
+        let insert = Insert::single_into(Foo::table().add_unique_index(Foo::bar)).value(Foo::bar, "lol");
+        let insert = Insert::from(insert)
+            .on_conflict(OnConflict::DoNothing)
+            .returning(vec![Column::new("bar")]);
+        let (sql, _) = Mssql::build(insert).unwrap();
+
+        assert!(sql.starts_with("DECLARE @generated_keys table([bar] NVARCHAR(255))"), "{}", sql);
+    }
+
     #[test]
     fn test_multi_insert() {
         let insert = Insert::multi_into(Foo::table(), vec![Foo::bar, Foo::wtf])
@@ -1460,7 +2078,7 @@ mod tests {
         let (sql, params) = Mssql::build(insert).unwrap();
 
         assert_eq!(
-            "INSERT INTO [foo] ([foo].[bar],[foo].[wtf]) VALUES (@P1,@P2),(@P3,@P4)",
+            "INSERT INTO [foo] ([bar],[wtf]) VALUES (@P1,@P2),(@P3,@P4)",
             sql
         );
 
@@ -1500,6 +2118,105 @@ mod tests {
         assert_eq!(vec![Value::from("lol"), Value::from("meow")], params);
     }
 
+    #[test]
+    fn test_single_insert_conflict_do_nothing_on_target_columns() {
+        let insert: Insert<'_> = Insert::single_into(Foo::table())
+            .value(Foo::bar, "lol")
+            .value(Foo::wtf, "meow")
+            .into();
+        let target = ConflictTarget::columns(vec![Foo::bar]);
+
+        let (sql, params) =
+            Mssql::build(insert.on_conflict(OnConflict::DoNothingOn(target))).unwrap();
+
+        let expected_sql = indoc!(
+            "
+            MERGE INTO [foo]
+            USING (SELECT @P1 AS [bar], @P2 AS [wtf]) AS [dual] ([bar],[wtf])
+            ON [dual].[bar] = [foo].[bar]
+            WHEN NOT MATCHED THEN
+            INSERT ([bar],[wtf]) VALUES ([dual].[bar],[dual].[wtf]);
+        "
+        );
+
+        assert_eq!(expected_sql.replace('\n', " ").trim(), sql);
+        assert_eq!(vec![Value::from("lol"), Value::from("meow")], params);
+    }
+
+    #[test]
+    fn test_single_insert_conflict_do_nothing_on_constraint_fails() {
+        let insert: Insert<'_> = Insert::single_into(Foo::table()).value(Foo::bar, "lol").into();
+        let target = ConflictTarget::constraint("foo_bar_key");
+
+        assert!(Mssql::build(insert.on_conflict(OnConflict::DoNothingOn(target))).is_err());
+    }
+
+    #[test]
+    fn test_single_insert_conflict_update_with_excluded_values() {
+        let insert: Insert<'_> = Insert::single_into(Foo::table())
+            .value(Foo::bar, "lol")
+            .value(Foo::wtf, "meow")
+            .into();
+        let target = ConflictTarget::columns(vec![Foo::bar]);
+        let assignments = vec![(Foo::wtf.into(), excluded(Foo::wtf))];
+
+        let (sql, params) =
+            Mssql::build(insert.on_conflict(OnConflict::Update(target, assignments))).unwrap();
+
+        let expected_sql = indoc!(
+            "
+            MERGE INTO [foo]
+            USING (SELECT @P1 AS [bar], @P2 AS [wtf]) AS [dual] ([bar],[wtf])
+            ON [dual].[bar] = [foo].[bar]
+            WHEN MATCHED THEN UPDATE SET [wtf] = [dual].[wtf]
+            WHEN NOT MATCHED THEN
+            INSERT ([bar],[wtf]) VALUES ([dual].[bar],[dual].[wtf]);
+        "
+        );
+
+        assert_eq!(expected_sql.replace('\n', " ").trim(), sql);
+        assert_eq!(vec![Value::from("lol"), Value::from("meow")], params);
+    }
+
+    #[test]
+    fn test_single_insert_conflict_update_with_assignments() {
+        let insert: Insert<'_> = Insert::single_into(Foo::table())
+            .value(Foo::bar, "lol")
+            .value(Foo::wtf, "meow")
+            .into();
+        let target = ConflictTarget::columns(vec![Foo::bar]);
+        let assignments = vec![(Foo::wtf.into(), Expression::from("purr"))];
+
+        let (sql, params) =
+            Mssql::build(insert.on_conflict(OnConflict::Update(target, assignments))).unwrap();
+
+        let expected_sql = indoc!(
+            "
+            MERGE INTO [foo]
+            USING (SELECT @P1 AS [bar], @P2 AS [wtf]) AS [dual] ([bar],[wtf])
+            ON [dual].[bar] = [foo].[bar]
+            WHEN MATCHED THEN UPDATE SET [wtf] = @P3
+            WHEN NOT MATCHED THEN
+            INSERT ([bar],[wtf]) VALUES ([dual].[bar],[dual].[wtf]);
+        "
+        );
+
+        assert_eq!(expected_sql.replace('\n', " ").trim(), sql);
+        assert_eq!(
+            vec![Value::from("lol"), Value::from("meow"), Value::from("purr")],
+            params
+        );
+    }
+
+    #[test]
+    fn test_single_insert_conflict_update_on_constraint_fails() {
+        let insert: Insert<'_> = Insert::single_into(Foo::table()).value(Foo::bar, "lol").into();
+        let target = ConflictTarget::constraint("foo_bar_key");
+        let assignments = vec![(Foo::wtf.into(), excluded(Foo::wtf))];
+
+        assert!(Mssql::build(insert.on_conflict(OnConflict::Update(target, assignments))).is_err());
+    }
+
     #[test]
     fn test_single_insert_conflict_do_nothing_single_unique_with_default() {
         let unique_column = Column::from(Foo::bar).default("purr");
@@ -1540,7 +2257,7 @@ mod tests {
 
         let expected_sql = indoc!(
             "
-            DECLARE @generated_keys table([bar] NVARCHAR(255),[wtf] NVARCHAR(255))
+            DECLARE @generated_keys table([bar] NVARCHAR(4000),[wtf] NVARCHAR(4000))
             MERGE INTO [foo]
             USING (SELECT @P1 AS [bar], @P2 AS [wtf]) AS [dual] ([bar],[wtf])
             ON [dual].[bar] = [foo].[bar]
@@ -1764,6 +2481,194 @@ mod tests {
         );
     }
 
+    #[derive(Entity)]
+    #[tablename = "cats"]
+    struct Cat {
+        #[column(primary_key, autoincrement)]
+        id: i32,
+        #[column(default = "purr")]
+        sound: String,
+        #[column(server_generated)]
+        tag: String,
+        name: String,
+    }
+
+    #[test]
+    fn entity_defaults_are_part_of_the_join() {
+        let table = Cat::table().add_unique_index(Cat::sound).add_unique_index(Cat::name);
+
+        let insert: Insert<'_> = Insert::single_into(table).value(Cat::name, "meow").into();
+        let (sql, params) = Mssql::build(insert.on_conflict(OnConflict::DoNothing)).unwrap();
+
+        let expected_sql = indoc!(
+            "
+            MERGE INTO [cats]
+            USING (SELECT @P1 AS [name]) AS [dual] ([name])
+            ON ([cats].[sound] = @P2 OR [dual].[name] = [cats].[name])
+            WHEN NOT MATCHED THEN
+            INSERT ([name]) VALUES ([dual].[name]);
+        "
+        );
+
+        assert_eq!(expected_sql.replace('\n', " ").trim(), sql);
+        assert_eq!(vec![Value::from("meow"), Value::from("purr")], params);
+    }
+
+    #[test]
+    fn server_generated_entity_columns_are_left_out_of_the_join() {
+        let table = Cat::table()
+            .add_unique_index(Cat::id)
+            .add_unique_index(Cat::tag)
+            .add_unique_index(Cat::name);
+
+        let insert: Insert<'_> = Insert::single_into(table).value(Cat::name, "meow").into();
+        let (sql, params) = Mssql::build(insert.on_conflict(OnConflict::DoNothing)).unwrap();
+
+        let expected_sql = indoc!(
+            "
+            MERGE INTO [cats]
+            USING (SELECT @P1 AS [name]) AS [dual] ([name])
+            ON [dual].[name] = [cats].[name]
+            WHEN NOT MATCHED THEN
+            INSERT ([name]) VALUES ([dual].[name]);
+        "
+        );
+
+        assert_eq!(expected_sql.replace('\n', " ").trim(), sql);
+        assert_eq!(vec![Value::from("meow")], params);
+    }
+
+    #[test]
+    fn provided_server_generated_entity_columns_are_part_of_the_join() {
+        let table = Cat::table().add_unique_index(Cat::tag).add_unique_index(vec![Cat::sound, Cat::name]);
+
+        let insert: Insert<'_> = Insert::single_into(table)
+            .value(Cat::tag, "tabby")
+            .value(Cat::name, "meow")
+            .into();
+        let (sql, params) = Mssql::build(insert.on_conflict(OnConflict::DoNothing)).unwrap();
+
+        let expected_sql = indoc!(
+            "
+            MERGE INTO [cats]
+            USING (SELECT @P1 AS [tag], @P2 AS [name]) AS [dual] ([tag],[name])
+            ON ([dual].[tag] = [cats].[tag] OR ([cats].[sound] = @P3 AND [dual].[name] = [cats].[name]))
+            WHEN NOT MATCHED THEN
+            INSERT ([tag],[name]) VALUES ([dual].[tag],[dual].[name]);
+        "
+        );
+
+        assert_eq!(expected_sql.replace('\n', " ").trim(), sql);
+        assert_eq!(vec![Value::from("tabby"), Value::from("meow"), Value::from("purr")], params);
+    }
+
+    #[test]
+    fn test_select_from_values_with_column_names() {
+        use crate::values;
+
+        let expected_sql = "SELECT [vals].* FROM (VALUES (@P1,@P2),(@P3,@P4)) AS [vals]([a], [b])";
+        let values = Table::from(values!((1, 2), (3, 4))).alias_with_columns("vals", ["a", "b"]);
+        let (sql, params) = Mssql::build(Select::from_table(values)).unwrap();
+
+        assert_eq!(expected_sql, sql);
+        assert_eq!(
+            vec![
+                Value::integer(1),
+                Value::integer(2),
+                Value::integer(3),
+                Value::integer(4),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_select_from_typed_values() {
+        let expected_sql =
+            "SELECT [vals].* FROM (VALUES (CAST(@P1 AS BIGINT),CAST(@P2 AS NVARCHAR(4000))),(@P3,@P4)) AS [vals]([a], [b])";
+        let values = values!((1, "x"), (2, "y")).typed([TypeFamily::Int, TypeFamily::Text(None)]);
+        let values = Table::from(values).alias_with_columns("vals", ["a", "b"]);
+        let (sql, params) = Mssql::build(Select::from_table(values)).unwrap();
+
+        assert_eq!(expected_sql, sql);
+        assert_eq!(4, params.len());
+    }
+
+    #[test]
+    fn test_select_from_values_without_column_names() {
+        use crate::values;
+
+        let values = Table::from(values!((1, 2), (3, 4))).alias("vals");
+        let res = Mssql::build(Select::from_table(values));
+
+        assert!(matches!(
+            res.unwrap_err().kind(),
+            crate::error::ErrorKind::ConversionError(_)
+        ));
+    }
+
+    #[test]
+    fn test_alter_table() {
+        let bar = ColumnDefinition::new("bar", Some(TypeFamily::Text(None)), false);
+        let (sql, _) = Mssql::build(AlterTable::add_column(Foo::table(), bar.clone())).unwrap();
+        assert_eq!("ALTER TABLE [foo] ADD [bar] NVARCHAR(4000) NOT NULL", sql);
+
+        let bar = ColumnDefinition { nullable: true, ..bar };
+        let (sql, _) = Mssql::build(AlterTable::set_nullable(Foo::table(), bar)).unwrap();
+        assert_eq!("ALTER TABLE [foo] ALTER COLUMN [bar] NVARCHAR(4000) NULL", sql);
+    }
+
+    #[test]
+    fn test_create_table() {
+        #[derive(Entity)]
+        #[tablename = "comments"]
+        struct Comment {
+            #[column(primary_key, autoincrement)]
+            id: i64,
+            #[column(foreign_key = "posts.id")]
+            post_id: i64,
+            #[column(unique, length = 32)]
+            slug: String,
+            #[column(server_default = 0)]
+            likes: i32,
+            body: Option<String>,
+        }
+
+        let schema = Comment::schema();
+        let (sql, _) = Mssql::build(AlterTable::create_table(Comment::table(), TableDefinition::from(&schema))).unwrap();
+
+        let expected_sql = "CREATE TABLE [comments] ([id] BIGINT NOT NULL IDENTITY(1,1) PRIMARY KEY, [post_id] BIGINT NOT NULL, [slug] NVARCHAR(32) NOT NULL, [likes] INT NOT NULL DEFAULT 0, [body] NVARCHAR(4000), UNIQUE ([slug]), FOREIGN KEY ([post_id]) REFERENCES [posts] ([id]))";
+        assert_eq!(expected_sql, sql);
+    }
+
+    #[test]
+    fn test_delete_using() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            banned: bool,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "posts"]
+        struct Post {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+        }
+
+        let query = Delete::from_table(Post::table())
+            .using(User::table())
+            .so_that(Post::user_id.equals(User::id).and(User::banned.equals(true)));
+        let (sql, params) = Mssql::build(query).unwrap();
+
+        let expected_sql = "DELETE [posts] FROM [posts], [users] WHERE ([posts].[user_id] = [users].[id] AND [users].[banned] = @P1)";
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(true)], params);
+    }
+
     #[test]
     fn test_distinct() {
         let expected_sql = "SELECT DISTINCT [test].[bar] FROM [test]";
@@ -1800,12 +2705,13 @@ mod tests {
     }
     #[test]
     fn test_from() {
-        let expected_sql = "SELECT [foo].*, [bar].[a] FROM [foo], (SELECT [a] FROM [baz]) AS [bar]";
+        let expected_sql = "SELECT [foo].*, [bar].[a] FROM [foo], (SELECT [baz].[a] FROM [baz]) AS [bar]";
+        let bar = Table::from(Select::from_table(Baz::table()).column(Baz::a)).alias("bar");
         let query = Select::default()
             .and_from(Foo::table())
-            .and_from(Table::from(Select::from_table(Baz::table()).column(Baz::a)).alias("bar"))
+            .and_from(bar.clone())
             .value(Foo::table().asterisk())
-            .column(Baz::a);
+            .column(bar.col(Baz::a));
 
         let (sql, _) = Mssql::build(query).unwrap();
         assert_eq!(expected_sql, sql);
@@ -1982,7 +2888,7 @@ mod tests {
         let (sql, _) = Mssql::build(insert).unwrap();
 
         assert_eq!(
-            "INSERT INTO [foo] ([foo].[foo],[foo].[baz]) VALUES (@P1,DEFAULT)",
+            "INSERT INTO [foo] ([foo],[baz]) VALUES (@P1,DEFAULT)",
             sql
         );
     }
@@ -1993,8 +2899,8 @@ mod tests {
 
     #[test]
     fn join_is_inserted_positionally() {
-        let joined_table =
-            User::table().left_join(Post::table().alias("p").on(Post::user_id.equals(User::id)));
+        let posts = Post::table().alias("p");
+        let joined_table = User::table().left_join(posts.clone().on(posts.col(Post::user_id).equals(User::id)));
         let q = Select::from_table(joined_table).and_from(Toto::table());
         let (sql, _) = Mssql::build(q).unwrap();
 
@@ -2003,4 +2909,514 @@ mod tests {
             sql
         );
     }
+
+    #[derive(Entity)]
+    #[tablename = "jobs"]
+    struct Job {
+        id: i32,
+        done: bool,
+    }
+
+    #[test]
+    fn test_select_for_update() {
+        let query = Select::from_table(Job::table()).so_that(Job::done.equals(false)).for_update();
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT [jobs].* FROM [jobs] WITH (UPDLOCK, ROWLOCK) WHERE [jobs].[done] = @P1",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_select_for_update_skip_locked() {
+        let query = Select::from_table(Job::table().alias("j")).for_update().skip_locked();
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT [j].* FROM [jobs] AS [j] WITH (UPDLOCK, ROWLOCK, READPAST)",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_select_for_share_nowait() {
+        let query = Select::from_table(Job::table()).for_share().nowait();
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!("SELECT [jobs].* FROM [jobs] WITH (HOLDLOCK, ROWLOCK, NOWAIT)", sql);
+    }
+
+    #[test]
+    fn test_invalid_locking_combinations() {
+        let query = Select::from_table(Job::table()).for_update().for_share();
+        let err = Mssql::build(query).unwrap_err();
+
+        assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidLocking(_)));
+    }
+
+    #[derive(Entity)]
+    #[tablename = "sessions"]
+    struct Session {
+        id: i32,
+        last_seen: String,
+    }
+
+    #[test]
+    fn test_date_sub_from_now() {
+        let query = Select::from_table(Session::table())
+            .so_that(Session::last_seen.less_than(date_sub(now(), IntervalArg::days(7))));
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT [sessions].* FROM [sessions] WHERE [sessions].[last_seen] < DATEADD(day, -7, SYSDATETIMEOFFSET())",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_date_add_in_update() {
+        let query = Update::table(Session::table())
+            .set(Session::last_seen, date_add(now(), IntervalArg::minutes(90)));
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!("UPDATE [sessions] SET [last_seen] = DATEADD(minute, 90, SYSDATETIMEOFFSET())", sql);
+    }
+
+    #[test]
+    fn test_date_sub_from_current_date() {
+        let query = Select::from_table(Session::table())
+            .so_that(Session::last_seen.greater_than(date_sub(current_date(), IntervalArg::hours(3))));
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT [sessions].* FROM [sessions] WHERE [sessions].[last_seen] > DATEADD(hour, -3, CAST(SYSDATETIMEOFFSET() AS DATE))",
+            sql
+        );
+    }
+
+    #[derive(Entity)]
+    #[tablename = "profiles"]
+    struct Profile {
+        id: i32,
+        name: String,
+        nickname: Option<String>,
+        surname: String,
+    }
+
+    #[test]
+    fn test_concat() {
+        let query = Select::from_table(Profile::table())
+            .value(concat((Profile::name, " ", Profile::surname)).alias("full_name"))
+            .value(concat([Profile::name]));
+        let (sql, params) = Mssql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT CONCAT([profiles].[name], @P1, [profiles].[surname]) AS [full_name], [profiles].[name] FROM [profiles]",
+            sql
+        );
+        assert_eq!(vec![Value::from(" ")], params);
+    }
+
+    #[test]
+    fn test_string_functions() {
+        let query = Select::from_table(Profile::table())
+            .value(length(trim(Profile::name)))
+            .value(nullif(Profile::nickname, Profile::name));
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT LEN(LTRIM(RTRIM([profiles].[name]))), NULLIF([profiles].[nickname], [profiles].[name]) FROM [profiles]",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_null_safe_equality_of_columns() {
+        let query = Select::from_table(Profile::table()).so_that(Profile::nickname.is_not_distinct_from(Profile::name));
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT [profiles].* FROM [profiles] WHERE EXISTS (SELECT [profiles].[nickname] INTERSECT SELECT [profiles].[name])",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_null_safe_inequality_with_parameter() {
+        let nickname: Option<&str> = None;
+        let query = Select::from_table(Profile::table()).so_that(Profile::nickname.is_distinct_from(nickname));
+        let (sql, params) = Mssql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT [profiles].* FROM [profiles] WHERE NOT EXISTS (SELECT [profiles].[nickname] INTERSECT SELECT @P1)",
+            sql
+        );
+        assert_eq!(vec![Value::Text(None)], params);
+    }
+
+    #[derive(Entity)]
+    #[tablename = "tickets"]
+    struct Ticket {
+        id: i32,
+        order: i32,
+        #[column(quote)]
+        status: String,
+    }
+
+    #[test]
+    fn test_minimal_quoting() {
+        let query = Select::from_table(Ticket::table())
+            .column(Ticket::id)
+            .column(Ticket::order)
+            .column(Ticket::status)
+            .column(Column::new("createdAt"))
+            .column(Ticket::id.alias("Id"))
+            .so_that(Ticket::id.equals(1));
+        let (sql, _) = Mssql::build_with(query, QuoteStyle::Minimal).unwrap();
+
+        assert_eq!(
+            "SELECT tickets.id, tickets.[order], tickets.[status], [createdAt], tickets.id AS [Id] FROM tickets WHERE tickets.id = @P1",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_quote_in_identifier_is_escaped() {
+        let query = || Select::from_table(Ticket::table()).column(Column::new("we]ird"));
+
+        let (sql, _) = Mssql::build_with(query(), QuoteStyle::Always).unwrap();
+        assert_eq!("SELECT [we]]ird] FROM [tickets]", sql);
+
+        let (sql, _) = Mssql::build_with(query(), QuoteStyle::Minimal).unwrap();
+        assert_eq!("SELECT [we]]ird] FROM tickets", sql);
+    }
+
+    #[test]
+    fn test_hostile_identifiers_stay_within_one_identifier() {
+        let query = || {
+            Select::from_table(Ticket::table())
+                .column(Column::new("x] FROM users; --").alias("a]b"))
+        };
+
+        let (sql, _) = Mssql::build_with(query(), QuoteStyle::Always).unwrap();
+        assert_eq!("SELECT [x]] FROM users; --] AS [a]]b] FROM [tickets]", sql);
+
+        let (sql, _) = Mssql::build_with(query(), QuoteStyle::Minimal).unwrap();
+        assert_eq!("SELECT [x]] FROM users; --] AS [a]]b] FROM tickets", sql);
+    }
+
+    #[test]
+    fn test_reserved_words_are_sorted() {
+        assert!(Mssql::RESERVED_WORDS.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_cast_in_condition() {
+        let query = Select::from_table(Job::table())
+            .so_that(cast(Job::id, TypeFamily::Text(None)).equals("42"));
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!("SELECT [jobs].* FROM [jobs] WHERE CAST([jobs].[id] AS NVARCHAR(4000)) = @P1", sql);
+    }
+
+    #[test]
+    fn test_cast_of_an_operation() {
+        let total = Expression::from(Job::id) + Expression::from(1);
+        let query = Select::from_table(Job::table())
+            .value(total.cast(TypeFamily::Decimal(Some((10, 2)))).alias("total"));
+        let (sql, _) = Mssql::build(query).unwrap();
+
+        assert_eq!("SELECT CAST(([jobs].[id] + @P1) AS DECIMAL(10,2)) AS [total] FROM [jobs]", sql);
+    }
+
+    #[test]
+    fn test_keyset_pagination() {
+        let query = Select::from_table(TestEntity::table()).after((TestEntity::id1, TestEntity::id2), (1, 2));
+        let (sql, params) = Mssql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT [test].* FROM [test] WHERE ([test].[id1] > @P1 OR ([test].[id1] = @P2 AND [test].[id2] > @P3))",
+            sql
+        );
+        assert_eq!(vec![Value::from(1), Value::from(1), Value::from(2)], params);
+    }
+
+    #[test]
+    fn test_row_comparison_expansion() {
+        let columns = Row::from((TestEntity::id1, TestEntity::id2, TestEntity::bar));
+        let query = Select::from_table(TestEntity::table()).so_that(columns.less_than_or_equals(Row::from((1, 2, "c"))));
+        let (sql, params) = Mssql::build(query).unwrap();
+
+        // Lexicographic: only the last column may be equal.
+        assert_eq!(
+            "SELECT [test].* FROM [test] WHERE ([test].[id1] < @P1 OR ([test].[id1] = @P2 AND [test].[id2] < @P3) \
+             OR ([test].[id1] = @P4 AND [test].[id2] = @P5 AND [test].[bar] <= @P6))",
+            sql
+        );
+        assert_eq!(
+            vec![
+                Value::from(1),
+                Value::from(1),
+                Value::from(2),
+                Value::from(1),
+                Value::from(2),
+                Value::from("c"),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_row_comparison_of_different_lengths() {
+        let columns = Row::from((TestEntity::id1, TestEntity::id2));
+        let query = Select::from_table(TestEntity::table()).so_that(columns.greater_than(Row::from((1,))));
+
+        assert!(matches!(
+            Mssql::build(query).unwrap_err().kind(),
+            crate::error::ErrorKind::ConversionError(_)
+        ));
+    }
+
+    #[test]
+    fn test_count_distinct() {
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            session_id: i32,
+        }
+
+        let query = Select::from_table(Event::table()).value(count_distinct(Event::user_id));
+        let (sql, _) = Mssql::build(query).unwrap();
+        assert_eq!("SELECT COUNT(DISTINCT [events].[user_id]) FROM [events]", sql);
+
+        let query = Select::from_table(Event::table()).value(count_distinct(Row::from((Event::user_id, Event::session_id))));
+        assert!(matches!(
+            Mssql::build(query).unwrap_err().kind(),
+            crate::error::ErrorKind::CountDistinctColumns { columns: 2 }
+        ));
+    }
+
+    #[test]
+    fn test_aggregates() {
+        #[derive(Entity)]
+        #[tablename = "orders"]
+        struct Order {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            total: i32,
+            paid: bool,
+        }
+
+        let query = Select::from_table(Order::table()).value(count_star()).so_that(Order::user_id.equals(1));
+        let (sql, _) = Mssql::build(query).unwrap();
+        assert_eq!("SELECT COUNT(*) FROM [orders] WHERE [orders].[user_id] = @P1", sql);
+
+        let query = Select::from_table(Order::table())
+            .value(count(Order::id))
+            .value(sum(Order::total))
+            .value(min(Order::total))
+            .value(max(Order::total));
+        let (sql, _) = Mssql::build(query).unwrap();
+        assert_eq!("SELECT COUNT([orders].[id]), SUM([orders].[total]), MIN([orders].[total]), MAX([orders].[total]) FROM [orders]", sql);
+
+        let query = Select::from_table(Order::table()).value(sum(Order::paid));
+        let (sql, _) = Mssql::build(query).unwrap();
+        assert_eq!("SELECT SUM(CONVERT(DECIMAL(32,16),[orders].[paid])) FROM [orders]", sql);
+    }
+
+    /// The numbers of the `@Pn` placeholders of `sql`, in order.
+    fn placeholders(sql: &str) -> Vec<usize> {
+        sql.split("@P").skip(1).map(|rest| rest.chars().take_while(char::is_ascii_digit).collect::<String>().parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_nested_selects_are_renumbered() {
+        #[derive(Entity)]
+        #[tablename = "orders"]
+        struct Order {
+            #[column(primary_key)]
+            id: i32,
+            customer_id: i32,
+            status: String,
+            total: f64,
+        }
+
+        let inner = Select::from_table(Order::table())
+            .column(Order::customer_id)
+            .so_that(Order::status.equals("paid").and(Order::total.greater_than(10.0)).and(Order::id.less_than(1000)));
+        let (_, inner_params) = Mssql::build(inner.clone()).unwrap();
+        assert_eq!(3, inner_params.len());
+
+        let outer = Select::from_table(Table::from(inner.clone()).alias("paid"))
+            .with(inner.clone().into_cte("recent"))
+            .value(inner.clone())
+            .so_that(Order::customer_id.in_selection(inner.clone()))
+            .and_where(Order::status.not_equals("void"))
+            .and_where(exists(inner));
+        let (sql, params) = Mssql::build(outer).unwrap();
+
+        assert_eq!((1..=16).collect::<Vec<_>>(), placeholders(&sql), "{}", sql);
+        assert_eq!(16, params.len());
+        let mut expected: Vec<_> = inner_params.iter().cloned().cycle().take(12).collect();
+        expected.insert(12, Value::from("void"));
+        expected.extend(inner_params);
+        assert_eq!(expected, params, "{}", sql);
+    }
+
+    #[test]
+    fn test_exists() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            banned: bool,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "posts"]
+        struct Post {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            published: bool,
+            flagged: bool,
+        }
+
+        let posts = || Select::from_table(Post::table()).value(1.raw());
+        let query = Select::from_table(User::table())
+            .so_that(User::banned.equals(false))
+            .and_where(exists(posts().so_that(Post::user_id.equals(User::id).and(Post::published.equals(true)))))
+            .and_where(not_exists(posts().so_that(Post::user_id.equals(User::id).and(Post::flagged.equals(true)))));
+        let (sql, params) = Mssql::build(query).unwrap();
+
+        let expected_sql = "SELECT [users].* FROM [users] WHERE ([users].[banned] = @P1 AND EXISTS (SELECT 1 FROM [posts] WHERE ([posts].[user_id] = [users].[id] AND [posts].[published] = @P2)) AND NOT EXISTS (SELECT 1 FROM [posts] WHERE ([posts].[user_id] = [users].[id] AND [posts].[flagged] = @P3)))";
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(false), Value::from(true), Value::from(true)], params);
+    }
+
+    #[test]
+    fn test_typed_columns_in_selection() {
+        #[derive(Entity)]
+        #[tablename = "orders"]
+        struct Order {
+            #[column(primary_key)]
+            id: i64,
+            region: String,
+            year: i32,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "archives"]
+        struct Archive {
+            #[column(primary_key)]
+            order_id: i64,
+            region: String,
+            year: i32,
+            purged: bool,
+        }
+
+        let archived = || Select::from_table(Archive::table()).so_that(Archive::purged.equals(true));
+
+        let query = Select::from_table(Order::table())
+            .so_that(Order::id.in_selection(archived().column(Archive::order_id)));
+        let (sql, params) = Mssql::build(query).unwrap();
+        assert_eq!(
+            "SELECT [orders].* FROM [orders] WHERE [orders].[id] IN (SELECT [archives].[order_id] FROM [archives] WHERE [archives].[purged] = @P1)",
+            sql
+        );
+        assert_eq!(vec![Value::from(true)], params);
+
+        let query = Select::from_table(Order::table()).so_that(
+            Row::from((Order::id, Order::region))
+                .in_selection(archived().column(Archive::order_id).column(Archive::region)),
+        );
+        let expected_sql = indoc!(
+            r#"WITH [cte_0] AS (SELECT [archives].[order_id], [archives].[region] FROM [archives] WHERE [archives].[purged] = @P1)
+            SELECT [orders].* FROM [orders]
+            WHERE [orders].[id] IN (SELECT [order_id] FROM [cte_0] WHERE [region] = [orders].[region])"#
+        )
+        .replace('\n', " ");
+        let (sql, params) = Mssql::build(query).unwrap();
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(true)], params);
+
+        let query = Select::from_table(Order::table()).so_that(
+            Row::from((Order::id, Order::region, Order::year)).not_in_selection(
+                archived()
+                    .column(Archive::order_id)
+                    .column(Archive::region)
+                    .column(Archive::year),
+            ),
+        );
+        let expected_sql = indoc!(
+            r#"WITH [cte_0] AS (SELECT [archives].[order_id], [archives].[region], [archives].[year] FROM [archives] WHERE [archives].[purged] = @P1)
+            SELECT [orders].* FROM [orders]
+            WHERE [orders].[id] NOT IN (SELECT [order_id] FROM [cte_0] WHERE ([region] = [orders].[region] AND [year] = [orders].[year]))"#
+        )
+        .replace('\n', " ");
+        let (sql, params) = Mssql::build(query).unwrap();
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(true)], params);
+    }
+
+    #[test]
+    fn test_empty_select_is_incomplete() {
+        let err = Mssql::build(Select::default()).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table or a value to select" }
+        ));
+    }
+
+    #[test]
+    fn test_select_with_only_ordering_is_incomplete() {
+        let query = Select::default().order_by(Foo::bar.descend());
+        let err = Mssql::build(query).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table or a value to select" }
+        ));
+    }
+
+    #[test]
+    fn test_insert_without_table_is_incomplete() {
+        let insert: Insert = Insert::single().value(Foo::bar, "a@b.c").into();
+        let err = Mssql::build(insert).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table to insert into" }
+        ));
+    }
+
+    #[test]
+    fn test_update_without_assignments_is_incomplete() {
+        let query = Update::table(Foo::table()).so_that(Foo::bar.equals("a@b.c"));
+        let err = Mssql::build(query).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a column to update" }
+        ));
+    }
+
+    #[test]
+    fn test_upsert_without_assignments_is_incomplete() {
+        let insert: Insert = Insert::single_into(Foo::table()).value(Foo::bar, "a@b.c").into();
+        let target = ConflictTarget::columns(vec![Foo::bar]);
+        let err = Mssql::build(insert.on_conflict(OnConflict::Update(target, Vec::new()))).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a column to update on conflict" }
+        ));
+    }
 }
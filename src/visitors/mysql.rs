@@ -1,21 +1,103 @@
-use std::fmt::{self, Write};
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
 
 use crate::{
     ast::*,
+    databases::check_parameters,
     error::{Error, ErrorKind},
-    visitors::{self, Visitor},
+    visitors::{self, QuoteStyle, Visitor, VisitorBuffers},
 };
 
 /// A visitor to generate queries for the MySQL database.
 ///
 /// The returned parameter values can be used directly with the mysql crate.
-#[cfg_attr(feature = "docs", doc(cfg(feature = "mysql")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "mysql")))]
 pub struct Mysql<'a> {
     query: String,
     parameters: Vec<Value<'a>>,
+    parameter_columns: Vec<Option<ColumnMeta>>,
+    /// The column parameters are bound to, set by `bind_parameters_to`.
+    column: Option<ColumnMeta>,
+    quote_style: QuoteStyle,
+    /// The number of expressions being visited.
+    depth: usize,
+    /// The number of predicates being visited, aliases are dropped in them.
+    predicates: usize,
+    version: MysqlVersion,
+}
+
+/// The MySQL server a [`Mysql`] visitor renders for, where the supported
+/// syntax differs.
+#[cfg_attr(docsrs, doc(cfg(feature = "mysql")))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MysqlVersion {
+    /// MySQL 5.7, without table value constructors. A `VALUES` table is
+    /// emulated with a `UNION ALL` of selects.
+    V5_7,
+    /// MySQL 8.0.19 or later.
+    #[default]
+    V8,
 }
 
+/// Reserved words of MySQL, quoted even with [`QuoteStyle::Minimal`].
+const RESERVED_WORDS: &[&str] = &[
+    "ADD", "ALL", "ALTER", "ANALYZE", "AND", "ANY", "AS", "ASC", "BEFORE", "BETWEEN", "BOTH", "BY",
+    "CALL", "CASCADE", "CASE", "CHANGE", "CHAR", "CHARACTER", "CHECK", "COLLATE", "COLUMN",
+    "CONDITION", "CONSTRAINT", "CONTINUE", "CONVERT", "CREATE", "CROSS", "CURRENT_DATE",
+    "CURRENT_TIME", "CURRENT_TIMESTAMP", "CURRENT_USER", "CURSOR", "DATABASE", "DATABASES",
+    "DECIMAL", "DECLARE", "DEFAULT", "DELAYED", "DELETE", "DESC", "DESCRIBE", "DISTINCT", "DIV",
+    "DOUBLE", "DROP", "DUAL", "EACH", "ELSE", "ELSEIF", "END", "ESCAPED", "EXCEPT", "EXISTS",
+    "EXIT", "EXPLAIN", "FALSE", "FETCH", "FLOAT", "FOR", "FORCE", "FOREIGN", "FROM", "FULL",
+    "FULLTEXT", "GENERATED", "GRANT", "GROUP", "HAVING", "HIGH_PRIORITY", "IF", "IGNORE", "IN",
+    "INDEX", "INFILE", "INNER", "INSERT", "INT", "INTEGER", "INTERSECT", "INTERVAL", "INTO", "IS",
+    "ITERATE", "JOIN", "KEY", "KEYS", "KILL", "LEADING", "LEAVE", "LEFT", "LIKE", "LIMIT",
+    "LINEAR", "LINES", "LOAD", "LOCALTIME", "LOCALTIMESTAMP", "LOCK", "LONG", "LOOP", "MATCH",
+    "MOD", "NATURAL", "NOT", "NULL", "NUMERIC", "ON", "OPTIMIZE", "OPTION", "OR", "ORDER", "OUT",
+    "OUTER", "OUTFILE", "PARTITION", "PRECISION", "PRIMARY", "PROCEDURE", "RANGE", "RANK", "READ",
+    "REAL", "REFERENCES", "REGEXP", "RELEASE", "RENAME", "REPEAT", "REPLACE", "REQUIRE",
+    "RESIGNAL", "RESTRICT", "RETURN", "REVOKE", "RIGHT", "RLIKE", "ROW", "ROWS", "SCHEMA",
+    "SCHEMAS", "SELECT", "SEPARATOR", "SET", "SHOW", "SIGNAL", "SPATIAL", "SQL", "STARTING",
+    "STORED", "STRAIGHT_JOIN", "SYSTEM", "TABLE", "TERMINATED", "THEN", "TO", "TRAILING",
+    "TRIGGER", "TRUE", "UNDO", "UNION", "UNIQUE", "UNLOCK", "UNSIGNED", "UPDATE", "USAGE", "USE",
+    "USING", "UTC_DATE", "UTC_TIME", "UTC_TIMESTAMP", "VALUES", "VARCHAR", "VIRTUAL", "WHEN",
+    "WHERE", "WHILE", "WINDOW", "WITH", "WRITE", "XOR", "ZEROFILL",
+];
+
 impl<'a> Mysql<'a> {
+    /// Like [`Visitor::build`], for a specific MySQL version.
+    ///
+    /// ```rust
+    /// # use xiayu::{ast::*, values, visitors::{Mysql, MysqlVersion}};
+    /// # fn main() -> xiayu::Result<()> {
+    /// let values = Table::from(values!((1, 2), (3, 4))).alias_with_columns("vals", ["a", "b"]);
+    /// let (sql, _) = Mysql::build_for_version(Select::from_table(values), MysqlVersion::V5_7)?;
+    ///
+    /// assert_eq!(
+    ///     "SELECT `vals`.* FROM (SELECT ? AS `a`, ? AS `b` UNION ALL SELECT ?, ?) AS `vals`",
+    ///     sql
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build_for_version<Q>(
+        query: Q,
+        version: MysqlVersion,
+    ) -> crate::Result<(String, Vec<Value<'a>>)>
+    where
+        Q: Into<Query<'a>>,
+    {
+        let mut mysql = Mysql {
+            version,
+            ..Mysql::default()
+        };
+
+        Mysql::visit_query(&mut mysql, query.into())?;
+
+        Ok((mysql.query, mysql.parameters))
+    }
+
     fn visit_regular_equality_comparison(
         &mut self,
         left: Expression<'a>,
@@ -102,8 +184,14 @@ impl<'a> Mysql<'a> {
 impl<'a> Default for Mysql<'a> {
     fn default() -> Self {
         Mysql {
-            query: String::with_capacity(4096),
-            parameters: Vec::with_capacity(128),
+            query: String::new(),
+            parameters: Vec::new(),
+            parameter_columns: Vec::new(),
+            column: None,
+            quote_style: QuoteStyle::default(),
+            depth: 0,
+            predicates: 0,
+            version: MysqlVersion::default(),
         }
     }
 }
@@ -112,20 +200,111 @@ impl<'a> Visitor<'a> for Mysql<'a> {
     const C_BACKTICK_OPEN: &'static str = "`";
     const C_BACKTICK_CLOSE: &'static str = "`";
     const C_WILDCARD: &'static str = "%";
+    const RESERVED_WORDS: &'static [&'static str] = RESERVED_WORDS;
 
-    #[tracing::instrument(name = "render_sql", skip(query))]
-    fn build<Q>(query: Q) -> crate::Result<(String, Vec<Value<'a>>)>
+    #[tracing::instrument(name = "render_sql", skip(query, buffers))]
+    fn build_into_with<Q>(query: Q, quote_style: QuoteStyle, buffers: &mut VisitorBuffers<'a>) -> visitors::Result
     where
         Q: Into<Query<'a>>,
     {
+        let VisitorBuffers {
+            query: sql,
+            parameters,
+            parameter_columns,
+        } = buffers.take();
         let mut mysql = Mysql {
-            query: String::with_capacity(4096),
-            parameters: Vec::with_capacity(128),
+            query: sql,
+            parameters,
+            parameter_columns,
+            column: None,
+            quote_style,
+            depth: 0,
+            predicates: 0,
+            version: MysqlVersion::default(),
         };
 
-        Mysql::visit_query(&mut mysql, query.into())?;
+        let visited = Mysql::visit_query(&mut mysql, query.into())
+            .and_then(|_| check_parameters::<sqlx::MySql>(&mut mysql.parameters, &mysql.parameter_columns));
+        if visited.is_ok() {
+            super::format::trace_sql(&mysql.query, &mysql.parameters, &mysql.parameter_columns);
+        }
+        buffers.restore(mysql.query, mysql.parameters, mysql.parameter_columns);
 
-        Ok((mysql.query, mysql.parameters))
+        visited
+    }
+
+    fn quote_style(&self) -> QuoteStyle {
+        self.quote_style
+    }
+
+    /// The names as understood by `CAST`, which only knows a subset of the
+    /// column types.
+    fn type_name(type_family: TypeFamily) -> Cow<'static, str> {
+        match type_family {
+            TypeFamily::Text(Some(TypeDataLength::Constant(len))) => format!("CHAR({})", len).into(),
+            TypeFamily::Text(_) => "CHAR".into(),
+            TypeFamily::Int | TypeFamily::Int32 | TypeFamily::Boolean => "SIGNED".into(),
+            TypeFamily::Float => "FLOAT".into(),
+            TypeFamily::Double => "DOUBLE".into(),
+            TypeFamily::Decimal(Some((p, s))) => format!("DECIMAL({},{})", p, s).into(),
+            TypeFamily::Decimal(None) => "DECIMAL".into(),
+            TypeFamily::Uuid => "CHAR(36)".into(),
+            TypeFamily::DateTime => "DATETIME".into(),
+            TypeFamily::Bytes(Some(TypeDataLength::Constant(len))) => {
+                format!("BINARY({})", len).into()
+            }
+            TypeFamily::Bytes(_) => "BINARY".into(),
+            TypeFamily::Json => "JSON".into(),
+        }
+    }
+
+    fn column_type(type_family: TypeFamily) -> Cow<'static, str> {
+        match type_family {
+            TypeFamily::Text(Some(TypeDataLength::Constant(len))) => {
+                format!("VARCHAR({})", len).into()
+            }
+            TypeFamily::Text(_) => "TEXT".into(),
+            TypeFamily::Int => "BIGINT".into(),
+            TypeFamily::Int32 => "INT".into(),
+            TypeFamily::Boolean => "BOOLEAN".into(),
+            TypeFamily::DateTime => "DATETIME(6)".into(),
+            TypeFamily::Bytes(Some(TypeDataLength::Constant(len))) => {
+                format!("VARBINARY({})", len).into()
+            }
+            TypeFamily::Bytes(_) => "BLOB".into(),
+            type_family => Self::type_name(type_family),
+        }
+    }
+
+    fn visit_delete_using(&mut self, delete: Delete<'a>) -> visitors::Result {
+        self.visit_multi_table_delete(delete)
+    }
+
+    fn visit_limited_delete(&mut self, delete: Delete<'a>) -> visitors::Result {
+        self.write("DELETE FROM ")?;
+        self.visit_table(delete.table, true)?;
+
+        if let Some(conditions) = delete.conditions {
+            self.write(" WHERE ")?;
+            self.visit_conditions(conditions)?;
+        }
+        if !delete.ordering.is_empty() {
+            self.write(" ORDER BY ")?;
+            self.visit_ordering(delete.ordering)?;
+        }
+        self.visit_limit_and_offset(delete.limit, None)
+    }
+
+    fn visit_generated_primary_key(&mut self) -> visitors::Result {
+        self.write(" AUTO_INCREMENT PRIMARY KEY")
+    }
+
+    /// MySQL restates the whole column, `MODIFY COLUMN definition`.
+    fn visit_set_nullable(&mut self, table: Table<'a>, column: ColumnDefinition) -> visitors::Result {
+        self.write("ALTER TABLE ")?;
+        self.visit_table(table, false)?;
+        self.write(" MODIFY COLUMN ")?;
+        self.visit_column_definition(column)
     }
 
     fn write<D: fmt::Display>(&mut self, s: D) -> visitors::Result {
@@ -197,9 +376,28 @@ impl<'a> Visitor<'a> for Mysql<'a> {
     }
 
     fn visit_insert(&mut self, insert: Insert<'a>) -> visitors::Result {
-        match insert.on_conflict {
-            Some(OnConflict::DoNothing) => self.write("INSERT IGNORE ")?,
-            None => self.write("INSERT ")?,
+        let assignments = match insert.on_conflict {
+            Some(OnConflict::DoNothing) => {
+                self.write("INSERT IGNORE ")?;
+                None
+            }
+            Some(OnConflict::DoNothingOn(target)) => {
+                tracing::warn!(
+                    ?target,
+                    "conflict targets are not supported by MySQL, ignoring every conflict"
+                );
+                self.write("INSERT IGNORE ")?;
+                None
+            }
+            // `ON DUPLICATE KEY` updates on a conflict in any unique index.
+            Some(OnConflict::Update(_, assignments)) => {
+                self.write("INSERT ")?;
+                Some(assignments)
+            }
+            None => {
+                self.write("INSERT ")?;
+                None
+            }
         };
 
         if let Some(table) = insert.table {
@@ -207,6 +405,8 @@ impl<'a> Visitor<'a> for Mysql<'a> {
             self.visit_table(table, true)?;
         }
 
+        let bound: Vec<ColumnMeta> = insert.columns.iter().map(ColumnMeta::from).collect();
+
         match insert.values {
             Expression {
                 kind: ExpressionKind::Row(row),
@@ -228,7 +428,7 @@ impl<'a> Visitor<'a> for Mysql<'a> {
 
                     self.write(")")?;
                     self.write(" VALUES ")?;
-                    self.visit_row(row)?;
+                    self.visit_insert_row(&bound, row)?;
                 }
             }
             Expression {
@@ -251,25 +451,69 @@ impl<'a> Visitor<'a> for Mysql<'a> {
                 let values_len = values.len();
 
                 for (i, row) in values.into_iter().enumerate() {
-                    self.visit_row(row)?;
+                    self.visit_insert_row(&bound, row)?;
 
                     if i < (values_len - 1) {
                         self.write(", ")?;
                     }
                 }
             }
+            Expression {
+                kind: ExpressionKind::Selection(selection),
+                ..
+            } => {
+                let columns = insert.columns.len();
+
+                if columns > 0 {
+                    self.write(" (")?;
+                    for (i, c) in insert.columns.into_iter().enumerate() {
+                        self.visit_column(c.into_bare())?;
+
+                        if i < (columns - 1) {
+                            self.write(",")?;
+                        }
+                    }
+                    self.write(")")?;
+                }
+
+                self.write(" ")?;
+                self.visit_selection(selection)?;
+            }
             expr => self.surround_with("(", ")", |ref mut s| s.visit_expression(expr))?,
         }
 
+        if let Some(assignments) = assignments {
+            self.write(" ON DUPLICATE KEY UPDATE ")?;
+            self.visit_conflict_assignments(assignments)?;
+        }
+
         Ok(())
     }
 
+    fn visit_excluded(&mut self, column: Column<'a>) -> visitors::Result {
+        self.write("VALUES")?;
+        self.surround_with("(", ")", |ref mut s| s.visit_column(column.into_bare()))
+    }
+
     fn parameter_substitution(&mut self) -> visitors::Result {
         self.write("?")
     }
 
     fn add_parameter(&mut self, value: Value<'a>) {
         self.parameters.push(value);
+        self.parameter_columns.push(self.column.clone());
+    }
+
+    fn bind_parameters_to(&mut self, column: Option<ColumnMeta>) {
+        self.column = column;
+    }
+
+    fn depth(&mut self) -> &mut usize {
+        &mut self.depth
+    }
+
+    fn predicates(&mut self) -> &mut usize {
+        &mut self.predicates
     }
 
     fn visit_limit_and_offset(
@@ -301,11 +545,131 @@ impl<'a> Visitor<'a> for Mysql<'a> {
         }
     }
 
+    /// Counts the combinations of a row of columns, `COUNT(DISTINCT a, b)`.
+    fn visit_count_distinct(&mut self, expr: Expression<'a>) -> visitors::Result {
+        match expr.kind {
+            ExpressionKind::Row(row) => self.visit_columns(row.values),
+            _ => self.visit_expression(expr),
+        }
+    }
+
     fn visit_aggregate_to_string(&mut self, value: Expression<'a>) -> visitors::Result {
         self.write(" GROUP_CONCAT")?;
         self.surround_with("(", ")", |ref mut s| s.visit_expression(value))
     }
 
+    fn visit_values_table(
+        &mut self,
+        values: Values<'a>,
+        alias: Option<Cow<'a, str>>,
+        columns: Vec<Cow<'a, str>>,
+    ) -> visitors::Result {
+        match self.version {
+            MysqlVersion::V8 => {
+                self.surround_with("(VALUES ", ")", |ref mut s| {
+                    let len = values.len();
+                    for (i, row) in values.into_iter().enumerate() {
+                        s.write("ROW")?;
+                        s.visit_row(row)?;
+
+                        if i < (len - 1) {
+                            s.write(",")?;
+                        }
+                    }
+                    Ok(())
+                })?;
+
+                if let Some(alias) = alias {
+                    self.write(" AS ")?;
+                    self.delimited_identifiers(&[&*alias])?;
+                    self.visit_column_aliases(columns)?;
+                }
+            }
+            MysqlVersion::V5_7 => {
+                // The columns are named by the first select, `column_0`, ... like
+                // the ones of a MySQL 8 `VALUES` table when not given.
+                self.surround_with("(", ")", |ref mut s| {
+                    for (i, row) in values.into_iter().enumerate() {
+                        s.write(if i == 0 { "SELECT " } else { " UNION ALL SELECT " })?;
+
+                        let len = row.values.len();
+                        for (j, value) in row.values.into_iter().enumerate() {
+                            s.visit_expression(value)?;
+
+                            if i == 0 {
+                                s.write(" AS ")?;
+                                match columns.get(j) {
+                                    Some(column) => s.visit_identifier(column, false)?,
+                                    None => s.visit_identifier(&format!("column_{}", j), false)?,
+                                }
+                            }
+
+                            if j < (len - 1) {
+                                s.write(", ")?;
+                            }
+                        }
+                    }
+                    Ok(())
+                })?;
+
+                if let Some(alias) = alias {
+                    self.write(" AS ")?;
+                    self.delimited_identifiers(&[&*alias])?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn visit_null_safe_equals(
+        &mut self,
+        left: Expression<'a>,
+        right: Expression<'a>,
+        negate: bool,
+    ) -> visitors::Result {
+        if negate {
+            return self.surround_with("NOT (", ")", |s| s.visit_null_safe_equals(left, right, false));
+        }
+
+        self.visit_expression(left)?;
+        self.write(" <=> ")?;
+        self.visit_expression(right)
+    }
+
+    fn visit_concat(&mut self, exprs: Vec<Expression<'a>>) -> visitors::Result {
+        self.write("CONCAT")?;
+        self.surround_with("(", ")", |s| s.visit_columns(exprs))
+    }
+
+    fn visit_length(&mut self, expression: Expression<'a>) -> visitors::Result {
+        self.write("CHAR_LENGTH")?;
+        self.surround_with("(", ")", |s| s.visit_expression(expression))
+    }
+
+    fn visit_date_add(&mut self, expression: Expression<'a>, interval: IntervalArg) -> visitors::Result {
+        if interval.amount() < 0 {
+            self.write("DATE_SUB")?;
+        } else {
+            self.write("DATE_ADD")?;
+        }
+
+        let unit = match interval.unit() {
+            IntervalUnit::Day => "DAY",
+            IntervalUnit::Hour => "HOUR",
+            IntervalUnit::Minute => "MINUTE",
+        };
+
+        self.surround_with("(", ")", |s| {
+            s.visit_expression(expression)?;
+            s.write(format!(
+                ", INTERVAL {} {}",
+                (interval.amount() as i64).abs(),
+                unit
+            ))
+        })
+    }
+
     fn visit_equals(&mut self, left: Expression<'a>, right: Expression<'a>) -> visitors::Result {
         #[cfg(feature = "json")]
         {
@@ -593,6 +957,47 @@ mod tests {
     #[tablename = "users"]
     struct UserWithoutAttributes {}
 
+    #[test]
+    fn test_limited_delete() {
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i32,
+            created_at: i32,
+        }
+
+        let query = Delete::from_table(Event::table())
+            .so_that(Event::created_at.less_than(1_000))
+            .order_by(Event::created_at.descend())
+            .limit(500);
+        let (sql, params) = Mysql::build(query).unwrap();
+
+        assert_eq!("DELETE FROM `events` WHERE `events`.`created_at` < ? ORDER BY `events`.`created_at` DESC LIMIT ?", sql);
+        assert_eq!(vec![Value::from(1_000), Value::from(500usize)], params);
+
+        let err = Mysql::build(Delete::from_table(Event::table()).using(Event::table().alias("e")).limit(1)).unwrap_err();
+        assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidDelete(_)));
+    }
+
+    #[test]
+    fn test_aliases_are_dropped_in_predicates() {
+        #[derive(Entity)]
+        #[tablename = "test"]
+        struct Test {
+            #[column(primary_key)]
+            id: i32,
+        }
+
+        let subquery = Select::default().value(val!(4).alias("c"));
+        let conditions = val!(2).alias("x").equals(val!(3).alias("y")).and(Column::new("a").alias("b").in_selection(subquery));
+        let query = Select::from_table(Test::table()).value(val!(1).alias("one")).so_that(conditions);
+        let (sql, params) = Mysql::build(query).unwrap();
+
+        assert_eq!("SELECT ? AS `one` FROM `test` WHERE (? = ? AND `a` IN (SELECT ? AS `c`))", sql);
+        assert_eq!(vec![Value::from(1), Value::from(2), Value::from(3), Value::from(4)], params);
+    }
+
     #[test]
     fn test_single_row_insert_default_values() {
         let query = Insert::single_into(UserWithoutAttributes::table());
@@ -634,6 +1039,22 @@ mod tests {
         assert_eq!(expected.1, params);
     }
 
+    #[test]
+    fn test_insert_column_list_is_not_qualified() {
+        // MySQL rejects `INSERT INTO t (t.c)` in some SQL modes.
+        let query = Insert::single_into(User::table())
+            .value(User::id, 1)
+            .value(User::foo, 10);
+        let (sql, _) = Mysql::build(query).unwrap();
+        assert_eq!("INSERT INTO `users` (`id`,`foo`) VALUES (?,?)", sql);
+
+        let query = Insert::multi_into(User::table(), vec![User::id, User::foo])
+            .values((1, 10))
+            .values((2, 20));
+        let (sql, _) = Mysql::build(query).unwrap();
+        assert_eq!("INSERT INTO `users` (`id`,`foo`) VALUES (?,?), (?,?)", sql);
+    }
+
     #[test]
     fn test_limit_and_offset_when_both_are_set() {
         let expected = expected_values(
@@ -790,6 +1211,184 @@ mod tests {
         assert!(params.is_empty());
     }
 
+    #[test]
+    fn test_select_from_values_with_column_names() {
+        use crate::values;
+
+        let expected_sql = "SELECT `vals`.* FROM (VALUES ROW(?,?),ROW(?,?)) AS `vals`(`a`, `b`)";
+        let values = Table::from(values!((1, 2), (3, 4))).alias_with_columns("vals", ["a", "b"]);
+        let (sql, params) = Mysql::build(Select::from_table(values)).unwrap();
+
+        assert_eq!(expected_sql, sql);
+        assert_eq!(
+            vec![
+                Value::integer(1),
+                Value::integer(2),
+                Value::integer(3),
+                Value::integer(4),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_select_from_values_on_mysql_5_7() {
+        use crate::values;
+
+        let expected_sql =
+            "SELECT `vals`.* FROM (SELECT ? AS `a`, ? AS `b` UNION ALL SELECT ?, ?) AS `vals`";
+        let values = Table::from(values!((1, 2), (3, 4))).alias_with_columns("vals", ["a", "b"]);
+        let query = Select::from_table(values);
+        let (sql, params) = Mysql::build_for_version(query, MysqlVersion::V5_7).unwrap();
+
+        assert_eq!(expected_sql, sql);
+        assert_eq!(
+            vec![
+                Value::integer(1),
+                Value::integer(2),
+                Value::integer(3),
+                Value::integer(4),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_select_from_values_on_mysql_5_7_without_column_names() {
+        use crate::values;
+
+        let expected_sql =
+            "SELECT `vals`.* FROM (SELECT ? AS `column_0`, ? AS `column_1` UNION ALL SELECT ?, ?) AS `vals`";
+        let values = Table::from(values!((1, 2), (3, 4))).alias("vals");
+        let query = Select::from_table(values);
+        let (sql, _) = Mysql::build_for_version(query, MysqlVersion::V5_7).unwrap();
+
+        assert_eq!(expected_sql, sql);
+    }
+
+    #[derive(Entity)]
+    #[tablename = "subscribers"]
+    struct Subscriber {
+        #[column(primary_key)]
+        id: i32,
+        email: String,
+        deleted_at: Option<String>,
+    }
+
+    #[test]
+    fn test_on_conflict_target_is_ignored() {
+        let insert: Insert = Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let (sql, _) = Mysql::build(insert.on_conflict(OnConflict::DoNothingOn(target))).unwrap();
+
+        assert_eq!("INSERT IGNORE INTO `subscribers` (`email`) VALUES (?)", sql);
+    }
+
+    #[test]
+    fn test_on_conflict_update_with_excluded_values() {
+        let insert: Insert = Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .value(Subscriber::deleted_at, Value::Text(None))
+            .into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let assignments = vec![(Subscriber::deleted_at.into(), excluded(Subscriber::deleted_at))];
+        let (sql, params) = Mysql::build(insert.on_conflict(OnConflict::Update(target, assignments))).unwrap();
+
+        assert_eq!(
+            "INSERT INTO `subscribers` (`email`,`deleted_at`) VALUES (?,?) ON DUPLICATE KEY UPDATE `deleted_at` = VALUES(`deleted_at`)",
+            sql
+        );
+        assert_eq!(vec![Value::from("a@b.c"), Value::Text(None)], params);
+    }
+
+    #[test]
+    fn test_on_conflict_update_with_assignments() {
+        let insert: Insert = Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let assignments = vec![(Subscriber::deleted_at.into(), Value::Text(None).into())];
+        let (sql, params) = Mysql::build(insert.on_conflict(OnConflict::Update(target, assignments))).unwrap();
+
+        assert_eq!(
+            "INSERT INTO `subscribers` (`email`) VALUES (?) ON DUPLICATE KEY UPDATE `deleted_at` = ?",
+            sql
+        );
+        assert_eq!(vec![Value::from("a@b.c"), Value::Text(None)], params);
+    }
+
+    #[test]
+    fn test_alter_table() {
+        let email = ColumnDefinition::new(
+            "email",
+            Some(TypeFamily::Text(Some(TypeDataLength::Constant(64)))),
+            false,
+        );
+        let (sql, _) =
+            Mysql::build(AlterTable::set_nullable(Subscriber::table(), email.clone())).unwrap();
+        assert_eq!(
+            "ALTER TABLE `subscribers` MODIFY COLUMN `email` VARCHAR(64) NOT NULL",
+            sql
+        );
+
+        let bio = ColumnDefinition::new("bio", Some(TypeFamily::Text(None)), true);
+        let (sql, _) = Mysql::build(AlterTable::add_column(Subscriber::table(), bio)).unwrap();
+        assert_eq!("ALTER TABLE `subscribers` ADD COLUMN `bio` TEXT", sql);
+    }
+
+    #[test]
+    fn test_create_table() {
+        #[derive(Entity)]
+        #[tablename = "comments"]
+        struct Comment {
+            #[column(primary_key, autoincrement)]
+            id: i64,
+            #[column(foreign_key = "posts.id")]
+            post_id: i64,
+            #[column(unique, length = 32)]
+            slug: String,
+            #[column(server_default = 0)]
+            likes: i32,
+            body: Option<String>,
+        }
+
+        let schema = Comment::schema();
+        let (sql, _) = Mysql::build(AlterTable::create_table(Comment::table(), TableDefinition::from(&schema))).unwrap();
+
+        let expected_sql = "CREATE TABLE `comments` (`id` BIGINT NOT NULL AUTO_INCREMENT PRIMARY KEY, `post_id` BIGINT NOT NULL, `slug` VARCHAR(32) NOT NULL, `likes` INT NOT NULL DEFAULT 0, `body` TEXT, UNIQUE (`slug`), FOREIGN KEY (`post_id`) REFERENCES `posts` (`id`))";
+        assert_eq!(expected_sql, sql);
+    }
+
+    #[test]
+    fn test_delete_using() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            banned: bool,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "posts"]
+        struct Post {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+        }
+
+        let query = Delete::from_table(Post::table())
+            .using(User::table())
+            .so_that(Post::user_id.equals(User::id).and(User::banned.equals(true)));
+        let (sql, params) = Mysql::build(query).unwrap();
+
+        let expected_sql = "DELETE `posts` FROM `posts`, `users` WHERE (`posts`.`user_id` = `users`.`id` AND `users`.`banned` = ?)";
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(true)], params);
+    }
+
     #[test]
     fn test_distinct() {
         let expected_sql = "SELECT DISTINCT `test`.`bar` FROM `test`";
@@ -833,12 +1432,13 @@ mod tests {
 
     #[test]
     fn test_from() {
-        let expected_sql = "SELECT `foo`.*, `bar`.`a` FROM `foo`, (SELECT `a` FROM `baz`) AS `bar`";
+        let expected_sql = "SELECT `foo`.*, `bar`.`a` FROM `foo`, (SELECT `baz`.`a` FROM `baz`) AS `bar`";
+        let bar = Table::from(Select::from_table(Baz::table()).column(Baz::a)).alias("bar");
         let query = Select::default()
             .and_from(Foo::table())
-            .and_from(Table::from(Select::from_table(Baz::table()).column(Baz::a)).alias("bar"))
+            .and_from(bar.clone())
             .value(Table::from(Foo::table()).asterisk())
-            .column(Baz::a); // ("bar", "a")
+            .column(bar.col(Baz::a));
 
         let (sql, _) = Mysql::build(query).unwrap();
         assert_eq!(expected_sql, sql);
@@ -901,8 +1501,9 @@ mod tests {
 
     #[test]
     fn join_is_inserted_positionally() {
+        let posts = Post::table().alias("p");
         let joined_table = Table::from(User::table())
-            .left_join(Post::table().alias("p").on(Post::user_id.equals(User::id)));
+            .left_join(posts.clone().on(posts.col(Post::user_id).equals(User::id)));
         let q = Select::from_table(joined_table).and_from(Toto::table());
         let (sql, _) = Mysql::build(q).unwrap();
 
@@ -911,4 +1512,378 @@ mod tests {
             sql
         );
     }
+
+    #[derive(Entity)]
+    #[tablename = "jobs"]
+    struct Job {
+        id: i32,
+        done: bool,
+    }
+
+    #[test]
+    fn test_select_for_update() {
+        let query = Select::from_table(Job::table()).so_that(Job::done.equals(false)).for_update();
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `jobs`.* FROM `jobs` WHERE `jobs`.`done` = ? FOR UPDATE",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_select_for_share_nowait() {
+        let query = Select::from_table(Job::table()).for_share().nowait();
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!("SELECT `jobs`.* FROM `jobs` FOR SHARE NOWAIT", sql);
+    }
+
+    #[test]
+    fn test_select_for_update_skip_locked() {
+        let query = Select::from_table(Job::table()).limit(10).for_update().skip_locked();
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `jobs`.* FROM `jobs` LIMIT ? FOR UPDATE SKIP LOCKED",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_invalid_locking_combinations() {
+        let queries = vec![
+            Select::from_table(Job::table()).for_update().for_share(),
+            Select::from_table(Job::table()).for_update().skip_locked().nowait(),
+            Select::from_table(Job::table()).skip_locked(),
+        ];
+
+        for query in queries {
+            let err = Mysql::build(query).unwrap_err();
+            assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidLocking(_)));
+        }
+    }
+
+    #[derive(Entity)]
+    #[tablename = "sessions"]
+    struct Session {
+        id: i32,
+        last_seen: String,
+    }
+
+    #[test]
+    fn test_date_sub_from_now() {
+        let query = Select::from_table(Session::table())
+            .so_that(Session::last_seen.less_than(date_sub(now(), IntervalArg::days(7))));
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `sessions`.* FROM `sessions` WHERE `sessions`.`last_seen` < DATE_SUB(NOW(), INTERVAL 7 DAY)",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_date_add_in_update() {
+        let query = Update::table(Session::table())
+            .set(Session::last_seen, date_add(now(), IntervalArg::minutes(90)));
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!("UPDATE `sessions` SET `last_seen` = DATE_ADD(NOW(), INTERVAL 90 MINUTE)", sql);
+    }
+
+    #[test]
+    fn test_date_sub_from_current_date() {
+        let query = Select::from_table(Session::table())
+            .so_that(Session::last_seen.greater_than(date_sub(current_date(), IntervalArg::hours(3))));
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `sessions`.* FROM `sessions` WHERE `sessions`.`last_seen` > DATE_SUB(CURRENT_DATE, INTERVAL 3 HOUR)",
+            sql
+        );
+    }
+
+    #[derive(Entity)]
+    #[tablename = "profiles"]
+    struct Profile {
+        id: i32,
+        name: String,
+        nickname: Option<String>,
+        surname: String,
+    }
+
+    #[test]
+    fn test_concat() {
+        let query = Select::from_table(Profile::table())
+            .value(concat((Profile::name, " ", Profile::surname)).alias("full_name"));
+        let (sql, params) = Mysql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT CONCAT(`profiles`.`name`, ?, `profiles`.`surname`) AS `full_name` FROM `profiles`",
+            sql
+        );
+        assert_eq!(vec![Value::from(" ")], params);
+    }
+
+    #[test]
+    fn test_string_functions_in_conditions_and_ordering() {
+        let query = Select::from_table(Profile::table())
+            .so_that(Expression::from(nullif(trim(Profile::name), "")).is_not_null())
+            .and_where(Expression::from(length(Profile::surname)).greater_than(2))
+            .order_by(coalesce((Profile::nickname, Profile::name)).ascend());
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `profiles`.* FROM `profiles` WHERE (NULLIF(TRIM(`profiles`.`name`), ?) IS NOT NULL AND CHAR_LENGTH(`profiles`.`surname`) > ?) ORDER BY COALESCE(`profiles`.`nickname`, `profiles`.`name`) ASC",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_null_safe_equality_of_columns() {
+        let query = Select::from_table(Profile::table()).so_that(Profile::nickname.is_not_distinct_from(Profile::name));
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `profiles`.* FROM `profiles` WHERE `profiles`.`nickname` <=> `profiles`.`name`",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_null_safe_inequality_with_parameter() {
+        let nickname: Option<&str> = None;
+        let query = Select::from_table(Profile::table()).so_that(Profile::nickname.is_distinct_from(nickname));
+        let (sql, params) = Mysql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `profiles`.* FROM `profiles` WHERE NOT (`profiles`.`nickname` <=> ?)",
+            sql
+        );
+        assert_eq!(vec![Value::Text(None)], params);
+    }
+
+    #[derive(Entity)]
+    #[tablename = "tickets"]
+    struct Ticket {
+        id: i32,
+        order: i32,
+        #[column(quote)]
+        status: String,
+    }
+
+    #[test]
+    fn test_minimal_quoting() {
+        let query = Select::from_table(Ticket::table())
+            .column(Ticket::id)
+            .column(Ticket::order)
+            .column(Ticket::status)
+            .column(Column::new("createdAt"))
+            .column(Ticket::id.alias("Id"))
+            .so_that(Ticket::id.equals(1));
+        let (sql, _) = Mysql::build_with(query, QuoteStyle::Minimal).unwrap();
+
+        assert_eq!(
+            "SELECT tickets.id, tickets.`order`, tickets.`status`, `createdAt`, tickets.id AS `Id` FROM tickets WHERE tickets.id = ?",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_quote_in_identifier_is_escaped() {
+        let query = || Select::from_table(Ticket::table()).column(Column::new("we`ird"));
+
+        let (sql, _) = Mysql::build_with(query(), QuoteStyle::Always).unwrap();
+        assert_eq!("SELECT `we``ird` FROM `tickets`", sql);
+
+        let (sql, _) = Mysql::build_with(query(), QuoteStyle::Minimal).unwrap();
+        assert_eq!("SELECT `we``ird` FROM tickets", sql);
+    }
+
+    #[test]
+    fn test_hostile_identifiers_stay_within_one_identifier() {
+        let query = || {
+            Select::from_table(Ticket::table())
+                .column(Column::new("x` FROM users; --").alias("a`b"))
+        };
+
+        let (sql, _) = Mysql::build_with(query(), QuoteStyle::Always).unwrap();
+        assert_eq!("SELECT `x`` FROM users; --` AS `a``b` FROM `tickets`", sql);
+
+        let (sql, _) = Mysql::build_with(query(), QuoteStyle::Minimal).unwrap();
+        assert_eq!("SELECT `x`` FROM users; --` AS `a``b` FROM tickets", sql);
+    }
+
+    #[test]
+    fn test_reserved_words_are_sorted() {
+        assert!(Mysql::RESERVED_WORDS.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_cast_in_condition() {
+        let query = Select::from_table(Job::table())
+            .so_that(cast(Job::id, TypeFamily::Text(None)).equals("42"));
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!("SELECT `jobs`.* FROM `jobs` WHERE CAST(`jobs`.`id` AS CHAR) = ?", sql);
+    }
+
+    #[test]
+    fn test_cast_of_an_operation() {
+        let total = Expression::from(Job::id) + Expression::from(1);
+        let query = Select::from_table(Job::table())
+            .value(total.cast(TypeFamily::Decimal(Some((10, 2)))).alias("total"));
+        let (sql, _) = Mysql::build(query).unwrap();
+
+        assert_eq!("SELECT CAST((`jobs`.`id` + ?) AS DECIMAL(10,2)) AS `total` FROM `jobs`", sql);
+    }
+
+    #[test]
+    fn test_keyset_pagination() {
+        let query = Select::from_table(Subscriber::table()).after((Subscriber::email, Subscriber::id), ("a@b.c", 3));
+        let (sql, params) = Mysql::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `subscribers`.* FROM `subscribers` WHERE (`subscribers`.`email`,`subscribers`.`id`) > (?,?)",
+            sql
+        );
+        assert_eq!(vec![Value::from("a@b.c"), Value::from(3)], params);
+    }
+
+    #[test]
+    fn test_count_distinct() {
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            session_id: i32,
+        }
+
+        let query = Select::from_table(Event::table()).value(count_distinct(Event::user_id));
+        let (sql, _) = Mysql::build(query).unwrap();
+        assert_eq!("SELECT COUNT(DISTINCT `events`.`user_id`) FROM `events`", sql);
+
+        let query = Select::from_table(Event::table()).value(count_distinct(Row::from((Event::user_id, Event::session_id))));
+        let (sql, _) = Mysql::build(query).unwrap();
+        assert_eq!("SELECT COUNT(DISTINCT `events`.`user_id`, `events`.`session_id`) FROM `events`", sql);
+    }
+
+    #[test]
+    fn test_aggregates() {
+        #[derive(Entity)]
+        #[tablename = "orders"]
+        struct Order {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            total: i32,
+            paid: bool,
+        }
+
+        let query = Select::from_table(Order::table()).value(count_star()).so_that(Order::user_id.equals(1));
+        let (sql, _) = Mysql::build(query).unwrap();
+        assert_eq!("SELECT COUNT(*) FROM `orders` WHERE `orders`.`user_id` = ?", sql);
+
+        let query = Select::from_table(Order::table())
+            .value(count(Order::id))
+            .value(sum(Order::total))
+            .value(min(Order::total))
+            .value(max(Order::total));
+        let (sql, _) = Mysql::build(query).unwrap();
+        assert_eq!("SELECT COUNT(`orders`.`id`), SUM(`orders`.`total`), MIN(`orders`.`total`), MAX(`orders`.`total`) FROM `orders`", sql);
+
+        let query = Select::from_table(Order::table()).value(sum(Order::paid));
+        let (sql, _) = Mysql::build(query).unwrap();
+        assert_eq!("SELECT SUM(`orders`.`paid`) FROM `orders`", sql);
+    }
+
+    #[test]
+    fn test_exists() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            banned: bool,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "posts"]
+        struct Post {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            published: bool,
+            flagged: bool,
+        }
+
+        let posts = || Select::from_table(Post::table()).value(1.raw());
+        let query = Select::from_table(User::table())
+            .so_that(User::banned.equals(false))
+            .and_where(exists(posts().so_that(Post::user_id.equals(User::id).and(Post::published.equals(true)))))
+            .and_where(not_exists(posts().so_that(Post::user_id.equals(User::id).and(Post::flagged.equals(true)))));
+        let (sql, params) = Mysql::build(query).unwrap();
+
+        let expected_sql = "SELECT `users`.* FROM `users` WHERE (`users`.`banned` = ? AND EXISTS (SELECT 1 FROM `posts` WHERE (`posts`.`user_id` = `users`.`id` AND `posts`.`published` = ?)) AND NOT EXISTS (SELECT 1 FROM `posts` WHERE (`posts`.`user_id` = `users`.`id` AND `posts`.`flagged` = ?)))";
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(false), Value::from(true), Value::from(true)], params);
+    }
+
+    #[test]
+    fn test_empty_select_is_incomplete() {
+        let err = Mysql::build(Select::default()).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table or a value to select" }
+        ));
+    }
+
+    #[test]
+    fn test_select_with_only_ordering_is_incomplete() {
+        let query = Select::default().order_by(Subscriber::email.descend());
+        let err = Mysql::build(query).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table or a value to select" }
+        ));
+    }
+
+    #[test]
+    fn test_insert_without_table_is_incomplete() {
+        let insert: Insert = Insert::single().value(Subscriber::email, "a@b.c").into();
+        let err = Mysql::build(insert).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table to insert into" }
+        ));
+    }
+
+    #[test]
+    fn test_update_without_assignments_is_incomplete() {
+        let query = Update::table(Subscriber::table()).so_that(Subscriber::email.equals("a@b.c"));
+        let err = Mysql::build(query).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a column to update" }
+        ));
+    }
+
+    #[test]
+    fn test_upsert_without_assignments_is_incomplete() {
+        let insert: Insert = Insert::single_into(Subscriber::table()).value(Subscriber::email, "a@b.c").into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let err = Mysql::build(insert.on_conflict(OnConflict::Update(target, Vec::new()))).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a column to update on conflict" }
+        ));
+    }
 }
@@ -1,23 +1,57 @@
-use std::fmt::{self, Write};
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
 
 use crate::ast::*;
-use crate::visitors::{self, Visitor};
+use crate::databases::check_parameters;
+use crate::visitors::{self, QuoteStyle, Visitor, VisitorBuffers};
 
 /// A visitor to generate queries for the PostgreSQL database.
 ///
 /// The returned parameter values implement the `ToSql` trait from postgres and
 /// can be used directly with the database.
-#[cfg_attr(feature = "docs", doc(cfg(feature = "postgres")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "postgres")))]
 pub struct Postgres<'a> {
     query: String,
     parameters: Vec<Value<'a>>,
+    parameter_columns: Vec<Option<ColumnMeta>>,
+    /// The column parameters are bound to, set by `bind_parameters_to`.
+    column: Option<ColumnMeta>,
+    quote_style: QuoteStyle,
+    /// The number of expressions being visited.
+    depth: usize,
+    /// The number of predicates being visited, aliases are dropped in them.
+    predicates: usize,
 }
 
+/// Reserved words of PostgreSQL, quoted even with [`QuoteStyle::Minimal`].
+const RESERVED_WORDS: &[&str] = &[
+    "ALL", "ANALYSE", "ANALYZE", "AND", "ANY", "ARRAY", "AS", "ASC", "ASYMMETRIC", "AUTHORIZATION",
+    "BETWEEN", "BINARY", "BOTH", "BY", "CASE", "CAST", "CHECK", "COLLATE", "COLLATION", "COLUMN",
+    "CONCURRENTLY", "CONSTRAINT", "CREATE", "CROSS", "CURRENT_CATALOG", "CURRENT_DATE",
+    "CURRENT_ROLE", "CURRENT_SCHEMA", "CURRENT_TIME", "CURRENT_TIMESTAMP", "CURRENT_USER",
+    "DEFAULT", "DEFERRABLE", "DELETE", "DESC", "DISTINCT", "DO", "DROP", "ELSE", "END", "EXCEPT",
+    "EXISTS", "FALSE", "FETCH", "FOR", "FOREIGN", "FREEZE", "FROM", "FULL", "GRANT", "GROUP",
+    "HAVING", "ILIKE", "IN", "INITIALLY", "INNER", "INSERT", "INTERSECT", "INTO", "IS", "ISNULL",
+    "JOIN", "LATERAL", "LEADING", "LEFT", "LIKE", "LIMIT", "LOCALTIME", "LOCALTIMESTAMP",
+    "NATURAL", "NOT", "NOTNULL", "NULL", "OFFSET", "ON", "ONLY", "OR", "ORDER", "OUTER",
+    "OVERLAPS", "PLACING", "PRIMARY", "REFERENCES", "RETURNING", "RIGHT", "SELECT", "SESSION_USER",
+    "SET", "SIMILAR", "SOME", "SYMMETRIC", "SYSTEM_USER", "TABLE", "TABLESAMPLE", "THEN", "TO",
+    "TRAILING", "TRUE", "UNION", "UNIQUE", "UPDATE", "USER", "USING", "VALUES", "VARIADIC",
+    "VERBOSE", "WHEN", "WHERE", "WINDOW", "WITH",
+];
+
 impl<'a> Default for Postgres<'a> {
     fn default() -> Self {
         Postgres {
-            query: String::with_capacity(4096),
-            parameters: Vec::with_capacity(128),
+            query: String::new(),
+            parameters: Vec::new(),
+            parameter_columns: Vec::new(),
+            column: None,
+            quote_style: QuoteStyle::default(),
+            depth: 0,
+            predicates: 0,
         }
     }
 }
@@ -26,20 +60,60 @@ impl<'a> Visitor<'a> for Postgres<'a> {
     const C_BACKTICK_OPEN: &'static str = "\"";
     const C_BACKTICK_CLOSE: &'static str = "\"";
     const C_WILDCARD: &'static str = "%";
+    const RESERVED_WORDS: &'static [&'static str] = RESERVED_WORDS;
 
-    #[tracing::instrument(name = "render_sql", skip(query))]
-    fn build<Q>(query: Q) -> crate::Result<(String, Vec<Value<'a>>)>
+    #[tracing::instrument(name = "render_sql", skip(query, buffers))]
+    fn build_into_with<Q>(query: Q, quote_style: QuoteStyle, buffers: &mut VisitorBuffers<'a>) -> visitors::Result
     where
         Q: Into<Query<'a>>,
     {
+        let VisitorBuffers {
+            query: sql,
+            parameters,
+            parameter_columns,
+        } = buffers.take();
         let mut postgres = Postgres {
-            query: String::with_capacity(4096),
-            parameters: Vec::with_capacity(128),
+            query: sql,
+            parameters,
+            parameter_columns,
+            column: None,
+            quote_style,
+            depth: 0,
+            predicates: 0,
         };
 
-        Postgres::visit_query(&mut postgres, query.into())?;
+        let visited = Postgres::visit_query(&mut postgres, query.into())
+            .and_then(|_| check_parameters::<sqlx::Postgres>(&mut postgres.parameters, &postgres.parameter_columns));
+        if visited.is_ok() {
+            super::format::trace_sql(&postgres.query, &postgres.parameters, &postgres.parameter_columns);
+        }
+        buffers.restore(postgres.query, postgres.parameters, postgres.parameter_columns);
+
+        visited
+    }
+
+    fn quote_style(&self) -> QuoteStyle {
+        self.quote_style
+    }
 
-        Ok((postgres.query, postgres.parameters))
+    fn type_name(type_family: TypeFamily) -> Cow<'static, str> {
+        match type_family {
+            TypeFamily::Text(Some(TypeDataLength::Constant(len))) => {
+                format!("varchar({})", len).into()
+            }
+            TypeFamily::Text(_) => "text".into(),
+            TypeFamily::Int => "bigint".into(),
+            TypeFamily::Int32 => "integer".into(),
+            TypeFamily::Float => "real".into(),
+            TypeFamily::Double => "double precision".into(),
+            TypeFamily::Decimal(Some((p, s))) => format!("numeric({},{})", p, s).into(),
+            TypeFamily::Decimal(None) => "numeric".into(),
+            TypeFamily::Boolean => "boolean".into(),
+            TypeFamily::Uuid => "uuid".into(),
+            TypeFamily::DateTime => "timestamptz".into(),
+            TypeFamily::Bytes(_) => "bytea".into(),
+            TypeFamily::Json => "jsonb".into(),
+        }
     }
 
     fn write<D: fmt::Display>(&mut self, s: D) -> visitors::Result {
@@ -49,6 +123,24 @@ impl<'a> Visitor<'a> for Postgres<'a> {
 
     fn add_parameter(&mut self, value: Value<'a>) {
         self.parameters.push(value);
+        self.parameter_columns.push(self.column.clone());
+    }
+
+    fn bind_parameters_to(&mut self, column: Option<ColumnMeta>) {
+        self.column = column;
+    }
+
+    fn depth(&mut self) -> &mut usize {
+        &mut self.depth
+    }
+
+    fn predicates(&mut self) -> &mut usize {
+        &mut self.predicates
+    }
+
+    /// PostgreSQL has no `DELETE .. LIMIT`, the rows are deleted by `ctid`.
+    fn visit_limited_delete(&mut self, delete: Delete<'a>) -> visitors::Result {
+        self.visit_delete_by_row_id(delete, "ctid")
     }
 
     fn parameter_substitution(&mut self) -> visitors::Result {
@@ -56,6 +148,11 @@ impl<'a> Visitor<'a> for Postgres<'a> {
         self.write(self.parameters.len())
     }
 
+    /// Placeholders are numbered, `$1` may be used more than once.
+    fn count_placeholders(sql: &str) -> usize {
+        visitors::highest_numbered_placeholder(&visitors::code_segments(sql, false, true), "$")
+    }
+
     fn visit_limit_and_offset(
         &mut self,
         limit: Option<Value<'a>>,
@@ -155,6 +252,8 @@ impl<'a> Visitor<'a> for Postgres<'a> {
             self.visit_table(table, true)?;
         }
 
+        let bound: Vec<ColumnMeta> = insert.columns.iter().map(ColumnMeta::from).collect();
+
         match insert.values {
             Expression {
                 kind: ExpressionKind::Row(row),
@@ -167,7 +266,7 @@ impl<'a> Visitor<'a> for Postgres<'a> {
 
                     self.write(" (")?;
                     for (i, c) in insert.columns.into_iter().enumerate() {
-                        self.visit_column(c)?;
+                        self.visit_column(c.into_bare())?;
 
                         if i < (columns - 1) {
                             self.write(",")?;
@@ -176,7 +275,7 @@ impl<'a> Visitor<'a> for Postgres<'a> {
 
                     self.write(")")?;
                     self.write(" VALUES ")?;
-                    self.visit_row(row)?;
+                    self.visit_insert_row(&bound, row)?;
                 }
             }
             Expression {
@@ -187,7 +286,7 @@ impl<'a> Visitor<'a> for Postgres<'a> {
 
                 self.write(" (")?;
                 for (i, c) in insert.columns.into_iter().enumerate() {
-                    self.visit_column(c)?;
+                    self.visit_column(c.into_bare())?;
 
                     if i < (columns - 1) {
                         self.write(",")?;
@@ -199,18 +298,51 @@ impl<'a> Visitor<'a> for Postgres<'a> {
                 let values_len = values.len();
 
                 for (i, row) in values.into_iter().enumerate() {
-                    self.visit_row(row)?;
+                    self.visit_insert_row(&bound, row)?;
 
                     if i < (values_len - 1) {
                         self.write(", ")?;
                     }
                 }
             }
+            Expression {
+                kind: ExpressionKind::Selection(selection),
+                ..
+            } => {
+                let columns = insert.columns.len();
+
+                if columns > 0 {
+                    self.write(" (")?;
+                    for (i, c) in insert.columns.into_iter().enumerate() {
+                        self.visit_column(c.into_bare())?;
+
+                        if i < (columns - 1) {
+                            self.write(",")?;
+                        }
+                    }
+                    self.write(")")?;
+                }
+
+                self.write(" ")?;
+                self.visit_selection(selection)?;
+            }
             expr => self.surround_with("(", ")", |ref mut s| s.visit_expression(expr))?,
         }
 
-        if let Some(OnConflict::DoNothing) = insert.on_conflict {
-            self.write(" ON CONFLICT DO NOTHING")?;
+        match insert.on_conflict {
+            Some(OnConflict::DoNothing) => self.write(" ON CONFLICT DO NOTHING")?,
+            Some(OnConflict::DoNothingOn(target)) => {
+                self.write(" ON CONFLICT ")?;
+                self.visit_conflict_target(target)?;
+                self.write(" DO NOTHING")?;
+            }
+            Some(OnConflict::Update(target, assignments)) => {
+                self.write(" ON CONFLICT ")?;
+                self.visit_conflict_target(target)?;
+                self.write(" DO UPDATE SET ")?;
+                self.visit_conflict_assignments(assignments)?;
+            }
+            None => (),
         };
 
         if let Some(returning) = insert.returning {
@@ -224,6 +356,14 @@ impl<'a> Visitor<'a> for Postgres<'a> {
         Ok(())
     }
 
+    /// Counts the combinations of a row of columns, `COUNT(DISTINCT (a, b))`.
+    fn visit_count_distinct(&mut self, expr: Expression<'a>) -> visitors::Result {
+        match expr.kind {
+            ExpressionKind::Row(mut row) if row.len() == 1 => self.visit_expression(row.values.remove(0)),
+            _ => self.visit_expression(expr),
+        }
+    }
+
     fn visit_aggregate_to_string(&mut self, value: Expression<'a>) -> visitors::Result {
         self.write("ARRAY_TO_STRING")?;
         self.write("(")?;
@@ -235,27 +375,58 @@ impl<'a> Visitor<'a> for Postgres<'a> {
         self.write(")")
     }
 
-    fn visit_equals(&mut self, left: Expression<'a>, right: Expression<'a>) -> visitors::Result {
-        // LHS must be cast to json/xml-text if the right is a json/xml-text value and vice versa.
-        let right_cast = match left {
-            #[cfg(feature = "json")]
-            _ if left.is_json_value() => "::jsonb",
-            _ if left.is_xml_value() => "::text",
-            _ => "",
-        };
+    fn visit_cast(&mut self, cast: Cast<'a>) -> visitors::Result {
+        let target = Self::type_name(cast.target);
+
+        match cast.expression.kind {
+            // Already atomic or surrounded with parentheses.
+            ExpressionKind::Column(_)
+            | ExpressionKind::Parameterized(_)
+            | ExpressionKind::RawValue(_)
+            | ExpressionKind::Function(_)
+            | ExpressionKind::Op(_) => self.visit_expression(cast.expression)?,
+            _ => self.surround_with("(", ")", |s| s.visit_expression(cast.expression))?,
+        }
 
-        let left_cast = match right {
-            #[cfg(feature = "json")]
-            _ if right.is_json_value() => "::jsonb",
-            _ if right.is_xml_value() => "::text",
-            _ => "",
-        };
+        self.write("::")?;
+        self.write(target)
+    }
+
+    /// The types of the columns are the ones of the first row, `($1::bigint, $2::text)`.
+    fn visit_values_table(
+        &mut self,
+        values: Values<'a>,
+        alias: Option<Cow<'a, str>>,
+        columns: Vec<Cow<'a, str>>,
+    ) -> visitors::Result {
+        let values = values.cast_first_row();
+        self.surround_with("(VALUES ", ")", |ref mut s| {
+            let len = values.len();
+            for (i, row) in values.into_iter().enumerate() {
+                s.visit_row(row)?;
+
+                if i < (len - 1) {
+                    s.write(",")?;
+                }
+            }
+            Ok(())
+        })?;
+
+        if let Some(alias) = alias {
+            self.write(" AS ")?;
+            self.delimited_identifiers(&[&*alias])?;
+            self.visit_column_aliases(columns)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_equals(&mut self, left: Expression<'a>, right: Expression<'a>) -> visitors::Result {
+        let (left, right) = cast_for_comparison(left, right);
 
         self.visit_expression(left)?;
-        self.write(left_cast)?;
         self.write(" = ")?;
         self.visit_expression(right)?;
-        self.write(right_cast)?;
 
         Ok(())
     }
@@ -265,26 +436,11 @@ impl<'a> Visitor<'a> for Postgres<'a> {
         left: Expression<'a>,
         right: Expression<'a>,
     ) -> visitors::Result {
-        // LHS must be cast to json/xml-text if the right is a json/xml-text value and vice versa.
-        let right_cast = match left {
-            #[cfg(feature = "json")]
-            _ if left.is_json_value() => "::jsonb",
-            _ if left.is_xml_value() => "::text",
-            _ => "",
-        };
-
-        let left_cast = match right {
-            #[cfg(feature = "json")]
-            _ if right.is_json_value() => "::jsonb",
-            _ if right.is_xml_value() => "::text",
-            _ => "",
-        };
+        let (left, right) = cast_for_comparison(left, right);
 
         self.visit_expression(left)?;
-        self.write(left_cast)?;
         self.write(" <> ")?;
         self.visit_expression(right)?;
-        self.write(right_cast)?;
 
         Ok(())
     }
@@ -455,6 +611,35 @@ impl<'a> Visitor<'a> for Postgres<'a> {
     }
 }
 
+/// Json and xml values can not be compared to the columns storing them as
+/// is, the other side of the comparison must be cast to jsonb or text.
+fn cast_for_comparison<'a>(
+    left: Expression<'a>,
+    right: Expression<'a>,
+) -> (Expression<'a>, Expression<'a>) {
+    let cast_target = |expr: &Expression<'a>| match expr {
+        #[cfg(feature = "json")]
+        _ if expr.is_json_value() => Some(TypeFamily::Json),
+        _ if expr.is_xml_value() => Some(TypeFamily::Text(None)),
+        _ => None,
+    };
+
+    let left_target = cast_target(&right);
+    let right_target = cast_target(&left);
+
+    let left = match left_target {
+        Some(target) => left.cast(target),
+        None => left,
+    };
+
+    let right = match right_target {
+        Some(target) => right.cast(target),
+        None => right,
+    };
+
+    (left, right)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{prelude::*, visitors::*};
@@ -492,10 +677,50 @@ mod tests {
         result
     }
 
+    #[test]
+    fn test_limited_delete() {
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i32,
+            created_at: i32,
+        }
+
+        let query = Delete::from_table(Event::table())
+            .so_that(Event::created_at.less_than(1_000))
+            .order_by(Event::created_at.descend())
+            .limit(500);
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        assert_eq!(r#"DELETE FROM "events" WHERE "ctid" IN (SELECT "ctid" FROM "events" WHERE "events"."created_at" < $1 ORDER BY "events"."created_at" DESC LIMIT $2)"#, sql);
+        assert_eq!(vec![Value::from(1_000), Value::from(500usize)], params);
+
+        let err = Postgres::build(Delete::from_table(Event::table()).using(Event::table().alias("e")).limit(1)).unwrap_err();
+        assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidDelete(_)));
+    }
+
+    #[test]
+    fn test_aliases_are_dropped_in_predicates() {
+        #[derive(Entity)]
+        #[tablename = "test"]
+        struct Test {
+            #[column(primary_key)]
+            id: i32,
+        }
+
+        let subquery = Select::default().value(val!(4).alias("c"));
+        let conditions = val!(2).alias("x").equals(val!(3).alias("y")).and(Column::new("a").alias("b").in_selection(subquery));
+        let query = Select::from_table(Test::table()).value(val!(1).alias("one")).so_that(conditions);
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        assert_eq!(r#"SELECT $1 AS "one" FROM "test" WHERE ($2 = $3 AND "a" IN (SELECT $4 AS "c"))"#, sql);
+        assert_eq!(vec![Value::from(1), Value::from(2), Value::from(3), Value::from(4)], params);
+    }
+
     #[test]
     fn test_single_row_insert_default_values() {
-        // let query = User::insert();
-        let query = Insert::single_into(<User as Entity>::table());
+        let query = User::insert::<sqlx::Postgres>().into_inner();
         let (sql, params) = Postgres::build(query).unwrap();
 
         assert_eq!("INSERT INTO \"users\" DEFAULT VALUES", sql);
@@ -505,7 +730,7 @@ mod tests {
     #[test]
     fn test_single_row_insert() {
         let expected = expected_values(
-            "INSERT INTO \"users\" (\"users\".\"foo\") VALUES ($1)",
+            "INSERT INTO \"users\" (\"foo\") VALUES ($1)",
             vec![10],
         );
         let query = Insert::single_into(User::table()).value(User::foo, 10);
@@ -519,7 +744,7 @@ mod tests {
     #[cfg(feature = "postgres")]
     fn test_returning_insert() {
         let expected = expected_values(
-            "INSERT INTO \"users\" (\"users\".\"foo\") VALUES ($1) RETURNING \"users\".\"foo\"",
+            "INSERT INTO \"users\" (\"foo\") VALUES ($1) RETURNING \"users\".\"foo\"",
             vec![10],
         );
         let query = Insert::single_into(User::table()).value(User::foo, 10);
@@ -533,7 +758,7 @@ mod tests {
     #[test]
     fn test_multi_row_insert() {
         let expected = expected_values(
-            "INSERT INTO \"users\" (\"users\".\"foo\") VALUES ($1), ($2)",
+            "INSERT INTO \"users\" (\"foo\") VALUES ($1), ($2)",
             vec![10, 11],
         );
         let query = Insert::multi_into(User::table(), vec![User::foo])
@@ -545,6 +770,32 @@ mod tests {
         assert_eq!(expected.1, params);
     }
 
+    #[test]
+    fn test_insert_from_select_numbers_parameters_in_order() {
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i32,
+            kind: String,
+        }
+
+        let query = Select::from_table(Event::table())
+            .so_that(Event::kind.equals("signup"))
+            .insert_into::<User, _, _, _>(vec![
+                (User::foo.c(), Expression::from(Event::id)),
+                (User::xml.c(), Value::from("<signup/>").into()),
+            ])
+            .unwrap();
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "INSERT INTO \"users\" (\"foo\",\"xmlField\") SELECT \"events\".\"id\", $1 FROM \"events\" WHERE \"events\".\"kind\" = $2",
+            sql
+        );
+        assert_eq!(vec![Value::from("<signup/>"), Value::from("signup")], params);
+    }
+
     #[test]
     fn test_limit_and_offset_when_both_are_set() {
         let expected = expected_values(
@@ -584,6 +835,228 @@ mod tests {
         // #[column(primary_key)]
         bar: i32,
     }
+    #[test]
+    fn test_select_from_values_with_column_names() {
+        use crate::values;
+
+        let expected_sql = r#"SELECT "vals".* FROM (VALUES ($1,$2),($3,$4)) AS "vals"("a", "b")"#;
+        let values = Table::from(values!((1, 2), (3, 4))).alias_with_columns("vals", ["a", "b"]);
+        let (sql, params) = Postgres::build(Select::from_table(values)).unwrap();
+
+        assert_eq!(expected_sql, sql);
+        assert_eq!(4, params.len());
+    }
+
+    #[test]
+    fn test_typed_values_cast_the_first_row() {
+        let expected_sql = r#"SELECT "vals".* FROM (VALUES ($1::bigint,$2::text,$3),($4,$5,$6)) AS "vals"("a", "b", "c")"#;
+        let values = values!((1, "x", true), (2, "y", false)).typed([TypeFamily::Int, TypeFamily::Text(None)]);
+        let values = Table::from(values).alias_with_columns("vals", ["a", "b", "c"]);
+        let (sql, params) = Postgres::build(Select::from_table(values)).unwrap();
+
+        assert_eq!(expected_sql, sql);
+        assert_eq!(
+            vec![
+                Value::from(1),
+                Value::from("x"),
+                Value::from(true),
+                Value::from(2),
+                Value::from("y"),
+                Value::from(false),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_typed_values_are_not_cast_in_comparisons() {
+        let values = values!((1, 2)).typed([TypeFamily::Int, TypeFamily::Int]);
+        let (sql, _) = Postgres::build(
+            Select::from_table(TestEntity::table()).so_that(Row::from((TestEntity::bar, TestEntity::bar)).in_selection(values)),
+        )
+        .unwrap();
+
+        assert_eq!(
+            r#"SELECT "test".* FROM "test" WHERE ("test"."bar","test"."bar") IN (($1,$2))"#,
+            sql
+        );
+    }
+
+    #[test]
+    fn test_numbered_placeholders_are_counted_once() {
+        assert_eq!(2, Postgres::count_placeholders("SELECT * FROM users WHERE email = $2 OR name = $1 OR alias = $2"));
+        assert_eq!(
+            1,
+            Postgres::count_placeholders("SELECT '$2', $$ $3 $$, $body$ it's $4 $body$, ? FROM t WHERE x = $1")
+        );
+    }
+
+    #[derive(Entity)]
+    #[tablename = "subscribers"]
+    struct Subscriber {
+        #[column(primary_key)]
+        id: i32,
+        email: String,
+        deleted_at: Option<String>,
+    }
+
+    fn subscribe<'a>() -> Insert<'a> {
+        Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .into()
+    }
+
+    #[test]
+    fn test_on_conflict_on_constraint() {
+        let target = ConflictTarget::constraint("subscribers_email_key");
+        let (sql, params) =
+            Postgres::build(subscribe().on_conflict(OnConflict::DoNothingOn(target))).unwrap();
+
+        assert_eq!(
+            r#"INSERT INTO "subscribers" ("email") VALUES ($1) ON CONFLICT ON CONSTRAINT "subscribers_email_key" DO NOTHING"#,
+            sql
+        );
+        assert_eq!(vec![Value::from("a@b.c")], params);
+    }
+
+    #[test]
+    fn test_on_conflict_with_partial_index() {
+        let target =
+            ConflictTarget::columns(vec![Subscriber::email]).so_that(Subscriber::deleted_at.is_null());
+        let (sql, _) =
+            Postgres::build(subscribe().on_conflict(OnConflict::DoNothingOn(target))).unwrap();
+
+        assert_eq!(
+            r#"INSERT INTO "subscribers" ("email") VALUES ($1) ON CONFLICT ("email") WHERE "subscribers"."deleted_at" IS NULL DO NOTHING"#,
+            sql
+        );
+    }
+
+    #[test]
+    fn test_on_conflict_untargeted_stays_the_default() {
+        let (sql, _) = Postgres::build(subscribe().on_conflict(OnConflict::DoNothing)).unwrap();
+
+        assert_eq!(
+            r#"INSERT INTO "subscribers" ("email") VALUES ($1) ON CONFLICT DO NOTHING"#,
+            sql
+        );
+    }
+
+    #[test]
+    fn test_on_conflict_update_with_excluded_values() {
+        let insert: Insert = Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .value(Subscriber::deleted_at, Value::Text(None))
+            .into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let assignments = vec![(Subscriber::deleted_at.into(), excluded(Subscriber::deleted_at))];
+        let (sql, params) = Postgres::build(insert.on_conflict(OnConflict::Update(target, assignments))).unwrap();
+
+        assert_eq!(
+            r#"INSERT INTO "subscribers" ("email","deleted_at") VALUES ($1,$2) ON CONFLICT ("email") DO UPDATE SET "deleted_at" = EXCLUDED."deleted_at""#,
+            sql
+        );
+        assert_eq!(vec![Value::from("a@b.c"), Value::Text(None)], params);
+    }
+
+    #[test]
+    fn test_on_conflict_update_with_assignments() {
+        let target = ConflictTarget::constraint("subscribers_email_key");
+        let assignments = vec![(Subscriber::deleted_at.into(), Value::Text(None).into())];
+        let (sql, params) = Postgres::build(subscribe().on_conflict(OnConflict::Update(target, assignments))).unwrap();
+
+        assert_eq!(
+            r#"INSERT INTO "subscribers" ("email") VALUES ($1) ON CONFLICT ON CONSTRAINT "subscribers_email_key" DO UPDATE SET "deleted_at" = $2"#,
+            sql
+        );
+        assert_eq!(vec![Value::from("a@b.c"), Value::Text(None)], params);
+    }
+
+    #[test]
+    fn test_on_conflict_on_constraint_with_conditions_fails() {
+        let target = ConflictTarget::constraint("subscribers_email_key")
+            .so_that(Subscriber::deleted_at.is_null());
+        let res = Postgres::build(subscribe().on_conflict(OnConflict::DoNothingOn(target)));
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_alter_table() {
+        let email = ColumnDefinition::new("email", Some(TypeFamily::Text(None)), false);
+        let (sql, _) = Postgres::build(AlterTable::add_column(Subscriber::table(), email)).unwrap();
+        assert_eq!(r#"ALTER TABLE "subscribers" ADD COLUMN "email" text NOT NULL"#, sql);
+
+        let (sql, _) = Postgres::build(AlterTable::drop_column(Subscriber::table(), "email")).unwrap();
+        assert_eq!(r#"ALTER TABLE "subscribers" DROP COLUMN "email""#, sql);
+
+        let deleted_at = ColumnDefinition::new("deleted_at", Some(TypeFamily::DateTime), true);
+        let (sql, _) =
+            Postgres::build(AlterTable::set_nullable(Subscriber::table(), deleted_at)).unwrap();
+        assert_eq!(
+            r#"ALTER TABLE "subscribers" ALTER COLUMN "deleted_at" DROP NOT NULL"#,
+            sql
+        );
+    }
+
+    #[test]
+    fn test_alter_table_with_unknown_type_fails() {
+        let column = ColumnDefinition::new("point", None, true);
+
+        assert!(Postgres::build(AlterTable::add_column(Subscriber::table(), column)).is_err());
+    }
+
+    #[test]
+    fn test_create_table() {
+        #[derive(Entity)]
+        #[tablename = "comments"]
+        struct Comment {
+            #[column(primary_key, autoincrement)]
+            id: i64,
+            #[column(foreign_key = "posts.id")]
+            post_id: i64,
+            #[column(unique, length = 32)]
+            slug: String,
+            #[column(server_default = 0)]
+            likes: i32,
+            body: Option<String>,
+        }
+
+        let schema = Comment::schema();
+        let (sql, _) = Postgres::build(AlterTable::create_table(Comment::table(), TableDefinition::from(&schema))).unwrap();
+
+        let expected_sql = "CREATE TABLE \"comments\" (\"id\" bigint NOT NULL GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY, \"post_id\" bigint NOT NULL, \"slug\" varchar(32) NOT NULL, \"likes\" integer NOT NULL DEFAULT 0, \"body\" text, UNIQUE (\"slug\"), FOREIGN KEY (\"post_id\") REFERENCES \"posts\" (\"id\"))";
+        assert_eq!(expected_sql, sql);
+    }
+
+    #[test]
+    fn test_delete_using() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            banned: bool,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "posts"]
+        struct Post {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+        }
+
+        let query = Delete::from_table(Post::table())
+            .using(User::table())
+            .so_that(Post::user_id.equals(User::id).and(User::banned.equals(true)));
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        let expected_sql = "DELETE FROM \"posts\" USING \"users\" WHERE (\"posts\".\"user_id\" = \"users\".\"id\" AND \"users\".\"banned\" = $1)";
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(true)], params);
+    }
+
     #[test]
     fn test_distinct() {
         let expected_sql = "SELECT DISTINCT \"test\".\"bar\" FROM \"test\"";
@@ -615,6 +1088,43 @@ mod tests {
         assert_eq!(expected_sql, sql);
     }
 
+    #[test]
+    fn test_correlated_scalar_subquery_numbers_parameters_in_order() {
+        #[derive(Entity)]
+        #[tablename = "authors"]
+        struct Author {
+            #[column(primary_key)]
+            id: i64,
+            name: String,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "posts"]
+        struct Post {
+            #[column(primary_key)]
+            id: i64,
+            author_id: i64,
+            published: bool,
+        }
+
+        let authors = Author::table().alias("a");
+        let published = Select::from_table(Post::table())
+            .value(count(asterisk()))
+            .so_that(Post::author_id.equals(authors.col(Author::id)).and(Post::published.equals(true)));
+        let query = Select::from_table(authors.clone())
+            .value(authors.clone().asterisk())
+            .value(published.alias("published"))
+            .so_that(authors.col(Author::name).equals("Ada"));
+
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT \"a\".*, (SELECT COUNT(*) FROM \"posts\" WHERE (\"posts\".\"author_id\" = \"a\".\"id\" AND \"posts\".\"published\" = $1)) AS \"published\" FROM \"authors\" AS \"a\" WHERE \"a\".\"name\" = $2",
+            sql
+        );
+        assert_eq!(vec![Value::from(true), Value::from("Ada")], params);
+    }
+
     #[derive(Entity)]
     #[tablename = "foo"]
     struct Foo {
@@ -633,13 +1143,12 @@ mod tests {
     fn test_from() {
         let expected_sql =
             "SELECT \"foo\".*, \"bar\".\"a\" FROM \"foo\", (SELECT \"baz\".\"a\" FROM \"baz\") AS \"bar\"";
+        let bar = Table::from(Select::from_table(Baz::table()).column(Baz::a_column)).alias("bar");
         let query = Select::default()
             .and_from(Foo::table())
-            .and_from(
-                Table::from(Select::from_table(Baz::table()).column(Baz::a_column)).alias("bar"),
-            )
+            .and_from(bar.clone())
             .value(Foo::table().asterisk())
-            .column(Baz::a_column);
+            .column(bar.col(Baz::a_column));
 
         let (sql, _) = Postgres::build(query).unwrap();
         assert_eq!(expected_sql, sql);
@@ -670,7 +1179,7 @@ mod tests {
             vec![serde_json::json!({"a": "b"})],
         );
 
-        let value_expr: Expression = Value::json(serde_json::json!({"a":"b"})).into();
+        let value_expr: Expression = Value::json(serde_json::json!({"a":"b"}).into()).into();
         let query = Select::from_table(User::table()).so_that(value_expr.equals(User::json));
         let (sql, params) = Postgres::build(query).unwrap();
 
@@ -702,7 +1211,7 @@ mod tests {
             vec![serde_json::json!({"a": "b"})],
         );
 
-        let value_expr: Expression = Value::json(serde_json::json!({"a":"b"})).into();
+        let value_expr: Expression = Value::json(serde_json::json!({"a":"b"}).into()).into();
         let query = Select::from_table(User::table()).so_that(value_expr.not_equals(User::json));
         let (sql, params) = Postgres::build(query).unwrap();
 
@@ -882,7 +1391,7 @@ mod tests {
         let (sql, _) = Postgres::build(insert).unwrap();
 
         assert_eq!(
-            "INSERT INTO \"foo\" (\"foo\".\"foo\",\"foo\".\"bar\") VALUES ($1,DEFAULT)",
+            "INSERT INTO \"foo\" (\"foo\",\"bar\") VALUES ($1,DEFAULT)",
             sql
         );
     }
@@ -909,11 +1418,501 @@ mod tests {
             #[column(primary_key)]
             id: i32,
         }
-        let joined_table =
-            User::table().left_join(Post::table().alias("p").on(Post::user_id.equals(User::id)));
+        let posts = Post::table().alias("p");
+        let joined_table = User::table().left_join(posts.clone().on(posts.col(Post::user_id).equals(User::id)));
         let q = Select::from_table(joined_table).and_from(Toto::table());
         let (sql, _) = Postgres::build(q).unwrap();
 
         assert_eq!("SELECT \"User\".*, \"Toto\".* FROM \"User\" LEFT JOIN \"Post\" AS \"p\" ON \"p\".\"userId\" = \"User\".\"id\", \"Toto\"", sql);
     }
+
+    #[derive(Entity)]
+    #[tablename = "jobs"]
+    struct Job {
+        id: i32,
+        done: bool,
+    }
+
+    #[test]
+    fn test_select_for_update() {
+        let query = Select::from_table(Job::table()).so_that(Job::done.equals(false)).for_update();
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT \"jobs\".* FROM \"jobs\" WHERE \"jobs\".\"done\" = $1 FOR UPDATE",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_select_for_share_nowait() {
+        let query = Select::from_table(Job::table()).for_share().nowait();
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!("SELECT \"jobs\".* FROM \"jobs\" FOR SHARE NOWAIT", sql);
+    }
+
+    #[test]
+    fn test_select_for_update_skip_locked() {
+        let query = Select::from_table(Job::table()).limit(10).for_update().skip_locked();
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT \"jobs\".* FROM \"jobs\" LIMIT $1 FOR UPDATE SKIP LOCKED",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_invalid_locking_combinations() {
+        let queries = vec![
+            Select::from_table(Job::table()).for_update().for_share(),
+            Select::from_table(Job::table()).for_update().skip_locked().nowait(),
+            Select::from_table(Job::table()).skip_locked(),
+        ];
+
+        for query in queries {
+            let err = Postgres::build(query).unwrap_err();
+            assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidLocking(_)));
+        }
+    }
+
+    #[derive(Entity)]
+    #[tablename = "sessions"]
+    struct Session {
+        id: i32,
+        last_seen: String,
+    }
+
+    #[test]
+    fn test_date_sub_from_now() {
+        let query = Select::from_table(Session::table())
+            .so_that(Session::last_seen.less_than(date_sub(now(), IntervalArg::days(7))));
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT \"sessions\".* FROM \"sessions\" WHERE \"sessions\".\"last_seen\" < NOW() - INTERVAL '7 days'",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_date_add_in_update() {
+        let query = Update::table(Session::table())
+            .set(Session::last_seen, date_add(now(), IntervalArg::minutes(90)));
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!("UPDATE \"sessions\" SET \"last_seen\" = NOW() + INTERVAL '90 minutes'", sql);
+    }
+
+    #[test]
+    fn test_date_sub_from_current_date() {
+        let query = Select::from_table(Session::table())
+            .so_that(Session::last_seen.greater_than(date_sub(current_date(), IntervalArg::hours(3))));
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT \"sessions\".* FROM \"sessions\" WHERE \"sessions\".\"last_seen\" > CURRENT_DATE - INTERVAL '3 hours'",
+            sql
+        );
+    }
+
+    #[derive(Entity)]
+    #[tablename = "profiles"]
+    struct Profile {
+        id: i32,
+        name: String,
+        nickname: Option<String>,
+        surname: String,
+    }
+
+    #[test]
+    fn test_concat() {
+        let query = Select::from_table(Profile::table())
+            .value(concat((Profile::name, " ", Profile::surname)).alias("full_name"))
+            .so_that(Expression::from(concat([Profile::name, Profile::surname])).equals("ab"));
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT (\"profiles\".\"name\" || $1 || \"profiles\".\"surname\") AS \"full_name\" FROM \"profiles\" WHERE (\"profiles\".\"name\" || \"profiles\".\"surname\") = $2",
+            sql
+        );
+        assert_eq!(vec![Value::from(" "), Value::from("ab")], params);
+    }
+
+    #[test]
+    fn test_string_functions() {
+        let query = Select::from_table(Profile::table())
+            .value(length(trim(Profile::name)))
+            .value(nullif(Profile::nickname, Profile::name));
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT LENGTH(TRIM(\"profiles\".\"name\")), NULLIF(\"profiles\".\"nickname\", \"profiles\".\"name\") FROM \"profiles\"",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_null_safe_equality_of_columns() {
+        let query = Select::from_table(Profile::table()).so_that(Profile::nickname.is_not_distinct_from(Profile::name));
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT \"profiles\".* FROM \"profiles\" WHERE \"profiles\".\"nickname\" IS NOT DISTINCT FROM \"profiles\".\"name\"",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_null_safe_inequality_with_parameter() {
+        let nickname: Option<&str> = None;
+        let query = Select::from_table(Profile::table()).so_that(Profile::nickname.is_distinct_from(nickname));
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT \"profiles\".* FROM \"profiles\" WHERE \"profiles\".\"nickname\" IS DISTINCT FROM $1",
+            sql
+        );
+        assert_eq!(vec![Value::Text(None)], params);
+    }
+
+    #[derive(Entity)]
+    #[tablename = "tickets"]
+    struct Ticket {
+        id: i32,
+        order: i32,
+        #[column(quote)]
+        status: String,
+    }
+
+    #[test]
+    fn test_minimal_quoting() {
+        let query = Select::from_table(Ticket::table())
+            .column(Ticket::id)
+            .column(Ticket::order)
+            .column(Ticket::status)
+            .column(Column::new("createdAt"))
+            .column(Ticket::id.alias("Id"))
+            .so_that(Ticket::id.equals(1));
+        let (sql, _) = Postgres::build_with(query, QuoteStyle::Minimal).unwrap();
+
+        assert_eq!(
+            "SELECT tickets.id, tickets.\"order\", tickets.\"status\", \"createdAt\", tickets.id AS \"Id\" FROM tickets WHERE tickets.id = $1",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_quote_in_identifier_is_escaped() {
+        let query = || Select::from_table(Ticket::table()).column(Column::new("we\"ird"));
+
+        let (sql, _) = Postgres::build_with(query(), QuoteStyle::Always).unwrap();
+        assert_eq!("SELECT \"we\"\"ird\" FROM \"tickets\"", sql);
+
+        let (sql, _) = Postgres::build_with(query(), QuoteStyle::Minimal).unwrap();
+        assert_eq!("SELECT \"we\"\"ird\" FROM tickets", sql);
+    }
+
+    #[test]
+    fn test_hostile_identifiers_stay_within_one_identifier() {
+        let query = || {
+            Select::from_table(Ticket::table())
+                .column(Column::new("x\" FROM users; --").alias("a\"b"))
+        };
+
+        let (sql, _) = Postgres::build_with(query(), QuoteStyle::Always).unwrap();
+        assert_eq!("SELECT \"x\"\" FROM users; --\" AS \"a\"\"b\" FROM \"tickets\"", sql);
+
+        let (sql, _) = Postgres::build_with(query(), QuoteStyle::Minimal).unwrap();
+        assert_eq!("SELECT \"x\"\" FROM users; --\" AS \"a\"\"b\" FROM tickets", sql);
+    }
+
+    #[test]
+    fn test_reserved_words_are_sorted() {
+        assert!(Postgres::RESERVED_WORDS.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_cast_in_condition() {
+        let query = Select::from_table(Job::table())
+            .so_that(cast(Job::id, TypeFamily::Text(None)).equals("42"));
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!("SELECT \"jobs\".* FROM \"jobs\" WHERE \"jobs\".\"id\"::text = $1", sql);
+    }
+
+    #[test]
+    fn test_cast_of_an_operation() {
+        let total = Expression::from(Job::id) + Expression::from(1);
+        let query = Select::from_table(Job::table())
+            .value(total.cast(TypeFamily::Decimal(Some((10, 2)))).alias("total"));
+        let (sql, _) = Postgres::build(query).unwrap();
+
+        assert_eq!("SELECT (\"jobs\".\"id\" + $1)::numeric(10,2) AS \"total\" FROM \"jobs\"", sql);
+    }
+
+    #[test]
+    fn test_keyset_pagination() {
+        let query = Select::from_table(Subscriber::table()).after((Subscriber::email, Subscriber::id), ("a@b.c", 3));
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT \"subscribers\".* FROM \"subscribers\" WHERE (\"subscribers\".\"email\",\"subscribers\".\"id\") > ($1,$2)",
+            sql
+        );
+        assert_eq!(vec![Value::from("a@b.c"), Value::from(3)], params);
+    }
+
+    #[test]
+    fn test_count_distinct() {
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            session_id: i32,
+        }
+
+        let query = Select::from_table(Event::table()).value(count_distinct(Event::user_id));
+        let (sql, _) = Postgres::build(query).unwrap();
+        assert_eq!("SELECT COUNT(DISTINCT \"events\".\"user_id\") FROM \"events\"", sql);
+
+        let query = Select::from_table(Event::table()).value(count_distinct(Row::from((Event::user_id, Event::session_id))));
+        let (sql, _) = Postgres::build(query).unwrap();
+        assert_eq!("SELECT COUNT(DISTINCT (\"events\".\"user_id\",\"events\".\"session_id\")) FROM \"events\"", sql);
+    }
+
+    #[test]
+    fn test_aggregates() {
+        #[derive(Entity)]
+        #[tablename = "orders"]
+        struct Order {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            total: i32,
+            paid: bool,
+        }
+
+        let query = Select::from_table(Order::table()).value(count_star()).so_that(Order::user_id.equals(1));
+        let (sql, _) = Postgres::build(query).unwrap();
+        assert_eq!(r#"SELECT COUNT(*) FROM "orders" WHERE "orders"."user_id" = $1"#, sql);
+
+        let query = Select::from_table(Order::table())
+            .value(count(Order::id))
+            .value(sum(Order::total))
+            .value(min(Order::total))
+            .value(max(Order::total));
+        let (sql, _) = Postgres::build(query).unwrap();
+        assert_eq!(r#"SELECT COUNT("orders"."id"), SUM("orders"."total"), MIN("orders"."total"), MAX("orders"."total") FROM "orders""#, sql);
+
+        let query = Select::from_table(Order::table()).value(sum(Order::paid));
+        let (sql, _) = Postgres::build(query).unwrap();
+        assert_eq!(r#"SELECT SUM("orders"."paid") FROM "orders""#, sql);
+    }
+
+    /// The numbers of the `$n` placeholders of `sql`, in order.
+    fn placeholders(sql: &str) -> Vec<usize> {
+        sql.split('$').skip(1).map(|rest| rest.chars().take_while(char::is_ascii_digit).collect::<String>().parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_nested_selects_are_renumbered() {
+        #[derive(Entity)]
+        #[tablename = "orders"]
+        struct Order {
+            #[column(primary_key)]
+            id: i32,
+            customer_id: i32,
+            status: String,
+            total: f64,
+        }
+
+        let inner = Select::from_table(Order::table())
+            .column(Order::customer_id)
+            .so_that(Order::status.equals("paid").and(Order::total.greater_than(10.0)).and(Order::id.less_than(1000)));
+        let (_, inner_params) = Postgres::build(inner.clone()).unwrap();
+        assert_eq!(3, inner_params.len());
+
+        let outer = Select::from_table(Table::from(inner.clone()).alias("paid"))
+            .with(inner.clone().into_cte("recent"))
+            .value(inner.clone())
+            .so_that(Order::customer_id.in_selection(inner.clone()))
+            .and_where(Order::status.not_equals("void"))
+            .and_where(exists(inner));
+        let (sql, params) = Postgres::build(outer).unwrap();
+
+        assert_eq!((1..=16).collect::<Vec<_>>(), placeholders(&sql), "{}", sql);
+        assert_eq!(16, params.len());
+        let mut expected: Vec<_> = inner_params.iter().cloned().cycle().take(12).collect();
+        expected.insert(12, Value::from("void"));
+        expected.extend(inner_params);
+        assert_eq!(expected, params, "{}", sql);
+    }
+
+    #[test]
+    fn test_exists() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            banned: bool,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "posts"]
+        struct Post {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            published: bool,
+            flagged: bool,
+        }
+
+        let posts = || Select::from_table(Post::table()).value(1.raw());
+        let query = Select::from_table(User::table())
+            .so_that(User::banned.equals(false))
+            .and_where(exists(posts().so_that(Post::user_id.equals(User::id).and(Post::published.equals(true)))))
+            .and_where(not_exists(posts().so_that(Post::user_id.equals(User::id).and(Post::flagged.equals(true)))));
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        let expected_sql = "SELECT \"users\".* FROM \"users\" WHERE (\"users\".\"banned\" = $1 AND EXISTS (SELECT 1 FROM \"posts\" WHERE (\"posts\".\"user_id\" = \"users\".\"id\" AND \"posts\".\"published\" = $2)) AND NOT EXISTS (SELECT 1 FROM \"posts\" WHERE (\"posts\".\"user_id\" = \"users\".\"id\" AND \"posts\".\"flagged\" = $3)))";
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(false), Value::from(true), Value::from(true)], params);
+    }
+
+    #[test]
+    fn test_typed_columns_in_selection() {
+        #[derive(Entity)]
+        #[tablename = "orders"]
+        struct Order {
+            #[column(primary_key)]
+            id: i64,
+            region: String,
+            year: i32,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "archives"]
+        struct Archive {
+            #[column(primary_key)]
+            order_id: i64,
+            region: String,
+            year: i32,
+            purged: bool,
+        }
+
+        let archived = || Select::from_table(Archive::table()).so_that(Archive::purged.equals(true));
+
+        let query = Select::from_table(Order::table())
+            .so_that(Order::id.in_selection(archived().column(Archive::order_id)));
+        let (sql, params) = Postgres::build(query).unwrap();
+        assert_eq!("SELECT \"orders\".* FROM \"orders\" WHERE \"orders\".\"id\" IN (SELECT \"archives\".\"order_id\" FROM \"archives\" WHERE \"archives\".\"purged\" = $1)", sql);
+        assert_eq!(vec![Value::from(true)], params);
+
+        let query = Select::from_table(Order::table()).so_that(
+            Row::from((Order::id, Order::region))
+                .in_selection(archived().column(Archive::order_id).column(Archive::region)),
+        );
+        let (sql, params) = Postgres::build(query).unwrap();
+        assert_eq!("SELECT \"orders\".* FROM \"orders\" WHERE (\"orders\".\"id\",\"orders\".\"region\") IN (SELECT \"archives\".\"order_id\", \"archives\".\"region\" FROM \"archives\" WHERE \"archives\".\"purged\" = $1)", sql);
+        assert_eq!(vec![Value::from(true)], params);
+
+        let query = Select::from_table(Order::table()).so_that(
+            Row::from((Order::id, Order::region, Order::year)).not_in_selection(
+                archived()
+                    .column(Archive::order_id)
+                    .column(Archive::region)
+                    .column(Archive::year),
+            ),
+        );
+        let (sql, params) = Postgres::build(query).unwrap();
+        assert_eq!("SELECT \"orders\".* FROM \"orders\" WHERE (\"orders\".\"id\",\"orders\".\"region\",\"orders\".\"year\") NOT IN (SELECT \"archives\".\"order_id\", \"archives\".\"region\", \"archives\".\"year\" FROM \"archives\" WHERE \"archives\".\"purged\" = $1)", sql);
+        assert_eq!(vec![Value::from(true)], params);
+    }
+
+    #[test]
+    fn test_self_join_with_aliases() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            manager_id: Option<i32>,
+            name: String,
+        }
+
+        let employee = User::table().alias("e");
+        let manager = User::table().alias("m");
+        let query = Select::from_table(employee.clone())
+            .left_join(manager.clone().on(employee.col(User::manager_id).equals(manager.col(User::id))))
+            .value(employee.clone().asterisk())
+            .column(manager.col(User::name).alias("manager"))
+            .so_that(manager.col(User::name).equals("Ada"));
+        let (sql, params) = Postgres::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT \"e\".*, \"m\".\"name\" AS \"manager\" FROM \"users\" AS \"e\" LEFT JOIN \"users\" AS \"m\" ON \"e\".\"manager_id\" = \"m\".\"id\" WHERE \"m\".\"name\" = $1",
+            sql
+        );
+        assert_eq!(vec![Value::from("Ada")], params);
+    }
+
+    #[test]
+    fn test_empty_select_is_incomplete() {
+        let err = Postgres::build(Select::default()).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table or a value to select" }
+        ));
+    }
+
+    #[test]
+    fn test_select_with_only_ordering_is_incomplete() {
+        let query = Select::default().order_by(Subscriber::email.descend());
+        let err = Postgres::build(query).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table or a value to select" }
+        ));
+    }
+
+    #[test]
+    fn test_insert_without_table_is_incomplete() {
+        let insert: Insert = Insert::single().value(Subscriber::email, "a@b.c").into();
+        let err = Postgres::build(insert).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table to insert into" }
+        ));
+    }
+
+    #[test]
+    fn test_update_without_assignments_is_incomplete() {
+        let query = Update::table(Subscriber::table()).so_that(Subscriber::email.equals("a@b.c"));
+        let err = Postgres::build(query).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a column to update" }
+        ));
+    }
+
+    #[test]
+    fn test_upsert_without_assignments_is_incomplete() {
+        let insert: Insert = Insert::single_into(Subscriber::table()).value(Subscriber::email, "a@b.c").into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let err = Postgres::build(insert.on_conflict(OnConflict::Update(target, Vec::new()))).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a column to update on conflict" }
+        ));
+    }
 }
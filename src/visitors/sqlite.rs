@@ -1,26 +1,75 @@
 use crate::{
     ast::*,
-    error::{Error, ErrorKind},
-    visitors::{self, Visitor},
+    databases::check_parameters,
+    error::{Error, ErrorKind, Name},
+    visitors::{self, QuoteStyle, Visitor, VisitorBuffers},
 };
 
-use std::fmt::{self, Write};
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
+
+/// The error for `ALTER TABLE` operations SQLite only supports by creating a
+/// new table and copying the rows over.
+fn rebuild_required(table: &Table<'_>, reason: &'static str) -> Error {
+    let table = match &table.typ {
+        TableType::Table(name) => Name::available(name),
+        _ => Name::Unavailable,
+    };
+
+    Error::builder(ErrorKind::TableRebuildRequired { table, reason }).build()
+}
 
 /// A visitor to generate queries for the SQLite database.
 ///
 /// The returned parameter values implement the `ToSql` trait from rusqlite and
 /// can be used directly with the database.
-#[cfg_attr(feature = "docs", doc(cfg(feature = "sqlite")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "sqlite")))]
 pub struct Sqlite<'a> {
     query: String,
     parameters: Vec<Value<'a>>,
+    parameter_columns: Vec<Option<ColumnMeta>>,
+    /// The column parameters are bound to, set by `bind_parameters_to`.
+    column: Option<ColumnMeta>,
+    quote_style: QuoteStyle,
+    /// The number of expressions being visited.
+    depth: usize,
+    /// The number of predicates being visited, aliases are dropped in them.
+    predicates: usize,
 }
 
+/// Reserved words of SQLite, quoted even with [`QuoteStyle::Minimal`].
+const RESERVED_WORDS: &[&str] = &[
+    "ABORT", "ACTION", "ADD", "AFTER", "ALL", "ALTER", "ALWAYS", "ANALYZE", "AND", "ANY", "AS",
+    "ASC", "ATTACH", "AUTOINCREMENT", "BEFORE", "BEGIN", "BETWEEN", "BY", "CASCADE", "CASE",
+    "CAST", "CHECK", "COLLATE", "COLUMN", "COMMIT", "CONFLICT", "CONSTRAINT", "CREATE", "CROSS",
+    "CURRENT", "CURRENT_DATE", "CURRENT_TIME", "CURRENT_TIMESTAMP", "DATABASE", "DEFAULT",
+    "DEFERRABLE", "DEFERRED", "DELETE", "DESC", "DETACH", "DISTINCT", "DO", "DROP", "EACH", "ELSE",
+    "END", "ESCAPE", "EXCEPT", "EXCLUDE", "EXCLUSIVE", "EXISTS", "EXPLAIN", "FAIL", "FALSE",
+    "FILTER", "FIRST", "FOLLOWING", "FOR", "FOREIGN", "FROM", "FULL", "GENERATED", "GLOB", "GRANT",
+    "GROUP", "GROUPS", "HAVING", "IF", "IGNORE", "IMMEDIATE", "IN", "INDEX", "INDEXED",
+    "INITIALLY", "INNER", "INSERT", "INSTEAD", "INTERSECT", "INTO", "IS", "ISNULL", "JOIN", "KEY",
+    "LAST", "LEFT", "LIKE", "LIMIT", "MATCH", "MATERIALIZED", "NATURAL", "NO", "NOT", "NOTHING",
+    "NOTNULL", "NULL", "NULLS", "OF", "OFFSET", "ON", "OR", "ORDER", "OTHERS", "OUTER", "OVER",
+    "PARTITION", "PLAN", "PRAGMA", "PRECEDING", "PRIMARY", "QUERY", "RAISE", "RANGE", "RECURSIVE",
+    "REFERENCES", "REGEXP", "REINDEX", "RELEASE", "RENAME", "REPLACE", "RESTRICT", "RETURNING",
+    "RIGHT", "ROLLBACK", "ROW", "ROWS", "SAVEPOINT", "SELECT", "SET", "TABLE", "TEMP", "TEMPORARY",
+    "THEN", "TIES", "TO", "TRANSACTION", "TRIGGER", "TRUE", "UNBOUNDED", "UNION", "UNIQUE",
+    "UPDATE", "USING", "VACUUM", "VALUES", "VIEW", "VIRTUAL", "WHEN", "WHERE", "WINDOW", "WITH",
+    "WITHOUT",
+];
+
 impl<'a> Default for Sqlite<'a> {
     fn default() -> Self {
         Sqlite {
-            query: String::with_capacity(4096),
-            parameters: Vec::with_capacity(128),
+            query: String::new(),
+            parameters: Vec::new(),
+            parameter_columns: Vec::new(),
+            column: None,
+            quote_style: QuoteStyle::default(),
+            depth: 0,
+            predicates: 0,
         }
     }
 }
@@ -29,20 +78,52 @@ impl<'a> Visitor<'a> for Sqlite<'a> {
     const C_BACKTICK_OPEN: &'static str = "`";
     const C_BACKTICK_CLOSE: &'static str = "`";
     const C_WILDCARD: &'static str = "%";
+    const RESERVED_WORDS: &'static [&'static str] = RESERVED_WORDS;
 
-    #[tracing::instrument(name = "render_sql", skip(query))]
-    fn build<Q>(query: Q) -> crate::Result<(String, Vec<Value<'a>>)>
+    #[tracing::instrument(name = "render_sql", skip(query, buffers))]
+    fn build_into_with<Q>(query: Q, quote_style: QuoteStyle, buffers: &mut VisitorBuffers<'a>) -> visitors::Result
     where
         Q: Into<Query<'a>>,
     {
+        let VisitorBuffers {
+            query: sql,
+            parameters,
+            parameter_columns,
+        } = buffers.take();
         let mut sqlite = Sqlite {
-            query: String::with_capacity(4096),
-            parameters: Vec::with_capacity(128),
+            query: sql,
+            parameters,
+            parameter_columns,
+            column: None,
+            quote_style,
+            depth: 0,
+            predicates: 0,
         };
 
-        Sqlite::visit_query(&mut sqlite, query.into())?;
+        let visited = Sqlite::visit_query(&mut sqlite, query.into())
+            .and_then(|_| check_parameters::<sqlx::Sqlite>(&mut sqlite.parameters, &sqlite.parameter_columns));
+        if visited.is_ok() {
+            super::format::trace_sql(&sqlite.query, &sqlite.parameters, &sqlite.parameter_columns);
+        }
+        buffers.restore(sqlite.query, sqlite.parameters, sqlite.parameter_columns);
+
+        visited
+    }
+
+    fn quote_style(&self) -> QuoteStyle {
+        self.quote_style
+    }
 
-        Ok((sqlite.query, sqlite.parameters))
+    /// The storage classes of SQLite.
+    fn type_name(type_family: TypeFamily) -> Cow<'static, str> {
+        match type_family {
+            TypeFamily::Int | TypeFamily::Int32 | TypeFamily::Boolean => "INTEGER",
+            TypeFamily::Float | TypeFamily::Double => "REAL",
+            TypeFamily::Decimal(_) => "NUMERIC",
+            TypeFamily::Bytes(_) => "BLOB",
+            TypeFamily::Text(_) | TypeFamily::Uuid | TypeFamily::DateTime | TypeFamily::Json => "TEXT",
+        }
+        .into()
     }
 
     fn write<D: fmt::Display>(&mut self, s: D) -> visitors::Result {
@@ -114,16 +195,42 @@ impl<'a> Visitor<'a> for Sqlite<'a> {
     }
 
     fn visit_insert(&mut self, insert: Insert<'a>) -> visitors::Result {
-        match insert.on_conflict {
-            Some(OnConflict::DoNothing) => self.write("INSERT OR IGNORE")?,
-            None => self.write("INSERT")?,
+        let conflict = match insert.on_conflict {
+            Some(OnConflict::DoNothing) => {
+                self.write("INSERT OR IGNORE")?;
+                None
+            }
+            Some(OnConflict::DoNothingOn(target)) => {
+                self.write("INSERT")?;
+                Some((target, None))
+            }
+            Some(OnConflict::Update(target, assignments)) => {
+                self.write("INSERT")?;
+                Some((target, Some(assignments)))
+            }
+            None => {
+                self.write("INSERT")?;
+                None
+            }
         };
 
+        if let Some((ConflictTarget { typ: ConflictTargetType::Constraint(_), .. }, _)) = conflict {
+            let msg = "SQLite has no named constraints to target on conflict.";
+            let kind = ErrorKind::conversion(msg);
+
+            let mut builder = Error::builder(kind);
+            builder.set_original_message(msg);
+
+            return Err(builder.build());
+        }
+
         if let Some(table) = insert.table {
             self.write(" INTO ")?;
             self.visit_table(table, true)?;
         }
 
+        let bound: Vec<ColumnMeta> = insert.columns.iter().map(ColumnMeta::from).collect();
+
         match insert.values {
             Expression {
                 kind: ExpressionKind::Row(row),
@@ -136,7 +243,7 @@ impl<'a> Visitor<'a> for Sqlite<'a> {
 
                     self.write(" (")?;
                     for (i, c) in insert.columns.into_iter().enumerate() {
-                        self.visit_column(c)?;
+                        self.visit_column(c.into_bare())?;
 
                         if i < (columns - 1) {
                             self.write(", ")?;
@@ -145,7 +252,7 @@ impl<'a> Visitor<'a> for Sqlite<'a> {
 
                     self.write(")")?;
                     self.write(" VALUES ")?;
-                    self.visit_row(row)?;
+                    self.visit_insert_row(&bound, row)?;
                 }
             }
             Expression {
@@ -156,7 +263,7 @@ impl<'a> Visitor<'a> for Sqlite<'a> {
 
                 self.write(" (")?;
                 for (i, c) in insert.columns.into_iter().enumerate() {
-                    self.visit_column(c)?;
+                    self.visit_column(c.into_bare())?;
 
                     if i < (columns - 1) {
                         self.write(", ")?;
@@ -168,16 +275,50 @@ impl<'a> Visitor<'a> for Sqlite<'a> {
                 let values_len = values.len();
 
                 for (i, row) in values.into_iter().enumerate() {
-                    self.visit_row(row)?;
+                    self.visit_insert_row(&bound, row)?;
 
                     if i < (values_len - 1) {
                         self.write(", ")?;
                     }
                 }
             }
+            Expression {
+                kind: ExpressionKind::Selection(selection),
+                ..
+            } => {
+                let columns = insert.columns.len();
+
+                if columns > 0 {
+                    self.write(" (")?;
+                    for (i, c) in insert.columns.into_iter().enumerate() {
+                        self.visit_column(c.into_bare())?;
+
+                        if i < (columns - 1) {
+                            self.write(", ")?;
+                        }
+                    }
+                    self.write(")")?;
+                }
+
+                self.write(" ")?;
+                self.visit_selection(selection)?;
+            }
             expr => self.visit_expression(expr)?,
         }
 
+        if let Some((target, assignments)) = conflict {
+            self.write(" ON CONFLICT ")?;
+            self.visit_conflict_target(target)?;
+
+            match assignments {
+                Some(assignments) => {
+                    self.write(" DO UPDATE SET ")?;
+                    self.visit_conflict_assignments(assignments)?;
+                }
+                None => self.write(" DO NOTHING")?,
+            }
+        }
+
         if let Some(returning) = insert.returning {
             if !returning.is_empty() {
                 let values_len = returning.len();
@@ -202,6 +343,60 @@ impl<'a> Visitor<'a> for Sqlite<'a> {
 
     fn add_parameter(&mut self, value: Value<'a>) {
         self.parameters.push(value);
+        self.parameter_columns.push(self.column.clone());
+    }
+
+    fn bind_parameters_to(&mut self, column: Option<ColumnMeta>) {
+        self.column = column;
+    }
+
+    fn depth(&mut self) -> &mut usize {
+        &mut self.depth
+    }
+
+    fn predicates(&mut self) -> &mut usize {
+        &mut self.predicates
+    }
+
+    /// SQLite adds nullable columns only, `NOT NULL` needs a default value.
+    fn visit_add_column(&mut self, table: Table<'a>, column: ColumnDefinition) -> visitors::Result {
+        if !column.nullable {
+            return Err(rebuild_required(&table, "SQLite can not add a NOT NULL column"));
+        }
+
+        self.write("ALTER TABLE ")?;
+        self.visit_table(table, false)?;
+        self.write(" ADD COLUMN ")?;
+        self.visit_column_definition(column)
+    }
+
+    fn visit_drop_column(&mut self, table: Table<'a>, _: Cow<'static, str>) -> visitors::Result {
+        Err(rebuild_required(&table, "SQLite can not drop columns"))
+    }
+
+    /// SQLite has no multi-table delete, the other tables are selected in
+    /// an `EXISTS` subquery with the conditions.
+    fn visit_delete_using(&mut self, delete: Delete<'a>) -> visitors::Result {
+        let mut select = Select::default().value(1.raw());
+        for table in delete.using {
+            select = select.and_from(table);
+        }
+        if let Some(conditions) = delete.conditions {
+            select = select.so_that(conditions);
+        }
+
+        self.write("DELETE FROM ")?;
+        self.visit_table(delete.table, true)?;
+        self.write(" WHERE EXISTS ")?;
+        self.surround_with("(", ")", |ref mut s| s.visit_select(select))
+    }
+
+    fn visit_generated_primary_key(&mut self) -> visitors::Result {
+        self.write(" PRIMARY KEY AUTOINCREMENT")
+    }
+
+    fn visit_set_nullable(&mut self, table: Table<'a>, _: ColumnDefinition) -> visitors::Result {
+        Err(rebuild_required(&table, "SQLite can not change the nullability of a column"))
     }
 
     fn visit_limit_and_offset(
@@ -232,11 +427,66 @@ impl<'a> Visitor<'a> for Sqlite<'a> {
         }
     }
 
+    fn visit_locking(&mut self, strength: LockStrength, _: LockWait) -> visitors::Result {
+        // The whole database is locked by a writing transaction, there are no
+        // row locks to take.
+        tracing::warn!(?strength, "row locking is not supported by SQLite, ignored");
+        Ok(())
+    }
+
+    fn visit_null_safe_equals(
+        &mut self,
+        left: Expression<'a>,
+        right: Expression<'a>,
+        negate: bool,
+    ) -> visitors::Result {
+        self.visit_expression(left)?;
+
+        if negate {
+            self.write(" IS NOT ")?;
+        } else {
+            self.write(" IS ")?;
+        }
+
+        self.visit_expression(right)
+    }
+
     fn visit_aggregate_to_string(&mut self, value: Expression<'a>) -> visitors::Result {
         self.write("GROUP_CONCAT")?;
         self.surround_with("(", ")", |ref mut s| s.visit_expression(value))
     }
 
+    fn visit_now(&mut self) -> visitors::Result {
+        self.write("datetime('now')")
+    }
+
+    fn visit_current_date(&mut self) -> visitors::Result {
+        self.write("date('now')")
+    }
+
+    fn visit_date_add(&mut self, expression: Expression<'a>, interval: IntervalArg) -> visitors::Result {
+        let unit = match interval.unit() {
+            IntervalUnit::Day => "days",
+            IntervalUnit::Hour => "hours",
+            IntervalUnit::Minute => "minutes",
+        };
+        let modifier = format!("'{:+} {}'", interval.amount(), unit);
+
+        self.write("datetime")?;
+        self.surround_with("(", ")", |s| {
+            // `datetime('now', ...)` instead of `datetime(datetime('now'), ...)`.
+            match expression.kind {
+                ExpressionKind::Function(ref fun) if fun.typ_ == FunctionType::Now => {
+                    s.write("'now'")?
+                }
+                _ => s.visit_expression(expression)?,
+            }
+
+            s.write(", ")?;
+            s.write(modifier)
+        })
+    }
+
     fn visit_values(&mut self, values: Values<'a>) -> visitors::Result {
         self.surround_with("(VALUES ", ")", |ref mut s| {
             let len = values.len();
@@ -251,6 +501,41 @@ impl<'a> Visitor<'a> for Sqlite<'a> {
         })
     }
 
+    /// SQLite has no column list for aliases, the columns of `VALUES` are
+    /// renamed from `column1`, `column2`, ... in a subquery.
+    fn visit_values_table(
+        &mut self,
+        values: Values<'a>,
+        alias: Option<Cow<'a, str>>,
+        columns: Vec<Cow<'a, str>>,
+    ) -> visitors::Result {
+        if columns.is_empty() || alias.is_none() {
+            self.visit_values(values)?;
+        } else {
+            self.surround_with("(SELECT ", ")", |ref mut s| {
+                let len = columns.len();
+                for (i, column) in columns.iter().enumerate() {
+                    s.write(format!("column{} AS ", i + 1))?;
+                    s.visit_identifier(column, false)?;
+
+                    if i < (len - 1) {
+                        s.write(", ")?;
+                    }
+                }
+
+                s.write(" FROM ")?;
+                s.visit_values(values)
+            })?;
+        }
+
+        if let Some(alias) = alias {
+            self.write(" AS ")?;
+            self.delimited_identifiers(&[&*alias])?;
+        }
+
+        Ok(())
+    }
+
     #[cfg(all(feature = "json", any(feature = "postgres", feature = "mysql")))]
     fn visit_json_extract(&mut self, _json_extract: JsonExtract<'a>) -> visitors::Result {
         unimplemented!("JSON filtering is not yet supported on SQLite")
@@ -371,6 +656,47 @@ mod tests {
         assert_eq!(vec![Value::Text(None)], params);
     }
 
+    #[test]
+    fn test_limited_delete() {
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i32,
+            created_at: i32,
+        }
+
+        let query = Delete::from_table(Event::table())
+            .so_that(Event::created_at.less_than(1_000))
+            .order_by(Event::created_at.descend())
+            .limit(500);
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!("DELETE FROM `events` WHERE `rowid` IN (SELECT `rowid` FROM `events` WHERE `events`.`created_at` < ? ORDER BY `events`.`created_at` DESC LIMIT ?)", sql);
+        assert_eq!(vec![Value::from(1_000), Value::from(500usize)], params);
+
+        let err = Sqlite::build(Delete::from_table(Event::table()).using(Event::table().alias("e")).limit(1)).unwrap_err();
+        assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidDelete(_)));
+    }
+
+    #[test]
+    fn test_aliases_are_dropped_in_predicates() {
+        #[derive(Entity)]
+        #[tablename = "test"]
+        struct Test {
+            #[column(primary_key)]
+            id: i32,
+        }
+
+        let subquery = Select::default().value(val!(4).alias("c"));
+        let conditions = val!(2).alias("x").equals(val!(3).alias("y")).and(Column::new("a").alias("b").in_selection(subquery));
+        let query = Select::from_table(Test::table()).value(val!(1).alias("one")).so_that(conditions);
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!("SELECT ? AS `one` FROM `test` WHERE (? = ? AND `a` IN (SELECT ? AS `c`))", sql);
+        assert_eq!(vec![Value::from(1), Value::from(2), Value::from(3), Value::from(4)], params);
+    }
+
     #[derive(Entity)]
     #[tablename = "musti"]
     struct Musti {
@@ -391,6 +717,35 @@ mod tests {
         assert_eq!(default_params(vec![]), params);
     }
 
+    #[test]
+    fn test_insert_from_select() {
+        #[derive(Entity)]
+        #[tablename = "orders"]
+        struct Order {
+            #[column(primary_key)]
+            id: i32,
+            total: i32,
+            archived: bool,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "order_archive"]
+        struct OrderArchive {
+            #[column(primary_key, autoincrement)]
+            id: i32,
+            total: i32,
+            note: Option<String>,
+        }
+
+        let expected_sql =
+            "INSERT INTO `order_archive` (`total`) SELECT `orders`.`total` FROM `orders` WHERE `orders`.`archived` = ?";
+        let query = OrderArchive::copy_from::<Order, _>(Order::archived.equals(true)).unwrap();
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(true)], params);
+    }
+
     #[derive(Entity)]
     #[tablename = "test"]
     struct TestEntity {
@@ -786,6 +1141,302 @@ mod tests {
         assert_eq!(expected_sql, sql);
     }
 
+    #[test]
+    fn test_select_from_values_with_column_names() {
+        use crate::values;
+
+        let expected_sql =
+            "SELECT `vals`.* FROM (SELECT column1 AS `a`, column2 AS `b` FROM (VALUES (?,?),(?,?))) AS `vals`";
+        let values = Table::from(values!((1, 2), (3, 4))).alias_with_columns("vals", ["a", "b"]);
+        let (sql, params) = Sqlite::build(Select::from_table(values)).unwrap();
+
+        assert_eq!(expected_sql, sql);
+        assert_eq!(4, params.len());
+    }
+
+    #[derive(Entity)]
+    #[tablename = "subscribers"]
+    struct Subscriber {
+        #[column(primary_key)]
+        id: i32,
+        email: String,
+        deleted_at: Option<String>,
+    }
+
+    #[test]
+    fn test_on_conflict_with_target_columns() {
+        let insert: Insert = Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let (sql, _) = Sqlite::build(insert.on_conflict(OnConflict::DoNothingOn(target))).unwrap();
+
+        assert_eq!(
+            "INSERT INTO `subscribers` (`email`) VALUES (?) ON CONFLICT (`email`) DO NOTHING",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_on_conflict_update_with_excluded_values() {
+        let insert: Insert = Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .value(Subscriber::deleted_at, Value::Text(None))
+            .into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let assignments = vec![(Subscriber::deleted_at.into(), excluded(Subscriber::deleted_at))];
+        let (sql, params) = Sqlite::build(insert.on_conflict(OnConflict::Update(target, assignments))).unwrap();
+
+        assert_eq!(
+            "INSERT INTO `subscribers` (`email`, `deleted_at`) VALUES (?,?) ON CONFLICT (`email`) DO UPDATE SET `deleted_at` = EXCLUDED.`deleted_at`",
+            sql
+        );
+        assert_eq!(vec![Value::from("a@b.c"), Value::Text(None)], params);
+    }
+
+    #[test]
+    fn test_on_conflict_update_with_assignments() {
+        let insert: Insert = Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let assignments = vec![
+            (Subscriber::deleted_at.into(), Value::Text(None).into()),
+            (Subscriber::email.into(), excluded(Subscriber::email)),
+        ];
+        let (sql, params) = Sqlite::build(insert.on_conflict(OnConflict::Update(target, assignments))).unwrap();
+
+        assert_eq!(
+            "INSERT INTO `subscribers` (`email`) VALUES (?) ON CONFLICT (`email`) DO UPDATE SET `deleted_at` = ?, `email` = EXCLUDED.`email`",
+            sql
+        );
+        assert_eq!(vec![Value::from("a@b.c"), Value::Text(None)], params);
+    }
+
+    #[test]
+    fn test_on_conflict_update_on_constraint_fails() {
+        let insert: Insert = Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .into();
+        let target = ConflictTarget::constraint("subscribers_email_key");
+        let assignments = vec![(Subscriber::email.into(), excluded(Subscriber::email))];
+        let res = Sqlite::build(insert.on_conflict(OnConflict::Update(target, assignments)));
+
+        assert!(matches!(
+            res.unwrap_err().kind(),
+            crate::error::ErrorKind::ConversionError(_)
+        ));
+    }
+
+    #[test]
+    fn test_on_conflict_on_constraint_fails() {
+        let insert: Insert = Insert::single_into(Subscriber::table())
+            .value(Subscriber::email, "a@b.c")
+            .into();
+        let target = ConflictTarget::constraint("subscribers_email_key");
+        let res = Sqlite::build(insert.on_conflict(OnConflict::DoNothingOn(target)));
+
+        assert!(matches!(
+            res.unwrap_err().kind(),
+            crate::error::ErrorKind::ConversionError(_)
+        ));
+    }
+
+    #[test]
+    fn test_alter_table_add_column() {
+        let column = ColumnDefinition::new("bio", Some(TypeFamily::Text(None)), true);
+        let (sql, _) = Sqlite::build(AlterTable::add_column(Subscriber::table(), column)).unwrap();
+
+        assert_eq!("ALTER TABLE `subscribers` ADD COLUMN `bio` TEXT", sql);
+    }
+
+    #[test]
+    fn test_create_index() {
+        let alter =
+            AlterTable::create_index(Subscriber::table(), "subscribers_email", vec!["email", "id"], true);
+        let (sql, _) = Sqlite::build(alter).unwrap();
+
+        assert_eq!(
+            "CREATE UNIQUE INDEX `subscribers_email` ON `subscribers` (`email`, `id`)",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_create_table() {
+        #[derive(Entity)]
+        #[tablename = "comments"]
+        struct Comment {
+            #[column(primary_key, autoincrement)]
+            id: i64,
+            #[column(foreign_key = "posts.id")]
+            post_id: i64,
+            #[column(unique, length = 32)]
+            slug: String,
+            #[column(server_default = 0)]
+            likes: i32,
+            body: Option<String>,
+        }
+
+        let schema = Comment::schema();
+        let (sql, _) = Sqlite::build(AlterTable::create_table(Comment::table(), TableDefinition::from(&schema))).unwrap();
+
+        let expected_sql = "CREATE TABLE `comments` (`id` INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT, `post_id` INTEGER NOT NULL, `slug` TEXT NOT NULL, `likes` INTEGER NOT NULL DEFAULT 0, `body` TEXT, UNIQUE (`slug`), FOREIGN KEY (`post_id`) REFERENCES `posts` (`id`))";
+        assert_eq!(expected_sql, sql);
+    }
+
+    #[test]
+    fn test_delete_using() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            banned: bool,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "posts"]
+        struct Post {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+        }
+
+        let query = Delete::from_table(Post::table())
+            .using(User::table())
+            .so_that(Post::user_id.equals(User::id).and(User::banned.equals(true)));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        let expected_sql = "DELETE FROM `posts` WHERE EXISTS (SELECT 1 FROM `users` WHERE (`posts`.`user_id` = `users`.`id` AND `users`.`banned` = ?))";
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(true)], params);
+    }
+
+    #[test]
+    fn test_alter_table_requiring_a_rebuild() {
+        let not_null = ColumnDefinition::new("bio", Some(TypeFamily::Text(None)), false);
+        let alters = vec![
+            AlterTable::add_column(Subscriber::table(), not_null.clone()),
+            AlterTable::drop_column(Subscriber::table(), "email"),
+            AlterTable::set_nullable(Subscriber::table(), not_null),
+        ];
+
+        for alter in alters {
+            let err = Sqlite::build(alter).unwrap_err();
+
+            assert!(matches!(
+                err.kind(),
+                crate::error::ErrorKind::TableRebuildRequired { table, .. }
+                    if table == &crate::error::Name::available("subscribers")
+            ));
+        }
+    }
+
+    #[derive(Entity)]
+    #[tablename = "coupons"]
+    struct Coupon {
+        id: i32,
+        #[column(length = 8)]
+        code: String,
+    }
+
+    #[test]
+    fn test_parameters_bound_to_columns() {
+        let insert = Insert::single_into(Coupon::table())
+            .value(Coupon::id, 1)
+            .value(Coupon::code, "SPRING");
+        let (_, _, columns) = Sqlite::build_with_columns(insert, QuoteStyle::default()).unwrap();
+
+        let code = ColumnMeta {
+            name: "code".into(),
+            length: Some(8),
+            write_only: false,
+        };
+        let id = ColumnMeta {
+            name: "id".into(),
+            length: None,
+            write_only: false,
+        };
+        assert_eq!(vec![Some(id), Some(code.clone())], columns);
+
+        let update = Update::table(Coupon::table())
+            .set(Coupon::code, "SUMMER")
+            .so_that(Coupon::id.equals(1));
+        let (_, _, columns) = Sqlite::build_with_columns(update, QuoteStyle::default()).unwrap();
+
+        // The condition is not bound to a column.
+        assert_eq!(vec![Some(code), None], columns);
+    }
+
+    #[test]
+    #[cfg(feature = "postgres")]
+    fn test_unsupported_parameter_fails_at_build() {
+        let money = Value::PgMoney(Some(sqlx::postgres::types::PgMoney(100)));
+
+        let insert = Insert::single_into(Coupon::table())
+            .value(Coupon::code, "SPRING")
+            .value(Coupon::id, money.clone());
+        let error = Sqlite::build(insert).unwrap_err();
+        assert_eq!(
+            "Conversion failed: parameter 2 for column `id`, PgMoney(Some(PgMoney(100))), is not supported by SQLite",
+            error.to_string()
+        );
+
+        let select = Select::from_table(Coupon::table()).so_that(Coupon::id.equals(money));
+        let error = Sqlite::build(select).unwrap_err();
+        assert_eq!(
+            "Conversion failed: parameter 1, PgMoney(Some(PgMoney(100))), is not supported by SQLite",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_all_of_and_any_of() {
+        let build = |conditions: ConditionTree<'static>| {
+            Sqlite::build(Select::from_table(TestEntity::table()).so_that(conditions)).unwrap()
+        };
+        let clauses = |count: i32| (0..count).map(|i| TestEntity::bar.equals(i)).collect::<Vec<_>>();
+
+        assert_eq!("SELECT `test`.* FROM `test` WHERE 1=1", build(ConditionTree::all_of(clauses(0))).0);
+        assert_eq!("SELECT `test`.* FROM `test` WHERE 1=0", build(ConditionTree::any_of(clauses(0))).0);
+
+        let (sql, params) = build(ConditionTree::all_of(clauses(1)));
+        assert_eq!("SELECT `test`.* FROM `test` WHERE `test`.`bar` = ?", sql);
+        assert_eq!(vec![Value::from(0)], params);
+
+        let (sql, params) = build(ConditionTree::any_of(clauses(3)));
+        assert_eq!(
+            "SELECT `test`.* FROM `test` WHERE (`test`.`bar` = ? OR `test`.`bar` = ? OR `test`.`bar` = ?)",
+            sql
+        );
+        assert_eq!(vec![Value::from(0), Value::from(1), Value::from(2)], params);
+
+        let (sql, params) = build(ConditionTree::all_of(clauses(5000)));
+        assert_eq!(5000, params.len());
+        assert_eq!(4999, sql.matches(" AND ").count());
+
+        let (sql, _) = Sqlite::build(Select::from_table(TestEntity::table()).so_that_all(clauses(3))).unwrap();
+        assert_eq!(
+            "SELECT `test`.* FROM `test` WHERE (`test`.`bar` = ? AND `test`.`bar` = ? AND `test`.`bar` = ?)",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_deeply_nested_conditions() {
+        // Left-nested the way a fold over optional filters builds them.
+        let nested = (1..5000).fold(ConditionTree::single(TestEntity::bar.equals(0)), |tree, i| {
+            ConditionTree::And(vec![tree.into(), TestEntity::bar.equals(i).into()])
+        });
+        let (sql, params) = Sqlite::build(Select::from_table(TestEntity::table()).so_that(nested)).unwrap();
+
+        assert_eq!(5000, params.len());
+        assert!(sql.starts_with("SELECT `test`.* FROM `test` WHERE (((("));
+        assert!(sql.ends_with(") AND `test`.`bar` = ?) AND `test`.`bar` = ?)"));
+        assert_eq!(4999, sql.matches('(').count());
+    }
+
     #[test]
     fn test_distinct() {
         let expected_sql = "SELECT DISTINCT `test`.`bar` FROM `test`";
@@ -831,11 +1482,12 @@ mod tests {
     fn test_from() {
         let expected_sql =
             "SELECT `foo`.*, `bar`.`a` FROM `foo`, (SELECT `baz`.`a` FROM `baz`) AS `bar`";
+        let bar = Table::from(Select::from_table(Baz::table()).column(Baz::a)).alias("bar");
         let query = Select::default()
             .and_from(Foo::table())
-            .and_from(Table::from(Select::from_table(Baz::table()).column(Baz::a)).alias("bar"))
+            .and_from(bar.clone())
             .value(Foo::table().asterisk())
-            .column(Baz::a);
+            .column(bar.col(Baz::a));
 
         let (sql, _) = Sqlite::build(query).unwrap();
         assert_eq!(expected_sql, sql);
@@ -940,7 +1592,7 @@ mod tests {
         let (sql, _) = Sqlite::build(insert).unwrap();
 
         assert_eq!(
-            "INSERT INTO `foo` (`foo`.`foo`, `foo`.`baz`) VALUES (?,DEFAULT)",
+            "INSERT INTO `foo` (`foo`, `baz`) VALUES (?,DEFAULT)",
             sql
         );
     }
@@ -951,14 +1603,594 @@ mod tests {
 
     #[test]
     fn join_is_inserted_positionally() {
+        let posts = Post::table().alias("p");
         let joined_table = Table::from(User::table())
-            .left_join(Post::table().alias("p").on(Post::user_id.equals(User::id)));
+            .left_join(posts.clone().on(posts.col(Post::user_id).equals(User::id)));
         let q = Select::from_table(joined_table).and_from(Toto::table());
         let (sql, _) = Sqlite::build(q).unwrap();
 
         assert_eq!(
-            "SELECT `users`.*, `Toto`.* FROM `users` LEFT JOIN `posts` AS `p` ON `p`.`user_id` = `user`.`id`, `Toto`",
+            "SELECT `users`.*, `Toto`.* FROM `users` LEFT JOIN `posts` AS `p` ON `p`.`user_id` = `users`.`id`, `Toto`",
             sql
         );
     }
+
+    #[derive(Entity)]
+    #[tablename = "members"]
+    struct Member {
+        name: String,
+        banned: bool,
+        #[column(soft_delete)]
+        deleted_at: Option<String>,
+    }
+
+    impl Member {
+        fn not_banned() -> Scope<'static> {
+            Member::banned.equals(false).into()
+        }
+
+        fn named_like(pattern: &'static str) -> Scope<'static> {
+            Member::name.like(pattern).or(Member::name.is_null()).into()
+        }
+    }
+
+    #[test]
+    fn test_select_with_scopes() {
+        let expected = expected_values(
+            "SELECT `members`.* FROM `members` WHERE (`members`.`name` <> ? AND `members`.`deleted_at` IS NULL AND (`members`.`banned` = ? AND (`members`.`name` LIKE ? OR `members`.`name` IS NULL)))",
+            vec![Value::from("musti"), Value::from(false), Value::from("%nauk%")],
+        );
+
+        let query = Select::from_table(Member::table())
+            .so_that(Member::name.not_equals("musti"))
+            .scope(Member::not_deleted())
+            .scope(Member::not_banned().and(Member::named_like("nauk")));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!(expected.0, sql);
+        assert_eq!(expected.1, params);
+    }
+
+    #[test]
+    fn test_or_combined_scopes() {
+        let query = Select::from_table(Member::table())
+            .scope(Member::not_deleted().or(Member::not_banned()))
+            .and_where(Member::name.equals("musti"));
+        let (sql, _) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `members`.* FROM `members` WHERE ((`members`.`deleted_at` IS NULL OR `members`.`banned` = ?) AND `members`.`name` = ?)",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_update_and_delete_with_scope() {
+        let update = Update::table(Member::table())
+            .set(Member::banned, true)
+            .so_that(Member::name.equals("musti"))
+            .scope(Member::not_deleted());
+        let (sql, _) = Sqlite::build(update).unwrap();
+
+        assert_eq!(
+            "UPDATE `members` SET `banned` = ? WHERE (`members`.`name` = ? AND `members`.`deleted_at` IS NULL)",
+            sql
+        );
+
+        let delete = Delete::from_table(Member::table()).scope(Member::not_deleted().and(Member::not_banned()));
+        let (sql, _) = Sqlite::build(delete).unwrap();
+
+        assert_eq!(
+            "DELETE FROM `members` WHERE (`members`.`deleted_at` IS NULL AND `members`.`banned` = ?)",
+            sql
+        );
+    }
+
+    #[derive(Entity)]
+    #[tablename = "jobs"]
+    struct Job {
+        id: i32,
+        done: bool,
+    }
+
+    #[test]
+    fn test_select_for_update_is_ignored() {
+        let query = Select::from_table(Job::table())
+            .so_that(Job::done.equals(false))
+            .for_update()
+            .skip_locked();
+        let (sql, _) = Sqlite::build(query).unwrap();
+
+        assert_eq!("SELECT `jobs`.* FROM `jobs` WHERE `jobs`.`done` = ?", sql);
+    }
+
+    #[test]
+    fn test_invalid_locking_combinations() {
+        let query = Select::from_table(Job::table()).for_update().for_share();
+        let err = Sqlite::build(query).unwrap_err();
+
+        assert!(matches!(err.kind(), crate::error::ErrorKind::InvalidLocking(_)));
+    }
+
+    #[derive(Entity)]
+    #[tablename = "sessions"]
+    struct Session {
+        id: i32,
+        last_seen: String,
+    }
+
+    #[test]
+    fn test_date_sub_from_now() {
+        let query = Select::from_table(Session::table())
+            .so_that(Session::last_seen.less_than(date_sub(now(), IntervalArg::days(7))));
+        let (sql, _) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `sessions`.* FROM `sessions` WHERE `sessions`.`last_seen` < datetime('now', '-7 days')",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_date_add_in_update() {
+        let query = Update::table(Session::table())
+            .set(Session::last_seen, date_add(now(), IntervalArg::minutes(90)));
+        let (sql, _) = Sqlite::build(query).unwrap();
+
+        assert_eq!("UPDATE `sessions` SET `last_seen` = datetime('now', '+90 minutes')", sql);
+    }
+
+    #[test]
+    fn test_date_sub_from_current_date() {
+        let query = Select::from_table(Session::table())
+            .so_that(Session::last_seen.greater_than(date_sub(current_date(), IntervalArg::hours(3))));
+        let (sql, _) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `sessions`.* FROM `sessions` WHERE `sessions`.`last_seen` > datetime(date('now'), '-3 hours')",
+            sql
+        );
+    }
+
+    #[derive(Entity)]
+    #[tablename = "profiles"]
+    struct Profile {
+        id: i32,
+        name: String,
+        nickname: Option<String>,
+        surname: String,
+    }
+
+    #[test]
+    fn test_coalesce_with_mixed_arguments() {
+        let query = Select::from_table(Profile::table())
+            .value(coalesce((Profile::nickname, Profile::name)).alias("display"))
+            .value(coalesce((Profile::nickname, "anonymous")));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT COALESCE(`profiles`.`nickname`, `profiles`.`name`) AS `display`, COALESCE(`profiles`.`nickname`, ?) FROM `profiles`",
+            sql
+        );
+        assert_eq!(vec![Value::from("anonymous")], params);
+    }
+
+    #[test]
+    fn test_concat() {
+        let query = Select::from_table(Profile::table())
+            .value(concat((Profile::name, " ", Profile::surname)).alias("full_name"));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT (`profiles`.`name` || ? || `profiles`.`surname`) AS `full_name` FROM `profiles`",
+            sql
+        );
+        assert_eq!(vec![Value::from(" ")], params);
+    }
+
+    #[test]
+    fn test_string_functions_in_conditions_and_ordering() {
+        let query = Select::from_table(Profile::table())
+            .so_that(Expression::from(nullif(trim(Profile::name), "")).is_not_null())
+            .and_where(Expression::from(length(Profile::surname)).greater_than(2))
+            .order_by(upper(Profile::surname).descend())
+            .order_by(lower(Profile::name));
+        let (sql, _) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `profiles`.* FROM `profiles` WHERE (NULLIF(TRIM(`profiles`.`name`), ?) IS NOT NULL AND LENGTH(`profiles`.`surname`) > ?) ORDER BY UPPER(`profiles`.`surname`) DESC, LOWER(`profiles`.`name`)",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_null_safe_equality_of_columns() {
+        let query = Select::from_table(Profile::table()).so_that(Profile::nickname.is_not_distinct_from(Profile::name));
+        let (sql, _) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `profiles`.* FROM `profiles` WHERE `profiles`.`nickname` IS `profiles`.`name`",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_null_safe_inequality_with_parameter() {
+        let nickname: Option<&str> = None;
+        let query = Select::from_table(Profile::table()).so_that(Profile::nickname.is_distinct_from(nickname));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `profiles`.* FROM `profiles` WHERE `profiles`.`nickname` IS NOT ?",
+            sql
+        );
+        assert_eq!(vec![Value::Text(None)], params);
+    }
+
+    #[derive(Entity)]
+    #[tablename = "tickets"]
+    struct Ticket {
+        id: i32,
+        order: i32,
+        #[column(quote)]
+        status: String,
+    }
+
+    #[test]
+    fn test_minimal_quoting() {
+        let query = Select::from_table(Ticket::table())
+            .column(Ticket::id)
+            .column(Ticket::order)
+            .column(Ticket::status)
+            .column(Column::new("createdAt"))
+            .column(Ticket::id.alias("Id"))
+            .so_that(Ticket::id.equals(1));
+        let (sql, _) = Sqlite::build_with(query, QuoteStyle::Minimal).unwrap();
+
+        assert_eq!(
+            "SELECT tickets.id, tickets.`order`, tickets.`status`, `createdAt`, tickets.id AS `Id` FROM tickets WHERE tickets.id = ?",
+            sql
+        );
+    }
+
+    #[test]
+    fn test_quote_in_identifier_is_escaped() {
+        let query = || Select::from_table(Ticket::table()).column(Column::new("we`ird"));
+
+        let (sql, _) = Sqlite::build_with(query(), QuoteStyle::Always).unwrap();
+        assert_eq!("SELECT `we``ird` FROM `tickets`", sql);
+
+        let (sql, _) = Sqlite::build_with(query(), QuoteStyle::Minimal).unwrap();
+        assert_eq!("SELECT `we``ird` FROM tickets", sql);
+    }
+
+    #[test]
+    fn test_hostile_identifiers_stay_within_one_identifier() {
+        let query = || {
+            Select::from_table(Ticket::table())
+                .column(Column::new("x` FROM users; --").alias("a`b"))
+        };
+
+        let (sql, _) = Sqlite::build_with(query(), QuoteStyle::Always).unwrap();
+        assert_eq!("SELECT `x`` FROM users; --` AS `a``b` FROM `tickets`", sql);
+
+        let (sql, _) = Sqlite::build_with(query(), QuoteStyle::Minimal).unwrap();
+        assert_eq!("SELECT `x`` FROM users; --` AS `a``b` FROM tickets", sql);
+    }
+
+    #[test]
+    fn test_reserved_words_are_sorted() {
+        assert!(Sqlite::RESERVED_WORDS.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_cast_in_condition() {
+        let query = Select::from_table(Job::table())
+            .so_that(cast(Job::id, TypeFamily::Text(None)).equals("42"));
+        let (sql, _) = Sqlite::build(query).unwrap();
+
+        assert_eq!("SELECT `jobs`.* FROM `jobs` WHERE CAST(`jobs`.`id` AS TEXT) = ?", sql);
+    }
+
+    #[test]
+    fn test_cast_of_an_operation() {
+        let total = Expression::from(Job::id) + Expression::from(1);
+        let query = Select::from_table(Job::table())
+            .value(total.cast(TypeFamily::Decimal(Some((10, 2)))).alias("total"));
+        let (sql, _) = Sqlite::build(query).unwrap();
+
+        assert_eq!("SELECT CAST((`jobs`.`id` + ?) AS NUMERIC) AS `total` FROM `jobs`", sql);
+    }
+
+    #[test]
+    fn test_row_comparison() {
+        let query = Select::from_table(TestEntity::table())
+            .so_that(Row::from((TestEntity::id1, TestEntity::id2)).greater_than_or_equals(Row::from((1, 2))));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!("SELECT `test`.* FROM `test` WHERE (`test`.`id1`,`test`.`id2`) >= (?,?)", sql);
+        assert_eq!(vec![Value::from(1), Value::from(2)], params);
+    }
+
+    #[test]
+    fn test_keyset_pagination() {
+        let query = Select::from_table(TestEntity::table())
+            .so_that(TestEntity::bar.equals(3))
+            .before((TestEntity::id1, TestEntity::id2), (1, 2));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `test`.* FROM `test` WHERE (`test`.`bar` = ? AND (`test`.`id1`,`test`.`id2`) < (?,?))",
+            sql
+        );
+        assert_eq!(vec![Value::from(3), Value::from(1), Value::from(2)], params);
+    }
+
+    #[test]
+    fn test_count_distinct() {
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            session_id: i32,
+        }
+
+        let query = Select::from_table(Event::table()).value(count_distinct(Event::user_id));
+        let (sql, _) = Sqlite::build(query).unwrap();
+        assert_eq!("SELECT COUNT(DISTINCT `events`.`user_id`) FROM `events`", sql);
+
+        let query = Select::from_table(Event::table()).value(count_distinct(Row::from((Event::user_id, Event::session_id))));
+        assert!(matches!(
+            Sqlite::build(query).unwrap_err().kind(),
+            crate::error::ErrorKind::CountDistinctColumns { columns: 2 }
+        ));
+    }
+
+    #[test]
+    fn test_aggregates() {
+        #[derive(Entity)]
+        #[tablename = "orders"]
+        struct Order {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            total: i32,
+            paid: bool,
+        }
+
+        let query = Select::from_table(Order::table()).value(count_star()).so_that(Order::user_id.equals(1));
+        let (sql, _) = Sqlite::build(query).unwrap();
+        assert_eq!("SELECT COUNT(*) FROM `orders` WHERE `orders`.`user_id` = ?", sql);
+
+        let query = Select::from_table(Order::table())
+            .value(count(Order::id))
+            .value(sum(Order::total))
+            .value(min(Order::total))
+            .value(max(Order::total));
+        let (sql, _) = Sqlite::build(query).unwrap();
+        assert_eq!("SELECT COUNT(`orders`.`id`), SUM(`orders`.`total`), MIN(`orders`.`total`), MAX(`orders`.`total`) FROM `orders`", sql);
+
+        let query = Select::from_table(Order::table()).value(sum(Order::paid));
+        let (sql, _) = Sqlite::build(query).unwrap();
+        assert_eq!("SELECT SUM(`orders`.`paid`) FROM `orders`", sql);
+    }
+
+    #[test]
+    fn test_exists() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            banned: bool,
+        }
+
+        #[derive(Entity)]
+        #[tablename = "posts"]
+        struct Post {
+            #[column(primary_key)]
+            id: i32,
+            user_id: i32,
+            published: bool,
+            flagged: bool,
+        }
+
+        let posts = || Select::from_table(Post::table()).value(1.raw());
+        let query = Select::from_table(User::table())
+            .so_that(User::banned.equals(false))
+            .and_where(exists(posts().so_that(Post::user_id.equals(User::id).and(Post::published.equals(true)))))
+            .and_where(not_exists(posts().so_that(Post::user_id.equals(User::id).and(Post::flagged.equals(true)))));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        let expected_sql = "SELECT `users`.* FROM `users` WHERE (`users`.`banned` = ? AND EXISTS (SELECT 1 FROM `posts` WHERE (`posts`.`user_id` = `users`.`id` AND `posts`.`published` = ?)) AND NOT EXISTS (SELECT 1 FROM `posts` WHERE (`posts`.`user_id` = `users`.`id` AND `posts`.`flagged` = ?)))";
+        assert_eq!(expected_sql, sql);
+        assert_eq!(vec![Value::from(false), Value::from(true), Value::from(true)], params);
+    }
+
+    #[test]
+    fn test_self_join_with_aliases() {
+        #[derive(Entity)]
+        #[tablename = "users"]
+        struct User {
+            #[column(primary_key)]
+            id: i32,
+            manager_id: Option<i32>,
+            name: String,
+        }
+
+        let employee = User::table().alias("e");
+        let manager = User::table().alias("m");
+        let query = Select::from_table(employee.clone())
+            .left_join(manager.clone().on(employee.col(User::manager_id).equals(manager.col(User::id))))
+            .value(employee.clone().asterisk())
+            .column(manager.col(User::name).alias("manager"))
+            .so_that(manager.col(User::name).equals("Ada"));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `e`.*, `m`.`name` AS `manager` FROM `users` AS `e` LEFT JOIN `users` AS `m` ON `e`.`manager_id` = `m`.`id` WHERE `m`.`name` = ?",
+            sql
+        );
+        assert_eq!(vec![Value::from("Ada")], params);
+    }
+
+    #[derive(Entity)]
+    #[tablename = "measurements"]
+    struct Measurement {
+        #[column(primary_key)]
+        id: i64,
+        reading: f64,
+        level: Option<i32>,
+    }
+
+    fn within_sql<'a>(condition: ConditionTree<'a>) -> (String, Vec<Value<'a>>) {
+        Sqlite::build(Select::from_table(Measurement::table()).so_that(condition)).unwrap()
+    }
+
+    #[test]
+    fn test_within_half_open_range() {
+        let (sql, params) = within_sql(Measurement::level.within(1..5));
+
+        assert_eq!(
+            "SELECT `measurements`.* FROM `measurements` WHERE (`measurements`.`level` >= ? AND `measurements`.`level` < ?)",
+            sql
+        );
+        assert_eq!(vec![Value::from(1), Value::from(5)], params);
+    }
+
+    #[test]
+    fn test_within_inclusive_range() {
+        let (sql, params) = within_sql(Measurement::reading.within(0.5..=1.5));
+
+        assert_eq!(
+            "SELECT `measurements`.* FROM `measurements` WHERE (`measurements`.`reading` >= ? AND `measurements`.`reading` <= ?)",
+            sql
+        );
+        assert_eq!(vec![Value::from(0.5), Value::from(1.5)], params);
+    }
+
+    #[test]
+    fn test_within_ranges_open_on_a_side() {
+        let (sql, params) = within_sql(Measurement::level.within(..5));
+        assert_eq!("SELECT `measurements`.* FROM `measurements` WHERE `measurements`.`level` < ?", sql);
+        assert_eq!(vec![Value::from(5)], params);
+
+        let (sql, _) = within_sql(Measurement::level.within(..=5));
+        assert_eq!("SELECT `measurements`.* FROM `measurements` WHERE `measurements`.`level` <= ?", sql);
+
+        let (sql, _) = within_sql(Measurement::level.within(1..));
+        assert_eq!("SELECT `measurements`.* FROM `measurements` WHERE `measurements`.`level` >= ?", sql);
+
+        let (sql, params) = within_sql(Measurement::level.within(..));
+        assert_eq!("SELECT `measurements`.* FROM `measurements` WHERE 1=1", sql);
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_within_excluded_start() {
+        use std::ops::Bound;
+
+        let (sql, _) = within_sql(Measurement::level.within((Bound::Excluded(1), Bound::Included(5))));
+
+        assert_eq!(
+            "SELECT `measurements`.* FROM `measurements` WHERE (`measurements`.`level` > ? AND `measurements`.`level` <= ?)",
+            sql
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_on_date_is_the_utc_day() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        #[derive(Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i64,
+            happened_at: chrono::DateTime<Utc>,
+        }
+
+        let query = Select::from_table(Event::table()).so_that(Event::happened_at.on_date(NaiveDate::from_ymd_opt(2021, 12, 31).unwrap()));
+        let (sql, params) = Sqlite::build(query).unwrap();
+
+        assert_eq!(
+            "SELECT `events`.* FROM `events` WHERE (`events`.`happened_at` >= ? AND `events`.`happened_at` < ?)",
+            sql
+        );
+        assert_eq!(
+            vec![
+                Value::from(Utc.with_ymd_and_hms(2021, 12, 31, 0, 0, 0).unwrap()),
+                Value::from(Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap()),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn test_placeholders_outside_of_literals_are_counted() {
+        assert_eq!(2, Sqlite::count_placeholders("SELECT * FROM users WHERE email = ? AND id > ?"));
+        assert_eq!(
+            1,
+            Sqlite::count_placeholders("SELECT '?', \"a?\", `b?` -- ?\n FROM t /* ? */ WHERE x = 'it''s?' AND y = ?")
+        );
+        assert_eq!(0, Sqlite::count_placeholders("SELECT 'unterminated ?"));
+    }
+
+    #[test]
+    fn test_empty_select_is_incomplete() {
+        let err = Sqlite::build(Select::default()).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table or a value to select" }
+        ));
+    }
+
+    #[test]
+    fn test_select_with_only_ordering_is_incomplete() {
+        let query = Select::default().order_by(Subscriber::email.descend());
+        let err = Sqlite::build(query).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table or a value to select" }
+        ));
+    }
+
+    #[test]
+    fn test_insert_without_table_is_incomplete() {
+        let insert: Insert = Insert::single().value(Subscriber::email, "a@b.c").into();
+        let err = Sqlite::build(insert).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a table to insert into" }
+        ));
+    }
+
+    #[test]
+    fn test_update_without_assignments_is_incomplete() {
+        let query = Update::table(Subscriber::table()).so_that(Subscriber::email.equals("a@b.c"));
+        let err = Sqlite::build(query).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a column to update" }
+        ));
+    }
+
+    #[test]
+    fn test_upsert_without_assignments_is_incomplete() {
+        let insert: Insert = Insert::single_into(Subscriber::table()).value(Subscriber::email, "a@b.c").into();
+        let target = ConflictTarget::columns(vec![Subscriber::email]);
+        let err = Sqlite::build(insert.on_conflict(OnConflict::Update(target, Vec::new()))).unwrap_err();
+
+        assert!(matches!(
+            err.kind(),
+            crate::error::ErrorKind::IncompleteQuery { missing: "a column to update on conflict" }
+        ));
+    }
 }
@@ -0,0 +1,111 @@
+//! Counts the allocations of building small queries, with a global allocator
+//! of its own, hence a test binary of its own.
+#![cfg(any(feature = "sqlite", feature = "postgres"))]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+#[cfg(feature = "sqlite")]
+use entities::User;
+use xiayu::databases::{convert_parameters, Binder};
+use xiayu::prelude::*;
+#[cfg(feature = "sqlite")]
+use xiayu::visitors::{Sqlite, Visitor, VisitorBuffers};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<(usize, usize)> = Cell::new((0, 0));
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| {
+            let (allocations, bytes) = count.get();
+            count.set((allocations + 1, bytes + layout.size()));
+        });
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| {
+            let (allocations, bytes) = count.get();
+            count.set((allocations + 1, bytes + new_size));
+        });
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// The allocations, and the bytes allocated, by `f` on this thread.
+fn allocations<T>(f: impl FnOnce() -> T) -> (usize, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    drop(f());
+    let after = ALLOCATIONS.with(Cell::get);
+    (after.0 - before.0, after.1 - before.1)
+}
+
+#[cfg(feature = "sqlite")]
+fn get_user(id: i32) -> Select<'static> {
+    Select::from_table(User::table()).so_that(User::id.equals(id))
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn small_queries_allocate_little() {
+    let query = get_user(1);
+    let (count, bytes) = allocations(|| Sqlite::build(query).unwrap());
+    assert!(bytes < 1024, "{} bytes allocated", bytes);
+
+    let mut buffers = VisitorBuffers::new();
+    Sqlite::build_into(get_user(1), &mut buffers).unwrap();
+    let query = get_user(2);
+    let (reused, reused_bytes) = allocations(|| Sqlite::build_into(query, &mut buffers).unwrap());
+    assert!(reused < count, "{} allocations reusing buffers, {} without", reused, count);
+    assert!(reused_bytes < bytes, "{} bytes reusing buffers, {} without", reused_bytes, bytes);
+    assert_eq!("SELECT `users`.* FROM `users` WHERE `users`.`id` = ?", buffers.sql());
+    assert_eq!(&[Value::from(2)], buffers.parameters());
+}
+
+const BLOB_SIZE: usize = 10 * 1024 * 1024;
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn borrowed_parameters_are_bound_without_a_copy_on_sqlite() {
+    let blob = vec![7u8; BLOB_SIZE];
+    let text = "x".repeat(BLOB_SIZE);
+
+    let (_, bytes) = allocations(|| {
+        let parameters = vec![Value::from(&blob[..]), Value::from(text.as_str())];
+        let mut query = sqlx::query::<sqlx::Sqlite>("INSERT INTO blobs (data, note) VALUES (?, ?)");
+        for parameter in convert_parameters::<sqlx::Sqlite>(parameters, &[]).unwrap() {
+            query = query.bind_value(parameter);
+        }
+        query
+    });
+    assert!(bytes < 1024, "{} bytes allocated", bytes);
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn borrowed_parameters_are_bound_without_a_copy_on_postgres() {
+    let blob = vec![7u8; BLOB_SIZE];
+
+    // PostgreSQL encodes the parameters into the buffer sent when binding
+    // them, the only copy.
+    let (_, bytes) = allocations(|| {
+        let parameters = vec![Value::from(&blob[..])];
+        let mut query = sqlx::query::<sqlx::Postgres>("INSERT INTO blobs (data) VALUES ($1)");
+        for parameter in convert_parameters::<sqlx::Postgres>(parameters, &[]).unwrap() {
+            query = query.bind_value(parameter);
+        }
+        query
+    });
+    assert!(bytes < BLOB_SIZE + 1024, "{} bytes allocated", bytes);
+}
@@ -0,0 +1,115 @@
+use xiayu::prelude::*;
+use xiayu::visitors::{Mysql, Sqlite, Visitor};
+
+#[derive(Debug, Entity)]
+#[tablename = "customers"]
+pub struct Customer {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+    pub country: String,
+    pub vip: bool,
+    pub note: Option<String>,
+    pub deleted_at: Option<String>,
+    #[column(default = 0)]
+    pub visits: i32,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "orders"]
+pub struct Order {
+    #[column(primary_key)]
+    pub id: i64,
+    pub customer_id: i64,
+    pub total: f64,
+    pub placed_at: String,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "flags"]
+pub struct Flag {
+    #[column(primary_key)]
+    pub id: i64,
+    pub customer_id: i64,
+    pub kind: String,
+}
+
+fn report<'a>() -> Select<'a> {
+    let recent = Table::from(
+        Select::from_table(Order::table())
+            .column(Order::customer_id)
+            .value(sum(Order::total).alias("spent"))
+            .so_that(Order::placed_at.greater_than("2021-01-01"))
+            .group_by(Order::customer_id),
+    )
+    .alias("recent");
+    let customers = Customer::table().alias("c");
+    let flagged = Select::from_table(Flag::table())
+        .column(Flag::customer_id)
+        .so_that(Flag::kind.equals(Value::Char(Some('F'))));
+
+    Select::from_table(customers.clone())
+        .column(customers.col(Customer::name))
+        .value(coalesce(vec![Expression::from(recent.col(Column::new("spent"))), Value::from(0.5).into()]).alias("spent"))
+        .value(count(asterisk()).alias("orders"))
+        .left_join(recent.clone().on(recent.col(Order::customer_id).equals(customers.col(Customer::id))))
+        .so_that(
+            customers
+                .col(Customer::country)
+                .in_selection(vec!["FR", "DE"])
+                .or(customers.col(Customer::vip).equals(true).and(customers.col(Customer::note).like("%gold%")))
+                .and(customers.col(Customer::id).not_in_selection(flagged))
+                .and(customers.col(Customer::deleted_at).is_null())
+                .and(customers.col(Column::new("avatar")).not_equals(Value::from(&b"\x00\xff"[..])))
+                .and(customers.col(Customer::visits).greater_than_or_equals(3).not()),
+        )
+        .group_by(customers.col(Customer::name))
+        .having(Column::new("orders").greater_than(2))
+        .order_by(customers.col(Customer::name).descend())
+        .limit(10)
+        .offset(20)
+}
+
+fn round_trip(query: Query<'static>) -> Query<'static> {
+    let stored = serde_json::to_string(&Versioned::new(query)).unwrap();
+    let restored: Versioned<Query<'static>> = serde_json::from_str(&stored).unwrap();
+    restored.into_inner()
+}
+
+#[test]
+fn stored_queries_render_the_same_sql_and_parameters() {
+    let restored = round_trip(report().into());
+
+    let (sql, params) = Sqlite::build(report()).unwrap();
+    let (restored_sql, restored_params) = Sqlite::build(restored.clone()).unwrap();
+    assert_eq!(sql, restored_sql);
+    assert_eq!(format!("{:?}", params), format!("{:?}", restored_params));
+
+    let (sql, params) = Mysql::build(report()).unwrap();
+    let (restored_sql, restored_params) = Mysql::build(restored).unwrap();
+    assert_eq!(sql, restored_sql);
+    assert_eq!(format!("{:?}", params), format!("{:?}", restored_params));
+}
+
+#[test]
+fn stored_writes_render_the_same_sql_and_parameters() {
+    let insert = || -> Query<'static> {
+        Insert::multi_into(Customer::table(), vec![Column::from(Customer::name), Column::from(Customer::vip)])
+            .values(("Ada", true))
+            .values(("Grace", Value::Boolean(None)))
+            .into()
+    };
+    let update = || -> Query<'static> {
+        Update::table(Customer::table())
+            .set(Customer::vip, false)
+            .so_that(Customer::name.equals("Ada"))
+            .into()
+    };
+
+    for query in [insert, update] {
+        let restored = round_trip(query());
+
+        assert_eq!(Sqlite::build(query()).unwrap(), Sqlite::build(restored.clone()).unwrap());
+        assert_eq!(Mysql::build(query()).unwrap(), Mysql::build(restored).unwrap());
+    }
+}
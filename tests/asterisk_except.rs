@@ -0,0 +1,89 @@
+#![cfg(feature = "sqlite")]
+use xiayu::error::{ErrorKind, Name};
+use xiayu::prelude::*;
+use xiayu::registry;
+use xiayu::visitors::{Sqlite, Visitor};
+
+#[derive(Debug, Entity)]
+#[tablename = "documents"]
+pub struct Document {
+    #[column(primary_key)]
+    pub id: i64,
+    pub title: String,
+    pub body: String,
+    pub thumbnail: Vec<u8>,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "authors"]
+pub struct Author {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+}
+
+/// Left out of the registry.
+#[derive(Debug, Entity)]
+#[tablename = "drafts"]
+pub struct Draft {
+    #[column(primary_key)]
+    pub id: i64,
+    pub body: String,
+}
+
+fn register() {
+    registry::set_default_registry(registry::collect!(Document, Author));
+}
+
+#[test]
+fn registered_tables_expand_into_their_columns() {
+    register();
+
+    let query = Select::from_table(Document::table())
+        .value(asterisk_except(Document::table(), [Column::from(Document::body), Document::thumbnail.into()]));
+    let (sql, _) = Sqlite::build(query).unwrap();
+
+    assert_eq!("SELECT `documents`.`id`, `documents`.`title` FROM `documents`", sql);
+}
+
+#[test]
+fn aliased_tables_qualify_with_the_alias() {
+    register();
+
+    let documents = Document::table().alias("d");
+    let query = Select::from_table(documents.clone())
+        .value(asterisk_except(documents.clone(), [Column::new("body"), Column::new("thumbnail"), Column::new("id")]))
+        .value(Author::name)
+        .inner_join(Author::table().on(Author::id.equals(Column::new("id").table(documents))));
+    let (sql, _) = Sqlite::build(query).unwrap();
+
+    assert_eq!(
+        "SELECT `d`.`title`, `authors`.`name` FROM `documents` AS `d` INNER JOIN `authors` ON `authors`.`id` = `d`.`id`",
+        sql
+    );
+}
+
+#[test]
+fn unregistered_tables_are_an_error() {
+    register();
+
+    let err = Sqlite::build(Select::from_table(Draft::table()).value(asterisk_except(Draft::table(), [Draft::body]))).unwrap_err();
+
+    assert!(
+        matches!(err.kind(), ErrorKind::UnexpandableAsterisk { table, reason } if *table == Name::available("drafts") && reason.contains("registry")),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn unknown_and_all_columns_are_an_error() {
+    register();
+
+    let err = Sqlite::build(Select::from_table(Author::table()).value(asterisk_except(Author::table(), [Column::new("email")]))).unwrap_err();
+    assert!(err.to_string().contains("`email` is not one of its columns"), "{}", err);
+
+    let err = Sqlite::build(Select::from_table(Author::table()).value(asterisk_except(Author::table(), [Column::from(Author::id), Author::name.into()])))
+        .unwrap_err();
+    assert!(err.to_string().contains("every column is excluded"), "{}", err);
+}
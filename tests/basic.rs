@@ -16,6 +16,222 @@ pub struct AnotherEntity {
     textual: String,
 }
 
+#[derive(Debug, Entity)]
+pub struct Typed {
+    pub id: i64,
+    pub small: i32,
+    pub ratio: f32,
+    pub score: Option<f64>,
+    pub active: bool,
+    #[column(length = 32)]
+    pub name: String,
+    pub bio: Option<String>,
+    pub avatar: Vec<u8>,
+}
+
+#[test]
+fn type_family_inference() {
+    assert_eq!(Some(TypeFamily::Int), Typed::id.type_family());
+    assert_eq!(Some(TypeFamily::Int32), Typed::small.type_family());
+    assert_eq!(Some(TypeFamily::Float), Typed::ratio.type_family());
+    assert_eq!(Some(TypeFamily::Double), Typed::score.type_family());
+    assert_eq!(Some(TypeFamily::Boolean), Typed::active.type_family());
+    assert_eq!(
+        Some(TypeFamily::Text(Some(TypeDataLength::Constant(32)))),
+        Typed::name.type_family()
+    );
+    assert_eq!(Some(TypeFamily::Text(None)), Typed::bio.type_family());
+    assert_eq!(Some(TypeFamily::Bytes(None)), Typed::avatar.type_family());
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "accounts"]
+#[index(name = "accounts_owner_name", columns = "owner_id, name", unique)]
+pub struct Account {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    #[column(foreign_key = "users.id", comment = "owning user")]
+    pub owner_id: i32,
+    #[column(name = "account_name", length = 64, unique)]
+    pub name: String,
+    #[column(server_default = "now()")]
+    pub created_at: Option<String>,
+}
+
+#[test]
+fn entity_schema() {
+    let schema = Account::schema();
+    assert_eq!("accounts", schema.tablename);
+    assert_eq!(None, schema.database);
+    assert_eq!(4, schema.columns.len());
+
+    assert_eq!(
+        ColumnSchema {
+            name: "id",
+            rust_type: "i64",
+            type_family: Some(TypeFamily::Int),
+            nullable: false,
+            primary_key: true,
+            autoincrement: true,
+            unique: false,
+            length: None,
+            foreign_key: None,
+            has_default: false,
+            has_server_default: false,
+            server_default: None,
+            comment: None,
+        },
+        schema.columns[0]
+    );
+
+    let owner_id = schema.column("owner_id").unwrap();
+    assert_eq!(Some("users.id"), owner_id.foreign_key);
+    assert_eq!(Some("owning user"), owner_id.comment);
+
+    let name = schema.column("account_name").unwrap();
+    assert!(name.unique);
+    assert_eq!(Some(64), name.length);
+    assert_eq!(
+        Some(TypeFamily::Text(Some(TypeDataLength::Constant(64)))),
+        name.type_family
+    );
+
+    let created_at = schema.column("created_at").unwrap();
+    assert_eq!("Option<String>", created_at.rust_type);
+    assert!(created_at.nullable);
+    assert!(created_at.has_server_default);
+    assert_eq!(Some("now()"), created_at.server_default);
+    assert!(!created_at.has_default);
+
+    assert_eq!(
+        vec!["id"],
+        schema.primary_key().map(|c| c.name).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        &[IndexSchema {
+            name: "accounts_owner_name",
+            columns: &["owner_id", "name"],
+            unique: true,
+        }],
+        schema.indexes
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn column_options_builder() {
+    const NEW: ColumnOptions<String> = ColumnOptions::new(
+        "account_name",
+        "accounts",
+        false,
+        false,
+        None,
+        None,
+        true,
+        Some(64),
+        false,
+        None,
+        Some(TypeFamily::Text(Some(TypeDataLength::Constant(64)))),
+    );
+    const BUILT: ColumnOptions<String> = ColumnOptions::builder("account_name", "accounts")
+        .unique()
+        .length(64)
+        .type_family(TypeFamily::Text(Some(TypeDataLength::Constant(64))))
+        .build();
+    assert_eq!(NEW, BUILT);
+    assert_eq!(BUILT, Account::name);
+
+    let id = ColumnOptions::<i64>::builder("id", "accounts")
+        .primary_key()
+        .autoincrement()
+        .type_family(TypeFamily::Int)
+        .build();
+    assert_eq!(id, Account::id);
+    assert_ne!(id, ColumnOptions::builder("id", "accounts").type_family(TypeFamily::Int).build());
+
+    let created_at = ColumnOptions::<Option<String>>::builder("created_at", "accounts")
+        .server_default("now()")
+        .type_family(TypeFamily::Text(None))
+        .build();
+    assert_eq!(created_at, Account::created_at);
+    assert_eq!(Some("now()"), Account::created_at.server_default());
+}
+
+#[derive(Debug, Clone, PartialEq, EmbeddedColumns)]
+pub struct Address {
+    #[column(length = 64)]
+    pub street: String,
+    pub city: String,
+    #[column(name = "zip")]
+    pub postal_code: Option<String>,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "customers"]
+pub struct Customer {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(flatten, prefix = "home_")]
+    pub home: Address,
+    pub name: String,
+    #[column(flatten)]
+    pub office: Address,
+}
+
+#[test]
+fn flattened_schema() {
+    let schema = Customer::schema();
+    assert_eq!(
+        vec!["id", "home_street", "home_city", "home_zip", "name", "street", "city", "zip"],
+        schema.columns.iter().map(|c| c.name).collect::<Vec<_>>()
+    );
+
+    let street = schema.column("home_street").unwrap();
+    assert_eq!(Some(64), street.length);
+    assert_eq!("String", street.rust_type);
+    assert!(!street.primary_key);
+    assert!(schema.column("home_zip").unwrap().nullable);
+
+    assert_eq!(
+        schema.columns.iter().map(|c| c.name).collect::<Vec<_>>(),
+        <Customer as Entity>::columns().iter().map(|c| c.name.as_ref()).collect::<Vec<_>>()
+    );
+    // Only the columns known at compile time.
+    assert_eq!(2, Customer::COLUMNS.len());
+}
+
+#[test]
+fn column_index_follows_declaration_order() {
+    assert_eq!(
+        vec!["id", "owner_id", "account_name", "created_at"],
+        <Account as Entity>::columns().iter().map(|c| c.name.as_ref()).collect::<Vec<_>>()
+    );
+    assert_eq!(Some(0), Account::column_index("id"));
+    assert_eq!(Some(2), Account::column_index("account_name"));
+    assert_eq!(Some(3), Account::column_index("created_at"));
+    // By the name in the database, not the name of the field.
+    assert_eq!(None, Account::column_index("name"));
+    assert_eq!(None, Account::column_index("missing"));
+
+    assert_eq!(Some(0), Customer::column_index("id"));
+    assert_eq!(Some(4), Customer::column_index("name"));
+    assert_eq!(Some(2), Customer::column_index("home_city"));
+    assert_eq!(Some(7), Customer::column_index("zip"));
+    assert_eq!(None, Customer::column_index("postal_code"));
+}
+
+#[test]
+fn registry_collects_schemas() {
+    let registry = xiayu::registry::collect!(Account, AnotherEntity, Account);
+
+    assert_eq!(2, registry.len());
+    assert_eq!(
+        vec!["accounts", "another_entities"],
+        registry.iter().map(|s| s.tablename).collect::<Vec<_>>()
+    );
+    assert_eq!(Some(&Account::schema()), registry.get("accounts"));
+}
+
 #[test]
 fn another_entity_definitions() {
     let entity = AnotherEntity { id: 1, textual: "string.".to_string() };
@@ -24,6 +240,52 @@ fn another_entity_definitions() {
     assert_eq!(entity.tablename(), "another_entities");
 }
 
+#[test]
+fn checked_column_names() {
+    assert!(Column::new_checked("display_name").is_ok());
+
+    for hostile in ["", "a`b", "a\"b", "a]b", "a[b", "a\nb", "a\0b"] {
+        let err = Column::new_checked(hostile).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            xiayu::error::ErrorKind::InvalidIdentifier { .. }
+        ));
+    }
+}
+
+#[test]
+fn entity_aliasing() {
+    let table = AnotherEntity::aliased("a");
+    assert_eq!(Some("a"), table.alias.as_deref());
+
+    #[allow(deprecated)]
+    use xiayu::prelude::compat::EntityAlias;
+
+    let entity = AnotherEntity { id: 1, textual: "string.".to_string() };
+    #[allow(deprecated)]
+    let legacy = entity.alias("a");
+    assert_eq!(table, legacy);
+}
+
+#[cfg(feature = "sqlite")]
+mod split_prelude {
+    use xiayu::prelude::ast::Select;
+    use xiayu::prelude::entity::{Entity, EntityAliasExt};
+    use xiayu::visitors::{Sqlite, Visitor};
+
+    #[derive(Entity)]
+    #[tablename = "tags"]
+    pub struct Tag {
+        pub id: i32,
+    }
+
+    #[test]
+    fn derive_with_split_imports() {
+        let (sql, _) = Sqlite::build(Select::from_table(Tag::aliased("t"))).unwrap();
+        assert_eq!("SELECT `t`.* FROM `tags` AS `t`", sql);
+    }
+}
+
 #[test]
 fn entity_definitions() {
     let entity = AnEntity {
@@ -77,3 +339,1411 @@ fn entity_definitions() {
         assert!(res.is_ok(), "{:?}", res)
     }
 }
+
+#[derive(Debug, Entity)]
+#[tablename = "big_keys"]
+pub struct BigKey {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "slugged"]
+pub struct Slugged {
+    #[column(primary_key)]
+    pub slug: String,
+    pub title: String,
+}
+
+#[cfg(feature = "uuid")]
+#[derive(Debug, Entity)]
+#[tablename = "docs"]
+pub struct Doc {
+    #[column(primary_key)]
+    pub id: sqlx::types::Uuid,
+    pub body: String,
+}
+
+// Never called, checks the accepted keys at compile time.
+#[allow(dead_code)]
+fn get_accepts_convertible_keys<DB>()
+where
+    DB: sqlx::Database,
+    BigKey: for<'r> sqlx::FromRow<'r, DB::Row>,
+    Slugged: for<'r> sqlx::FromRow<'r, DB::Row>,
+{
+    let id: i32 = 7;
+    let _ = BigKey::get::<DB, _>(42);
+    let _ = BigKey::get::<DB, _>(id);
+    let _ = BigKey::get::<DB, _>(&42i64);
+
+    let slug = String::from("hello");
+    let _ = Slugged::get::<DB, _>("hello");
+    let _ = Slugged::get::<DB, _>(&slug);
+    let _ = Slugged::get::<DB, _>(slug);
+}
+
+#[cfg(feature = "uuid")]
+#[allow(dead_code)]
+fn get_accepts_borrowed_uuid<DB>()
+where
+    DB: sqlx::Database,
+    Doc: for<'r> sqlx::FromRow<'r, DB::Row>,
+{
+    let id = sqlx::types::Uuid::nil();
+    let _ = Doc::get::<DB, _>(&id);
+    let _ = Doc::get::<DB, _>(id);
+}
+
+// Never called, checks the values accepted by the finders at compile time.
+#[allow(dead_code)]
+fn finders_accept_the_type_of_the_column<DB>()
+where
+    DB: sqlx::Database,
+    Account: for<'r> sqlx::FromRow<'r, DB::Row>,
+{
+    let _: SelectingExecution<Account, DB> = Account::find_by_id(42i64);
+    let _: SelectingExecution<Account, DB> = Account::find_by_name("savings");
+    let _: SelectingExecution<Account, DB> = Account::find_by_name(String::from("savings"));
+    let _: SelectingExecution<Account, DB> = Account::find_by_name(String::from("savings").as_str());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn unique_columns_have_finders() {
+    let query = Account::find_by_name::<sqlx::Sqlite, _>("savings").prepare().unwrap();
+    assert_eq!(
+        "SELECT `accounts`.* FROM `accounts` WHERE `accounts`.`account_name` = ?",
+        query.sql()
+    );
+    assert_eq!(&[Value::from("savings")], query.parameters());
+
+    let query = Account::find_by_id::<sqlx::Sqlite, _>(7i64).prepare().unwrap();
+    assert_eq!("SELECT `accounts`.* FROM `accounts` WHERE `accounts`.`id` = ?", query.sql());
+
+    let query = Account::exists_by_name::<sqlx::Sqlite, _>("savings").prepare().unwrap();
+    assert_eq!(
+        "SELECT EXISTS (SELECT 1 FROM `accounts` WHERE `accounts`.`account_name` = ?)",
+        query.sql()
+    );
+    assert_eq!(&[Value::from("savings")], query.parameters());
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "page_views"]
+#[entity(no_primary_key)]
+pub struct PageView {
+    pub path: String,
+    pub views: i64,
+}
+
+// Without the methods of the entities missing a key, it has a `get` of its own.
+impl PageView {
+    pub fn get(path: &str) -> Select<'static> {
+        Select::from_table(PageView::table()).so_that(PageView::path.equals(path.to_string()))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn entities_without_a_primary_key_on_purpose_keep_their_methods() {
+    use xiayu::visitors::{Sqlite, Visitor};
+
+    let (sql, params) = Sqlite::build(PageView::get("/")).unwrap();
+    assert_eq!("SELECT `page_views`.* FROM `page_views` WHERE `page_views`.`path` = ?", sql);
+    assert_eq!(vec![Value::from("/")], params);
+}
+
+#[cfg(feature = "uuid")]
+#[derive(Debug, Entity)]
+#[tablename = "notes"]
+pub struct Note {
+    #[column(primary_key, generator = "uuid_v4")]
+    pub id: Option<sqlx::types::Uuid>,
+    pub body: String,
+}
+
+#[cfg(feature = "uuid")]
+#[derive(Debug, Entity)]
+#[tablename = "labels"]
+#[entity(builder)]
+pub struct Label {
+    #[column(primary_key, generator = "uuid_v4")]
+    pub id: sqlx::types::Uuid,
+    pub name: String,
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn client_generated_uuid_keys() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::types::Uuid;
+        use sqlx::Executor;
+        use xiayu::error::ErrorKind;
+
+        async fn run() -> Result<()> {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+            pool.execute("CREATE TABLE notes (id BLOB PRIMARY KEY, body TEXT NOT NULL);")
+                .await?;
+            pool.execute("CREATE TABLE labels (id BLOB PRIMARY KEY, name TEXT NOT NULL);")
+                .await?;
+
+            // A new note gets its key on the first save, later saves update it.
+            let mut note = Note {
+                id: None,
+                body: "draft".to_string(),
+            };
+            note.save().conn(&pool).await?;
+            let id = note.id.expect("the key is written back");
+            note.body = "final".to_string();
+            note.save().conn(&pool).await?;
+
+            let fetched = Note::get(id).conn(&pool).await?;
+            assert_eq!(Some(id), fetched.id);
+            assert_eq!("final", fetched.body);
+
+            let err = Note::get::<sqlx::Sqlite, _>(None::<Uuid>)
+                .conn(&pool)
+                .await
+                .unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::PrimaryKeyNotSet { .. }));
+
+            let mut unsaved = Note {
+                id: None,
+                body: "unsaved".to_string(),
+            };
+            let err = unsaved.delete().conn(&pool).await.unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::PrimaryKeyNotSet { .. }));
+
+            let mut note = fetched;
+            note.delete().conn(&pool).await?;
+            assert!(Note::get(id).conn(&pool).await.is_err());
+
+            // The builder generates the key when it is left out.
+            Label::builder().name("red".to_string()).insert().conn(&pool).await?;
+            let mut label = Label {
+                id: Uuid::nil(),
+                name: "blue".to_string(),
+            };
+            label.save().conn(&pool).await?;
+            assert_ne!(Uuid::nil(), label.id);
+
+            let labels = Select::from_table(Label::table())
+                .value(count(asterisk()))
+                .fetch_as::<Scalar<i64>, sqlx::Sqlite>()
+                .conn(&pool)
+                .await?;
+            assert_eq!(Scalar(2), labels);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[test]
+fn get_with_borrowed_key() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Executor;
+        async fn run() -> Result<()> {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+            pool.execute("CREATE TABLE slugged (slug TEXT PRIMARY KEY, title TEXT);")
+                .await?;
+            pool.execute("INSERT INTO slugged (slug, title) VALUES ('hello', 'Hello');")
+                .await?;
+
+            let entity = Slugged::get("hello").conn(&pool).await?;
+            assert_eq!("Hello", entity.title);
+            assert_eq!("hello", entity.pk());
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+fn default_label() -> String {
+    "inbox".to_string()
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "tasks"]
+#[entity(builder)]
+pub struct Task {
+    #[column(primary_key, autoincrement)]
+    pub id: i32,
+    pub title: String,
+    #[column(default = "pending")]
+    pub status: String,
+    #[column(default = 3)]
+    pub retries: i32,
+    #[column(default = 1)]
+    pub priority: Option<i32>,
+    #[column(default(default_label))]
+    pub label: String,
+}
+
+#[test]
+fn column_defaults() {
+    assert_eq!(None, Task::title.default_value());
+    assert_eq!(Some("pending".to_string()), Task::status.default_value());
+    assert_eq!(Some(3), Task::retries.default_value());
+    assert_eq!(Some(Some(1)), Task::priority.default_value());
+    assert_eq!(Some("inbox".to_string()), Task::label.default_value());
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "chores"]
+#[entity(derive_default)]
+pub struct Chore {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    pub title: String,
+    #[column(default = "pending")]
+    pub status: String,
+    #[column(default = 3)]
+    pub retries: i32,
+    #[column(default = 1)]
+    pub priority: Option<i32>,
+    pub due: Option<i64>,
+    #[column(default(default_label))]
+    pub label: String,
+}
+
+#[test]
+fn derived_default_uses_column_defaults() {
+    let chore = Chore::default();
+
+    assert_eq!(0, chore.id);
+    assert_eq!("", chore.title);
+    assert_eq!(Chore::status.default_value(), Some(chore.status));
+    assert_eq!(Chore::retries.default_value(), Some(chore.retries));
+    assert_eq!(Chore::priority.default_value(), Some(chore.priority));
+    assert_eq!(None, chore.due);
+    assert_eq!("inbox", chore.label);
+}
+
+fn unloaded() -> String {
+    "not loaded".to_string()
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "bookmarks"]
+pub struct Bookmark {
+    #[column(primary_key)]
+    pub id: i64,
+    pub url: String,
+    #[column(skip)]
+    pub selected: bool,
+    #[column(skip, skip_default = "unloaded")]
+    pub preview: String,
+}
+
+#[test]
+fn skipped_fields_have_no_column() {
+    let names: Vec<_> = Bookmark::schema().columns.iter().map(|column| column.name).collect();
+    assert_eq!(vec!["id", "url"], names);
+    assert_eq!(2, Bookmark::COLUMNS.len());
+
+    let bookmark = Bookmark {
+        id: 1,
+        url: "https://example.com".to_string(),
+        selected: true,
+        preview: "Example".to_string(),
+    };
+    assert_eq!(vec![Value::from(1i64), Value::from("https://example.com")], bookmark.values());
+
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Executor;
+        async fn run() -> Result<()> {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+            pool.execute("CREATE TABLE bookmarks (id INTEGER PRIMARY KEY, url TEXT NOT NULL);")
+                .await?;
+
+            Bookmark::insert()
+                .value(Bookmark::id, 1)
+                .value(Bookmark::url, "https://example.com")
+                .conn(&pool)
+                .await?;
+            let bookmark = Bookmark::get(1).conn(&pool).await?;
+            assert_eq!("https://example.com", bookmark.url);
+            assert!(!bookmark.selected);
+            assert_eq!("not loaded", bookmark.preview);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[test]
+fn fetch_optional_and_fetch_all() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Executor;
+        async fn run() -> Result<()> {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+            pool.execute("CREATE TABLE bookmarks (id INTEGER PRIMARY KEY, url TEXT NOT NULL);")
+                .await?;
+
+            assert!(Bookmark::get(1).fetch_optional(&pool).await?.is_none());
+
+            pool.execute("INSERT INTO bookmarks (id, url) VALUES (1, 'https://example.com'), (2, 'https://example.org');")
+                .await?;
+            let bookmark = Bookmark::get(2).fetch_optional(&pool).await?;
+            assert_eq!(Some("https://example.org"), bookmark.as_ref().map(|bookmark| bookmark.url.as_str()));
+
+            let bookmarks = Select::from_table(Bookmark::table())
+                .order_by(Bookmark::id.descend())
+                .fetch_as::<Bookmark, sqlx::Sqlite>()
+                .fetch_all(&pool)
+                .await?;
+            assert_eq!(vec![2, 1], bookmarks.iter().map(|bookmark| bookmark.id).collect::<Vec<_>>());
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[test]
+fn fetch_streams_rows_lazily() {
+    #[cfg(feature = "sqlite")]
+    {
+        use futures_util::TryStreamExt;
+        use sqlx::{Executor, Row};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static DECODED: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug, Entity)]
+        #[tablename = "numbers"]
+        struct Number {
+            n: i64,
+        }
+
+        /// Counts the rows decoded.
+        struct Counted(i64);
+
+        impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Counted {
+            fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> std::result::Result<Self, sqlx::Error> {
+                DECODED.fetch_add(1, Ordering::SeqCst);
+                Ok(Self(row.try_get("n")?))
+            }
+        }
+
+        fn numbers() -> SelectingExecution<Counted, sqlx::Sqlite> {
+            Select::from_table(Number::table()).column(Number::n).order_by(Number::n.ascend()).fetch_as()
+        }
+
+        async fn run() -> Result<()> {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+            pool.execute(
+                "CREATE TABLE numbers (n INTEGER NOT NULL); \
+                 INSERT INTO numbers WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < 10000) SELECT n FROM seq;",
+            )
+            .await?;
+
+            let mut rows = numbers().fetch(&pool);
+            assert_eq!(Some(1), rows.try_next().await?.map(|Counted(n)| n));
+            assert_eq!(Some(2), rows.try_next().await?.map(|Counted(n)| n));
+            drop(rows);
+            assert_eq!(2, DECODED.load(Ordering::SeqCst));
+
+            let mut conn = pool.acquire().await?;
+            let (count, sum) = numbers()
+                .fetch(&mut conn)
+                .try_fold((0, 0), |(count, sum), Counted(n)| async move { Ok((count + 1, sum + n)) })
+                .await?;
+            assert_eq!((10_000, 50_005_000), (count, sum));
+
+            let err = Bookmark::get(1).fetch(&mut conn).try_next().await.unwrap_err();
+            assert!(err.to_string().contains("no such table"), "{}", err);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[test]
+fn column_defaults_fill_missing_columns() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Executor;
+        async fn run() -> Result<()> {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+            pool.execute(
+                "CREATE TABLE tasks (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, status TEXT NOT NULL, retries INTEGER NOT NULL, priority INTEGER, label TEXT NOT NULL);",
+            )
+            .await?;
+
+            Task::builder()
+                .title("write docs".to_string())
+                .retries(5)
+                .insert()
+                .conn(&pool)
+                .await?;
+            let task = Task::get(1).conn(&pool).await?;
+            assert_eq!("pending", task.status);
+            assert_eq!(5, task.retries);
+            assert_eq!(Some(1), task.priority);
+            assert_eq!("inbox", task.label);
+
+            // Columns left out of the selection fall back to their defaults.
+            let task = Select::from_table(Task::table())
+                .column(Task::id)
+                .column(Task::title)
+                .fetch_as::<Task, sqlx::Sqlite>()
+                .conn(&pool)
+                .await?;
+            assert_eq!("write docs", task.title);
+            assert_eq!(3, task.retries);
+            assert_eq!(Some(1), task.priority);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "profiles"]
+#[index(name = "profiles_handle", columns = "handle", unique)]
+pub struct Profile {
+    #[column(primary_key)]
+    pub id: i64,
+    pub handle: String,
+    pub bio: Option<String>,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "profiles"]
+pub struct LegacyProfile {
+    #[column(primary_key)]
+    pub id: i64,
+    pub handle: Option<String>,
+}
+
+#[test]
+fn schema_diff_migrates_a_table() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Executor;
+        use xiayu::error::ErrorKind;
+        use xiayu::schema_diff::{self, TableIndex};
+        use xiayu::visitors::{Sqlite, Visitor};
+        async fn run() -> Result<()> {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+            pool.execute("CREATE TABLE profiles (id INTEGER PRIMARY KEY, handle TEXT NOT NULL);")
+                .await?;
+            let mut conn = pool.acquire().await?;
+
+            let current = schema_diff::introspect::<sqlx::Sqlite>(&mut conn, "profiles").await?;
+            assert_eq!(2, current.columns.len());
+            assert!(!current.column("handle").unwrap().nullable);
+            assert!(current.indexes.is_empty());
+
+            let alters = schema_diff::diff(&current, &Profile::schema());
+            assert_eq!(
+                vec![
+                    AlterTable::add_column(
+                        Profile::table(),
+                        ColumnDefinition::new("bio", Some(TypeFamily::Text(None)), true)
+                    ),
+                    AlterTable::create_index(Profile::table(), "profiles_handle", vec!["handle"], true),
+                ],
+                alters
+            );
+
+            for alter in alters {
+                let (sql, _) = Sqlite::build(alter)?;
+                conn.execute(sql.as_str()).await?;
+            }
+
+            let current = schema_diff::introspect::<sqlx::Sqlite>(&mut conn, "profiles").await?;
+            assert!(current.column("bio").unwrap().nullable);
+            assert_eq!(
+                Some(&TableIndex {
+                    name: "profiles_handle".into(),
+                    columns: vec!["handle".into()],
+                    unique: true,
+                }),
+                current.index("profiles_handle")
+            );
+            assert!(schema_diff::diff(&current, &Profile::schema()).is_empty());
+
+            // Dropping `bio` and making `handle` nullable needs a new table.
+            let alters = schema_diff::diff(&current, &LegacyProfile::schema());
+            assert_eq!(2, alters.len());
+            assert!(alters.iter().any(|alter| alter.operation().is_destructive()));
+            for alter in alters {
+                let err = Sqlite::build(alter).unwrap_err();
+                assert!(matches!(err.kind(), ErrorKind::TableRebuildRequired { .. }));
+            }
+
+            let err = schema_diff::introspect::<sqlx::Sqlite>(&mut conn, "nothing")
+                .await
+                .unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::TableDoesNotExist { .. }));
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "profiles"]
+pub struct ReorderedProfile {
+    pub bio: Option<String>,
+    pub handle: String,
+    #[column(primary_key)]
+    pub id: i64,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "profiles"]
+pub struct RenamedProfile {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(name = "nickname")]
+    pub handle: String,
+    pub bio: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, EmbeddedColumns)]
+pub struct ProfileText {
+    pub handle: String,
+    pub bio: Option<String>,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "profiles"]
+pub struct EmbeddingProfile {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(flatten)]
+    pub text: ProfileText,
+}
+
+#[test]
+fn schema_hash_follows_the_layout() {
+    // The order of the fields does not matter, nor do indexes.
+    assert_eq!(Profile::schema_hash(), ReorderedProfile::schema_hash());
+    assert_eq!(Profile::schema_hash(), EmbeddingProfile::schema_hash());
+    assert_eq!(EmbeddingProfile::schema().hash(), EmbeddingProfile::schema_hash());
+
+    assert_ne!(Profile::schema_hash(), RenamedProfile::schema_hash());
+    assert_ne!(Profile::schema_hash(), LegacyProfile::schema_hash());
+    assert_ne!(Profile::schema_hash(), Draft::schema_hash());
+}
+
+#[test]
+fn verify_schema_detects_drift() {
+    #[cfg(feature = "sqlite")]
+    {
+        use xiayu::error::ErrorKind;
+        use xiayu::schema_diff::{record_schema, verify_schema};
+        async fn run() -> Result<()> {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+
+            // Recorded the first time, in a table created on demand.
+            verify_schema::<Profile, _>(&pool).await?;
+            verify_schema::<ReorderedProfile, _>(&pool).await?;
+
+            let err = verify_schema::<RenamedProfile, _>(&pool).await.unwrap_err();
+            match err.kind() {
+                ErrorKind::SchemaMismatch {
+                    entity,
+                    recorded,
+                    compiled,
+                } => {
+                    assert_eq!("profiles", *entity);
+                    assert_eq!(Profile::schema_hash(), *recorded);
+                    assert_eq!(RenamedProfile::schema_hash(), *compiled);
+                }
+                kind => panic!("{:?}", kind),
+            }
+
+            // Migrated.
+            record_schema::<RenamedProfile, _>(&pool).await?;
+            verify_schema::<RenamedProfile, _>(&pool).await?;
+            assert!(verify_schema::<Profile, _>(&pool).await.is_err());
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "subscribers"]
+pub struct Subscriber {
+    #[column(primary_key)]
+    pub id: i64,
+    pub email: String,
+}
+
+#[test]
+fn raw_queries_decode_into_the_entity() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Connection;
+        use xiayu::error::ErrorKind;
+        async fn run() -> Result<()> {
+            let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+            Subscriber::raw_query::<sqlx::Sqlite>("CREATE TABLE subscribers (id INTEGER PRIMARY KEY, email TEXT NOT NULL)")
+                .execute(&mut conn)
+                .await?;
+            let inserted = Subscriber::raw_query::<sqlx::Sqlite>("INSERT INTO subscribers (id, email) VALUES (?, ?), (?, ?)")
+                .bind(1)
+                .bind("Ada@example.com")
+                .bind(2)
+                .bind("grace@example.com")
+                .execute(&mut conn)
+                .await?;
+            assert_eq!(2, inserted.rows_affected());
+
+            let subscribers = Subscriber::raw_query::<sqlx::Sqlite>("SELECT * FROM subscribers WHERE lower(email) = lower(?) -- the email, '?'")
+                .bind("ADA@EXAMPLE.COM")
+                .fetch_all(&mut conn)
+                .await?;
+            assert_eq!(1, subscribers.len());
+            assert_eq!(1, subscribers[0].id);
+
+            let err = Subscriber::raw_query::<sqlx::Sqlite>("SELECT * FROM subscribers WHERE id = ? OR email = ?")
+                .bind(1)
+                .fetch_one(&mut conn)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err.kind(), ErrorKind::IncorrectNumberOfParameters { expected: 2, actual: 1 }),
+                "{:?}",
+                err
+            );
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Entity)]
+#[tablename = "posts"]
+pub struct Post {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    pub title: String,
+    pub draft: bool,
+}
+
+#[test]
+fn entities_insert_as_rows() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Connection;
+        async fn run() -> Result<()> {
+            let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+            (&mut conn)
+                .execute_compiled(
+                    "CREATE TABLE posts (id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, draft BOOLEAN NOT NULL)".to_string(),
+                    Vec::new(),
+                )
+                .await?;
+
+            let posts: Vec<Post> = (1..=100)
+                .map(|id| Post {
+                    id,
+                    title: format!("post {}", id),
+                    draft: id % 2 == 0,
+                })
+                .collect();
+            let inserted = (&mut conn).insert(Insert::multi_from_entities(posts.clone())).await?;
+            assert_eq!(100, inserted.rows_affected());
+
+            let selected = Select::from_table(Post::table())
+                .order_by(Post::id.ascend())
+                .fetch_as::<Post, sqlx::Sqlite>()
+                .prepare()?
+                .fetch_all(&mut conn)
+                .await?;
+            assert_eq!(posts, selected);
+
+            // The rows of `From`, for inserts of chosen columns.
+            let row = Row::from(&posts[0]);
+            assert_eq!(vec![Expression::from(1i64), Expression::from("post 1"), Expression::from(false)], row.values);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Entity)]
+#[tablename = "parcels"]
+#[entity(builder)]
+pub struct Parcel {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(unique)]
+    pub r#type: String,
+    pub r#async: bool,
+    pub größe: i64,
+}
+
+#[test]
+fn raw_and_non_ascii_field_names() {
+    assert_eq!(
+        vec!["id", "type", "async", "größe"],
+        <Parcel as Entity>::columns().iter().map(|c| c.name.as_ref()).collect::<Vec<_>>()
+    );
+
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Connection;
+        use xiayu::visitors::{QuoteStyle, Sqlite, Visitor};
+
+        // Not reserved words of SQLite, the name out of ASCII is quoted.
+        let select = Select::from_table(Parcel::table())
+            .column(Parcel::r#type)
+            .so_that(Parcel::r#async.equals(true).and(Parcel::größe.greater_than(1)));
+        let (sql, _) = Sqlite::build_with(select, QuoteStyle::Minimal).unwrap();
+        assert_eq!(
+            "SELECT parcels.type FROM parcels WHERE (parcels.async = ? AND parcels.`größe` > ?)",
+            sql
+        );
+
+        async fn run() -> Result<()> {
+            let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+            (&mut conn)
+                .execute_compiled(
+                    "CREATE TABLE parcels (id INTEGER PRIMARY KEY, type TEXT NOT NULL UNIQUE, async BOOLEAN NOT NULL, größe INTEGER NOT NULL)"
+                        .to_string(),
+                    Vec::new(),
+                )
+                .await?;
+            Parcel::builder()
+                .id(1)
+                .r#type("box".to_string())
+                .r#async(false)
+                .größe(3)
+                .insert()
+                .conn(&mut conn)
+                .await?;
+
+            let found = Parcel::find_by_type::<sqlx::Sqlite, _>("box").conn(&mut conn).await?;
+            assert_eq!((1, false, 3), (found.id, found.r#async, found.größe));
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "drafts"]
+pub struct Draft {
+    #[column(primary_key)]
+    pub id: Option<i64>,
+    pub title: String,
+}
+
+#[test]
+fn unboxed_executioner() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::{Connection, Executor};
+        use xiayu::error::ErrorKind;
+        async fn run() -> Result<()> {
+            let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+            conn.execute("CREATE TABLE drafts (id INTEGER PRIMARY KEY, title TEXT NOT NULL);")
+                .await?;
+
+            let insertion = Insert::single_into(Draft::table()).value(Draft::title, "first");
+            let result = (&mut conn).insert_into(insertion).await?;
+            assert_eq!(1, result.rows_affected());
+
+            let mut draft = Draft {
+                id: Some(1),
+                title: "second".to_string(),
+            };
+            (&mut conn).save_entity(&mut draft).await?;
+            let draft = Draft::get(1).conn(&mut conn).await?;
+            assert_eq!("second", draft.title);
+
+            // Both paths refuse to save without a key.
+            let mut draft = Draft {
+                id: None,
+                title: "third".to_string(),
+            };
+            let err = (&mut conn).save_entity(&mut draft).await.unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::PrimaryKeyNotSet { .. }));
+            let err = (&mut conn).save(&mut draft).await.unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::PrimaryKeyNotSet { .. }));
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "coupons"]
+pub struct Coupon {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(length = 8)]
+    pub code: String,
+}
+
+#[test]
+fn validate_lengths() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::{Connection, Executor};
+        use xiayu::databases::InsertingExecution;
+        use xiayu::error::{ErrorKind, Name};
+        async fn run() -> Result<()> {
+            let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+            conn.execute("CREATE TABLE coupons (id INTEGER PRIMARY KEY, code VARCHAR(8) NOT NULL);")
+                .await?;
+
+            let insert = |id: i64, code: &'static str| {
+                let insert = Insert::single_into(Coupon::table())
+                    .value(Coupon::id, id)
+                    .value(Coupon::code, code);
+                InsertingExecution::<sqlx::Sqlite, _>::from(insert).validate_lengths(true)
+            };
+
+            // Exactly at the limit, counted in characters rather than bytes.
+            insert(1, "SPRING22").conn(&mut conn).await?;
+            insert(2, "ÉTÉ-ÉTÉ!").conn(&mut conn).await?;
+
+            let err = insert(3, "SPRING2022").conn(&mut conn).await.unwrap_err();
+            assert!(matches!(
+                err.kind(),
+                ErrorKind::LengthMismatch { column } if column == &Name::available("code")
+            ));
+            let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM coupons")
+                .fetch_one(&mut conn)
+                .await?;
+            assert_eq!(2, count.0);
+
+            let mut coupon = Coupon {
+                id: 1,
+                code: "SUMMER2022".to_string(),
+            };
+            let err = coupon.save().validate_lengths(true).conn(&mut conn).await.unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::LengthMismatch { .. }));
+
+            // SQLite does not enforce the length itself.
+            coupon.save().conn(&mut conn).await?;
+            let coupon = Coupon::get(1).conn(&mut conn).await?;
+            assert_eq!("SUMMER2022", coupon.code);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "events"]
+pub struct Event {
+    #[column(primary_key)]
+    pub id: i64,
+    pub kind: String,
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn table_named_for_tenants() {
+    use xiayu::visitors::{Sqlite, Visitor};
+
+    let table = Event::table_named(format!("events_tenant{}", 42));
+
+    let select = Select::from_table(table.clone())
+        .column(Event::kind.for_table(&table))
+        .so_that(Event::id.for_table(&table).equals(1));
+    let (sql, _) = Sqlite::build(select).unwrap();
+    assert_eq!(
+        "SELECT `events_tenant42`.`kind` FROM `events_tenant42` WHERE `events_tenant42`.`id` = ?",
+        sql
+    );
+
+    let insert = Insert::single_into(table.clone())
+        .value(Event::id, 1)
+        .value(Event::kind, "signup");
+    let (sql, _) = Sqlite::build(insert).unwrap();
+    assert_eq!("INSERT INTO `events_tenant42` (`id`, `kind`) VALUES (?,?)", sql);
+
+    let update = Update::table(table.clone())
+        .set(Event::kind, "login")
+        .so_that(Event::id.for_table(&table).equals(1));
+    let (sql, _) = Sqlite::build(update).unwrap();
+    assert_eq!(
+        "UPDATE `events_tenant42` SET `kind` = ? WHERE `events_tenant42`.`id` = ?",
+        sql
+    );
+
+    // The entity itself keeps its table.
+    let (sql, _) = Sqlite::build(Select::from_table(Event::table()).so_that(Event::id.equals(1))).unwrap();
+    assert_eq!("SELECT `events`.* FROM `events` WHERE `events`.`id` = ?", sql);
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "kind_totals"]
+#[entity(view = "SELECT kind, COUNT(*) AS total FROM events GROUP BY kind")]
+pub struct KindTotal {
+    #[column(primary_key)]
+    pub kind: String,
+    pub total: i64,
+}
+
+#[test]
+fn select_from_inline_view() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Executor;
+        use xiayu::visitors::{Sqlite, Visitor};
+        async fn run() -> Result<()> {
+            let query = Select::from_table(KindTotal::table()).so_that(KindTotal::total.greater_than(1));
+            let (sql, _) = Sqlite::build(query.clone())?;
+            assert_eq!(
+                "SELECT `kind_totals`.* FROM (SELECT kind, COUNT(*) AS total FROM events GROUP BY kind) AS `kind_totals` WHERE `kind_totals`.`total` > ?",
+                sql
+            );
+
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+            pool.execute("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT);")
+                .await?;
+            pool.execute("INSERT INTO events (kind) VALUES ('login'), ('signup'), ('login');")
+                .await?;
+
+            let busiest = query.fetch_as::<KindTotal, sqlx::Sqlite>().conn(&pool).await?;
+            assert_eq!(("login", 2), (busiest.kind.as_str(), busiest.total));
+            let signups = KindTotal::get("signup").conn(&pool).await?;
+            assert_eq!(1, signups.total);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "readings"]
+pub struct Reading {
+    #[column(primary_key)]
+    pub id: i64,
+    pub small: i32,
+    pub ratio: f64,
+    pub valid: bool,
+    pub note: String,
+}
+
+#[test]
+fn converted_parameters_round_trip() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::{Connection, Executor};
+        use xiayu::visitors::{Sqlite, Visitor};
+        async fn run() -> Result<()> {
+            let insert = Insert::single_into(Reading::table())
+                .value(Reading::id, 1i64)
+                .value(Reading::small, 7)
+                .value(Reading::ratio, 0.5f64)
+                .value(Reading::valid, true)
+                .value(Reading::note, "calibrated");
+            let (_, parameters) = Sqlite::build(insert.clone())?;
+            assert_eq!(
+                vec![
+                    Value::from(1i64),
+                    Value::from(7),
+                    Value::from(0.5f64),
+                    Value::from(true),
+                    Value::from("calibrated")
+                ],
+                parameters
+            );
+
+            let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+            conn.execute("CREATE TABLE readings (id INTEGER PRIMARY KEY, small INTEGER, ratio REAL, valid BOOLEAN, note TEXT);")
+                .await?;
+            conn.insert(insert).await?;
+
+            let reading = Reading::get(1).conn(&mut conn).await?;
+            assert_eq!((7, 0.5, true, "calibrated"), (reading.small, reading.ratio, reading.valid, reading.note.as_str()));
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+/// XORs the text with a key and stores it hex encoded.
+#[derive(Default)]
+pub struct Scrambled;
+
+impl Transformer<String> for Scrambled {
+    type Stored = String;
+
+    fn encode(&self, value: &String) -> Value<'static> {
+        let hex: String = value.bytes().map(|b| format!("{:02x}", b ^ 0x5a)).collect();
+        Value::from(hex)
+    }
+
+    fn decode(&self, stored: String) -> std::result::Result<String, sqlx::error::BoxDynError> {
+        let bytes = (0..stored.len())
+            .step_by(2)
+            .map(|i| stored.get(i..i + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok()).map(|b| b ^ 0x5a))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or("not hex encoded")?;
+        Ok(String::from_utf8(bytes)?)
+    }
+}
+
+impl Transformer<Option<String>> for Scrambled {
+    type Stored = Option<String>;
+
+    fn encode(&self, value: &Option<String>) -> Value<'static> {
+        match value {
+            Some(value) => Transformer::<String>::encode(self, value),
+            None => Value::Text(None),
+        }
+    }
+
+    fn decode(&self, stored: Option<String>) -> std::result::Result<Option<String>, sqlx::error::BoxDynError> {
+        stored.map(|stored| Transformer::<String>::decode(self, stored)).transpose()
+    }
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "secrets"]
+#[entity(builder)]
+pub struct Secret {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    #[column(transform = "Scrambled")]
+    pub content: String,
+    #[column(transform = "Scrambled")]
+    pub hint: Option<String>,
+}
+
+#[test]
+fn transformed_columns_round_trip() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::{Connection, Executor, Row};
+        use xiayu::error::ErrorKind;
+        async fn run() -> Result<()> {
+            let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+            conn.execute("CREATE TABLE secrets (id INTEGER PRIMARY KEY, content TEXT, hint TEXT);")
+                .await?;
+
+            Secret::builder()
+                .content("hunter2".to_string())
+                .hint("hi".to_string())
+                .insert()
+                .conn(&mut conn)
+                .await?;
+            let row = conn.fetch_one("SELECT content, hint FROM secrets WHERE id = 1").await?;
+            assert_eq!("322f342e3f2868", row.try_get::<String, _>(0)?);
+            assert_eq!("3233", row.try_get::<String, _>(1)?);
+            let mut secret = Secret::get(1).conn(&mut conn).await?;
+            assert_eq!(("hunter2", Some("hi")), (secret.content.as_str(), secret.hint.as_deref()));
+
+            secret.content = "correct horse".to_string();
+            secret.hint = None;
+            conn.save(&mut secret).await?;
+            let secret = Secret::get(1).conn(&mut conn).await?;
+            assert_eq!(("correct horse", None), (secret.content.as_str(), secret.hint.as_deref()));
+
+            conn.execute("UPDATE secrets SET content = 'plain' WHERE id = 1;").await?;
+            let err = Secret::get(1).conn(&mut conn).await.unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::ColumnReadFailure(_)), "{:?}", err);
+            assert!(err.to_string().contains("`content`"), "{}", err);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[test]
+fn fetch_as_without_entity() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Executor;
+        async fn run() -> Result<()> {
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?;
+            pool.execute("CREATE TABLE another_entities (id INTEGER PRIMARY KEY, textual TEXT);")
+                .await?;
+            pool.execute("INSERT INTO another_entities (textual) VALUES ('a'), ('b');")
+                .await?;
+
+            let total = Select::from_table(AnotherEntity::table())
+                .value(count(asterisk()))
+                .fetch_as::<Scalar<i64>, sqlx::Sqlite>()
+                .conn(&pool)
+                .await?;
+            assert_eq!(Scalar(2), total);
+
+            let row = Select::from_table(AnotherEntity::table())
+                .column(AnotherEntity::id)
+                .column(AnotherEntity::textual)
+                .so_that(AnotherEntity::textual.equals("b"))
+                .fetch_as::<(i32, String), sqlx::Sqlite>()
+                .conn(&pool)
+                .await?;
+            assert_eq!((2, "b".to_string()), row);
+
+            let entity = Select::from_table(AnotherEntity::table())
+                .so_that(AnotherEntity::id.equals(1))
+                .fetch_as::<AnotherEntity, sqlx::Sqlite>()
+                .conn(&pool)
+                .await?;
+            assert_eq!("a", entity.textual);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[test]
+fn acquired_executioner_reuses_connection() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Executor;
+        async fn run() -> Result<()> {
+            // Every connection to `sqlite::memory:` opens a database of its own,
+            // the table only exists if all the operations share one connection.
+            let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(4)
+                .connect("sqlite::memory:")
+                .await?;
+            let mut exec = pool.acquire_executioner().await?;
+            exec.execute("CREATE TABLE another_entities (id INTEGER PRIMARY KEY, textual TEXT);")
+                .await?;
+            exec.execute("INSERT INTO another_entities (textual) VALUES ('abc');")
+                .await?;
+
+            for i in 0..5 {
+                let mut entity = AnotherEntity::get(1).conn(&mut exec).await?;
+                entity.textual = format!("saved {}", i);
+                exec.save(&mut entity).await?;
+            }
+
+            let entity = AnotherEntity::get(1).conn(&mut exec).await?;
+            assert_eq!("saved 4", entity.textual);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[test]
+fn saving_an_unset_autoincrement_key_inserts() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::{Connection, Executor};
+        async fn run() -> Result<()> {
+            let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+            conn.execute("CREATE TABLE entities (id INTEGER PRIMARY KEY AUTOINCREMENT, maybe_float REAL);")
+                .await?;
+
+            let mut first = AnEntity { id: 0, maybe_float: None };
+            first.save::<sqlx::Sqlite>().conn(&mut conn).await?;
+            assert_eq!(1, first.id);
+
+            let mut second = AnEntity {
+                id: 0,
+                maybe_float: Some(0.5),
+            };
+            (&mut conn).save(&mut second).await?;
+            assert_eq!(2, second.id);
+
+            // Saved again with its key, the row is updated.
+            first.maybe_float = Some(1.5);
+            first.save::<sqlx::Sqlite>().conn(&mut conn).await?;
+            let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM entities").fetch_one(&mut conn).await?;
+            assert_eq!(2, count.0);
+            assert_eq!(Some(1.5), AnEntity::get(1).conn(&mut conn).await?.maybe_float);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
+
+#[test]
+fn old_rows_are_purged_in_batches() {
+    #[cfg(feature = "sqlite")]
+    {
+        use sqlx::Executor;
+
+        #[derive(Debug, Entity)]
+        #[tablename = "events"]
+        struct Event {
+            #[column(primary_key)]
+            id: i64,
+            created_at: i64,
+        }
+
+        async fn run() -> Result<()> {
+            let mut conn = sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+                .await?
+                .acquire()
+                .await?;
+            conn.execute(
+                "CREATE TABLE events (id INTEGER PRIMARY KEY, created_at INTEGER NOT NULL); \
+                 CREATE INDEX events_created_at ON events (created_at); \
+                 INSERT INTO events WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < 10000) SELECT n, n FROM seq;",
+            )
+            .await?;
+
+            // Stopped by the cap, the oldest rows go first.
+            let purged = Event::purge_older_than(Event::created_at, 5_001i64, 500).max_batches(3).conn(&mut conn).await?;
+            assert_eq!((1_500, 3, false), (purged.rows_deleted(), purged.batches(), purged.is_complete()));
+            let oldest = Event::count_distinct::<sqlx::Sqlite, _>(Event::id).so_that(Event::created_at.less_than(1_502i64)).conn(&mut conn).await?;
+            assert_eq!(1, oldest);
+
+            let mut progress = Vec::new();
+            let purged = Event::purge_older_than(Event::created_at, 5_001i64, 500)
+                .pause(std::time::Duration::from_millis(1))
+                .on_progress(|purged| progress.push(purged.rows_deleted()))
+                .conn(&mut conn)
+                .await?;
+            // Seven full batches, and an empty one finding nothing left.
+            assert_eq!((3_500, 8, true), (purged.rows_deleted(), purged.batches(), purged.is_complete()));
+            assert_eq!(vec![500, 1_000, 1_500, 2_000, 2_500, 3_000, 3_500, 3_500], progress);
+
+            let left = Event::count_distinct::<sqlx::Sqlite, _>(Event::id).conn(&mut conn).await?;
+            assert_eq!(5_000, left);
+            let older = Event::count_distinct::<sqlx::Sqlite, _>(Event::id).so_that(Event::created_at.less_than(5_001i64)).conn(&mut conn).await?;
+            assert_eq!(0, older);
+            Ok(())
+        }
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(run());
+        assert!(res.is_ok(), "{:?}", res)
+    }
+}
@@ -0,0 +1,113 @@
+use std::borrow::Cow;
+
+use sqlx::FromRow;
+use xiayu::prelude::*;
+
+#[derive(Debug, Entity)]
+#[tablename = "docs"]
+pub struct Doc<'a> {
+    #[column(primary_key)]
+    pub id: i64,
+    pub title: Cow<'a, str>,
+    pub summary: Option<Cow<'a, str>>,
+}
+
+#[derive(Debug, PartialEq, Entity)]
+#[tablename = "docs"]
+pub struct OwnedDoc {
+    #[column(primary_key)]
+    pub id: i64,
+    pub title: String,
+    pub summary: Option<String>,
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn borrowed_columns_are_the_owned_ones() {
+    let _: ColumnOptions<Cow<'static, str>> = Doc::title;
+    assert_eq!(Some(TypeFamily::Text(None)), Doc::title.type_family());
+    let columns = |schema: EntitySchema| schema.columns.iter().map(|column| column.name).collect::<Vec<_>>();
+    assert_eq!(columns(OwnedDoc::schema()), columns(Doc::schema()));
+
+    let title = String::from("Notes");
+    let doc = Doc {
+        id: 1,
+        title: Cow::Borrowed(&title),
+        summary: None,
+    };
+    assert_eq!(vec![Value::from(1), Value::from("Notes"), Value::Text(None)], doc.values());
+}
+
+fn check_read(borrowed: &Doc<'_>, owned: OwnedDoc) {
+    assert!(matches!(borrowed.title, Cow::Borrowed("Notes")), "{:?}", borrowed);
+    assert_eq!(Some("on borrowing"), borrowed.summary.as_deref());
+    assert_eq!(
+        OwnedDoc {
+            id: 1,
+            title: "Notes".to_string(),
+            summary: Some("on borrowing".to_string()),
+        },
+        owned
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_decodes_borrowed_and_owned() {
+    use sqlx::{Connection, Executor};
+
+    async fn run() -> xiayu::Result<()> {
+        let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+        conn.execute("CREATE TABLE docs (id INTEGER PRIMARY KEY, title TEXT NOT NULL, summary TEXT)")
+            .await?;
+        Doc::insert::<sqlx::Sqlite>()
+            .value(Doc::id, 1)
+            .value(Doc::title, "Notes")
+            .value(Doc::summary, "on borrowing")
+            .conn(&mut conn)
+            .await?;
+
+        let row = sqlx::query("SELECT id, title, summary FROM docs").fetch_one(&mut conn).await?;
+        let borrowed = Doc::from_row(&row)?;
+        let owned = OwnedDoc::get(1).conn(&mut conn).await?;
+        check_read(&borrowed, owned);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+#[ignore = "requires a running postgres, set DATABASE_URL"]
+fn postgres_decodes_borrowed_and_owned() {
+    use sqlx::{Connection, Executor};
+
+    async fn run() -> xiayu::Result<()> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL");
+        let mut conn = sqlx::PgConnection::connect(&url).await?;
+        conn.execute("CREATE TEMPORARY TABLE docs (id BIGINT PRIMARY KEY, title TEXT NOT NULL, summary TEXT)")
+            .await?;
+        Doc::insert::<sqlx::Postgres>()
+            .value(Doc::id, 1)
+            .value(Doc::title, "Notes")
+            .value(Doc::summary, "on borrowing")
+            .conn(&mut conn)
+            .await?;
+
+        let row = sqlx::query("SELECT id, title, summary FROM docs").fetch_one(&mut conn).await?;
+        let borrowed = Doc::from_row(&row)?;
+        let owned = OwnedDoc::get(1).conn(&mut conn).await?;
+        check_read(&borrowed, owned);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
@@ -0,0 +1,117 @@
+use xiayu::prelude::*;
+use xiayu::test::MockExecutioner;
+
+#[derive(Debug, Entity)]
+#[entity(track_changes)]
+#[tablename = "accounts"]
+pub struct Account {
+    #[column(primary_key)]
+    pub id: i64,
+    pub email: String,
+    pub balance: i64,
+    pub snapshot: Snapshot,
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+/// An account loaded through a mock, with its snapshot taken.
+fn loaded(conn: &mut MockExecutioner<sqlx::Sqlite>) -> Account {
+    conn.push_row(vec![
+        ("id", Value::from(1i64)),
+        ("email", Value::from("ada@example.com")),
+        ("balance", Value::from(100i64)),
+    ]);
+    block_on(Account::get(1).conn(conn)).unwrap()
+}
+
+fn update_sql<DB>(account: &mut Account) -> (String, Vec<Value<'static>>)
+where
+    DB: sqlx::Database + for<'v> HasVisitor<'v>,
+{
+    let prepared = account.update::<DB>().prepare().unwrap().expect("changed columns");
+    (prepared.sql().to_string(), prepared.parameters().to_vec())
+}
+
+#[test]
+fn only_changed_columns_are_set() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    let mut account = loaded(&mut conn);
+    assert!(account.snapshot.is_taken());
+    account.balance += 10;
+
+    let parameters = vec![Value::from(110i64), Value::from(1i64)];
+    #[cfg(feature = "sqlite")]
+    assert_eq!(
+        ("UPDATE `accounts` SET `balance` = ? WHERE `accounts`.`id` = ?".to_string(), parameters.clone()),
+        update_sql::<sqlx::Sqlite>(&mut account)
+    );
+    #[cfg(feature = "mysql")]
+    assert_eq!(
+        ("UPDATE `accounts` SET `balance` = ? WHERE `accounts`.`id` = ?".to_string(), parameters.clone()),
+        update_sql::<sqlx::MySql>(&mut account)
+    );
+    #[cfg(feature = "postgres")]
+    assert_eq!(
+        (r#"UPDATE "accounts" SET "balance" = $1 WHERE "accounts"."id" = $2"#.to_string(), parameters.clone()),
+        update_sql::<sqlx::Postgres>(&mut account)
+    );
+    #[cfg(feature = "mssql")]
+    assert_eq!(
+        ("UPDATE [accounts] SET [balance] = @P1 WHERE [accounts].[id] = @P2".to_string(), parameters.clone()),
+        update_sql::<sqlx::Mssql>(&mut account)
+    );
+}
+
+#[test]
+fn unchanged_entities_send_nothing() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    let mut account = loaded(&mut conn);
+
+    assert!(!account.update::<sqlx::Sqlite>().has_changes());
+    let res = block_on(account.update().conn(&mut conn));
+    assert_eq!(ExecResult::default(), res.unwrap());
+    assert_eq!(1, conn.queries().len());
+
+    account.email = "ada@example.org".to_string();
+    block_on(account.update().conn(&mut conn)).unwrap();
+    assert_eq!(
+        "UPDATE `accounts` SET `email` = ? WHERE `accounts`.`id` = ?",
+        conn.sql()[1]
+    );
+
+    // Updated, the snapshot has the new values.
+    block_on(account.update().conn(&mut conn)).unwrap();
+    assert_eq!(2, conn.queries().len());
+}
+
+#[test]
+fn built_entities_set_every_column() {
+    let mut account = Account {
+        id: 2,
+        email: "grace@example.com".to_string(),
+        balance: 0,
+        snapshot: Snapshot::default(),
+    };
+
+    assert_eq!(
+        (
+            "UPDATE `accounts` SET `id` = ?, `email` = ?, `balance` = ? WHERE `accounts`.`id` = ?".to_string(),
+            vec![Value::from(2i64), Value::from("grace@example.com"), Value::from(0i64), Value::from(2i64)]
+        ),
+        update_sql::<sqlx::Sqlite>(&mut account)
+    );
+}
+
+#[test]
+fn snapshot_is_not_a_column() {
+    assert_eq!(
+        vec!["id", "email", "balance"],
+        <Account as Entity>::columns().iter().map(|column| column.name.as_ref()).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,35 @@
+use xiayu::comment::{clear_default_commenter, set_default_commenter};
+use xiayu::prelude::*;
+
+#[derive(Debug, Clone, Entity)]
+#[tablename = "articles"]
+pub struct Article {
+    #[column(primary_key)]
+    pub id: i64,
+    pub title: String,
+}
+
+// The default commenter is global, the only test of this file sets it.
+#[test]
+fn default_commenter_tags_every_statement() {
+    set_default_commenter(|| vec![("app".to_string(), "api".to_string()), ("trace".to_string(), "abc".to_string())]);
+
+    let lookup = Article::get::<sqlx::Sqlite, _>(1).prepare().unwrap();
+    assert_eq!(
+        "SELECT `articles`.* FROM `articles` WHERE `articles`.`id` = ? /*app='api',trace='abc'*/",
+        lookup.sql()
+    );
+
+    let lookup = Article::get::<sqlx::Sqlite, _>(1).comment("trace", "def").prepare().unwrap();
+    assert_eq!(
+        "SELECT `articles`.* FROM `articles` WHERE `articles`.`id` = ? /*app='api',trace='def'*/",
+        lookup.sql()
+    );
+
+    let lookup = Article::get::<sqlx::Sqlite, _>(1).without_comments().prepare().unwrap();
+    assert_eq!("SELECT `articles`.* FROM `articles` WHERE `articles`.`id` = ?", lookup.sql());
+
+    clear_default_commenter();
+    let lookup = Article::get::<sqlx::Sqlite, _>(1).prepare().unwrap();
+    assert_eq!("SELECT `articles`.* FROM `articles` WHERE `articles`.`id` = ?", lookup.sql());
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "sqlite")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use xiayu::comment::set_default_commenter;
+use xiayu::prelude::*;
+
+#[derive(Debug, Entity)]
+#[tablename = "notes"]
+pub struct Note {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    pub body: String,
+}
+
+/// The default commenter is called each time a statement is built.
+static BUILDS: AtomicUsize = AtomicUsize::new(0);
+
+fn builds() -> usize {
+    BUILDS.swap(0, Ordering::SeqCst)
+}
+
+// The default commenter is global, the only test of this file sets it.
+#[test]
+fn inserts_are_built_once_per_execution() {
+    set_default_commenter(|| {
+        BUILDS.fetch_add(1, Ordering::SeqCst);
+        Vec::new()
+    });
+
+    async fn run() -> xiayu::Result<()> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await?;
+        (&pool)
+            .execute_compiled(
+                "CREATE TABLE notes (id INTEGER PRIMARY KEY AUTOINCREMENT, body TEXT NOT NULL)".to_string(),
+                Vec::new(),
+            )
+            .await?;
+        builds();
+
+        Note::insert::<sqlx::Sqlite>().value(Note::body, "through the pool").conn(&pool).await?;
+        assert_eq!(1, builds());
+
+        let mut conn = pool.acquire().await?;
+        Note::insert::<sqlx::Sqlite>().value(Note::body, "through a connection").conn(&mut conn).await?;
+        assert_eq!(1, builds());
+
+        let id: i64 = Note::insert::<sqlx::Sqlite>()
+            .value(Note::body, "returning its key")
+            .returning_id(&mut conn)
+            .await?;
+        assert_eq!(3, id);
+        assert_eq!(1, builds());
+        Ok(())
+    }
+    let res = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
@@ -0,0 +1,91 @@
+use xiayu::error::{ErrorKind, Name};
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_missing_file_does_not_exist() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    let err = block_on(xiayu::databases::connect(
+        "sqlite:///no/such/directory/data.db",
+        SqlitePoolOptions::new(),
+    ))
+    .unwrap_err();
+
+    assert!(
+        matches!(err.kind(), ErrorKind::DatabaseDoesNotExist { db_name } if *db_name == Name::available("/no/such/directory/data.db")),
+        "{:?}",
+        err
+    );
+    assert_eq!(Some("14"), err.original_code());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_malformed_url_is_invalid() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    let err = block_on(xiayu::databases::connect("sqlite://data.db?mode=sideways", SqlitePoolOptions::new())).unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::DatabaseUrlIsInvalid(_)), "{:?}", err);
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_memory_connects() {
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    let pool = block_on(xiayu::databases::connect("sqlite::memory:", SqlitePoolOptions::new()));
+
+    assert!(pool.is_ok(), "{:?}", pool.err());
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+fn postgres_refused_connection_times_out() {
+    use sqlx::postgres::PgPoolOptions;
+    use std::time::Duration;
+
+    // Nothing listens on port 1, the connection is refused until the timeout.
+    let options = PgPoolOptions::new().connect_timeout(Duration::from_millis(200));
+    let err = block_on(xiayu::databases::connect("postgres://ada@127.0.0.1:1/shop", options)).unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::ConnectTimeout), "{:?}", err);
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+#[ignore = "requires a running postgres, set DATABASE_URL"]
+fn postgres_wrong_database_and_credentials() {
+    use sqlx::postgres::PgPoolOptions;
+    use std::time::Duration;
+
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL");
+    let (server, _) = url.rsplit_once('/').expect("a database in DATABASE_URL");
+    let (scheme, host) = server.split_once("://").expect("a postgres url");
+    let host = host.rsplit_once('@').map(|(_, host)| host).unwrap_or(host);
+    let options = || PgPoolOptions::new().connect_timeout(Duration::from_secs(5));
+
+    let missing = format!("{}/xiayu_no_such_database", server);
+    let err = block_on(xiayu::databases::connect(&missing, options())).unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::DatabaseDoesNotExist { db_name } if *db_name == Name::available("xiayu_no_such_database")),
+        "{:?}",
+        err
+    );
+
+    let stranger = format!("{}://xiayu_no_such_user:wrong@{}/postgres", scheme, host);
+    let err = block_on(xiayu::databases::connect(&stranger, options())).unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::AuthenticationFailed { user } if *user == Name::available("xiayu_no_such_user")),
+        "{:?}",
+        err
+    );
+}
@@ -0,0 +1,48 @@
+use sqlx::Connection;
+use xiayu::prelude::*;
+
+#[derive(Debug, Entity)]
+#[tablename = "copied_users"]
+pub struct User {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+    pub nickname: Option<String>,
+}
+
+#[test]
+#[ignore = "requires a running postgres, set DATABASE_URL"]
+fn copies_fifty_thousand_rows() {
+    async fn run() -> xiayu::Result<()> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL");
+        let mut conn = sqlx::PgConnection::connect(&url).await?;
+        sqlx::query("CREATE TEMPORARY TABLE copied_users (id BIGINT PRIMARY KEY, name TEXT NOT NULL, nickname TEXT)")
+            .execute(&mut conn)
+            .await?;
+
+        let users = (1..=50_000).map(|id| User {
+            id,
+            name: format!("user \"{}\",\n", id),
+            nickname: if id % 2 == 0 { Some(String::new()) } else { None },
+        });
+        assert_eq!(50_000, xiayu::copy::copy_from(users, &mut conn).await?);
+
+        let (count, nulls): (i64, i64) =
+            sqlx::query_as("SELECT count(*), count(*) FILTER (WHERE nickname IS NULL) FROM copied_users")
+                .fetch_one(&mut conn)
+                .await?;
+        assert_eq!((50_000, 25_000), (count, nulls));
+
+        let (name,): (String,) = sqlx::query_as("SELECT name FROM copied_users WHERE id = 7")
+            .fetch_one(&mut conn)
+            .await?;
+        assert_eq!("user \"7\",\n", name);
+        Ok(())
+    }
+    let res = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
@@ -0,0 +1,155 @@
+use sqlx::Connection;
+use xiayu::prelude::*;
+
+#[derive(Debug, Entity)]
+#[tablename = "members"]
+pub struct Member {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    pub name: String,
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+/// Inserts, saves and deletes members, written once for every database.
+async fn write_members<DB, C>(conn: &mut C, create: &str) -> xiayu::Result<Vec<ExecResult>>
+where
+    DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    for<'c> &'c mut C: Executioner<'c, DB>,
+{
+    (&mut *conn).execute_compiled(create.to_string(), Vec::new()).await?;
+
+    let inserted = Member::insert::<DB>().value(Member::name, "ada").conn(&mut *conn).await?;
+    let inserted_many = Member::multi::<_, _, DB>(vec![Member::name])
+        .values(("grace",))
+        .values(("alan",))
+        .conn(&mut *conn)
+        .await?;
+
+    let mut member = Member {
+        id: 2,
+        name: "grace hopper".to_string(),
+    };
+    let saved = member.save::<DB>().conn(&mut *conn).await?;
+    let deleted = member.delete::<DB>().conn(&mut *conn).await?;
+
+    Ok(vec![inserted, inserted_many, saved, deleted])
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_reports_rows_and_keys() {
+    async fn run() -> xiayu::Result<()> {
+        let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+        let results = write_members::<sqlx::Sqlite, _>(
+            &mut conn,
+            "CREATE TABLE members (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL)",
+        )
+        .await?;
+
+        assert_eq!(
+            vec![
+                ExecResult::new(1, Some(1)),
+                ExecResult::new(2, Some(3)),
+                ExecResult::new(1, None),
+                ExecResult::new(1, None),
+            ],
+            results
+        );
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn missing_rows_are_not_affected() {
+    async fn run() -> xiayu::Result<()> {
+        let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+        (&mut conn)
+            .execute_compiled("CREATE TABLE members (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL)".to_string(), Vec::new())
+            .await?;
+        Member::insert::<sqlx::Sqlite>().value(Member::name, "ada").conn(&mut conn).await?;
+
+        let mut member = Member {
+            id: 1,
+            name: "ada".to_string(),
+        };
+        assert_eq!(1, member.delete::<sqlx::Sqlite>().conn(&mut conn).await?.rows_affected());
+        // Deleted already, an optimistic check sees nothing was deleted.
+        assert_eq!(0, member.delete::<sqlx::Sqlite>().conn(&mut conn).await?.rows_affected());
+
+        member.id = 42;
+        assert_eq!(ExecResult::new(0, None), member.delete::<sqlx::Sqlite>().conn(&mut conn).await?);
+        // With its key set, the member is updated, not inserted.
+        assert_eq!(ExecResult::new(0, None), member.save::<sqlx::Sqlite>().conn(&mut conn).await?);
+        assert_eq!(0, Member::delete_where::<sqlx::Sqlite, _>(Member::name.equals("grace")).conn(&mut conn).await?);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[cfg(feature = "mysql")]
+#[test]
+#[ignore = "requires a running mysql, set MYSQL_URL"]
+fn mysql_reports_rows_and_keys() {
+    async fn run() -> xiayu::Result<()> {
+        let url = std::env::var("MYSQL_URL").expect("MYSQL_URL");
+        let mut conn = sqlx::MySqlConnection::connect(&url).await?;
+        let results = write_members::<sqlx::MySql, _>(
+            &mut conn,
+            "CREATE TEMPORARY TABLE members (id BIGINT AUTO_INCREMENT PRIMARY KEY, name TEXT NOT NULL)",
+        )
+        .await?;
+
+        // The key of a multi-row insert is the one of its first row.
+        assert_eq!(
+            vec![
+                ExecResult::new(1, Some(1)),
+                ExecResult::new(2, Some(2)),
+                ExecResult::new(1, None),
+                ExecResult::new(1, None),
+            ],
+            results
+        );
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+#[ignore = "requires a running postgres, set DATABASE_URL"]
+fn postgres_reports_rows() {
+    async fn run() -> xiayu::Result<()> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL");
+        let mut conn = sqlx::PgConnection::connect(&url).await?;
+        let results = write_members::<sqlx::Postgres, _>(
+            &mut conn,
+            "CREATE TEMPORARY TABLE members (id BIGSERIAL PRIMARY KEY, name TEXT NOT NULL)",
+        )
+        .await?;
+
+        assert_eq!(
+            vec![
+                ExecResult::new(1, None),
+                ExecResult::new(2, None),
+                ExecResult::new(1, None),
+                ExecResult::new(1, None),
+            ],
+            results
+        );
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
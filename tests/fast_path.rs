@@ -0,0 +1,110 @@
+use xiayu::error::ErrorKind;
+use xiayu::prelude::*;
+use xiayu::visitors::Visitor;
+
+#[derive(Debug, Entity)]
+#[tablename = "users"]
+pub struct User {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(name = "full_name")]
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "sessions"]
+pub struct Session {
+    #[column(primary_key)]
+    pub token: Option<String>,
+    pub user_id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, EmbeddedColumns)]
+pub struct Address {
+    pub street: String,
+    pub city: String,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "customers"]
+pub struct Customer {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(flatten, prefix = "home_")]
+    pub home: Address,
+}
+
+/// The SQL and parameters of both paths, the fast one and the visitor.
+fn both<'a, DB, Q>(fast: xiayu::Result<PreparedQuery<DB>>, query: Q) -> ((String, Vec<Value<'static>>), (String, Vec<Value<'a>>))
+where
+    DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    Q: Into<Query<'a>>,
+{
+    let fast = fast.unwrap();
+    let built = <DB as HasVisitor<'a>>::Visitor::build(query).unwrap();
+    ((fast.sql().to_string(), fast.parameters().to_vec()), built)
+}
+
+fn assert_same_sql<DB>()
+where
+    DB: sqlx::Database + for<'v> HasVisitor<'v>,
+{
+    let (fast, built) = both::<DB, _>(
+        User::fast_select_by_pk::<DB, _>(42),
+        Select::from_table(User::table()).columns_for::<User>().so_that(User::id.equals(42i64)),
+    );
+    assert_eq!(built, fast);
+
+    let (fast, built) = both::<DB, _>(
+        User::fast_delete_by_pk::<DB, _>(42),
+        Delete::from_table(User::table()).so_that(User::id.equals(42i64)),
+    );
+    assert_eq!(built, fast);
+
+    let (fast, built) = both::<DB, _>(
+        Session::fast_select_by_pk::<DB, _>(Some("t0k3n".to_string())),
+        Select::from_table(Session::table()).columns_for::<Session>().so_that(Session::token.equals("t0k3n")),
+    );
+    assert_eq!(built, fast);
+}
+
+#[test]
+fn fast_sql_is_the_sql_of_the_visitors() {
+    #[cfg(feature = "sqlite")]
+    assert_same_sql::<sqlx::Sqlite>();
+    #[cfg(feature = "mysql")]
+    assert_same_sql::<sqlx::MySql>();
+    #[cfg(feature = "postgres")]
+    assert_same_sql::<sqlx::Postgres>();
+    #[cfg(feature = "mssql")]
+    assert_same_sql::<sqlx::Mssql>();
+}
+
+#[test]
+fn identifiers_are_quoted_for_every_database() {
+    let quoted = User::QUALIFIED_COLUMNS.unwrap();
+
+    assert_eq!("`users`.`id`, `users`.`full_name`, `users`.`email`", quoted.quoted(IdentifierQuotes::Backticks).columns);
+    assert_eq!(r#""users""#, quoted.quoted(IdentifierQuotes::DoubleQuotes).table);
+    assert_eq!(Some("[users].[id]"), quoted.quoted(IdentifierQuotes::Brackets).primary_key);
+}
+
+#[test]
+fn flattened_entities_take_the_visitor() {
+    assert_eq!(None, Customer::QUALIFIED_COLUMNS);
+
+    let (fast, built) = both::<sqlx::Sqlite, _>(
+        Customer::fast_select_by_pk::<sqlx::Sqlite, _>(7),
+        Select::from_table(Customer::table()).columns_for::<Customer>().so_that(Customer::id.equals(7i64)),
+    );
+    assert_eq!(built, fast);
+    assert!(fast.0.contains("`customers`.`home_street`"), "{}", fast.0);
+}
+
+#[test]
+fn keys_not_set_are_an_error() {
+    let err = Session::fast_select_by_pk::<sqlx::Sqlite, _>(None).unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::PrimaryKeyNotSet { .. }), "{:?}", err);
+}
@@ -0,0 +1,802 @@
+use xiayu::error::ErrorKind;
+use xiayu::prelude::*;
+use xiayu::test::fixtures::TestDb;
+use xiayu::visitors::{Sqlite, Visitor};
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "members"]
+#[entity(builder)]
+pub struct Member {
+    #[column(primary_key, autoincrement)]
+    pub id: i32,
+    pub name: String,
+    pub nickname: Option<String>,
+    #[column(server_default = 0)]
+    pub visits: i32,
+}
+
+#[test]
+fn builder_inserts_without_generated_columns() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new().with_entity::<Member>().build().await?;
+        let mut conn = pool.acquire().await?;
+
+        Member::builder()
+            .name("alice".to_string())
+            .insert()
+            .conn(&mut conn)
+            .await?;
+        let result = Member::builder()
+            .nickname("b".to_string())
+            .name("bob".to_string())
+            .insert()
+            .conn(&mut conn)
+            .await?;
+
+        let member = Member::get(result.last_insert_id().unwrap() as i32).conn(&mut *conn).await?;
+        assert_eq!(2, member.id);
+        assert_eq!("bob", member.name);
+        assert_eq!(Some("b".to_string()), member.nickname);
+        assert_eq!(0, member.visits);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[test]
+fn returning_generated_ids() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new().with_entity::<Member>().build().await?;
+        let mut conn = pool.acquire().await?;
+
+        let mut alice = Member {
+            id: 0,
+            name: "alice".to_string(),
+            nickname: None,
+            visits: 0,
+        };
+        alice.id = Member::insert()
+            .value(Member::name, alice.name.as_str())
+            .returning_id(&mut *conn)
+            .await?;
+        assert_eq!(1, alice.id);
+
+        // SQLite before 3.35 has no RETURNING.
+        let mut bob = Member {
+            id: 0,
+            name: "bob".to_string(),
+            nickname: None,
+            visits: 0,
+        };
+        bob.id = Member::builder()
+            .name(bob.name.clone())
+            .insert()
+            .without_returning()
+            .returning_id(&mut *conn)
+            .await?;
+        assert_eq!(2, bob.id);
+
+        let fetched = Member::get(bob.id).conn(&mut *conn).await?;
+        assert_eq!("bob", fetched.name);
+
+        // The key of a hand-built insert is not known.
+        let insert = || -> InsertingExecution<sqlx::Sqlite, _> {
+            Insert::single_into(Member::table()).value(Member::name, "carol").into()
+        };
+        assert!(insert().returning_id::<i32>(&mut *conn).await.is_err());
+        let carol: i32 = insert().generated_key(Member::id).returning_id(&mut *conn).await?;
+        assert_eq!(3, carol);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "authors"]
+#[index(name = "authors_email", columns = "email", unique)]
+pub struct Author {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    pub email: String,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "books"]
+pub struct Book {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    #[column(foreign_key = "authors.id")]
+    pub author_id: i64,
+    pub title: String,
+}
+
+#[test]
+fn seeds_referenced_tables_first() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<Book, _>(vec![(1, 1, "Dune"), (2, 1, "Children of Dune")])
+            .seed::<Author, _>(vec![(1, "frank@example.com")])
+            .build()
+            .await?;
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM books WHERE author_id = 1")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(2, count);
+
+        // Foreign keys and indexes are created with the tables.
+        let orphan = Book::insert()
+            .value(Book::author_id, 2)
+            .value(Book::title, "Orphan")
+            .conn(&pool)
+            .await
+            .unwrap_err();
+        assert!(orphan.to_string().contains("FOREIGN KEY"), "{:?}", orphan);
+
+        let duplicate = Author::insert()
+            .value(Author::email, "frank@example.com")
+            .conn(&pool)
+            .await
+            .unwrap_err();
+        assert!(duplicate.to_string().contains("UNIQUE"), "{:?}", duplicate);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[test]
+fn explain_shows_the_index_used() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new().with_entity::<Author>().with_entity::<Book>().build().await?;
+        let mut conn = pool.acquire().await?;
+
+        let by_email: SelectingExecution<Author, sqlx::Sqlite> =
+            Select::from_table(Author::table()).so_that(Author::email.equals("frank@example.com")).into();
+        let plan = by_email.explain(&mut *conn).await?;
+        assert!(plan.iter().any(|line| line.contains("INDEX authors_email")), "{:?}", plan);
+
+        let by_title: SelectingExecution<Book, sqlx::Sqlite> =
+            Select::from_table(Book::table()).so_that(Book::title.equals("Dune")).into();
+        let plan = by_title.explain(&mut *conn).await?;
+        assert_eq!(vec!["SCAN books".to_string()], plan);
+
+        let err = Author::get(1).explain_as(ExplainFormat::Json, &mut *conn).await.unwrap_err();
+        assert!(
+            matches!(err.kind(), ErrorKind::UnsupportedExplainFormat { format: ExplainFormat::Json, .. }),
+            "{:?}",
+            err
+        );
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "eggs"]
+pub struct Egg {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(foreign_key = "chickens.id")]
+    pub chicken_id: i64,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "chickens"]
+pub struct Chicken {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(foreign_key = "eggs.id")]
+    pub egg_id: Option<i64>,
+    #[column(foreign_key = "chickens.id")]
+    pub mother_id: Option<i64>,
+}
+
+#[test]
+fn foreign_key_cycles_are_reported() {
+    let err = block_on(TestDb::new().with_entity::<Egg>().with_entity::<Chicken>().build()).unwrap_err();
+
+    match err.kind() {
+        ErrorKind::ForeignKeyCycle { tables } => assert_eq!(&["eggs", "chickens"], tables.as_slice()),
+        kind => panic!("unexpected error {:?}", kind),
+    }
+
+    // A table referencing itself is fine.
+    assert!(block_on(TestDb::new().with_entity::<Chicken>().build()).is_ok());
+}
+
+#[test]
+fn deletes_using_other_tables() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<Author, _>(vec![(1, "frank@example.com"), (2, "ursula@example.com")])
+            .seed::<Book, _>(vec![(1, 1, "Dune"), (2, 2, "The Dispossessed"), (3, 1, "Children of Dune")])
+            .build()
+            .await?;
+
+        // SQLite deletes through an `EXISTS` subquery.
+        let delete = Delete::from_table(Book::table())
+            .using(Author::table())
+            .so_that(Book::author_id.equals(Author::id).and(Author::email.equals("frank@example.com")));
+        let (sql, parameters) = Sqlite::build(delete)?;
+        let result = (&pool).execute_compiled(sql, parameters).await?;
+        assert_eq!(2, result.rows_affected());
+
+        let titles: Vec<String> = sqlx::query_scalar("SELECT title FROM books ORDER BY id")
+            .fetch_all(&pool)
+            .await?;
+        assert_eq!(vec!["The Dispossessed".to_string()], titles);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[test]
+fn prepared_queries_run_many_times() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new().with_entity::<Member>().build().await?;
+        let mut conn = pool.acquire().await?;
+
+        let insert = Member::builder().name("alice".to_string()).insert().prepare()?;
+        insert.execute(&mut conn).await?;
+        insert.execute(&mut conn).await?;
+
+        let lookup = Member::get::<sqlx::Sqlite, _>(2).prepare()?;
+        let mut member: Member = lookup.fetch_one(&mut conn).await?;
+        assert_eq!("alice", member.name);
+
+        member.name = "bob".to_string();
+        member.save().conn(&mut conn).await?;
+
+        let member: Member = lookup.fetch_one(&mut conn).await?;
+        assert_eq!("bob", member.name);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[derive(Debug, Clone, PartialEq, EmbeddedColumns)]
+pub struct Address {
+    pub street: String,
+    pub city: String,
+    pub zip: Option<String>,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "people"]
+pub struct Person {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+    #[column(flatten, prefix = "home_")]
+    pub home: Address,
+}
+
+#[test]
+fn flattened_fields_round_trip() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new().with_entity::<Person>().build().await?;
+        let mut conn = pool.acquire().await?;
+
+        let mut alice = Person {
+            id: 1,
+            name: "alice".to_string(),
+            home: Address {
+                street: "1 Main Street".to_string(),
+                city: "Springfield".to_string(),
+                zip: None,
+            },
+        };
+        Person::insert()
+            .value(Person::id, 1)
+            .value(Person::name, "alice")
+            .value(Column::new("home_street"), alice.home.street.as_str())
+            .value(Column::new("home_city"), alice.home.city.as_str())
+            .conn(&mut conn)
+            .await?;
+
+        let fetched = Person::get(1).conn(&mut *conn).await?;
+        assert_eq!(alice.home, fetched.home);
+
+        alice.home.city = "Shelbyville".to_string();
+        alice.home.zip = Some("12345".to_string());
+        alice.save().conn(&mut conn).await?;
+
+        let fetched = Person::get(1).conn(&mut *conn).await?;
+        assert_eq!("alice", fetched.name);
+        assert_eq!(alice.home, fetched.home);
+
+        let city: String = sqlx::query_scalar("SELECT home_city FROM people WHERE id = 1")
+            .fetch_one(&mut conn)
+            .await?;
+        assert_eq!("Shelbyville", city);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "users"]
+pub struct NullableUser {
+    #[column(primary_key)]
+    pub id: i64,
+    pub age: Option<i64>,
+    pub logins: i64,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "users"]
+pub struct User {
+    #[column(primary_key)]
+    pub id: i64,
+    pub age: i64,
+    #[column(try_from = "i64")]
+    pub logins: u32,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "users"]
+pub struct LenientUser {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(try_from = "i64")]
+    pub age: Option<u8>,
+    #[column(try_from = "i64")]
+    pub logins: u32,
+}
+
+#[test]
+fn decode_errors_name_the_field() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<NullableUser, _>(vec![(1, None, 3), (2, Some(300), 4), (3, Some(30), -1)])
+            .build()
+            .await?;
+        let mut conn = pool.acquire().await?;
+
+        let err = User::get(1).conn(&mut *conn).await.unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ColumnReadFailure(_)), "{:?}", err);
+        assert!(err.to_string().contains("`User.age`"), "{}", err);
+
+        let lenient = LenientUser::get(1).conn(&mut *conn).await?;
+        assert_eq!(None, lenient.age);
+        assert_eq!(3, lenient.logins);
+
+        // 300 is out of the range of an `u8`.
+        let err = LenientUser::get(2).conn(&mut *conn).await.unwrap_err();
+        assert!(err.to_string().contains("`LenientUser.age`"), "{}", err);
+
+        let user = User::get(2).conn(&mut *conn).await?;
+        assert_eq!(4, user.logins);
+        let err = User::get(3).conn(&mut *conn).await.unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ColumnReadFailure(_)), "{:?}", err);
+        assert!(err.to_string().contains("`User.logins`"), "{}", err);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[test]
+fn failed_batches_roll_back() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new().with_entity::<Member>().build().await?;
+        let mut conn = pool.acquire().await?;
+
+        let insert = |id: i32, name: &'static str| -> Query<'static> {
+            Insert::single_into(Member::table())
+                .value(Member::id, id)
+                .value(Member::name, name)
+                .into()
+        };
+
+        let err = conn
+            .execute_batch(vec![insert(1, "alice"), insert(1, "bob"), insert(2, "carol")])
+            .await
+            .unwrap_err();
+        match err.kind() {
+            ErrorKind::BatchFailed { index, sql, .. } => {
+                assert_eq!(1, *index);
+                assert!(sql.starts_with("INSERT INTO `members`"), "{}", sql);
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM members")
+            .fetch_one(&mut conn)
+            .await?;
+        assert_eq!(0, count);
+
+        let report = conn
+            .execute_batch(vec![
+                insert(1, "alice"),
+                insert(2, "bob"),
+                Update::table(Member::table()).set(Member::visits, 1).into(),
+            ])
+            .await?;
+        assert_eq!(&[1, 1, 2], report.rows_affected());
+        assert_eq!(4, report.total_rows_affected());
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "posts"]
+#[entity(before_save = "Post::compute_slug", after_load = "Post::normalize")]
+pub struct Post {
+    #[column(primary_key)]
+    pub id: i64,
+    pub title: String,
+    pub slug: String,
+}
+
+impl Post {
+    fn compute_slug(&mut self) -> Result<()> {
+        self.title = self.title.trim().to_string();
+        if self.title.is_empty() {
+            return Err(ErrorKind::ConversionError("a post needs a title".into()).into());
+        }
+        self.slug = self.title.to_lowercase().replace(' ', "-");
+        Ok(())
+    }
+
+    fn normalize(&mut self) -> Result<()> {
+        if self.slug.is_empty() {
+            return Err(ErrorKind::ConversionError("a post needs a slug".into()).into());
+        }
+        self.slug = self.slug.to_lowercase();
+        Ok(())
+    }
+}
+
+#[test]
+fn lifecycle_hooks_run_on_entity_paths() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<Post, _>(vec![(1, "Draft", "draft")])
+            .build()
+            .await?;
+        let mut conn = pool.acquire().await?;
+
+        let mut post = Post::get(1).conn(&mut *conn).await?;
+        post.title = "  Hello World ".to_string();
+        post.save().conn(&mut conn).await?;
+        assert_eq!("hello-world", post.slug);
+
+        let (title, slug): (String, String) = sqlx::query_as("SELECT title, slug FROM posts WHERE id = 1")
+            .fetch_one(&mut conn)
+            .await?;
+        assert_eq!(("Hello World", "hello-world"), (title.as_str(), slug.as_str()));
+
+        post.title = " ".to_string();
+        assert!(post.save().conn(&mut conn).await.is_err());
+
+        // Statements built by hand skip the hooks, loading still normalizes.
+        let update = Update::table(Post::table())
+            .set(Post::title, " Raw ")
+            .set(Post::slug, "RAW")
+            .so_that(Post::id.equals(1));
+        let (sql, parameters) = Sqlite::build(update)?;
+        (&mut conn).execute_compiled(sql, parameters).await?;
+        let (title, slug): (String, String) = sqlx::query_as("SELECT title, slug FROM posts WHERE id = 1")
+            .fetch_one(&mut conn)
+            .await?;
+        assert_eq!((" Raw ", "RAW"), (title.as_str(), slug.as_str()));
+
+        let post = Post::get(1).conn(&mut *conn).await?;
+        assert_eq!(" Raw ", post.title);
+        assert_eq!("raw", post.slug);
+
+        sqlx::query("UPDATE posts SET slug = ''").execute(&mut conn).await?;
+        let err = Post::get(1).conn(&mut *conn).await.unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ConversionError(_)), "{:?}", err);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[test]
+fn before_save_runs_when_the_save_is_built() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<Post, _>(vec![(1, "Draft", "draft")])
+            .build()
+            .await?;
+        let mut conn = pool.acquire().await?;
+
+        let mut post = Post { id: 1, title: " Built Only ".to_string(), slug: String::new() };
+        let saving: SavingExecution<'_, Post, sqlx::Sqlite> = post.save();
+        drop(saving);
+        assert_eq!(("Built Only", "built-only"), (post.title.as_str(), post.slug.as_str()));
+
+        let (title, slug): (String, String) = sqlx::query_as("SELECT title, slug FROM posts WHERE id = 1")
+            .fetch_one(&mut conn)
+            .await?;
+        assert_eq!(("Draft", "draft"), (title.as_str(), slug.as_str()));
+
+        // The statement carries the values the hook left.
+        let query = post.save::<sqlx::Sqlite>().prepare()?;
+        assert!(query.parameters().contains(&Value::from("built-only")), "{:?}", query.parameters());
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[derive(Debug, Clone, Entity)]
+#[tablename = "categories"]
+#[entity(belongs_to(Self, via = "parent_id"))]
+pub struct Category {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(foreign_key = "categories.id")]
+    pub parent_id: Option<i64>,
+    pub name: String,
+}
+
+#[test]
+fn self_references_walk_the_hierarchy() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<Category, _>(vec![
+                (1, None, "animals"),
+                (2, Some(1), "mammals"),
+                (3, Some(2), "cats"),
+                (4, Some(3), "lions"),
+                (5, Some(2), "dogs"),
+            ])
+            .build()
+            .await?;
+        let mut conn = pool.acquire().await?;
+
+        let mammals = Category::get(2).conn(&mut *conn).await?;
+        let parent = mammals.fetch_parent(&mut *conn).await?.unwrap();
+        assert_eq!("animals", parent.name);
+        assert!(parent.fetch_parent(&mut *conn).await?.is_none());
+
+        let children = mammals.fetch_children(&mut *conn).await?;
+        let names: Vec<_> = children.iter().map(|child| child.name.as_str()).collect();
+        assert_eq!(vec!["cats", "dogs"], names);
+
+        let lions = Category::get(4).conn(&mut *conn).await?;
+        let ancestors = lions.ancestors(&mut *conn, 3).await?;
+        let names: Vec<_> = ancestors.iter().map(|ancestor| ancestor.name.as_str()).collect();
+        assert_eq!(vec!["cats", "mammals", "animals"], names);
+        assert_eq!(2, lions.ancestors(&mut *conn, 2).await?.len());
+        assert_eq!(3, lions.ancestors(&mut *conn, 10).await?.len());
+
+        // The table joined to itself, told apart by aliases.
+        let child = Category::aliased("child");
+        let parent = Category::aliased("parent");
+        let query = Select::from_table(child.clone())
+            .inner_join(Category::join_parent(&child, &parent))
+            .column(child.col(Category::name))
+            .column(parent.col(Category::name).alias("parent_name"))
+            .so_that(parent.col(Category::name).equals("mammals"))
+            .order_by(child.col(Category::id).ascend());
+        let (sql, parameters) = Sqlite::build(query)?;
+        assert_eq!(vec![Value::from("mammals")], parameters);
+        assert_eq!(
+            "SELECT `child`.`name`, `parent`.`name` AS `parent_name` FROM `categories` AS `child` \
+             INNER JOIN `categories` AS `parent` ON `child`.`parent_id` = `parent`.`id` \
+             WHERE `parent`.`name` = ? ORDER BY `child`.`id` ASC",
+            sql
+        );
+        let rows: Vec<(String, String)> = sqlx::query_as(&sql).bind("mammals").fetch_all(&mut conn).await?;
+        assert_eq!(
+            vec![
+                ("cats".to_string(), "mammals".to_string()),
+                ("dogs".to_string(), "mammals".to_string()),
+            ],
+            rows
+        );
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[test]
+fn get_many_keeps_the_order_of_the_keys() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<Category, _>(vec![(1, None, "animals"), (2, Some(1), "mammals"), (3, Some(2), "cats")])
+            .build()
+            .await?;
+        let mut conn = pool.acquire().await?;
+
+        let fetched = Category::get_many(vec![3, 7, 1, 3, 5]).conn(&mut *conn).await?;
+        let names: Vec<_> = fetched.entities().iter().map(|category| category.name.as_str()).collect();
+        assert_eq!(vec!["cats", "animals", "cats"], names);
+        assert_eq!(&[7, 5], fetched.missing());
+
+        let (entities, missing) = Category::get_many(Vec::new()).conn(&mut *conn).await?.into_parts();
+        assert!(entities.is_empty());
+        assert!(missing.is_empty());
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[test]
+fn unique_columns_are_looked_up() {
+    #[derive(Debug, Entity)]
+    #[tablename = "subscribers"]
+    pub struct Subscriber {
+        #[column(primary_key)]
+        pub id: i64,
+        #[column(unique)]
+        pub email: String,
+    }
+
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<Subscriber, _>(vec![(1, "ada@example.com"), (2, "grace@example.com")])
+            .build()
+            .await?;
+        let mut conn = pool.acquire().await?;
+
+        let subscriber = Subscriber::find_by_email("grace@example.com").conn(&mut *conn).await?;
+        assert_eq!(2, subscriber.id);
+        let subscriber = Subscriber::find_by_id(1).conn(&mut *conn).await?;
+        assert_eq!("ada@example.com", subscriber.email);
+
+        let err = Subscriber::find_by_email("alan@example.com").conn(&mut *conn).await.unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NotFound(_)), "{:?}", err);
+
+        assert!(Subscriber::exists_by_email("ada@example.com").conn(&mut *conn).await?.into_inner());
+        assert!(!Subscriber::exists_by_email("alan@example.com").conn(&mut *conn).await?.into_inner());
+        assert!(!Subscriber::exists_by_id(3).conn(&mut *conn).await?.into_inner());
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[test]
+fn distinct_values_are_counted() {
+    #[derive(Debug, Entity)]
+    #[tablename = "events"]
+    pub struct Event {
+        #[column(primary_key)]
+        pub id: i64,
+        pub user_id: Option<i64>,
+        pub kind: String,
+    }
+
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<Event, _>(vec![
+                (1, Some(1), "visit"),
+                (2, Some(1), "visit"),
+                (3, Some(2), "visit"),
+                (4, Some(3), "purchase"),
+                (5, None, "visit"),
+                (6, Some(2), "purchase"),
+            ])
+            .build()
+            .await?;
+        let mut conn = pool.acquire().await?;
+
+        assert_eq!(3, Event::count_distinct(Event::user_id).conn(&mut *conn).await?);
+        let visitors = Event::count_distinct(Event::user_id)
+            .so_that(Event::kind.equals("visit"))
+            .conn(&mut *conn)
+            .await?;
+        assert_eq!(2, visitors);
+        assert_eq!(0, Event::count_distinct(Event::user_id).so_that(Event::kind.equals("refund")).conn(&mut *conn).await?);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn on_date_spans_the_utc_day() {
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    #[derive(Debug, Entity)]
+    #[tablename = "check_ins"]
+    pub struct CheckIn {
+        #[column(primary_key)]
+        pub id: i64,
+        pub at: chrono::DateTime<Utc>,
+    }
+
+    async fn run() -> Result<()> {
+        // Clocks went forward in most of Europe on 2021-03-28, the day still
+        // has 24 hours in UTC.
+        let pool = TestDb::new()
+            .seed::<CheckIn, _>(vec![
+                (1, Utc.with_ymd_and_hms(2021, 3, 27, 23, 59, 59).unwrap()),
+                (2, Utc.with_ymd_and_hms(2021, 3, 28, 0, 0, 0).unwrap()),
+                (3, Utc.with_ymd_and_hms(2021, 3, 28, 1, 30, 0).unwrap()),
+                (4, Utc.with_ymd_and_hms(2021, 3, 28, 23, 59, 59).unwrap()),
+                (5, Utc.with_ymd_and_hms(2021, 3, 29, 0, 0, 0).unwrap()),
+            ])
+            .build()
+            .await?;
+        let mut conn = pool.acquire().await?;
+
+        let day = NaiveDate::from_ymd_opt(2021, 3, 28).unwrap();
+        let select = Select::from_table(CheckIn::table())
+            .so_that(CheckIn::at.on_date(day))
+            .order_by(CheckIn::id.ascend());
+        let (sql, parameters) = Sqlite::build(select)?;
+        let check_ins: Vec<CheckIn> = (&mut *conn).fetch_all_compiled(sql, parameters).await?;
+        let ids: Vec<_> = check_ins.iter().map(|check_in| check_in.id).collect();
+        assert_eq!(vec![2, 3, 4], ids);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+static CLOCK: std::sync::atomic::AtomicI64 = std::sync::atomic::AtomicI64::new(1_000);
+
+fn tick() -> i64 {
+    CLOCK.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "documents"]
+pub struct Document {
+    #[column(primary_key)]
+    pub id: i64,
+    pub body: String,
+    #[column(onupdate = "tick")]
+    pub updated_at: i64,
+    #[column(onupdate = "tick")]
+    pub touched_at: Option<i64>,
+}
+
+#[test]
+fn onupdate_columns_are_refreshed_on_save() {
+    async fn run() -> Result<()> {
+        let pool = TestDb::new()
+            .seed::<Document, _>(vec![(1, "draft", 0, None::<i64>)])
+            .build()
+            .await?;
+        let mut conn = pool.acquire().await?;
+
+        let mut document = Document::get(1).conn(&mut *conn).await?;
+        document.body = "final".to_string();
+        document.save().conn(&mut conn).await?;
+        assert!(document.updated_at >= 1_000, "{:?}", document);
+        assert!(document.touched_at.is_some(), "{:?}", document);
+
+        let (updated_at, touched_at): (i64, Option<i64>) =
+            sqlx::query_as("SELECT updated_at, touched_at FROM documents WHERE id = 1")
+                .fetch_one(&mut conn)
+                .await?;
+        assert_eq!((document.updated_at, document.touched_at), (updated_at, touched_at));
+
+        // Saved again, the columns move on even if nothing else changed.
+        let previous = document.updated_at;
+        document.save().conn(&mut conn).await?;
+        assert!(document.updated_at > previous, "{:?}", document);
+        let saved = Document::get(1).conn(&mut *conn).await?;
+        assert_eq!(document.updated_at, saved.updated_at);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
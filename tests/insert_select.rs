@@ -0,0 +1,109 @@
+use xiayu::error::ErrorKind;
+use xiayu::prelude::*;
+
+#[derive(Debug, Entity)]
+#[tablename = "customers"]
+pub struct Customer {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+    pub country: String,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "leads"]
+pub struct Lead {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    pub name: String,
+    #[column(default = "unknown")]
+    pub source: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "invoices"]
+pub struct Invoice {
+    #[column(primary_key)]
+    pub id: i64,
+    pub amount: f64,
+}
+
+#[test]
+fn mappings_match_the_selected_columns() {
+    let err = Select::from_table(Customer::table())
+        .column(Customer::name)
+        .insert_into::<Lead, _, _, _>(vec![(Lead::name.c(), Expression::from(Customer::name))])
+        .unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::ColumnCountMismatch { columns: 1, values: 2 }),
+        "{:?}",
+        err
+    );
+
+    let err = Select::from_table(Customer::table())
+        .insert_into::<Lead, _, _, Expression>(Vec::<(Column, _)>::new())
+        .unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::ColumnCountMismatch { columns: 0, values: 0 }),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn unmatched_columns_without_defaults_are_rejected() {
+    let err = Invoice::copy_from::<Customer, _>(ConditionTree::NoCondition).unwrap_err();
+
+    assert_eq!(
+        "No value to insert into `amount` of `invoices`, and no default.",
+        err.kind().to_string()
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn filtered_rows_are_copied() {
+    use sqlx::Connection;
+
+    async fn run() -> xiayu::Result<()> {
+        let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+        for sql in [
+            "CREATE TABLE customers (id INTEGER PRIMARY KEY, name TEXT NOT NULL, country TEXT NOT NULL)",
+            "CREATE TABLE leads (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, source TEXT NOT NULL, note TEXT)",
+        ] {
+            (&mut conn).execute_compiled(sql.to_string(), Vec::new()).await?;
+        }
+        for (id, name, country) in [(1, "Ada", "FR"), (2, "Grace", "US"), (3, "Alan", "FR")] {
+            Customer::insert::<sqlx::Sqlite>()
+                .value(Customer::id, id)
+                .value(Customer::name, name)
+                .value(Customer::country, country)
+                .conn(&mut conn)
+                .await?;
+        }
+
+        let insert = Lead::copy_from::<Customer, _>(Customer::country.equals("FR"))?;
+        let result = (&mut conn).insert(insert).await?;
+        assert_eq!(2, result.rows_affected());
+
+        let leads: Vec<Lead> = Select::from_table(Lead::table())
+            .order_by(Lead::id.ascend())
+            .fetch_as::<Lead, sqlx::Sqlite>()
+            .prepare()?
+            .fetch_all(&mut conn)
+            .await?;
+        let leads: Vec<_> = leads
+            .iter()
+            .map(|lead| (lead.name.as_str(), lead.source.as_str(), lead.note.as_deref()))
+            .collect();
+        assert_eq!(vec![("Ada", "unknown", None), ("Alan", "unknown", None)], leads);
+        Ok(())
+    }
+    let res = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
@@ -0,0 +1,462 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::time::Duration;
+
+use xiayu::identity_map::IdentityMap;
+use xiayu::prelude::*;
+use xiayu::retry::{transaction, with_retries, RetryPolicy};
+use xiayu::test::{assert_sql, MockExecutioner};
+
+#[derive(Debug, Clone, Entity)]
+#[tablename = "articles"]
+pub struct Article {
+    #[column(primary_key)]
+    pub id: i64,
+    pub title: String,
+    pub views: i32,
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn save_issues_an_update() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    let mut article = Article {
+        id: 7,
+        title: "Mocking".to_string(),
+        views: 3,
+    };
+
+    let res = block_on(article.save().conn(&mut conn));
+    assert!(res.is_ok(), "{:?}", res);
+
+    assert_eq!(
+        vec!["UPDATE `articles` SET `id` = ?, `title` = ?, `views` = ? WHERE `articles`.`id` = ?"],
+        conn.sql()
+    );
+    assert_eq!(
+        vec![Value::from(7i64), Value::from("Mocking"), Value::from(3), Value::from(7i64)],
+        conn.queries()[0].parameters
+    );
+}
+
+#[test]
+fn get_decodes_a_canned_row() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    conn.push_row(vec![
+        ("id", Value::from(7i64)),
+        ("title", Value::from("Mocking")),
+        ("views", Value::from(3)),
+    ]);
+
+    let article = block_on(Article::get(7).conn(&mut conn)).unwrap();
+    assert_eq!("Mocking", article.title);
+    assert_eq!(3, article.views);
+
+    assert_eq!(
+        vec!["SELECT `articles`.* FROM `articles` WHERE `articles`.`id` = ?"],
+        conn.sql()
+    );
+
+    let res = block_on(Article::get(8).conn(&mut conn));
+    assert!(res.is_err());
+    assert_eq!(2, conn.queries().len());
+}
+
+#[test]
+fn missing_rows_are_none_and_all_rows_are_decoded() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+
+    let article = block_on(Article::get(7).fetch_optional(&mut conn)).unwrap();
+    assert!(article.is_none());
+
+    conn.push_row(article_row("Mocking"));
+    conn.push_row(article_row("Stubbing"));
+    let articles = block_on(
+        Select::from_table(Article::table())
+            .fetch_as::<Article, sqlx::Sqlite>()
+            .fetch_all(&mut conn),
+    )
+    .unwrap();
+    assert_eq!(
+        vec!["Mocking", "Stubbing"],
+        articles.iter().map(|article| article.title.as_str()).collect::<Vec<_>>()
+    );
+
+    conn.push_row(article_row("Mocking"));
+    let article = block_on(Article::get(7).fetch_one(&mut conn)).unwrap();
+    assert_eq!("Mocking", article.title);
+}
+
+#[test]
+fn canned_rows_are_streamed() {
+    use futures_util::TryStreamExt;
+
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    conn.push_row(article_row("Mocking"));
+    conn.push_row(article_row("Stubbing"));
+
+    let articles: Vec<Article> = block_on(
+        Select::from_table(Article::table())
+            .fetch_as::<Article, sqlx::Sqlite>()
+            .fetch(&mut conn)
+            .try_collect(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        vec!["Mocking", "Stubbing"],
+        articles.iter().map(|article| article.title.as_str()).collect::<Vec<_>>()
+    );
+    assert_eq!(1, conn.queries().len());
+}
+
+#[test]
+fn delete_and_insert_are_recorded() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    let mut article = Article {
+        id: 7,
+        title: "Mocking".to_string(),
+        views: 3,
+    };
+
+    let res = block_on(article.delete().conn(&mut conn));
+    assert!(res.is_ok(), "{:?}", res);
+    let title = String::from("Borrowed");
+    let res = block_on(conn.insert(Insert::single_into(Article::table()).value(Article::title, title.as_str())));
+    assert!(res.is_ok(), "{:?}", res);
+
+    assert_eq!(
+        vec![
+            "DELETE FROM `articles` WHERE `articles`.`id` = ?",
+            "INSERT INTO `articles` (`title`) VALUES (?)",
+        ],
+        conn.sql()
+    );
+    assert_eq!(vec![Value::from("Borrowed")], conn.queries()[1].parameters);
+}
+
+#[test]
+fn delete_where_deletes_by_condition() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    let deleted = block_on(Article::delete_where(Article::views.less_than(10)).conn(&mut conn));
+    assert_eq!(0, deleted.unwrap());
+    assert_eq!(vec!["DELETE FROM `articles` WHERE `articles`.`views` < ?"], conn.sql());
+
+    fn sql<DB>() -> String
+    where
+        DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    {
+        let conditions = Article::views.less_than(10).and(Article::title.like("%draft%"));
+        Article::delete_where::<DB, _>(conditions).without_comments().prepare().unwrap().sql().to_string()
+    }
+    #[cfg(feature = "sqlite")]
+    assert_eq!("DELETE FROM `articles` WHERE (`articles`.`views` < ? AND `articles`.`title` LIKE ?)", sql::<sqlx::Sqlite>());
+    #[cfg(feature = "mysql")]
+    assert_eq!("DELETE FROM `articles` WHERE (`articles`.`views` < ? AND `articles`.`title` LIKE ?)", sql::<sqlx::MySql>());
+    #[cfg(feature = "postgres")]
+    assert_eq!(
+        r#"DELETE FROM "articles" WHERE ("articles"."views" < $1 AND "articles"."title" LIKE $2)"#,
+        sql::<sqlx::Postgres>()
+    );
+    #[cfg(feature = "mssql")]
+    assert_eq!(
+        "DELETE FROM [articles] WHERE ([articles].[views] < @P1 AND [articles].[title] LIKE @P2)",
+        sql::<sqlx::Mssql>()
+    );
+}
+
+#[test]
+fn too_many_parameters_fail_before_running() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    let mut insert = Insert::multi_into(Article::table(), vec![Article::views]);
+    for views in 0..32767 {
+        insert = insert.values(vec![views]);
+    }
+
+    let error = block_on(conn.insert(insert)).unwrap_err();
+    assert!(matches!(
+        error.kind(),
+        xiayu::error::ErrorKind::TooManyParameters {
+            got: 32767,
+            max: 32766,
+            database: "SQLite",
+        }
+    ));
+    assert!(conn.sql().is_empty());
+}
+
+#[test]
+fn get_many_is_chunked_by_the_parameter_limit() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    conn.push_row(article_row("Mocking"));
+
+    let keys: Vec<i64> = (0..32768).chain(vec![7, 7]).collect();
+    let fetched = block_on(Article::get_many(keys).conn(&mut conn)).unwrap();
+
+    assert_eq!(2, conn.sql().len());
+    assert_eq!(32766, conn.queries()[0].parameters.len());
+    assert_eq!(2, conn.queries()[1].parameters.len());
+    assert_eq!(3, fetched.entities().len());
+    assert_eq!(32767, fetched.missing().len());
+}
+
+#[test]
+fn assert_sql_checks_every_dialect() {
+    let query = Select::from_table(Article::table()).so_that(Article::views.greater_than(10));
+
+    assert_sql!(
+        query,
+        sqlite: "SELECT `articles`.* FROM `articles` WHERE `articles`.`views` > ?",
+        mysql: "SELECT `articles`.* FROM `articles` WHERE `articles`.`views` > ?";
+        params: [10],
+    );
+}
+
+#[test]
+#[should_panic(expected = "SQL built by sqlite")]
+fn assert_sql_names_the_failing_dialect() {
+    assert_sql!(
+        Select::from_table(Article::table()),
+        sqlite: "SELECT * FROM `articles`",
+    );
+}
+
+/// A database error with a SQLSTATE code.
+#[derive(Debug)]
+struct CodedError(&'static str);
+
+impl std::fmt::Display for CodedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error {}", self.0)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+impl sqlx::error::DatabaseError for CodedError {
+    fn message(&self) -> &str {
+        "could not serialize access due to concurrent update"
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self.0))
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+}
+
+fn serialization_failure() -> sqlx::Error {
+    sqlx::Error::Database(Box::new(CodedError("40001")))
+}
+
+fn quick_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy::new(max_attempts).backoff(Duration::from_millis(1), Duration::from_millis(2))
+}
+
+#[test]
+fn database_codes_are_classified() {
+    let error = xiayu::error::Error::from(serialization_failure());
+    assert!(matches!(error.kind(), xiayu::error::ErrorKind::SerializationFailure(_)));
+    assert!(error.is_retryable());
+    assert_eq!(Some("40001"), error.original_code());
+
+    let error = xiayu::error::Error::from(sqlx::Error::Database(Box::new(CodedError("40P01"))));
+    assert!(matches!(error.kind(), xiayu::error::ErrorKind::Deadlock(_)));
+
+    let error = xiayu::error::Error::from(sqlx::Error::Database(Box::new(CodedError("23505"))));
+    assert!(!error.is_retryable());
+}
+
+#[test]
+// Attempts run one after the other, the connection is never borrowed twice.
+#[allow(clippy::await_holding_refcell_ref)]
+fn retries_until_success() {
+    let conn = RefCell::new(MockExecutioner::<sqlx::Sqlite>::new());
+    conn.borrow_mut()
+        .push_error(serialization_failure())
+        .push_error(serialization_failure())
+        .push_row(vec![
+            ("id", Value::from(7i64)),
+            ("title", Value::from("Mocking")),
+            ("views", Value::from(3)),
+        ]);
+
+    let article = block_on(with_retries(&quick_policy(3), || async {
+        let mut conn = conn.borrow_mut();
+        Article::get(7).conn(&mut *conn).await
+    }))
+    .unwrap();
+    assert_eq!("Mocking", article.title);
+    assert_eq!(3, conn.borrow().queries().len());
+}
+
+#[test]
+#[allow(clippy::await_holding_refcell_ref)]
+fn gives_up_after_the_last_attempt() {
+    let conn = RefCell::new(MockExecutioner::<sqlx::Sqlite>::new());
+    for _ in 0..5 {
+        conn.borrow_mut().push_error(serialization_failure());
+    }
+
+    let res = block_on(with_retries(&quick_policy(3), || async {
+        let mut conn = conn.borrow_mut();
+        Article::get(7).conn(&mut *conn).await
+    }));
+    assert!(res.unwrap_err().is_retryable());
+    assert_eq!(3, conn.borrow().queries().len());
+}
+
+#[test]
+#[allow(clippy::await_holding_refcell_ref)]
+fn other_errors_are_not_retried() {
+    let conn = RefCell::new(MockExecutioner::<sqlx::Sqlite>::new());
+
+    // No rows are queued, the select fails with `NotFound`.
+    let res = block_on(with_retries(&quick_policy(3), || async {
+        let mut conn = conn.borrow_mut();
+        Article::get(7).conn(&mut *conn).await
+    }));
+    assert!(matches!(res.unwrap_err().kind(), xiayu::error::ErrorKind::NotFound(_)));
+    assert_eq!(1, conn.borrow().queries().len());
+}
+
+#[test]
+fn transactions_are_run_again() {
+    use sqlx::Executor;
+
+    async fn run() -> xiayu::Result<()> {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await?;
+        pool.execute("CREATE TABLE articles (id INTEGER PRIMARY KEY, title TEXT, views INTEGER);")
+            .await?;
+
+        let mut attempts = 0;
+        let policy = quick_policy(3);
+        let id = transaction(&pool, Some(&policy), |tx| {
+            attempts += 1;
+            let attempt = attempts;
+            Box::pin(async move {
+                tx.execute("INSERT INTO articles (title, views) VALUES ('Retried', 0);").await?;
+                if attempt < 3 {
+                    return Err(serialization_failure().into());
+                }
+                Ok(attempt)
+            })
+        })
+        .await?;
+        assert_eq!(3, id);
+
+        // The failed attempts were rolled back.
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM articles").fetch_one(&pool).await?;
+        assert_eq!(1, count.0);
+
+        // Without a policy, the transaction is run once.
+        let res: xiayu::Result<()> = transaction(&pool, None, |_| Box::pin(async { Err(serialization_failure().into()) })).await;
+        assert!(res.unwrap_err().is_retryable());
+        Ok(())
+    }
+
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res);
+}
+
+fn article_row(title: &str) -> Vec<(&'static str, Value<'static>)> {
+    vec![
+        ("id", Value::from(7i64)),
+        ("title", Value::from(title.to_string())),
+        ("views", Value::from(3)),
+    ]
+}
+
+#[test]
+fn cached_get_is_fetched_once() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    conn.push_row(article_row("Mocking"));
+    let map = IdentityMap::new();
+
+    let first = block_on(Article::get(7).cached(&map).conn(&mut conn)).unwrap();
+    let second = block_on(Article::get(7).cached(&map).conn(&mut conn)).unwrap();
+    assert_eq!("Mocking", first.title);
+    assert_eq!("Mocking", second.title);
+    assert_eq!(1, conn.queries().len());
+    assert_eq!(1, map.len());
+
+    // Other keys, and lookups without the map, still query.
+    assert!(block_on(Article::get(8).cached(&map).conn(&mut conn)).is_err());
+    assert!(block_on(Article::get(7).conn(&mut conn)).is_err());
+    assert_eq!(3, conn.queries().len());
+}
+
+#[test]
+fn save_and_delete_evict_cached_entities() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    conn.push_row(article_row("Mocking"));
+    let map = IdentityMap::new();
+
+    let mut article = block_on(Article::get(7).cached(&map).conn(&mut conn)).unwrap();
+    article.title = "Caching".to_string();
+    let res = block_on(article.save().cached(&map).conn(&mut conn));
+    assert!(res.is_ok(), "{:?}", res);
+    assert!(map.is_empty());
+
+    conn.push_row(article_row("Caching"));
+    let article = block_on(Article::get(7).cached(&map).conn(&mut conn)).unwrap();
+    assert_eq!("Caching", article.title);
+    assert_eq!(3, conn.queries().len());
+
+    let mut article = block_on(Article::get(7).cached(&map).conn(&mut conn)).unwrap();
+    assert_eq!(3, conn.queries().len());
+    let res = block_on(article.delete().cached(&map).conn(&mut conn));
+    assert!(res.is_ok(), "{:?}", res);
+    assert!(map.is_empty());
+}
+
+#[test]
+fn comments_end_the_statements() {
+    let mut conn = MockExecutioner::<sqlx::Sqlite>::new();
+    let mut article = Article {
+        id: 7,
+        title: "Mocking".to_string(),
+        views: 3,
+    };
+
+    let res = block_on(article.save().comment("trace", "abc").comment("app", "api").conn(&mut conn));
+    assert!(res.is_ok(), "{:?}", res);
+    let res = block_on(article.delete().comment("user", "*/ DROP TABLE articles; --").conn(&mut conn));
+    assert!(res.is_ok(), "{:?}", res);
+
+    assert_eq!(
+        vec![
+            "UPDATE `articles` SET `id` = ?, `title` = ?, `views` = ? WHERE `articles`.`id` = ? /*app='api',trace='abc'*/",
+            "DELETE FROM `articles` WHERE `articles`.`id` = ? /*user='%2A%2F%20DROP%20TABLE%20articles%3B%20--'*/",
+        ],
+        conn.sql()
+    );
+    assert_eq!(4, conn.queries()[0].parameters.len());
+
+    let lookup = Article::get::<sqlx::Sqlite, _>(7)
+        .comment("trace", "abc")
+        .without_comments()
+        .prepare()
+        .unwrap();
+    assert_eq!("SELECT `articles`.* FROM `articles` WHERE `articles`.`id` = ?", lookup.sql());
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "sqlite")]
+use xiayu::error::ErrorKind;
+use xiayu::prelude::*;
+use xiayu::visitors::{self, Sqlite, Visitor};
+
+#[derive(Debug, Entity)]
+#[tablename = "events"]
+pub struct Event {
+    #[column(primary_key)]
+    pub id: i64,
+    pub score: i64,
+}
+
+#[test]
+fn deeply_nested_conditions_render() {
+    // What a filter of the user turns into, each `OR` in the one before.
+    let mut filter = ConditionTree::single(Event::id.equals(0));
+    for id in 1..100_000 {
+        filter = ConditionTree::Or(vec![Expression::from(filter), Event::id.equals(id).into()]);
+    }
+
+    let (sql, params) = Sqlite::build(Select::from_table(Event::table()).so_that(filter)).unwrap();
+
+    assert!(sql.starts_with("SELECT `events`.* FROM `events` WHERE ((((("), "{}", &sql[..64]);
+    assert_eq!(100_000, params.len());
+}
+
+#[test]
+fn deeply_nested_expressions_fail_cleanly() {
+    let nested = |depth: usize| {
+        let mut score = Expression::from(Event::score);
+        for _ in 1..depth {
+            score = score + Expression::from(1);
+        }
+        Select::from_table(Event::table()).value(score)
+    };
+
+    assert!(Sqlite::build(nested(visitors::DEFAULT_MAX_DEPTH - 1)).is_ok());
+
+    let err = Sqlite::build(nested(1_000)).unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::QueryTooComplex { depth } if *depth == visitors::DEFAULT_MAX_DEPTH),
+        "{:?}",
+        err
+    );
+
+    visitors::set_max_depth(8);
+    let err = Sqlite::build(nested(10)).unwrap_err();
+    visitors::set_max_depth(visitors::DEFAULT_MAX_DEPTH);
+    assert!(matches!(err.kind(), ErrorKind::QueryTooComplex { depth: 8 }), "{:?}", err);
+}
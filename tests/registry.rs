@@ -0,0 +1,165 @@
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::Executor;
+use xiayu::error::{ErrorKind, Name};
+use xiayu::prelude::*;
+use xiayu::registry;
+
+#[derive(Debug, Entity)]
+#[entity(register)]
+#[tablename = "streets"]
+pub struct Street {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(foreign_key = "cities.id")]
+    pub city_id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Entity)]
+#[entity(register)]
+#[tablename = "cities"]
+pub struct City {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(foreign_key = "countries.id")]
+    pub country_id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Entity)]
+#[entity(register)]
+#[tablename = "countries"]
+pub struct Country {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+}
+
+/// Referencing each other, left out of the registered entities.
+#[derive(Debug, Entity)]
+#[tablename = "employees"]
+pub struct Employee {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(foreign_key = "departments.id")]
+    pub department_id: i64,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "departments"]
+pub struct Department {
+    #[column(primary_key)]
+    pub id: i64,
+    #[column(foreign_key = "employees.id")]
+    pub manager_id: Option<i64>,
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+async fn memory() -> xiayu::Result<SqlitePool> {
+    Ok(SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await?)
+}
+
+#[test]
+fn registered_entities_are_created_referenced_first() {
+    async fn run() -> xiayu::Result<()> {
+        let registered = registry::registered();
+        assert_eq!(
+            vec!["cities", "countries", "streets"],
+            registered.iter().map(|schema| schema.tablename).collect::<Vec<_>>()
+        );
+
+        let pool = memory().await?;
+        assert_eq!(vec!["countries", "cities", "streets"], registry::create_all(&pool).await?);
+        assert!(registry::create_all(&pool).await?.is_empty());
+        registry::validate_all(&pool).await?;
+
+        // The foreign keys are created, the referenced tables exist.
+        pool.execute("PRAGMA foreign_keys = ON").await?;
+        pool.execute("INSERT INTO countries (id, name) VALUES (1, 'Iceland')").await?;
+        pool.execute("INSERT INTO cities (id, country_id, name) VALUES (1, 1, 'Reykjavik')").await?;
+        assert!(pool.execute("INSERT INTO streets (id, city_id, name) VALUES (1, 2, 'Laugavegur')").await.is_err());
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res);
+}
+
+#[test]
+fn existing_tables_are_skipped() {
+    async fn run() -> xiayu::Result<()> {
+        let pool = memory().await?;
+        pool.execute("CREATE TABLE countries (id INTEGER PRIMARY KEY, name TEXT NOT NULL)").await?;
+
+        assert_eq!(vec!["cities", "streets"], registry::create_all(&pool).await?);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res);
+}
+
+#[test]
+fn cycles_are_an_error() {
+    async fn run() -> xiayu::Result<()> {
+        let pool = memory().await?;
+        let err = registry::collect!(Employee, Department, Country).create_all(&pool).await.unwrap_err();
+
+        assert!(
+            matches!(err.kind(), ErrorKind::ForeignKeyCycle { tables } if *tables == ["employees", "departments"]),
+            "{:?}",
+            err
+        );
+        assert_eq!(
+            "The foreign keys of tables employees, departments form a cycle.",
+            err.to_string()
+        );
+        // Nothing is created before the order is known.
+        assert!(registry::collect!(Country).create_all(&pool).await?.contains(&"countries"));
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res);
+}
+
+#[test]
+fn validation_names_missing_and_outdated_tables() {
+    async fn run() -> xiayu::Result<()> {
+        let pool = memory().await?;
+        pool.execute("CREATE TABLE cities (id INTEGER PRIMARY KEY, country_id INTEGER NOT NULL, name TEXT NOT NULL)").await?;
+
+        let err = registry::validate_all(&pool).await.unwrap_err();
+        assert!(
+            matches!(err.kind(), ErrorKind::TableDoesNotExist { table } if *table == Name::available("countries")),
+            "{:?}",
+            err
+        );
+
+        pool.execute("CREATE TABLE countries (id INTEGER PRIMARY KEY, name TEXT NOT NULL, code TEXT)").await?;
+        let err = registry::validate_all(&pool).await.unwrap_err();
+        match err.kind() {
+            ErrorKind::TableMismatch { table, alters } => {
+                assert_eq!(Name::available("countries"), *table);
+                // Told by the error of the visitor, SQLite rebuilds the table.
+                assert_eq!(
+                    &["Altering `countries` requires rebuilding the table: SQLite can not drop columns"],
+                    alters.as_slice()
+                );
+            }
+            _ => panic!("{:?}", err),
+        }
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res);
+}
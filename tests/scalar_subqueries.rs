@@ -0,0 +1,138 @@
+use sqlx::Connection;
+use xiayu::prelude::*;
+
+#[derive(Debug, Entity)]
+#[tablename = "authors"]
+pub struct Author {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "posts"]
+pub struct Post {
+    #[column(primary_key)]
+    pub id: i64,
+    pub author_id: i64,
+    pub published: bool,
+}
+
+/// The columns of an author and the number of their published posts,
+/// selected by a scalar subquery.
+#[derive(Debug, PartialEq, Entity)]
+#[tablename = "authors"]
+#[entity(readonly)]
+pub struct AuthorStats {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+    pub published: i64,
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+/// Counts the published posts of every author but one, written once for
+/// every database.
+async fn author_stats<DB, C>(conn: &mut C, create: [&str; 2]) -> xiayu::Result<Vec<AuthorStats>>
+where
+    DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    for<'c> &'c mut C: Executioner<'c, DB>,
+    AuthorStats: for<'r> sqlx::FromRow<'r, DB::Row>,
+{
+    for create in create {
+        (&mut *conn).execute_compiled(create.to_string(), Vec::new()).await?;
+    }
+    Author::multi::<_, _, DB>(vec![Column::from(Author::id), Column::from(Author::name)])
+        .values((1, "ada"))
+        .values((2, "grace"))
+        .values((3, "alan"))
+        .conn(&mut *conn)
+        .await?;
+    Post::multi::<_, _, DB>(vec![Column::from(Post::id), Column::from(Post::author_id), Column::from(Post::published)])
+        .values((1, 1, true))
+        .values((2, 1, true))
+        .values((3, 1, false))
+        .values((4, 2, false))
+        .conn(&mut *conn)
+        .await?;
+
+    // Correlated through the alias of the outer table, its parameter
+    // numbered before the one of the outer condition.
+    let authors = Author::table().alias("a");
+    let published = Select::from_table(Post::table())
+        .value(count(asterisk()))
+        .so_that(Post::author_id.equals(authors.col(Author::id)).and(Post::published.equals(true)));
+    let select = Select::from_table(authors.clone())
+        .value(authors.clone().asterisk())
+        .value(published.alias("published"))
+        .so_that(authors.col(Author::name).not_equals("alan"))
+        .order_by(authors.col(Author::id).ascend());
+
+    select.fetch_as::<AuthorStats, DB>().prepare()?.fetch_all(&mut *conn).await
+}
+
+fn expected() -> Vec<AuthorStats> {
+    vec![
+        AuthorStats {
+            id: 1,
+            name: "ada".to_string(),
+            published: 2,
+        },
+        AuthorStats {
+            id: 2,
+            name: "grace".to_string(),
+            published: 0,
+        },
+    ]
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_decodes_scalar_subqueries() {
+    async fn run() -> xiayu::Result<()> {
+        let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+        let stats = author_stats::<sqlx::Sqlite, _>(
+            &mut conn,
+            [
+                "CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+                "CREATE TABLE posts (id INTEGER PRIMARY KEY, author_id INTEGER NOT NULL, published BOOLEAN NOT NULL)",
+            ],
+        )
+        .await?;
+
+        assert_eq!(expected(), stats);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+#[ignore = "requires a running postgres, set DATABASE_URL"]
+fn postgres_decodes_scalar_subqueries() {
+    async fn run() -> xiayu::Result<()> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL");
+        let mut conn = sqlx::PgConnection::connect(&url).await?;
+        let stats = author_stats::<sqlx::Postgres, _>(
+            &mut conn,
+            [
+                "CREATE TEMPORARY TABLE authors (id BIGINT PRIMARY KEY, name TEXT NOT NULL)",
+                "CREATE TEMPORARY TABLE posts (id BIGINT PRIMARY KEY, author_id BIGINT NOT NULL, published BOOLEAN NOT NULL)",
+            ],
+        )
+        .await?;
+
+        assert_eq!(expected(), stats);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
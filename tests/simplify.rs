@@ -0,0 +1,121 @@
+//! Simplified conditions select the same rows as the conditions they come
+//! from, checked on random condition trees.
+use xiayu::prelude::*;
+use xiayu::test::fixtures::TestDb;
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[derive(Debug, Entity)]
+#[tablename = "items"]
+pub struct Item {
+    #[column(primary_key)]
+    pub id: i64,
+    /// `1 << id`, the sum of the masks of the selected rows tells which they
+    /// are.
+    pub mask: i64,
+    pub a: Option<i64>,
+    pub b: Option<i64>,
+}
+
+/// A xorshift generator, the trees are the same on every run.
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % n
+    }
+
+    fn column(&mut self) -> Column<'static> {
+        if self.below(2) == 0 {
+            Item::a.into()
+        } else {
+            Item::b.into()
+        }
+    }
+
+    fn values(&mut self) -> Vec<i64> {
+        (0..self.below(3)).map(|_| self.below(4) as i64).collect()
+    }
+
+    fn leaf(&mut self) -> Expression<'static> {
+        let column = self.column();
+        let value = self.below(4) as i64;
+        match self.below(10) {
+            0 => ConditionTree::NoCondition.into(),
+            1 => ConditionTree::NegativeCondition.into(),
+            2 => column.equals(value).into(),
+            3 => column.not_equals(value).into(),
+            4 => column.less_than(value).into(),
+            5 => column.is_null().into(),
+            6 => column.not_in_selection(self.values()).into(),
+            _ => column.in_selection(self.values()).into(),
+        }
+    }
+
+    fn tree(&mut self, depth: usize) -> ConditionTree<'static> {
+        if depth == 0 {
+            return ConditionTree::single(self.leaf());
+        }
+
+        match self.below(7) {
+            0 => ConditionTree::And(self.operands(depth - 1)),
+            1 => ConditionTree::Or(self.operands(depth - 1)),
+            2 => ConditionTree::not(self.tree(depth - 1)),
+            3 => ConditionTree::single(self.tree(depth - 1)),
+            _ => ConditionTree::single(self.leaf()),
+        }
+    }
+
+    fn operands(&mut self, depth: usize) -> Vec<Expression<'static>> {
+        (0..self.below(4)).map(|_| self.tree(depth).into()).collect()
+    }
+}
+
+/// The selected rows, as a set of bits.
+async fn selected(pool: &sqlx::SqlitePool, select: Select<'static>) -> Result<Option<i64>> {
+    let mask = select
+        .value(sum(Item::mask))
+        .fetch_as::<Scalar<Option<i64>>, sqlx::Sqlite>()
+        .conn(pool)
+        .await?;
+    Ok(mask.into_inner())
+}
+
+#[test]
+fn simplified_conditions_select_the_same_rows() {
+    async fn run() -> Result<()> {
+        let mut rows = Vec::new();
+        for (i, a) in [None, Some(0), Some(1), Some(2), Some(3)].iter().enumerate() {
+            for (j, b) in [None, Some(0), Some(1), Some(3)].iter().enumerate() {
+                let id = (i * 4 + j) as i64;
+                rows.push((id, 1i64 << id, *a, *b));
+            }
+        }
+        let pool = TestDb::new().seed::<Item, _>(rows).build().await?;
+
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        for _ in 0..2000 {
+            let tree = random.tree(4);
+            let select = Select::from_table(Item::table()).so_that(tree.clone());
+            let simplified = match Query::from(select.clone()).simplify() {
+                Query::Select(select) => *select,
+                query => panic!("unexpected query {:?}", query),
+            };
+
+            let expected = selected(&pool, select).await?;
+            assert_eq!(expected, selected(&pool, simplified).await?, "{:?}", tree);
+        }
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
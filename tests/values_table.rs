@@ -0,0 +1,91 @@
+use sqlx::Connection;
+use xiayu::prelude::*;
+use xiayu::values;
+
+#[derive(Debug, Entity)]
+#[tablename = "products"]
+pub struct Product {
+    #[column(primary_key)]
+    pub id: i64,
+    pub name: String,
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+/// Joins the products against a list of `(id, payload)` pairs, written once
+/// for every database.
+async fn join_pairs<DB, C>(conn: &mut C, create: &str) -> xiayu::Result<Vec<(String, String)>>
+where
+    DB: sqlx::Database + for<'v> HasVisitor<'v>,
+    for<'c> &'c mut C: Executioner<'c, DB>,
+    for<'r> (String, String): sqlx::FromRow<'r, DB::Row>,
+{
+    (&mut *conn).execute_compiled(create.to_string(), Vec::new()).await?;
+    Product::multi::<_, _, DB>(vec![Column::from(Product::id), Column::from(Product::name)])
+        .values((1, "tea"))
+        .values((2, "coffee"))
+        .values((3, "cocoa"))
+        .conn(&mut *conn)
+        .await?;
+
+    let pairs = Table::from(values!((3, "hot"), (1, "green")).typed([TypeFamily::Int, TypeFamily::Text(None)]))
+        .alias_with_columns("pairs", ["id", "payload"]);
+    let select = Select::from_table(Product::table())
+        .column(Product::name)
+        .column(pairs.col(Column::new("payload")))
+        .inner_join(pairs.clone().on(pairs.col(Column::new("id")).equals(Product::id)))
+        .order_by(Product::id.ascend());
+
+    select.fetch_as::<(String, String), DB>().prepare()?.fetch_all(&mut *conn).await
+}
+
+fn expected() -> Vec<(String, String)> {
+    vec![
+        ("tea".to_string(), "green".to_string()),
+        ("cocoa".to_string(), "hot".to_string()),
+    ]
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_joins_typed_values() {
+    async fn run() -> xiayu::Result<()> {
+        let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+        let joined = join_pairs::<sqlx::Sqlite, _>(
+            &mut conn,
+            "CREATE TABLE products (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+        )
+        .await?;
+
+        assert_eq!(expected(), joined);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
+
+#[cfg(feature = "postgres")]
+#[test]
+#[ignore = "requires a running postgres, set DATABASE_URL"]
+fn postgres_joins_typed_values() {
+    async fn run() -> xiayu::Result<()> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL");
+        let mut conn = sqlx::PgConnection::connect(&url).await?;
+        let joined = join_pairs::<sqlx::Postgres, _>(
+            &mut conn,
+            "CREATE TEMPORARY TABLE products (id BIGINT PRIMARY KEY, name TEXT NOT NULL)",
+        )
+        .await?;
+
+        assert_eq!(expected(), joined);
+        Ok(())
+    }
+    let res = block_on(run());
+    assert!(res.is_ok(), "{:?}", res)
+}
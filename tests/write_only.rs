@@ -0,0 +1,82 @@
+use sqlx::{Connection, Executor as _};
+use xiayu::prelude::*;
+use xiayu::visitors::Visitor;
+
+#[derive(Debug, Entity)]
+#[tablename = "users"]
+pub struct User {
+    #[column(primary_key, autoincrement)]
+    pub id: i64,
+    pub name: String,
+    #[column(write_only)]
+    pub password_hash: Option<String>,
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+async fn stored_hash(conn: &mut sqlx::SqliteConnection, id: i64) -> Option<String> {
+    sqlx::query_scalar("SELECT password_hash FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_one(conn)
+        .await
+        .unwrap()
+}
+
+#[test]
+fn write_only_columns_are_not_selected() {
+    let names: Vec<_> = User::readable_columns().iter().map(|column| column.name.to_string()).collect();
+    assert_eq!(vec!["id", "name"], names);
+
+    let get = User::get::<sqlx::Sqlite, _>(1).prepare().unwrap();
+    assert!(get.sql().starts_with("SELECT `users`.`id`, `users`.`name` FROM `users`"), "{}", get.sql());
+
+    let (sql, _) = xiayu::visitors::Sqlite::build(Select::from_table(User::table()).columns_for::<User>()).unwrap();
+    assert_eq!("SELECT `users`.`id`, `users`.`name` FROM `users`", sql);
+
+    let with_hash = User::get::<sqlx::Sqlite, _>(1).with_column(User::password_hash).prepare().unwrap();
+    assert!(with_hash.sql().contains("`users`.`name`, `users`.`password_hash` FROM"), "{}", with_hash.sql());
+}
+
+#[test]
+fn write_only_columns_are_written() {
+    block_on(async {
+        let mut conn = sqlx::SqliteConnection::connect("sqlite::memory:").await?;
+        conn.execute("CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, password_hash TEXT)")
+            .await?;
+
+        let mut user = User {
+            id: 0,
+            name: "ada".to_string(),
+            password_hash: Some("$argon2id$first".to_string()),
+        };
+        let insert = user.save::<sqlx::Sqlite>().prepare()?;
+        assert!(insert.sql().contains("`password_hash`"), "{}", insert.sql());
+        user.save::<sqlx::Sqlite>().conn(&mut conn).await?;
+        assert_eq!(Some("$argon2id$first".to_string()), stored_hash(&mut conn, user.id).await);
+
+        // Loaded without it, saving keeps the stored hash.
+        let mut loaded: User = User::get(user.id).conn(&mut conn).await?;
+        assert_eq!(None, loaded.password_hash);
+        loaded.name = "ada lovelace".to_string();
+        let update = loaded.save::<sqlx::Sqlite>().prepare()?;
+        assert!(!update.sql().contains("`password_hash`"), "{}", update.sql());
+        loaded.save::<sqlx::Sqlite>().conn(&mut conn).await?;
+        assert_eq!(Some("$argon2id$first".to_string()), stored_hash(&mut conn, user.id).await);
+
+        loaded.password_hash = Some("$argon2id$second".to_string());
+        loaded.save::<sqlx::Sqlite>().conn(&mut conn).await?;
+        assert_eq!(Some("$argon2id$second".to_string()), stored_hash(&mut conn, user.id).await);
+
+        let fetched: User = User::get(user.id).with_column(User::password_hash).conn(&mut conn).await?;
+        assert_eq!(Some("$argon2id$second".to_string()), fetched.password_hash);
+
+        xiayu::Result::Ok(())
+    })
+    .unwrap();
+}